@@ -23,9 +23,13 @@ public class SolanaUnity
     private static extern IntPtr solana_get_account_data(
         IntPtr client,
         string pubkey,
+        out UIntPtr dataLen,
         out IntPtr error
     );
 
+    [DllImport("solana_unity")]
+    private static extern void solana_free_bytes(IntPtr ptr, UIntPtr len);
+
     [DllImport("solana_unity")]
     private static extern int solana_confirm_transaction(
         IntPtr client,
@@ -77,7 +81,8 @@ public class SolanaUnity
             throw new InvalidOperationException("Client not initialized");
 
         IntPtr error;
-        IntPtr dataPtr = solana_get_account_data(clientPtr, pubkey, out error);
+        UIntPtr nativeLen;
+        IntPtr dataPtr = solana_get_account_data(clientPtr, pubkey, out nativeLen, out error);
 
         if (dataPtr == IntPtr.Zero)
         {
@@ -86,17 +91,15 @@ public class SolanaUnity
             throw new Exception($"Failed to get account data: {errorMsg}");
         }
 
-        // Get data length from first 8 bytes
-        byte[] lenBytes = new byte[8];
-        Marshal.Copy(dataPtr, lenBytes, 0, 8);
-        int dataLen = BitConverter.ToInt32(lenBytes, 0);
-
-        // Get actual data
+        int dataLen = (int)nativeLen.ToUInt32();
         byte[] data = new byte[dataLen];
-        Marshal.Copy(IntPtr.Add(dataPtr, 8), data, 0, dataLen);
+        if (dataLen > 0)
+        {
+            Marshal.Copy(dataPtr, data, 0, dataLen);
+        }
 
         // Free native memory
-        Marshal.FreeHGlobal(dataPtr);
+        solana_free_bytes(dataPtr, nativeLen);
 
         return data;
     }
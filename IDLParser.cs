@@ -152,8 +152,11 @@ namespace SolanaUnity
             using (var transaction = new SolanaClient.Transaction(_client))
             {
                 // Get signer pubkey from private key
-                var tempAccount = new SolanaClient.Account(signerPrivateKey);
-                string signerPubkey = tempAccount.GetPublicKey();
+                string signerPubkey;
+                using (var tempAccount = new SolanaClient.Account(signerPrivateKey))
+                {
+                    signerPubkey = tempAccount.GetPublicKey();
+                }
 
                 transaction.BuildWithInstructions(new[] { instruction }, signerPubkey, blockhash);
                 transaction.Sign(signerPrivateKey);
@@ -27,7 +27,8 @@ public class SolanaExample : MonoBehaviour
     {
         try
         {
-            // Initialize Solana client
+            // Initialize the library and the Solana client
+            SolanaClient.Initialize();
             solanaClient = new SolanaClient(rpcUrl, commitment);
             
             // Generate a new account
@@ -11,10 +11,42 @@ namespace SolanaUnity
         public SolanaException(string message) : base(message) { }
     }
 
+    // Thrown by calls that report structured errors
+    public class SolanaErrorException : SolanaException
+    {
+        // Error kind, e.g. "RpcError" or "Timeout"
+        public string Code { get; }
+        // Program logs captured with the error as a JSON array, "[]" when there are none
+        public string LogsJson { get; }
+        public bool IsRetryable { get; }
+        // Stable numeric code, e.g. 1001 for a failed preflight simulation
+        public uint NumericCode { get; }
+        // The whole error as JSON, including context such as the pubkey involved
+        public string Json { get; }
+
+        public SolanaErrorException(string code, string message, string logsJson, bool isRetryable,
+            uint numericCode, string json)
+            : base(message)
+        {
+            Code = code;
+            LogsJson = logsJson;
+            IsRetryable = isRetryable;
+            NumericCode = numericCode;
+            Json = json;
+        }
+    }
+
     public class SolanaClient
     {
         private IntPtr _clientPtr;
         private bool _disposed = false;
+        // Set for clients from Shared(), which give back their reference instead of
+        // destroying the native client
+        private bool _shared = false;
+
+        // Must equal solana_get_abi_version() of the native plugin; bump together with
+        // ABI_VERSION in ffi.rs
+        private const uint ExpectedAbiVersion = 1;
 
         // RPC Client functions
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
@@ -23,390 +55,3553 @@ namespace SolanaUnity
             [MarshalAs(UnmanagedType.LPStr)] string commitment,
             out IntPtr error);
 
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_create_rpc_client_configured(
+            [MarshalAs(UnmanagedType.LPStr)] string url,
+            [MarshalAs(UnmanagedType.LPStr)] string commitment,
+            [MarshalAs(UnmanagedType.LPStr)] string configJson,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_create_rpc_client_with_proxy(
+            [MarshalAs(UnmanagedType.LPStr)] string url,
+            [MarshalAs(UnmanagedType.LPStr)] string commitment,
+            [MarshalAs(UnmanagedType.LPStr)] string proxyUrl,
+            [MarshalAs(UnmanagedType.LPStr)] string proxyUsername,
+            [MarshalAs(UnmanagedType.LPStr)] string proxyPassword,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_create_rpc_client_fastest(
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 1)] string[] urls,
+            UIntPtr urlsCount,
+            [MarshalAs(UnmanagedType.LPStr)] string commitment,
+            ulong timeoutMs,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_probe_endpoints(
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 1)] string[] urls,
+            UIntPtr urlsCount,
+            ulong timeoutMs,
+            out IntPtr error);
+
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
         private static extern void solana_destroy_rpc_client(IntPtr client);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern ulong solana_get_balance(
+        private static extern IntPtr solana_get_or_create_rpc_client(
+            [MarshalAs(UnmanagedType.LPStr)] string url,
+            [MarshalAs(UnmanagedType.LPStr)] string commitment,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_release_rpc_client(IntPtr client);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_clone_rpc_client(IntPtr client, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_enable_account_cache(
+            IntPtr client,
+            ulong ttlMs,
+            UIntPtr maxEntries,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_disable_account_cache(IntPtr client, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_invalidate_account(
             IntPtr client,
             [MarshalAs(UnmanagedType.LPStr)] string pubkey,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_get_latest_blockhash(
+        private static extern int solana_invalidate_all_accounts(IntPtr client, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_enable_metrics(IntPtr client, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_disable_metrics(IntPtr client, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_metrics_json(IntPtr client, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_reset_metrics(IntPtr client);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern ulong solana_get_balance(
             IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern ulong solana_get_token_account_balance(
+        private static extern int solana_get_balances(
             IntPtr client,
-            [MarshalAs(UnmanagedType.LPStr)] string tokenAccount,
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 2)] string[] pubkeys,
+            UIntPtr count,
+            [Out] ulong[] lamports,
+            [Out] int[] exists,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_get_account_info(
+        private static extern IntPtr solana_get_latest_blockhash(
             IntPtr client,
-            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_get_program_accounts(
+        private static extern IntPtr solana_get_epoch_info(
             IntPtr client,
-            [MarshalAs(UnmanagedType.LPStr)] string programId,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_get_transaction_status(
+        private static extern int solana_get_slot(
             IntPtr client,
-            [MarshalAs(UnmanagedType.LPStr)] string signature,
+            out ulong slot,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_confirm_transaction(
+        private static extern int solana_get_block_height(
             IntPtr client,
-            [MarshalAs(UnmanagedType.LPStr)] string signature,
+            out ulong blockHeight,
             out IntPtr error);
 
-        // New RPC methods
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_simulate_transaction(
+        private static extern IntPtr solana_request_airdrop(
             IntPtr client,
-            IntPtr transaction,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            ulong lamports,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_get_multiple_accounts(
+        private static extern int solana_get_latest_blockhash_with_expiry(
             IntPtr client,
-            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 2)] string[] pubkeys,
-            int pubkeysCount,
+            out IntPtr blockhash,
+            out ulong lastValidBlockHeight,
             out IntPtr error);
 
-        // Transaction functions
+        // Variants reporting errors as error objects
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_create_transaction();
+        private static extern ulong solana_get_balance_e(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern void solana_destroy_transaction(IntPtr transaction);
+        private static extern IntPtr solana_get_latest_blockhash_e(
+            IntPtr client,
+            out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_build_transfer(
+        private static extern IntPtr solana_send_transaction_e(
+            IntPtr client,
             IntPtr transaction,
-            [MarshalAs(UnmanagedType.LPStr)] string fromPubkey,
-            [MarshalAs(UnmanagedType.LPStr)] string toPubkey,
-            ulong lamports,
-            [MarshalAs(UnmanagedType.LPStr)] string recentBlockhash,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_build_token_transfer(
-            IntPtr transaction,
-            [MarshalAs(UnmanagedType.LPStr)] string tokenProgramId,
-            [MarshalAs(UnmanagedType.LPStr)] string sourcePubkey,
-            [MarshalAs(UnmanagedType.LPStr)] string destinationPubkey,
-            [MarshalAs(UnmanagedType.LPStr)] string ownerPubkey,
-            ulong amount,
-            [MarshalAs(UnmanagedType.LPStr)] string recentBlockhash,
+        private static extern IntPtr solana_error_get_code(IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_error_get_message(IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_error_get_logs_json(IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_error_is_retryable(IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern uint solana_error_get_numeric_code(IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_error_get_json(IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_error_destroy(IntPtr error);
+
+        // Per-call timeouts, 0 for the client default
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern ulong solana_get_balance_t(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            ulong timeoutMs,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_sign_transaction(
-            IntPtr transaction,
-            byte[] privateKey,
-            int privateKeyLen,
+        private static extern IntPtr solana_get_latest_blockhash_t(
+            IntPtr client,
+            ulong timeoutMs,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_send_transaction(
+        private static extern IntPtr solana_send_transaction_t(
             IntPtr client,
             IntPtr transaction,
+            ulong timeoutMs,
             out IntPtr error);
 
-        // New transaction methods
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_sign_transaction_with_keypairs(
-            IntPtr transaction,
-            IntPtr[] privateKeysData,
-            IntPtr[] privateKeysLengths,
-            int privateKeysCount,
+        private static extern IntPtr solana_get_account_info_t(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            ulong timeoutMs,
             out IntPtr error);
 
+        // Library lifecycle
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_build_with_instructions(
-            IntPtr transaction,
-            IntPtr instructionsData,
-            int instructionsDataLen,
-            int instructionsCount,
-            [MarshalAs(UnmanagedType.LPStr)] string feePayer,
-            [MarshalAs(UnmanagedType.LPStr)] string recentBlockhash,
+        private static extern int solana_init(
+            [MarshalAs(UnmanagedType.LPStr)] string configJson,
             out IntPtr error);
 
-        // Account functions
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_create_account();
+        private static extern int solana_shutdown();
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern void solana_destroy_account(IntPtr account);
+        private static extern IntPtr solana_get_version();
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_account_from_pubkey(
-            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+        private static extern uint solana_get_abi_version();
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_decode_program_error(
+            [MarshalAs(UnmanagedType.LPStr)] string logsJson,
+            uint code,
+            [MarshalAs(UnmanagedType.LPStr)] string idlJson,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_account_from_private_key(
-            byte[] privateKey,
-            int privateKeyLen,
+        private static extern IntPtr solana_decode_account_with_schema(
+            byte[] data,
+            UIntPtr dataLen,
+            [MarshalAs(UnmanagedType.LPStr)] string schemaJson,
             out IntPtr error);
 
-#if UNITY_SOLANA_BIP39
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_account_from_mnemonic(
-            [MarshalAs(UnmanagedType.LPStr)] string mnemonic,
-            [MarshalAs(UnmanagedType.LPStr)] string passphrase,
-            [MarshalAs(UnmanagedType.LPStr)] string derivationPath,
+        private static extern IntPtr solana_format_token_amount(ulong raw, byte decimals, int trimZeros);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_parse_token_amount(
+            [MarshalAs(UnmanagedType.LPStr)] string amount,
+            byte decimals,
+            out ulong raw,
             out IntPtr error);
-#endif
+
+        // Diagnostics
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        private delegate void LogCallback(int level, IntPtr message, IntPtr userData);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_account_generate();
+        private static extern void solana_set_log_callback(LogCallback callback, IntPtr userData, int minLevel);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_account_get_pubkey(
-            IntPtr account,
+        private static extern void solana_clear_log_callback();
+
+        // Request queue functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern ulong solana_request_get_balance(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_account_get_private_key(
-            IntPtr account,
+        private static extern ulong solana_request_latest_blockhash(
+            IntPtr client,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_account_has_private_key(
-            IntPtr account,
+        private static extern ulong solana_request_send_transaction(
+            IntPtr client,
+            IntPtr transaction,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_account_get_keypair(
-            IntPtr account,
+        private static extern ulong solana_request_confirm_transaction(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string signature,
             out IntPtr error);
 
-        // PDA functions
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_find_program_address(
-            IntPtr[] seeds,
-            int seedsLen,
-            [MarshalAs(UnmanagedType.LPStr)] string programId,
-            out IntPtr addressOut,
-            out byte bumpOut,
+        private static extern IntPtr solana_poll_request(
+            ulong requestId,
+            out int status);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_cancel_request(ulong requestId);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_get_balance_v2(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out ulong lamports,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_find_associated_token_address(
-            [MarshalAs(UnmanagedType.LPStr)] string walletAddress,
-            [MarshalAs(UnmanagedType.LPStr)] string tokenMint,
-            out IntPtr addressOut,
+        private static extern int solana_get_token_account_balance_v2(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string tokenAccount,
+            out ulong amount,
             out IntPtr error);
 
-        // Instruction building functions
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern int solana_create_token_transfer_instruction(
-            [MarshalAs(UnmanagedType.LPStr)] string source,
-            [MarshalAs(UnmanagedType.LPStr)] string destination,
-            [MarshalAs(UnmanagedType.LPStr)] string owner,
-            ulong amount,
-            out IntPtr encodedDataOut,
-            out IntPtr encodedDataLenOut,
+        private static extern int solana_get_balance_at_slot(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            ulong minContextSlot,
+            out ulong lamports,
             out IntPtr error);
 
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern void solana_free_encoded_instruction(IntPtr dataPtr);
+        private static extern int solana_get_token_account_balance_at_slot(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string tokenAccount,
+            ulong minContextSlot,
+            out ulong amount,
+            out IntPtr error);
 
-        // Helper functions
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern void solana_free_string(IntPtr ptr);
+        private static extern IntPtr solana_get_account_info_at_slot(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            ulong minContextSlot,
+            out IntPtr error);
 
-        // Helper to handle errors
-        private static void CheckError(IntPtr errorPtr)
-        {
-            if (errorPtr != IntPtr.Zero)
-            {
-                string errorMessage = Marshal.PtrToStringAnsi(errorPtr);
-                solana_free_string(errorPtr);
-                throw new SolanaException(errorMessage);
-            }
-        }
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern ulong solana_get_token_account_balance(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string tokenAccount,
+            out IntPtr error);
 
-        // Helper to convert IntPtr to string and free the memory
-        private static string PtrToStringAndFree(IntPtr ptr)
-        {
-            if (ptr == IntPtr.Zero)
-                return null;
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_account_info(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out IntPtr error);
 
-            string result = Marshal.PtrToStringAnsi(ptr);
-            solana_free_string(ptr);
-            return result;
-        }
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_pyth_price(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string priceAccount,
+            out IntPtr error);
 
-        // Constructor
-        public SolanaClient(string url, string commitment = "confirmed")
-        {
-            IntPtr errorPtr;
-            _clientPtr = solana_create_rpc_client(url, commitment, out errorPtr);
-            CheckError(errorPtr);
-        }
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_stake_account_state(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string stakeAccount,
+            out IntPtr error);
 
-        // Destructor
-        ~SolanaClient()
-        {
-            Dispose(false);
-        }
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_nonce_account(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string noncePubkey,
+            out IntPtr error);
 
-        // Dispose pattern
-        public void Dispose()
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_nonce_value(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string noncePubkey,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_program_accounts(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_program_accounts_paged(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            [MarshalAs(UnmanagedType.LPStr)] string filtersJson,
+            UIntPtr pageSize,
+            [MarshalAs(UnmanagedType.LPStr)] string cursor,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_anchor_program_accounts(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            [MarshalAs(UnmanagedType.LPStr)] string accountName,
+            [MarshalAs(UnmanagedType.LPStr)] string filtersJson,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_transaction_status(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string signature,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_transaction_events(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string signature,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_nfts_by_owner(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string owner,
+            int fetchMetadata,
+            UIntPtr limit,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_token_holders(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string mint,
+            ulong minAmount,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_token_supply(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string mint,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_confirm_transaction(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string signature,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_wait_for_confirmations(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 2)] string[] signatures,
+            UIntPtr signaturesCount,
+            [MarshalAs(UnmanagedType.LPStr)] string commitment,
+            ulong timeoutMs,
+            out IntPtr error);
+
+        // New RPC methods
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_simulate_transaction(
+            IntPtr client,
+            IntPtr transaction,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_simulate_transaction_detailed(
+            IntPtr client,
+            IntPtr transaction,
+            out IntPtr err,
+            out IntPtr logs,
+            out UIntPtr logsCount,
+            out ulong unitsConsumed,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_estimate_transaction_cost(
+            IntPtr client,
+            IntPtr transaction,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_recent_prioritization_fees(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 2)] string[] addresses,
+            UIntPtr addressesCount,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern ulong solana_get_fee_for_transaction(
+            IntPtr client,
+            IntPtr transaction,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_free_string_array(IntPtr ptr, UIntPtr count);
+
+        // Mirrors the native SolanaStringArray; items is IntPtr.Zero on failure
+        [StructLayout(LayoutKind.Sequential)]
+        private struct SolanaStringArray
         {
-            Dispose(true);
-            GC.SuppressFinalize(this);
+            public IntPtr items;
+            public UIntPtr len;
         }
 
-        protected virtual void Dispose(bool disposing)
-        {
-            if (!_disposed)
-            {
-                if (_clientPtr != IntPtr.Zero)
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_string_array_free(SolanaStringArray array);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SolanaStringArray solana_get_signatures_for_address_list(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string address,
+            UIntPtr limit,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_signatures_for_address(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string address,
+            UIntPtr limit,
+            [MarshalAs(UnmanagedType.LPStr)] string before,
+            [MarshalAs(UnmanagedType.LPStr)] string until,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SolanaStringArray solana_get_program_account_pubkeys(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_get_multiple_accounts(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 2)] string[] pubkeys,
+            UIntPtr pubkeysCount,
+            int policy,
+            out IntPtr error);
+
+        // Transaction functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_create_transaction();
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_destroy_transaction(IntPtr transaction);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_build_transfer(
+            IntPtr transaction,
+            [MarshalAs(UnmanagedType.LPStr)] string fromPubkey,
+            [MarshalAs(UnmanagedType.LPStr)] string toPubkey,
+            ulong lamports,
+            [MarshalAs(UnmanagedType.LPStr)] string recentBlockhash,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_build_token_transfer(
+            IntPtr transaction,
+            [MarshalAs(UnmanagedType.LPStr)] string tokenProgramId,
+            [MarshalAs(UnmanagedType.LPStr)] string sourcePubkey,
+            [MarshalAs(UnmanagedType.LPStr)] string destinationPubkey,
+            [MarshalAs(UnmanagedType.LPStr)] string ownerPubkey,
+            ulong amount,
+            [MarshalAs(UnmanagedType.LPStr)] string recentBlockhash,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_sign_transaction(
+            IntPtr transaction,
+            byte[] privateKey,
+            int privateKeyLen,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_sign_transaction_b64(
+            IntPtr transaction,
+            [MarshalAs(UnmanagedType.LPStr)] string privateKeyBase64,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_deserialize_transaction_b64(
+            IntPtr transaction,
+            [MarshalAs(UnmanagedType.LPStr)] string dataBase64,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_build_program_call_b64(
+            IntPtr transaction,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            [MarshalAs(UnmanagedType.LPArray, ArraySubType = UnmanagedType.LPStr, SizeParamIndex = 5)] string[] accounts,
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 5)] int[] accountsIsSigner,
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 5)] int[] accountsIsWritable,
+            UIntPtr accountsCount,
+            [MarshalAs(UnmanagedType.LPStr)] string dataBase64,
+            [MarshalAs(UnmanagedType.LPStr)] string recentBlockhash,
+            [MarshalAs(UnmanagedType.LPStr)] string feePayer,
+            out IntPtr error);
+
+        // Solana Pay functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_pay_fetch_metadata(
+            [MarshalAs(UnmanagedType.LPStr)] string url,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_pay_request_transaction(
+            [MarshalAs(UnmanagedType.LPStr)] string url,
+            [MarshalAs(UnmanagedType.LPStr)] string accountPubkey,
+            out IntPtr error);
+
+        // Subscription functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_subscription_poll_next(IntPtr subscription, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UIntPtr solana_subscription_pending_count(IntPtr subscription);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_subscription_state(IntPtr subscription);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_subscription_destroy(IntPtr subscription);
+
+        // Balance watcher functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_balance_watcher_create();
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_balance_watcher_add(
+            IntPtr watcher,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            int kind,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_balance_watcher_remove(
+            IntPtr watcher,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_balance_watcher_start(
+            IntPtr watcher,
+            IntPtr client,
+            ulong pollIntervalMs,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_balance_watcher_stop(IntPtr watcher);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_balance_watcher_poll_event(IntPtr watcher, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_balance_watcher_destroy(IntPtr watcher);
+
+        // Address book functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_address_book_create();
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_address_book_load(
+            [MarshalAs(UnmanagedType.LPStr)] string path,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_address_book_save(
+            IntPtr book,
+            [MarshalAs(UnmanagedType.LPStr)] string path,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_address_book_add(
+            IntPtr book,
+            [MarshalAs(UnmanagedType.LPStr)] string label,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            [MarshalAs(UnmanagedType.LPStr)] string memo,
+            int replace,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_address_book_remove(
+            IntPtr book,
+            [MarshalAs(UnmanagedType.LPStr)] string label,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_address_book_lookup(
+            IntPtr book,
+            [MarshalAs(UnmanagedType.LPStr)] string label,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_address_book_reverse_lookup(
+            IntPtr book,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_address_book_list_json(IntPtr book, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_address_book_destroy(IntPtr book);
+
+        // Vanity address functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_grind_start(
+            [MarshalAs(UnmanagedType.LPStr)] string prefix,
+            int caseInsensitive,
+            ulong maxAttempts,
+            UIntPtr threads,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_grind_poll(
+            IntPtr handle,
+            out ulong attempts,
+            out IntPtr accountOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_grind_cancel(IntPtr handle);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_grind_destroy(IntPtr handle);
+
+        // Wallet manager functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_wallet_manager_create();
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_wallet_manager_add_account(
+            IntPtr manager,
+            [MarshalAs(UnmanagedType.LPStr)] string label,
+            IntPtr account,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_wallet_manager_remove_account(
+            IntPtr manager,
+            [MarshalAs(UnmanagedType.LPStr)] string label,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_wallet_manager_get_pubkey(
+            IntPtr manager,
+            [MarshalAs(UnmanagedType.LPStr)] string label,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_wallet_manager_list_json(IntPtr manager, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_wallet_manager_sign_transaction(
+            IntPtr manager,
+            [MarshalAs(UnmanagedType.LPStr)] string label,
+            IntPtr transaction,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_wallet_manager_destroy(IntPtr manager);
+
+        // Phantom deeplink functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_deeplink_session_create();
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_deeplink_session_public_key(IntPtr session, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_deeplink_encrypt(
+            IntPtr session,
+            [MarshalAs(UnmanagedType.LPStr)] string payloadJson,
+            out IntPtr nonceOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_deeplink_decrypt(
+            IntPtr session,
+            [MarshalAs(UnmanagedType.LPStr)] string walletPublicKey,
+            [MarshalAs(UnmanagedType.LPStr)] string nonce,
+            [MarshalAs(UnmanagedType.LPStr)] string data,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_deeplink_session_destroy(IntPtr session);
+
+        // Session key functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_session_create(
+            [MarshalAs(UnmanagedType.LPStr)] string ownerTokenAccount,
+            [MarshalAs(UnmanagedType.LPStr)] string owner,
+            [MarshalAs(UnmanagedType.LPStr)] string mint,
+            byte decimals,
+            ulong allowance,
+            long expiresAt,
+            out SolanaBuffer approve,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_session_get_account(IntPtr session, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SolanaBuffer solana_session_approve_instruction(IntPtr session, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SolanaBuffer solana_session_revoke_instruction(IntPtr session, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern long solana_session_expires_at(IntPtr session, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_session_serialize(
+            IntPtr session,
+            [MarshalAs(UnmanagedType.LPStr)] string password,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_session_deserialize(
+            [MarshalAs(UnmanagedType.LPStr)] string json,
+            [MarshalAs(UnmanagedType.LPStr)] string password,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_session_destroy(IntPtr session);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_send_transaction(
+            IntPtr client,
+            IntPtr transaction,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_send_with_refresh(
+            IntPtr client,
+            IntPtr transaction,
+            IntPtr account,
+            byte maxRebuilds,
+            ulong timeoutMs,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_send_and_confirm_transaction(
+            IntPtr client,
+            IntPtr transaction,
+            ulong timeoutMs,
+            out ulong slot,
+            out IntPtr error);
+
+        // New transaction methods
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_sign_transaction_with_keypairs(
+            IntPtr transaction,
+            IntPtr[] privateKeysData,
+            IntPtr[] privateKeysLengths,
+            int privateKeysCount,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_sign_transaction_with_account(
+            IntPtr transaction,
+            IntPtr account,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_build_with_instructions(
+            IntPtr transaction,
+            IntPtr instructionsData,
+            int instructionsDataLen,
+            int instructionsCount,
+            [MarshalAs(UnmanagedType.LPStr)] string feePayer,
+            [MarshalAs(UnmanagedType.LPStr)] string recentBlockhash,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_build_with_instruction_list(
+            IntPtr transaction,
+            IntPtr[] instructionsData,
+            UIntPtr[] instructionsLens,
+            UIntPtr instructionsCount,
+            [MarshalAs(UnmanagedType.LPStr)] string feePayer,
+            [MarshalAs(UnmanagedType.LPStr)] string recentBlockhash,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_transaction_set_blockhash_with_expiry(
+            IntPtr transaction,
+            [MarshalAs(UnmanagedType.LPStr)] string blockhash,
+            ulong lastValidBlockHeight,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_transaction_set_instruction_label(
+            IntPtr transaction,
+            UIntPtr index,
+            [MarshalAs(UnmanagedType.LPStr)] string label,
+            out IntPtr error);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        private delegate int ExternalSignerCallback(
+            IntPtr pubkey,
+            IntPtr message,
+            UIntPtr messageLen,
+            IntPtr signatureOut,
+            IntPtr userData);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_set_external_signer(ExternalSignerCallback callback, IntPtr userData);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_sign_transaction_externally(
+            IntPtr transaction,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out IntPtr error);
+
+        // Account functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_create_account();
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_destroy_account(IntPtr account);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_pubkey(
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_private_key(
+            byte[] privateKey,
+            int privateKeyLen,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_keypair_file(
+            [MarshalAs(UnmanagedType.LPStr)] string path,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_write_keypair_file(
+            IntPtr account,
+            [MarshalAs(UnmanagedType.LPStr)] string path,
+            int overwrite,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_pkcs8_pem(
+            [MarshalAs(UnmanagedType.LPStr)] string pem,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_to_pkcs8_pem(IntPtr account, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_secure_free_string(IntPtr ptr);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SolanaStringArray solana_account_split_key(
+            IntPtr account,
+            byte threshold,
+            byte shares,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_shares(
+            [MarshalAs(UnmanagedType.LPArray, SizeParamIndex = 1)] string[] shares,
+            UIntPtr count,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_string_array_secure_free(SolanaStringArray array);
+
+#if UNITY_SOLANA_BIP39
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_mnemonic(
+            [MarshalAs(UnmanagedType.LPStr)] string mnemonic,
+            [MarshalAs(UnmanagedType.LPStr)] string passphrase,
+            [MarshalAs(UnmanagedType.LPStr)] string derivationPath,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_mnemonic_index(
+            [MarshalAs(UnmanagedType.LPStr)] string mnemonic,
+            [MarshalAs(UnmanagedType.LPStr)] string passphrase,
+            uint accountIndex,
+            int scheme,
+            out IntPtr error);
+#endif
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_generate();
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_get_pubkey(
+            IntPtr account,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_get_pubkey_buf(
+            IntPtr account,
+            byte[] buffer,
+            UIntPtr bufferLen,
+            out UIntPtr written,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_get_private_key(
+            IntPtr account,
+            byte[] buffer,
+            UIntPtr bufferLen,
+            out UIntPtr written,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_has_private_key(
+            IntPtr account,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_keypair_json(
+            [MarshalAs(UnmanagedType.LPStr)] string json,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_to_keypair_json(IntPtr account, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_base58_private_key(
+            [MarshalAs(UnmanagedType.LPStr)] string privateKey,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_get_private_key_base58(IntPtr account, out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_sign_message(
+            IntPtr account,
+            byte[] message,
+            UIntPtr messageLen,
+            [Out] byte[] signatureOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_verify_signature(
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            byte[] message,
+            UIntPtr messageLen,
+            byte[] signature,
+            UIntPtr signatureLen,
+            out int resultOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_export_encrypted(
+            IntPtr account,
+            [MarshalAs(UnmanagedType.LPStr)] string password,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_encrypted(
+            [MarshalAs(UnmanagedType.LPStr)] string json,
+            [MarshalAs(UnmanagedType.LPStr)] string password,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_get_pubkey_bytes(
+            IntPtr account,
+            [Out] byte[] pubkeyOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_pubkey_to_bytes(
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            [Out] byte[] pubkeyOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_is_valid_pubkey([MarshalAs(UnmanagedType.LPStr)] string pubkey);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_validate_pubkey(
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_seed(
+            byte[] seed,
+            UIntPtr seedLen,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_sign_offchain_message(
+            IntPtr account,
+            byte[] message,
+            UIntPtr messageLen,
+            [Out] byte[] signatureOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_verify_offchain_message(
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            byte[] message,
+            UIntPtr messageLen,
+            byte[] signature,
+            UIntPtr signatureLen,
+            out int resultOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_set_secure_memory(
+            IntPtr account,
+            int enabled,
+            out int lockedOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_account_is_memory_locked(IntPtr account);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_account_from_private_key_b64(
+            [MarshalAs(UnmanagedType.LPStr)] string privateKeyBase64,
+            out IntPtr error);
+
+        // PDA functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_find_program_address(
+            IntPtr[] seeds,
+            int seedsLen,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr addressOut,
+            out byte bumpOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_find_associated_token_address(
+            [MarshalAs(UnmanagedType.LPStr)] string walletAddress,
+            [MarshalAs(UnmanagedType.LPStr)] string tokenMint,
+            out IntPtr addressOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_find_metadata_address(
+            [MarshalAs(UnmanagedType.LPStr)] string mint,
+            out IntPtr addressOut,
+            out byte bumpOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_find_master_edition_address(
+            [MarshalAs(UnmanagedType.LPStr)] string mint,
+            out IntPtr addressOut,
+            out byte bumpOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_find_edition_marker_address(
+            [MarshalAs(UnmanagedType.LPStr)] string mint,
+            ulong edition,
+            out IntPtr addressOut,
+            out byte bumpOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_find_associated_token_address_with_program(
+            [MarshalAs(UnmanagedType.LPStr)] string walletAddress,
+            [MarshalAs(UnmanagedType.LPStr)] string tokenMint,
+            [MarshalAs(UnmanagedType.LPStr)] string tokenProgram,
+            int strict,
+            out IntPtr addressOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr solana_find_program_addresses_batch(
+            byte[] seedsData,
+            UIntPtr seedsDataLen,
+            UIntPtr[] seedLengths,
+            UIntPtr seedCount,
+            UIntPtr[] setOffsets,
+            UIntPtr setCount,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_validate_pda(
+            [MarshalAs(UnmanagedType.LPStr)] string address,
+            byte[] seedsData,
+            UIntPtr seedsDataLen,
+            UIntPtr[] seedLengths,
+            UIntPtr seedCount,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out int resultOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_validate_pda_with_bump(
+            [MarshalAs(UnmanagedType.LPStr)] string address,
+            byte[] seedsData,
+            UIntPtr seedsDataLen,
+            UIntPtr[] seedLengths,
+            UIntPtr seedCount,
+            byte bump,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out int resultOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_find_program_address_spec(
+            [MarshalAs(UnmanagedType.LPStr)] string seedsJson,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr addressOut,
+            out byte bumpOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_is_on_curve(
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out int resultOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_derive_lookup_table_address(
+            [MarshalAs(UnmanagedType.LPStr)] string authority,
+            ulong recentSlot,
+            out IntPtr addressOut,
+            out byte bumpOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_create_program_address_with_bump(
+            byte[] seedsData,
+            UIntPtr seedsDataLen,
+            UIntPtr[] seedLengths,
+            UIntPtr seedCount,
+            byte bump,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr addressOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_try_find_program_address_from_bump(
+            byte[] seedsData,
+            UIntPtr seedsDataLen,
+            UIntPtr[] seedLengths,
+            UIntPtr seedCount,
+            byte startingBump,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr addressOut,
+            out byte bumpOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_find_pda_from_template(
+            [MarshalAs(UnmanagedType.LPStr)] string template,
+            [MarshalAs(UnmanagedType.LPStr)] string bindingsJson,
+            [MarshalAs(UnmanagedType.LPStr)] string programId,
+            out IntPtr addressOut,
+            out byte bumpOut,
+            out IntPtr error);
+
+        // Instruction building functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_create_token_transfer_instruction(
+            [MarshalAs(UnmanagedType.LPStr)] string source,
+            [MarshalAs(UnmanagedType.LPStr)] string destination,
+            [MarshalAs(UnmanagedType.LPStr)] string owner,
+            ulong amount,
+            out IntPtr encodedDataOut,
+            out IntPtr encodedDataLenOut,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_free_encoded_instruction(IntPtr dataPtr);
+
+        // Helper functions
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_free_string(IntPtr ptr);
+
+        // Helper to handle errors
+        private static void CheckError(IntPtr errorPtr)
+        {
+            if (errorPtr != IntPtr.Zero)
+            {
+                string errorMessage = Marshal.PtrToStringAnsi(errorPtr);
+                solana_free_string(errorPtr);
+                throw new SolanaException(errorMessage);
+            }
+        }
+
+        // Like CheckError for the error-object variants. The object owns its strings, so
+        // they are copied out before it is destroyed.
+        private static void CheckErrorObject(IntPtr errorObj)
+        {
+            if (errorObj != IntPtr.Zero)
+            {
+                var exception = new SolanaErrorException(
+                    Marshal.PtrToStringAnsi(solana_error_get_code(errorObj)),
+                    Marshal.PtrToStringAnsi(solana_error_get_message(errorObj)),
+                    Marshal.PtrToStringAnsi(solana_error_get_logs_json(errorObj)),
+                    solana_error_is_retryable(errorObj) != 0,
+                    solana_error_get_numeric_code(errorObj),
+                    Marshal.PtrToStringAnsi(solana_error_get_json(errorObj)));
+                solana_error_destroy(errorObj);
+                throw exception;
+            }
+        }
+
+        // Outcome of a simulated transaction
+        public class SimulationResult
+        {
+            public bool Succeeded;
+            // Transaction error, null if the simulation succeeded
+            public string Error;
+            public string[] Logs;
+            public ulong UnitsConsumed;
+        }
+
+        // Helper to convert IntPtr to string and free the memory
+        private static string PtrToStringAndFree(IntPtr ptr)
+        {
+            if (ptr == IntPtr.Zero)
+                return null;
+
+            string result = Marshal.PtrToStringAnsi(ptr);
+            solana_free_string(ptr);
+            return result;
+        }
+
+        // Constructor
+        public SolanaClient(string url, string commitment = "confirmed")
+        {
+            IntPtr errorPtr;
+            _clientPtr = solana_create_rpc_client(url, commitment, out errorPtr);
+            CheckError(errorPtr);
+        }
+
+        // With connection reuse settings, e.g. {"maxIdlePerHost": 4, "tcpKeepaliveMs": 60000}
+        public SolanaClient(string url, string commitment, string configJson)
+        {
+            IntPtr errorPtr;
+            _clientPtr = solana_create_rpc_client_configured(url, commitment, configJson, out errorPtr);
+            CheckError(errorPtr);
+        }
+
+        private SolanaClient(IntPtr clientPtr)
+        {
+            _clientPtr = clientPtr;
+        }
+
+        // Sends every request through an HTTP or SOCKS5 proxy, e.g. "http://proxy.corp:3128"
+        // or "socks5h://10.0.0.2:1080". Leave the username and password null for a proxy
+        // without auth.
+        public static SolanaClient WithProxy(
+            string url,
+            string commitment,
+            string proxyUrl,
+            string proxyUsername = null,
+            string proxyPassword = null)
+        {
+            IntPtr errorPtr;
+            IntPtr clientPtr = solana_create_rpc_client_with_proxy(
+                url, commitment, proxyUrl, proxyUsername, proxyPassword, out errorPtr);
+            CheckError(errorPtr);
+            return new SolanaClient(clientPtr);
+        }
+
+        // Connects to whichever of the urls answers fastest; throws with every endpoint's
+        // reason if none is healthy
+        public static SolanaClient Fastest(string[] urls, string commitment, ulong timeoutMs)
+        {
+            IntPtr errorPtr;
+            IntPtr clientPtr = solana_create_rpc_client_fastest(
+                urls, (UIntPtr)urls.Length, commitment, timeoutMs, out errorPtr);
+            CheckError(errorPtr);
+            return new SolanaClient(clientPtr);
+        }
+
+        // JSON ranking of the urls by latency, with the reason for each one that failed
+        public static string ProbeEndpoints(string[] urls, ulong timeoutMs)
+        {
+            IntPtr errorPtr;
+            IntPtr rankingPtr = solana_probe_endpoints(urls, (UIntPtr)urls.Length, timeoutMs, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(rankingPtr);
+        }
+
+        // Returns a client shared with every other Shared() caller for the same url and
+        // commitment. Disposing it only releases this reference.
+        public static SolanaClient Shared(string url, string commitment = "confirmed")
+        {
+            IntPtr errorPtr;
+            IntPtr clientPtr = solana_get_or_create_rpc_client(url, commitment, out errorPtr);
+            CheckError(errorPtr);
+            return new SolanaClient(clientPtr) { _shared = true };
+        }
+
+        // Returns an independent client reusing this client's connection
+        public SolanaClient Clone()
+        {
+            IntPtr errorPtr;
+            IntPtr clientPtr = solana_clone_rpc_client(_clientPtr, out errorPtr);
+            CheckError(errorPtr);
+            return new SolanaClient(clientPtr);
+        }
+
+        // Caches GetAccountInfo and GetAccountData results for ttlMs, shared with clones.
+        // Accounts written by transactions sent through this client are dropped on send.
+        public void EnableAccountCache(ulong ttlMs, int maxEntries)
+        {
+            IntPtr errorPtr;
+            solana_enable_account_cache(_clientPtr, ttlMs, (UIntPtr)maxEntries, out errorPtr);
+            CheckError(errorPtr);
+        }
+
+        public void DisableAccountCache()
+        {
+            IntPtr errorPtr;
+            solana_disable_account_cache(_clientPtr, out errorPtr);
+            CheckError(errorPtr);
+        }
+
+        public void InvalidateAccount(string pubkey)
+        {
+            IntPtr errorPtr;
+            solana_invalidate_account(_clientPtr, pubkey, out errorPtr);
+            CheckError(errorPtr);
+        }
+
+        public void InvalidateAllAccounts()
+        {
+            IntPtr errorPtr;
+            solana_invalidate_all_accounts(_clientPtr, out errorPtr);
+            CheckError(errorPtr);
+        }
+
+        // Counts calls, failures and latencies per RPC method, shared with clones
+        public void EnableMetrics()
+        {
+            IntPtr errorPtr;
+            solana_enable_metrics(_clientPtr, out errorPtr);
+            CheckError(errorPtr);
+        }
+
+        public void DisableMetrics()
+        {
+            IntPtr errorPtr;
+            solana_disable_metrics(_clientPtr, out errorPtr);
+            CheckError(errorPtr);
+        }
+
+        public string GetMetricsJson()
+        {
+            IntPtr errorPtr;
+            IntPtr jsonPtr = solana_get_metrics_json(_clientPtr, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(jsonPtr);
+        }
+
+        public void ResetMetrics()
+        {
+            solana_reset_metrics(_clientPtr);
+        }
+
+        // Destructor
+        ~SolanaClient()
+        {
+            Dispose(false);
+        }
+
+        // Dispose pattern
+        public void Dispose()
+        {
+            Dispose(true);
+            GC.SuppressFinalize(this);
+        }
+
+        protected virtual void Dispose(bool disposing)
+        {
+            if (!_disposed)
+            {
+                if (_clientPtr != IntPtr.Zero)
+                {
+                    if (_shared)
+                    {
+                        solana_release_rpc_client(_clientPtr);
+                    }
+                    else
+                    {
+                        solana_destroy_rpc_client(_clientPtr);
+                    }
+                    _clientPtr = IntPtr.Zero;
+                }
+                _disposed = true;
+            }
+        }
+
+        // API Methods
+        // Failures throw SolanaErrorException
+        public ulong GetBalance(string pubkey)
+        {
+            IntPtr errorObj;
+            ulong balance = solana_get_balance_e(_clientPtr, pubkey, out errorObj);
+            CheckErrorObject(errorObj);
+            return balance;
+        }
+
+        public string GetLatestBlockhash()
+        {
+            IntPtr errorObj;
+            IntPtr blockhashPtr = solana_get_latest_blockhash_e(_clientPtr, out errorObj);
+            CheckErrorObject(errorObj);
+            return PtrToStringAndFree(blockhashPtr);
+        }
+
+        // The current epoch and progress through it as JSON: {"epoch", "slotIndex",
+        // "slotsInEpoch", "absoluteSlot", "blockHeight"}
+        public string GetEpochInfo()
+        {
+            IntPtr errorPtr;
+            IntPtr infoPtr = solana_get_epoch_info(_clientPtr, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(infoPtr);
+        }
+
+        // The slot the node has reached at the client's commitment
+        public ulong GetSlot()
+        {
+            ulong slot;
+            IntPtr errorPtr;
+            int result = solana_get_slot(_clientPtr, out slot, out errorPtr);
+            CheckError(errorPtr);
+            if (result == 0)
+            {
+                throw new SolanaException("Failed to get slot");
+            }
+            return slot;
+        }
+
+        // The block height at the client's commitment. Skipped slots have no block, so it
+        // trails the slot.
+        public ulong GetBlockHeight()
+        {
+            ulong blockHeight;
+            IntPtr errorPtr;
+            int result = solana_get_block_height(_clientPtr, out blockHeight, out errorPtr);
+            CheckError(errorPtr);
+            if (result == 0)
+            {
+                throw new SolanaException("Failed to get block height");
+            }
+            return blockHeight;
+        }
+
+        // Funds the address from the faucet of devnet, testnet or a local validator and
+        // returns the airdrop's signature. When the faucet refuses because of its limits
+        // the exception message starts with "Rate limited"; wait before asking again.
+        public string RequestAirdrop(string pubkey, ulong lamports)
+        {
+            IntPtr errorPtr;
+            IntPtr signaturePtr = solana_request_airdrop(_clientPtr, pubkey, lamports, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(signaturePtr);
+        }
+
+        // The blockhash with the last block height at which a transaction using it can
+        // still land; pass both to Transaction.SetBlockhashWithExpiry
+        public string GetLatestBlockhashWithExpiry(out ulong lastValidBlockHeight)
+        {
+            IntPtr blockhashPtr;
+            IntPtr errorPtr;
+            int result = solana_get_latest_blockhash_with_expiry(
+                _clientPtr,
+                out blockhashPtr,
+                out lastValidBlockHeight,
+                out errorPtr);
+            CheckError(errorPtr);
+            if (result == 0)
+            {
+                throw new SolanaException("Failed to get latest blockhash");
+            }
+            return PtrToStringAndFree(blockhashPtr);
+        }
+
+        // Overloads with a per-call timeout; they throw "Timed out after N ms" once it
+        // passes. Requires Initialize().
+        public ulong GetBalance(string pubkey, ulong timeoutMs)
+        {
+            IntPtr errorPtr;
+            ulong balance = solana_get_balance_t(_clientPtr, pubkey, timeoutMs, out errorPtr);
+            CheckError(errorPtr);
+            return balance;
+        }
+
+        public string GetLatestBlockhash(ulong timeoutMs)
+        {
+            IntPtr errorPtr;
+            IntPtr blockhashPtr = solana_get_latest_blockhash_t(_clientPtr, timeoutMs, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(blockhashPtr);
+        }
+
+        public string GetAccountInfo(string pubkey, ulong timeoutMs)
+        {
+            IntPtr errorPtr;
+            IntPtr infoPtr = solana_get_account_info_t(_clientPtr, pubkey, timeoutMs, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(infoPtr);
+        }
+
+        // Balances of many wallets in as few requests as possible, in the same order; null
+        // for wallets without an account
+        public ulong?[] GetBalances(string[] pubkeys)
+        {
+            IntPtr errorPtr;
+            ulong[] lamports = new ulong[pubkeys.Length];
+            int[] exists = new int[pubkeys.Length];
+            solana_get_balances(_clientPtr, pubkeys, (UIntPtr)pubkeys.Length, lamports, exists, out errorPtr);
+            CheckError(errorPtr);
+
+            ulong?[] balances = new ulong?[pubkeys.Length];
+            for (int i = 0; i < pubkeys.Length; i++)
+            {
+                balances[i] = exists[i] != 0 ? lamports[i] : (ulong?)null;
+            }
+            return balances;
+        }
+
+        // Returns false instead of throwing when the balance cannot be fetched; error holds the reason
+        public bool TryGetBalance(string pubkey, out ulong lamports, out string error)
+        {
+            IntPtr errorPtr;
+            bool ok = solana_get_balance_v2(_clientPtr, pubkey, out lamports, out errorPtr) != 0;
+            error = PtrToStringAndFree(errorPtr);
+            return ok;
+        }
+
+        public bool TryGetTokenAccountBalance(string tokenAccount, out ulong amount, out string error)
+        {
+            IntPtr errorPtr;
+            bool ok = solana_get_token_account_balance_v2(_clientPtr, tokenAccount, out amount, out errorPtr) != 0;
+            error = PtrToStringAndFree(errorPtr);
+            return ok;
+        }
+
+        public ulong GetTokenAccountBalance(string tokenAccount)
+        {
+            IntPtr errorPtr;
+            ulong balance = solana_get_token_account_balance(_clientPtr, tokenAccount, out errorPtr);
+            CheckError(errorPtr);
+            return balance;
+        }
+
+        // The AtSlot reads are only answered by a node that has seen minContextSlot, e.g. the
+        // slot from Transaction.SendAndConfirm, so they never show state from before it. A
+        // node still behind throws a retryable error with code 1007. 0 reads from any node.
+        public ulong GetBalanceAtSlot(string pubkey, ulong minContextSlot)
+        {
+            IntPtr errorPtr;
+            ulong lamports;
+            solana_get_balance_at_slot(_clientPtr, pubkey, minContextSlot, out lamports, out errorPtr);
+            CheckError(errorPtr);
+            return lamports;
+        }
+
+        public ulong GetTokenAccountBalanceAtSlot(string tokenAccount, ulong minContextSlot)
+        {
+            IntPtr errorPtr;
+            ulong amount;
+            solana_get_token_account_balance_at_slot(_clientPtr, tokenAccount, minContextSlot, out amount, out errorPtr);
+            CheckError(errorPtr);
+            return amount;
+        }
+
+        public string GetAccountInfoAtSlot(string pubkey, ulong minContextSlot)
+        {
+            IntPtr errorPtr;
+            IntPtr infoPtr = solana_get_account_info_at_slot(_clientPtr, pubkey, minContextSlot, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(infoPtr);
+        }
+
+        public byte[] GetAccountDataAtSlot(string pubkey, ulong minContextSlot)
+        {
+            IntPtr errorPtr;
+            SolanaBuffer buffer = solana_get_account_data_at_slot(_clientPtr, pubkey, minContextSlot, out errorPtr);
+            CheckError(errorPtr);
+            return BufferToBytesAndFree(buffer);
+        }
+
+        public string GetAccountInfo(string pubkey)
+        {
+            IntPtr errorPtr;
+            IntPtr infoPtr = solana_get_account_info(_clientPtr, pubkey, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(infoPtr);
+        }
+
+        // Envelope with the aggregate price of a Pyth price account, e.g.
+        // {"price": "145.23456789", "status": "trading", "trading": true, ...}. Only show
+        // the price as live when "trading" is true.
+        public string GetPythPrice(string priceAccount)
+        {
+            IntPtr errorPtr;
+            IntPtr pricePtr = solana_get_pyth_price(_clientPtr, priceAccount, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(pricePtr);
+        }
+
+        // Envelope with a decoded stake account, e.g. {"state": "delegated", "status":
+        // "active", "delegation": {"voter", "stake", ...}, ...}
+        public string GetStakeAccountState(string stakeAccount)
+        {
+            IntPtr errorPtr;
+            IntPtr statePtr = solana_get_stake_account_state(_clientPtr, stakeAccount, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(statePtr);
+        }
+
+        // Envelope with a decoded durable nonce account: {"version", "authority", "nonce",
+        // "lamportsPerSignature"}
+        public string GetNonceAccount(string noncePubkey)
+        {
+            IntPtr errorPtr;
+            IntPtr accountPtr = solana_get_nonce_account(_clientPtr, noncePubkey, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(accountPtr);
+        }
+
+        // The nonce stored in a durable nonce account, to sign over instead of a blockhash
+        public string GetNonceValue(string noncePubkey)
+        {
+            IntPtr errorPtr;
+            IntPtr noncePtr = solana_get_nonce_value(_clientPtr, noncePubkey, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(noncePtr);
+        }
+
+        public string GetProgramAccounts(string programId)
+        {
+            IntPtr errorPtr;
+            IntPtr accountsPtr = solana_get_program_accounts(_clientPtr, programId, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(accountsPtr);
+        }
+
+        // One page of the program's accounts, {accounts, nextCursor}. Pass null as the cursor for
+        // the first page and nextCursor after that; it is null on the last page.
+        public string GetProgramAccountsPaged(string programId, int pageSize, string cursor = null, string filtersJson = null)
+        {
+            IntPtr errorPtr;
+            IntPtr pagePtr = solana_get_program_accounts_paged(
+                _clientPtr, programId, filtersJson, (UIntPtr)pageSize, cursor, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(pagePtr);
+        }
+
+        // All accounts of one Anchor account type, e.g. "PlayerState", matched by its
+        // discriminator. filtersJson adds further getProgramAccounts filters.
+        public string GetAnchorProgramAccounts(string programId, string accountName, string filtersJson = null)
+        {
+            IntPtr errorPtr;
+            IntPtr accountsPtr = solana_get_anchor_program_accounts(
+                _clientPtr, programId, accountName, filtersJson, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(accountsPtr);
+        }
+
+        // Addresses of the accounts owned by the program, without their data
+        public string[] GetProgramAccountPubkeys(string programId)
+        {
+            IntPtr errorPtr;
+            SolanaStringArray array = solana_get_program_account_pubkeys(_clientPtr, programId, out errorPtr);
+            CheckError(errorPtr);
+            return StringArrayToManagedAndFree(array);
+        }
+
+        // Latest transaction signatures for the address, newest first; 0 for the node's default limit
+        public string[] GetSignaturesForAddress(string address, int limit = 0)
+        {
+            IntPtr errorPtr;
+            SolanaStringArray array = solana_get_signatures_for_address_list(_clientPtr, address, (UIntPtr)limit, out errorPtr);
+            CheckError(errorPtr);
+            return StringArrayToManagedAndFree(array);
+        }
+
+        // One page of the address's transaction history, newest first, as JSON:
+        // [{"signature", "slot", "blockTime", "err", "memo"}]. Pass the last signature of a
+        // page as before to get the next one; until stops at a signature already shown.
+        public string GetSignaturesForAddressPage(string address, int limit, string before = null, string until = null)
+        {
+            IntPtr errorPtr;
+            IntPtr pagePtr = solana_get_signatures_for_address(
+                _clientPtr, address, (UIntPtr)limit, before, until, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(pagePtr);
+        }
+
+        private static string[] StringArrayToManagedAndFree(SolanaStringArray array)
+        {
+            int count = (int)array.len.ToUInt32();
+            string[] strings = new string[count];
+            for (int i = 0; i < count; i++)
+            {
+                strings[i] = Marshal.PtrToStringAnsi(Marshal.ReadIntPtr(array.items, i * IntPtr.Size));
+            }
+            solana_string_array_free(array);
+            return strings;
+        }
+
+        public string GetTransactionStatus(string signature)
+        {
+            IntPtr errorPtr;
+            IntPtr statusPtr = solana_get_transaction_status(_clientPtr, signature, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(statusPtr);
+        }
+
+        // Envelope with the emit! events and msg! lines the program logged in the
+        // transaction, including from inside other programs' CPIs
+        public string GetTransactionEvents(string signature, string programId)
+        {
+            IntPtr errorPtr;
+            IntPtr eventsPtr = solana_get_transaction_events(_clientPtr, signature, programId, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(eventsPtr);
+        }
+
+        // Envelope with the NFTs the owner holds under either token program, with their
+        // Metaplex name, symbol and URI when fetchMetadata is set. limit 0 returns all.
+        public string GetNftsByOwner(string owner, bool fetchMetadata = true, int limit = 0)
+        {
+            IntPtr errorPtr;
+            IntPtr nftsPtr = solana_get_nfts_by_owner(
+                _clientPtr, owner, fetchMetadata ? 1 : 0, (UIntPtr)limit, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(nftsPtr);
+        }
+
+        // Every token account holding the mint with at least minAmount base units, largest
+        // first: [{"owner", "token_account", "amount"}]. Works for Token-2022 mints too.
+        public string GetTokenHolders(string mint, ulong minAmount = 0)
+        {
+            IntPtr errorPtr;
+            IntPtr holdersPtr = solana_get_token_holders(_clientPtr, mint, minAmount, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(holdersPtr);
+        }
+
+        // The total supply of a token mint as JSON: {"amount", "decimals", "uiAmountString"}
+        public string GetTokenSupply(string mint)
+        {
+            IntPtr errorPtr;
+            IntPtr supplyPtr = solana_get_token_supply(_clientPtr, mint, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(supplyPtr);
+        }
+
+        public bool ConfirmTransaction(string signature)
+        {
+            IntPtr errorPtr;
+            int result = solana_confirm_transaction(_clientPtr, signature, out errorPtr);
+            CheckError(errorPtr);
+            return result != 0;
+        }
+
+        // Waits for all of the signatures at once. The result maps each signature to
+        // {"status": "confirmed" | "failed" | "timedOut", ...}.
+        public string WaitForConfirmations(string[] signatures, string commitment, ulong timeoutMs)
+        {
+            IntPtr errorPtr;
+            IntPtr outcomesPtr = solana_wait_for_confirmations(
+                _clientPtr, signatures, (UIntPtr)signatures.Length, commitment, timeoutMs, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(outcomesPtr);
+        }
+
+        // Queued requests: these return a request id straight away. Poll it from a
+        // coroutine with PollRequest until the status is no longer Pending.
+        public enum RequestStatus
+        {
+            Unknown = -1,
+            Pending = 0,
+            Complete = 1,
+            Failed = 2
+        }
+
+        public ulong RequestBalance(string pubkey)
+        {
+            IntPtr errorPtr;
+            ulong requestId = solana_request_get_balance(_clientPtr, pubkey, out errorPtr);
+            CheckError(errorPtr);
+            return requestId;
+        }
+
+        public ulong RequestLatestBlockhash()
+        {
+            IntPtr errorPtr;
+            ulong requestId = solana_request_latest_blockhash(_clientPtr, out errorPtr);
+            CheckError(errorPtr);
+            return requestId;
+        }
+
+        public ulong RequestConfirmTransaction(string signature)
+        {
+            IntPtr errorPtr;
+            ulong requestId = solana_request_confirm_transaction(_clientPtr, signature, out errorPtr);
+            CheckError(errorPtr);
+            return requestId;
+        }
+
+        // Starts the async runtime every RPC call runs on and the background workers used by
+        // the Request* methods; RPC calls fail with "Library not initialized" until it has
+        // been called. configJson may be null, or e.g. {"requestWorkers": 2}. Does nothing if
+        // already initialized.
+        public static void Initialize(string configJson = null)
+        {
+            uint abiVersion = solana_get_abi_version();
+            if (abiVersion != ExpectedAbiVersion)
+            {
+                throw new SolanaException(
+                    $"Native plugin ABI version {abiVersion} does not match the expected version {ExpectedAbiVersion}. Rebuild or replace the solana_unity binary.");
+            }
+
+            IntPtr errorPtr;
+            int result = solana_init(configJson, out errorPtr);
+            CheckError(errorPtr);
+            if (result == 0)
+            {
+                throw new SolanaException("Failed to initialize the Solana library");
+            }
+        }
+
+        // Native build information as JSON (crate and Solana SDK versions, features and build
+        // profile), e.g. for attaching to bug reports
+        public static string GetNativeVersion()
+        {
+            IntPtr versionPtr = solana_get_version();
+            string version = Marshal.PtrToStringAnsi(versionPtr);
+            solana_free_string(versionPtr);
+            return version;
+        }
+
+        // Decodes borsh account data, e.g. from GetAccountData, into a JSON object of its
+        // fields as described by schemaJson. 64- and 128-bit integers come back as strings.
+        public static string DecodeAccountWithSchema(byte[] data, string schemaJson)
+        {
+            IntPtr errorPtr;
+            IntPtr jsonPtr = solana_decode_account_with_schema(
+                data, (UIntPtr)data.Length, schemaJson, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(jsonPtr);
+        }
+
+        // Formats raw base units exactly, e.g. FormatTokenAmount(1500000, 6) is "1.500000",
+        // or "1.5" with trimZeros
+        public static string FormatTokenAmount(ulong raw, byte decimals, bool trimZeros = false)
+        {
+            return PtrToStringAndFree(solana_format_token_amount(raw, decimals, trimZeros ? 1 : 0));
+        }
+
+        // Parses a typed amount such as "1.5" into base units. Throws for amounts finer than one
+        // base unit or too large for a ulong.
+        public static ulong ParseTokenAmount(string amount, byte decimals)
+        {
+            IntPtr errorPtr;
+            ulong raw;
+            solana_parse_token_amount(amount, decimals, out raw, out errorPtr);
+            CheckError(errorPtr);
+            return raw;
+        }
+
+        // Fetches {"label", "icon"} of a Solana Pay transaction request (a solana: URL whose
+        // target is an https link), to show before asking the merchant for a transaction
+        public static string PayFetchMetadata(string url)
+        {
+            IntPtr errorPtr;
+            IntPtr jsonPtr = solana_pay_fetch_metadata(url, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(jsonPtr);
+        }
+
+        // Sends the player's account to a Solana Pay transaction request and returns
+        // {"transaction", "message"}. Load the transaction with Transaction.DeserializeBase64.
+        public static string PayRequestTransaction(string url, string accountPubkey)
+        {
+            IntPtr errorPtr;
+            IntPtr jsonPtr = solana_pay_request_transaction(url, accountPubkey, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(jsonPtr);
+        }
+
+        // Names a custom program error as JSON ({"code", "name", "message", "builtin"}) from
+        // Anchor's built-in errors, the program's AnchorError log line or the IDL's errors.
+        // logsJson and idlJson may be null. Returns null when nothing names the code.
+        public static string DecodeProgramError(uint code, string logsJson = null, string idlJson = null)
+        {
+            IntPtr errorPtr;
+            IntPtr decodedPtr = solana_decode_program_error(logsJson, code, idlJson, out errorPtr);
+            CheckError(errorPtr);
+            if (decodedPtr == IntPtr.Zero)
+            {
+                return null;
+            }
+            string decoded = Marshal.PtrToStringAnsi(decodedPtr);
+            solana_free_string(decodedPtr);
+            return decoded;
+        }
+
+        // Cancels outstanding requests and stops the background workers. Call before a
+        // domain reload, e.g. from AssemblyReloadEvents.beforeAssemblyReload in the editor.
+        // Returns false if a worker was still busy when the shutdown timeout passed.
+        public static bool Shutdown()
+        {
+            return solana_shutdown() != 0;
+        }
+
+        // Returns the result envelope JSON once the request has finished, and null while it
+        // is pending. A finished result can only be retrieved once.
+        public static string PollRequest(ulong requestId, out RequestStatus status)
+        {
+            int rawStatus;
+            IntPtr jsonPtr = solana_poll_request(requestId, out rawStatus);
+            status = (RequestStatus)rawStatus;
+            return PtrToStringAndFree(jsonPtr);
+        }
+
+        public static bool CancelRequest(ulong requestId)
+        {
+            return solana_cancel_request(requestId) != 0;
+        }
+
+        // Held in static fields so neither is collected while the native side can call them
+        private static Func<string, byte[], byte[]> _externalSigner;
+        private static readonly ExternalSignerCallback ExternalSignerThunk = SignWithExternalSigner;
+
+        [AOT.MonoPInvokeCallback(typeof(ExternalSignerCallback))]
+        private static int SignWithExternalSigner(
+            IntPtr pubkey, IntPtr message, UIntPtr messageLen, IntPtr signatureOut, IntPtr userData)
+        {
+            // Exceptions must not unwind into native code, so any failure is a refusal
+            try
+            {
+                Func<string, byte[], byte[]> signer = _externalSigner;
+                if (signer == null)
+                {
+                    return 1;
+                }
+
+                byte[] messageBytes = new byte[(int)messageLen.ToUInt64()];
+                Marshal.Copy(message, messageBytes, 0, messageBytes.Length);
+                byte[] signature = signer(Marshal.PtrToStringAnsi(pubkey), messageBytes);
+                if (signature == null || signature.Length != 64)
+                {
+                    return 1;
+                }
+
+                Marshal.Copy(signature, 0, signatureOut, signature.Length);
+                return 0;
+            }
+            catch (Exception)
+            {
+                return 1;
+            }
+        }
+
+        // Signs for keys the library never sees, e.g. in a wallet app or platform keystore,
+        // through Transaction.SignExternally. The signer receives the pubkey and the message
+        // bytes and returns the 64-byte signature, or null to refuse. It runs on the thread
+        // that signs, while the transaction is locked, so it must not use that transaction.
+        // Pass null to unregister.
+        public static void SetExternalSigner(Func<string, byte[], byte[]> signer)
+        {
+            _externalSigner = signer;
+            solana_set_external_signer(signer != null ? ExternalSignerThunk : null, IntPtr.Zero);
+        }
+
+        public enum LogLevel
+        {
+            Debug = 0,
+            Info = 1,
+            Warn = 2,
+            Error = 3
+        }
+
+        private static Action<LogLevel, string> _logHandler;
+        private static readonly LogCallback LogThunk = ForwardLog;
+
+        [AOT.MonoPInvokeCallback(typeof(LogCallback))]
+        private static void ForwardLog(int level, IntPtr message, IntPtr userData)
+        {
+            // Exceptions must not unwind into native code
+            try
+            {
+                Action<LogLevel, string> handler = _logHandler;
+                if (handler != null)
+                {
+                    handler((LogLevel)level, Marshal.PtrToStringAnsi(message));
+                }
+            }
+            catch (Exception)
+            {
+            }
+        }
+
+        // Receives the native layer's log lines at minLevel and above: RPC request timing and
+        // failures, transaction signing and panics in request workers. The handler can run on
+        // worker threads, so hand lines to the main thread before touching Unity objects.
+        public static void SetLogHandler(Action<LogLevel, string> handler, LogLevel minLevel = LogLevel.Info)
+        {
+            if (handler == null)
+            {
+                ClearLogHandler();
+                return;
+            }
+
+            _logHandler = handler;
+            solana_set_log_callback(LogThunk, IntPtr.Zero, (int)minLevel);
+        }
+
+        public static void ClearLogHandler()
+        {
+            solana_clear_log_callback();
+            _logHandler = null;
+        }
+
+        // PDA methods
+        public (string address, byte bump) FindProgramAddress(string[] seeds, string programId)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            byte bump;
+
+            IntPtr[] seedsPtr = new IntPtr[seeds.Length];
+            for (int i = 0; i < seeds.Length; i++)
+            {
+                seedsPtr[i] = Marshal.StringToHGlobalAnsi(seeds[i]);
+            }
+
+            int result = solana_find_program_address(seedsPtr, seeds.Length, programId, out addressPtr, out bump, out errorPtr);
+
+            // Free the allocated memory
+            foreach (IntPtr ptr in seedsPtr)
+            {
+                Marshal.FreeHGlobal(ptr);
+            }
+
+            CheckError(errorPtr);
+
+            if (result == 0)
+            {
+                throw new SolanaException("Failed to find program address");
+            }
+
+            string address = PtrToStringAndFree(addressPtr);
+            return (address, bump);
+        }
+
+        public string FindAssociatedTokenAddress(string walletAddress, string tokenMint)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+
+            int result = solana_find_associated_token_address(walletAddress, tokenMint, out addressPtr, out errorPtr);
+            CheckError(errorPtr);
+
+            if (result == 0)
+            {
+                throw new SolanaException("Failed to find associated token address");
+            }
+
+            return PtrToStringAndFree(addressPtr);
+        }
+
+        // For a mint owned by another token program, e.g. Token-2022. With strict set, a
+        // program other than the two token programs is rejected instead of derived from.
+        public string FindAssociatedTokenAddress(string walletAddress, string tokenMint, string tokenProgram, bool strict = false)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            int result = solana_find_associated_token_address_with_program(
+                walletAddress, tokenMint, tokenProgram, strict ? 1 : 0, out addressPtr, out errorPtr);
+            return TakeDerivedAddress(result, addressPtr, errorPtr);
+        }
+
+        // Checks the outcome of a call that derives an address and takes ownership of it
+        private static string TakeDerivedAddress(int result, IntPtr addressPtr, IntPtr errorPtr)
+        {
+            CheckError(errorPtr);
+            if (result == 0)
+            {
+                throw new SolanaException("Failed to derive address");
+            }
+            return PtrToStringAndFree(addressPtr);
+        }
+
+        // The Metaplex metadata account of a mint, which holds its name, symbol and URI
+        public static (string address, byte bump) FindMetadataAddress(string mint)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            byte bump;
+            int result = solana_find_metadata_address(mint, out addressPtr, out bump, out errorPtr);
+            return (TakeDerivedAddress(result, addressPtr, errorPtr), bump);
+        }
+
+        // The master edition account of an NFT mint
+        public static (string address, byte bump) FindMasterEditionAddress(string mint)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            byte bump;
+            int result = solana_find_master_edition_address(mint, out addressPtr, out bump, out errorPtr);
+            return (TakeDerivedAddress(result, addressPtr, errorPtr), bump);
+        }
+
+        // The edition marker account that records whether a print edition has been minted
+        public static (string address, byte bump) FindEditionMarkerAddress(string mint, ulong edition)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            byte bump;
+            int result = solana_find_edition_marker_address(mint, edition, out addressPtr, out bump, out errorPtr);
+            return (TakeDerivedAddress(result, addressPtr, errorPtr), bump);
+        }
+
+        // Lays seeds out the way the byte-seed calls take them: the bytes back to back, and
+        // the length of each seed
+        private static byte[] FlattenSeeds(byte[][] seeds, out UIntPtr[] lengths)
+        {
+            lengths = new UIntPtr[seeds.Length];
+            byte[] data = new byte[seeds.Sum(seed => seed.Length)];
+            int offset = 0;
+            for (int i = 0; i < seeds.Length; i++)
+            {
+                Buffer.BlockCopy(seeds[i], 0, data, offset, seeds[i].Length);
+                offset += seeds[i].Length;
+                lengths[i] = (UIntPtr)seeds[i].Length;
+            }
+            return data;
+        }
+
+        // Derives the PDA of every seed set in one call, e.g. the accounts of a whole
+        // inventory. Returns a JSON array of {"address", "bump"} in the order of the sets.
+        public static string FindProgramAddresses(byte[][][] seedSets, string programId)
+        {
+            UIntPtr[] setOffsets = new UIntPtr[seedSets.Length];
+            int seedCount = 0;
+            for (int i = 0; i < seedSets.Length; i++)
+            {
+                setOffsets[i] = (UIntPtr)seedCount;
+                seedCount += seedSets[i].Length;
+            }
+
+            UIntPtr[] seedLengths;
+            byte[] seedsData = FlattenSeeds(seedSets.SelectMany(set => set).ToArray(), out seedLengths);
+
+            IntPtr errorPtr;
+            IntPtr addressesPtr = solana_find_program_addresses_batch(
+                seedsData, (UIntPtr)seedsData.Length, seedLengths, (UIntPtr)seedLengths.Length,
+                setOffsets, (UIntPtr)setOffsets.Length, programId, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(addressesPtr);
+        }
+
+        // Whether address is the canonical PDA of the seeds, the one FindProgramAddress
+        // returns. An address derived with any other bump does not match.
+        public static bool ValidatePda(string address, byte[][] seeds, string programId)
+        {
+            UIntPtr[] seedLengths;
+            byte[] seedsData = FlattenSeeds(seeds, out seedLengths);
+
+            IntPtr errorPtr;
+            int valid;
+            solana_validate_pda(
+                address, seedsData, (UIntPtr)seedsData.Length, seedLengths, (UIntPtr)seedLengths.Length,
+                programId, out valid, out errorPtr);
+            CheckError(errorPtr);
+            return valid != 0;
+        }
+
+        // Whether address is the PDA of the seeds with this bump appended, e.g. a bump stored
+        // in the account itself
+        public static bool ValidatePda(string address, byte[][] seeds, byte bump, string programId)
+        {
+            UIntPtr[] seedLengths;
+            byte[] seedsData = FlattenSeeds(seeds, out seedLengths);
+
+            IntPtr errorPtr;
+            int valid;
+            solana_validate_pda_with_bump(
+                address, seedsData, (UIntPtr)seedsData.Length, seedLengths, (UIntPtr)seedLengths.Length,
+                bump, programId, out valid, out errorPtr);
+            CheckError(errorPtr);
+            return valid != 0;
+        }
+
+        // Derives a PDA from typed seeds, e.g.
+        // [{"kind":"utf8","value":"player"},{"kind":"pubkey","value":"..."},{"kind":"u64le","value":42}]
+        public static (string address, byte bump) FindProgramAddressFromSpec(string seedsJson, string programId)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            byte bump;
+            int result = solana_find_program_address_spec(seedsJson, programId, out addressPtr, out bump, out errorPtr);
+            return (TakeDerivedAddress(result, addressPtr, errorPtr), bump);
+        }
+
+        // Whether the public key lies on the ed25519 curve, i.e. could have a private key.
+        // A PDA never does.
+        public static bool IsOnCurve(string pubkey)
+        {
+            IntPtr errorPtr;
+            int onCurve;
+            solana_is_on_curve(pubkey, out onCurve, out errorPtr);
+            CheckError(errorPtr);
+            return onCurve != 0;
+        }
+
+        // The address of the lookup table an authority creates at recentSlot
+        public static (string address, byte bump) DeriveLookupTableAddress(string authority, ulong recentSlot)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            byte bump;
+            int result = solana_derive_lookup_table_address(authority, recentSlot, out addressPtr, out bump, out errorPtr);
+            return (TakeDerivedAddress(result, addressPtr, errorPtr), bump);
+        }
+
+        // The PDA of the seeds with a known bump appended, without searching for the bump.
+        // Throws if that combination lands on the curve.
+        public static string CreateProgramAddress(byte[][] seeds, byte bump, string programId)
+        {
+            UIntPtr[] seedLengths;
+            byte[] seedsData = FlattenSeeds(seeds, out seedLengths);
+
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            int result = solana_create_program_address_with_bump(
+                seedsData, (UIntPtr)seedsData.Length, seedLengths, (UIntPtr)seedLengths.Length,
+                bump, programId, out addressPtr, out errorPtr);
+            return TakeDerivedAddress(result, addressPtr, errorPtr);
+        }
+
+        // Like FindProgramAddress, searching down from startingBump instead of 255
+        public static (string address, byte bump) TryFindProgramAddressFromBump(byte[][] seeds, byte startingBump, string programId)
+        {
+            UIntPtr[] seedLengths;
+            byte[] seedsData = FlattenSeeds(seeds, out seedLengths);
+
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            byte bump;
+            int result = solana_try_find_program_address_from_bump(
+                seedsData, (UIntPtr)seedsData.Length, seedLengths, (UIntPtr)seedLengths.Length,
+                startingBump, programId, out addressPtr, out bump, out errorPtr);
+            return (TakeDerivedAddress(result, addressPtr, errorPtr), bump);
+        }
+
+        // Derives a PDA from a seed template like "player:{wallet_pubkey}:inventory:{slot_u64}",
+        // filling the placeholders from a JSON object such as {"wallet": "...", "slot": 3}
+        public static (string address, byte bump) FindPdaFromTemplate(string template, string bindingsJson, string programId)
+        {
+            IntPtr errorPtr;
+            IntPtr addressPtr;
+            byte bump;
+            int result = solana_find_pda_from_template(
+                template, bindingsJson, programId, out addressPtr, out bump, out errorPtr);
+            return (TakeDerivedAddress(result, addressPtr, errorPtr), bump);
+        }
+
+        // Any number of pubkeys; they are fetched 100 at a time. With returnPartial the result is
+        // {accounts, errors} and a failed batch leaves nulls instead of failing the call.
+        public string GetMultipleAccounts(string[] pubkeys, bool returnPartial = false)
+        {
+            IntPtr errorPtr;
+            IntPtr accountsPtr = solana_get_multiple_accounts(
+                _clientPtr, pubkeys, (UIntPtr)pubkeys.Length, returnPartial ? 1 : 0, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(accountsPtr);
+        }
+
+        // Recent priority fees as a JSON array of {"slot", "prioritizationFee"}, oldest
+        // first, in micro-lamports per compute unit. Pass the writable accounts of the
+        // transaction to be sent, or none for fees across the cluster.
+        public string GetRecentPrioritizationFees(params string[] writableAccounts)
+        {
+            writableAccounts = writableAccounts ?? new string[0];
+            IntPtr errorPtr;
+            IntPtr feesPtr = solana_get_recent_prioritization_fees(
+                _clientPtr, writableAccounts, (UIntPtr)writableAccounts.Length, out errorPtr);
+            CheckError(errorPtr);
+            return PtrToStringAndFree(feesPtr);
+        }
+
+        // Transaction class wrapper
+        public class Transaction : IDisposable
+        {
+            private IntPtr _transactionPtr;
+            private bool _disposed = false;
+            private SolanaClient _client;
+
+            public Transaction(SolanaClient client)
+            {
+                _client = client;
+                _transactionPtr = solana_create_transaction();
+            }
+
+            ~Transaction()
+            {
+                Dispose(false);
+            }
+
+            public void Dispose()
+            {
+                Dispose(true);
+                GC.SuppressFinalize(this);
+            }
+
+            protected virtual void Dispose(bool disposing)
+            {
+                if (!_disposed)
+                {
+                    if (_transactionPtr != IntPtr.Zero)
+                    {
+                        solana_destroy_transaction(_transactionPtr);
+                        _transactionPtr = IntPtr.Zero;
+                    }
+                    _disposed = true;
+                }
+            }
+
+            public void BuildTransfer(string fromPubkey, string toPubkey, ulong lamports, string recentBlockhash)
+            {
+                IntPtr errorPtr;
+                int result = solana_build_transfer(
+                    _transactionPtr,
+                    fromPubkey,
+                    toPubkey,
+                    lamports,
+                    recentBlockhash,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to build transfer transaction");
+                }
+            }
+
+            // Replaces the blockhash and records its last valid block height, so
+            // SendAndConfirm stops waiting with code 2001 once the chain passes it
+            public void SetBlockhashWithExpiry(string blockhash, ulong lastValidBlockHeight)
+            {
+                IntPtr errorPtr;
+                int result = solana_transaction_set_blockhash_with_expiry(
+                    _transactionPtr,
+                    blockhash,
+                    lastValidBlockHeight,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to set blockhash");
+                }
+            }
+
+            public void BuildTokenTransfer(
+                string tokenProgramId,
+                string sourcePubkey,
+                string destinationPubkey,
+                string ownerPubkey,
+                ulong amount,
+                string recentBlockhash)
+            {
+                IntPtr errorPtr;
+                int result = solana_build_token_transfer(
+                    _transactionPtr,
+                    tokenProgramId,
+                    sourcePubkey,
+                    destinationPubkey,
+                    ownerPubkey,
+                    amount,
+                    recentBlockhash,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to build token transfer transaction");
+                }
+            }
+
+            public void Sign(byte[] privateKey)
+            {
+                IntPtr errorPtr;
+                int result = solana_sign_transaction(
+                    _transactionPtr,
+                    privateKey,
+                    privateKey.Length,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to sign transaction");
+                }
+            }
+
+            // Sign with a base64 encoded private key
+            public void SignBase64(string privateKeyBase64)
+            {
+                IntPtr errorPtr;
+                int result = solana_sign_transaction_b64(
+                    _transactionPtr,
+                    privateKeyBase64,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to sign transaction");
+                }
+            }
+
+            // Build a single program call from base64 instruction data, e.g. data produced by
+            // a server or an Anchor client
+            public void BuildProgramCallBase64(
+                string programId,
+                (string pubkey, bool isSigner, bool isWritable)[] accounts,
+                string dataBase64,
+                string recentBlockhash,
+                string feePayer)
+            {
+                string[] accountPubkeys = accounts.Select(a => a.pubkey).ToArray();
+                int[] isSigner = accounts.Select(a => a.isSigner ? 1 : 0).ToArray();
+                int[] isWritable = accounts.Select(a => a.isWritable ? 1 : 0).ToArray();
+
+                IntPtr errorPtr;
+                int result = solana_build_program_call_b64(
+                    _transactionPtr,
+                    programId,
+                    accountPubkeys,
+                    isSigner,
+                    isWritable,
+                    (UIntPtr)accounts.Length,
+                    dataBase64,
+                    recentBlockhash,
+                    feePayer,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to build program call");
+                }
+            }
+
+            // Replace this transaction with one in base64 wire format
+            public void DeserializeBase64(string dataBase64)
+            {
+                IntPtr errorPtr;
+                int result = solana_deserialize_transaction_b64(
+                    _transactionPtr,
+                    dataBase64,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to deserialize transaction");
+                }
+            }
+
+            // Sign with an Account without exporting its private key
+            public void Sign(Account account)
+            {
+                IntPtr errorPtr;
+                int result = solana_sign_transaction_with_account(
+                    _transactionPtr,
+                    account.Handle,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to sign transaction");
+                }
+            }
+
+            // Signs for pubkey through the signer registered with SetExternalSigner. The
+            // signature is checked against the message before it is added.
+            public void SignExternally(string pubkey)
+            {
+                IntPtr errorPtr;
+                solana_sign_transaction_externally(_transactionPtr, pubkey, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            // New method for multi-signature
+            public void SignWithKeypairs(byte[][] privateKeys)
+            {
+                IntPtr errorPtr;
+
+                IntPtr[] keyPtrs = new IntPtr[privateKeys.Length];
+                IntPtr[] keyLengths = new IntPtr[privateKeys.Length];
+
+                for (int i = 0; i < privateKeys.Length; i++)
+                {
+                    byte[] key = privateKeys[i];
+                    IntPtr keyPtr = Marshal.AllocHGlobal(key.Length);
+                    Marshal.Copy(key, 0, keyPtr, key.Length);
+                    keyPtrs[i] = keyPtr;
+                    keyLengths[i] = (IntPtr)key.Length;
+                }
+
+                int result = solana_sign_transaction_with_keypairs(
+                    _transactionPtr,
+                    keyPtrs,
+                    keyLengths,
+                    privateKeys.Length,
+                    out errorPtr);
+
+                // Free allocated memory
+                for (int i = 0; i < keyPtrs.Length; i++)
+                {
+                    Marshal.FreeHGlobal(keyPtrs[i]);
+                }
+
+                CheckError(errorPtr);
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to sign transaction with multiple keypairs");
+                }
+            }
+
+            // Failures throw SolanaErrorException, with the program logs of a failed
+            // preflight simulation in LogsJson
+            public string Send()
+            {
+                IntPtr errorObj;
+                IntPtr signaturePtr = solana_send_transaction_e(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    out errorObj);
+
+                CheckErrorObject(errorObj);
+                return PtrToStringAndFree(signaturePtr);
+            }
+
+            // Like Send, writing the signature into buffer as UTF-8 and returning its length.
+            // The buffer is checked first, so a transaction is never sent without room for
+            // its signature; 89 bytes always fit.
+            public int SendInto(byte[] buffer)
+            {
+                IntPtr errorPtr;
+                UIntPtr written;
+                solana_send_transaction_buf(
+                    _client._clientPtr, _transactionPtr, buffer, (UIntPtr)buffer.Length, out written, out errorPtr);
+                CheckError(errorPtr);
+                return (int)written.ToUInt32() - 1;
+            }
+
+            public string Send(ulong timeoutMs)
+            {
+                IntPtr errorPtr;
+                IntPtr signaturePtr = solana_send_transaction_t(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    timeoutMs,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                return PtrToStringAndFree(signaturePtr);
+            }
+
+            // Signs with the account, sends and waits for confirmation. If the blockhash
+            // expired meanwhile, e.g. while a player was confirming, the transaction is
+            // rebuilt with a fresh one and re-signed, up to maxRebuilds times.
+            public string SendWithRefresh(Account signer, byte maxRebuilds, ulong timeoutMs)
+            {
+                IntPtr errorPtr;
+                IntPtr signaturePtr = solana_send_with_refresh(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    signer.Handle,
+                    maxRebuilds,
+                    timeoutMs,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                return PtrToStringAndFree(signaturePtr);
+            }
+
+            // Sends the signed transaction and waits for confirmation. slot is where it
+            // landed; pass it to the AtSlot reads to see its effects.
+            public string SendAndConfirm(ulong timeoutMs, out ulong slot)
+            {
+                IntPtr errorPtr;
+                IntPtr signaturePtr = solana_send_and_confirm_transaction(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    timeoutMs,
+                    out slot,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                return PtrToStringAndFree(signaturePtr);
+            }
+
+            // Queues the send and returns a request id for SolanaClient.PollRequest
+            public ulong RequestSend()
+            {
+                IntPtr errorPtr;
+                ulong requestId = solana_request_send_transaction(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                return requestId;
+            }
+
+            // Returns the signed or unsigned transaction in wire format
+            public byte[] Serialize()
+            {
+                IntPtr errorPtr;
+                SolanaBuffer buffer = solana_serialize_transaction_buffer(_transactionPtr, out errorPtr);
+                CheckError(errorPtr);
+                return BufferToBytesAndFree(buffer);
+            }
+
+            // New method for transaction simulation
+            public string Simulate()
+            {
+                IntPtr errorPtr;
+                IntPtr resultPtr = solana_simulate_transaction(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                return PtrToStringAndFree(resultPtr);
+            }
+
+            // The exact fee in lamports the cluster would charge, priority fee included.
+            // Needs a built transaction with a recent blockhash.
+            public ulong GetFee()
+            {
+                IntPtr errorPtr;
+                ulong fee = solana_get_fee_for_transaction(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                return fee;
+            }
+
+            // The fee, priority fee and rent of created accounts as JSON:
+            // {"fee_lamports", "priority_fee_lamports", "rent_lamports", "total_lamports", "breakdown"}
+            public string EstimateTotalCost()
+            {
+                IntPtr errorPtr;
+                IntPtr costPtr = solana_estimate_transaction_cost(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+                return PtrToStringAndFree(costPtr);
+            }
+
+            public SimulationResult SimulateDetailed()
+            {
+                IntPtr errorPtr;
+                IntPtr errPtr;
+                IntPtr logsPtr;
+                UIntPtr logsCount;
+                ulong unitsConsumed;
+                int result = solana_simulate_transaction_detailed(
+                    _client._clientPtr,
+                    _transactionPtr,
+                    out errPtr,
+                    out logsPtr,
+                    out logsCount,
+                    out unitsConsumed,
+                    out errorPtr);
+
+                CheckError(errorPtr);
+
+                int count = (int)logsCount.ToUInt32();
+                string[] logs = new string[count];
+                for (int i = 0; i < count; i++)
+                {
+                    IntPtr linePtr = Marshal.ReadIntPtr(logsPtr, i * IntPtr.Size);
+                    logs[i] = Marshal.PtrToStringAnsi(linePtr);
+                }
+                solana_free_string_array(logsPtr, logsCount);
+
+                return new SimulationResult
+                {
+                    Succeeded = result != 0,
+                    Error = PtrToStringAndFree(errPtr),
+                    Logs = logs,
+                    UnitsConsumed = unitsConsumed,
+                };
+            }
+
+            // New method to build with instructions
+            public void BuildWithInstructions(Instruction[] instructions, string feePayer, string recentBlockhash)
+            {
+                if (instructions == null || instructions.Length == 0)
+                {
+                    throw new SolanaException("No instructions provided for transaction");
+                }
+
+                IntPtr errorPtr;
+
+                // Each instruction is passed in its own buffer, so the native side can
+                // report which one failed to decode
+                IntPtr[] dataPtrs = new IntPtr[instructions.Length];
+                UIntPtr[] dataLens = new UIntPtr[instructions.Length];
+                try
+                {
+                    for (int i = 0; i < instructions.Length; i++)
+                    {
+                        byte[] data = instructions[i].GetEncodedData();
+                        dataPtrs[i] = Marshal.AllocHGlobal(data.Length);
+                        Marshal.Copy(data, 0, dataPtrs[i], data.Length);
+                        dataLens[i] = (UIntPtr)data.Length;
+                    }
+
+                    int result = solana_build_with_instruction_list(
+                        _transactionPtr,
+                        dataPtrs,
+                        dataLens,
+                        (UIntPtr)instructions.Length,
+                        feePayer,
+                        recentBlockhash,
+                        out errorPtr);
+
+                    CheckError(errorPtr);
+
+                    if (result == 0)
+                    {
+                        throw new SolanaException("Failed to build transaction with instructions");
+                    }
+                }
+                finally
+                {
+                    foreach (IntPtr dataPtr in dataPtrs)
+                    {
+                        if (dataPtr != IntPtr.Zero)
+                        {
+                            Marshal.FreeHGlobal(dataPtr);
+                        }
+                    }
+                }
+            }
+
+            // Names instruction `index` of the built transaction. If it fails, the error's
+            // context.failedInstruction carries the label next to the index and program id.
+            public void SetInstructionLabel(int index, string label)
+            {
+                IntPtr errorPtr;
+                int result = solana_transaction_set_instruction_label(
+                    _transactionPtr, (UIntPtr)index, label, out errorPtr);
+                CheckError(errorPtr);
+
+                if (result == 0)
+                {
+                    throw new SolanaException("Failed to set instruction label");
+                }
+            }
+
+            internal IntPtr Handle => _transactionPtr;
+        }
+
+        // Account class wrapper
+        public class Account : IDisposable
+        {
+            private IntPtr _accountPtr;
+            private bool _disposed = false;
+
+            // Generate a new random account
+            public Account()
+            {
+                _accountPtr = solana_account_generate();
+            }
+
+            // Create from public key (read-only)
+            public Account(string pubkey)
+            {
+                IntPtr errorPtr;
+                _accountPtr = solana_account_from_pubkey(pubkey, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            // Create from private key
+            public Account(byte[] privateKey)
+            {
+                IntPtr errorPtr;
+                _accountPtr = solana_account_from_private_key(privateKey, privateKey.Length, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+#if UNITY_SOLANA_BIP39
+            // Create from mnemonic (when BIP39 feature is enabled)
+            public Account(string mnemonic, string passphrase = "", string derivationPath = "")
+            {
+                IntPtr errorPtr;
+                _accountPtr = solana_account_from_mnemonic(mnemonic, passphrase, derivationPath, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            public enum DerivationScheme
+            {
+                // m/44'/501'/{index}'/0', used by Phantom, Solflare and solana-keygen
+                Standard = 0,
+                // m/44'/501'/{index}', used by some older wallets
+                Short = 1
+            }
+
+            // The account at accountIndex of a mnemonic, as a wallet lists them
+            public static Account FromMnemonic(string mnemonic, uint accountIndex,
+                DerivationScheme scheme = DerivationScheme.Standard, string passphrase = "")
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_mnemonic_index(
+                    mnemonic, passphrase, accountIndex, (int)scheme, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+#endif
+
+            internal Account(IntPtr accountPtr)
+            {
+                _accountPtr = accountPtr;
+            }
+
+            // Load a solana-keygen JSON keypair file, e.g. a service keypair on a dedicated server
+            public static Account FromKeypairFile(string path)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_keypair_file(path, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+
+            // Write the keypair as a solana-keygen JSON file; an existing file is only replaced when overwrite is set
+            public void WriteKeypairFile(string path, bool overwrite = false)
+            {
+                IntPtr errorPtr;
+                solana_account_write_keypair_file(_accountPtr, path, overwrite ? 1 : 0, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            // Load an Ed25519 key from PKCS#8 PEM, as written by openssl or exported from a KMS
+            public static Account FromPkcs8Pem(string pem)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_pkcs8_pem(pem, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+
+            // The keypair as PKCS#8 PEM, readable by openssl
+            public string ToPkcs8Pem()
+            {
+                IntPtr errorPtr;
+                IntPtr pemPtr = solana_account_to_pkcs8_pem(_accountPtr, out errorPtr);
+                CheckError(errorPtr);
+                string pem = Marshal.PtrToStringAnsi(pemPtr);
+                solana_secure_free_string(pemPtr);
+                return pem;
+            }
+
+            // Split the key into `shares` backup shares, any `threshold` of which restore it,
+            // e.g. 2 of 3: one kept by the player, one by a friend, one in cloud storage
+            public string[] SplitPrivateKey(byte threshold, byte shares)
+            {
+                IntPtr errorPtr;
+                SolanaStringArray array = solana_account_split_key(_accountPtr, threshold, shares, out errorPtr);
+                CheckError(errorPtr);
+
+                int count = (int)array.len.ToUInt32();
+                string[] result = new string[count];
+                for (int i = 0; i < count; i++)
+                {
+                    result[i] = Marshal.PtrToStringAnsi(Marshal.ReadIntPtr(array.items, i * IntPtr.Size));
+                }
+                solana_string_array_secure_free(array);
+                return result;
+            }
+
+            // Restore an account from at least the threshold of shares from SplitPrivateKey
+            public static Account FromShares(string[] shares)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_shares(shares, (UIntPtr)shares.Length, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+
+            // Create from solana-keygen JSON, an array of the 64 keypair bytes
+            public static Account FromKeypairJson(string json)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_keypair_json(json, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+
+            // The keypair as solana-keygen JSON. It holds the private key, so do not log it.
+            public string ToKeypairJson()
+            {
+                IntPtr errorPtr;
+                IntPtr jsonPtr = solana_account_to_keypair_json(_accountPtr, out errorPtr);
+                CheckError(errorPtr);
+                string json = Marshal.PtrToStringAnsi(jsonPtr);
+                solana_secure_free_string(jsonPtr);
+                return json;
+            }
+
+            // Create from a base58 private key, as exported by Phantom and Solflare
+            public static Account FromBase58PrivateKey(string privateKey)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_base58_private_key(privateKey, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+
+            // The keypair as a base58 string that wallets can import. Do not log it.
+            public string GetPrivateKeyBase58()
+            {
+                IntPtr errorPtr;
+                IntPtr keyPtr = solana_account_get_private_key_base58(_accountPtr, out errorPtr);
+                CheckError(errorPtr);
+                string key = Marshal.PtrToStringAnsi(keyPtr);
+                solana_secure_free_string(keyPtr);
+                return key;
+            }
+
+            // Signs arbitrary bytes, e.g. a login challenge, and returns the 64-byte ed25519
+            // signature
+            public byte[] SignMessage(byte[] message)
+            {
+                IntPtr errorPtr;
+                byte[] signature = new byte[64];
+                solana_account_sign_message(_accountPtr, message, (UIntPtr)message.Length, signature, out errorPtr);
+                CheckError(errorPtr);
+                return signature;
+            }
+
+            // Whether signature is pubkey's signature of message. Throws for a malformed
+            // pubkey or a signature that is not 64 bytes.
+            public static bool VerifySignature(string pubkey, byte[] message, byte[] signature)
+            {
+                IntPtr errorPtr;
+                int valid;
+                solana_verify_signature(
+                    pubkey, message, (UIntPtr)message.Length, signature, (UIntPtr)signature.Length,
+                    out valid, out errorPtr);
+                CheckError(errorPtr);
+                return valid != 0;
+            }
+
+            // The keypair as a password-encrypted JSON keystore, safe to write to disk or
+            // PlayerPrefs
+            public string ExportEncrypted(string password)
+            {
+                IntPtr errorPtr;
+                IntPtr jsonPtr = solana_account_export_encrypted(_accountPtr, password, out errorPtr);
+                CheckError(errorPtr);
+                return PtrToStringAndFree(jsonPtr);
+            }
+
+            // Restore an account from an ExportEncrypted keystore; throws for a wrong password
+            public static Account FromEncrypted(string json, string password)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_encrypted(json, password, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+
+            // The raw 32-byte public key, e.g. for a PDA seed
+            public byte[] GetPublicKeyBytes()
+            {
+                IntPtr errorPtr;
+                byte[] pubkey = new byte[32];
+                solana_account_get_pubkey_bytes(_accountPtr, pubkey, out errorPtr);
+                CheckError(errorPtr);
+                return pubkey;
+            }
+
+            // Decodes a base58 public key into its 32 bytes
+            public static byte[] PubkeyToBytes(string pubkey)
+            {
+                IntPtr errorPtr;
+                byte[] bytes = new byte[32];
+                solana_pubkey_to_bytes(pubkey, bytes, out errorPtr);
+                CheckError(errorPtr);
+                return bytes;
+            }
+
+            // Cheap enough to call on every keystroke of an address field
+            public static bool IsValidPubkey(string pubkey)
+            {
+                return solana_is_valid_pubkey(pubkey) != 0;
+            }
+
+            // Throws with the reason the public key is invalid, e.g. to show under the field
+            public static void ValidatePubkey(string pubkey)
+            {
+                IntPtr errorPtr;
+                solana_validate_pubkey(pubkey, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            // Create from a 32-byte ed25519 seed, the first half of a keypair
+            public static Account FromSeed(byte[] seed)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_seed(seed, (UIntPtr)seed.Length, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+
+            // Signs the message inside Solana's off-chain message envelope, the format wallets
+            // use for signMessage, so the signature can never pass for a transaction
+            public byte[] SignOffchainMessage(byte[] message)
+            {
+                IntPtr errorPtr;
+                byte[] signature = new byte[64];
+                solana_account_sign_offchain_message(
+                    _accountPtr, message, (UIntPtr)message.Length, signature, out errorPtr);
+                CheckError(errorPtr);
+                return signature;
+            }
+
+            // Whether signature is pubkey's SignOffchainMessage signature of message
+            public static bool VerifyOffchainMessage(string pubkey, byte[] message, byte[] signature)
+            {
+                IntPtr errorPtr;
+                int valid;
+                solana_verify_offchain_message(
+                    pubkey, message, (UIntPtr)message.Length, signature, (UIntPtr)signature.Length,
+                    out valid, out errorPtr);
+                CheckError(errorPtr);
+                return valid != 0;
+            }
+
+            // Moves the keypair into memory locked against swapping, or back out of it.
+            // Returns whether the pages are actually locked: when the OS refuses, the key
+            // still lives on dedicated pages that are wiped when it is released.
+            public bool SetSecureMemory(bool enabled)
+            {
+                IntPtr errorPtr;
+                int locked;
+                solana_account_set_secure_memory(_accountPtr, enabled ? 1 : 0, out locked, out errorPtr);
+                CheckError(errorPtr);
+                return locked != 0;
+            }
+
+            public bool IsMemoryLocked()
+            {
+                return solana_account_is_memory_locked(_accountPtr) != 0;
+            }
+
+            // Create from a base64 encoded 64-byte keypair, without pinning a byte array
+            public static Account FromPrivateKeyBase64(string privateKeyBase64)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr = solana_account_from_private_key_b64(privateKeyBase64, out errorPtr);
+                CheckError(errorPtr);
+                return new Account(accountPtr);
+            }
+
+            ~Account()
+            {
+                Dispose(false);
+            }
+
+            public void Dispose()
+            {
+                Dispose(true);
+                GC.SuppressFinalize(this);
+            }
+
+            protected virtual void Dispose(bool disposing)
+            {
+                if (!_disposed)
+                {
+                    if (_accountPtr != IntPtr.Zero)
+                    {
+                        solana_destroy_account(_accountPtr);
+                        _accountPtr = IntPtr.Zero;
+                    }
+                    _disposed = true;
+                }
+            }
+
+            public string GetPublicKey()
+            {
+                // Base58 public keys are at most 44 characters plus the terminator
+                byte[] buffer = new byte[45];
+                IntPtr errorPtr;
+                UIntPtr written;
+                solana_account_get_pubkey_buf(_accountPtr, buffer, (UIntPtr)buffer.Length, out written, out errorPtr);
+                CheckError(errorPtr);
+                return Encoding.UTF8.GetString(buffer, 0, (int)written.ToUInt64() - 1);
+            }
+
+            // Like the bytes passed to solana_secure_free_bytes, the returned array holds the
+            // secret key: clear it with Array.Clear as soon as it has been used.
+            public byte[] GetPrivateKey()
+            {
+                IntPtr errorPtr;
+                UIntPtr written;
+                byte[] buffer = new byte[64]; // Ed25519 keypair is 64 bytes
+                int result = solana_account_get_private_key(
+                    _accountPtr, buffer, (UIntPtr)buffer.Length, out written, out errorPtr);
+                if (errorPtr != IntPtr.Zero || result == 0)
+                {
+                    Array.Clear(buffer, 0, buffer.Length);
+                }
+                CheckError(errorPtr);
+
+                if (result == 0)
+                {
+                    throw new SolanaException("No private key available");
+                }
+
+                int length = (int)written.ToUInt32();
+                if (length == buffer.Length)
                 {
-                    solana_destroy_rpc_client(_clientPtr);
-                    _clientPtr = IntPtr.Zero;
+                    return buffer;
                 }
-                _disposed = true;
+
+                byte[] privateKey = new byte[length];
+                Array.Copy(buffer, privateKey, length);
+                Array.Clear(buffer, 0, buffer.Length);
+                return privateKey;
             }
-        }
 
-        // API Methods
-        public ulong GetBalance(string pubkey)
-        {
-            IntPtr errorPtr;
-            ulong balance = solana_get_balance(_clientPtr, pubkey, out errorPtr);
-            CheckError(errorPtr);
-            return balance;
-        }
+            public bool HasPrivateKey()
+            {
+                IntPtr errorPtr;
+                int result = solana_account_has_private_key(_accountPtr, out errorPtr);
+                CheckError(errorPtr);
+                return result != 0;
+            }
 
-        public string GetLatestBlockhash()
-        {
-            IntPtr errorPtr;
-            IntPtr blockhashPtr = solana_get_latest_blockhash(_clientPtr, out errorPtr);
-            CheckError(errorPtr);
-            return PtrToStringAndFree(blockhashPtr);
+            internal IntPtr Handle => _accountPtr;
         }
 
-        public ulong GetTokenAccountBalance(string tokenAccount)
+        public enum SubscriptionState
         {
-            IntPtr errorPtr;
-            ulong balance = solana_get_token_account_balance(_clientPtr, tokenAccount, out errorPtr);
-            CheckError(errorPtr);
-            return balance;
+            Closed = 0,
+            Active = 1,
+            Reconnecting = 2,
         }
 
-        public string GetAccountInfo(string pubkey)
+        // Subscription wrapper, for the handles the solana_subscribe_* functions return.
+        // Every kind of subscription is polled the same way: each event is JSON with a "type"
+        // of "notification", "subscriptionError" or "connectionState" to dispatch on.
+        // Disposing unsubscribes on the node and discards events still queued.
+        public class Subscription : IDisposable
         {
-            IntPtr errorPtr;
-            IntPtr infoPtr = solana_get_account_info(_clientPtr, pubkey, out errorPtr);
-            CheckError(errorPtr);
-            return PtrToStringAndFree(infoPtr);
-        }
+            private IntPtr _subscriptionPtr;
+            private bool _disposed = false;
 
-        public string GetProgramAccounts(string programId)
-        {
-            IntPtr errorPtr;
-            IntPtr accountsPtr = solana_get_program_accounts(_clientPtr, programId, out errorPtr);
-            CheckError(errorPtr);
-            return PtrToStringAndFree(accountsPtr);
-        }
+            internal Subscription(IntPtr subscriptionPtr)
+            {
+                _subscriptionPtr = subscriptionPtr;
+            }
 
-        public string GetTransactionStatus(string signature)
-        {
-            IntPtr errorPtr;
-            IntPtr statusPtr = solana_get_transaction_status(_clientPtr, signature, out errorPtr);
-            CheckError(errorPtr);
-            return PtrToStringAndFree(statusPtr);
+            ~Subscription()
+            {
+                Dispose(false);
+            }
+
+            public void Dispose()
+            {
+                Dispose(true);
+                GC.SuppressFinalize(this);
+            }
+
+            protected virtual void Dispose(bool disposing)
+            {
+                if (!_disposed)
+                {
+                    if (_subscriptionPtr != IntPtr.Zero)
+                    {
+                        solana_subscription_destroy(_subscriptionPtr);
+                        _subscriptionPtr = IntPtr.Zero;
+                    }
+                    _disposed = true;
+                }
+            }
+
+            // The oldest queued event as JSON, or null when there is none
+            public string PollNext()
+            {
+                IntPtr errorPtr;
+                IntPtr eventPtr = solana_subscription_poll_next(_subscriptionPtr, out errorPtr);
+                CheckError(errorPtr);
+                return PtrToStringAndFree(eventPtr);
+            }
+
+            public int PendingCount => (int)solana_subscription_pending_count(_subscriptionPtr).ToUInt32();
+
+            public SubscriptionState State
+            {
+                get
+                {
+                    int state = solana_subscription_state(_subscriptionPtr);
+                    if (state < 0)
+                    {
+                        throw new ObjectDisposedException(nameof(Subscription));
+                    }
+                    return (SubscriptionState)state;
+                }
+            }
         }
 
-        public bool ConfirmTransaction(string signature)
+        public enum BalanceKind
         {
-            IntPtr errorPtr;
-            int result = solana_confirm_transaction(_clientPtr, signature, out errorPtr);
-            CheckError(errorPtr);
-            return result != 0;
+            Sol = 0,
+            TokenAccount = 1,
         }
 
-        // PDA methods
-        public (string address, byte bump) FindProgramAddress(string[] seeds, string programId)
+        // Polls the balances of watched addresses on a native background thread. Each change
+        // is queued as JSON: {"pubkey", "kind", "old", "new", "slot"}, with "old" null on the
+        // first reading and "new" null once the account no longer exists.
+        public class BalanceWatcher : IDisposable
         {
-            IntPtr errorPtr;
-            IntPtr addressPtr;
-            byte bump;
+            private IntPtr _watcherPtr;
+            private bool _disposed = false;
 
-            IntPtr[] seedsPtr = new IntPtr[seeds.Length];
-            for (int i = 0; i < seeds.Length; i++)
+            public BalanceWatcher()
             {
-                seedsPtr[i] = Marshal.StringToHGlobalAnsi(seeds[i]);
+                _watcherPtr = solana_balance_watcher_create();
             }
 
-            int result = solana_find_program_address(seedsPtr, seeds.Length, programId, out addressPtr, out bump, out errorPtr);
+            ~BalanceWatcher()
+            {
+                Dispose(false);
+            }
 
-            // Free the allocated memory
-            foreach (IntPtr ptr in seedsPtr)
+            public void Dispose()
             {
-                Marshal.FreeHGlobal(ptr);
+                Dispose(true);
+                GC.SuppressFinalize(this);
             }
 
-            CheckError(errorPtr);
+            protected virtual void Dispose(bool disposing)
+            {
+                if (!_disposed)
+                {
+                    if (_watcherPtr != IntPtr.Zero)
+                    {
+                        solana_balance_watcher_destroy(_watcherPtr);
+                        _watcherPtr = IntPtr.Zero;
+                    }
+                    _disposed = true;
+                }
+            }
 
-            if (result == 0)
+            public void Add(string pubkey, BalanceKind kind = BalanceKind.Sol)
             {
-                throw new SolanaException("Failed to find program address");
+                IntPtr errorPtr;
+                solana_balance_watcher_add(_watcherPtr, pubkey, (int)kind, out errorPtr);
+                CheckError(errorPtr);
             }
 
-            string address = PtrToStringAndFree(addressPtr);
-            return (address, bump);
+            // Returns false if the address was not watched
+            public bool Remove(string pubkey)
+            {
+                IntPtr errorPtr;
+                int removed = solana_balance_watcher_remove(_watcherPtr, pubkey, out errorPtr);
+                CheckError(errorPtr);
+                return removed != 0;
+            }
+
+            // The watcher keeps its own reference to the client's connection, so the client
+            // may be disposed while the watcher runs
+            public void Start(SolanaClient client, ulong pollIntervalMs = 5000)
+            {
+                IntPtr errorPtr;
+                solana_balance_watcher_start(_watcherPtr, client._clientPtr, pollIntervalMs, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            public void Stop()
+            {
+                solana_balance_watcher_stop(_watcherPtr);
+            }
+
+            // The oldest queued change as JSON, or null when there is none
+            public string PollEvent()
+            {
+                IntPtr errorPtr;
+                IntPtr eventPtr = solana_balance_watcher_poll_event(_watcherPtr, out errorPtr);
+                CheckError(errorPtr);
+                return PtrToStringAndFree(eventPtr);
+            }
         }
 
-        public string FindAssociatedTokenAddress(string walletAddress, string tokenMint)
+        // Friends' addresses under nicknames. Labels are unique ignoring case and an address
+        // can only be saved under one label. Entries are JSON: {"label", "pubkey", "memo"}.
+        public class AddressBook : IDisposable
         {
-            IntPtr errorPtr;
-            IntPtr addressPtr;
+            private IntPtr _bookPtr;
+            private bool _disposed = false;
 
-            int result = solana_find_associated_token_address(walletAddress, tokenMint, out addressPtr, out errorPtr);
-            CheckError(errorPtr);
+            public AddressBook()
+            {
+                _bookPtr = solana_address_book_create();
+            }
 
-            if (result == 0)
+            private AddressBook(IntPtr bookPtr)
             {
-                throw new SolanaException("Failed to find associated token address");
+                _bookPtr = bookPtr;
             }
 
-            return PtrToStringAndFree(addressPtr);
+            // A corrupted file throws with the line and column, and is left as it is
+            public static AddressBook Load(string path)
+            {
+                IntPtr errorPtr;
+                IntPtr bookPtr = solana_address_book_load(path, out errorPtr);
+                CheckError(errorPtr);
+                return new AddressBook(bookPtr);
+            }
+
+            ~AddressBook()
+            {
+                Dispose(false);
+            }
+
+            public void Dispose()
+            {
+                Dispose(true);
+                GC.SuppressFinalize(this);
+            }
+
+            protected virtual void Dispose(bool disposing)
+            {
+                if (!_disposed)
+                {
+                    if (_bookPtr != IntPtr.Zero)
+                    {
+                        solana_address_book_destroy(_bookPtr);
+                        _bookPtr = IntPtr.Zero;
+                    }
+                    _disposed = true;
+                }
+            }
+
+            // Replaces the previous file only once the new one is fully written
+            public void Save(string path)
+            {
+                IntPtr errorPtr;
+                solana_address_book_save(_bookPtr, path, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            // Throws if the label is taken, unless `replace` is set
+            public void Add(string label, string pubkey, string memo = null, bool replace = false)
+            {
+                IntPtr errorPtr;
+                solana_address_book_add(_bookPtr, label, pubkey, memo, replace ? 1 : 0, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            // Returns false if nothing was saved under the label
+            public bool Remove(string label)
+            {
+                IntPtr errorPtr;
+                int removed = solana_address_book_remove(_bookPtr, label, out errorPtr);
+                CheckError(errorPtr);
+                return removed != 0;
+            }
+
+            // The entry saved under the label as JSON, or null
+            public string Lookup(string label)
+            {
+                IntPtr errorPtr;
+                IntPtr entryPtr = solana_address_book_lookup(_bookPtr, label, out errorPtr);
+                CheckError(errorPtr);
+                return PtrToStringAndFree(entryPtr);
+            }
+
+            // The entry saving the address as JSON, or null
+            public string ReverseLookup(string pubkey)
+            {
+                IntPtr errorPtr;
+                IntPtr entryPtr = solana_address_book_reverse_lookup(_bookPtr, pubkey, out errorPtr);
+                CheckError(errorPtr);
+                return PtrToStringAndFree(entryPtr);
+            }
+
+            // Every entry as a JSON array, in the order they were added
+            public string ListJson()
+            {
+                IntPtr errorPtr;
+                IntPtr listPtr = solana_address_book_list_json(_bookPtr, out errorPtr);
+                CheckError(errorPtr);
+                return PtrToStringAndFree(listPtr);
+            }
         }
 
-        // New method for multiple accounts
-        public string GetMultipleAccounts(string[] pubkeys)
+        // Background search for an account whose address starts with a prefix, e.g. a
+        // player's initials. Each extra character makes the search about 58 times longer.
+        public class VanitySearch : IDisposable
         {
-            // TODO: Implement this method once the FFI function is updated to handle string arrays
-            throw new NotImplementedException("GetMultipleAccounts is not yet implemented");
+            private IntPtr _handlePtr;
+            private bool _disposed = false;
+
+            // The search gives up after maxAttempts keypairs; threads 0 uses every core
+            public VanitySearch(string prefix, bool caseInsensitive, ulong maxAttempts, int threads = 0)
+            {
+                IntPtr errorPtr;
+                _handlePtr = solana_grind_start(
+                    prefix, caseInsensitive ? 1 : 0, maxAttempts, (UIntPtr)threads, out errorPtr);
+                CheckError(errorPtr);
+            }
+
+            ~VanitySearch()
+            {
+                Dispose(false);
+            }
+
+            public void Dispose()
+            {
+                Dispose(true);
+                GC.SuppressFinalize(this);
+            }
+
+            protected virtual void Dispose(bool disposing)
+            {
+                if (!_disposed)
+                {
+                    if (_handlePtr != IntPtr.Zero)
+                    {
+                        solana_grind_destroy(_handlePtr);
+                        _handlePtr = IntPtr.Zero;
+                    }
+                    _disposed = true;
+                }
+            }
+
+            // Returns the matching account once found and null while the search runs; poll it
+            // from a coroutine. Throws once the search was cancelled or ran out of attempts.
+            public Account Poll(out ulong attempts)
+            {
+                IntPtr errorPtr;
+                IntPtr accountPtr;
+                int result = solana_grind_poll(_handlePtr, out attempts, out accountPtr, out errorPtr);
+                CheckError(errorPtr);
+                return result == 1 ? new Account(accountPtr) : null;
+            }
+
+            // Stops the worker threads; the next Poll throws
+            public void Cancel()
+            {
+                solana_grind_cancel(_handlePtr);
+            }
         }
 
-        // Transaction class wrapper
-        public class Transaction : IDisposable
+        // Several accounts under labels, e.g. a session wallet, the player's watch-only main
+        // wallet and a sponsor wallet. Entries are JSON: {"label", "pubkey", "watch_only"}.
+        public class WalletManager : IDisposable
         {
-            private IntPtr _transactionPtr;
+            private IntPtr _managerPtr;
             private bool _disposed = false;
-            private SolanaClient _client;
 
-            public Transaction(SolanaClient client)
-            {
-                _client = client;
-                _transactionPtr = solana_create_transaction();
+            public WalletManager()
+            {
+                _managerPtr = solana_wallet_manager_create();
             }
 
-            ~Transaction()
+            ~WalletManager()
             {
                 Dispose(false);
             }
@@ -421,226 +3616,176 @@ namespace SolanaUnity
             {
                 if (!_disposed)
                 {
-                    if (_transactionPtr != IntPtr.Zero)
+                    if (_managerPtr != IntPtr.Zero)
                     {
-                        solana_destroy_transaction(_transactionPtr);
-                        _transactionPtr = IntPtr.Zero;
+                        solana_wallet_manager_destroy(_managerPtr);
+                        _managerPtr = IntPtr.Zero;
                     }
                     _disposed = true;
                 }
             }
 
-            public void BuildTransfer(string fromPubkey, string toPubkey, ulong lamports, string recentBlockhash)
+            // Stores a copy of the account, so the caller still disposes its own. Labels must
+            // be unique and non-empty.
+            public void Add(string label, Account account)
             {
                 IntPtr errorPtr;
-                int result = solana_build_transfer(
-                    _transactionPtr,
-                    fromPubkey,
-                    toPubkey,
-                    lamports,
-                    recentBlockhash,
-                    out errorPtr);
-
+                solana_wallet_manager_add_account(_managerPtr, label, account.Handle, out errorPtr);
                 CheckError(errorPtr);
-                if (result == 0)
-                {
-                    throw new SolanaException("Failed to build transfer transaction");
-                }
             }
 
-            public void BuildTokenTransfer(
-                string tokenProgramId,
-                string sourcePubkey,
-                string destinationPubkey,
-                string ownerPubkey,
-                ulong amount,
-                string recentBlockhash)
+            public void Remove(string label)
             {
                 IntPtr errorPtr;
-                int result = solana_build_token_transfer(
-                    _transactionPtr,
-                    tokenProgramId,
-                    sourcePubkey,
-                    destinationPubkey,
-                    ownerPubkey,
-                    amount,
-                    recentBlockhash,
-                    out errorPtr);
-
+                solana_wallet_manager_remove_account(_managerPtr, label, out errorPtr);
                 CheckError(errorPtr);
-                if (result == 0)
-                {
-                    throw new SolanaException("Failed to build token transfer transaction");
-                }
             }
 
-            public void Sign(byte[] privateKey)
+            public string GetPublicKey(string label)
             {
                 IntPtr errorPtr;
-                int result = solana_sign_transaction(
-                    _transactionPtr,
-                    privateKey,
-                    privateKey.Length,
-                    out errorPtr);
-
+                IntPtr pubkeyPtr = solana_wallet_manager_get_pubkey(_managerPtr, label, out errorPtr);
                 CheckError(errorPtr);
-                if (result == 0)
-                {
-                    throw new SolanaException("Failed to sign transaction");
-                }
+                return PtrToStringAndFree(pubkeyPtr);
             }
 
-            // New method for multi-signature
-            public void SignWithKeypairs(byte[][] privateKeys)
+            // Never includes private keys
+            public string ListJson()
             {
                 IntPtr errorPtr;
-
-                IntPtr[] keyPtrs = new IntPtr[privateKeys.Length];
-                IntPtr[] keyLengths = new IntPtr[privateKeys.Length];
-
-                for (int i = 0; i < privateKeys.Length; i++)
-                {
-                    byte[] key = privateKeys[i];
-                    IntPtr keyPtr = Marshal.AllocHGlobal(key.Length);
-                    Marshal.Copy(key, 0, keyPtr, key.Length);
-                    keyPtrs[i] = keyPtr;
-                    keyLengths[i] = (IntPtr)key.Length;
-                }
-
-                int result = solana_sign_transaction_with_keypairs(
-                    _transactionPtr,
-                    keyPtrs,
-                    keyLengths,
-                    privateKeys.Length,
-                    out errorPtr);
-
-                // Free allocated memory
-                for (int i = 0; i < keyPtrs.Length; i++)
-                {
-                    Marshal.FreeHGlobal(keyPtrs[i]);
-                }
-
+                IntPtr listPtr = solana_wallet_manager_list_json(_managerPtr, out errorPtr);
                 CheckError(errorPtr);
-                if (result == 0)
-                {
-                    throw new SolanaException("Failed to sign transaction with multiple keypairs");
-                }
+                return PtrToStringAndFree(listPtr);
             }
 
-            public string Send()
+            // Throws if the wallet is watch-only
+            public void SignTransaction(string label, Transaction transaction)
             {
                 IntPtr errorPtr;
-                IntPtr signaturePtr = solana_send_transaction(
-                    _client._clientPtr,
-                    _transactionPtr,
-                    out errorPtr);
-
+                solana_wallet_manager_sign_transaction(_managerPtr, label, transaction.Handle, out errorPtr);
                 CheckError(errorPtr);
-                return PtrToStringAndFree(signaturePtr);
             }
+        }
 
-            // New method for transaction simulation
-            public string Simulate()
-            {
-                IntPtr errorPtr;
-                IntPtr resultPtr = solana_simulate_transaction(
-                    _client._clientPtr,
-                    _transactionPtr,
-                    out errorPtr);
+        // Encryption for Phantom's deeplink protocol. Send PublicKey as
+        // dapp_encryption_public_key with the connect link, then pass the wallet's
+        // phantom_encryption_public_key to the first Decrypt; later payloads reuse the
+        // shared secret.
+        public class DeeplinkSession : IDisposable
+        {
+            private IntPtr _sessionPtr;
+            private bool _disposed = false;
 
-                CheckError(errorPtr);
-                return PtrToStringAndFree(resultPtr);
+            public DeeplinkSession()
+            {
+                _sessionPtr = solana_deeplink_session_create();
             }
 
-            // New method to build with instructions
-            public void BuildWithInstructions(Instruction[] instructions, string feePayer, string recentBlockhash)
+            ~DeeplinkSession()
             {
-                if (instructions == null || instructions.Length == 0)
-                {
-                    throw new SolanaException("No instructions provided for transaction");
-                }
+                Dispose(false);
+            }
 
-                IntPtr errorPtr;
+            public void Dispose()
+            {
+                Dispose(true);
+                GC.SuppressFinalize(this);
+            }
 
-                // Prepare the serialized instructions
-                using (var memoryStream = new System.IO.MemoryStream())
+            protected virtual void Dispose(bool disposing)
+            {
+                if (!_disposed)
                 {
-                    foreach (var instruction in instructions)
+                    if (_sessionPtr != IntPtr.Zero)
                     {
-                        var data = instruction.GetEncodedData();
-                        memoryStream.Write(data, 0, data.Length);
+                        solana_deeplink_session_destroy(_sessionPtr);
+                        _sessionPtr = IntPtr.Zero;
                     }
+                    _disposed = true;
+                }
+            }
 
-                    byte[] instructionsData = memoryStream.ToArray();
-
-                    IntPtr dataPtr = Marshal.AllocHGlobal(instructionsData.Length);
-                    try
-                    {
-                        Marshal.Copy(instructionsData, 0, dataPtr, instructionsData.Length);
-
-                        int result = solana_build_with_instructions(
-                            _transactionPtr,
-                            dataPtr,
-                            instructionsData.Length,
-                            instructions.Length,
-                            feePayer,
-                            recentBlockhash,
-                            out errorPtr);
+            // The base58 dapp encryption public key
+            public string PublicKey
+            {
+                get
+                {
+                    IntPtr errorPtr;
+                    IntPtr keyPtr = solana_deeplink_session_public_key(_sessionPtr, out errorPtr);
+                    CheckError(errorPtr);
+                    return PtrToStringAndFree(keyPtr);
+                }
+            }
 
-                        CheckError(errorPtr);
+            // Encrypts a JSON payload for the wallet; returns the base58 data and nonce
+            // parameters of the link
+            public string Encrypt(string payloadJson, out string nonce)
+            {
+                IntPtr errorPtr;
+                IntPtr noncePtr;
+                IntPtr dataPtr = solana_deeplink_encrypt(_sessionPtr, payloadJson, out noncePtr, out errorPtr);
+                CheckError(errorPtr);
+                nonce = PtrToStringAndFree(noncePtr);
+                return PtrToStringAndFree(dataPtr);
+            }
 
-                        if (result == 0)
-                        {
-                            throw new SolanaException("Failed to build transaction with instructions");
-                        }
-                    }
-                    finally
-                    {
-                        Marshal.FreeHGlobal(dataPtr);
-                    }
-                }
+            // Decrypts the data parameter of a wallet response. walletPublicKey is the
+            // phantom_encryption_public_key of the connect response, and "" afterwards.
+            public string Decrypt(string walletPublicKey, string nonce, string data)
+            {
+                IntPtr errorPtr;
+                IntPtr jsonPtr = solana_deeplink_decrypt(_sessionPtr, walletPublicKey, nonce, data, out errorPtr);
+                CheckError(errorPtr);
+                return PtrToStringAndFree(jsonPtr);
             }
         }
 
-        // Account class wrapper
-        public class Account : IDisposable
+        // An ephemeral keypair delegated a limited token allowance, so small actions are signed
+        // without prompting the player's wallet. The owner signs ApproveInstruction once and
+        // RevokeInstruction when the session ends.
+        public class SessionKey : IDisposable
         {
-            private IntPtr _accountPtr;
+            private IntPtr _sessionPtr;
             private bool _disposed = false;
 
-            // Generate a new random account
-            public Account()
-            {
-                _accountPtr = solana_account_generate();
-            }
+            // The approve instruction for the owner to sign
+            public Instruction ApproveInstruction { get; private set; }
 
-            // Create from public key (read-only)
-            public Account(string pubkey)
+            private SessionKey(IntPtr sessionPtr)
             {
-                IntPtr errorPtr;
-                _accountPtr = solana_account_from_pubkey(pubkey, out errorPtr);
-                CheckError(errorPtr);
+                _sessionPtr = sessionPtr;
             }
 
-            // Create from private key
-            public Account(byte[] privateKey)
+            // With a mint the approve is ApproveChecked against it and `decimals`.
+            // `expiresAt` is Unix seconds, or 0 for no expiry; it is only recorded.
+            public static SessionKey Create(string ownerTokenAccount, string owner, ulong allowance,
+                string mint = null, byte decimals = 0, long expiresAt = 0)
             {
                 IntPtr errorPtr;
-                _accountPtr = solana_account_from_private_key(privateKey, privateKey.Length, out errorPtr);
+                SolanaBuffer approve;
+                IntPtr sessionPtr = solana_session_create(
+                    ownerTokenAccount, owner, mint, decimals, allowance, expiresAt, out approve, out errorPtr);
                 CheckError(errorPtr);
+                return new SessionKey(sessionPtr)
+                {
+                    ApproveInstruction = new Instruction(BufferToBytesAndFree(approve)),
+                };
             }
 
-#if UNITY_SOLANA_BIP39
-            // Create from mnemonic (when BIP39 feature is enabled)
-            public Account(string mnemonic, string passphrase = "", string derivationPath = "")
+            // Restores a session saved with Serialize. A wrong password throws.
+            public static SessionKey Deserialize(string json, string password)
             {
                 IntPtr errorPtr;
-                _accountPtr = solana_account_from_mnemonic(mnemonic, passphrase, derivationPath, out errorPtr);
+                IntPtr sessionPtr = solana_session_deserialize(json, password, out errorPtr);
                 CheckError(errorPtr);
+                var session = new SessionKey(sessionPtr);
+                SolanaBuffer approve = solana_session_approve_instruction(sessionPtr, out errorPtr);
+                CheckError(errorPtr);
+                session.ApproveInstruction = new Instruction(BufferToBytesAndFree(approve));
+                return session;
             }
-#endif
 
-            ~Account()
+            ~SessionKey()
             {
                 Dispose(false);
             }
@@ -655,56 +3800,60 @@ namespace SolanaUnity
             {
                 if (!_disposed)
                 {
-                    if (_accountPtr != IntPtr.Zero)
+                    if (_sessionPtr != IntPtr.Zero)
                     {
-                        solana_destroy_account(_accountPtr);
-                        _accountPtr = IntPtr.Zero;
+                        solana_session_destroy(_sessionPtr);
+                        _sessionPtr = IntPtr.Zero;
                     }
                     _disposed = true;
                 }
             }
 
-            public string GetPublicKey()
+            // A copy of the session keypair, which signs as the delegate
+            public Account GetAccount()
             {
                 IntPtr errorPtr;
-                IntPtr pubkeyPtr = solana_account_get_pubkey(_accountPtr, out errorPtr);
+                IntPtr accountPtr = solana_session_get_account(_sessionPtr, out errorPtr);
                 CheckError(errorPtr);
-                return PtrToStringAndFree(pubkeyPtr);
+                return new Account(accountPtr);
             }
 
-            public byte[] GetPrivateKey()
+            public Instruction RevokeInstruction()
             {
                 IntPtr errorPtr;
-                IntPtr privateKeyPtr = solana_account_get_private_key(_accountPtr, out errorPtr);
+                SolanaBuffer revoke = solana_session_revoke_instruction(_sessionPtr, out errorPtr);
                 CheckError(errorPtr);
+                return new Instruction(BufferToBytesAndFree(revoke));
+            }
 
-                if (privateKeyPtr == IntPtr.Zero)
+            // Unix seconds, or 0 if the session has no expiry
+            public long ExpiresAt
+            {
+                get
                 {
-                    throw new SolanaException("No private key available");
+                    IntPtr errorPtr;
+                    long expiresAt = solana_session_expires_at(_sessionPtr, out errorPtr);
+                    CheckError(errorPtr);
+                    return expiresAt;
                 }
-
-                // Convert the native byte array to managed byte array
-                byte[] privateKey = new byte[64]; // Ed25519 keypair is 64 bytes
-                Marshal.Copy(privateKeyPtr, privateKey, 0, 64);
-                solana_free_string(privateKeyPtr);
-
-                return privateKey;
             }
 
-            public bool HasPrivateKey()
+            public bool IsExpired
             {
-                IntPtr errorPtr;
-                int result = solana_account_has_private_key(_accountPtr, out errorPtr);
-                CheckError(errorPtr);
-                return result != 0;
+                get
+                {
+                    long expiresAt = ExpiresAt;
+                    return expiresAt != 0 && DateTimeOffset.UtcNow.ToUnixTimeSeconds() >= expiresAt;
+                }
             }
 
-            public IntPtr GetKeypair()
+            // JSON with the session key encrypted under `password`, for PlayerPrefs or a file
+            public string Serialize(string password)
             {
                 IntPtr errorPtr;
-                IntPtr keypairPtr = solana_account_get_keypair(_accountPtr, out errorPtr);
+                IntPtr jsonPtr = solana_session_serialize(_sessionPtr, password, out errorPtr);
                 CheckError(errorPtr);
-                return keypairPtr;
+                return PtrToStringAndFree(jsonPtr);
             }
         }
 
@@ -723,13 +3872,99 @@ namespace SolanaUnity
             out IntPtr error
         );
 
+        // Variants writing the string into a caller-provided buffer, so there is nothing
+        // native to free. `written` receives the size needed including the terminator.
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_get_latest_blockhash_buf(
+            IntPtr client,
+            byte[] buf,
+            UIntPtr bufLen,
+            out UIntPtr written,
+            out IntPtr error
+        );
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_get_account_info_buf(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            byte[] buf,
+            UIntPtr bufLen,
+            out UIntPtr written,
+            out IntPtr error
+        );
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_get_transaction_status_buf(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string signature,
+            byte[] buf,
+            UIntPtr bufLen,
+            out UIntPtr written,
+            out IntPtr error
+        );
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern int solana_send_transaction_buf(
+            IntPtr client,
+            IntPtr transaction,
+            byte[] buf,
+            UIntPtr bufLen,
+            out UIntPtr written,
+            out IntPtr error
+        );
+
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
-        private static extern IntPtr solana_get_account_data(
+        private static extern int solana_read_account_data_into(
             IntPtr client,
             [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            UIntPtr offset,
+            byte[] buf,
+            UIntPtr bufLen,
+            out UIntPtr written,
             out IntPtr error
         );
 
+        // Mirrors the native SolanaBuffer; data is IntPtr.Zero on failure
+        [StructLayout(LayoutKind.Sequential)]
+        private struct SolanaBuffer
+        {
+            public IntPtr data;
+            public UIntPtr len;
+        }
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SolanaBuffer solana_serialize_transaction_buffer(
+            IntPtr transaction,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SolanaBuffer solana_get_account_data_buffer(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SolanaBuffer solana_get_account_data_at_slot(
+            IntPtr client,
+            [MarshalAs(UnmanagedType.LPStr)] string pubkey,
+            ulong minContextSlot,
+            out IntPtr error);
+
+        [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void solana_buffer_free(SolanaBuffer buffer);
+
+        // Copies a native buffer into managed memory and releases it
+        private static byte[] BufferToBytesAndFree(SolanaBuffer buffer)
+        {
+            if (buffer.data == IntPtr.Zero)
+                return null;
+
+            byte[] bytes = new byte[(int)buffer.len.ToUInt64()];
+            Marshal.Copy(buffer.data, bytes, 0, bytes.Length);
+            solana_buffer_free(buffer);
+            return bytes;
+        }
+
         [DllImport("solana_unity", CallingConvention = CallingConvention.Cdecl)]
         private static extern int solana_confirm_transaction(
             IntPtr client,
@@ -776,33 +4011,69 @@ namespace SolanaUnity
         }
 
         public byte[] GetAccountData(string pubkey)
+        {
+            if (_clientPtr == IntPtr.Zero)
+                throw new InvalidOperationException("Client not initialized");
+
+            IntPtr errorPtr;
+            SolanaBuffer buffer = solana_get_account_data_buffer(_clientPtr, pubkey, out errorPtr);
+            CheckError(errorPtr);
+            return BufferToBytesAndFree(buffer);
+        }
+
+        // Copies the account data from offset to the end into buffer without allocating, for
+        // accounts that are polled often. Returns the number of bytes copied; throws if the
+        // buffer is too small.
+        public int ReadAccountDataInto(string pubkey, int offset, byte[] buffer)
         {
             if (_clientPtr == IntPtr.Zero)
                 throw new InvalidOperationException("Client not initialized");
 
             IntPtr error;
-            IntPtr dataPtr = solana_get_account_data(_clientPtr, pubkey, out error);
+            UIntPtr written;
+            int result = solana_read_account_data_into(
+                _clientPtr, pubkey, (UIntPtr)offset, buffer, (UIntPtr)buffer.Length, out written, out error);
 
-            if (dataPtr == IntPtr.Zero)
+            if (result == 0)
             {
                 string errorMsg = Marshal.PtrToStringAnsi(error);
                 solana_free_string(error);
-                throw new SolanaException($"Failed to get account data: {errorMsg}");
+                throw new SolanaException($"Failed to read account data: {errorMsg}");
             }
 
-            // Get data length from first 8 bytes
-            byte[] lenBytes = new byte[8];
-            Marshal.Copy(dataPtr, lenBytes, 0, 8);
-            int dataLen = BitConverter.ToInt32(lenBytes, 0);
+            return (int)written.ToUInt32();
+        }
 
-            // Get actual data
-            byte[] data = new byte[dataLen];
-            Marshal.Copy(IntPtr.Add(dataPtr, 8), data, 0, dataLen);
+        // Like GetLatestBlockhash, writing the blockhash into buffer as UTF-8 and returning its
+        // length, for code that polls it every few frames. 45 bytes always fit. Throws if the
+        // buffer is too small; an empty buffer writes nothing and returns the length needed.
+        public int GetLatestBlockhashInto(byte[] buffer)
+        {
+            IntPtr errorPtr;
+            UIntPtr written;
+            solana_get_latest_blockhash_buf(_clientPtr, buffer, (UIntPtr)buffer.Length, out written, out errorPtr);
+            CheckError(errorPtr);
+            return (int)written.ToUInt32() - 1;
+        }
 
-            // Free native memory
-            Marshal.FreeHGlobal(dataPtr);
+        // Like GetAccountInfo, writing the JSON into buffer the same way
+        public int GetAccountInfoInto(string pubkey, byte[] buffer)
+        {
+            IntPtr errorPtr;
+            UIntPtr written;
+            solana_get_account_info_buf(_clientPtr, pubkey, buffer, (UIntPtr)buffer.Length, out written, out errorPtr);
+            CheckError(errorPtr);
+            return (int)written.ToUInt32() - 1;
+        }
 
-            return data;
+        // Like GetTransactionStatus, writing the status into buffer the same way
+        public int GetTransactionStatusInto(string signature, byte[] buffer)
+        {
+            IntPtr errorPtr;
+            UIntPtr written;
+            solana_get_transaction_status_buf(_clientPtr, signature, buffer, (UIntPtr)buffer.Length, out written, out errorPtr);
+            CheckError(errorPtr);
+            return (int)written.ToUInt32() - 1;
         }
 
         public bool ConfirmTransaction(string signature)
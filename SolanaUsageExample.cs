@@ -18,7 +18,8 @@ namespace SolanaUnity.Examples
 
         void Start()
         {
-            // Initialize the client
+            // Initialize the library and the client
+            SolanaClient.Initialize();
             _client = new SolanaClient(_rpcUrl, "confirmed");
 
             // Run a few examples
@@ -30,7 +30,8 @@ namespace TraderProgramClient
 
     private void Awake()
     {
-      // Initialize Solana client
+      // Initialize the library and the Solana client
+      SolanaClient.Initialize();
       _client = new SolanaClient("https://api.devnet.solana.com", "confirmed");
 
       // Load the trader program IDL from the JSON file
@@ -60,6 +61,20 @@ namespace TraderProgramClient
       Debug.Log($"Wallet set: {_wallet.GetPublicKey()}");
     }
 
+    // Sends a transaction signed by the wallet, then clears the copy of its private key
+    private string SendWithWallet(string instruction, Dictionary<string, object> args, List<AccountMeta> accounts)
+    {
+      byte[] privateKey = _wallet.GetPrivateKey();
+      try
+      {
+        return _programClient.SendTransaction(instruction, args, accounts, privateKey);
+      }
+      finally
+      {
+        Array.Clear(privateKey, 0, privateKey.Length);
+      }
+    }
+
     /// <summary>
     /// Initialize a new NPC for the game
     /// </summary>
@@ -104,12 +119,7 @@ namespace TraderProgramClient
         if (simulationResult.Contains("\"err\":null"))
         {
           // Send the transaction
-          string signature = _programClient.SendTransaction(
-              "initialize_npc",
-              args,
-              accounts,
-              _wallet.GetPrivateKey()
-          );
+          string signature = SendWithWallet("initialize_npc", args, accounts);
 
           onSuccess?.Invoke(signature);
           Debug.Log($"NPC initialized with signature: {signature}");
@@ -177,12 +187,7 @@ namespace TraderProgramClient
         if (simulationResult.Contains("\"err\":null"))
         {
           // Send the transaction
-          string signature = _programClient.SendTransaction(
-              "add_item",
-              args,
-              accounts,
-              _wallet.GetPrivateKey()
-          );
+          string signature = SendWithWallet("add_item", args, accounts);
 
           onSuccess?.Invoke(signature);
           Debug.Log($"Item added with signature: {signature}");
@@ -250,12 +255,7 @@ namespace TraderProgramClient
         if (simulationResult.Contains("\"err\":null"))
         {
           // Send the transaction
-          string signature = _programClient.SendTransaction(
-              "buy_item",
-              args,
-              accounts,
-              _wallet.GetPrivateKey()
-          );
+          string signature = SendWithWallet("buy_item", args, accounts);
 
           onSuccess?.Invoke(signature);
           Debug.Log($"Item purchased with signature: {signature}");
@@ -323,12 +323,7 @@ namespace TraderProgramClient
         if (simulationResult.Contains("\"err\":null"))
         {
           // Send the transaction
-          string signature = _programClient.SendTransaction(
-              "sell_item",
-              args,
-              accounts,
-              _wallet.GetPrivateKey()
-          );
+          string signature = SendWithWallet("sell_item", args, accounts);
 
           onSuccess?.Invoke(signature);
           Debug.Log($"Item sold with signature: {signature}");
@@ -0,0 +1,186 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Shared with the ffi_export macro so the manifest always matches the exported names
+#[path = "macros/src/symbol.rs"]
+mod symbol;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=macros/src/symbol.rs");
+    println!("cargo:rerun-if-env-changed={}", symbol::USER_PREFIX_ENV);
+    println!("cargo:rerun-if-env-changed=SOLANA_UNITY_SYMBOL_MANIFEST");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    // Reported by solana_get_version. The lock file is missing when the crate is built as a
+    // dependency of another workspace, in which case the versions are reported as unknown.
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    for (env_name, package) in [
+        ("SOLANA_UNITY_SDK_VERSION", "solana-sdk"),
+        ("SOLANA_UNITY_CLIENT_VERSION", "solana-client"),
+    ] {
+        let version = locked_version(&lock, package).unwrap_or_else(|| "unknown".to_string());
+        println!("cargo:rustc-env={}={}", env_name, version);
+    }
+    println!(
+        "cargo:rustc-env=SOLANA_UNITY_PROFILE={}",
+        env::var("PROFILE").unwrap_or_default()
+    );
+
+    let prefix = if env::var_os("CARGO_FEATURE_SYMBOL_PREFIX").is_some() {
+        env::var(symbol::USER_PREFIX_ENV).unwrap_or_else(|_| symbol::DEFAULT_PREFIX.to_string())
+    } else {
+        String::new()
+    };
+    if !symbol::is_valid_prefix(&prefix) {
+        panic!(
+            "{} must contain only ASCII letters, digits and underscores and not start with a digit, got {:?}",
+            symbol::USER_PREFIX_ENV,
+            prefix
+        );
+    }
+    // Read by the ffi_export macro while this crate compiles
+    println!("cargo:rustc-env={}={}", symbol::PREFIX_ENV, prefix);
+
+    let source = fs::read_to_string("src/ffi.rs").expect("failed to read src/ffi.rs");
+    let exported: Vec<String> = symbol::exported_functions(&source)
+        .into_iter()
+        .filter(|function| match &function.feature {
+            Some(feature) => {
+                let feature = feature.to_uppercase().replace('-', "_");
+                env::var_os(format!("CARGO_FEATURE_{}", feature)).is_some()
+            }
+            None => true,
+        })
+        .map(|function| symbol::exported_name(&prefix, &function.name))
+        .collect();
+    let symbols: Vec<String> = exported
+        .iter()
+        .map(|name| format!("\"{}\"", name))
+        .collect();
+
+    // Exported names are C identifiers, so they need no escaping
+    let manifest = format!(
+        "{{\n  \"prefix\": \"{}\",\n  \"symbols\": [\n    {}\n  ]\n}}\n",
+        prefix,
+        symbols.join(",\n    ")
+    );
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("symbols.json"), &manifest)
+        .expect("failed to write symbol manifest");
+    if let Ok(path) = env::var("SOLANA_UNITY_SYMBOL_MANIFEST") {
+        fs::write(&path, &manifest).expect("failed to write SOLANA_UNITY_SYMBOL_MANIFEST");
+    }
+
+    #[cfg(feature = "header")]
+    header::generate(&source, &prefix, &exported, Path::new(&out_dir));
+}
+
+// Version of `package` recorded in Cargo.lock. Takes the first entry if several versions
+// are locked, which only happens for crates this library does not depend on directly.
+fn locked_version(lock: &str, package: &str) -> Option<String> {
+    let name = format!("name = \"{}\"", package);
+    let mut lines = lock.lines();
+    lines.find(|line| *line == name)?;
+    lines
+        .next()?
+        .strip_prefix("version = \"")?
+        .strip_suffix('"')
+        .map(str::to_string)
+}
+
+#[cfg(feature = "header")]
+mod header {
+    use std::fs;
+    use std::path::Path;
+
+    // Modules defining the callback types that appear in FFI signatures
+    const CALLBACK_SOURCES: [&str; 3] = [
+        "src/external_signer.rs",
+        "src/logging.rs",
+        "src/allocator.rs",
+    ];
+
+    /// Writes the C header to OUT_DIR and include/solana_unity.h, and fails the build if any
+    /// exported function is missing from it. cbindgen silently skips functions it cannot
+    /// express in C, so the check is what keeps the header and the exports in step.
+    pub fn generate(source: &str, prefix: &str, exported: &[String], out_dir: &Path) {
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        for callbacks in CALLBACK_SOURCES {
+            println!("cargo:rerun-if-changed={}", callbacks);
+        }
+
+        // cbindgen does not expand proc macros, so it reads a copy of ffi.rs with
+        // #[ffi_export] spelled out as the attribute the macro expands to
+        let mut ffi = source.replace("#[ffi_export]", "#[no_mangle]");
+        if !prefix.is_empty() {
+            for declaration in ["pub extern \"C\" fn ", "pub unsafe extern \"C\" fn "] {
+                ffi = ffi.replace(
+                    &format!("{}solana_", declaration),
+                    &format!("{}{}solana_", declaration, prefix),
+                );
+            }
+        }
+        let src_dir = out_dir.join("header-src");
+        fs::create_dir_all(&src_dir).expect("failed to create header source directory");
+        fs::write(src_dir.join("ffi.rs"), ffi).expect("failed to write header source");
+
+        let handles = opaque_handles(source);
+        let typedefs: String = handles
+            .iter()
+            .map(|name| format!("\ntypedef struct {0} {0};", name))
+            .collect();
+
+        let config =
+            cbindgen::Config::from_file("cbindgen.toml").expect("failed to read cbindgen.toml");
+        let mut builder = cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(src_dir.join("ffi.rs"))
+            .with_after_include(typedefs);
+        for callbacks in CALLBACK_SOURCES {
+            builder = builder.with_src(callbacks);
+        }
+        for name in &handles {
+            builder = builder.exclude_item(name);
+        }
+        let bindings = builder.generate().expect("failed to generate C header");
+
+        let path = out_dir.join("solana_unity.h");
+        bindings.write_to_file(&path);
+        let header = fs::read_to_string(&path).expect("failed to read generated header");
+
+        let missing: Vec<&String> = exported
+            .iter()
+            .filter(|name| !declares(&header, name))
+            .collect();
+        if !missing.is_empty() {
+            panic!(
+                "exported functions missing from the C header, check that their signatures use FFI-safe types: {:?}",
+                missing
+            );
+        }
+
+        fs::create_dir_all("include").expect("failed to create include directory");
+        fs::write("include/solana_unity.h", &header)
+            .expect("failed to write include/solana_unity.h");
+    }
+
+    fn declares(header: &str, function: &str) -> bool {
+        header
+            .match_indices(&format!("{}(", function))
+            .any(|(at, _)| !header[..at].ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_'))
+    }
+
+    /// Names of the `pub type SolanaX = Handle<X>;` aliases, declared as opaque structs
+    fn opaque_handles(source: &str) -> Vec<String> {
+        source
+            .lines()
+            .filter_map(|line| line.strip_prefix("pub type "))
+            .filter(|rest| rest.contains("= Handle<"))
+            .filter_map(|rest| rest.split_whitespace().next())
+            .map(str::to_string)
+            .collect()
+    }
+}
@@ -0,0 +1,51 @@
+//! Attribute macros for the solana_unity FFI
+
+use proc_macro::{TokenStream, TokenTree};
+
+mod symbol;
+
+/// Exports an `extern "C"` function from the native library. By default this is the same
+/// as `#[no_mangle]`. With the `symbol-prefix` feature of solana_unity the function is
+/// exported as `<prefix><name>` instead, where the prefix comes from the build script.
+#[proc_macro_attribute]
+pub fn ffi_export(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return compile_error("#[ffi_export] takes no arguments");
+    }
+
+    let name = match function_name(&item) {
+        Some(name) => name,
+        None => return compile_error("#[ffi_export] can only be applied to a function"),
+    };
+
+    let prefix = std::env::var(symbol::PREFIX_ENV).unwrap_or_default();
+    let attribute = if prefix.is_empty() {
+        "#[no_mangle]".to_string()
+    } else {
+        format!(
+            "#[export_name = {:?}]",
+            symbol::exported_name(&prefix, &name)
+        )
+    };
+
+    let mut output: TokenStream = attribute.parse().unwrap();
+    output.extend(item);
+    output
+}
+
+fn function_name(item: &TokenStream) -> Option<String> {
+    let mut tokens = item.clone().into_iter();
+    while let Some(token) = tokens.next() {
+        if matches!(&token, TokenTree::Ident(ident) if ident.to_string() == "fn") {
+            return match tokens.next() {
+                Some(TokenTree::Ident(name)) => Some(name.to_string()),
+                _ => None,
+            };
+        }
+    }
+    None
+}
+
+fn compile_error(message: &str) -> TokenStream {
+    format!("compile_error!({:?});", message).parse().unwrap()
+}
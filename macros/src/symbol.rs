@@ -0,0 +1,66 @@
+// Symbol naming shared by the `ffi_export` macro and the solana_unity build script, which
+// includes this file with `#[path]`. Each side uses only part of it.
+#![allow(dead_code)]
+
+/// Set by users to choose the prefix when the `symbol-prefix` feature is enabled
+pub const USER_PREFIX_ENV: &str = "SOLANA_UNITY_PREFIX";
+/// Set by the build script to the prefix in effect, empty when symbols are not prefixed
+pub const PREFIX_ENV: &str = "SOLANA_UNITY_SYMBOL_PREFIX";
+/// Used when `symbol-prefix` is enabled without SOLANA_UNITY_PREFIX
+pub const DEFAULT_PREFIX: &str = "su_";
+
+/// The name `function` is exported under
+pub fn exported_name(prefix: &str, function: &str) -> String {
+    format!("{}{}", prefix, function)
+}
+
+/// A prefix must keep the exported names valid C identifiers
+pub fn is_valid_prefix(prefix: &str) -> bool {
+    !prefix.starts_with(|c: char| c.is_ascii_digit())
+        && prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// An `#[ffi_export]` function found in source
+pub struct ExportedFunction {
+    pub name: String,
+    /// Feature the function is gated behind, if any
+    pub feature: Option<String>,
+}
+
+/// Finds the `#[ffi_export]` functions in a source file. Only handles the layout used in
+/// ffi.rs: attributes on their own lines directly above `pub extern "C" fn name(` or
+/// `pub unsafe extern "C" fn name(`.
+pub fn exported_functions(source: &str) -> Vec<ExportedFunction> {
+    let mut functions = Vec::new();
+    let mut exported = false;
+    let mut feature = None;
+
+    for line in source.lines().map(str::trim) {
+        if line == "#[ffi_export]" {
+            exported = true;
+        } else if let Some(rest) = line.strip_prefix("#[cfg(feature = \"") {
+            feature = rest.strip_suffix("\")]").map(str::to_string);
+        } else if line.starts_with("#[") {
+            continue;
+        } else {
+            if exported {
+                let name = line
+                    .strip_prefix("pub extern \"C\" fn ")
+                    .or_else(|| line.strip_prefix("pub unsafe extern \"C\" fn "))
+                    .and_then(|rest| rest.split('(').next());
+                if let Some(name) = name {
+                    functions.push(ExportedFunction {
+                        name: name.to_string(),
+                        feature: feature.clone(),
+                    });
+                }
+            }
+            exported = false;
+            feature = None;
+        }
+    }
+
+    functions
+}
@@ -1,12 +1,60 @@
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::signature::{keypair_from_seed, Keypair, Signer};
+use std::io::ErrorKind as IoErrorKind;
 use std::str::FromStr;
+use zeroize::Zeroizing;
 
-use crate::error::SolanaUnityError;
+use crate::error::{code, ErrorKind, SolanaUnityError};
+use crate::keystore::{Keystore, DEFAULT_KDF_ITERATIONS};
+use crate::offchain_message;
+use crate::pda::ProgramDerivedAddress;
+use crate::pkcs8;
+use crate::secure_memory::{SecureBox, ZeroizingBox};
+use crate::shamir;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::vanity::GrindHandle;
+
+// Shared with the vanity search, which runs on native targets only
+pub(crate) const BASE58_ALPHABET: &str =
+    "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// BIP44 derivation layout used when deriving an account from a mnemonic by index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerivationScheme {
+    /// `m/44'/501'/{index}'/0'`, used by Phantom, Solflare and solana-keygen
+    Standard,
+    /// `m/44'/501'/{index}'`, used by some older wallets
+    Short,
+}
+
+impl DerivationScheme {
+    pub fn path(&self, account_index: u32) -> String {
+        match self {
+            DerivationScheme::Standard => format!("m/44'/501'/{}'/0'", account_index),
+            DerivationScheme::Short => format!("m/44'/501'/{}'", account_index),
+        }
+    }
+}
+
+// Where an account's keypair lives. Both wipe it when the account is dropped; secure
+// storage also keeps it on locked pages, see `Account::set_secure_memory`.
+enum KeypairStorage {
+    Heap(ZeroizingBox<Keypair>),
+    Secure(SecureBox<Keypair>),
+}
+
+impl KeypairStorage {
+    fn keypair(&self) -> &Keypair {
+        match self {
+            KeypairStorage::Heap(keypair) => keypair,
+            KeypairStorage::Secure(keypair) => keypair,
+        }
+    }
+}
 
 pub struct Account {
     pubkey: Option<Pubkey>,
-    keypair: Option<Keypair>,
+    keypair: Option<KeypairStorage>,
 }
 
 impl Account {
@@ -27,18 +75,272 @@ impl Account {
         })
     }
 
+    pub(crate) fn from_keypair(keypair: Keypair) -> Self {
+        Self {
+            pubkey: Some(keypair.pubkey()),
+            keypair: Some(KeypairStorage::Heap(ZeroizingBox::new(keypair))),
+        }
+    }
+
     pub fn from_private_key(private_key: &[u8]) -> Result<Self, SolanaUnityError> {
+        if private_key.len() == 32 {
+            return Err(SolanaUnityError::WalletError(
+                "Invalid keypair: got 32 bytes, which looks like an ed25519 seed; use from_seed for 32-byte seeds or pass the 64-byte keypair".to_string(),
+            ));
+        }
+
         let keypair = Keypair::from_bytes(private_key)
             .map_err(|e| SolanaUnityError::WalletError(format!("Invalid keypair: {}", e)))?;
 
-        let pubkey = keypair.pubkey();
+        Ok(Self::from_keypair(keypair))
+    }
 
-        Ok(Self {
-            pubkey: Some(pubkey),
-            keypair: Some(keypair),
+    /// Creates an account from a 32-byte ed25519 seed
+    pub fn from_seed(seed: &[u8]) -> Result<Self, SolanaUnityError> {
+        if seed.len() != 32 {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Seed must be exactly 32 bytes, got {}",
+                seed.len()
+            )));
+        }
+
+        let keypair = keypair_from_seed(seed)
+            .map_err(|e| SolanaUnityError::WalletError(format!("Invalid seed: {}", e)))?;
+
+        Ok(Self::from_keypair(keypair))
+    }
+
+    /// Creates an account from a base58-encoded 64-byte secret key, as exported by wallets
+    /// such as Phantom and Solflare
+    pub fn from_base58_private_key(encoded: &str) -> Result<Self, SolanaUnityError> {
+        let bytes = Zeroizing::new(bs58::decode(encoded.trim()).into_vec().map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Private key is not valid base58: {}", e))
+        })?);
+
+        match bytes.len() {
+            64 => Self::from_private_key(&bytes),
+            32 => Err(SolanaUnityError::InvalidInput(
+                "Private key decodes to 32 bytes, which is a seed rather than a 64-byte keypair"
+                    .to_string(),
+            )),
+            len => Err(SolanaUnityError::InvalidInput(format!(
+                "Private key decodes to {} bytes, expected 64",
+                len
+            ))),
+        }
+    }
+
+    /// Returns the 64-byte secret key encoded as base58
+    pub fn to_base58_private_key(&self) -> Result<String, SolanaUnityError> {
+        let private_key = self.get_private_key_zeroizing()?;
+        Ok(bs58::encode(&private_key[..]).into_string())
+    }
+
+    /// Creates an account from a solana-keygen style JSON keypair (an array of 64 bytes)
+    pub fn from_keypair_json(json: &str) -> Result<Self, SolanaUnityError> {
+        let values: Zeroizing<Vec<u64>> =
+            Zeroizing::new(serde_json::from_str(json.trim()).map_err(|e| {
+                SolanaUnityError::InvalidInput(format!("Invalid keypair JSON: {}", e))
+            })?);
+
+        if values.len() != 64 {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Invalid keypair JSON: expected 64 bytes, found {}",
+                values.len()
+            )));
+        }
+
+        let mut bytes = Zeroizing::new(Vec::with_capacity(values.len()));
+        for (i, value) in values.iter().enumerate() {
+            let byte = u8::try_from(*value).map_err(|_| {
+                SolanaUnityError::InvalidInput(format!(
+                    "Invalid keypair JSON: value {} at index {} is out of byte range",
+                    value, i
+                ))
+            })?;
+            bytes.push(byte);
+        }
+
+        Self::from_private_key(&bytes)
+    }
+
+    /// Serializes the keypair in the solana-keygen JSON format
+    pub fn to_keypair_json(&self) -> Result<String, SolanaUnityError> {
+        let private_key = self.get_private_key_zeroizing()?;
+
+        serde_json::to_string(&private_key[..]).map_err(|e| {
+            SolanaUnityError::SerializationError(format!("Failed to serialize keypair: {}", e))
         })
     }
 
+    /// Reads a solana-keygen JSON keypair file. A missing file, a permission problem and
+    /// contents that are not a 64-byte keypair each give a distinct message.
+    pub fn from_keypair_file(path: &str) -> Result<Self, SolanaUnityError> {
+        let json = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| {
+            SolanaUnityError::WalletError(match e.kind() {
+                IoErrorKind::NotFound => format!("Keypair file not found: {}", path),
+                IoErrorKind::PermissionDenied => {
+                    format!("Permission denied reading keypair file {}", path)
+                }
+                _ => format!("Failed to read keypair file {}: {}", path, e),
+            })
+        })?);
+
+        Self::from_keypair_json(&json).map_err(|mut e| {
+            if e.kind == ErrorKind::InvalidInput {
+                e.message = format!("{} in {}", e.message, path);
+            }
+            e
+        })
+    }
+
+    /// Writes the keypair to a solana-keygen JSON file. An existing file is only replaced
+    /// when `overwrite` is set. On Unix the file is left with owner-only read/write
+    /// permissions, including when an existing file is replaced.
+    pub fn write_keypair_file(&self, path: &str, overwrite: bool) -> Result<(), SolanaUnityError> {
+        use std::io::Write;
+
+        let json = Zeroizing::new(self.to_keypair_json()?);
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true);
+        if overwrite {
+            options.create(true).truncate(true);
+        } else {
+            options.create_new(true);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(path).map_err(|e| {
+            SolanaUnityError::WalletError(match e.kind() {
+                IoErrorKind::AlreadyExists => format!(
+                    "Keypair file {} already exists; set overwrite to replace it",
+                    path
+                ),
+                IoErrorKind::PermissionDenied => {
+                    format!("Permission denied writing keypair file {}", path)
+                }
+                _ => format!("Failed to create keypair file {}: {}", path, e),
+            })
+        })?;
+
+        // `mode` only applies when the file is created, so an overwritten file keeps its old
+        // permissions unless they are tightened before the key is written
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| {
+                    SolanaUnityError::WalletError(format!(
+                        "Failed to set permissions on keypair file {}: {}",
+                        path, e
+                    ))
+                })?;
+        }
+
+        file.write_all(json.as_bytes()).map_err(|e| {
+            SolanaUnityError::WalletError(format!("Failed to write keypair file {}: {}", path, e))
+        })
+    }
+
+    /// Creates an account from an unencrypted Ed25519 key in PKCS#8 PEM, as written by
+    /// `openssl genpkey -algorithm ed25519`. Both the v1 and the v2 encoding are read; a v2
+    /// public key must match the private key.
+    pub fn from_pkcs8_pem(pem: &str) -> Result<Self, SolanaUnityError> {
+        Self::from_pkcs8_der(&pkcs8::pem_to_der(pem)?)
+    }
+
+    /// Like `from_pkcs8_pem`, for the DER bytes inside the PEM block
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, SolanaUnityError> {
+        let key = pkcs8::decode_der(der)?;
+        let account = Self::from_seed(&key.seed[..])?;
+        if let Some(public_key) = key.public_key {
+            if public_key != account.get_pubkey_bytes()? {
+                return Err(SolanaUnityError::InvalidInput(
+                    "Invalid PKCS#8 key: the public key does not match the private key",
+                )
+                .with_code(code::INVALID_KEY_ENCODING));
+            }
+        }
+        Ok(account)
+    }
+
+    /// Returns the keypair as PKCS#8 v2 PEM, public key included
+    pub fn to_pkcs8_pem(&self) -> Result<String, SolanaUnityError> {
+        let der = self.to_pkcs8_der()?;
+        Ok(pkcs8::der_to_pem(&der).to_string())
+    }
+
+    /// Returns the keypair as PKCS#8 v2 DER, public key included
+    pub fn to_pkcs8_der(&self) -> Result<Vec<u8>, SolanaUnityError> {
+        let private_key = self.get_private_key_zeroizing()?;
+        let mut seed = Zeroizing::new([0u8; 32]);
+        seed.copy_from_slice(&private_key[..32]);
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(&private_key[32..]);
+        Ok(pkcs8::encode_der(&seed, &public_key).to_vec())
+    }
+
+    /// Exports the keypair as a password-encrypted JSON keystore
+    pub fn export_encrypted(&self, password: &str) -> Result<String, SolanaUnityError> {
+        self.export_encrypted_with_iterations(password, DEFAULT_KDF_ITERATIONS)
+    }
+
+    /// Exports the keypair as an encrypted keystore with a custom PBKDF2 iteration count,
+    /// e.g. to trade strength for unlock time on low-end mobile devices
+    pub fn export_encrypted_with_iterations(
+        &self,
+        password: &str,
+        iterations: u32,
+    ) -> Result<String, SolanaUnityError> {
+        let keypair = self.get_keypair()?;
+        let keystore = Keystore::encrypt(
+            &Zeroizing::new(keypair.to_bytes())[..],
+            &keypair.pubkey().to_string(),
+            password,
+            iterations,
+        )?;
+        keystore.to_json()
+    }
+
+    /// Restores an account from a keystore produced by `export_encrypted`
+    pub fn from_encrypted(json: &str, password: &str) -> Result<Self, SolanaUnityError> {
+        let keystore = Keystore::from_json(json)?;
+        let secret = Zeroizing::new(keystore.decrypt(password)?);
+        let account = Self::from_private_key(&secret)?;
+
+        if account.get_pubkey()? != keystore.pubkey {
+            return Err(SolanaUnityError::DecryptionFailed());
+        }
+
+        Ok(account)
+    }
+
+    /// Splits the key into `shares` base58 shares, any `threshold` of which restore it with
+    /// `from_shares`, e.g. 2 of 3 so one can be lost. At most 16 shares; the threshold must be
+    /// at least 2.
+    pub fn split_private_key(
+        &self,
+        threshold: u8,
+        shares: u8,
+    ) -> Result<Vec<String>, SolanaUnityError> {
+        let private_key = self.get_private_key_zeroizing()?;
+        let mut seed = Zeroizing::new([0u8; 32]);
+        seed.copy_from_slice(&private_key[..32]);
+        shamir::split(&seed, threshold, shares)
+    }
+
+    /// Restores an account from shares made by `split_private_key`. Fails if any share is
+    /// corrupted, repeated or from another split, or if there are fewer than the threshold.
+    pub fn from_shares(shares: &[&str]) -> Result<Self, SolanaUnityError> {
+        let seed = shamir::combine(shares)?;
+        Self::from_seed(&seed[..])
+    }
+
     #[cfg(feature = "bip39")]
     pub fn from_mnemonic(
         mnemonic: &str,
@@ -66,21 +368,70 @@ impl Account {
                 SolanaUnityError::WalletError(format!("Keypair derivation failed: {}", e))
             })?;
 
-        let pubkey = keypair.pubkey();
+        Ok(Self::from_keypair(keypair))
+    }
 
-        Ok(Self {
-            pubkey: Some(pubkey),
-            keypair: Some(keypair),
-        })
+    /// Derives an account from a mnemonic using the standard Solana path
+    /// `m/44'/501'/{account_index}'/0'`
+    #[cfg(feature = "bip39")]
+    pub fn from_mnemonic_with_index(
+        mnemonic: &str,
+        passphrase: &str,
+        account_index: u32,
+    ) -> Result<Self, SolanaUnityError> {
+        Self::from_mnemonic_with_scheme(
+            mnemonic,
+            passphrase,
+            account_index,
+            DerivationScheme::Standard,
+        )
+    }
+
+    /// Derives an account from a mnemonic by index using the given derivation scheme
+    #[cfg(feature = "bip39")]
+    pub fn from_mnemonic_with_scheme(
+        mnemonic: &str,
+        passphrase: &str,
+        account_index: u32,
+        scheme: DerivationScheme,
+    ) -> Result<Self, SolanaUnityError> {
+        if account_index >= 1 << 31 {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Account index {} is out of range for hardened derivation",
+                account_index
+            )));
+        }
+
+        Self::from_mnemonic(mnemonic, passphrase, &scheme.path(account_index))
+    }
+
+    /// Generates keypairs on `threads` worker threads until one has an address starting
+    /// with `prefix`, or fails after `max_attempts` keypairs. Blocks until done; see
+    /// `GrindHandle` for a cancellable background search.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn grind(
+        prefix: &str,
+        case_insensitive: bool,
+        max_attempts: u64,
+        threads: usize,
+    ) -> Result<Self, SolanaUnityError> {
+        GrindHandle::start(prefix, case_insensitive, max_attempts, threads)?.wait()
     }
 
     pub fn generate() -> Self {
-        let keypair = Keypair::new();
-        let pubkey = keypair.pubkey();
+        Self::from_keypair(Keypair::new())
+    }
 
-        Self {
-            pubkey: Some(pubkey),
-            keypair: Some(keypair),
+    /// Returns an independent copy of the account, including the keypair when present
+    pub fn try_clone(&self) -> Result<Self, SolanaUnityError> {
+        match &self.keypair {
+            Some(storage) => {
+                Self::from_private_key(&Zeroizing::new(storage.keypair().to_bytes())[..])
+            }
+            None => Ok(Self {
+                pubkey: self.pubkey,
+                keypair: None,
+            }),
         }
     }
 
@@ -91,13 +442,115 @@ impl Account {
             .ok_or_else(|| SolanaUnityError::WalletError("No public key available".to_string()))
     }
 
+    /// Returns true when the string is a valid base58-encoded 32-byte public key
+    pub fn is_valid_pubkey(pubkey: &str) -> bool {
+        Self::validate_pubkey(pubkey).is_ok()
+    }
+
+    /// Validates a public key string, explaining why it was rejected
+    pub fn validate_pubkey(pubkey: &str) -> Result<(), SolanaUnityError> {
+        if pubkey.is_empty() {
+            return Err(SolanaUnityError::InvalidInput(
+                "Address is empty".to_string(),
+            ));
+        }
+
+        if let Some((i, c)) = pubkey
+            .char_indices()
+            .find(|(_, c)| !BASE58_ALPHABET.contains(*c))
+        {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Address contains invalid character {:?} at position {}",
+                c, i
+            )));
+        }
+
+        if !(32..=44).contains(&pubkey.len()) {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Address has {} characters, expected between 32 and 44",
+                pubkey.len()
+            )));
+        }
+
+        let decoded = bs58::decode(pubkey).into_vec().map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Address is not valid base58: {}", e))
+        })?;
+
+        if decoded.len() != 32 {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Address decodes to {} bytes, expected 32",
+                decoded.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the raw 32-byte public key
+    pub fn get_pubkey_bytes(&self) -> Result<[u8; 32], SolanaUnityError> {
+        self.pubkey
+            .as_ref()
+            .map(|pk| pk.to_bytes())
+            .ok_or_else(|| SolanaUnityError::WalletError("No public key available".to_string()))
+    }
+
+    /// Decodes a base58 public key string into its raw 32 bytes
+    pub fn pubkey_to_bytes(pubkey: &str) -> Result<[u8; 32], SolanaUnityError> {
+        Pubkey::from_str(pubkey)
+            .map(|pk| pk.to_bytes())
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid pubkey: {}", e)))
+    }
+
+    /// Returns a copy of the 64-byte secret key. The returned Vec is owned by the caller;
+    /// prefer `get_private_key_zeroizing` when the bytes are only needed temporarily.
     pub fn get_private_key(&self) -> Result<Vec<u8>, SolanaUnityError> {
+        self.get_private_key_zeroizing().map(|bytes| bytes.to_vec())
+    }
+
+    /// Returns the 64-byte secret key in a buffer that is wiped when dropped
+    pub fn get_private_key_zeroizing(&self) -> Result<Zeroizing<[u8; 64]>, SolanaUnityError> {
         self.keypair
             .as_ref()
-            .map(|kp| kp.to_bytes().to_vec())
+            .map(|storage| Zeroizing::new(storage.keypair().to_bytes()))
             .ok_or_else(|| SolanaUnityError::WalletError("No keypair available".to_string()))
     }
 
+    /// Drops the keypair, leaving a read-only account. The memory that held it is
+    /// zeroized.
+    pub fn clear_private_key(&mut self) {
+        self.keypair = None;
+    }
+
+    /// Moves the keypair into (or out of) memory locked against swapping. Returns whether
+    /// the pages are actually locked; when the OS refuses (e.g. RLIMIT_MEMLOCK is reached)
+    /// the keypair still lives on dedicated pages that are wiped on drop.
+    pub fn set_secure_memory(&mut self, enabled: bool) -> Result<bool, SolanaUnityError> {
+        let storage = self
+            .keypair
+            .as_ref()
+            .ok_or_else(|| SolanaUnityError::WalletError("No keypair available".to_string()))?;
+
+        let replacement = match (storage, enabled) {
+            (KeypairStorage::Heap(keypair), true) => Some(KeypairStorage::Secure(SecureBox::new(
+                copy_keypair(keypair)?,
+            ))),
+            (KeypairStorage::Secure(keypair), false) => Some(KeypairStorage::Heap(
+                ZeroizingBox::new(copy_keypair(keypair)?),
+            )),
+            _ => None,
+        };
+
+        if let Some(storage) = replacement {
+            self.keypair = Some(storage);
+        }
+        Ok(self.is_memory_locked())
+    }
+
+    /// Returns true when the keypair is held in memory locked against swapping
+    pub fn is_memory_locked(&self) -> bool {
+        matches!(&self.keypair, Some(KeypairStorage::Secure(keypair)) if keypair.is_locked())
+    }
+
     pub fn has_private_key(&self) -> bool {
         self.keypair.is_some()
     }
@@ -105,8 +558,76 @@ impl Account {
     pub fn get_keypair(&self) -> Result<&Keypair, SolanaUnityError> {
         self.keypair
             .as_ref()
+            .map(KeypairStorage::keypair)
             .ok_or_else(|| SolanaUnityError::WalletError("No keypair available".to_string()))
     }
+
+    /// Signs an arbitrary message with the account's keypair and returns the 64-byte
+    /// ed25519 signature
+    pub fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>, SolanaUnityError> {
+        let keypair = self.get_keypair()?;
+        Ok(keypair.sign_message(message).as_ref().to_vec())
+    }
+
+    /// Signs an arbitrary message and returns the signature encoded as base58
+    pub fn sign_message_base58(&self, message: &[u8]) -> Result<String, SolanaUnityError> {
+        let keypair = self.get_keypair()?;
+        Ok(keypair.sign_message(message).to_string())
+    }
+
+    /// Signs `message` wrapped in the Solana off-chain message envelope, matching what
+    /// browser wallets produce for signMessage-style requests that use the standard format
+    pub fn sign_offchain_message(&self, message: &[u8]) -> Result<Vec<u8>, SolanaUnityError> {
+        let keypair = self.get_keypair()?;
+        let data = offchain_message::serialize(message)?;
+        Ok(keypair.sign_message(&data).as_ref().to_vec())
+    }
+
+    /// Verifies a signature produced by `sign_offchain_message` or a compatible wallet
+    pub fn verify_offchain_message(
+        pubkey: &str,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, SolanaUnityError> {
+        let data = offchain_message::serialize(message)?;
+        Self::verify_signature(pubkey, &data, signature)
+    }
+
+    /// Verifies an ed25519 signature over `message` for the given public key without
+    /// requiring a keypair. Returns Ok(false) when the signature is well formed but does
+    /// not match, and an error only when the inputs themselves are malformed.
+    pub fn verify_signature(
+        pubkey: &str,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, SolanaUnityError> {
+        let pubkey = Pubkey::from_str(pubkey)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid pubkey: {}", e)))?;
+
+        if signature.len() != 64 {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Signature must be 64 bytes, got {}",
+                signature.len()
+            )));
+        }
+
+        let signature = solana_sdk::signature::Signature::try_from(signature)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid signature: {}", e)))?;
+
+        Ok(signature.verify(pubkey.as_ref(), message))
+    }
+
+    /// Returns true when the address is a program derived address (off the ed25519 curve)
+    /// and therefore can never sign, e.g. as a fee payer
+    pub fn is_pda_address(pubkey: &str) -> Result<bool, SolanaUnityError> {
+        ProgramDerivedAddress::is_on_curve(pubkey).map(|on_curve| !on_curve)
+    }
+}
+
+fn copy_keypair(keypair: &Keypair) -> Result<Keypair, SolanaUnityError> {
+    let bytes = Zeroizing::new(keypair.to_bytes());
+    Keypair::from_bytes(&bytes[..])
+        .map_err(|e| SolanaUnityError::WalletError(format!("Invalid keypair: {}", e)))
 }
 
 #[cfg(test)]
@@ -197,7 +718,10 @@ mod tests {
             );
 
             match result {
-                Err(SolanaUnityError::InvalidInput(_)) => {}
+                Err(SolanaUnityError {
+                    kind: ErrorKind::InvalidInput,
+                    ..
+                }) => {}
                 _ => panic!("Expected InvalidInput error for pubkey: {}", pubkey),
             }
         }
@@ -214,7 +738,10 @@ mod tests {
         assert!(result.is_err());
 
         match result {
-            Err(SolanaUnityError::WalletError(_)) => {}
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                ..
+            }) => {}
             _ => panic!("Expected WalletError for invalid private key"),
         }
     }
@@ -281,4 +808,849 @@ mod tests {
 
         println!("Successfully created read-only account from public key");
     }
+
+    #[test]
+    fn test_is_pda_address() {
+        let account = Account::generate();
+        let pubkey = account.get_pubkey().unwrap();
+        assert!(!Account::is_pda_address(&pubkey).unwrap());
+
+        let program_id = solana_sdk::pubkey::Pubkey::new_unique().to_string();
+        let owner = Pubkey::from_str(&pubkey).unwrap();
+        let (pda, _) =
+            ProgramDerivedAddress::find_program_address(&[b"vault", owner.as_ref()], &program_id)
+                .unwrap();
+        assert!(Account::is_pda_address(&pda).unwrap());
+
+        match Account::is_pda_address("not-a-valid-pubkey") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput error for invalid pubkey"),
+        }
+    }
+
+    #[test]
+    fn test_keypair_json_round_trip() {
+        let account = Account::generate();
+        let json = account.to_keypair_json().unwrap();
+
+        let restored = Account::from_keypair_json(&json).unwrap();
+        assert_eq!(
+            restored.get_pubkey().unwrap(),
+            account.get_pubkey().unwrap()
+        );
+        assert_eq!(
+            restored.get_private_key().unwrap(),
+            account.get_private_key().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_keypair_json() {
+        let too_short = serde_json::to_string(&vec![1u8; 32]).unwrap();
+        let mut out_of_range = vec![1u16; 64];
+        out_of_range[10] = 256;
+        let out_of_range = serde_json::to_string(&out_of_range).unwrap();
+
+        for json in [
+            "",
+            "not json",
+            "[1, 2, -3]",
+            too_short.as_str(),
+            out_of_range.as_str(),
+        ] {
+            match Account::from_keypair_json(json) {
+                Err(SolanaUnityError {
+                    kind: ErrorKind::InvalidInput,
+                    ..
+                }) => {}
+                _ => panic!("Expected InvalidInput error for keypair JSON: {}", json),
+            }
+        }
+    }
+
+    #[test]
+    fn test_keypair_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("id.json");
+        let path_str = path.to_str().unwrap();
+
+        let account = Account::generate();
+        account.write_keypair_file(path_str, false).unwrap();
+
+        // The file must be readable by the Solana SDK
+        let keypair = solana_sdk::signature::read_keypair_file(&path).unwrap();
+        assert_eq!(keypair.pubkey().to_string(), account.get_pubkey().unwrap());
+
+        let restored = Account::from_keypair_file(path_str).unwrap();
+        assert_eq!(
+            restored.get_pubkey().unwrap(),
+            account.get_pubkey().unwrap()
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_keypair_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("id.json");
+        let path_str = path.to_str().unwrap();
+
+        match Account::from_keypair_file(path_str) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => {
+                assert_eq!(msg, format!("Keypair file not found: {}", path_str))
+            }
+            _ => panic!("Expected WalletError for a missing file"),
+        }
+
+        std::fs::write(&path, "[1, 2,").unwrap();
+        match Account::from_keypair_file(path_str) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => {
+                assert!(msg.starts_with("Invalid keypair JSON:"));
+                assert!(msg.ends_with(path_str));
+            }
+            _ => panic!("Expected InvalidInput for malformed JSON"),
+        }
+
+        std::fs::write(&path, serde_json::to_string(&[1u8; 32]).unwrap()).unwrap();
+        match Account::from_keypair_file(path_str) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => {
+                assert!(msg.contains("expected 64 bytes, found 32"))
+            }
+            _ => panic!("Expected InvalidInput for a short keypair"),
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let account = Account::generate();
+            std::fs::write(&path, account.to_keypair_json().unwrap()).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o000)).unwrap();
+            // Root ignores file permissions, so only check when the read actually fails
+            if std::fs::read(&path).is_err() {
+                match Account::from_keypair_file(path_str) {
+                    Err(SolanaUnityError {
+                        kind: ErrorKind::Wallet,
+                        message: msg,
+                        ..
+                    }) => {
+                        assert!(msg.starts_with("Permission denied reading keypair file"))
+                    }
+                    _ => panic!("Expected WalletError for an unreadable file"),
+                }
+            }
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_write_keypair_file_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("id.json");
+        let path_str = path.to_str().unwrap();
+
+        let first = Account::generate();
+        let second = Account::generate();
+        first.write_keypair_file(path_str, false).unwrap();
+
+        match second.write_keypair_file(path_str, false) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("already exists")),
+            _ => panic!("Expected WalletError when the file exists"),
+        }
+        assert_eq!(
+            Account::from_keypair_file(path_str)
+                .unwrap()
+                .get_pubkey()
+                .unwrap(),
+            first.get_pubkey().unwrap()
+        );
+
+        second.write_keypair_file(path_str, true).unwrap();
+        assert_eq!(
+            Account::from_keypair_file(path_str)
+                .unwrap()
+                .get_pubkey()
+                .unwrap(),
+            second.get_pubkey().unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_keypair_file_overwrite_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("id.json");
+        let path_str = path.to_str().unwrap();
+
+        std::fs::write(&path, "[]").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let account = Account::generate();
+        account.write_keypair_file(path_str, true).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            Account::from_keypair_file(path_str)
+                .unwrap()
+                .get_pubkey()
+                .unwrap(),
+            account.get_pubkey().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_base58_private_key_round_trip() {
+        let keypair = Keypair::new();
+        let encoded = keypair.to_base58_string();
+
+        // Surrounding whitespace from copy/paste is tolerated
+        let account = Account::from_base58_private_key(&format!("  {}\n", encoded)).unwrap();
+        assert_eq!(account.get_pubkey().unwrap(), keypair.pubkey().to_string());
+        assert_eq!(account.to_base58_private_key().unwrap(), encoded);
+    }
+
+    // ed25519.pem was generated with `openssl genpkey -algorithm ed25519` (PKCS#8 v1),
+    // ed25519_v2.pem holds the same key in v2 form and ed25519_keypair.json in the
+    // solana-keygen format
+    const PKCS8_PUBKEY: &str = "4NHdUBwjpvre1mjV1kaVmdBvNYRtUYJGQrEWWoLkRXQG";
+
+    #[test]
+    fn test_pkcs8_round_trip() {
+        let keypair_json = include_str!("../tests/fixtures/pkcs8/ed25519_keypair.json");
+        let expected = Account::from_keypair_json(keypair_json).unwrap();
+        assert_eq!(expected.get_pubkey().unwrap(), PKCS8_PUBKEY);
+
+        for pem in [
+            include_str!("../tests/fixtures/pkcs8/ed25519.pem"),
+            include_str!("../tests/fixtures/pkcs8/ed25519_v2.pem"),
+        ] {
+            let account = Account::from_pkcs8_pem(pem).unwrap();
+            assert_eq!(account.get_pubkey().unwrap(), PKCS8_PUBKEY);
+            assert_eq!(
+                account.to_keypair_json().unwrap(),
+                expected.to_keypair_json().unwrap()
+            );
+        }
+
+        // Written as v2, which reads back to the same key
+        let pem = expected.to_pkcs8_pem().unwrap();
+        assert_eq!(pem, include_str!("../tests/fixtures/pkcs8/ed25519_v2.pem"));
+        let der = expected.to_pkcs8_der().unwrap();
+        let account = Account::from_pkcs8_der(&der).unwrap();
+        assert_eq!(account.get_pubkey().unwrap(), PKCS8_PUBKEY);
+
+        // Text around the block is ignored
+        let pem = format!("key for the game server\n{}\n", pem);
+        assert!(Account::from_pkcs8_pem(&pem).is_ok());
+
+        assert!(Account::from_pubkey(PKCS8_PUBKEY)
+            .unwrap()
+            .to_pkcs8_pem()
+            .is_err());
+    }
+
+    #[test]
+    fn test_pkcs8_errors() {
+        for (pem, algorithm) in [
+            (include_str!("../tests/fixtures/pkcs8/p256.pem"), "EC"),
+            (include_str!("../tests/fixtures/pkcs8/x25519.pem"), "X25519"),
+        ] {
+            let error = Account::from_pkcs8_pem(pem).err().unwrap();
+            assert_eq!(error.code, code::UNSUPPORTED_KEY_ALGORITHM);
+            assert!(error.message.contains(algorithm), "{}", error);
+        }
+
+        let pem = include_str!("../tests/fixtures/pkcs8/ed25519.pem");
+        for bad in [
+            "not a key".to_string(),
+            pem.replace("-----END PRIVATE KEY-----", ""),
+            pem.replace("PRIVATE KEY", "ENCRYPTED PRIVATE KEY"),
+            pem.replace("MC4CAQAw", "MC4CAQAw!"),
+            pem.replace("MC4CAQAw", "MC8CAQAw"),
+        ] {
+            let error = Account::from_pkcs8_pem(&bad).err().unwrap();
+            assert_eq!(error.code, code::INVALID_KEY_ENCODING, "{}", bad);
+            assert_eq!(error.kind, ErrorKind::InvalidInput);
+        }
+
+        // A v2 public key that belongs to another key
+        let mut der = Account::generate().to_pkcs8_der().unwrap();
+        der[82] ^= 1;
+        let error = Account::from_pkcs8_der(&der).err().unwrap();
+        assert!(error.message.contains("does not match"), "{}", error);
+    }
+
+    #[test]
+    fn test_split_private_key_recovers_with_threshold() {
+        let account = Account::generate();
+        let shares = account.split_private_key(2, 3).unwrap();
+        assert_eq!(shares.len(), 3);
+
+        // Exactly the threshold, in any order
+        for pair in [[0, 1], [2, 0], [1, 2]] {
+            let chosen: Vec<&str> = pair.iter().map(|&i| shares[i].as_str()).collect();
+            let restored = Account::from_shares(&chosen).unwrap();
+            assert_eq!(
+                restored.to_keypair_json().unwrap(),
+                account.to_keypair_json().unwrap()
+            );
+        }
+
+        // One fewer
+        let error = Account::from_shares(&[shares[1].as_str()]).err().unwrap();
+        assert_eq!(error.code, code::NOT_ENOUGH_KEY_SHARES);
+
+        for (threshold, count) in [(3, 2), (2, 17), (0, 3)] {
+            let error = account.split_private_key(threshold, count).err().unwrap();
+            assert_eq!(
+                error.kind,
+                ErrorKind::InvalidInput,
+                "{} of {}",
+                threshold,
+                count
+            );
+        }
+        assert!(Account::from_pubkey(&account.get_pubkey().unwrap())
+            .unwrap()
+            .split_private_key(2, 3)
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_shares_rejects_bad_shares() {
+        let account = Account::generate();
+        let shares = account.split_private_key(2, 3).unwrap();
+
+        // A mistyped character
+        let mut corrupted: Vec<char> = shares[0].chars().collect();
+        corrupted[10] = if corrupted[10] == 'a' { 'b' } else { 'a' };
+        let corrupted: String = corrupted.into_iter().collect();
+        let error = Account::from_shares(&[corrupted.as_str(), shares[1].as_str()])
+            .err()
+            .unwrap();
+        assert_eq!(error.code, code::INVALID_KEY_SHARE);
+        assert!(error.message.contains("checksum"), "{}", error);
+
+        // The same share twice
+        let error = Account::from_shares(&[shares[2].as_str(), shares[2].as_str()])
+            .err()
+            .unwrap();
+        assert_eq!(error.code, code::INVALID_KEY_SHARE);
+
+        // Another split of the same key, and a split of another key
+        let resplit = account.split_private_key(2, 3).unwrap();
+        let other = Account::generate().split_private_key(2, 3).unwrap();
+        for mixed in [&resplit[1], &other[1]] {
+            let error = Account::from_shares(&[shares[0].as_str(), mixed.as_str()])
+                .err()
+                .unwrap();
+            assert_eq!(error.code, code::KEY_SHARE_MISMATCH);
+        }
+
+        assert!(Account::from_shares(&[]).is_err());
+        assert!(Account::from_shares(&["not a share"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_base58_private_key() {
+        match Account::from_base58_private_key("0OIl-not-base58") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message,
+                ..
+            }) => {
+                assert!(message.contains("not valid base58"), "{}", message)
+            }
+            _ => panic!("Expected InvalidInput error for invalid base58"),
+        }
+
+        let seed = bs58::encode([7u8; 32]).into_string();
+        match Account::from_base58_private_key(&seed) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message,
+                ..
+            }) => {
+                assert!(message.contains("32 bytes"), "{}", message)
+            }
+            _ => panic!("Expected InvalidInput error for 32-byte seed"),
+        }
+
+        let wrong_length = bs58::encode([7u8; 40]).into_string();
+        match Account::from_base58_private_key(&wrong_length) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message,
+                ..
+            }) => {
+                assert!(message.contains("40 bytes"), "{}", message)
+            }
+            _ => panic!("Expected InvalidInput error for wrong length"),
+        }
+
+        let read_only = Account::from_pubkey("11111111111111111111111111111111").unwrap();
+        assert!(read_only.to_base58_private_key().is_err());
+    }
+
+    #[test]
+    fn test_sign_message() {
+        let account = Account::generate();
+        let pubkey = Pubkey::from_str(&account.get_pubkey().unwrap()).unwrap();
+        let message = b"login nonce 1234";
+
+        let signature_bytes = account.sign_message(message).unwrap();
+        assert_eq!(signature_bytes.len(), 64);
+
+        let signature =
+            solana_sdk::signature::Signature::try_from(signature_bytes.as_slice()).unwrap();
+        assert!(signature.verify(pubkey.as_ref(), message));
+        assert!(!signature.verify(pubkey.as_ref(), b"another message"));
+
+        let encoded = account.sign_message_base58(message).unwrap();
+        assert_eq!(encoded, signature.to_string());
+    }
+
+    #[test]
+    fn test_sign_message_read_only() {
+        let account = Account::from_pubkey("11111111111111111111111111111111").unwrap();
+
+        match account.sign_message(b"nonce") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                ..
+            }) => {}
+            _ => panic!("Expected WalletError when signing with a read-only account"),
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let account = Account::generate();
+        let pubkey = account.get_pubkey().unwrap();
+        let message = b"server challenge";
+        let signature = account.sign_message(message).unwrap();
+
+        assert!(Account::verify_signature(&pubkey, message, &signature).unwrap());
+
+        // Flipped bit in the signature
+        let mut tampered = signature.clone();
+        tampered[10] ^= 0x01;
+        assert!(!Account::verify_signature(&pubkey, message, &tampered).unwrap());
+
+        // Different message
+        assert!(!Account::verify_signature(&pubkey, b"other challenge", &signature).unwrap());
+
+        // Wrong signer
+        let other = Account::generate().get_pubkey().unwrap();
+        assert!(!Account::verify_signature(&other, message, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_verify_signature_malformed_inputs() {
+        let account = Account::generate();
+        let pubkey = account.get_pubkey().unwrap();
+        let signature = account.sign_message(b"msg").unwrap();
+
+        match Account::verify_signature(&pubkey, b"msg", &signature[..63]) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput for a short signature"),
+        }
+
+        match Account::verify_signature("not-a-pubkey", b"msg", &signature) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput for a malformed pubkey"),
+        }
+    }
+
+    #[test]
+    fn test_derivation_scheme_paths() {
+        assert_eq!(DerivationScheme::Standard.path(0), "m/44'/501'/0'/0'");
+        assert_eq!(DerivationScheme::Short.path(3), "m/44'/501'/3'");
+    }
+
+    #[cfg(feature = "bip39")]
+    const TEST_MNEMONIC: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[cfg(feature = "bip39")]
+    #[test]
+    fn test_from_mnemonic_with_index_matches_explicit_path() {
+        let by_index = Account::from_mnemonic_with_index(TEST_MNEMONIC, "", 0).unwrap();
+        let by_path = Account::from_mnemonic(TEST_MNEMONIC, "", "m/44'/501'/0'/0'").unwrap();
+        assert_eq!(
+            by_index.get_pubkey().unwrap(),
+            by_path.get_pubkey().unwrap()
+        );
+
+        let second = Account::from_mnemonic_with_index(TEST_MNEMONIC, "", 1).unwrap();
+        assert_ne!(second.get_pubkey().unwrap(), by_index.get_pubkey().unwrap());
+    }
+
+    #[cfg(feature = "bip39")]
+    #[test]
+    fn test_from_mnemonic_schemes_differ() {
+        let standard =
+            Account::from_mnemonic_with_scheme(TEST_MNEMONIC, "", 0, DerivationScheme::Standard)
+                .unwrap();
+        let short =
+            Account::from_mnemonic_with_scheme(TEST_MNEMONIC, "", 0, DerivationScheme::Short)
+                .unwrap();
+        assert_ne!(standard.get_pubkey().unwrap(), short.get_pubkey().unwrap());
+
+        match Account::from_mnemonic_with_index(TEST_MNEMONIC, "", 1 << 31) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput for an out of range index"),
+        }
+    }
+
+    #[test]
+    fn test_encrypted_keystore_round_trip() {
+        let account = Account::generate();
+        let json = account.export_encrypted("correct horse").unwrap();
+        assert!(!json.contains(&account.to_base58_private_key().unwrap()));
+
+        let restored = Account::from_encrypted(&json, "correct horse").unwrap();
+        assert_eq!(
+            restored.get_pubkey().unwrap(),
+            account.get_pubkey().unwrap()
+        );
+        assert_eq!(
+            restored.get_private_key().unwrap(),
+            account.get_private_key().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encrypted_keystore_wrong_password() {
+        let account = Account::generate();
+        let json = account
+            .export_encrypted_with_iterations("correct horse", 1_000)
+            .unwrap();
+
+        match Account::from_encrypted(&json, "battery staple") {
+            Err(e) if e.code == code::DECRYPTION_FAILED => {
+                assert!(!e.to_string().contains("correct horse"));
+            }
+            _ => panic!("Expected DecryptionFailed for a wrong password"),
+        }
+    }
+
+    #[test]
+    fn test_encrypted_keystore_corrupted_ciphertext() {
+        let account = Account::generate();
+        let json = account
+            .export_encrypted_with_iterations("pw", 1_000)
+            .unwrap();
+
+        let mut keystore: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let ciphertext = keystore["ciphertext"].as_str().unwrap().to_string();
+        let flipped = if ciphertext.starts_with('A') {
+            "B"
+        } else {
+            "A"
+        };
+        keystore["ciphertext"] =
+            serde_json::Value::String(format!("{}{}", flipped, &ciphertext[1..]));
+
+        match Account::from_encrypted(&keystore.to_string(), "pw") {
+            Err(SolanaUnityError {
+                code: code::DECRYPTION_FAILED,
+                ..
+            }) => {}
+            _ => panic!("Expected DecryptionFailed for corrupted ciphertext"),
+        }
+    }
+
+    #[test]
+    fn test_encrypted_keystore_tampered_salt() {
+        let account = Account::generate();
+        let json = account
+            .export_encrypted_with_iterations("pw", 1_000)
+            .unwrap();
+
+        let mut keystore: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let salt = keystore["kdf"]["salt"].as_str().unwrap().to_string();
+        let flipped = if salt.starts_with('A') { "B" } else { "A" };
+        keystore["kdf"]["salt"] = serde_json::Value::String(format!("{}{}", flipped, &salt[1..]));
+
+        match Account::from_encrypted(&keystore.to_string(), "pw") {
+            Err(SolanaUnityError {
+                code: code::DECRYPTION_FAILED,
+                ..
+            }) => {}
+            _ => panic!("Expected DecryptionFailed for a tampered salt"),
+        }
+    }
+
+    #[test]
+    fn test_encrypted_keystore_read_only_account() {
+        let account = Account::from_pubkey("11111111111111111111111111111111").unwrap();
+
+        match account.export_encrypted("pw") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                ..
+            }) => {}
+            _ => panic!("Expected WalletError when exporting a read-only account"),
+        }
+    }
+
+    // Wiping on drop is checked against the allocator in tests/zeroize_on_drop.rs
+    #[test]
+    fn test_private_key_zeroizing_matches_private_key() {
+        let account = Account::generate();
+        let secret = account.get_private_key_zeroizing().unwrap();
+        assert_eq!(secret.to_vec(), account.get_private_key().unwrap());
+        assert!(secret.iter().any(|b| *b != 0));
+    }
+
+    #[test]
+    fn test_clear_private_key() {
+        let mut account = Account::generate();
+        let pubkey = account.get_pubkey().unwrap();
+
+        account.clear_private_key();
+        assert!(!account.has_private_key());
+        assert!(account.get_private_key().is_err());
+        assert_eq!(account.get_pubkey().unwrap(), pubkey);
+    }
+
+    #[test]
+    fn test_grind() {
+        let account = Account::grind("B", false, 1_000_000, 2).unwrap();
+        assert!(account.get_pubkey().unwrap().starts_with('B'));
+
+        let restored = Account::from_private_key(&account.get_private_key().unwrap()).unwrap();
+        assert_eq!(
+            restored.get_pubkey().unwrap(),
+            account.get_pubkey().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_get_pubkey_bytes() {
+        let account = Account::generate();
+        let pubkey = account.get_pubkey().unwrap();
+
+        let bytes = account.get_pubkey_bytes().unwrap();
+        assert_eq!(Pubkey::new_from_array(bytes).to_string(), pubkey);
+        assert_eq!(Account::pubkey_to_bytes(&pubkey).unwrap(), bytes);
+
+        let system = Account::pubkey_to_bytes("11111111111111111111111111111111").unwrap();
+        assert_eq!(system, [0u8; 32]);
+
+        match Account::pubkey_to_bytes("not-a-pubkey") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput for a malformed pubkey"),
+        }
+
+        assert!(Account::new().get_pubkey_bytes().is_err());
+    }
+
+    #[test]
+    fn test_is_valid_pubkey() {
+        let pubkey = Account::generate().get_pubkey().unwrap();
+        assert!(Account::is_valid_pubkey(&pubkey));
+        assert!(Account::is_valid_pubkey("11111111111111111111111111111111"));
+        assert!(Account::is_valid_pubkey(
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+        ));
+
+        // Close but wrong: two characters dropped (31 bytes), swapped in a '0', extra
+        // character. Dropping just one still decodes to 32 bytes.
+        assert!(!Account::is_valid_pubkey(
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt"
+        ));
+        assert!(!Account::is_valid_pubkey(
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt10"
+        ));
+        assert!(!Account::is_valid_pubkey(
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1vv"
+        ));
+
+        assert!(!Account::is_valid_pubkey(""));
+        assert!(!Account::is_valid_pubkey(
+            "11111111111111111111111111111111\0"
+        ));
+    }
+
+    #[test]
+    fn test_validate_pubkey_reasons() {
+        let reason = |pubkey: &str| match Account::validate_pubkey(pubkey) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => msg,
+            _ => panic!("Expected InvalidInput for {:?}", pubkey),
+        };
+
+        assert!(reason("").contains("empty"));
+        assert!(reason("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1O").contains("position 43"));
+        assert!(reason("abc").contains("3 characters"));
+        assert!(reason("zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz").contains("bytes"));
+    }
+
+    #[test]
+    fn test_from_seed() {
+        let seed = [7u8; 32];
+        let account = Account::from_seed(&seed).unwrap();
+        assert_eq!(
+            account.get_pubkey().unwrap(),
+            "GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB"
+        );
+
+        let again = Account::from_seed(&seed).unwrap();
+        assert_eq!(
+            again.get_private_key().unwrap(),
+            account.get_private_key().unwrap()
+        );
+        assert_eq!(&account.get_private_key().unwrap()[..32], &seed[..]);
+
+        for len in [0, 31, 33, 64] {
+            match Account::from_seed(&vec![1u8; len]) {
+                Err(SolanaUnityError {
+                    kind: ErrorKind::InvalidInput,
+                    message: msg,
+                    ..
+                }) => assert!(msg.contains("32 bytes")),
+                _ => panic!("Expected InvalidInput for a {}-byte seed", len),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_private_key_seed_hint() {
+        match Account::from_private_key(&[7u8; 32]) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("from_seed")),
+            _ => panic!("Expected WalletError with a from_seed hint"),
+        }
+    }
+
+    #[test]
+    fn test_sign_offchain_message() {
+        let account = Account::generate();
+        let pubkey = account.get_pubkey().unwrap();
+        let message = b"Sign in to Example Game";
+
+        let signature = account.sign_offchain_message(message).unwrap();
+        assert!(Account::verify_offchain_message(&pubkey, message, &signature).unwrap());
+
+        // The envelope is what gets signed, not the raw message
+        assert!(!Account::verify_signature(&pubkey, message, &signature).unwrap());
+        let envelope = crate::offchain_message::serialize(message).unwrap();
+        assert!(Account::verify_signature(&pubkey, &envelope, &signature).unwrap());
+
+        assert!(
+            !Account::verify_offchain_message(&pubkey, b"Sign in to Other Game", &signature)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sign_offchain_message_too_long() {
+        let account = Account::generate();
+        let message = vec![b'a'; crate::offchain_message::MAX_LEN + 1];
+
+        match account.sign_offchain_message(&message) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => {
+                assert!(msg.contains(&crate::offchain_message::MAX_LEN.to_string()))
+            }
+            _ => panic!("Expected InvalidInput for an oversized message"),
+        }
+    }
+
+    #[test]
+    fn test_secure_memory_signing_equivalence() {
+        let mut account = Account::generate();
+        let message = b"secure memory";
+        let before = account.sign_message(message).unwrap();
+        let pubkey = account.get_pubkey().unwrap();
+
+        // Locking may legitimately fail in constrained environments; signing must not
+        let locked = account.set_secure_memory(true).unwrap();
+        assert_eq!(locked, account.is_memory_locked());
+        assert_eq!(account.sign_message(message).unwrap(), before);
+        assert_eq!(account.get_pubkey().unwrap(), pubkey);
+
+        assert!(!account.set_secure_memory(false).unwrap());
+        assert!(!account.is_memory_locked());
+        assert_eq!(account.sign_message(message).unwrap(), before);
+    }
+
+    #[test]
+    fn test_secure_memory_fallback_storage() {
+        let account = Account::generate();
+        let message = b"fallback";
+        let expected = account.sign_message(message).unwrap();
+
+        // Simulate the OS refusing to lock the pages
+        let fallback = Account {
+            pubkey: account.pubkey,
+            keypair: Some(KeypairStorage::Secure(SecureBox::with_lock(
+                copy_keypair(account.get_keypair().unwrap()).unwrap(),
+                false,
+            ))),
+        };
+        assert!(!fallback.is_memory_locked());
+        assert_eq!(fallback.sign_message(message).unwrap(), expected);
+        assert_eq!(
+            fallback.get_private_key().unwrap(),
+            account.get_private_key().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_secure_memory_requires_keypair() {
+        let mut account = Account::from_pubkey("11111111111111111111111111111111").unwrap();
+        assert!(account.set_secure_memory(true).is_err());
+        assert!(!account.is_memory_locked());
+    }
 }
@@ -0,0 +1,202 @@
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::error::SolanaUnityError;
+
+/// Accounts an `RpcClient` fetched recently, kept for a fixed time. When full, the least
+/// recently used entry makes room for a new one.
+#[derive(Debug)]
+pub struct AccountCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: HashMap<(Pubkey, CommitmentLevel), Entry>,
+    // Stamps each use, so the smallest stamp is the least recently used entry
+    uses: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    account: Account,
+    fetched: Instant,
+    last_used: u64,
+}
+
+impl AccountCache {
+    pub const MAX_TTL_MS: u64 = 3_600_000;
+    pub const MAX_ENTRIES: usize = 100_000;
+
+    /// A cache keeping accounts for `ttl_ms` milliseconds, at most `max_entries` at a time
+    pub fn new(ttl_ms: u64, max_entries: usize) -> Result<Self, SolanaUnityError> {
+        if !(1..=Self::MAX_TTL_MS).contains(&ttl_ms) {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Account cache ttl_ms must be between 1 and {}, got {}",
+                Self::MAX_TTL_MS,
+                ttl_ms
+            )));
+        }
+        if !(1..=Self::MAX_ENTRIES).contains(&max_entries) {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Account cache max_entries must be between 1 and {}, got {}",
+                Self::MAX_ENTRIES,
+                max_entries
+            )));
+        }
+
+        Ok(Self {
+            ttl: Duration::from_millis(ttl_ms),
+            max_entries,
+            entries: HashMap::new(),
+            uses: 0,
+        })
+    }
+
+    /// The cached account, unless it is missing or older than the TTL at `now`
+    pub fn get(
+        &mut self,
+        pubkey: &Pubkey,
+        commitment: CommitmentLevel,
+        now: Instant,
+    ) -> Option<Account> {
+        let key = (*pubkey, commitment);
+        let entry = self.entries.get_mut(&key)?;
+        if now.saturating_duration_since(entry.fetched) >= self.ttl {
+            self.entries.remove(&key);
+            return None;
+        }
+
+        self.uses += 1;
+        entry.last_used = self.uses;
+        Some(entry.account.clone())
+    }
+
+    /// Caches `account` as fetched at `now`, evicting the least recently used entry if the
+    /// cache is full
+    pub fn insert(
+        &mut self,
+        pubkey: &Pubkey,
+        commitment: CommitmentLevel,
+        account: Account,
+        now: Instant,
+    ) {
+        let key = (*pubkey, commitment);
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.max_entries {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.uses += 1;
+        self.entries.insert(
+            key,
+            Entry {
+                account,
+                fetched: now,
+                last_used: self.uses,
+            },
+        );
+    }
+
+    /// Drops `pubkey` at every commitment
+    pub fn invalidate(&mut self, pubkey: &Pubkey) {
+        self.entries.retain(|(cached, _), _| cached != pubkey);
+    }
+
+    /// Drops every entry
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIRMED: CommitmentLevel = CommitmentLevel::Confirmed;
+
+    fn account(lamports: u64) -> Account {
+        Account {
+            lamports,
+            ..Account::default()
+        }
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let mut cache = AccountCache::new(1000, 10).unwrap();
+        let pubkey = Pubkey::new_unique();
+        let start = Instant::now();
+
+        cache.insert(&pubkey, CONFIRMED, account(1), start);
+        let fresh = start + Duration::from_millis(999);
+        assert_eq!(cache.get(&pubkey, CONFIRMED, fresh), Some(account(1)));
+        // Using an entry does not extend its life
+        let expired = start + Duration::from_millis(1000);
+        assert_eq!(cache.get(&pubkey, CONFIRMED, expired), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted() {
+        let mut cache = AccountCache::new(60_000, 3).unwrap();
+        let now = Instant::now();
+        let pubkeys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+
+        for (lamports, pubkey) in pubkeys[..3].iter().enumerate() {
+            cache.insert(pubkey, CONFIRMED, account(lamports as u64), now);
+        }
+        // The first entry is used, so the second is now the least recently used
+        assert!(cache.get(&pubkeys[0], CONFIRMED, now).is_some());
+        cache.insert(&pubkeys[3], CONFIRMED, account(3), now);
+        assert_eq!(cache.len(), 3);
+        assert!(cache.get(&pubkeys[1], CONFIRMED, now).is_none());
+
+        // Replacing an entry evicts nothing
+        cache.insert(&pubkeys[0], CONFIRMED, account(10), now);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get(&pubkeys[0], CONFIRMED, now), Some(account(10)));
+        assert!(cache.get(&pubkeys[2], CONFIRMED, now).is_some());
+        assert!(cache.get(&pubkeys[3], CONFIRMED, now).is_some());
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let mut cache = AccountCache::new(60_000, 10).unwrap();
+        let now = Instant::now();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        cache.insert(&first, CONFIRMED, account(1), now);
+        cache.insert(&first, CommitmentLevel::Finalized, account(1), now);
+        cache.insert(&second, CONFIRMED, account(2), now);
+        cache.invalidate(&first);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&second, CONFIRMED, now).is_some());
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_settings_are_validated() {
+        assert!(AccountCache::new(0, 10).is_err());
+        assert!(AccountCache::new(AccountCache::MAX_TTL_MS + 1, 10).is_err());
+        assert!(AccountCache::new(1000, 0).is_err());
+        assert!(AccountCache::new(1000, AccountCache::MAX_ENTRIES + 1).is_err());
+        assert!(AccountCache::new(1000, 1).is_ok());
+    }
+}
@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::ErrorKind as IoErrorKind;
+use std::str::FromStr;
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::SolanaUnityError;
+
+/// Version of the address book file. Files of a newer version are refused rather than
+/// rewritten in an older format.
+pub const ADDRESS_BOOK_VERSION: u32 = 1;
+
+/// Longest label an address book accepts, in characters
+pub const MAX_LABEL_LEN: usize = 64;
+
+/// A saved address and the nickname it goes by
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressBookEntry {
+    pub label: String,
+    pub pubkey: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    // Fields written by a later version, kept so saving does not drop them
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AddressBookFile {
+    version: u32,
+    entries: Vec<AddressBookEntry>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+/// Friends' addresses under nicknames. Labels are unique ignoring case, and each address is
+/// saved under one label only, so lookups either way give a single entry.
+#[derive(Debug, Default)]
+pub struct AddressBook {
+    entries: Vec<AddressBookEntry>,
+    extra: Map<String, Value>,
+}
+
+impl AddressBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Saves `pubkey` under `label`. Fails if the label is taken or the address is already
+    /// saved under another label; use `set` to replace an entry.
+    pub fn add(
+        &mut self,
+        label: &str,
+        pubkey: &str,
+        memo: Option<&str>,
+    ) -> Result<(), SolanaUnityError> {
+        let entry = Self::entry(label, pubkey, memo)?;
+        if let Some(existing) = self.lookup_by_label(&entry.label) {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "The label '{}' is already used for {}",
+                existing.label, existing.pubkey
+            )));
+        }
+        self.check_pubkey_unused(&entry)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Like `add`, but replaces the entry already saved under `label`, keeping its place
+    pub fn set(
+        &mut self,
+        label: &str,
+        pubkey: &str,
+        memo: Option<&str>,
+    ) -> Result<(), SolanaUnityError> {
+        let entry = Self::entry(label, pubkey, memo)?;
+        self.check_pubkey_unused(&entry)?;
+        match self.position(&entry.label) {
+            Some(index) => self.entries[index] = entry,
+            None => self.entries.push(entry),
+        }
+        Ok(())
+    }
+
+    /// Removes the entry under `label`, returning whether there was one
+    pub fn remove(&mut self, label: &str) -> bool {
+        match self.position(label.trim()) {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The entry under `label`, ignoring case
+    pub fn lookup_by_label(&self, label: &str) -> Option<&AddressBookEntry> {
+        self.position(label.trim())
+            .map(|index| &self.entries[index])
+    }
+
+    /// The entry saving `pubkey`, to show a nickname instead of an address
+    pub fn reverse_lookup(&self, pubkey: &str) -> Option<&AddressBookEntry> {
+        let pubkey = pubkey.trim();
+        self.entries.iter().find(|entry| entry.pubkey == pubkey)
+    }
+
+    /// Entries in the order they were added
+    pub fn entries(&self) -> &[AddressBookEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entries as a JSON array of `{"label","pubkey","memo"}` objects
+    pub fn list_json(&self) -> Result<String, SolanaUnityError> {
+        serde_json::to_string(&self.entries).map_err(|e| {
+            SolanaUnityError::SerializationError(format!("Failed to serialize address book: {}", e))
+        })
+    }
+
+    /// Reads an address book file. A file that is not valid JSON or holds an invalid entry
+    /// is a SerializationError naming where the problem is; the file is left as it is.
+    pub fn load_from_file(path: &str) -> Result<Self, SolanaUnityError> {
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            SolanaUnityError::WalletError(match e.kind() {
+                IoErrorKind::NotFound => format!("Address book file not found: {}", path),
+                IoErrorKind::PermissionDenied => {
+                    format!("Permission denied reading address book file {}", path)
+                }
+                _ => format!("Failed to read address book file {}: {}", path, e),
+            })
+        })?;
+        Self::from_json(&json).map_err(|mut e| {
+            e.message = format!("{} in {}", e.message, path);
+            e
+        })
+    }
+
+    /// Writes the address book to `path`, replacing it only once the new contents are
+    /// fully written, so a failed save leaves the previous file intact
+    pub fn save_to_file(&self, path: &str) -> Result<(), SolanaUnityError> {
+        let json = self.to_json()?;
+        let temp_path = format!("{}.tmp", path);
+        std::fs::write(&temp_path, json).map_err(|e| {
+            SolanaUnityError::WalletError(format!(
+                "Failed to write address book file {}: {}",
+                temp_path, e
+            ))
+        })?;
+        std::fs::rename(&temp_path, path).map_err(|e| {
+            let _ = std::fs::remove_file(&temp_path);
+            SolanaUnityError::WalletError(format!(
+                "Failed to replace address book file {}: {}",
+                path, e
+            ))
+        })
+    }
+
+    /// The address book in its file format, `{"version":1,"entries":[...]}`
+    pub fn to_json(&self) -> Result<String, SolanaUnityError> {
+        let file = AddressBookFile {
+            version: ADDRESS_BOOK_VERSION,
+            entries: self.entries.clone(),
+            extra: self.extra.clone(),
+        };
+        serde_json::to_string_pretty(&file).map_err(|e| {
+            SolanaUnityError::SerializationError(format!("Failed to serialize address book: {}", e))
+        })
+    }
+
+    /// Reads the file format of `to_json`. Fields this version does not know are kept.
+    pub fn from_json(json: &str) -> Result<Self, SolanaUnityError> {
+        let file: AddressBookFile = serde_json::from_str(json).map_err(|e| {
+            // serde_json names the line and column
+            SolanaUnityError::SerializationError(format!("Invalid address book: {}", e))
+        })?;
+        if file.version > ADDRESS_BOOK_VERSION {
+            return Err(SolanaUnityError::SerializationError(format!(
+                "Address book version {} is newer than the supported version {}",
+                file.version, ADDRESS_BOOK_VERSION
+            )));
+        }
+
+        let mut book = Self {
+            entries: Vec::with_capacity(file.entries.len()),
+            extra: file.extra,
+        };
+        for (index, entry) in file.entries.into_iter().enumerate() {
+            let extra = entry.extra;
+            book.add(&entry.label, &entry.pubkey, entry.memo.as_deref())
+                .map_err(|e| {
+                    SolanaUnityError::SerializationError(format!(
+                        "Invalid address book entry {}: {}",
+                        index, e.message
+                    ))
+                })?;
+            book.entries[index].extra = extra;
+        }
+        Ok(book)
+    }
+
+    fn entry(
+        label: &str,
+        pubkey: &str,
+        memo: Option<&str>,
+    ) -> Result<AddressBookEntry, SolanaUnityError> {
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(SolanaUnityError::InvalidInput(
+                "Address book label must not be empty",
+            ));
+        }
+        if label.chars().count() > MAX_LABEL_LEN {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Address book label is longer than {} characters",
+                MAX_LABEL_LEN
+            )));
+        }
+        let pubkey = pubkey.trim();
+        Pubkey::from_str(pubkey).map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Invalid pubkey: {}", e)).with_pubkey(pubkey)
+        })?;
+
+        Ok(AddressBookEntry {
+            label: label.to_string(),
+            pubkey: pubkey.to_string(),
+            memo: memo.filter(|memo| !memo.is_empty()).map(str::to_string),
+            extra: Map::new(),
+        })
+    }
+
+    fn position(&self, label: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.label.to_lowercase() == label.to_lowercase())
+    }
+
+    // The address of `entry` may only be saved under its own label
+    fn check_pubkey_unused(&self, entry: &AddressBookEntry) -> Result<(), SolanaUnityError> {
+        match self.reverse_lookup(&entry.pubkey) {
+            Some(existing) if existing.label.to_lowercase() != entry.label.to_lowercase() => {
+                Err(SolanaUnityError::InvalidInput(format!(
+                    "{} is already saved as '{}'",
+                    entry.pubkey, existing.label
+                ))
+                .with_pubkey(&entry.pubkey))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn pubkey() -> String {
+        Pubkey::new_unique().to_string()
+    }
+
+    #[test]
+    fn test_add_and_lookup() {
+        let mut book = AddressBook::new();
+        let alice = pubkey();
+        let bob = pubkey();
+        book.add("Alice", &alice, Some("guild leader")).unwrap();
+        book.add(" Bob ", &bob, None).unwrap();
+
+        assert_eq!(book.len(), 2);
+        assert_eq!(book.lookup_by_label("alice").unwrap().pubkey, alice);
+        assert_eq!(book.lookup_by_label("Bob").unwrap().memo, None);
+        assert_eq!(book.reverse_lookup(&bob).unwrap().label, "Bob");
+        assert!(book.reverse_lookup(&pubkey()).is_none());
+
+        let list: Value = serde_json::from_str(&book.list_json().unwrap()).unwrap();
+        assert_eq!(
+            list,
+            serde_json::json!([
+                {"label": "Alice", "pubkey": alice, "memo": "guild leader"},
+                {"label": "Bob", "pubkey": bob},
+            ])
+        );
+
+        assert!(book.remove("ALICE"));
+        assert!(!book.remove("Alice"));
+        assert_eq!(book.len(), 1);
+    }
+
+    #[test]
+    fn test_duplicates_and_validation() {
+        let mut book = AddressBook::new();
+        let alice = pubkey();
+        book.add("Alice", &alice, None).unwrap();
+
+        // A label is taken whatever its case, and an address has one label
+        let error = book.add("ALICE", &pubkey(), None).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidInput);
+        let error = book.add("Alice's alt", &alice, None).unwrap_err();
+        assert!(
+            error.message.contains("already saved as 'Alice'"),
+            "{}",
+            error
+        );
+
+        // set replaces in place
+        let new_alice = pubkey();
+        book.add("Carol", &pubkey(), None).unwrap();
+        book.set("alice", &new_alice, Some("new wallet")).unwrap();
+        assert_eq!(book.entries()[0].pubkey, new_alice);
+        assert_eq!(book.entries()[0].label, "alice");
+        assert!(book.set("Dave", &new_alice, None).is_err());
+
+        assert!(book.add("", &pubkey(), None).is_err());
+        assert!(book
+            .add(&"x".repeat(MAX_LABEL_LEN + 1), &pubkey(), None)
+            .is_err());
+        let error = book.add("Eve", "not-a-pubkey", None).unwrap_err();
+        assert_eq!(error.context.pubkey.as_deref(), Some("not-a-pubkey"));
+        assert_eq!(book.len(), 2);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("friends.json");
+        let path = path.to_str().unwrap();
+
+        let mut book = AddressBook::new();
+        book.add("Alice", &pubkey(), Some("met in raid")).unwrap();
+        book.add("Bob", &pubkey(), None).unwrap();
+        book.save_to_file(path).unwrap();
+
+        let loaded = AddressBook::load_from_file(path).unwrap();
+        assert_eq!(loaded.entries(), book.entries());
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+
+        let error = AddressBook::load_from_file(&format!("{}.missing", path)).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Wallet);
+    }
+
+    #[test]
+    fn test_unknown_fields_survive_a_save() {
+        let alice = pubkey();
+        let json = format!(
+            r#"{{"version": 1, "syncedAt": 1700000000, "entries": [
+                {{"label": "Alice", "pubkey": "{}", "avatar": "fox.png"}}
+            ]}}"#,
+            alice
+        );
+        let book = AddressBook::from_json(&json).unwrap();
+        assert_eq!(book.lookup_by_label("Alice").unwrap().pubkey, alice);
+
+        let saved: Value = serde_json::from_str(&book.to_json().unwrap()).unwrap();
+        assert_eq!(saved["syncedAt"], 1700000000);
+        assert_eq!(saved["entries"][0]["avatar"], "fox.png");
+        assert_eq!(saved["version"], ADDRESS_BOOK_VERSION);
+    }
+
+    #[test]
+    fn test_corrupted_file_is_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("friends.json");
+        let path = path.to_str().unwrap();
+        let corrupted = "{\n  \"version\": 1,\n  \"entries\": [\n    {\"label\": \"Alice\",,}\n";
+        std::fs::write(path, corrupted).unwrap();
+
+        let error = AddressBook::load_from_file(path).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Serialization);
+        assert!(error.message.contains("at line 4 column"), "{}", error);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), corrupted);
+
+        let invalid = r#"{"version": 1, "entries": [{"label": "Bob", "pubkey": "nope"}]}"#;
+        let error = AddressBook::from_json(invalid).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Serialization);
+        assert!(error.message.contains("entry 0"), "{}", error);
+
+        let future = r#"{"version": 2, "entries": []}"#;
+        assert!(AddressBook::from_json(future).is_err());
+    }
+}
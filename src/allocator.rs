@@ -0,0 +1,99 @@
+use std::os::raw::c_void;
+use std::ptr;
+use std::sync::Mutex;
+
+/// Allocates `size` bytes. The result must be aligned like `malloc`'s, or be null on failure.
+pub type AllocFn = extern "C" fn(size: usize, user_data: *mut c_void) -> *mut c_void;
+/// Releases a pointer previously returned by the matching `AllocFn`.
+pub type FreeFn = extern "C" fn(ptr: *mut c_void, user_data: *mut c_void);
+
+struct Hooks {
+    alloc: AllocFn,
+    free: FreeFn,
+    user_data: *mut c_void,
+}
+
+// Hooks are leaked and never mutated; the user data pointer is only handed back to them
+unsafe impl Send for Hooks {}
+unsafe impl Sync for Hooks {}
+
+// Installed hooks, or None for the system allocator. Each set leaks one small Hooks value
+// so buffers produced under earlier hooks can still reach their own free function.
+static HOOKS: Mutex<Option<&'static Hooks>> = Mutex::new(None);
+
+// Every buffer is preceded by a header holding the Hooks that produced it (null for the
+// system allocator). 16 bytes keeps the payload at malloc's alignment.
+const HEADER: usize = 16;
+
+/// Routes later buffer allocations through `alloc` and `free`. Buffers that were already
+/// handed out keep the allocator that produced them and are released with it.
+pub fn set_allocator(alloc: AllocFn, free: FreeFn, user_data: *mut c_void) {
+    let hooks: &'static Hooks = Box::leak(Box::new(Hooks {
+        alloc,
+        free,
+        user_data,
+    }));
+    *HOOKS.lock().unwrap_or_else(|e| e.into_inner()) = Some(hooks);
+}
+
+/// Goes back to the system allocator for later allocations
+pub fn reset_allocator() {
+    *HOOKS.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Allocates a buffer for the host, or null on failure. Release it with `free`.
+pub fn alloc(size: usize) -> *mut u8 {
+    let hooks = *HOOKS.lock().unwrap_or_else(|e| e.into_inner());
+    let total = match size.checked_add(HEADER) {
+        Some(total) => total,
+        None => return ptr::null_mut(),
+    };
+
+    let base = match hooks {
+        Some(hooks) => (hooks.alloc)(total, hooks.user_data),
+        None => unsafe { libc::malloc(total) },
+    } as *mut u8;
+    if base.is_null() {
+        return ptr::null_mut();
+    }
+
+    let tag = hooks.map_or(ptr::null(), |hooks| hooks as *const Hooks);
+    unsafe {
+        (base as *mut *const Hooks).write(tag);
+        base.add(HEADER)
+    }
+}
+
+/// Releases a buffer from `alloc` with the allocator that produced it
+///
+/// # Safety
+/// `ptr` must be null or come from `alloc` and not have been freed yet.
+pub unsafe fn free(ptr: *mut u8) {
+    if ptr.is_null() {
+        return;
+    }
+
+    let base = ptr.sub(HEADER);
+    let tag = (base as *const *const Hooks).read();
+    match tag.as_ref() {
+        Some(hooks) => (hooks.free)(base as *mut c_void, hooks.user_data),
+        None => libc::free(base as *mut c_void),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_allocation_round_trip() {
+        let ptr = alloc(32);
+        assert!(!ptr.is_null());
+        assert_eq!(ptr as usize % std::mem::align_of::<u64>(), 0);
+        unsafe {
+            ptr::write_bytes(ptr, 0xAB, 32);
+            free(ptr);
+            free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::error::SolanaUnityError;
+
+/// How `ui_amount_to_raw` treats digits beyond the token's decimals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Toward zero, so a payment never exceeds what the user typed
+    Down,
+    /// Away from zero, e.g. for minimums that must be met
+    Up,
+    /// To the nearest raw unit, ties away from zero
+    Nearest,
+}
+
+/// `raw` base units as a decimal string with exactly `decimals` fraction digits, e.g.
+/// (1_500_000, 6) -> "1.500000". No floating point is involved, so every u64 renders exactly.
+pub fn format_amount(raw: u64, decimals: u8) -> String {
+    let digits = raw.to_string();
+    if decimals == 0 {
+        return digits;
+    }
+
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    format!("{}.{}", whole, fraction)
+}
+
+/// `format_amount` without trailing fraction zeros, e.g. (1_500_000, 6) -> "1.5" and
+/// (1_000_000, 6) -> "1"
+pub fn format_amount_trimmed(raw: u64, decimals: u8) -> String {
+    let formatted = format_amount(raw, decimals);
+    if !formatted.contains('.') {
+        return formatted;
+    }
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Parses a decimal string such as "1.5" into base units of a token with `decimals`
+/// decimals. Signs, exponents, separators and surrounding whitespace are rejected, as are
+/// amounts finer than one base unit and amounts that do not fit in a u64. Trailing zeros
+/// past `decimals` are accepted, since they do not change the value.
+pub fn parse_amount(s: &str, decimals: u8) -> Result<u64, SolanaUnityError> {
+    let (whole, fraction) = split_decimal(s)?;
+    let excess = fraction.len().saturating_sub(decimals as usize);
+    let (fraction, dropped) = fraction.split_at(fraction.len() - excess);
+    if dropped.bytes().any(|digit| digit != b'0') {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Amount {} has more than {} decimal places",
+            s, decimals
+        )));
+    }
+
+    to_raw(whole, fraction, decimals).ok_or_else(|| too_large(s))
+}
+
+/// Converts a UI amount such as 1.5 into base units of a token with `decimals` decimals.
+/// The amount is taken as the shortest decimal that reads back as the same f64, e.g. 0.29
+/// rather than 0.28999999999999998, and digits past `decimals` are rounded as `rounding`
+/// says.
+pub fn ui_amount_to_raw(
+    ui_amount: f64,
+    decimals: u8,
+    rounding: Rounding,
+) -> Result<u64, SolanaUnityError> {
+    if !ui_amount.is_finite() || ui_amount < 0.0 {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Amount must be a finite number of at least 0, got {}",
+            ui_amount
+        )));
+    }
+
+    // Display gives the shortest round-trip digits and never an exponent
+    let text = ui_amount.to_string();
+    let (whole, fraction) = split_decimal(&text)?;
+    let kept = fraction.len().min(decimals as usize);
+    let (fraction, dropped) = fraction.split_at(kept);
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => dropped.bytes().any(|digit| digit != b'0'),
+        Rounding::Nearest => dropped.bytes().next().is_some_and(|digit| digit >= b'5'),
+    };
+
+    let raw = to_raw(whole, fraction, decimals).ok_or_else(|| too_large(&text))?;
+    if round_up {
+        raw.checked_add(1).ok_or_else(|| too_large(&text))
+    } else {
+        Ok(raw)
+    }
+}
+
+// The digits before and after the point of a plain decimal such as "12.34"
+fn split_decimal(s: &str) -> Result<(&str, &str), SolanaUnityError> {
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+    let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !all_digits(whole) || !all_digits(fraction) || s.ends_with('.') {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Invalid amount: {:?}",
+            s
+        )));
+    }
+    Ok((whole, fraction))
+}
+
+// `whole`.`fraction` in units of 10^-decimals, with `fraction` at most `decimals` digits;
+// None on overflow
+fn to_raw(whole: &str, fraction: &str, decimals: u8) -> Option<u64> {
+    let padding = decimals as usize - fraction.len();
+    whole
+        .bytes()
+        .chain(fraction.bytes())
+        .chain("0".repeat(padding).bytes())
+        .try_fold(0u64, |raw, digit| {
+            raw.checked_mul(10)?.checked_add((digit - b'0') as u64)
+        })
+}
+
+fn too_large(amount: &str) -> SolanaUnityError {
+    SolanaUnityError::InvalidInput(format!("Amount {} is too large for a u64", amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_amount(0, 9), "0.000000000");
+        assert_eq!(format_amount(1, 9), "0.000000001");
+        assert_eq!(format_amount(1_500_000_000, 9), "1.500000000");
+        assert_eq!(format_amount(u64::MAX, 9), "18446744073.709551615");
+        assert_eq!(format_amount(0, 0), "0");
+        assert_eq!(format_amount(1, 0), "1");
+        assert_eq!(format_amount(u64::MAX, 0), "18446744073709551615");
+        assert_eq!(format_amount(1, 25), "0.0000000000000000000000001");
+
+        assert_eq!(format_amount_trimmed(0, 9), "0");
+        assert_eq!(format_amount_trimmed(1, 9), "0.000000001");
+        assert_eq!(format_amount_trimmed(1_500_000_000, 9), "1.5");
+        assert_eq!(format_amount_trimmed(2_000_000, 6), "2");
+        assert_eq!(format_amount_trimmed(100, 0), "100");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_amount("0", 9).unwrap(), 0);
+        assert_eq!(parse_amount("0.000000001", 9).unwrap(), 1);
+        assert_eq!(parse_amount("1", 9).unwrap(), 1_000_000_000);
+        assert_eq!(parse_amount("1.5", 6).unwrap(), 1_500_000);
+        assert_eq!(parse_amount("18446744073.709551615", 9).unwrap(), u64::MAX);
+        assert_eq!(parse_amount("18446744073709551615", 0).unwrap(), u64::MAX);
+        assert_eq!(parse_amount("1.000000000", 6).unwrap(), 1_000_000);
+        assert_eq!(parse_amount("007", 0).unwrap(), 7);
+
+        let error = parse_amount("1.000000001", 6).unwrap_err();
+        assert_eq!(
+            error.message,
+            "Amount 1.000000001 has more than 6 decimal places"
+        );
+        assert!(parse_amount("1.5", 0).is_err());
+        assert!(parse_amount("18446744073.709551616", 9)
+            .unwrap_err()
+            .message
+            .contains("too large"));
+        assert!(parse_amount("18446744073709551616", 0).is_err());
+        assert!(parse_amount("1", 20).is_err());
+
+        for invalid in [
+            "", ".", "1.", ".5", "-1", "+1", "1e9", " 1", "1,000", "1.2.3", "١",
+        ] {
+            let error = parse_amount(invalid, 9).unwrap_err();
+            assert!(error.message.starts_with("Invalid amount"), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for decimals in [0, 6, 9] {
+            for raw in [0, 1, 10, 999_999, 1_000_000, u64::MAX] {
+                assert_eq!(
+                    parse_amount(&format_amount(raw, decimals), decimals).unwrap(),
+                    raw
+                );
+                assert_eq!(
+                    parse_amount(&format_amount_trimmed(raw, decimals), decimals).unwrap(),
+                    raw
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ui_amount_to_raw() {
+        // 0.29 * 100 is 28.999999999999996 in floating point
+        assert_eq!(ui_amount_to_raw(0.29, 2, Rounding::Down).unwrap(), 29);
+        assert_eq!(
+            ui_amount_to_raw(1.5, 9, Rounding::Down).unwrap(),
+            1_500_000_000
+        );
+        assert_eq!(ui_amount_to_raw(0.0, 9, Rounding::Up).unwrap(), 0);
+
+        assert_eq!(
+            ui_amount_to_raw(1.0000015, 6, Rounding::Down).unwrap(),
+            1_000_001
+        );
+        assert_eq!(
+            ui_amount_to_raw(1.0000015, 6, Rounding::Up).unwrap(),
+            1_000_002
+        );
+        assert_eq!(
+            ui_amount_to_raw(1.0000015, 6, Rounding::Nearest).unwrap(),
+            1_000_002
+        );
+        assert_eq!(
+            ui_amount_to_raw(1.0000014, 6, Rounding::Nearest).unwrap(),
+            1_000_001
+        );
+        assert_eq!(ui_amount_to_raw(2.5, 0, Rounding::Nearest).unwrap(), 3);
+        assert_eq!(ui_amount_to_raw(0.000000001, 6, Rounding::Up).unwrap(), 1);
+
+        for invalid in [f64::NAN, f64::INFINITY, -1.0] {
+            assert!(ui_amount_to_raw(invalid, 9, Rounding::Down).is_err());
+        }
+        assert!(ui_amount_to_raw(1e30, 9, Rounding::Down)
+            .unwrap_err()
+            .message
+            .contains("too large"));
+    }
+}
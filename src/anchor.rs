@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::SolanaUnityError;
+
+/// Programs built with Anchor number their own errors from here up
+pub const ERROR_CODE_OFFSET: u32 = 6000;
+
+/// A custom program error with the name and message its program gave it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedProgramError {
+    pub code: u32,
+    /// e.g. "ConstraintSeeds" or "SlippageExceeded"
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// True for errors defined by Anchor itself rather than by the program
+    pub builtin: bool,
+}
+
+/// One entry of an IDL's `errors` array
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IdlError {
+    pub code: u32,
+    pub name: String,
+    #[serde(default)]
+    pub msg: Option<String>,
+}
+
+// Anchor's own errors (anchor_lang::error::ErrorCode), by number
+const BUILTIN_ERRORS: &[(u32, &str, &str)] = &[
+    (100, "InstructionMissing", "8 byte instruction identifier not provided"),
+    (101, "InstructionFallbackNotFound", "Fallback functions are not supported"),
+    (102, "InstructionDidNotDeserialize", "The program could not deserialize the given instruction"),
+    (103, "InstructionDidNotSerialize", "The program could not serialize the given instruction"),
+    (1000, "IdlInstructionStub", "The program was compiled without idl instructions"),
+    (1001, "IdlInstructionInvalidProgram", "Invalid program given to the IDL instruction"),
+    (1500, "EventInstructionStub", "The program was compiled without `event-cpi` feature"),
+    (2000, "ConstraintMut", "A mut constraint was violated"),
+    (2001, "ConstraintHasOne", "A has one constraint was violated"),
+    (2002, "ConstraintSigner", "A signer constraint was violated"),
+    (2003, "ConstraintRaw", "A raw constraint was violated"),
+    (2004, "ConstraintOwner", "An owner constraint was violated"),
+    (2005, "ConstraintRentExempt", "A rent exemption constraint was violated"),
+    (2006, "ConstraintSeeds", "A seeds constraint was violated"),
+    (2007, "ConstraintExecutable", "An executable constraint was violated"),
+    (2008, "ConstraintState", "Deprecated Error, feel free to replace with something else"),
+    (2009, "ConstraintAssociated", "An associated constraint was violated"),
+    (2010, "ConstraintAssociatedInit", "An associated init constraint was violated"),
+    (2011, "ConstraintClose", "A close constraint was violated"),
+    (2012, "ConstraintAddress", "An address constraint was violated"),
+    (2013, "ConstraintZero", "Expected zero account discriminant"),
+    (2014, "ConstraintTokenMint", "A token mint constraint was violated"),
+    (2015, "ConstraintTokenOwner", "A token owner constraint was violated"),
+    (2016, "ConstraintMintMintAuthority", "A mint mint authority constraint was violated"),
+    (2017, "ConstraintMintFreezeAuthority", "A mint freeze authority constraint was violated"),
+    (2018, "ConstraintMintDecimals", "A mint decimals constraint was violated"),
+    (2019, "ConstraintSpace", "A space constraint was violated"),
+    (2020, "ConstraintAccountIsNone", "A required account for the constraint is None"),
+    (2500, "RequireViolated", "A require expression was violated"),
+    (2501, "RequireEqViolated", "A require_eq expression was violated"),
+    (2502, "RequireKeysEqViolated", "A require_keys_eq expression was violated"),
+    (2503, "RequireNeqViolated", "A require_neq expression was violated"),
+    (2504, "RequireKeysNeqViolated", "A require_keys_neq expression was violated"),
+    (2505, "RequireGtViolated", "A require_gt expression was violated"),
+    (2506, "RequireGteViolated", "A require_gte expression was violated"),
+    (3000, "AccountDiscriminatorAlreadySet", "The account discriminator was already set on this account"),
+    (3001, "AccountDiscriminatorNotFound", "No 8 byte discriminator was found on the account"),
+    (3002, "AccountDiscriminatorMismatch", "8 byte discriminator did not match what was expected"),
+    (3003, "AccountDidNotDeserialize", "Failed to deserialize the account"),
+    (3004, "AccountDidNotSerialize", "Failed to serialize the account"),
+    (3005, "AccountNotEnoughKeys", "Not enough account keys given to the instruction"),
+    (3006, "AccountNotMutable", "The given account is not mutable"),
+    (3007, "AccountOwnedByWrongProgram", "The given account is owned by a different program than expected"),
+    (3008, "InvalidProgramId", "Program ID was not as expected"),
+    (3009, "InvalidProgramExecutable", "Program account is not executable"),
+    (3010, "AccountNotSigner", "The given account did not sign"),
+    (3011, "AccountNotSystemOwned", "The given account is not owned by the system program"),
+    (3012, "AccountNotInitialized", "The program expected this account to be already initialized"),
+    (3013, "AccountNotProgramData", "The given account is not a program data account"),
+    (3014, "AccountNotAssociatedTokenAccount", "The given account is not the associated token account"),
+    (3015, "AccountSysvarMismatch", "The given public key does not match the required sysvar"),
+    (3016, "AccountReallocExceedsLimit", "The account reallocation exceeds the MAX_PERMITTED_DATA_INCREASE limit"),
+    (3017, "AccountDuplicateReallocs", "The account was duplicated for more than one reallocation"),
+    (4100, "DeclaredProgramIdMismatch", "The declared program id does not match the actual program id"),
+    (4101, "TryingToInitPayerAsProgramAccount", "You cannot/should not initialize the payer account as a program account"),
+    (4102, "InvalidNumericConversion", "The program could not perform the numeric conversion, out of range integral type conversion attempted"),
+    (5000, "Deprecated", "The API being used is deprecated and should no longer be used"),
+];
+
+// Names the errors Anchor reserves a range for but that are missing from the table above,
+// e.g. ones added by a newer Anchor release
+fn builtin_range(code: u32) -> Option<&'static str> {
+    match code {
+        100..=999 => Some("instruction"),
+        1000..=1499 => Some("IDL"),
+        1500..=1999 => Some("event"),
+        2000..=2499 => Some("constraint"),
+        2500..=2999 => Some("require"),
+        3000..=4099 => Some("account"),
+        4100..=4999 => Some("miscellaneous"),
+        5000 => Some("deprecated"),
+        _ => None,
+    }
+}
+
+/// The 8 bytes Anchor puts at the start of every account of type `name`, the Rust struct
+/// name such as "PlayerState": the start of sha256("account:<name>")
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("account:{}", name));
+    hash[..8].try_into().unwrap()
+}
+
+/// Reads the `errors` array of an Anchor IDL. Accepts the whole IDL or just the array.
+pub fn parse_idl_errors(idl_json: &str) -> Result<Vec<IdlError>, SolanaUnityError> {
+    let value: Value = serde_json::from_str(idl_json)?;
+    let errors = match value {
+        Value::Object(mut idl) => idl.remove("errors").unwrap_or(Value::Array(Vec::new())),
+        errors => errors,
+    };
+    serde_json::from_value(errors).map_err(|e| {
+        SolanaUnityError::InvalidInput(format!("IDL errors must be a list of code and name: {}", e))
+    })
+}
+
+// The number in a transaction error such as {"InstructionError": [1, {"Custom": 6001}]},
+// or in the node's text "custom program error: 0x1771"
+fn custom_code(err_json: &str) -> Option<u32> {
+    fn find(value: &Value) -> Option<u32> {
+        match value {
+            Value::Object(map) => match map.get("Custom").and_then(Value::as_u64) {
+                Some(code) => u32::try_from(code).ok(),
+                None => map.values().find_map(find),
+            },
+            Value::Array(values) => values.iter().find_map(find),
+            _ => None,
+        }
+    }
+
+    match serde_json::from_str::<Value>(err_json) {
+        Ok(value) => find(&value),
+        Err(_) => hex_code(err_json),
+    }
+}
+
+fn hex_code(text: &str) -> Option<u32> {
+    let hex = text.split("custom program error: 0x").nth(1)?;
+    let end = hex
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(hex.len());
+    u32::from_str_radix(&hex[..end], 16).ok()
+}
+
+// An "AnchorError ... Error Code: X. Error Number: N. Error Message: M." log line
+fn anchor_log(line: &str) -> Option<DecodedProgramError> {
+    let line = line.strip_prefix("Program log: AnchorError")?;
+    let (_, rest) = line.split_once("Error Code: ")?;
+    let (name, rest) = rest.split_once(". Error Number: ")?;
+    let (number, message) = rest.split_once(". Error Message: ")?;
+    let code = number.trim().parse().ok()?;
+    let message = message.strip_suffix('.').unwrap_or(message);
+    Some(DecodedProgramError {
+        code,
+        name: name.to_string(),
+        message: (!message.is_empty()).then(|| message.to_string()),
+        builtin: code < ERROR_CODE_OFFSET,
+    })
+}
+
+/// Works out which custom program error a failed transaction hit and what it means.
+///
+/// The code comes from `err_json` (the transaction error, e.g.
+/// `{"InstructionError":[0,{"Custom":6001}]}`), or failing that from the logs. It is
+/// named from `idl_errors` (an IDL or its `errors` array) first, then from the program's
+/// `AnchorError` log line, then from Anchor's built-in errors. Returns None when there is
+/// no custom error or nothing names it. Malformed IDL JSON is ignored; check it with
+/// `parse_idl_errors` first to report it.
+pub fn decode_error(
+    logs: &[String],
+    err_json: &str,
+    idl_errors: Option<&str>,
+) -> Option<DecodedProgramError> {
+    let logged: Vec<DecodedProgramError> =
+        logs.iter().filter_map(|line| anchor_log(line)).collect();
+    let code = custom_code(err_json)
+        .or_else(|| logged.last().map(|error| error.code))
+        .or_else(|| logs.iter().rev().find_map(|line| hex_code(line)))?;
+
+    let idl_errors = idl_errors
+        .and_then(|json| parse_idl_errors(json).ok())
+        .unwrap_or_default();
+    if let Some(error) = idl_errors.into_iter().find(|error| error.code == code) {
+        return Some(DecodedProgramError {
+            code,
+            name: error.name,
+            message: error.msg,
+            builtin: false,
+        });
+    }
+
+    if let Some(error) = logged.into_iter().rev().find(|error| error.code == code) {
+        return Some(error);
+    }
+
+    if let Some((_, name, message)) = BUILTIN_ERRORS.iter().find(|(c, _, _)| *c == code) {
+        return Some(DecodedProgramError {
+            code,
+            name: name.to_string(),
+            message: Some(message.to_string()),
+            builtin: true,
+        });
+    }
+
+    builtin_range(code).map(|range| DecodedProgramError {
+        code,
+        name: "Unknown".to_string(),
+        message: Some(format!("Unrecognized Anchor {} error", range)),
+        builtin: true,
+    })
+}
+
+/// True when the logs show an Anchor program reporting an error. Built-in Anchor numbers
+/// overlap the custom errors of other programs, so they are only trusted with this.
+pub fn logs_anchor_error(logs: &[String]) -> bool {
+    logs.iter()
+        .any(|line| line.starts_with("Program log: AnchorError"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_logs(json: &str) -> Vec<String> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_error_from_logs() {
+        let logs = fixture_logs(include_str!(
+            "../tests/fixtures/anchor/constraint_seeds.json"
+        ));
+        let err = r#"{"InstructionError":[0,{"Custom":2006}]}"#;
+
+        let decoded = decode_error(&logs, err, None).unwrap();
+        assert_eq!(decoded.code, 2006);
+        assert_eq!(decoded.name, "ConstraintSeeds");
+        assert_eq!(
+            decoded.message.as_deref(),
+            Some("A seeds constraint was violated")
+        );
+        assert!(decoded.builtin);
+
+        // The table names it without the logs too
+        assert_eq!(decode_error(&[], err, None), Some(decoded));
+        assert!(logs_anchor_error(&logs));
+    }
+
+    #[test]
+    fn test_custom_error_from_idl() {
+        let logs = fixture_logs(include_str!("../tests/fixtures/anchor/custom_error.json"));
+        let idl = include_str!("../tests/fixtures/anchor/idl.json");
+
+        // Only the IDL knows this program's error; the code comes from the failure log
+        let decoded = decode_error(&logs, "null", Some(idl)).unwrap();
+        assert_eq!(decoded.code, 6000);
+        assert_eq!(decoded.name, "StakeTooLow");
+        assert_eq!(
+            decoded.message.as_deref(),
+            Some("Stake is below the minimum for this lobby")
+        );
+        assert!(!decoded.builtin);
+
+        assert_eq!(decode_error(&logs, "null", None), None);
+        assert!(!logs_anchor_error(&logs));
+    }
+
+    #[test]
+    fn test_custom_error_from_anchor_log() {
+        let logs = vec![
+            "Program log: AnchorError occurred. Error Code: SlippageExceeded. Error Number: 6001. Error Message: Slippage tolerance exceeded.".to_string(),
+        ];
+        let decoded = decode_error(&logs, "custom program error: 0x1771", None).unwrap();
+        assert_eq!(decoded.name, "SlippageExceeded");
+        assert_eq!(
+            decoded.message.as_deref(),
+            Some("Slippage tolerance exceeded")
+        );
+        assert!(!decoded.builtin);
+    }
+
+    #[test]
+    fn test_undecodable_errors() {
+        // Not a custom error at all
+        assert_eq!(decode_error(&[], r#""BlockhashNotFound""#, None), None);
+        // A custom error outside Anchor's ranges that nothing names
+        assert_eq!(decode_error(&[], r#"{"Custom":1}"#, None), None);
+
+        let decoded = decode_error(&[], r#"{"Custom":2999}"#, None).unwrap();
+        assert_eq!(decoded.name, "Unknown");
+        assert!(decoded.builtin);
+
+        assert!(parse_idl_errors("[{\"code\": 6000}]").is_err());
+        assert_eq!(parse_idl_errors("{\"name\": \"game\"}").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_account_discriminator() {
+        // As derived by anchor-lang's #[account]
+        assert_eq!(
+            account_discriminator("PlayerState"),
+            [56, 3, 60, 86, 174, 16, 244, 195]
+        );
+        assert_eq!(
+            account_discriminator("NewAccount"),
+            [176, 95, 4, 118, 91, 177, 125, 232]
+        );
+    }
+}
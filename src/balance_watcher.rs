@@ -0,0 +1,605 @@
+use serde::Serialize;
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::SolanaUnityError;
+use crate::instruction::TokenInstructions;
+use crate::logging::LOG_WARN;
+use crate::native_log;
+use crate::rpc::RpcClient;
+use crate::runtime;
+
+/// Events kept for `poll_event`. Past this the oldest are dropped, so a watcher nobody
+/// polls does not grow without bound.
+pub const MAX_QUEUED_EVENTS: usize = 4096;
+
+// Length of an SPL token account, and where its amount is. Token-2022 accounts with
+// extensions are longer but start with the same layout.
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_AMOUNT_OFFSET: usize = 64;
+
+/// What the balance of a watched address is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BalanceKind {
+    /// Lamports
+    Sol,
+    /// The raw amount of an SPL Token or Token-2022 account, in base units of its mint
+    TokenAccount,
+}
+
+impl BalanceKind {
+    /// The balance of this kind held by `account`. None for a token account that is not
+    /// owned by either token program.
+    pub fn balance_of(self, account: &SolanaAccount) -> Option<u64> {
+        match self {
+            BalanceKind::Sol => Some(account.lamports),
+            BalanceKind::TokenAccount => {
+                let owner = account.owner.to_string();
+                let token_program = owner == TokenInstructions::TOKEN_PROGRAM_ID
+                    || owner == TokenInstructions::TOKEN_2022_PROGRAM_ID;
+                if !token_program || account.data.len() < TOKEN_ACCOUNT_LEN {
+                    return None;
+                }
+                let amount = &account.data[TOKEN_AMOUNT_OFFSET..TOKEN_AMOUNT_OFFSET + 8];
+                Some(u64::from_le_bytes(amount.try_into().ok()?))
+            }
+        }
+    }
+}
+
+/// A watched balance that changed, e.g.
+/// `{"pubkey": "...", "kind": "sol", "old": 1000, "new": 2500, "slot": 250000000}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceChange {
+    pub pubkey: String,
+    pub kind: BalanceKind,
+    /// None on the first reading of the address, or if the account did not exist
+    pub old: Option<u64>,
+    /// None once the account does not exist
+    pub new: Option<u64>,
+    /// The slot `new` was read at
+    pub slot: u64,
+}
+
+/// Where a `BalanceWatcher` reads balances from. `RpcClient` reads them with
+/// getMultipleAccounts.
+pub trait BalanceSource: Send + 'static {
+    /// The balance of each address, in order, with the slot it was read at. None means
+    /// the account does not exist.
+    fn balances(
+        &self,
+        addresses: &[(Pubkey, BalanceKind)],
+    ) -> Result<Vec<(u64, Option<u64>)>, SolanaUnityError>;
+}
+
+impl BalanceSource for RpcClient {
+    fn balances(
+        &self,
+        addresses: &[(Pubkey, BalanceKind)],
+    ) -> Result<Vec<(u64, Option<u64>)>, SolanaUnityError> {
+        let pubkeys: Vec<Pubkey> = addresses.iter().map(|(pubkey, _)| *pubkey).collect();
+        let accounts = runtime::block_on(self.get_accounts_with_slots_async(&pubkeys))??;
+        Ok(addresses
+            .iter()
+            .zip(accounts)
+            .map(|((_, kind), (slot, account))| {
+                (slot, account.and_then(|account| kind.balance_of(&account)))
+            })
+            .collect())
+    }
+}
+
+struct Watched {
+    pubkey: Pubkey,
+    kind: BalanceKind,
+    // Tells a re-added address apart from the one a reading in flight was made for
+    generation: u64,
+    // None until the first reading, and while the account does not exist
+    balance: Option<u64>,
+    slot: u64,
+}
+
+struct State {
+    // In the order they were added, which is the order a cycle's events are queued in
+    addresses: Vec<Watched>,
+    next_generation: u64,
+    events: VecDeque<BalanceChange>,
+    stop: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Polls the balances of a set of addresses in the background and queues an event for
+/// every change. Poll the events with `poll_event`.
+pub struct BalanceWatcher {
+    shared: Arc<Shared>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Default for BalanceWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BalanceWatcher {
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    addresses: Vec::new(),
+                    next_generation: 0,
+                    events: VecDeque::new(),
+                    stop: false,
+                }),
+                wake: Condvar::new(),
+            }),
+            worker: Mutex::new(None),
+        }
+    }
+
+    /// Starts watching `pubkey`. Its first reading is queued as a change from None.
+    /// Returns false if it was already watched as `kind`; watching it as the other kind
+    /// replaces the old kind and starts over from a first reading.
+    pub fn add_address(&self, pubkey: &str, kind: BalanceKind) -> Result<bool, SolanaUnityError> {
+        let pubkey = parse_pubkey(pubkey)?;
+        let mut state = self.shared.state();
+        if let Some(index) = state.addresses.iter().position(|w| w.pubkey == pubkey) {
+            if state.addresses[index].kind == kind {
+                return Ok(false);
+            }
+            state.addresses.remove(index);
+        }
+        let generation = state.next_generation;
+        state.next_generation += 1;
+        state.addresses.push(Watched {
+            pubkey,
+            kind,
+            generation,
+            balance: None,
+            slot: 0,
+        });
+        Ok(true)
+    }
+
+    /// Stops watching `pubkey`. No event for it is queued after this returns, even from
+    /// a reading already in flight. Returns false if it was not watched.
+    pub fn remove_address(&self, pubkey: &str) -> Result<bool, SolanaUnityError> {
+        let pubkey = parse_pubkey(pubkey)?;
+        let mut state = self.shared.state();
+        let before = state.addresses.len();
+        state.addresses.retain(|w| w.pubkey != pubkey);
+        Ok(state.addresses.len() < before)
+    }
+
+    /// Starts reading all watched balances from `source` every `poll_interval_ms`, the
+    /// first time right away. Addresses added while running are read from the next cycle.
+    pub fn start(
+        &self,
+        source: impl BalanceSource,
+        poll_interval_ms: u64,
+    ) -> Result<(), SolanaUnityError> {
+        if poll_interval_ms == 0 {
+            return Err(SolanaUnityError::InvalidInput(
+                "Poll interval must be at least 1 ms".to_string(),
+            ));
+        }
+        let mut worker = self.worker.lock().unwrap_or_else(|e| e.into_inner());
+        if worker.is_some() {
+            return Err(SolanaUnityError::InvalidInput(
+                "Balance watcher is already running".to_string(),
+            ));
+        }
+
+        self.shared.state().stop = false;
+        let shared = Arc::clone(&self.shared);
+        let interval = Duration::from_millis(poll_interval_ms);
+        let handle = thread::Builder::new()
+            .name("solana-unity-balance-watcher".to_string())
+            .spawn(move || run(&shared, &source, interval))
+            .map_err(|e| {
+                SolanaUnityError::FfiError(format!("Failed to start balance watcher: {}", e))
+            })?;
+        *worker = Some(handle);
+        Ok(())
+    }
+
+    /// Stops polling and waits for a cycle in progress to finish. Watched addresses, their
+    /// last readings and queued events are kept, so `start` carries on from there.
+    pub fn stop(&self) {
+        let handle = self.worker.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(handle) = handle {
+            self.shared.state().stop = true;
+            self.shared.wake.notify_all();
+            let _ = handle.join();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.worker
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
+    /// The oldest queued change, or None if there is none
+    pub fn poll_event(&self) -> Option<BalanceChange> {
+        self.shared.state().events.pop_front()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.shared.state().events.len()
+    }
+}
+
+impl Drop for BalanceWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, SolanaUnityError> {
+    Pubkey::from_str(pubkey).map_err(|e| {
+        SolanaUnityError::InvalidInput(format!("Invalid pubkey: {}", e)).with_pubkey(pubkey)
+    })
+}
+
+fn run(shared: &Shared, source: &dyn BalanceSource, interval: Duration) {
+    loop {
+        let watched: Vec<(Pubkey, BalanceKind, u64)> = {
+            let state = shared.state();
+            if state.stop {
+                return;
+            }
+            state
+                .addresses
+                .iter()
+                .map(|w| (w.pubkey, w.kind, w.generation))
+                .collect()
+        };
+
+        if !watched.is_empty() {
+            let addresses: Vec<(Pubkey, BalanceKind)> = watched
+                .iter()
+                .map(|(pubkey, kind, _)| (*pubkey, *kind))
+                .collect();
+            match source.balances(&addresses) {
+                Ok(readings) => apply(shared, &watched, readings),
+                // The next cycle tries again
+                Err(e) => native_log!(LOG_WARN, "Balance watcher: {}", e),
+            }
+        }
+
+        let state = shared.state();
+        let (state, _) = shared
+            .wake
+            .wait_timeout_while(state, interval, |state| !state.stop)
+            .unwrap_or_else(|e| e.into_inner());
+        if state.stop {
+            return;
+        }
+    }
+}
+
+// Queues an event for every reading that changed a balance. Readings for addresses removed
+// (or re-added) since the cycle began are dropped, as are readings from an older slot
+// than the last one, which a load-balanced endpoint can return.
+fn apply(
+    shared: &Shared,
+    watched: &[(Pubkey, BalanceKind, u64)],
+    readings: Vec<(u64, Option<u64>)>,
+) {
+    let mut state = shared.state();
+    let State {
+        addresses, events, ..
+    } = &mut *state;
+    for ((_, _, generation), (slot, balance)) in watched.iter().zip(readings) {
+        let entry = match addresses.iter_mut().find(|w| w.generation == *generation) {
+            Some(entry) => entry,
+            None => continue,
+        };
+        if slot < entry.slot {
+            continue;
+        }
+        entry.slot = slot;
+        if entry.balance == balance {
+            continue;
+        }
+        if events.len() == MAX_QUEUED_EVENTS {
+            events.pop_front();
+            native_log!(
+                LOG_WARN,
+                "Balance watcher: event queue full, dropped the oldest event"
+            );
+        }
+        events.push_back(BalanceChange {
+            pubkey: entry.pubkey.to_string(),
+            kind: entry.kind,
+            old: entry.balance,
+            new: balance,
+            slot,
+        });
+        entry.balance = balance;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    // Balances and slot set by the test, with a count of the cycles that read them
+    #[derive(Clone, Default)]
+    struct FakeSource {
+        chain: Arc<Mutex<(u64, HashMap<Pubkey, u64>)>>,
+        reads: Arc<AtomicUsize>,
+        failures: Arc<AtomicUsize>,
+    }
+
+    impl FakeSource {
+        fn set(&self, slot: u64, pubkey: &Pubkey, balance: Option<u64>) {
+            let mut chain = self.chain.lock().unwrap();
+            chain.0 = slot;
+            match balance {
+                Some(balance) => chain.1.insert(*pubkey, balance),
+                None => chain.1.remove(pubkey),
+            };
+        }
+
+        // Waits until `cycles` more cycles have read the balances
+        fn wait_for_reads(&self, cycles: usize) {
+            let target = self.reads.load(Ordering::SeqCst) + cycles;
+            let deadline = Instant::now() + WAIT;
+            while self.reads.load(Ordering::SeqCst) < target {
+                assert!(Instant::now() < deadline, "timed out waiting for reads");
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+
+    impl BalanceSource for FakeSource {
+        fn balances(
+            &self,
+            addresses: &[(Pubkey, BalanceKind)],
+        ) -> Result<Vec<(u64, Option<u64>)>, SolanaUnityError> {
+            let result = if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                Err(SolanaUnityError::RpcError("Node unavailable".to_string()))
+            } else {
+                let chain = self.chain.lock().unwrap();
+                Ok(addresses
+                    .iter()
+                    .map(|(pubkey, _)| (chain.0, chain.1.get(pubkey).copied()))
+                    .collect())
+            };
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            result
+        }
+    }
+
+    fn next_events(watcher: &BalanceWatcher, count: usize) -> Vec<BalanceChange> {
+        let deadline = Instant::now() + WAIT;
+        let mut events = Vec::new();
+        while events.len() < count {
+            match watcher.poll_event() {
+                Some(event) => events.push(event),
+                None => {
+                    assert!(Instant::now() < deadline, "timed out after {:?}", events);
+                    thread::sleep(Duration::from_millis(5));
+                }
+            }
+        }
+        events
+    }
+
+    fn change(pubkey: &Pubkey, old: Option<u64>, new: Option<u64>, slot: u64) -> BalanceChange {
+        BalanceChange {
+            pubkey: pubkey.to_string(),
+            kind: BalanceKind::Sol,
+            old,
+            new,
+            slot,
+        }
+    }
+
+    #[test]
+    fn test_balance_changes() {
+        let (a, b, c) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let source = FakeSource::default();
+        source.set(10, &a, Some(1_000));
+        source.set(10, &b, Some(5));
+
+        let watcher = BalanceWatcher::new();
+        assert!(watcher
+            .add_address(&a.to_string(), BalanceKind::Sol)
+            .unwrap());
+        assert!(watcher
+            .add_address(&b.to_string(), BalanceKind::TokenAccount)
+            .unwrap());
+        // Missing from the start, so nothing changes until it is created
+        assert!(watcher
+            .add_address(&c.to_string(), BalanceKind::Sol)
+            .unwrap());
+        watcher.start(source.clone(), 10).unwrap();
+        assert!(watcher.is_running());
+
+        // First readings, in the order the addresses were added
+        let events = next_events(&watcher, 2);
+        assert_eq!(events[0], change(&a, None, Some(1_000), 10));
+        assert_eq!(events[1].pubkey, b.to_string());
+        assert_eq!(events[1].kind, BalanceKind::TokenAccount);
+        assert_eq!((events[1].old, events[1].new), (None, Some(5)));
+
+        source.set(11, &a, Some(2_500));
+        source.set(11, &c, Some(7));
+        assert_eq!(
+            next_events(&watcher, 2),
+            vec![
+                change(&a, Some(1_000), Some(2_500), 11),
+                change(&c, None, Some(7), 11)
+            ]
+        );
+
+        // Closed
+        source.set(12, &a, None);
+        assert_eq!(
+            next_events(&watcher, 1),
+            vec![change(&a, Some(2_500), None, 12)]
+        );
+
+        // Unchanged balances queue nothing
+        source.wait_for_reads(3);
+        assert_eq!(watcher.poll_event(), None);
+
+        watcher.stop();
+        assert!(!watcher.is_running());
+        let json = serde_json::to_value(change(&a, Some(1), None, 3)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"pubkey": a.to_string(), "kind": "sol", "old": 1, "new": null, "slot": 3})
+        );
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let (a, missing) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let source = FakeSource::default();
+        source.set(1, &a, Some(100));
+
+        let watcher = BalanceWatcher::new();
+        // Keeps the cycles reading after `a` is removed
+        watcher
+            .add_address(&missing.to_string(), BalanceKind::Sol)
+            .unwrap();
+        assert!(watcher
+            .add_address(&a.to_string(), BalanceKind::Sol)
+            .unwrap());
+        assert!(!watcher
+            .add_address(&a.to_string(), BalanceKind::Sol)
+            .unwrap());
+        assert!(watcher
+            .add_address("not a pubkey", BalanceKind::Sol)
+            .is_err());
+        watcher.start(source.clone(), 10).unwrap();
+        assert!(watcher.start(source.clone(), 10).is_err());
+
+        // Watched once despite the second add
+        assert_eq!(
+            next_events(&watcher, 1),
+            vec![change(&a, None, Some(100), 1)]
+        );
+        source.wait_for_reads(2);
+        assert_eq!(watcher.pending_count(), 0);
+
+        assert!(watcher.remove_address(&a.to_string()).unwrap());
+        assert!(!watcher.remove_address(&a.to_string()).unwrap());
+        source.set(2, &a, Some(200));
+        source.wait_for_reads(2);
+        assert_eq!(watcher.poll_event(), None);
+
+        // Re-added addresses start over from a first reading
+        assert!(watcher
+            .add_address(&a.to_string(), BalanceKind::Sol)
+            .unwrap());
+        assert_eq!(
+            next_events(&watcher, 1),
+            vec![change(&a, None, Some(200), 2)]
+        );
+    }
+
+    #[test]
+    fn test_stale_slots_and_failures() {
+        let a = Pubkey::new_unique();
+        let source = FakeSource::default();
+        source.failures.store(2, Ordering::SeqCst);
+        source.set(20, &a, Some(100));
+
+        let watcher = BalanceWatcher::new();
+        watcher
+            .add_address(&a.to_string(), BalanceKind::Sol)
+            .unwrap();
+        assert!(watcher.start(source.clone(), 0).is_err());
+        watcher.start(source.clone(), 10).unwrap();
+
+        // Failed cycles are retried
+        assert_eq!(
+            next_events(&watcher, 1),
+            vec![change(&a, None, Some(100), 20)]
+        );
+
+        // A node behind the last reading is ignored
+        source.set(19, &a, Some(50));
+        source.wait_for_reads(2);
+        assert_eq!(watcher.poll_event(), None);
+        source.set(21, &a, Some(50));
+        assert_eq!(
+            next_events(&watcher, 1),
+            vec![change(&a, Some(100), Some(50), 21)]
+        );
+
+        // Stopping keeps the last readings
+        watcher.stop();
+        source.set(22, &a, Some(60));
+        watcher.start(source.clone(), 10).unwrap();
+        assert_eq!(
+            next_events(&watcher, 1),
+            vec![change(&a, Some(50), Some(60), 22)]
+        );
+    }
+
+    #[test]
+    fn test_token_balance_of() {
+        let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+        data[TOKEN_AMOUNT_OFFSET..TOKEN_AMOUNT_OFFSET + 8]
+            .copy_from_slice(&123_456_789u64.to_le_bytes());
+        let mut account = SolanaAccount {
+            lamports: 2_039_280,
+            data,
+            owner: Pubkey::from_str(TokenInstructions::TOKEN_PROGRAM_ID).unwrap(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        assert_eq!(BalanceKind::Sol.balance_of(&account), Some(2_039_280));
+        assert_eq!(
+            BalanceKind::TokenAccount.balance_of(&account),
+            Some(123_456_789)
+        );
+
+        account.owner = Pubkey::from_str(TokenInstructions::TOKEN_2022_PROGRAM_ID).unwrap();
+        account.data.extend_from_slice(&[1, 0, 0, 0]);
+        assert_eq!(
+            BalanceKind::TokenAccount.balance_of(&account),
+            Some(123_456_789)
+        );
+
+        account.data.truncate(TOKEN_AMOUNT_OFFSET + 8);
+        assert_eq!(BalanceKind::TokenAccount.balance_of(&account), None);
+        account.data.resize(TOKEN_ACCOUNT_LEN, 0);
+        account.owner = solana_sdk::system_program::id();
+        assert_eq!(BalanceKind::TokenAccount.balance_of(&account), None);
+    }
+}
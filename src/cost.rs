@@ -0,0 +1,381 @@
+use serde::Serialize;
+use solana_sdk::compute_budget;
+use solana_sdk::message::Message;
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::system_program;
+
+use crate::instruction::TokenInstructions;
+
+/// Size of an SPL token account
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+/// Size of a Token-2022 account as the associated token program creates it: a token
+/// account, its account type and the ImmutableOwner extension. Mints with extensions
+/// that add to their token accounts make them larger.
+pub const TOKEN_2022_ACCOUNT_LEN: usize = 170;
+
+// The compute unit limit of an instruction, and of a transaction, without SetComputeUnitLimit
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+// ComputeBudgetInstruction is a borsh enum with a one-byte tag
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// What a transaction costs its fee payer: the fee, and the rent locked in the accounts
+/// it creates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransactionCost {
+    /// The fee without its priority fee
+    pub fee_lamports: u64,
+    /// Compute unit limit times compute unit price
+    pub priority_fee_lamports: u64,
+    pub rent_lamports: u64,
+    pub total_lamports: u64,
+    pub breakdown: Vec<CostItem>,
+}
+
+/// One of the amounts a `TransactionCost` adds up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CostItem {
+    /// "fee", "priority_fee" or "rent"
+    pub kind: &'static str,
+    pub lamports: u64,
+    /// For rent, the instruction creating the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_index: Option<usize>,
+    /// For rent, what creates the account, such as "system create account"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<&'static str>,
+    /// For rent, the account created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// For the priority fee
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_unit_limit: Option<u64>,
+    /// For the priority fee, in micro-lamports per compute unit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_unit_price: Option<u64>,
+}
+
+impl CostItem {
+    fn new(kind: &'static str, lamports: u64) -> Self {
+        Self {
+            kind,
+            lamports,
+            instruction_index: None,
+            label: None,
+            account: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+        }
+    }
+}
+
+/// An account an instruction of a message creates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedAccount {
+    pub instruction_index: usize,
+    pub label: &'static str,
+    pub account: String,
+    /// What the instruction funds it with, or None when that is the rent exemption of
+    /// `space` bytes
+    pub lamports: Option<u64>,
+    pub space: usize,
+}
+
+/// What a message spends besides its signature fee, read from its instructions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostScan {
+    pub compute_unit_limit: u64,
+    /// Micro-lamports per compute unit
+    pub compute_unit_price: u64,
+    pub created: Vec<CreatedAccount>,
+}
+
+impl CostScan {
+    /// Finds the compute budget instructions of `message` and the accounts it creates
+    /// with System create_account, create_account_with_seed and associated token program
+    /// creates. Idempotent creates are counted even though the account may already exist.
+    pub fn scan(message: &Message) -> Self {
+        let mut compute_unit_limit = None;
+        let mut compute_unit_price = 0;
+        let mut other_instructions = 0;
+        let mut created = Vec::new();
+
+        for (index, instruction) in message.instructions.iter().enumerate() {
+            let key = |position: usize| {
+                instruction
+                    .accounts
+                    .get(position)
+                    .and_then(|&account| message.account_keys.get(account as usize))
+            };
+            let program_id = match message
+                .account_keys
+                .get(instruction.program_id_index as usize)
+            {
+                Some(program_id) => program_id,
+                None => continue,
+            };
+
+            if *program_id == compute_budget::id() {
+                match instruction.data.split_first() {
+                    Some((&SET_COMPUTE_UNIT_LIMIT, rest)) if rest.len() == 4 => {
+                        let limit = u32::from_le_bytes(rest.try_into().unwrap());
+                        compute_unit_limit = Some(limit as u64);
+                    }
+                    Some((&SET_COMPUTE_UNIT_PRICE, rest)) if rest.len() == 8 => {
+                        compute_unit_price = u64::from_le_bytes(rest.try_into().unwrap());
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            other_instructions += 1;
+
+            if *program_id == system_program::id() {
+                let (label, lamports, space) =
+                    match bincode::deserialize::<SystemInstruction>(&instruction.data) {
+                        Ok(SystemInstruction::CreateAccount {
+                            lamports, space, ..
+                        }) => ("system create account", lamports, space),
+                        Ok(SystemInstruction::CreateAccountWithSeed {
+                            lamports, space, ..
+                        }) => ("system create account with seed", lamports, space),
+                        _ => continue,
+                    };
+                if let Some(account) = key(1) {
+                    created.push(CreatedAccount {
+                        instruction_index: index,
+                        label,
+                        account: account.to_string(),
+                        lamports: Some(lamports),
+                        space: space as usize,
+                    });
+                }
+            } else if program_id.to_string() == TokenInstructions::ASSOCIATED_TOKEN_PROGRAM_ID {
+                // Create has no data or 0, CreateIdempotent is 1 and RecoverNested 2.
+                // The accounts are payer, associated account, wallet, mint, system
+                // program and token program.
+                if !matches!(instruction.data.first(), None | Some(0) | Some(1)) {
+                    continue;
+                }
+                let space = match key(5) {
+                    Some(token_program)
+                        if token_program.to_string()
+                            == TokenInstructions::TOKEN_2022_PROGRAM_ID =>
+                    {
+                        TOKEN_2022_ACCOUNT_LEN
+                    }
+                    _ => TOKEN_ACCOUNT_LEN,
+                };
+                if let Some(account) = key(1) {
+                    created.push(CreatedAccount {
+                        instruction_index: index,
+                        label: "associated token account create",
+                        account: account.to_string(),
+                        lamports: None,
+                        space,
+                    });
+                }
+            }
+        }
+
+        let compute_unit_limit = compute_unit_limit.unwrap_or_else(|| {
+            (other_instructions * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+                .min(MAX_COMPUTE_UNIT_LIMIT)
+        });
+        Self {
+            compute_unit_limit,
+            compute_unit_price,
+            created,
+        }
+    }
+
+    /// The priority fee, rounded up to a whole lamport as the runtime does
+    pub fn priority_fee(&self) -> u64 {
+        let micro_lamports = self.compute_unit_price as u128 * self.compute_unit_limit as u128;
+        micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT) as u64
+    }
+
+    /// The account sizes whose rent exemption has to be looked up, without repeats
+    pub fn rent_lookups(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = self
+            .created
+            .iter()
+            .filter(|created| created.lamports.is_none())
+            .map(|created| created.space)
+            .collect();
+        sizes.sort_unstable();
+        sizes.dedup();
+        sizes
+    }
+
+    /// Adds up the cost from `fee`, what getFeeForMessage quotes for the message, which
+    /// includes the priority fee, and `rent`, the rent exemption of each of
+    /// `rent_lookups`.
+    pub fn into_cost(self, fee: u64, rent: impl Fn(usize) -> u64) -> TransactionCost {
+        let priority_fee = self.priority_fee();
+        let fee = fee.saturating_sub(priority_fee);
+
+        let mut breakdown = vec![CostItem::new("fee", fee)];
+        if priority_fee > 0 {
+            breakdown.push(CostItem {
+                compute_unit_limit: Some(self.compute_unit_limit),
+                compute_unit_price: Some(self.compute_unit_price),
+                ..CostItem::new("priority_fee", priority_fee)
+            });
+        }
+        let mut rent_total = 0u64;
+        for created in self.created {
+            let lamports = created.lamports.unwrap_or_else(|| rent(created.space));
+            rent_total = rent_total.saturating_add(lamports);
+            breakdown.push(CostItem {
+                instruction_index: Some(created.instruction_index),
+                label: Some(created.label),
+                account: Some(created.account),
+                ..CostItem::new("rent", lamports)
+            });
+        }
+
+        TransactionCost {
+            fee_lamports: fee,
+            priority_fee_lamports: priority_fee,
+            rent_lamports: rent_total,
+            total_lamports: fee.saturating_add(priority_fee).saturating_add(rent_total),
+            breakdown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::system_instruction;
+    use std::str::FromStr;
+
+    fn create_associated_account(payer: &Pubkey, token_program: &str) -> (Instruction, Pubkey) {
+        let associated = Pubkey::new_unique();
+        let instruction = Instruction {
+            program_id: Pubkey::from_str(TokenInstructions::ASSOCIATED_TOKEN_PROGRAM_ID).unwrap(),
+            accounts: vec![
+                AccountMeta::new(*payer, true),
+                AccountMeta::new(associated, false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(Pubkey::from_str(token_program).unwrap(), false),
+            ],
+            data: vec![],
+        };
+        (instruction, associated)
+    }
+
+    #[test]
+    fn test_transfer_and_associated_account_create() {
+        let payer = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1_000);
+        let (create, associated) =
+            create_associated_account(&payer, TokenInstructions::TOKEN_PROGRAM_ID);
+        let message = Message::new(&[transfer, create], Some(&payer));
+
+        let scan = CostScan::scan(&message);
+        assert_eq!(scan.compute_unit_limit, 400_000);
+        assert_eq!(scan.priority_fee(), 0);
+        assert_eq!(
+            scan.created,
+            [CreatedAccount {
+                instruction_index: 1,
+                label: "associated token account create",
+                account: associated.to_string(),
+                lamports: None,
+                space: TOKEN_ACCOUNT_LEN,
+            }]
+        );
+        assert_eq!(scan.rent_lookups(), [TOKEN_ACCOUNT_LEN]);
+
+        let cost = scan.into_cost(5_000, |space| {
+            assert_eq!(space, TOKEN_ACCOUNT_LEN);
+            2_039_280
+        });
+        assert_eq!(cost.fee_lamports, 5_000);
+        assert_eq!(cost.rent_lamports, 2_039_280);
+        assert_eq!(cost.total_lamports, 2_044_280);
+        assert_eq!(
+            serde_json::to_value(&cost.breakdown).unwrap(),
+            serde_json::json!([
+                {"kind": "fee", "lamports": 5_000},
+                {
+                    "kind": "rent",
+                    "lamports": 2_039_280,
+                    "instruction_index": 1,
+                    "label": "associated token account create",
+                    "account": associated.to_string(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_system_creates_and_priority_fee() {
+        let payer = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let created = Pubkey::new_unique();
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_limit(50_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1_001),
+            system_instruction::create_account(&payer, &created, 1_500_000, 82, &owner),
+            system_instruction::create_account_with_seed(
+                &payer,
+                &Pubkey::create_with_seed(&payer, "seed", &owner).unwrap(),
+                &payer,
+                "seed",
+                900_000,
+                10,
+                &owner,
+            ),
+            create_associated_account(&payer, TokenInstructions::TOKEN_2022_PROGRAM_ID).0,
+        ];
+        let message = Message::new(&instructions, Some(&payer));
+
+        let scan = CostScan::scan(&message);
+        assert_eq!(scan.compute_unit_limit, 50_000);
+        // 50,050,000 micro-lamports, rounded up
+        assert_eq!(scan.priority_fee(), 51);
+        let labels: Vec<_> = scan.created.iter().map(|created| created.label).collect();
+        assert_eq!(
+            labels,
+            [
+                "system create account",
+                "system create account with seed",
+                "associated token account create"
+            ]
+        );
+        assert_eq!(scan.rent_lookups(), [TOKEN_2022_ACCOUNT_LEN]);
+
+        // The quote includes the priority fee, which is reported separately
+        let cost = scan.into_cost(5_051, |_| 2_074_080);
+        assert_eq!(cost.fee_lamports, 5_000);
+        assert_eq!(cost.priority_fee_lamports, 51);
+        assert_eq!(cost.rent_lamports, 1_500_000 + 900_000 + 2_074_080);
+        assert_eq!(cost.total_lamports, 5_051 + cost.rent_lamports);
+        assert_eq!(cost.breakdown[1].compute_unit_price, Some(1_001));
+    }
+
+    #[test]
+    fn test_default_compute_unit_limit_is_capped() {
+        let payer = Pubkey::new_unique();
+        let transfers: Vec<_> = (0..10)
+            .map(|_| system_instruction::transfer(&payer, &Pubkey::new_unique(), 1))
+            .collect();
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_price(1)];
+        instructions.extend(transfers);
+        let scan = CostScan::scan(&Message::new(&instructions, Some(&payer)));
+        assert_eq!(scan.compute_unit_limit, MAX_COMPUTE_UNIT_LIMIT);
+        assert_eq!(scan.priority_fee(), 2);
+        assert!(scan.created.is_empty());
+    }
+}
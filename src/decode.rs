@@ -0,0 +1,423 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::SolanaUnityError;
+
+/// Bytes an Anchor account starts with before its fields
+pub const ANCHOR_DISCRIMINATOR_LEN: usize = 8;
+
+/// Describes a borsh struct: `{"offset": 8, "fields": [{"name": "level", "type": "u16"}]}`.
+/// `offset` bytes are skipped before the first field, e.g. 8 for an Anchor discriminator.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schema {
+    #[serde(default)]
+    pub offset: usize,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: FieldType,
+}
+
+/// A field type, written as in Anchor IDLs: "u64", "pubkey", {"vec": "u8"},
+/// {"option": "string"}, {"array": ["u8", 32]}, or {"struct": [fields]} for a nested struct
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Bool,
+    String,
+    Pubkey,
+    Vec(Box<FieldType>),
+    Option(Box<FieldType>),
+    Array(Box<FieldType>, usize),
+    Struct(Vec<Field>),
+}
+
+/// Decodes borsh-serialized `data` into a JSON object keyed by the field names of the
+/// schema in `schema_json`. Pubkeys become base58 strings, and 64- and 128-bit integers
+/// decimal strings so they survive JSON readers that use doubles. Bytes after the last
+/// field are ignored, as accounts are often allocated larger than their contents.
+pub fn borsh_decode_with_schema(
+    data: &[u8],
+    schema_json: &str,
+) -> Result<String, SolanaUnityError> {
+    let schema: Schema = serde_json::from_str(schema_json)
+        .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid borsh schema: {}", e)))?;
+    let value = decode(data, &schema)?;
+    Ok(value.to_string())
+}
+
+pub fn decode(data: &[u8], schema: &Schema) -> Result<Value, SolanaUnityError> {
+    check_fields(&schema.fields, "")?;
+    if schema.offset > data.len() {
+        return Err(SolanaUnityError::SerializationError(format!(
+            "Offset {} is past the end of the {} bytes of data",
+            schema.offset,
+            data.len()
+        )));
+    }
+
+    let mut reader = Reader {
+        data,
+        pos: schema.offset,
+    };
+    reader.read_struct(&schema.fields, "")
+}
+
+// Rejects empty structs, which take no bytes and would let a forged vec length of
+// billions through, and duplicate names, which would silently drop a value
+fn check_fields(fields: &[Field], parent: &str) -> Result<(), SolanaUnityError> {
+    if fields.is_empty() {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Invalid borsh schema: struct {} has no fields",
+            if parent.is_empty() { "<root>" } else { parent }
+        )));
+    }
+    for (index, field) in fields.iter().enumerate() {
+        let path = join(parent, &field.name);
+        if fields[..index].iter().any(|other| other.name == field.name) {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Invalid borsh schema: field {} appears twice",
+                path
+            )));
+        }
+        let mut ty = &field.ty;
+        loop {
+            match ty {
+                FieldType::Vec(inner) | FieldType::Option(inner) | FieldType::Array(inner, _) => {
+                    ty = inner
+                }
+                FieldType::Struct(fields) => {
+                    check_fields(fields, &path)?;
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, path: &str, at: usize, message: impl std::fmt::Display) -> SolanaUnityError {
+        SolanaUnityError::SerializationError(format!("Field {} at byte {}: {}", path, at, message))
+    }
+
+    fn take(&mut self, len: usize, path: &str) -> Result<&'a [u8], SolanaUnityError> {
+        let left = self.data.len() - self.pos;
+        if len > left {
+            return Err(self.error(
+                path,
+                self.pos,
+                format!("needs {} bytes, {} left", len, left),
+            ));
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self, path: &str) -> Result<[u8; N], SolanaUnityError> {
+        Ok(self.take(N, path)?.try_into().unwrap())
+    }
+
+    // A length prefix, checked against the bytes left since every element takes at least one
+    fn read_len(&mut self, path: &str) -> Result<usize, SolanaUnityError> {
+        let at = self.pos;
+        let len = u32::from_le_bytes(self.take_array(path)?) as usize;
+        let left = self.data.len() - self.pos;
+        if len > left {
+            return Err(self.error(
+                path,
+                at,
+                format!("length {} is longer than the {} bytes left", len, left),
+            ));
+        }
+        Ok(len)
+    }
+
+    fn read_struct(&mut self, fields: &[Field], parent: &str) -> Result<Value, SolanaUnityError> {
+        let mut object = Map::new();
+        for field in fields {
+            let path = join(parent, &field.name);
+            object.insert(field.name.clone(), self.read(&field.ty, &path)?);
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn read(&mut self, ty: &FieldType, path: &str) -> Result<Value, SolanaUnityError> {
+        let at = self.pos;
+        let value = match ty {
+            FieldType::U8 => Value::from(self.take_array::<1>(path)?[0]),
+            FieldType::U16 => Value::from(u16::from_le_bytes(self.take_array(path)?)),
+            FieldType::U32 => Value::from(u32::from_le_bytes(self.take_array(path)?)),
+            FieldType::U64 => Value::from(u64::from_le_bytes(self.take_array(path)?).to_string()),
+            FieldType::U128 => Value::from(u128::from_le_bytes(self.take_array(path)?).to_string()),
+            FieldType::I8 => Value::from(i8::from_le_bytes(self.take_array(path)?)),
+            FieldType::I16 => Value::from(i16::from_le_bytes(self.take_array(path)?)),
+            FieldType::I32 => Value::from(i32::from_le_bytes(self.take_array(path)?)),
+            FieldType::I64 => Value::from(i64::from_le_bytes(self.take_array(path)?).to_string()),
+            FieldType::I128 => Value::from(i128::from_le_bytes(self.take_array(path)?).to_string()),
+            FieldType::Bool => match self.take_array::<1>(path)?[0] {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                other => return Err(self.error(path, at, format!("invalid bool {}", other))),
+            },
+            FieldType::String => {
+                let len = self.read_len(path)?;
+                let bytes = self.take(len, path)?;
+                match std::str::from_utf8(bytes) {
+                    Ok(text) => Value::from(text),
+                    Err(e) => return Err(self.error(path, at, format!("invalid UTF-8: {}", e))),
+                }
+            }
+            FieldType::Pubkey => {
+                Value::from(Pubkey::new_from_array(self.take_array(path)?).to_string())
+            }
+            FieldType::Vec(inner) => {
+                let len = self.read_len(path)?;
+                self.read_elements(inner, len, path)?
+            }
+            FieldType::Array(inner, len) => {
+                let left = self.data.len() - self.pos;
+                if *len > left {
+                    return Err(self.error(
+                        path,
+                        at,
+                        format!("array of {} is longer than the {} bytes left", len, left),
+                    ));
+                }
+                self.read_elements(inner, *len, path)?
+            }
+            FieldType::Option(inner) => match self.take_array::<1>(path)?[0] {
+                0 => Value::Null,
+                1 => self.read(inner, path)?,
+                other => return Err(self.error(path, at, format!("invalid option tag {}", other))),
+            },
+            FieldType::Struct(fields) => self.read_struct(fields, path)?,
+        };
+        Ok(value)
+    }
+
+    fn read_elements(
+        &mut self,
+        ty: &FieldType,
+        len: usize,
+        path: &str,
+    ) -> Result<Value, SolanaUnityError> {
+        let mut values = Vec::with_capacity(len);
+        for index in 0..len {
+            values.push(self.read(ty, &format!("{}[{}]", path, index))?);
+        }
+        Ok(Value::Array(values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[derive(BorshSerialize)]
+    struct Stats {
+        hp: u32,
+        buffs: Vec<i16>,
+    }
+
+    #[derive(BorshSerialize)]
+    struct PlayerState {
+        owner: [u8; 32],
+        level: u16,
+        xp: u64,
+        gold: u128,
+        delta: i64,
+        alive: bool,
+        name: String,
+        position: [i32; 3],
+        inventory: Vec<u8>,
+        guild: Option<String>,
+        mentor: Option<[u8; 32]>,
+        stats: Stats,
+        history: Vec<Stats>,
+    }
+
+    const PLAYER_SCHEMA: &str = r#"{
+        "offset": 8,
+        "fields": [
+            {"name": "owner", "type": "pubkey"},
+            {"name": "level", "type": "u16"},
+            {"name": "xp", "type": "u64"},
+            {"name": "gold", "type": "u128"},
+            {"name": "delta", "type": "i64"},
+            {"name": "alive", "type": "bool"},
+            {"name": "name", "type": "string"},
+            {"name": "position", "type": {"array": ["i32", 3]}},
+            {"name": "inventory", "type": {"vec": "u8"}},
+            {"name": "guild", "type": {"option": "string"}},
+            {"name": "mentor", "type": {"option": "pubkey"}},
+            {"name": "stats", "type": {"struct": [
+                {"name": "hp", "type": "u32"},
+                {"name": "buffs", "type": {"vec": "i16"}}
+            ]}},
+            {"name": "history", "type": {"vec": {"struct": [
+                {"name": "hp", "type": "u32"},
+                {"name": "buffs", "type": {"vec": "i16"}}
+            ]}}}
+        ]
+    }"#;
+
+    // An Anchor account: discriminator, then the borsh struct
+    fn player_account(player: &PlayerState) -> Vec<u8> {
+        let mut data = vec![0xAA; ANCHOR_DISCRIMINATOR_LEN];
+        data.extend(borsh::to_vec(player).unwrap());
+        data
+    }
+
+    fn player() -> PlayerState {
+        PlayerState {
+            owner: Pubkey::new_unique().to_bytes(),
+            level: 42,
+            xp: u64::MAX,
+            gold: u128::MAX - 1,
+            delta: -5,
+            alive: true,
+            name: "Ünïcode hero".to_string(),
+            position: [1, -2, 3],
+            inventory: vec![7, 8, 9],
+            guild: None,
+            mentor: Some(Pubkey::new_unique().to_bytes()),
+            stats: Stats {
+                hp: 100,
+                buffs: vec![-1, 2],
+            },
+            history: vec![
+                Stats {
+                    hp: 90,
+                    buffs: vec![],
+                },
+                Stats {
+                    hp: 80,
+                    buffs: vec![i16::MIN],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let player = player();
+        let mut data = player_account(&player);
+        // Space the account was allocated beyond its contents
+        data.extend([0; 16]);
+
+        let json: Value =
+            serde_json::from_str(&borsh_decode_with_schema(&data, PLAYER_SCHEMA).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "owner": Pubkey::new_from_array(player.owner).to_string(),
+                "level": 42,
+                "xp": "18446744073709551615",
+                "gold": (u128::MAX - 1).to_string(),
+                "delta": "-5",
+                "alive": true,
+                "name": "Ünïcode hero",
+                "position": [1, -2, 3],
+                "inventory": [7, 8, 9],
+                "guild": null,
+                "mentor": Pubkey::new_from_array(player.mentor.unwrap()).to_string(),
+                "stats": {"hp": 100, "buffs": [-1, 2]},
+                "history": [{"hp": 90, "buffs": []}, {"hp": 80, "buffs": [-32768]}],
+            })
+        );
+    }
+
+    #[test]
+    fn test_errors_name_field_and_offset() {
+        let data = player_account(&player());
+
+        // Cut inside the second buff of the first stats
+        let stats_buffs = data.len() - 4 - (4 + 4) - (4 + 4 + 2) - 2;
+        let error = borsh_decode_with_schema(&data[..stats_buffs + 1], PLAYER_SCHEMA).unwrap_err();
+        assert_eq!(
+            error.message,
+            format!(
+                "Field stats.buffs[1] at byte {}: needs 2 bytes, 1 left",
+                stats_buffs
+            )
+        );
+
+        // alive is at 8 + 32 + 2 + 8 + 16 + 8
+        let mut bad_bool = data.clone();
+        bad_bool[74] = 2;
+        let error = borsh_decode_with_schema(&bad_bool, PLAYER_SCHEMA).unwrap_err();
+        assert_eq!(error.message, "Field alive at byte 74: invalid bool 2");
+
+        // A forged vec length fails before anything is allocated
+        let mut forged = vec![0; 8];
+        forged.extend(u32::MAX.to_le_bytes());
+        let error = borsh_decode_with_schema(
+            &forged,
+            r#"{"offset": 8, "fields": [{"name": "v", "type": {"vec": "u64"}}]}"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .message
+            .starts_with("Field v at byte 8: length 4294967295"));
+    }
+
+    #[test]
+    fn test_schema_is_validated() {
+        let invalid = [
+            r#"{"fields": []}"#,
+            r#"{"fields": [{"name": "a", "type": "u256"}]}"#,
+            r#"{"fields": [{"name": "a", "type": "u8"}, {"name": "a", "type": "u8"}]}"#,
+            r#"{"fields": [{"name": "a", "type": {"vec": {"struct": []}}}]}"#,
+            r#"{"fields": [{"name": "a", "type": "u8"}], "discriminator": 8}"#,
+        ];
+        for schema in invalid {
+            let error = borsh_decode_with_schema(&[0; 8], schema).unwrap_err();
+            assert!(
+                error.message.starts_with("Invalid borsh schema"),
+                "{}",
+                schema
+            );
+        }
+
+        let error = borsh_decode_with_schema(
+            &[0; 4],
+            r#"{"offset": 8, "fields": [{"name": "a", "type": "u8"}]}"#,
+        )
+        .unwrap_err();
+        assert!(error.message.contains("Offset 8"));
+    }
+}
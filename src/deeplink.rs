@@ -0,0 +1,299 @@
+use crypto_box::aead::{Aead, AeadCore};
+use crypto_box::{Nonce, PublicKey, SalsaBox, SecretKey};
+use rand::rngs::OsRng;
+
+use crate::error::SolanaUnityError;
+
+/// Length of a NaCl box nonce
+pub const NONCE_LEN: usize = 24;
+
+/// Encryption state for a Phantom deeplink session. The dapp side holds an ephemeral
+/// X25519 keypair; once the wallet's encryption public key is known the NaCl box shared
+/// secret is derived and used for every later payload in both directions.
+pub struct DeeplinkSession {
+    secret_key: SecretKey,
+    shared: Option<SalsaBox>,
+}
+
+impl DeeplinkSession {
+    pub fn new() -> Self {
+        Self {
+            secret_key: SecretKey::generate(&mut OsRng),
+            shared: None,
+        }
+    }
+
+    /// The dapp encryption public key, sent as `dapp_encryption_public_key`
+    pub fn public_key_base58(&self) -> String {
+        bs58::encode(self.secret_key.public_key().as_bytes()).into_string()
+    }
+
+    /// Derives the shared secret from the wallet's encryption public key
+    pub fn set_wallet_public_key(
+        &mut self,
+        wallet_public_key: &str,
+    ) -> Result<(), SolanaUnityError> {
+        self.shared = Some(self.derive_shared(wallet_public_key)?);
+        Ok(())
+    }
+
+    pub fn has_shared_secret(&self) -> bool {
+        self.shared.is_some()
+    }
+
+    /// Decrypts a wallet response. `wallet_public_key` is the `phantom_encryption_public_key`
+    /// from the connect response; pass an empty string for later responses to reuse the
+    /// established shared secret. A new wallet key replaces the shared secret only once a
+    /// payload has been authenticated with it, so a forged response cannot end the session.
+    pub fn open(
+        &mut self,
+        wallet_public_key: &str,
+        nonce: &str,
+        data: &str,
+    ) -> Result<String, SolanaUnityError> {
+        let nonce = decode_nonce(nonce)?;
+        let ciphertext = decode_base58("data", data)?;
+
+        let plaintext = if wallet_public_key.is_empty() {
+            decrypt(self.shared_box()?, &nonce, &ciphertext)?
+        } else {
+            let shared = self.derive_shared(wallet_public_key)?;
+            let plaintext = decrypt(&shared, &nonce, &ciphertext)?;
+            self.shared = Some(shared);
+            plaintext
+        };
+
+        String::from_utf8(plaintext).map_err(|e| {
+            SolanaUnityError::SerializationError(format!(
+                "Deeplink payload is not valid UTF-8: {}",
+                e
+            ))
+        })
+    }
+
+    /// Encrypts a JSON payload for the wallet, returning (nonce, ciphertext) as base58
+    pub fn seal(&self, payload_json: &str) -> Result<(String, String), SolanaUnityError> {
+        let shared = self.shared_box()?;
+        let nonce = SalsaBox::generate_nonce(&mut OsRng);
+
+        let ciphertext = shared
+            .encrypt(&nonce, payload_json.as_bytes())
+            .map_err(|_| SolanaUnityError::WalletError("Failed to encrypt payload".to_string()))?;
+
+        Ok((
+            bs58::encode(nonce.as_slice()).into_string(),
+            bs58::encode(ciphertext).into_string(),
+        ))
+    }
+
+    fn derive_shared(&self, wallet_public_key: &str) -> Result<SalsaBox, SolanaUnityError> {
+        let bytes = decode_base58("wallet encryption public key", wallet_public_key)?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            SolanaUnityError::InvalidInput(format!(
+                "Wallet encryption public key must be 32 bytes, got {}",
+                bytes.len()
+            ))
+        })?;
+        Ok(SalsaBox::new(&PublicKey::from(bytes), &self.secret_key))
+    }
+
+    fn shared_box(&self) -> Result<&SalsaBox, SolanaUnityError> {
+        self.shared.as_ref().ok_or_else(|| {
+            SolanaUnityError::WalletError(
+                "No shared secret yet, the wallet public key has not been set".to_string(),
+            )
+        })
+    }
+}
+
+impl Default for DeeplinkSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn decode_base58(name: &str, value: &str) -> Result<Vec<u8>, SolanaUnityError> {
+    bs58::decode(value.trim())
+        .into_vec()
+        .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid base58 {}: {}", name, e)))
+}
+
+fn decrypt(
+    shared: &SalsaBox,
+    nonce: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, SolanaUnityError> {
+    shared
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| {
+            SolanaUnityError::WalletError("Deeplink payload failed authentication".to_string())
+        })
+}
+
+fn decode_nonce(nonce: &str) -> Result<Vec<u8>, SolanaUnityError> {
+    let nonce = decode_base58("nonce", nonce)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Nonce must be {} bytes, got {}",
+            NONCE_LEN,
+            nonce.len()
+        )));
+    }
+    Ok(nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    struct WalletSide {
+        secret_key: SecretKey,
+    }
+
+    impl WalletSide {
+        fn new() -> Self {
+            Self {
+                secret_key: SecretKey::generate(&mut OsRng),
+            }
+        }
+
+        fn public_key_base58(&self) -> String {
+            bs58::encode(self.secret_key.public_key().as_bytes()).into_string()
+        }
+
+        fn shared(&self, dapp_public_key: &str) -> SalsaBox {
+            let bytes: [u8; 32] = bs58::decode(dapp_public_key)
+                .into_vec()
+                .unwrap()
+                .try_into()
+                .unwrap();
+            SalsaBox::new(&PublicKey::from(bytes), &self.secret_key)
+        }
+
+        fn seal(&self, dapp_public_key: &str, payload: &str) -> (String, String) {
+            let nonce = SalsaBox::generate_nonce(&mut OsRng);
+            let ciphertext = self
+                .shared(dapp_public_key)
+                .encrypt(&nonce, payload.as_bytes())
+                .unwrap();
+            (
+                bs58::encode(nonce.as_slice()).into_string(),
+                bs58::encode(ciphertext).into_string(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_open_wallet_response() {
+        let mut session = DeeplinkSession::new();
+        let wallet = WalletSide::new();
+        let payload = r#"{"public_key":"11111111111111111111111111111111","session":"abc"}"#;
+
+        let (nonce, data) = wallet.seal(&session.public_key_base58(), payload);
+        let opened = session
+            .open(&wallet.public_key_base58(), &nonce, &data)
+            .unwrap();
+        assert_eq!(opened, payload);
+        assert!(session.has_shared_secret());
+
+        // Later responses reuse the shared secret
+        let (nonce, data) = wallet.seal(&session.public_key_base58(), "{}");
+        assert_eq!(session.open("", &nonce, &data).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_seal_for_wallet() {
+        let mut session = DeeplinkSession::new();
+        let wallet = WalletSide::new();
+        session
+            .set_wallet_public_key(&wallet.public_key_base58())
+            .unwrap();
+
+        let payload = r#"{"transaction":"abc","session":"xyz"}"#;
+        let (nonce, data) = session.seal(payload).unwrap();
+
+        let nonce = bs58::decode(nonce).into_vec().unwrap();
+        let ciphertext = bs58::decode(data).into_vec().unwrap();
+        let plaintext = wallet
+            .shared(&session.public_key_base58())
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .unwrap();
+        assert_eq!(String::from_utf8(plaintext).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_seal_requires_shared_secret() {
+        let session = DeeplinkSession::new();
+        assert!(session.seal("{}").is_err());
+    }
+
+    #[test]
+    fn test_bad_nonce_and_authentication_errors_are_distinct() {
+        let mut session = DeeplinkSession::new();
+        let wallet = WalletSide::new();
+        let (nonce, data) = wallet.seal(&session.public_key_base58(), "{}");
+
+        let short_nonce = bs58::encode([1u8; 23]).into_string();
+        match session.open(&wallet.public_key_base58(), &short_nonce, &data) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("24 bytes")),
+            _ => panic!("Expected InvalidInput for a short nonce"),
+        }
+
+        let mut ciphertext = bs58::decode(&data).into_vec().unwrap();
+        ciphertext[0] ^= 0x01;
+        let tampered = bs58::encode(ciphertext).into_string();
+        match session.open(&wallet.public_key_base58(), &nonce, &tampered) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("authentication")),
+            _ => panic!("Expected WalletError for a tampered payload"),
+        }
+
+        // A different wallet key cannot open the payload either
+        let other = WalletSide::new();
+        match session.open(&other.public_key_base58(), &nonce, &data) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                ..
+            }) => {}
+            _ => panic!("Expected WalletError for the wrong wallet key"),
+        }
+    }
+
+    #[test]
+    fn test_failed_open_with_foreign_key_keeps_session() {
+        let mut session = DeeplinkSession::new();
+        let wallet = WalletSide::new();
+        let (nonce, data) = wallet.seal(&session.public_key_base58(), "{}");
+        session
+            .open(&wallet.public_key_base58(), &nonce, &data)
+            .unwrap();
+
+        // A response naming some other wallet key does not authenticate
+        let other = WalletSide::new();
+        let (nonce, data) = wallet.seal(&session.public_key_base58(), "{}");
+        assert!(session
+            .open(&other.public_key_base58(), &nonce, &data)
+            .is_err());
+
+        // The shared secret with the real wallet still works in both directions
+        let (nonce, data) = wallet.seal(&session.public_key_base58(), r#"{"a":1}"#);
+        assert_eq!(session.open("", &nonce, &data).unwrap(), r#"{"a":1}"#);
+
+        let (nonce, data) = session.seal("{}").unwrap();
+        let nonce = bs58::decode(nonce).into_vec().unwrap();
+        let ciphertext = bs58::decode(data).into_vec().unwrap();
+        let plaintext = wallet
+            .shared(&session.public_key_base58())
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .unwrap();
+        assert_eq!(plaintext, b"{}");
+    }
+}
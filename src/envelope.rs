@@ -0,0 +1,172 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::SolanaUnityError;
+
+/// Version of the JSON returned by the FFI. Only bumped when a change would break existing
+/// readers, such as removing or renaming a field; adding a field is not a breaking change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Wraps the outcome of `method` in the envelope every JSON-returning FFI function uses:
+/// `{"schema":1,"method":"getAccountInfo","ok":true,"result":...}` on success and
+/// `{"schema":1,"method":"getAccountInfo","ok":false,"error":{"kind":...,"code":...,"message":...}}`
+/// on failure.
+pub fn to_envelope<T: Serialize>(method: &str, result: Result<T, SolanaUnityError>) -> Value {
+    let result = result.and_then(|value| {
+        serde_json::to_value(value).map_err(|e| {
+            SolanaUnityError::SerializationError(format!("Failed to serialize result: {}", e))
+        })
+    });
+
+    match result {
+        Ok(value) => json!({
+            "schema": SCHEMA_VERSION,
+            "method": method,
+            "ok": true,
+            "result": value,
+        }),
+        Err(e) => json!({
+            "schema": SCHEMA_VERSION,
+            "method": method,
+            "ok": false,
+            "error": {
+                "kind": e.name(),
+                "code": e.code,
+                "message": e.to_string(),
+            },
+        }),
+    }
+}
+
+/// `to_envelope` rendered as a JSON string
+pub fn to_envelope_string<T: Serialize>(
+    method: &str,
+    result: Result<T, SolanaUnityError>,
+) -> String {
+    to_envelope(method, result).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "rpc")]
+    use crate::error::FailedInstruction;
+    #[cfg(feature = "rpc")]
+    use crate::rpc::{AccountInfo, KeyedAccount, SimulationResult, TransactionStatus};
+
+    // Golden files live in tests/golden. A failure here means the JSON handed to C# has
+    // changed shape: update the golden file, and bump SCHEMA_VERSION if the change breaks
+    // existing readers.
+    #[cfg(feature = "rpc")]
+    fn assert_golden(envelope: Value, golden: &str) {
+        let expected: Value = serde_json::from_str(golden).unwrap();
+        assert_eq!(
+            envelope,
+            expected,
+            "envelope does not match golden file:\n{}",
+            serde_json::to_string_pretty(&envelope).unwrap()
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    fn sample_account() -> AccountInfo {
+        AccountInfo {
+            lamports: 1_141_440,
+            owner: "BPFLoader2111111111111111111111111111111111".to_string(),
+            data: "AQID".to_string(),
+            executable: true,
+            rent_epoch: 361,
+        }
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_account_info_envelope() {
+        assert_golden(
+            to_envelope("getAccountInfo", Ok(sample_account())),
+            include_str!("../tests/golden/get_account_info.json"),
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_program_accounts_envelope() {
+        let accounts = vec![KeyedAccount {
+            pubkey: "GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB".to_string(),
+            account: sample_account(),
+        }];
+        assert_golden(
+            to_envelope("getProgramAccounts", Ok(accounts)),
+            include_str!("../tests/golden/get_program_accounts.json"),
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_multiple_accounts_envelope() {
+        let accounts = vec![Some(sample_account()), None];
+        assert_golden(
+            to_envelope("getMultipleAccounts", Ok(accounts)),
+            include_str!("../tests/golden/get_multiple_accounts.json"),
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_transaction_status_envelope() {
+        let status = TransactionStatus {
+            slot: 250_000_000,
+            block_time: Some(1_700_000_000),
+            err: None,
+            fee: 5000,
+            log_messages: vec![
+                "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+                "Program 11111111111111111111111111111111 success".to_string(),
+            ],
+        };
+        assert_golden(
+            to_envelope("getTransaction", Ok(status)),
+            include_str!("../tests/golden/get_transaction.json"),
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_simulate_transaction_envelope() {
+        let simulation = SimulationResult {
+            err: Some("Error processing Instruction 0: custom program error: 0x1".to_string()),
+            logs: vec!["Transfer: insufficient lamports 0, need 1000".to_string()],
+            units_consumed: 150,
+            failed_instruction: Some(FailedInstruction {
+                instruction_index: 0,
+                program_id: Some("11111111111111111111111111111111".to_string()),
+                label: Some("system transfer".to_string()),
+                error: "custom program error: 0x1".to_string(),
+            }),
+        };
+        assert_golden(
+            to_envelope("simulateTransaction", Ok(simulation)),
+            include_str!("../tests/golden/simulate_transaction.json"),
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_error_envelope() {
+        let result: Result<AccountInfo, _> =
+            Err(SolanaUnityError::RpcError("Account not found".to_string()));
+        assert_golden(
+            to_envelope("getAccountInfo", result),
+            include_str!("../tests/golden/error.json"),
+        );
+    }
+
+    #[test]
+    fn test_envelope_string_round_trips() {
+        let json = to_envelope_string("getBalance", Ok(42u64));
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["schema"], SCHEMA_VERSION);
+        assert_eq!(value["ok"], true);
+        assert_eq!(value["result"], 42);
+    }
+}
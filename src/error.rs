@@ -1,26 +1,530 @@
-use libc::c_char;
-use std::ffi::CString;
-use thiserror::Error;
+use serde::Serialize;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::os::raw::c_char;
 
-#[derive(Error, Debug)]
-pub enum SolanaUnityError {
-    #[error("RPC error: {0}")]
-    RpcError(String),
+use solana_sdk::message::Message;
+use solana_sdk::transaction::TransactionError;
 
-    #[error("Transaction error: {0}")]
-    TransactionError(String),
+use crate::anchor::DecodedProgramError;
 
-    #[error("Serialization error: {0}")]
-    SerializationError(String),
+/// Broad category of an error, for callers that branch on what went wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ErrorKind {
+    Rpc,
+    Transaction,
+    Serialization,
+    Wallet,
+    InvalidInput,
+    Ffi,
+    Timeout,
+    NotFound,
+    RateLimited,
+}
+
+impl ErrorKind {
+    /// The code used for errors of this kind that have no more specific one
+    pub fn default_code(self) -> u32 {
+        match self {
+            ErrorKind::Rpc => code::RPC,
+            ErrorKind::Transaction => code::TRANSACTION,
+            ErrorKind::Serialization => code::SERIALIZATION,
+            ErrorKind::Wallet => code::WALLET,
+            ErrorKind::InvalidInput => code::INVALID_INPUT,
+            ErrorKind::Ffi => code::FFI,
+            ErrorKind::Timeout => code::TIMEOUT,
+            ErrorKind::NotFound => code::NOT_FOUND,
+            ErrorKind::RateLimited => code::RATE_LIMITED,
+        }
+    }
+
+    /// Stable name of the kind, e.g. "RpcError", as used in JSON envelopes
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Rpc => "RpcError",
+            ErrorKind::Transaction => "TransactionError",
+            ErrorKind::Serialization => "SerializationError",
+            ErrorKind::Wallet => "WalletError",
+            ErrorKind::InvalidInput => "InvalidInput",
+            ErrorKind::Ffi => "FfiError",
+            ErrorKind::Timeout => "Timeout",
+            ErrorKind::NotFound => "NotFound",
+            ErrorKind::RateLimited => "RateLimited",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ErrorKind::Rpc => "RPC error",
+            ErrorKind::Transaction => "Transaction error",
+            ErrorKind::Serialization => "Serialization error",
+            ErrorKind::Wallet => "Wallet error",
+            ErrorKind::InvalidInput => "Invalid input",
+            ErrorKind::Ffi => "FFI error",
+            ErrorKind::Timeout => "Timeout",
+            ErrorKind::NotFound => "Not found",
+            ErrorKind::RateLimited => "Rate limited",
+        }
+    }
+}
+
+/// Stable numeric error codes. The thousands give the kind; codes within a kind name a
+/// more specific failure. Codes are never reused or renumbered.
+pub mod code {
+    pub const RPC: u32 = 1000;
+    /// The node simulated the transaction and it failed; the program logs are attached
+    pub const RPC_SIMULATION_FAILED: u32 = 1001;
+    /// Preflight: the fee payer cannot pay the transaction fee
+    pub const INSUFFICIENT_FUNDS_FOR_FEE: u32 = 1002;
+    /// Preflight: a System program transfer needs more lamports than the sender has
+    pub const INSUFFICIENT_LAMPORTS: u32 = 1003;
+    /// Preflight: the recent blockhash expired or is unknown to the node; rebuild and
+    /// re-sign with a fresh one
+    pub const BLOCKHASH_NOT_FOUND: u32 = 1004;
+    /// Preflight: the fee payer or another debited account does not exist
+    pub const ACCOUNT_NOT_FOUND: u32 = 1005;
+    /// Preflight: the same transaction has already been processed
+    pub const ALREADY_PROCESSED: u32 = 1006;
+    /// The node has not yet caught up with the minimum context slot the read asked for;
+    /// retry, possibly against another node
+    pub const MIN_CONTEXT_SLOT_NOT_REACHED: u32 = 1007;
+    pub const TRANSACTION: u32 = 2000;
+    /// The block height passed the last valid block height of the transaction's blockhash
+    /// before it was seen, so it can no longer land; rebuild and re-sign with a fresh one
+    pub const BLOCKHASH_EXPIRED: u32 = 2001;
+    pub const SERIALIZATION: u32 = 3000;
+    pub const WALLET: u32 = 4000;
+    /// Wrong password or corrupted keystore; the two are indistinguishable by design
+    pub const DECRYPTION_FAILED: u32 = 4001;
+    pub const INVALID_INPUT: u32 = 5000;
+    /// The account is a durable nonce account that has not been initialized yet
+    pub const NONCE_UNINITIALIZED: u32 = 5001;
+    /// The account is not a durable nonce account
+    pub const NOT_A_NONCE_ACCOUNT: u32 = 5002;
+    /// A PEM or DER private key that could not be parsed
+    pub const INVALID_KEY_ENCODING: u32 = 5003;
+    /// A well-formed private key of an algorithm other than Ed25519
+    pub const UNSUPPORTED_KEY_ALGORITHM: u32 = 5004;
+    /// A Shamir key share that is malformed, mistyped or repeated
+    pub const INVALID_KEY_SHARE: u32 = 5005;
+    /// Key shares from different keys, or from different splits of the same key
+    pub const KEY_SHARE_MISMATCH: u32 = 5006;
+    /// Fewer key shares than the threshold the key was split with
+    pub const NOT_ENOUGH_KEY_SHARES: u32 = 5007;
+    pub const FFI: u32 = 6000;
+    /// A call ran past the budget its caller gave it
+    pub const TIMEOUT: u32 = 7000;
+    /// The HTTP request to the RPC node timed out
+    pub const RPC_TIMEOUT: u32 = 7001;
+    /// The account or transaction does not exist; the context names it
+    pub const NOT_FOUND: u32 = 8000;
+    /// The RPC node answered HTTP 429 even after the client honoured its Retry-After hints
+    pub const RATE_LIMITED: u32 = 9000;
+}
+
+/// What an error was about, where known. Empty for most errors.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorContext {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pubkey: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Program logs, e.g. from a failed preflight simulation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// The transaction error reported by a failed simulation, e.g. "Blockhash not found"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_error: Option<String>,
+    /// The instruction that failed, when the transaction error names one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_index: Option<u8>,
+    /// The program's own error number for `custom program error` failures, e.g. 6001
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_error: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units_consumed: Option<u64>,
+    /// The Anchor error behind `custom_error`, when the program's logs name it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program_error: Option<DecodedProgramError>,
+    /// The instruction the transaction failed at, with the program it called
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_instruction: Option<FailedInstruction>,
+    /// The JSON-RPC method that failed, e.g. "getBalance"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// The RPC endpoint the call went to, with credentials and query string redacted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// The JSON-RPC request solana-client sent, which can differ from `method`, e.g.
+    /// "getSignatureStatuses" for confirmTransaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<String>,
+    /// The code of a JSON-RPC error response, e.g. -32002 for a failed preflight
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_code: Option<i64>,
+    /// The `data` of a JSON-RPC error response, e.g. the preflight simulation result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_data: Option<serde_json::Value>,
+}
+
+/// The instruction a transaction error names, e.g. `{"instructionIndex":1,
+/// "programId":"Tokenkeg...","label":"token transfer","error":"custom program error: 0x1"}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedInstruction {
+    pub instruction_index: u8,
+    /// The program the instruction called, when the failed transaction is at hand
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
+    /// The name this crate's builders gave the instruction, e.g. "system transfer"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The instruction error, e.g. "custom program error: 0x1"
+    pub error: String,
+}
+
+impl FailedInstruction {
+    /// The failed instruction of `error`, or None when the transaction failed as a whole
+    pub fn from_transaction_error(error: &TransactionError) -> Option<Self> {
+        match error {
+            TransactionError::InstructionError(index, instruction_error) => Some(Self {
+                instruction_index: *index,
+                program_id: None,
+                label: None,
+                error: instruction_error.to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Looks the instruction up in the failed transaction's `message` for its program id,
+    /// and in `labels`, one per instruction, for its label
+    pub fn resolve(&mut self, message: &Message, labels: &[Option<String>]) {
+        let index = self.instruction_index as usize;
+        if let Some(instruction) = message.instructions.get(index) {
+            self.program_id = message
+                .account_keys
+                .get(instruction.program_id_index as usize)
+                .map(|program_id| program_id.to_string());
+        }
+        if self.label.is_none() {
+            self.label = labels.get(index).cloned().flatten();
+        }
+    }
+}
+
+impl ErrorContext {
+    pub fn is_empty(&self) -> bool {
+        *self == ErrorContext::default()
+    }
+}
+
+/// The error type of every fallible call in this crate. `Display` gives the same text as
+/// before errors carried a kind and code, e.g. "Invalid input: Invalid pubkey", so it is
+/// safe to show to users and to match in older callers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolanaUnityError {
+    pub kind: ErrorKind,
+    pub code: u32,
+    /// The message without the kind prefix that `Display` adds
+    pub message: String,
+    /// Boxed so that `Result<T, SolanaUnityError>` stays small; most errors leave it empty
+    pub context: Box<ErrorContext>,
+}
+
+impl SolanaUnityError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            code: kind.default_code(),
+            message: message.into(),
+            context: Box::default(),
+        }
+    }
+
+    pub fn with_code(mut self, code: u32) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_pubkey(mut self, pubkey: impl Into<String>) -> Self {
+        self.context.pubkey = Some(pubkey.into());
+        self
+    }
+
+    pub fn with_signature(mut self, signature: impl Into<String>) -> Self {
+        self.context.signature = Some(signature.into());
+        self
+    }
+
+    pub fn with_logs(mut self, logs: Vec<String>) -> Self {
+        self.context.logs = logs;
+        self
+    }
+
+    /// Resolves the failed instruction, if the error names one, against the transaction
+    /// that failed; see `FailedInstruction::resolve`
+    pub fn resolve_failed_instruction(
+        mut self,
+        message: &Message,
+        labels: &[Option<String>],
+    ) -> Self {
+        if let Some(failed) = self.context.failed_instruction.as_mut() {
+            failed.resolve(message, labels);
+        }
+        self
+    }
+}
+
+// Constructors named after the variants of the original error enum, so the many
+// `SolanaUnityError::InvalidInput(format!(...))` call sites keep working unchanged
+#[allow(non_snake_case)]
+impl SolanaUnityError {
+    pub fn RpcError(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Rpc, message)
+    }
+
+    /// An RPC failure that came with program logs, e.g. a failed preflight simulation
+    pub fn RpcErrorWithLogs(message: impl Into<String>, logs: Vec<String>) -> Self {
+        Self::new(ErrorKind::Rpc, message)
+            .with_code(code::RPC_SIMULATION_FAILED)
+            .with_logs(logs)
+    }
+
+    pub fn TransactionError(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Transaction, message)
+    }
+
+    pub fn SerializationError(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Serialization, message)
+    }
+
+    pub fn WalletError(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Wallet, message)
+    }
+
+    pub fn InvalidInput(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidInput, message)
+    }
+
+    pub fn FfiError(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Ffi, message)
+    }
+
+    pub fn NotFound(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NotFound, message)
+    }
+
+    pub fn RateLimited(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::RateLimited, message)
+    }
+
+    pub fn DecryptionFailed() -> Self {
+        Self::new(
+            ErrorKind::Wallet,
+            "Decryption failed: incorrect password or corrupted keystore",
+        )
+        .with_code(code::DECRYPTION_FAILED)
+    }
+
+    pub fn Timeout(ms: u64) -> Self {
+        let mut error = Self::new(ErrorKind::Timeout, format!("Timed out after {} ms", ms));
+        error.context.timeout_ms = Some(ms);
+        error
+    }
+}
+
+impl SolanaUnityError {
+    /// Stable name used as the error kind in JSON envelopes and error objects, e.g.
+    /// "RpcError". Kept from before errors had numeric codes, so existing readers work.
+    pub fn name(&self) -> &'static str {
+        match self.code {
+            code::DECRYPTION_FAILED => "DecryptionFailed",
+            code::MIN_CONTEXT_SLOT_NOT_REACHED => "MinContextSlotNotReached",
+            code::BLOCKHASH_EXPIRED => "BlockhashExpired",
+            _ => self.kind.name(),
+        }
+    }
+
+    /// Program logs captured with the error, empty for most errors
+    pub fn logs(&self) -> &[String] {
+        &self.context.logs
+    }
+
+    /// Whether the same call may succeed if made again: timeouts, rate limiting, lagging
+    /// nodes and RPC failures, except those where the node ran the transaction and it
+    /// failed. Retry logic should rely on this rather than on the message text.
+    pub fn is_retryable(&self) -> bool {
+        match self.kind {
+            ErrorKind::Timeout | ErrorKind::RateLimited => true,
+            ErrorKind::Rpc => matches!(self.code, code::RPC | code::MIN_CONTEXT_SLOT_NOT_REACHED),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for SolanaUnityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            // These messages already read as a complete sentence
+            code::DECRYPTION_FAILED | code::TIMEOUT => f.write_str(&self.message)?,
+            _ => write!(f, "{}: {}", self.kind.label(), self.message)?,
+        }
+
+        // Which RPC call failed, so log lines can be told apart
+        if let Some(method) = &self.context.method {
+            write!(f, " [method={}", method)?;
+            if let Some(endpoint) = &self.context.endpoint {
+                write!(f, " url={}", endpoint)?;
+            }
+            if let Some(signature) = &self.context.signature {
+                let short: String = signature.chars().take(8).collect();
+                write!(f, " signature={}", short)?;
+            }
+            f.write_str("]")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SolanaUnityError {}
+
+impl From<serde_json::Error> for SolanaUnityError {
+    fn from(error: serde_json::Error) -> Self {
+        SolanaUnityError::SerializationError(error.to_string())
+    }
+}
+
+impl From<bincode::Error> for SolanaUnityError {
+    fn from(error: bincode::Error) -> Self {
+        SolanaUnityError::SerializationError(error.to_string())
+    }
+}
+
+impl From<solana_sdk::pubkey::ParsePubkeyError> for SolanaUnityError {
+    fn from(error: solana_sdk::pubkey::ParsePubkeyError) -> Self {
+        SolanaUnityError::InvalidInput(format!("Invalid pubkey: {}", error))
+    }
+}
+
+// Classifies the failure (see rpc::classify) and keeps what the client knew about it: the
+// request that was sent and the JSON-RPC error code and data
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+impl From<solana_client::client_error::ClientError> for SolanaUnityError {
+    fn from(error: solana_client::client_error::ClientError) -> Self {
+        use solana_client::client_error::ClientErrorKind;
+        use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+
+        let mut converted = crate::rpc::classify(&error);
+        converted.context.request = error.request.as_ref().map(|request| request.to_string());
+        if let ClientErrorKind::RpcError(RpcError::RpcResponseError { code, data, .. }) =
+            error.kind()
+        {
+            converted.context.rpc_code = Some(*code);
+            converted.context.rpc_data = match data {
+                RpcResponseErrorData::Empty => None,
+                RpcResponseErrorData::SendTransactionPreflightFailure(result) => {
+                    serde_json::to_value(result).ok()
+                }
+                RpcResponseErrorData::NodeUnhealthy { num_slots_behind } => {
+                    Some(serde_json::json!({ "numSlotsBehind": num_slots_behind }))
+                }
+            };
+        }
+        converted
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+impl SolanaUnityError {
+    /// Converts a failed solana-client call made for `method`, e.g. "getBalance"
+    pub fn from_client_error(
+        method: &str,
+        error: solana_client::client_error::ClientError,
+    ) -> Self {
+        let mut converted = Self::from(error);
+        converted.context.method = Some(method.to_string());
+        converted
+    }
+}
+
+// The full error as JSON: kind, code, name, message, display text, context and
+// retryability
+fn error_json(error: &SolanaUnityError) -> String {
+    serde_json::json!({
+        "kind": error.kind,
+        "code": error.code,
+        "name": error.name(),
+        "message": error.message,
+        "display": error.to_string(),
+        "context": error.context,
+        "retryable": error.is_retryable(),
+    })
+    .to_string()
+}
+
+/// An error captured for the FFI error-object functions. It owns every string it hands
+/// out, so they stay valid after the failing call returns, until the object is destroyed.
+#[derive(Debug)]
+pub struct ErrorObject {
+    code: CString,
+    numeric_code: u32,
+    message: CString,
+    logs_json: CString,
+    json: CString,
+    retryable: bool,
+}
 
-    #[error("Wallet error: {0}")]
-    WalletError(String),
+impl ErrorObject {
+    pub fn new(error: &SolanaUnityError) -> Self {
+        let logs_json = serde_json::to_string(error.logs()).unwrap_or_else(|_| "[]".to_string());
+        Self {
+            code: lossy_c_string(error.name().to_string()),
+            numeric_code: error.code,
+            message: lossy_c_string(error.to_string()),
+            logs_json: lossy_c_string(logs_json),
+            json: lossy_c_string(error_json(error)),
+            retryable: error.is_retryable(),
+        }
+    }
+
+    /// The error name, as in `SolanaUnityError::name`
+    pub fn code(&self) -> &CStr {
+        &self.code
+    }
+
+    /// The numeric code, one of the constants in `error::code`
+    pub fn numeric_code(&self) -> u32 {
+        self.numeric_code
+    }
+
+    pub fn message(&self) -> &CStr {
+        &self.message
+    }
+
+    /// The captured logs as a JSON array of strings
+    pub fn logs_json(&self) -> &CStr {
+        &self.logs_json
+    }
+
+    /// The whole error as JSON, as produced by `error_to_c_json`
+    pub fn json(&self) -> &CStr {
+        &self.json
+    }
 
-    #[error("Invalid input: {0}")]
-    InvalidInput(String),
+    pub fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+}
 
-    #[error("FFI error: {0}")]
-    FfiError(String),
+// Drops interior NULs rather than losing the whole string
+fn lossy_c_string(value: String) -> CString {
+    let mut bytes = value.into_bytes();
+    bytes.retain(|b| *b != 0);
+    CString::new(bytes).unwrap_or_default()
 }
 
 // Convert error to C string for FFI
@@ -31,6 +535,13 @@ pub fn error_to_c_string(error: &SolanaUnityError) -> *mut c_char {
     c_error.into_raw()
 }
 
+// Like error_to_c_string, but with the full structure as JSON, e.g.
+// {"kind":"InvalidInput","code":5000,"name":"InvalidInput","message":"Invalid pubkey",
+//  "display":"Invalid input: Invalid pubkey","context":{"pubkey":"..."},"retryable":false}
+pub fn error_to_c_json(error: &SolanaUnityError) -> *mut c_char {
+    lossy_c_string(error_json(error)).into_raw()
+}
+
 // Free C string (to be called from C#)
 pub unsafe fn free_c_string(ptr: *mut c_char) {
     if !ptr.is_null() {
@@ -39,3 +550,118 @@ pub unsafe fn free_c_string(ptr: *mut c_char) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_matches_previous_format() {
+        assert_eq!(
+            SolanaUnityError::InvalidInput("Invalid pubkey").to_string(),
+            "Invalid input: Invalid pubkey"
+        );
+        assert_eq!(
+            SolanaUnityError::RpcErrorWithLogs("simulation failed", vec!["log".into()]).to_string(),
+            "RPC error: simulation failed"
+        );
+        assert_eq!(
+            SolanaUnityError::Timeout(250).to_string(),
+            "Timed out after 250 ms"
+        );
+        assert_eq!(
+            SolanaUnityError::DecryptionFailed().to_string(),
+            "Decryption failed: incorrect password or corrupted keystore"
+        );
+    }
+
+    #[test]
+    fn test_codes_and_names() {
+        let error = SolanaUnityError::DecryptionFailed();
+        assert_eq!(error.kind, ErrorKind::Wallet);
+        assert_eq!(error.code, code::DECRYPTION_FAILED);
+        assert_eq!(error.name(), "DecryptionFailed");
+
+        let error = SolanaUnityError::RpcErrorWithLogs("failed", vec!["log".into()]);
+        assert_eq!(error.code, code::RPC_SIMULATION_FAILED);
+        assert_eq!(error.name(), "RpcError");
+        assert!(!error.is_retryable());
+        assert!(SolanaUnityError::RpcError("node unavailable").is_retryable());
+    }
+
+    #[test]
+    fn test_error_json_carries_context() {
+        let error = SolanaUnityError::InvalidInput("Account has no data")
+            .with_pubkey("11111111111111111111111111111111");
+        let ptr = error_to_c_json(&error);
+        let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        unsafe { free_c_string(ptr) };
+
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["kind"], "InvalidInput");
+        assert_eq!(json["code"], code::INVALID_INPUT);
+        assert_eq!(json["message"], "Account has no data");
+        assert_eq!(json["display"], "Invalid input: Account has no data");
+        assert_eq!(
+            json["context"]["pubkey"],
+            "11111111111111111111111111111111"
+        );
+        assert!(json["context"].get("logs").is_none());
+    }
+
+    #[test]
+    fn test_failed_instruction_resolves_against_message() {
+        use solana_sdk::instruction::{Instruction, InstructionError};
+        use solana_sdk::pubkey::Pubkey;
+
+        let payer = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let message = Message::new(
+            &[
+                solana_sdk::system_instruction::transfer(&payer, &Pubkey::new_unique(), 1),
+                Instruction::new_with_bytes(program, &[1], vec![]),
+            ],
+            Some(&payer),
+        );
+        let failed = TransactionError::InstructionError(1, InstructionError::Custom(42));
+
+        let mut error = SolanaUnityError::TransactionError(failed.to_string());
+        error.context.failed_instruction = FailedInstruction::from_transaction_error(&failed);
+        let error =
+            error.resolve_failed_instruction(&message, &[None, Some("mint reward".to_string())]);
+
+        let ptr = error_to_c_json(&error);
+        let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        unsafe { free_c_string(ptr) };
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            json["context"]["failedInstruction"],
+            serde_json::json!({
+                "instructionIndex": 1,
+                "programId": program.to_string(),
+                "label": "mint reward",
+                "error": "custom program error: 0x2a",
+            })
+        );
+
+        // Errors about the transaction as a whole name no instruction
+        assert!(
+            FailedInstruction::from_transaction_error(&TransactionError::AccountInUse).is_none()
+        );
+    }
+
+    #[test]
+    fn test_display_names_the_failed_call() {
+        let mut error = SolanaUnityError::RpcError("node unavailable")
+            .with_signature("5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnb");
+        assert_eq!(error.to_string(), "RPC error: node unavailable");
+
+        error.context.method = Some("sendTransaction".to_string());
+        error.context.endpoint = Some("https://rpc.example.com/?redacted".to_string());
+        assert_eq!(
+            error.to_string(),
+            "RPC error: node unavailable [method=sendTransaction \
+             url=https://rpc.example.com/?redacted signature=5VERv8NM]"
+        );
+    }
+}
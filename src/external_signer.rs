@@ -0,0 +1,80 @@
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
+use std::sync::Mutex;
+
+use crate::error::SolanaUnityError;
+
+/// Callback that signs `message` for `pubkey`, writing 64 bytes to `signature_out`.
+/// Must return 0 on success; any other value is treated as a failure.
+pub type ExternalSignerCallback = extern "C" fn(
+    pubkey: *const c_char,
+    message: *const c_uchar,
+    message_len: usize,
+    signature_out: *mut c_uchar,
+    user_data: *mut c_void,
+) -> c_int;
+
+#[derive(Clone, Copy)]
+struct ExternalSigner {
+    callback: ExternalSignerCallback,
+    user_data: *mut c_void,
+}
+
+// The user data pointer is opaque to us and only ever handed back to the callback
+unsafe impl Send for ExternalSigner {}
+
+static EXTERNAL_SIGNER: Mutex<Option<ExternalSigner>> = Mutex::new(None);
+
+/// Registers the process-wide external signer, replacing any previous one. Passing None
+/// removes it.
+pub fn set_external_signer(callback: Option<ExternalSignerCallback>, user_data: *mut c_void) {
+    let mut signer = EXTERNAL_SIGNER.lock().unwrap_or_else(|e| e.into_inner());
+    *signer = callback.map(|callback| ExternalSigner {
+        callback,
+        user_data,
+    });
+}
+
+pub fn has_external_signer() -> bool {
+    EXTERNAL_SIGNER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some()
+}
+
+/// Asks the registered external signer to sign `message` for `pubkey`. The returned bytes
+/// are not verified here.
+pub fn sign(pubkey: &str, message: &[u8]) -> Result<[u8; 64], SolanaUnityError> {
+    // Copy the signer out so the lock is not held while calling back into the host,
+    // which may itself register a new signer
+    let signer = EXTERNAL_SIGNER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .ok_or_else(|| {
+            SolanaUnityError::WalletError(format!(
+                "No external signer registered to sign for {}",
+                pubkey
+            ))
+        })?;
+
+    let c_pubkey = CString::new(pubkey)
+        .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid pubkey: {}", e)))?;
+
+    let mut signature = [0u8; 64];
+    let status = (signer.callback)(
+        c_pubkey.as_ptr(),
+        message.as_ptr(),
+        message.len(),
+        signature.as_mut_ptr(),
+        signer.user_data,
+    );
+
+    if status != 0 {
+        return Err(SolanaUnityError::WalletError(format!(
+            "External signer failed for {} with status {}",
+            pubkey, status
+        )));
+    }
+
+    Ok(signature)
+}
@@ -1,14 +1,79 @@
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::{DecodeError, Engine};
+#[cfg(feature = "rpc")]
+use serde::Serialize;
+use serde_json::json;
+use solana_sdk::signature::Signer;
+use solana_unity_macros::ffi_export;
+#[cfg(feature = "rpc")]
+use std::collections::BTreeMap;
 use std::ffi::{CStr, CString};
-use std::os::raw::{c_char, c_int, c_uchar};
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
 use std::ptr;
 use std::slice;
+#[cfg(feature = "rpc")]
+use std::sync::{Mutex, MutexGuard};
+#[cfg(feature = "rpc")]
+use std::time::Duration;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::account::Account;
-use crate::error::{error_to_c_string, free_c_string, SolanaUnityError};
+use crate::address_book::AddressBook;
+use crate::allocator::{self, AllocFn, FreeFn};
+use crate::amount;
+use crate::anchor;
+#[cfg(feature = "rpc")]
+use crate::balance_watcher::{BalanceKind, BalanceWatcher};
+use crate::decode;
+use crate::deeplink::DeeplinkSession;
+#[cfg(feature = "rpc")]
+use crate::envelope::to_envelope_string;
+use crate::error::{error_to_c_string, free_c_string, ErrorObject, SolanaUnityError};
+use crate::external_signer::{self, ExternalSignerCallback};
+use crate::handle::{deref_handle, destroy_handle, handle_ref, into_handle, Handle};
 use crate::instruction::{InstructionBuilder, TokenInstructions};
+use crate::logging::{self, LogCallback};
 use crate::pda::ProgramDerivedAddress;
-use crate::rpc::RpcClient;
+use crate::requests::{self, RequestStatus};
+#[cfg(feature = "rpc")]
+use crate::rpc::{PartialFailure, RpcClient, RpcClientConfig, RpcProxy, SimulationResult};
+use crate::runtime::{self, RuntimeConfig};
+use crate::session::SessionKey;
+#[cfg(feature = "rpc")]
+use crate::solana_pay::TransactionRequest;
+#[cfg(feature = "websocket")]
+use crate::subscription::{Subscription, SubscriptionState};
 use crate::transaction::Transaction;
+use crate::vanity::GrindHandle;
+use crate::wallet_manager::WalletManager;
+
+/// JSON manifest of the symbols this build exports, `{"prefix": "...", "symbols": [...]}`.
+/// Generated by build.rs; see the `symbol-prefix` feature.
+pub const EXPORTED_SYMBOLS_JSON: &str = include_str!(concat!(env!("OUT_DIR"), "/symbols.json"));
+
+/// Returned by solana_get_abi_version. Bump it whenever an exported function or `#[repr(C)]`
+/// type changes in a way that breaks existing callers, so bindings built against an older
+/// surface can refuse to load this binary.
+pub const ABI_VERSION: u32 = 1;
+
+// Opaque handle types. The generated C header declares each one as an incomplete struct,
+// e.g. `typedef struct SolanaRpcClient SolanaRpcClient;`, so C and C# callers only ever
+// hold pointers to them.
+#[cfg(feature = "rpc")]
+pub type SolanaRpcClient = Handle<RpcClient>;
+pub type SolanaTransaction = Handle<Transaction>;
+pub type SolanaAccount = Handle<Account>;
+pub type SolanaGrindHandle = Handle<GrindHandle>;
+pub type SolanaWalletManager = Handle<WalletManager>;
+pub type SolanaDeeplinkSession = Handle<DeeplinkSession>;
+pub type SolanaErrorObj = Handle<ErrorObject>;
+#[cfg(feature = "websocket")]
+pub type SolanaSubscription = Handle<Subscription>;
+#[cfg(feature = "rpc")]
+pub type SolanaBalanceWatcher = Handle<BalanceWatcher>;
+pub type SolanaAddressBook = Handle<AddressBook>;
+pub type SolanaSessionKey = Handle<SessionKey>;
 
 // Helper to convert C string to Rust string
 unsafe fn c_str_to_string(c_str: *const c_char) -> Result<String, SolanaUnityError> {
@@ -26,6 +91,387 @@ unsafe fn c_str_to_string(c_str: *const c_char) -> Result<String, SolanaUnityErr
     }
 }
 
+// Longest instruction data, or serialized transaction, the network accepts
+const MAX_WIRE_LEN: usize = solana_sdk::packet::PACKET_DATA_SIZE;
+// Longest encoded instruction list a transaction's instructions can take up. Each account
+// is a 1-byte index on the wire but a 34-byte account meta in the encoding.
+const MAX_ENCODED_INSTRUCTIONS_LEN: usize = MAX_WIRE_LEN * 34;
+// Most signatures a transaction fits on the wire
+const MAX_SIGNERS: usize = MAX_WIRE_LEN / 64;
+// No slice may be longer than isize::MAX bytes
+const MAX_SLICE_LEN: usize = isize::MAX as usize;
+
+// Borrows `len` bytes at `data`. A null pointer is only accepted with a length of 0, and a
+// length over `max_len` is rejected before anything is read, since it comes from a caller
+// bug such as a length in bits or a stale variable. `name` is the parameter, for the error.
+unsafe fn byte_slice<'a>(
+    data: *const c_uchar,
+    len: usize,
+    name: &str,
+    max_len: usize,
+) -> Result<&'a [u8], SolanaUnityError> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(SolanaUnityError::FfiError(format!(
+            "Null {} pointer with a length of {}",
+            name, len
+        )));
+    }
+    if len > max_len.min(MAX_SLICE_LEN) {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "{} is {} bytes, more than the maximum of {}",
+            name, len, max_len
+        )));
+    }
+    Ok(unsafe { slice::from_raw_parts(data, len) })
+}
+
+// Like byte_slice for a 64-byte keypair, telling a 32-byte seed apart from other lengths
+unsafe fn keypair_slice<'a>(
+    data: *const c_uchar,
+    len: usize,
+    name: &str,
+) -> Result<&'a [u8], SolanaUnityError> {
+    match len {
+        64 => unsafe { byte_slice(data, len, name, 64) },
+        32 => Err(SolanaUnityError::InvalidInput(format!(
+            "{} is 32 bytes, which is an ed25519 seed; pass the 64-byte keypair, or use \
+             solana_account_from_seed for seeds",
+            name
+        ))),
+        _ => Err(SolanaUnityError::InvalidInput(format!(
+            "{} must be a 64-byte keypair, got {} bytes",
+            name, len
+        ))),
+    }
+}
+
+// Helper to split a flattened seeds buffer into individual seeds using per-seed lengths
+unsafe fn split_seeds<'a>(
+    seeds_data: *const c_uchar,
+    seeds_data_len: usize,
+    seed_lengths: *const usize,
+    seed_count: usize,
+) -> Result<Vec<&'a [u8]>, SolanaUnityError> {
+    if (seeds_data.is_null() && seeds_data_len > 0) || (seed_lengths.is_null() && seed_count > 0) {
+        return Err(SolanaUnityError::FfiError(
+            "Null seeds pointer provided".to_string(),
+        ));
+    }
+
+    let data = unsafe { byte_slice(seeds_data, seeds_data_len, "seeds_data", MAX_SLICE_LEN) }?;
+    let lengths: &[usize] = if seed_count == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(seed_lengths, seed_count) }
+    };
+
+    let mut seeds = Vec::with_capacity(lengths.len());
+    let mut position = 0usize;
+    for (i, len) in lengths.iter().enumerate() {
+        let end = match position.checked_add(*len) {
+            Some(end) if end <= data.len() => end,
+            _ => {
+                return Err(SolanaUnityError::InvalidInput(format!(
+                    "Seed {} extends past the end of the seeds buffer",
+                    i
+                )));
+            }
+        };
+        seeds.push(&data[position..end]);
+        position = end;
+    }
+
+    Ok(seeds)
+}
+
+// Helper to read `count` C strings from an array; null is allowed when `count` is 0
+unsafe fn c_str_array(
+    strings: *const *const c_char,
+    count: usize,
+) -> Result<Vec<String>, SolanaUnityError> {
+    if strings.is_null() && count > 0 {
+        return Err(SolanaUnityError::FfiError(
+            "Null pointer(s) provided".to_string(),
+        ));
+    }
+    (0..count)
+        .map(|i| unsafe { c_str_to_string(*strings.add(i)) })
+        .collect()
+}
+
+// Helper to copy `value` and a NUL terminator into a caller-provided buffer. `written_out`
+// (optional) always receives the size needed including the terminator; a `buf_len` of 0
+// only queries that size.
+unsafe fn write_str_to_buf(
+    value: &str,
+    buf: *mut c_char,
+    buf_len: usize,
+    written_out: *mut usize,
+) -> Result<(), SolanaUnityError> {
+    if value.as_bytes().contains(&0) {
+        return Err(SolanaUnityError::FfiError(
+            "String contains an interior NUL byte".to_string(),
+        ));
+    }
+
+    let required = value.len() + 1;
+    if !written_out.is_null() {
+        unsafe {
+            *written_out = required;
+        }
+    }
+
+    if buf_len == 0 {
+        return Ok(());
+    }
+
+    if buf.is_null() {
+        return Err(SolanaUnityError::FfiError(
+            "Null buffer pointer provided".to_string(),
+        ));
+    }
+
+    if buf_len < required {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Buffer too small: {} bytes required, {} provided",
+            required, buf_len
+        )));
+    }
+
+    unsafe {
+        ptr::copy_nonoverlapping(value.as_ptr(), buf as *mut u8, value.len());
+        *buf.add(value.len()) = 0;
+    }
+    Ok(())
+}
+
+// Owned byte buffer returned by value. `data` is null only on failure; empty results have
+// a non-null `data` and a `len` of 0. Release it with solana_buffer_free.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SolanaBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl SolanaBuffer {
+    fn from_vec(data: Vec<u8>) -> Self {
+        let len = data.len();
+        let data = Box::into_raw(data.into_boxed_slice()) as *mut u8;
+        Self { data, len }
+    }
+
+    fn null() -> Self {
+        Self {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+// `value` is only meaningful when `ok` is 1
+#[repr(C)]
+#[derive(Debug)]
+pub struct SolanaU64Result {
+    pub ok: c_int,
+    pub value: u64,
+}
+
+// On success `ok` is 1 and `value` is a string owned by the caller, freed with
+// solana_free_string. On failure `ok` is 0 and `value` is null.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SolanaStringResult {
+    pub ok: c_int,
+    pub value: *mut c_char,
+}
+
+// An owned array of strings returned by value, released with solana_string_array_free.
+// An empty result has a non-null `items` and a `len` of 0; `items` is null only on failure.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SolanaStringArray {
+    pub items: *mut *mut c_char,
+    pub len: usize,
+}
+
+impl SolanaStringArray {
+    fn from_vec(strings: Vec<String>) -> Self {
+        let items: Box<[*mut c_char]> = strings.into_iter().map(string_to_c).collect();
+        let len = items.len();
+        Self {
+            items: Box::into_raw(items) as *mut *mut c_char,
+            len,
+        }
+    }
+
+    fn null() -> Self {
+        Self {
+            items: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+// The layouts above are part of the C ABI
+const _: () = assert!(std::mem::size_of::<SolanaBuffer>() == 2 * std::mem::size_of::<usize>());
+const _: () = assert!(std::mem::align_of::<SolanaBuffer>() == std::mem::align_of::<usize>());
+const _: () = assert!(std::mem::size_of::<SolanaU64Result>() == 16);
+const _: () = assert!(std::mem::align_of::<SolanaU64Result>() == 8);
+const _: () =
+    assert!(std::mem::size_of::<SolanaStringResult>() == 2 * std::mem::size_of::<usize>());
+const _: () = assert!(std::mem::size_of::<SolanaStringArray>() == 2 * std::mem::size_of::<usize>());
+
+// Helper to decode base64 leniently per RFC 4648: whitespace is skipped and padding is
+// optional. Errors give the position of the first bad character in the original string.
+fn decode_base64_lenient(name: &str, input: &str) -> Result<Vec<u8>, SolanaUnityError> {
+    let invalid = |reason: String| {
+        SolanaUnityError::InvalidInput(format!("Invalid base64 {}: {}", name, reason))
+    };
+
+    let mut compact = String::with_capacity(input.len());
+    let mut positions = Vec::with_capacity(input.len());
+    for (position, c) in input.char_indices() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if !(c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '=') {
+            return Err(invalid(format!(
+                "unexpected character {:?} at position {}",
+                c, position
+            )));
+        }
+        compact.push(c);
+        positions.push(position);
+    }
+
+    let engine = GeneralPurpose::new(
+        &alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+
+    engine.decode(&compact).map_err(|e| match e {
+        DecodeError::InvalidByte(offset, byte) => invalid(format!(
+            "unexpected character {:?} at position {}",
+            byte as char, positions[offset]
+        )),
+        DecodeError::InvalidLastSymbol(offset, byte) => invalid(format!(
+            "invalid final character {:?} at position {}",
+            byte as char, positions[offset]
+        )),
+        DecodeError::InvalidLength => invalid("truncated input".to_string()),
+        DecodeError::InvalidPadding => invalid("invalid padding".to_string()),
+    })
+}
+
+// Helper to hand a string to C. Interior NUL bytes, which never occur in RPC output, are
+// dropped rather than failing the whole call.
+fn string_to_c(value: String) -> *mut c_char {
+    CString::new(value)
+        .unwrap_or_else(|e| {
+            let mut bytes = e.into_vec();
+            bytes.retain(|b| *b != 0);
+            CString::new(bytes).unwrap_or_default()
+        })
+        .into_raw()
+}
+
+// Helper for the JSON-returning RPC functions. Whatever the RPC method returns, success or
+// failure, comes back as an envelope (see envelope.rs). `error_out` is only set, and null
+// returned, when the call cannot be made at all: a null or invalid client handle or a null
+// string argument.
+#[cfg(feature = "rpc")]
+fn rpc_envelope<T: Serialize>(
+    client: *const SolanaRpcClient,
+    arg: *const c_char,
+    method: &str,
+    error_out: *mut *mut c_char,
+    call: impl FnOnce(&RpcClient, &str) -> Result<T, SolanaUnityError>,
+) -> Option<String> {
+    let prepared = handle_ref(client).and_then(|client| {
+        let arg = unsafe { c_str_to_string(arg) }?;
+        Ok((client, arg))
+    });
+    let (client, arg) = handle_result(prepared, error_out)?;
+    Some(to_envelope_string(method, call(&client, arg.as_str())))
+}
+
+// Runs `call` on the client and writes its result to `out`, for the functions returning a
+// u64 whose every value is valid. Returns 1 on success and 0 on failure.
+#[cfg(feature = "rpc")]
+fn write_u64_result(
+    client: *const SolanaRpcClient,
+    out: *mut u64,
+    error_out: *mut *mut c_char,
+    call: impl FnOnce(&RpcClient) -> Result<u64, SolanaUnityError>,
+) -> c_int {
+    let result = if out.is_null() {
+        Err(SolanaUnityError::FfiError(
+            "Null output pointer".to_string(),
+        ))
+    } else {
+        handle_ref(client).and_then(|client| call(&client))
+    };
+    match handle_result(result, error_out) {
+        Some(value) => {
+            unsafe {
+                *out = value;
+            }
+            1
+        }
+        None => 0,
+    }
+}
+
+// Like handle_result, for the _e variants that report errors as error objects
+#[cfg(feature = "rpc")]
+fn handle_result_obj<T>(
+    result: Result<T, SolanaUnityError>,
+    error_out: *mut *mut SolanaErrorObj,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = into_handle(ErrorObject::new(&err));
+                }
+            }
+            None
+        }
+    }
+}
+
+// Runs `call` against `client` within `timeout_ms`. The call goes to a request worker so
+// the caller can stop waiting at the deadline and get a Timeout error; the abandoned call
+// finishes on the worker and its result is dropped. 0 runs it here under the client's own
+// timeout.
+#[cfg(feature = "rpc")]
+fn call_with_timeout<T, F>(
+    client: &RpcClient,
+    timeout_ms: u64,
+    call: F,
+) -> Result<T, SolanaUnityError>
+where
+    T: Send + 'static,
+    F: FnOnce(&RpcClient) -> Result<T, SolanaUnityError> + Send + 'static,
+{
+    if timeout_ms == 0 {
+        return call(client);
+    }
+    let client = client.clone();
+    requests::run_with_timeout(Duration::from_millis(timeout_ms), move || call(&client))
+}
+
+// Helper to read the minimum context slot of the _at_slot functions, where 0 means none
+#[cfg(feature = "rpc")]
+fn optional_slot(slot: u64) -> Option<u64> {
+    (slot > 0).then_some(slot)
+}
+
 // Helper to convert Rust result to C result with error
 fn handle_result<T>(result: Result<T, SolanaUnityError>, error_out: *mut *mut c_char) -> Option<T> {
     match result {
@@ -43,12 +489,13 @@ fn handle_result<T>(result: Result<T, SolanaUnityError>, error_out: *mut *mut c_
 
 // RPC Client functions
 
-#[no_mangle]
+#[cfg(feature = "rpc")]
+#[ffi_export]
 pub extern "C" fn solana_create_rpc_client(
     url: *const c_char,
     commitment: *const c_char,
     error_out: *mut *mut c_char,
-) -> *mut RpcClient {
+) -> *mut SolanaRpcClient {
     let url_str = match unsafe { c_str_to_string(url) } {
         Ok(s) => s,
         Err(e) => {
@@ -74,7 +521,7 @@ pub extern "C" fn solana_create_rpc_client(
     };
 
     match RpcClient::new(&url_str, &commitment_str) {
-        Ok(client) => Box::into_raw(Box::new(client)),
+        Ok(client) => into_handle(client),
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -86,18 +533,377 @@ pub extern "C" fn solana_create_rpc_client(
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_destroy_rpc_client(client: *mut RpcClient) {
-    if !client.is_null() {
-        unsafe {
-            let _ = Box::from_raw(client);
+/// Like solana_create_rpc_client, with connection reuse settings. `config_json` may be null
+/// for the defaults, or e.g. {"maxIdlePerHost": 4, "idleTimeoutMs": 90000,
+/// "tcpKeepaliveMs": 60000, "http2PriorKnowledge": false, "proxy": {"url", "username",
+/// "password"}, "compression": true}. Set "compression" to false to receive responses
+/// uncompressed, e.g. to inspect them on the wire.
+///
+/// # Safety
+///
+/// `url`, `commitment` and `config_json` must each be null or a NUL-terminated string.
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_create_rpc_client_configured(
+    url: *const c_char,
+    commitment: *const c_char,
+    config_json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaRpcClient {
+    let config = if config_json.is_null() {
+        Ok(RpcClientConfig::default())
+    } else {
+        unsafe { c_str_to_string(config_json) }.and_then(|json| RpcClientConfig::from_json(&json))
+    };
+    let client = config.and_then(|config| {
+        let url = unsafe { c_str_to_string(url) }?;
+        let commitment = unsafe { c_str_to_string(commitment) }?;
+        RpcClient::new_with_config(&url, &commitment, config)
+    });
+    handle_result(client, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+/// Like solana_create_rpc_client, sending every request through an HTTP or SOCKS5 proxy:
+/// `proxy_url` is e.g. "http://proxy.corp:3128" or "socks5h://10.0.0.2:1080". The username
+/// and password are optional; pass null for a proxy without auth. An invalid proxy URL
+/// fails here rather than on the first request.
+///
+/// # Safety
+///
+/// `url`, `commitment`, `proxy_url`, `proxy_username` and `proxy_password` must each be
+/// null or a NUL-terminated string. `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_create_rpc_client_with_proxy(
+    url: *const c_char,
+    commitment: *const c_char,
+    proxy_url: *const c_char,
+    proxy_username: *const c_char,
+    proxy_password: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaRpcClient {
+    let optional = |value: *const c_char| {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            unsafe { c_str_to_string(value) }.map(Some)
+        }
+    };
+    let proxy = unsafe { c_str_to_string(proxy_url) }.and_then(|proxy_url| {
+        Ok(RpcProxy {
+            url: proxy_url,
+            username: optional(proxy_username)?,
+            password: optional(proxy_password)?,
+        })
+    });
+    let client = proxy.and_then(|proxy| {
+        let url = unsafe { c_str_to_string(url) }?;
+        let commitment = unsafe { c_str_to_string(commitment) }?;
+        let config = RpcClientConfig {
+            proxy: Some(proxy),
+            ..Default::default()
+        };
+        RpcClient::new_with_config(&url, &commitment, config)
+    });
+    handle_result(client, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+/// Returns a getHealth envelope ranking the `urls_count` URLs: healthy endpoints by
+/// latency, fastest first, then the ones that failed with their reason, e.g.
+/// [{"index": 1, "url": "...", "ok": true, "latencyMs": 84}, {"index": 0, "url": "...",
+/// "ok": false, "error": {...}}]. `index` is the URL's position in `urls`.
+///
+/// # Safety
+///
+/// Unless `urls_count` is 0, `urls` must point to that many pointers, each null or a
+/// NUL-terminated string. `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_probe_endpoints(
+    urls: *const *const c_char,
+    urls_count: usize,
+    timeout_ms: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let urls = match handle_result(unsafe { c_str_array(urls, urls_count) }, error_out) {
+        Some(urls) => urls,
+        None => return ptr::null_mut(),
+    };
+    let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+    string_to_c(to_envelope_string(
+        "getHealth",
+        RpcClient::probe_endpoints_ranked(&urls, timeout_ms),
+    ))
+}
+
+/// Creates a client for the fastest healthy endpoint of `urls`, see solana_probe_endpoints.
+/// Fails with every endpoint's reason when none can be used.
+///
+/// # Safety
+///
+/// `commitment` must be null or a NUL-terminated string. Unless `urls_count` is 0, `urls`
+/// must point to that many pointers, each null or a NUL-terminated string. `error_out` must
+/// be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_create_rpc_client_fastest(
+    urls: *const *const c_char,
+    urls_count: usize,
+    commitment: *const c_char,
+    timeout_ms: u64,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaRpcClient {
+    let client = unsafe { c_str_array(urls, urls_count) }.and_then(|urls| {
+        let commitment = unsafe { c_str_to_string(commitment) }?;
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        RpcClient::new_fastest(&urls, &commitment, timeout_ms)
+    });
+    handle_result(client, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+// Destroys a client. For a client from solana_get_or_create_rpc_client this only drops
+// one reference, the same as solana_release_rpc_client.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_destroy_rpc_client(client: *mut SolanaRpcClient) {
+    if release_shared_client(client) == Some(false) {
+        return;
+    }
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(client);
+}
+
+#[cfg(feature = "rpc")]
+struct SharedClient {
+    handle: usize,
+    refs: usize,
+}
+
+// Clients handed out by solana_get_or_create_rpc_client, keyed by (url, commitment)
+#[cfg(feature = "rpc")]
+static SHARED_CLIENTS: Mutex<BTreeMap<(String, String), SharedClient>> =
+    Mutex::new(BTreeMap::new());
+
+#[cfg(feature = "rpc")]
+fn shared_clients() -> MutexGuard<'static, BTreeMap<(String, String), SharedClient>> {
+    SHARED_CLIENTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Drops one reference to a shared client. Returns None if `client` is not shared, and
+// Some(true) once the last reference is gone and the caller should destroy the handle.
+#[cfg(feature = "rpc")]
+fn release_shared_client(client: *mut SolanaRpcClient) -> Option<bool> {
+    let mut clients = shared_clients();
+    let key = clients
+        .iter()
+        .find(|(_, shared)| shared.handle == client as usize)
+        .map(|(key, _)| key.clone())?;
+
+    let shared = clients.get_mut(&key)?;
+    shared.refs -= 1;
+    if shared.refs > 0 {
+        return Some(false);
+    }
+    clients.remove(&key);
+    Some(true)
+}
+
+/// Returns the process-wide client for this url and commitment, creating it on first use.
+/// Each call adds a reference; give each one back with solana_release_rpc_client. The
+/// handle stays valid until the last reference is released.
+///
+/// # Safety
+///
+/// `url` and `commitment` must each be null or a NUL-terminated string. `error_out` must be
+/// null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_or_create_rpc_client(
+    url: *const c_char,
+    commitment: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaRpcClient {
+    let key = unsafe { c_str_to_string(url) }
+        .and_then(|url| Ok((url, unsafe { c_str_to_string(commitment) }?)));
+    let key = match handle_result(key, error_out) {
+        Some(key) => key,
+        None => return ptr::null_mut(),
+    };
+
+    let mut clients = shared_clients();
+    if let Some(shared) = clients.get_mut(&key) {
+        shared.refs += 1;
+        return shared.handle as *mut SolanaRpcClient;
+    }
+
+    match handle_result(RpcClient::new(&key.0, &key.1), error_out) {
+        Some(client) => {
+            let handle = into_handle(client);
+            clients.insert(
+                key,
+                SharedClient {
+                    handle: handle as usize,
+                    refs: 1,
+                },
+            );
+            handle
         }
+        None => ptr::null_mut(),
+    }
+}
+
+// Gives back one reference from solana_get_or_create_rpc_client. Any other client is
+// destroyed outright.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_release_rpc_client(client: *mut SolanaRpcClient) {
+    solana_destroy_rpc_client(client);
+}
+
+/// Returns a new handle sharing the connection of `client`. The two are independent:
+/// destroying either leaves the other usable.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_clone_rpc_client(
+    client: *const SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaRpcClient {
+    let clone = handle_ref(client).map(|client| client.clone());
+    handle_result(clone, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+/// Caches the accounts read through `client` and its clones for `ttl_ms` milliseconds, at
+/// most `max_entries` at a time; see RpcClient::enable_account_cache. Cache hits are logged
+/// at debug level as "getAccountInfo <url> cache hit for <pubkey>".
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_enable_account_cache(
+    client: *const SolanaRpcClient,
+    ttl_ms: u64,
+    max_entries: usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result =
+        handle_ref(client).and_then(|client| client.enable_account_cache(ttl_ms, max_entries));
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_disable_account_cache(
+    client: *const SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(client).map(|client| client.disable_account_cache());
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// Drops `pubkey` from the account cache, e.g. after it was changed by another client
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_invalidate_account(
+    client: *const SolanaRpcClient,
+    pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(client).and_then(|client| {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        client.invalidate_account(&pubkey)
+    });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_invalidate_all_accounts(
+    client: *const SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(client).map(|client| client.invalidate_all());
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// Starts counting this client's RPC calls, shared with its clones; see
+/// solana_get_metrics_json
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_enable_metrics(
+    client: *const SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(client).map(|client| client.enable_metrics());
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_disable_metrics(
+    client: *const SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(client).map(|client| client.disable_metrics());
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// Returns {"enabled": bool, "methods": {method: {"calls", "failures", "totalMs", "p95Ms",
+/// "latencyBuckets"}}}. Free with solana_free_string.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_metrics_json(
+    client: *const SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| client.metrics_snapshot_json());
+    match handle_result(result, error_out) {
+        Some(json) => string_to_c(json),
+        None => ptr::null_mut(),
     }
 }
 
-#[no_mangle]
+// Clears the counts; returns 0 only for an invalid client
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_reset_metrics(client: *const SolanaRpcClient) -> c_int {
+    handle_ref(client)
+        .map(|client| client.reset_metrics())
+        .is_ok() as c_int
+}
+
+#[cfg(feature = "rpc")]
+#[ffi_export]
 pub extern "C" fn solana_get_balance(
-    client: *mut RpcClient,
+    client: *mut SolanaRpcClient,
     pubkey: *const c_char,
     error_out: *mut *mut c_char,
 ) -> u64 {
@@ -124,7 +930,19 @@ pub extern "C" fn solana_get_balance(
         }
     };
 
-    match unsafe { (*client).get_balance(&pubkey_str) } {
+    let client = match handle_ref(client) {
+        Ok(client) => client,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match client.get_balance(&pubkey_str) {
         Ok(balance) => balance,
         Err(e) => {
             if !error_out.is_null() {
@@ -137,9 +955,33 @@ pub extern "C" fn solana_get_balance(
     }
 }
 
-#[no_mangle]
+/// Asks the faucet of a devnet, testnet or local validator for `lamports` for `pubkey` and
+/// returns the airdrop's signature. When the faucet refuses because of its limits the error
+/// is of kind RateLimited and starts with "Rate limited", so callers can back off.
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_request_airdrop(
+    client: *const SolanaRpcClient,
+    pubkey: *const c_char,
+    lamports: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        client.request_airdrop(&pubkey, lamports)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+#[cfg(feature = "rpc")]
+#[ffi_export]
 pub extern "C" fn solana_get_latest_blockhash(
-    client: *mut RpcClient,
+    client: *mut SolanaRpcClient,
     error_out: *mut *mut c_char,
 ) -> *mut c_char {
     if client.is_null() {
@@ -153,7 +995,19 @@ pub extern "C" fn solana_get_latest_blockhash(
         return ptr::null_mut();
     }
 
-    match unsafe { (*client).get_latest_blockhash() } {
+    let client = match handle_ref(client) {
+        Ok(client) => client,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match client.get_latest_blockhash() {
         Ok(blockhash) => match CString::new(blockhash) {
             Ok(c_blockhash) => c_blockhash.into_raw(),
             Err(e) => {
@@ -181,23 +1035,20 @@ pub extern "C" fn solana_get_latest_blockhash(
 
 // Transaction functions
 
-#[no_mangle]
-pub extern "C" fn solana_create_transaction() -> *mut Transaction {
-    Box::into_raw(Box::new(Transaction::new()))
+#[ffi_export]
+pub extern "C" fn solana_create_transaction() -> *mut SolanaTransaction {
+    into_handle(Transaction::new())
 }
 
-#[no_mangle]
-pub extern "C" fn solana_destroy_transaction(transaction: *mut Transaction) {
-    if !transaction.is_null() {
-        unsafe {
-            let _ = Box::from_raw(transaction);
-        }
-    }
+#[ffi_export]
+pub extern "C" fn solana_destroy_transaction(transaction: *mut SolanaTransaction) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(transaction);
 }
 
-#[no_mangle]
+#[ffi_export]
 pub extern "C" fn solana_build_transfer(
-    transaction: *mut Transaction,
+    transaction: *mut SolanaTransaction,
     from_pubkey: *const c_char,
     to_pubkey: *const c_char,
     lamports: u64,
@@ -251,7 +1102,19 @@ pub extern "C" fn solana_build_transfer(
         }
     };
 
-    match unsafe { (*transaction).build_transfer(&from_str, &to_str, lamports, &blockhash_str) } {
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match transaction.build_transfer(&from_str, &to_str, lamports, &blockhash_str) {
         Ok(_) => 1,
         Err(e) => {
             if !error_out.is_null() {
@@ -264,9 +1127,9 @@ pub extern "C" fn solana_build_transfer(
     }
 }
 
-#[no_mangle]
+#[ffi_export]
 pub extern "C" fn solana_sign_transaction(
-    transaction: *mut Transaction,
+    transaction: *mut SolanaTransaction,
     private_key_bytes: *const c_uchar,
     private_key_len: usize,
     error_out: *mut *mut c_char,
@@ -282,9 +1145,32 @@ pub extern "C" fn solana_sign_transaction(
         return 0;
     }
 
-    let private_key = unsafe { slice::from_raw_parts(private_key_bytes, private_key_len) };
+    let private_key =
+        match unsafe { keypair_slice(private_key_bytes, private_key_len, "private_key_bytes") } {
+            Ok(private_key) => private_key,
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&e);
+                    }
+                }
+                return 0;
+            }
+        };
+
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
 
-    match unsafe { (*transaction).sign(private_key) } {
+    match transaction.sign(private_key) {
         Ok(_) => 1,
         Err(e) => {
             if !error_out.is_null() {
@@ -297,13 +1183,73 @@ pub extern "C" fn solana_sign_transaction(
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_send_transaction(
-    client: *mut RpcClient,
-    transaction: *mut Transaction,
+/// Signs the transaction with the account's keypair without exposing the secret key
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_sign_transaction_with_account(
+    transaction: *mut SolanaTransaction,
+    account: *const SolanaAccount,
     error_out: *mut *mut c_char,
-) -> *mut c_char {
-    if client.is_null() || transaction.is_null() {
+) -> c_int {
+    if transaction.is_null() || account.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let account = match handle_ref(account) {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match transaction.sign_with_account(&account) {
+        Ok(_) => 1,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_send_transaction(
+    client: *mut SolanaRpcClient,
+    transaction: *mut SolanaTransaction,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    if client.is_null() || transaction.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
@@ -314,8 +1260,32 @@ pub extern "C" fn solana_send_transaction(
         return ptr::null_mut();
     }
 
+    let client = match handle_ref(client) {
+        Ok(client) => client,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
     // Get transaction
-    let tx_result = unsafe { (*transaction).get_transaction() };
+    let transaction = match handle_ref(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let tx_result = transaction.get_transaction();
     let tx = match tx_result {
         Ok(tx) => tx,
         Err(e) => {
@@ -329,7 +1299,10 @@ pub extern "C" fn solana_send_transaction(
     };
 
     // Send the transaction
-    match unsafe { (*client).send_transaction(tx) } {
+    match client
+        .send_transaction(tx)
+        .map_err(|e| transaction.annotate_error(e))
+    {
         Ok(signature) => match CString::new(signature) {
             Ok(c_signature) => c_signature.into_raw(),
             Err(e) => {
@@ -355,27 +1328,370 @@ pub extern "C" fn solana_send_transaction(
     }
 }
 
-// Account functions
+/// Signs the transaction with the account, sends it and waits up to `timeout_ms` for the
+/// client's commitment, returning the signature. If the blockhash expires before the node
+/// accepts it, the transaction is given a fresh one and re-signed, at most `max_rebuilds`
+/// times; the handle then holds the rebuilt transaction.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_send_with_refresh(
+    client: *const SolanaRpcClient,
+    transaction: *mut SolanaTransaction,
+    account: *const SolanaAccount,
+    max_rebuilds: u8,
+    timeout_ms: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| {
+        let mut transaction = deref_handle(transaction)?;
+        let account = handle_ref(account)?;
+        client.send_with_refresh(&mut transaction, &account, max_rebuilds, timeout_ms)
+    });
+    match handle_result(result, error_out) {
+        Some(signature) => string_to_c(signature),
+        None => ptr::null_mut(),
+    }
+}
 
-#[no_mangle]
-pub extern "C" fn solana_create_account() -> *mut Account {
-    Box::into_raw(Box::new(Account::new()))
+/// Sends a signed transaction and waits up to `timeout_ms` for the client's commitment.
+/// Returns the signature and writes the slot it landed in to `slot_out` (optional), to pass
+/// to the _at_slot reads. If the blockhash was set with
+/// solana_transaction_set_blockhash_with_expiry, it fails with code 2001 as soon as the
+/// blockhash expires without the transaction having been seen.
+///
+/// # Safety
+///
+/// `slot_out` and `error_out` must each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_send_and_confirm_transaction(
+    client: *const SolanaRpcClient,
+    transaction: *const SolanaTransaction,
+    timeout_ms: u64,
+    slot_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| {
+        let transaction = handle_ref(transaction)?;
+        client
+            .send_and_confirm_transaction_with_expiry(
+                transaction.get_transaction()?,
+                transaction.last_valid_block_height(),
+                timeout_ms,
+            )
+            .map_err(|e| transaction.annotate_error(e))
+    });
+    match handle_result(result, error_out) {
+        Some(confirmed) => {
+            if !slot_out.is_null() {
+                unsafe { *slot_out = confirmed.slot };
+            }
+            string_to_c(confirmed.signature)
+        }
+        None => ptr::null_mut(),
+    }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_destroy_account(account: *mut Account) {
-    if !account.is_null() {
-        unsafe {
-            let _ = Box::from_raw(account);
+// The _t variants below give up after `timeout_ms` with a "Timed out" error, 0 meaning
+// the client default. A non-zero timeout runs the call on a request worker.
+
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_balance_t(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    timeout_ms: u64,
+    error_out: *mut *mut c_char,
+) -> u64 {
+    let result = handle_ref(client).and_then(|client| {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        call_with_timeout(&client, timeout_ms, move |client| {
+            client.get_balance(&pubkey)
+        })
+    });
+    handle_result(result, error_out).unwrap_or(0)
+}
+
+/// Writes the latest blockhash to `blockhash_out`, to be freed with solana_free_string, and
+/// the last block height at which it is valid to `last_valid_height_out`. Returns 1 on
+/// success and 0 on error, leaving both outputs untouched.
+///
+/// # Safety
+///
+/// `blockhash_out`, `last_valid_height_out` and `error_out` must each be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_latest_blockhash_with_expiry(
+    client: *const SolanaRpcClient,
+    blockhash_out: *mut *mut c_char,
+    last_valid_height_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = if blockhash_out.is_null() || last_valid_height_out.is_null() {
+        Err(SolanaUnityError::FfiError(
+            "Null output pointer".to_string(),
+        ))
+    } else {
+        handle_ref(client).and_then(|client| client.get_latest_blockhash_with_expiry())
+    };
+    match handle_result(result, error_out) {
+        Some((blockhash, last_valid_height)) => {
+            unsafe {
+                *blockhash_out = string_to_c(blockhash);
+                *last_valid_height_out = last_valid_height;
+            }
+            1
         }
+        None => 0,
+    }
+}
+
+/// Returns the current epoch and the cluster's progress through it as JSON: {"epoch",
+/// "slotIndex", "slotsInEpoch", "absoluteSlot", "blockHeight"}
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_epoch_info(
+    client: *const SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| client.get_epoch_info_json());
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Writes the slot the node has reached at the client's commitment to `slot_out`. Returns 1
+/// on success and 0 on failure, since 0 is also a valid slot.
+///
+/// # Safety
+///
+/// `slot_out` and `error_out` must each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_slot(
+    client: *const SolanaRpcClient,
+    slot_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    write_u64_result(client, slot_out, error_out, |client| client.get_slot())
+}
+
+/// Writes the block height at the client's commitment to `height_out`. Returns 1 on
+/// success and 0 on failure.
+///
+/// # Safety
+///
+/// `height_out` and `error_out` must each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_block_height(
+    client: *const SolanaRpcClient,
+    height_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    write_u64_result(client, height_out, error_out, |client| {
+        client.get_block_height()
+    })
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_latest_blockhash_t(
+    client: *mut SolanaRpcClient,
+    timeout_ms: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| {
+        call_with_timeout(&client, timeout_ms, |client| client.get_latest_blockhash())
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_send_transaction_t(
+    client: *mut SolanaRpcClient,
+    transaction: *mut SolanaTransaction,
+    timeout_ms: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(transaction)
+        .and_then(|transaction| transaction.get_transaction().cloned())
+        .and_then(|tx| {
+            let client = handle_ref(client)?;
+            call_with_timeout(&client, timeout_ms, move |client| {
+                client.send_transaction(&tx)
+            })
+        });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns a getAccountInfo envelope. A timeout is reported inside the envelope with
+/// error kind "Timeout".
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_account_info_t(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    timeout_ms: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        pubkey,
+        "getAccountInfo",
+        error_out,
+        |client, pubkey| {
+            let pubkey = pubkey.to_string();
+            call_with_timeout(client, timeout_ms, move |client| {
+                client.get_account_info(&pubkey)
+            })
+        },
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+// The _e variants below report failures as an error object instead of a string. On
+// failure `*error_out` is set to an object that must be released with solana_error_destroy.
+
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_balance_e(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    error_out: *mut *mut SolanaErrorObj,
+) -> u64 {
+    let result = handle_ref(client).and_then(|client| {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        client.get_balance(&pubkey)
+    });
+    handle_result_obj(result, error_out).unwrap_or(0)
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_latest_blockhash_e(
+    client: *mut SolanaRpcClient,
+    error_out: *mut *mut SolanaErrorObj,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| client.get_latest_blockhash());
+    handle_result_obj(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_send_transaction_e(
+    client: *mut SolanaRpcClient,
+    transaction: *mut SolanaTransaction,
+    error_out: *mut *mut SolanaErrorObj,
+) -> *mut c_char {
+    let result = handle_ref(transaction).and_then(|transaction| {
+        let client = handle_ref(client)?;
+        client
+            .send_transaction(transaction.get_transaction()?)
+            .map_err(|e| transaction.annotate_error(e))
+    });
+    handle_result_obj(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+// Error object accessors. Returned strings belong to the object and stay valid until
+// solana_error_destroy; do not free them. A null or destroyed object gives null, or 0.
+
+// The error kind, e.g. "RpcError" or "Timeout"
+#[ffi_export]
+pub extern "C" fn solana_error_get_code(error: *const SolanaErrorObj) -> *const c_char {
+    handle_ref(error).map_or(ptr::null(), |error| error.code().as_ptr())
+}
+
+#[ffi_export]
+pub extern "C" fn solana_error_get_message(error: *const SolanaErrorObj) -> *const c_char {
+    handle_ref(error).map_or(ptr::null(), |error| error.message().as_ptr())
+}
+
+// Program logs captured with the error as a JSON array of strings, "[]" when there are none
+#[ffi_export]
+pub extern "C" fn solana_error_get_logs_json(error: *const SolanaErrorObj) -> *const c_char {
+    handle_ref(error).map_or(ptr::null(), |error| error.logs_json().as_ptr())
+}
+
+// 1 if making the same call again may succeed, e.g. after a timeout or network failure
+#[ffi_export]
+pub extern "C" fn solana_error_is_retryable(error: *const SolanaErrorObj) -> c_int {
+    handle_ref(error).map_or(0, |error| error.is_retryable() as c_int)
+}
+
+// The stable numeric code, e.g. 1001 for a failed preflight simulation; see error.rs
+#[ffi_export]
+pub extern "C" fn solana_error_get_numeric_code(error: *const SolanaErrorObj) -> u32 {
+    handle_ref(error).map_or(0, |error| error.numeric_code())
+}
+
+// The whole error as JSON: kind, code, name, message, display text, retryability and a
+// context object with the pubkey, signature or logs involved where known
+#[ffi_export]
+pub extern "C" fn solana_error_get_json(error: *const SolanaErrorObj) -> *const c_char {
+    handle_ref(error).map_or(ptr::null(), |error| error.json().as_ptr())
+}
+
+#[ffi_export]
+pub extern "C" fn solana_error_destroy(error: *mut SolanaErrorObj) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(error);
+}
+
+// Account functions
+
+#[ffi_export]
+pub extern "C" fn solana_create_account() -> *mut SolanaAccount {
+    into_handle(Account::new())
+}
+
+#[ffi_export]
+pub extern "C" fn solana_destroy_account(account: *mut SolanaAccount) {
+    // Drop the keypair explicitly so the secret key is wiped before the allocation is
+    // released. Null, destroyed and mismatched handles are ignored.
+    if let Ok(mut account) = destroy_handle(account) {
+        account.clear_private_key();
     }
 }
 
-#[no_mangle]
+#[ffi_export]
 pub extern "C" fn solana_account_from_pubkey(
     pubkey: *const c_char,
     error_out: *mut *mut c_char,
-) -> *mut Account {
+) -> *mut SolanaAccount {
     let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
         Ok(s) => s,
         Err(e) => {
@@ -389,7 +1705,7 @@ pub extern "C" fn solana_account_from_pubkey(
     };
 
     match Account::from_pubkey(&pubkey_str) {
-        Ok(account) => Box::into_raw(Box::new(account)),
+        Ok(account) => into_handle(account),
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -401,16 +1717,17 @@ pub extern "C" fn solana_account_from_pubkey(
     }
 }
 
-#[no_mangle]
+#[ffi_export]
 pub extern "C" fn solana_account_from_private_key(
     private_key_bytes: *const c_uchar,
     private_key_len: usize,
     error_out: *mut *mut c_char,
-) -> *mut Account {
-    let private_key = unsafe { slice::from_raw_parts(private_key_bytes, private_key_len) };
+) -> *mut SolanaAccount {
+    let result = unsafe { keypair_slice(private_key_bytes, private_key_len, "private_key_bytes") }
+        .and_then(Account::from_private_key);
 
-    match Account::from_private_key(private_key) {
-        Ok(account) => Box::into_raw(Box::new(account)),
+    match result {
+        Ok(account) => into_handle(account),
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -422,14 +1739,14 @@ pub extern "C" fn solana_account_from_private_key(
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_account_generate() -> *mut Account {
-    Box::into_raw(Box::new(Account::generate()))
+#[ffi_export]
+pub extern "C" fn solana_account_generate() -> *mut SolanaAccount {
+    into_handle(Account::generate())
 }
 
-#[no_mangle]
+#[ffi_export]
 pub extern "C" fn solana_account_get_pubkey(
-    account: *const Account,
+    account: *const SolanaAccount,
     error_out: *mut *mut c_char,
 ) -> *mut c_char {
     if account.is_null() {
@@ -443,7 +1760,19 @@ pub extern "C" fn solana_account_get_pubkey(
         return ptr::null_mut();
     }
 
-    match unsafe { (*account).get_pubkey() } {
+    let account = match handle_ref(account) {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match account.get_pubkey() {
         Ok(pubkey) => match CString::new(pubkey) {
             Ok(c_pubkey) => c_pubkey.into_raw(),
             Err(e) => {
@@ -469,73 +1798,229 @@ pub extern "C" fn solana_account_get_pubkey(
     }
 }
 
-// Free C string (exported for Unity to clean up strings)
-#[no_mangle]
-pub extern "C" fn solana_free_string(ptr: *mut c_char) {
-    unsafe {
-        free_c_string(ptr);
+/// # Safety
+///
+/// `private_key` must be null or a NUL-terminated string. `error_out` must be null or valid
+/// for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_base58_private_key(
+    private_key: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let private_key_str = match unsafe { c_str_to_string(private_key) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match Account::from_base58_private_key(&private_key_str) {
+        Ok(account) => into_handle(account),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            ptr::null_mut()
+        }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_build_token_transfer(
-    transaction: *mut Transaction,
-    token_program_id: *const c_char,
-    source_pubkey: *const c_char,
-    destination_pubkey: *const c_char,
-    owner_pubkey: *const c_char,
-    amount: u64,
-    recent_blockhash: *const c_char,
+/// The returned string must be freed with solana_free_string
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_get_private_key_base58(
+    account: *const SolanaAccount,
     error_out: *mut *mut c_char,
-) -> c_int {
-    if transaction.is_null() {
+) -> *mut c_char {
+    if account.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null transaction pointer".to_string(),
+                    "Null account pointer".to_string(),
                 ));
             }
         }
-        return 0;
+        return ptr::null_mut();
     }
 
-    let token_program_str = match unsafe { c_str_to_string(token_program_id) } {
-        Ok(s) => s,
+    let account = match handle_ref(account) {
+        Ok(account) => account,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            return ptr::null_mut();
         }
     };
 
-    let source_str = match unsafe { c_str_to_string(source_pubkey) } {
-        Ok(s) => s,
+    match account.to_base58_private_key() {
+        Ok(encoded) => match CString::new(encoded) {
+            Ok(c_encoded) => c_encoded.into_raw(),
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                            "Failed to convert private key to C string: {}",
+                            e
+                        )));
+                    }
+                }
+                ptr::null_mut()
+            }
+        },
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Creates an account from an unencrypted Ed25519 key in PKCS#8 PEM, v1 or v2, as written
+/// by `openssl genpkey -algorithm ed25519`. Keys of other algorithms fail with code 5004 and
+/// malformed PEM or DER with 5003.
+///
+/// # Safety
+///
+/// `pem` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_pkcs8_pem(
+    pem: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let pem = unsafe { c_str_to_string(pem) }.map(Zeroizing::new);
+    let account = pem.and_then(|pem| Account::from_pkcs8_pem(&pem));
+    handle_result(account, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+/// Returns the keypair as PKCS#8 v2 PEM, public key included. Free it with
+/// solana_secure_free_string.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_to_pkcs8_pem(
+    account: *const SolanaAccount,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let pem = handle_ref(account).and_then(|account| account.to_pkcs8_pem());
+    handle_result(pem, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Splits the account's key into `shares` base58 shares (at most 16), any `threshold` of
+/// which restore it with solana_account_from_shares. Each share is secret material: free
+/// the array with solana_string_array_secure_free.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_split_key(
+    account: *const SolanaAccount,
+    threshold: u8,
+    shares: u8,
+    error_out: *mut *mut c_char,
+) -> SolanaStringArray {
+    let result =
+        handle_ref(account).and_then(|account| account.split_private_key(threshold, shares));
+    handle_result(result, error_out)
+        .map_or_else(SolanaStringArray::null, SolanaStringArray::from_vec)
+}
+
+/// Restores an account from `count` shares made by solana_account_split_key. Corrupted or
+/// repeated shares fail with code 5005, shares of different splits with 5006 and too few
+/// shares with 5007.
+///
+/// # Safety
+///
+/// Unless `count` is 0, `shares` must point to that many pointers, each null or a
+/// NUL-terminated string. `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_shares(
+    shares: *const *const c_char,
+    count: usize,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let account = unsafe { c_str_array(shares, count) }.and_then(|shares| {
+        let shares: Vec<Zeroizing<String>> = shares.into_iter().map(Zeroizing::new).collect();
+        let shares: Vec<&str> = shares.iter().map(|share| share.as_str()).collect();
+        Account::from_shares(&shares)
+    });
+    handle_result(account, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+/// Creates an account from a 32-byte ed25519 seed
+///
+/// # Safety
+///
+/// Unless `seed_len` is 0, `seed` must be valid for reads of that many bytes. `error_out`
+/// must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_seed(
+    seed: *const c_uchar,
+    seed_len: usize,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    if seed.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out =
+                    error_to_c_string(&SolanaUnityError::FfiError("Null seed pointer".to_string()));
+            }
         }
+        return ptr::null_mut();
+    }
+
+    let result = if seed_len == 64 {
+        Err(SolanaUnityError::InvalidInput(
+            "seed is 64 bytes, which is a keypair; use solana_account_from_private_key for \
+             keypairs"
+                .to_string(),
+        ))
+    } else {
+        unsafe { byte_slice(seed, seed_len, "seed", 32) }.and_then(Account::from_seed)
     };
 
-    let destination_str = match unsafe { c_str_to_string(destination_pubkey) } {
-        Ok(s) => s,
+    match result {
+        Ok(account) => into_handle(account),
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            ptr::null_mut()
         }
-    };
+    }
+}
 
-    let owner_str = match unsafe { c_str_to_string(owner_pubkey) } {
+/// # Safety
+///
+/// `json` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_keypair_json(
+    json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let json_str = match unsafe { c_str_to_string(json) } {
         Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
@@ -543,53 +2028,141 @@ pub extern "C" fn solana_build_token_transfer(
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            return ptr::null_mut();
         }
     };
 
-    let blockhash_str = match unsafe { c_str_to_string(recent_blockhash) } {
-        Ok(s) => s,
+    match Account::from_keypair_json(&json_str) {
+        Ok(account) => into_handle(account),
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Loads a solana-keygen JSON keypair file, e.g. ~/.config/solana/id.json
+///
+/// # Safety
+///
+/// `path` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_keypair_file(
+    path: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let account =
+        unsafe { c_str_to_string(path) }.and_then(|path| Account::from_keypair_file(&path));
+    handle_result(account, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+/// Writes the keypair as a solana-keygen JSON file, owner-only on Unix. Fails if the file
+/// exists unless `overwrite` is non-zero. Returns 1 on success, 0 on failure.
+///
+/// # Safety
+///
+/// `path` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_write_keypair_file(
+    account: *const SolanaAccount,
+    path: *const c_char,
+    overwrite: c_int,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(account).and_then(|account| {
+        let path = unsafe { c_str_to_string(path) }?;
+        account.write_keypair_file(&path, overwrite != 0)
+    });
+    handle_result(result, error_out).map_or(0, |_| 1)
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_to_keypair_json(
+    account: *const SolanaAccount,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    if account.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null account pointer".to_string(),
+                ));
+            }
+        }
+        return ptr::null_mut();
+    }
+
+    let account = match handle_ref(account) {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
         }
     };
 
-    match unsafe {
-        (*transaction).build_token_transfer(
-            &token_program_str,
-            &source_str,
-            &destination_str,
-            &owner_str,
-            amount,
-            &blockhash_str,
-        )
-    } {
-        Ok(_) => 1,
+    match account.to_keypair_json() {
+        Ok(json) => match CString::new(json) {
+            Ok(c_json) => c_json.into_raw(),
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                            "Failed to convert keypair JSON to C string: {}",
+                            e
+                        )));
+                    }
+                }
+                ptr::null_mut()
+            }
+        },
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            0
+            ptr::null_mut()
         }
     }
 }
 
-#[cfg(feature = "bip39")]
-#[no_mangle]
-pub extern "C" fn solana_account_from_mnemonic(
-    mnemonic: *const c_char,
-    passphrase: *const c_char,
-    derivation_path: *const c_char,
+/// Exports the account as a password-encrypted JSON keystore
+///
+/// # Safety
+///
+/// `password` must be null or a NUL-terminated string. `error_out` must be null or valid
+/// for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_export_encrypted(
+    account: *const SolanaAccount,
+    password: *const c_char,
     error_out: *mut *mut c_char,
-) -> *mut Account {
-    let mnemonic_str = match unsafe { c_str_to_string(mnemonic) } {
+) -> *mut c_char {
+    if account.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null account pointer".to_string(),
+                ));
+            }
+        }
+        return ptr::null_mut();
+    }
+
+    let password_str = match unsafe { c_str_to_string(password) } {
         Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
@@ -601,7 +2174,57 @@ pub extern "C" fn solana_account_from_mnemonic(
         }
     };
 
-    let passphrase_str = match unsafe { c_str_to_string(passphrase) } {
+    let account = match handle_ref(account) {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match account.export_encrypted(&password_str) {
+        Ok(json) => match CString::new(json) {
+            Ok(c_json) => c_json.into_raw(),
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                            "Failed to convert keystore JSON to C string: {}",
+                            e
+                        )));
+                    }
+                }
+                ptr::null_mut()
+            }
+        },
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Restores an account from a keystore produced by solana_account_export_encrypted
+///
+/// # Safety
+///
+/// `json` and `password` must each be null or a NUL-terminated string. `error_out` must be
+/// null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_encrypted(
+    json: *const c_char,
+    password: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let json_str = match unsafe { c_str_to_string(json) } {
         Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
@@ -613,7 +2236,7 @@ pub extern "C" fn solana_account_from_mnemonic(
         }
     };
 
-    let path_str = match unsafe { c_str_to_string(derivation_path) } {
+    let password_str = match unsafe { c_str_to_string(password) } {
         Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
@@ -625,8 +2248,8 @@ pub extern "C" fn solana_account_from_mnemonic(
         }
     };
 
-    match Account::from_mnemonic(&mnemonic_str, &passphrase_str, &path_str) {
-        Ok(account) => Box::into_raw(Box::new(account)),
+    match Account::from_encrypted(&json_str, &password_str) {
+        Ok(account) => into_handle(account),
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -638,25 +2261,31 @@ pub extern "C" fn solana_account_from_mnemonic(
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_get_token_account_balance(
-    client: *mut RpcClient,
-    token_account: *const c_char,
+/// Writes the account's raw public key into a caller-provided 32-byte buffer
+///
+/// # Safety
+///
+/// `pubkey_out` must be null or valid for writes of 32 bytes. `error_out` must be null or
+/// valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_get_pubkey_bytes(
+    account: *const SolanaAccount,
+    pubkey_out: *mut c_uchar,
     error_out: *mut *mut c_char,
-) -> u64 {
-    if client.is_null() {
+) -> c_int {
+    if account.is_null() || pubkey_out.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null client pointer".to_string(),
+                    "Null pointer(s) provided".to_string(),
                 ));
             }
         }
         return 0;
     }
 
-    let token_account_str = match unsafe { c_str_to_string(token_account) } {
-        Ok(s) => s,
+    let account = match handle_ref(account) {
+        Ok(account) => account,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -667,8 +2296,13 @@ pub extern "C" fn solana_get_token_account_balance(
         }
     };
 
-    match unsafe { (*client).get_token_account_balance(&token_account_str) } {
-        Ok(balance) => balance,
+    match account.get_pubkey_bytes() {
+        Ok(bytes) => {
+            unsafe {
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), pubkey_out, bytes.len());
+            }
+            1
+        }
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -680,21 +2314,27 @@ pub extern "C" fn solana_get_token_account_balance(
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_get_account_info(
-    client: *mut RpcClient,
+/// Decodes a base58 public key into a caller-provided 32-byte buffer
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `pubkey_out` must be null or valid for
+/// writes of 32 bytes. `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_pubkey_to_bytes(
     pubkey: *const c_char,
+    pubkey_out: *mut c_uchar,
     error_out: *mut *mut c_char,
-) -> *mut c_char {
-    if client.is_null() {
+) -> c_int {
+    if pubkey_out.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null client pointer".to_string(),
+                    "Null pointer(s) provided".to_string(),
                 ));
             }
         }
-        return ptr::null_mut();
+        return 0;
     }
 
     let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
@@ -705,158 +2345,229 @@ pub extern "C" fn solana_get_account_info(
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return ptr::null_mut();
+            return 0;
         }
     };
 
-    match unsafe { (*client).get_account_info(&pubkey_str) } {
-        Ok(info) => match CString::new(info) {
-            Ok(c_info) => c_info.into_raw(),
-            Err(e) => {
-                if !error_out.is_null() {
-                    unsafe {
-                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
-                            "Failed to convert account info to C string: {}",
-                            e
-                        )));
-                    }
+    match Account::pubkey_to_bytes(&pubkey_str) {
+        Ok(bytes) => {
+            unsafe {
+                std::ptr::copy_nonoverlapping(bytes.as_ptr(), pubkey_out, bytes.len());
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
                 }
-                ptr::null_mut()
             }
-        },
+            0
+        }
+    }
+}
+
+/// Returns 1 if the string is a valid public key and 0 otherwise. Never allocates an error
+/// string, so it is cheap enough to call on every keystroke.
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string.
+#[ffi_export]
+pub unsafe extern "C" fn solana_is_valid_pubkey(pubkey: *const c_char) -> c_int {
+    match unsafe { c_str_to_string(pubkey) } {
+        Ok(s) => Account::is_valid_pubkey(&s) as c_int,
+        Err(_) => 0,
+    }
+}
+
+/// Validates a public key, writing the reason for rejection to `error_out`
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_validate_pubkey(
+    pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
+        Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            ptr::null_mut()
+            return 0;
+        }
+    };
+
+    match Account::validate_pubkey(&pubkey_str) {
+        Ok(()) => 1,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_get_program_accounts(
-    client: *mut RpcClient,
-    program_id: *const c_char,
+/// Moves the account's keypair into memory locked against swapping (or back out of it).
+/// On success `locked_out` is set to 1 if the pages are locked, 0 if locking fell back.
+///
+/// # Safety
+///
+/// `locked_out` and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_set_secure_memory(
+    account: *mut SolanaAccount,
+    enabled: c_int,
+    locked_out: *mut c_int,
     error_out: *mut *mut c_char,
-) -> *mut c_char {
-    if client.is_null() {
+) -> c_int {
+    if account.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null client pointer".to_string(),
+                    "Null account pointer".to_string(),
                 ));
             }
         }
-        return ptr::null_mut();
+        return 0;
     }
 
-    let program_id_str = match unsafe { c_str_to_string(program_id) } {
-        Ok(s) => s,
+    let mut account = match deref_handle(account) {
+        Ok(account) => account,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return ptr::null_mut();
+            return 0;
         }
     };
 
-    match unsafe { (*client).get_program_accounts(&program_id_str) } {
-        Ok(accounts) => match CString::new(accounts) {
-            Ok(c_accounts) => c_accounts.into_raw(),
-            Err(e) => {
-                if !error_out.is_null() {
-                    unsafe {
-                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
-                            "Failed to convert program accounts to C string: {}",
-                            e
-                        )));
-                    }
+    match account.set_secure_memory(enabled != 0) {
+        Ok(locked) => {
+            if !locked_out.is_null() {
+                unsafe {
+                    *locked_out = locked as c_int;
                 }
-                ptr::null_mut()
             }
-        },
+            1
+        }
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            ptr::null_mut()
+            0
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_get_transaction_status(
-    client: *mut RpcClient,
-    signature: *const c_char,
+// Returns 1 if the account's keypair is locked in memory, 0 otherwise
+#[ffi_export]
+pub extern "C" fn solana_account_is_memory_locked(account: *const SolanaAccount) -> c_int {
+    match handle_ref(account) {
+        Ok(account) => account.is_memory_locked() as c_int,
+        Err(_) => 0,
+    }
+}
+
+/// Signs an arbitrary message. `signature_out` must point to a caller-provided 64-byte buffer.
+///
+/// # Safety
+///
+/// Unless `message_len` is 0, `message` must be valid for reads of that many bytes.
+/// `signature_out` must be null or valid for writes of 64 bytes. `error_out` must be null
+/// or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_sign_message(
+    account: *const SolanaAccount,
+    message: *const c_uchar,
+    message_len: usize,
+    signature_out: *mut c_uchar,
     error_out: *mut *mut c_char,
-) -> *mut c_char {
-    if client.is_null() {
+) -> c_int {
+    if account.is_null() || (message.is_null() && message_len > 0) || signature_out.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null client pointer".to_string(),
+                    "Null pointer(s) provided".to_string(),
                 ));
             }
         }
-        return ptr::null_mut();
+        return 0;
     }
 
-    let signature_str = match unsafe { c_str_to_string(signature) } {
-        Ok(s) => s,
+    let message_bytes = match unsafe { byte_slice(message, message_len, "message", MAX_SLICE_LEN) }
+    {
+        Ok(message_bytes) => message_bytes,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return ptr::null_mut();
+            return 0;
         }
     };
 
-    match unsafe { (*client).get_transaction_status(&signature_str) } {
-        Ok(status) => match CString::new(status) {
-            Ok(c_status) => c_status.into_raw(),
-            Err(e) => {
-                if !error_out.is_null() {
-                    unsafe {
-                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
-                            "Failed to convert transaction status to C string: {}",
-                            e
-                        )));
-                    }
+    let account = match handle_ref(account) {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
                 }
-                ptr::null_mut()
             }
-        },
+            return 0;
+        }
+    };
+
+    match account.sign_message(message_bytes) {
+        Ok(signature) => {
+            unsafe {
+                std::ptr::copy_nonoverlapping(signature.as_ptr(), signature_out, signature.len());
+            }
+            1
+        }
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            ptr::null_mut()
+            0
         }
     }
 }
 
-// Add new FFI functions for PDA
-
-#[no_mangle]
-pub extern "C" fn solana_find_program_address(
-    seeds_ptr: *const *const c_char,
-    seeds_len: usize,
-    program_id: *const c_char,
-    address_out: *mut *mut c_char,
-    bump_out: *mut u8,
+/// Signs a message using the Solana off-chain message envelope. `signature_out` must point
+/// to a caller-provided 64-byte buffer.
+///
+/// # Safety
+///
+/// Unless `message_len` is 0, `message` must be valid for reads of that many bytes.
+/// `signature_out` must be null or valid for writes of 64 bytes. `error_out` must be null
+/// or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_sign_offchain_message(
+    account: *const SolanaAccount,
+    message: *const c_uchar,
+    message_len: usize,
+    signature_out: *mut c_uchar,
     error_out: *mut *mut c_char,
 ) -> c_int {
-    if seeds_ptr.is_null() || program_id.is_null() || address_out.is_null() || bump_out.is_null() {
+    if account.is_null() || (message.is_null() && message_len > 0) || signature_out.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
@@ -867,8 +2578,9 @@ pub extern "C" fn solana_find_program_address(
         return 0;
     }
 
-    let program_id_str = match unsafe { c_str_to_string(program_id) } {
-        Ok(s) => s,
+    let message_bytes = match unsafe { byte_slice(message, message_len, "message", MAX_SLICE_LEN) }
+    {
+        Ok(message_bytes) => message_bytes,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -879,46 +2591,22 @@ pub extern "C" fn solana_find_program_address(
         }
     };
 
-    // Convert C array of strings to Rust Vec of byte arrays
-    let mut seeds_vec = Vec::with_capacity(seeds_len);
-    for i in 0..seeds_len {
-        let seed_ptr = unsafe { *seeds_ptr.add(i) };
-        let seed_str = match unsafe { c_str_to_string(seed_ptr) } {
-            Ok(s) => s,
-            Err(e) => {
-                if !error_out.is_null() {
-                    unsafe {
-                        *error_out = error_to_c_string(&e);
-                    }
+    let account = match handle_ref(account) {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
                 }
-                return 0;
             }
-        };
-        seeds_vec.push(seed_str.into_bytes());
-    }
-
-    // Convert Vec<Vec<u8>> to Vec<&[u8]>
-    let seeds_slice: Vec<&[u8]> = seeds_vec.iter().map(|s| s.as_slice()).collect();
+            return 0;
+        }
+    };
 
-    match ProgramDerivedAddress::find_program_address(&seeds_slice, &program_id_str) {
-        Ok((address, bump)) => {
-            // Set the output address
-            match CString::new(address) {
-                Ok(c_address) => unsafe {
-                    *address_out = c_address.into_raw();
-                    *bump_out = bump;
-                },
-                Err(e) => {
-                    if !error_out.is_null() {
-                        unsafe {
-                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
-                                "Failed to convert address to C string: {}",
-                                e
-                            )));
-                        }
-                    }
-                    return 0;
-                }
+    match account.sign_offchain_message(message_bytes) {
+        Ok(signature) => {
+            unsafe {
+                std::ptr::copy_nonoverlapping(signature.as_ptr(), signature_out, signature.len());
             }
             1
         }
@@ -933,14 +2621,30 @@ pub extern "C" fn solana_find_program_address(
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_find_associated_token_address(
-    wallet_address: *const c_char,
-    token_mint: *const c_char,
-    address_out: *mut *mut c_char,
+/// Verifies an off-chain message signature. On success `result_out` is set to 1 for a valid
+/// signature and 0 for a mismatch.
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. Unless `message_len` is 0, `message`
+/// must be valid for reads of that many bytes. Unless `signature_len` is 0, `signature`
+/// must be valid for reads of that many bytes. `result_out` and `error_out` must each be
+/// null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_verify_offchain_message(
+    pubkey: *const c_char,
+    message: *const c_uchar,
+    message_len: usize,
+    signature: *const c_uchar,
+    signature_len: usize,
+    result_out: *mut c_int,
     error_out: *mut *mut c_char,
 ) -> c_int {
-    if wallet_address.is_null() || token_mint.is_null() || address_out.is_null() {
+    if pubkey.is_null()
+        || (message.is_null() && message_len > 0)
+        || signature.is_null()
+        || result_out.is_null()
+    {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
@@ -951,7 +2655,7 @@ pub extern "C" fn solana_find_associated_token_address(
         return 0;
     }
 
-    let wallet_str = match unsafe { c_str_to_string(wallet_address) } {
+    let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
         Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
@@ -963,8 +2667,9 @@ pub extern "C" fn solana_find_associated_token_address(
         }
     };
 
-    let mint_str = match unsafe { c_str_to_string(token_mint) } {
-        Ok(s) => s,
+    let message_bytes = match unsafe { byte_slice(message, message_len, "message", MAX_SLICE_LEN) }
+    {
+        Ok(message_bytes) => message_bytes,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -974,26 +2679,33 @@ pub extern "C" fn solana_find_associated_token_address(
             return 0;
         }
     };
-
-    match ProgramDerivedAddress::find_associated_token_address(&wallet_str, &mint_str) {
-        Ok(address) => {
-            // Set the output address
-            match CString::new(address) {
-                Ok(c_address) => unsafe {
-                    *address_out = c_address.into_raw();
-                },
-                Err(e) => {
-                    if !error_out.is_null() {
-                        unsafe {
-                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
-                                "Failed to convert address to C string: {}",
-                                e
-                            )));
-                        }
-                    }
-                    return 0;
+    let signature_bytes = match unsafe { byte_slice(signature, signature_len, "signature", 64) }
+        .and_then(|signature| {
+            if signature.len() == 64 {
+                Ok(signature)
+            } else {
+                Err(SolanaUnityError::InvalidInput(format!(
+                    "signature must be 64 bytes, got {}",
+                    signature.len()
+                )))
+            }
+        }) {
+        Ok(signature_bytes) => signature_bytes,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
                 }
             }
+            return 0;
+        }
+    };
+
+    match Account::verify_offchain_message(&pubkey_str, message_bytes, signature_bytes) {
+        Ok(valid) => {
+            unsafe {
+                *result_out = valid as c_int;
+            }
             1
         }
         Err(e) => {
@@ -1007,15 +2719,30 @@ pub extern "C" fn solana_find_associated_token_address(
     }
 }
 
-// Add simulation function
-
-#[no_mangle]
-pub extern "C" fn solana_simulate_transaction(
-    client: *mut RpcClient,
-    transaction: *mut Transaction,
+/// Verifies a signature for a pubkey. On success `result_out` is set to 1 for a valid
+/// signature and 0 for a mismatch.
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. Unless `message_len` is 0, `message`
+/// must be valid for reads of that many bytes. Unless `signature_len` is 0, `signature`
+/// must be valid for reads of that many bytes. `result_out` and `error_out` must each be
+/// null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_verify_signature(
+    pubkey: *const c_char,
+    message: *const c_uchar,
+    message_len: usize,
+    signature: *const c_uchar,
+    signature_len: usize,
+    result_out: *mut c_int,
     error_out: *mut *mut c_char,
-) -> *mut c_char {
-    if client.is_null() || transaction.is_null() {
+) -> c_int {
+    if pubkey.is_null()
+        || (message.is_null() && message_len > 0)
+        || signature.is_null()
+        || result_out.is_null()
+    {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
@@ -1023,91 +2750,89 @@ pub extern "C" fn solana_simulate_transaction(
                 ));
             }
         }
-        return ptr::null_mut();
+        return 0;
     }
 
-    // Get transaction
-    let tx_result = unsafe { (*transaction).get_transaction() };
-    let tx = match tx_result {
-        Ok(tx) => tx,
+    let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
+        Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return ptr::null_mut();
+            return 0;
         }
     };
 
-    // Simulate the transaction
-    match unsafe { (*client).simulate_transaction(tx) } {
-        Ok(result) => match CString::new(result) {
-            Ok(c_result) => c_result.into_raw(),
-            Err(e) => {
-                if !error_out.is_null() {
-                    unsafe {
-                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
-                            "Failed to convert simulation result to C string: {}",
-                            e
-                        )));
-                    }
+    let message_bytes = match unsafe { byte_slice(message, message_len, "message", MAX_SLICE_LEN) }
+    {
+        Ok(message_bytes) => message_bytes,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
                 }
-                ptr::null_mut()
             }
-        },
+            return 0;
+        }
+    };
+    let signature_bytes = match unsafe { byte_slice(signature, signature_len, "signature", 64) }
+        .and_then(|signature| {
+            if signature.len() == 64 {
+                Ok(signature)
+            } else {
+                Err(SolanaUnityError::InvalidInput(format!(
+                    "signature must be 64 bytes, got {}",
+                    signature.len()
+                )))
+            }
+        }) {
+        Ok(signature_bytes) => signature_bytes,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            ptr::null_mut()
+            return 0;
         }
-    }
-}
-
-// Add instruction functions
+    };
 
-#[no_mangle]
-pub extern "C" fn solana_create_token_transfer_instruction(
-    source: *const c_char,
-    destination: *const c_char,
-    owner: *const c_char,
-    amount: u64,
-    encoded_data_out: *mut *mut c_uchar,
-    encoded_data_len_out: *mut usize,
-    error_out: *mut *mut c_char,
-) -> c_int {
-    if source.is_null()
-        || destination.is_null()
-        || owner.is_null()
-        || encoded_data_out.is_null()
-        || encoded_data_len_out.is_null()
-    {
-        if !error_out.is_null() {
+    match Account::verify_signature(&pubkey_str, message_bytes, signature_bytes) {
+        Ok(valid) => {
             unsafe {
-                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null pointer(s) provided".to_string(),
-                ));
+                *result_out = valid as c_int;
             }
+            1
         }
-        return 0;
-    }
-
-    let source_str = match unsafe { c_str_to_string(source) } {
-        Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            0
         }
-    };
+    }
+}
 
-    let destination_str = match unsafe { c_str_to_string(destination) } {
+/// Starts a background vanity address search. Poll with solana_grind_poll and release
+/// with solana_grind_destroy.
+///
+/// # Safety
+///
+/// `prefix` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_grind_start(
+    prefix: *const c_char,
+    case_insensitive: c_int,
+    max_attempts: u64,
+    threads: usize,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaGrindHandle {
+    let prefix_str = match unsafe { c_str_to_string(prefix) } {
         Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
@@ -1115,97 +2840,127 @@ pub extern "C" fn solana_create_token_transfer_instruction(
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            return ptr::null_mut();
         }
     };
 
-    let owner_str = match unsafe { c_str_to_string(owner) } {
-        Ok(s) => s,
+    match GrindHandle::start(&prefix_str, case_insensitive != 0, max_attempts, threads) {
+        Ok(handle) => into_handle(handle),
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            ptr::null_mut()
         }
-    };
+    }
+}
 
-    match TokenInstructions::transfer(&source_str, &destination_str, &owner_str, amount) {
-        Ok(instruction) => {
-            // Encode the instruction for returning to C#
-            let encoded = match bincode::serialize(&instruction) {
-                Ok(data) => data,
-                Err(e) => {
-                    if !error_out.is_null() {
-                        unsafe {
-                            *error_out = error_to_c_string(&SolanaUnityError::SerializationError(
-                                format!("Failed to serialize instruction: {}", e),
-                            ));
-                        }
-                    }
-                    return 0;
-                }
-            };
+/// Polls a vanity search without blocking. Returns 0 while running, 1 when a match was found
+/// (written to `account_out`) and -1 when the search was cancelled or ran out of attempts.
+/// `attempts_out` is optional and receives the number of keypairs generated so far.
+///
+/// # Safety
+///
+/// `attempts_out`, `account_out` and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_grind_poll(
+    handle: *mut SolanaGrindHandle,
+    attempts_out: *mut u64,
+    account_out: *mut *mut SolanaAccount,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if handle.is_null() || account_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return -1;
+    }
 
-            // Allocate memory for the instruction data
-            let data_len = encoded.len();
-            let data_ptr = unsafe { libc::malloc(data_len) } as *mut c_uchar;
-            if data_ptr.is_null() {
-                if !error_out.is_null() {
-                    unsafe {
-                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                            "Failed to allocate memory for instruction data".to_string(),
-                        ));
-                    }
+    let handle = match handle_ref(handle) {
+        Ok(handle) => handle,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
                 }
-                return 0;
             }
+            return -1;
+        }
+    };
 
-            // Copy the data
+    if !attempts_out.is_null() {
+        unsafe {
+            *attempts_out = handle.attempts();
+        }
+    }
+
+    match handle.poll() {
+        Ok(Some(account)) => {
             unsafe {
-                std::ptr::copy_nonoverlapping(encoded.as_ptr(), data_ptr, data_len);
-                *encoded_data_out = data_ptr;
-                *encoded_data_len_out = data_len;
+                *account_out = into_handle(account);
             }
-
             1
         }
+        Ok(None) => 0,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            0
+            -1
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_free_encoded_instruction(data_ptr: *mut c_uchar) {
-    if !data_ptr.is_null() {
-        unsafe {
-            libc::free(data_ptr as *mut libc::c_void);
-        }
+#[ffi_export]
+pub extern "C" fn solana_grind_cancel(handle: *mut SolanaGrindHandle) {
+    if let Ok(handle) = handle_ref(handle) {
+        handle.cancel();
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_build_with_instructions(
-    transaction: *mut Transaction,
-    instructions_data: *const c_uchar,
-    instructions_data_len: usize,
-    instructions_count: usize,
-    fee_payer: *const c_char,
-    recent_blockhash: *const c_char,
+// Stops any running workers and frees the handle
+#[ffi_export]
+pub extern "C" fn solana_grind_destroy(handle: *mut SolanaGrindHandle) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(handle);
+}
+
+// Wallet manager functions
+
+#[ffi_export]
+pub extern "C" fn solana_wallet_manager_create() -> *mut SolanaWalletManager {
+    into_handle(WalletManager::new())
+}
+
+#[ffi_export]
+pub extern "C" fn solana_wallet_manager_destroy(manager: *mut SolanaWalletManager) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(manager);
+}
+
+/// Adds a copy of `account` under `label`. The caller keeps ownership of `account` and
+/// must still destroy it.
+///
+/// # Safety
+///
+/// `label` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_wallet_manager_add_account(
+    manager: *mut SolanaWalletManager,
+    label: *const c_char,
+    account: *const SolanaAccount,
     error_out: *mut *mut c_char,
 ) -> c_int {
-    if transaction.is_null()
-        || instructions_data.is_null()
-        || fee_payer.is_null()
-        || recent_blockhash.is_null()
-    {
+    if manager.is_null() || account.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
@@ -1216,7 +2971,7 @@ pub extern "C" fn solana_build_with_instructions(
         return 0;
     }
 
-    let fee_payer_str = match unsafe { c_str_to_string(fee_payer) } {
+    let label_str = match unsafe { c_str_to_string(label) } {
         Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
@@ -1228,8 +2983,8 @@ pub extern "C" fn solana_build_with_instructions(
         }
     };
 
-    let blockhash_str = match unsafe { c_str_to_string(recent_blockhash) } {
-        Ok(s) => s,
+    let account = match handle_ref(account) {
+        Ok(account) => account,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -1240,29 +2995,32 @@ pub extern "C" fn solana_build_with_instructions(
         }
     };
 
-    // Deserialize the instructions
-    let instructions_bytes =
-        unsafe { slice::from_raw_parts(instructions_data, instructions_data_len) };
-    let instructions: Vec<solana_sdk::instruction::Instruction> =
-        match bincode::deserialize(instructions_bytes) {
-            Ok(insts) => insts,
-            Err(e) => {
-                if !error_out.is_null() {
-                    unsafe {
-                        *error_out = error_to_c_string(&SolanaUnityError::SerializationError(
-                            format!("Failed to deserialize instructions: {}", e),
-                        ));
-                    }
+    let account_copy = match account.try_clone() {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
                 }
-                return 0;
             }
-        };
+            return 0;
+        }
+    };
 
-    // Build the transaction
-    match unsafe {
-        (*transaction).build_with_instructions(&instructions, &fee_payer_str, &blockhash_str)
-    } {
-        Ok(_) => 1,
+    let mut manager = match deref_handle(manager) {
+        Ok(manager) => manager,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match manager.add(&label_str, account_copy) {
+        Ok(_) => 1,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -1274,159 +3032,150 @@ pub extern "C" fn solana_build_with_instructions(
     }
 }
 
-// Add multiple signatures support
-#[no_mangle]
-pub extern "C" fn solana_sign_transaction_with_keypairs(
-    transaction: *mut Transaction,
-    private_keys_data: *const *const c_uchar,
-    private_keys_lengths: *const usize,
-    private_keys_count: usize,
+/// # Safety
+///
+/// `label` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_wallet_manager_remove_account(
+    manager: *mut SolanaWalletManager,
+    label: *const c_char,
     error_out: *mut *mut c_char,
 ) -> c_int {
-    if transaction.is_null() || private_keys_data.is_null() || private_keys_lengths.is_null() {
+    if manager.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null pointer(s) provided".to_string(),
+                    "Null wallet manager pointer".to_string(),
                 ));
             }
         }
         return 0;
     }
 
-    // Convert C array of byte arrays to Rust Vec of &[u8]
-    let mut private_keys = Vec::with_capacity(private_keys_count);
-    for i in 0..private_keys_count {
-        let key_ptr = unsafe { *private_keys_data.add(i) };
-        let key_len = unsafe { *private_keys_lengths.add(i) };
-        let key_slice = unsafe { slice::from_raw_parts(key_ptr, key_len) };
-        private_keys.push(key_slice);
-    }
-    // Sign the transaction
-    let key_slices: Vec<&[u8]> = private_keys.iter().map(|k| *k).collect();
-    match unsafe { (*transaction).sign_with_keypairs(&key_slices) } {
-        Ok(_) => 1,
+    let label_str = match unsafe { c_str_to_string(label) } {
+        Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            0
-        }
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn solana_account_get_private_key(
-    account: *mut Account,
-    error_out: *mut *mut c_char,
-) -> *mut c_uchar {
-    if account.is_null() {
-        if !error_out.is_null() {
-            unsafe {
-                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null account pointer".to_string(),
-                ));
-            }
+            return 0;
         }
-        return std::ptr::null_mut();
-    }
+    };
 
-    match unsafe { (*account).get_private_key() } {
-        Ok(private_key) => {
-            let len = private_key.len();
-            let ptr = unsafe { libc::malloc(len) as *mut c_uchar };
-            if !ptr.is_null() {
+    let mut manager = match deref_handle(manager) {
+        Ok(manager) => manager,
+        Err(e) => {
+            if !error_out.is_null() {
                 unsafe {
-                    std::ptr::copy_nonoverlapping(private_key.as_ptr(), ptr, len);
+                    *error_out = error_to_c_string(&e);
                 }
             }
-            ptr
+            return 0;
         }
+    };
+
+    match manager.remove(&label_str) {
+        Ok(_) => 1,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            std::ptr::null_mut()
+            0
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_account_has_private_key(
-    account: *mut Account,
+/// # Safety
+///
+/// `label` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_wallet_manager_get_pubkey(
+    manager: *const SolanaWalletManager,
+    label: *const c_char,
     error_out: *mut *mut c_char,
-) -> c_int {
-    if account.is_null() {
+) -> *mut c_char {
+    if manager.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null account pointer".to_string(),
+                    "Null wallet manager pointer".to_string(),
                 ));
             }
         }
-        return 0;
+        return ptr::null_mut();
     }
 
-    unsafe { (*account).has_private_key() as c_int }
-}
-
-#[no_mangle]
-pub extern "C" fn solana_account_get_keypair(
-    account: *mut Account,
-    error_out: *mut *mut c_char,
-) -> *mut std::os::raw::c_void {
-    if account.is_null() {
-        if !error_out.is_null() {
-            unsafe {
-                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null account pointer".to_string(),
-                ));
+    let label_str = match unsafe { c_str_to_string(label) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
             }
+            return ptr::null_mut();
         }
-        return std::ptr::null_mut();
-    }
+    };
 
-    match unsafe { (*account).get_keypair() } {
-        Ok(keypair) => {
-            // Convert the keypair reference to a raw pointer
-            keypair as *const _ as *mut std::os::raw::c_void
+    let manager = match handle_ref(manager) {
+        Ok(manager) => manager,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
         }
+    };
+
+    match manager
+        .get(&label_str)
+        .and_then(|account| account.get_pubkey())
+    {
+        Ok(value) => match CString::new(value) {
+            Ok(c_value) => c_value.into_raw(),
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                            "Failed to convert pubkey to C string: {}",
+                            e
+                        )));
+                    }
+                }
+                ptr::null_mut()
+            }
+        },
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            std::ptr::null_mut()
+            ptr::null_mut()
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_build_program_call(
-    transaction: *mut Transaction,
-    program_id: *const c_char,
-    accounts: *const *const c_char,
-    accounts_is_signer: *const c_int,
-    accounts_is_writable: *const c_int,
-    accounts_count: usize,
-    data: *const c_uchar,
-    data_len: usize,
-    recent_blockhash: *const c_char,
-    fee_payer: *const c_char,
+/// # Safety
+///
+/// `label` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_wallet_manager_sign_transaction(
+    manager: *const SolanaWalletManager,
+    label: *const c_char,
+    transaction: *mut SolanaTransaction,
     error_out: *mut *mut c_char,
 ) -> c_int {
-    if transaction.is_null()
-        || program_id.is_null()
-        || accounts.is_null()
-        || data.is_null()
-        || recent_blockhash.is_null()
-        || fee_payer.is_null()
-    {
+    if manager.is_null() || transaction.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
@@ -1437,7 +3186,7 @@ pub extern "C" fn solana_build_program_call(
         return 0;
     }
 
-    let program_id_str = match unsafe { c_str_to_string(program_id) } {
+    let label_str = match unsafe { c_str_to_string(label) } {
         Ok(s) => s,
         Err(e) => {
             if !error_out.is_null() {
@@ -1449,8 +3198,8 @@ pub extern "C" fn solana_build_program_call(
         }
     };
 
-    let blockhash_str = match unsafe { c_str_to_string(recent_blockhash) } {
-        Ok(s) => s,
+    let manager = match handle_ref(manager) {
+        Ok(manager) => manager,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -1461,8 +3210,8 @@ pub extern "C" fn solana_build_program_call(
         }
     };
 
-    let fee_payer_str = match unsafe { c_str_to_string(fee_payer) } {
-        Ok(s) => s,
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
@@ -1473,65 +3222,150 @@ pub extern "C" fn solana_build_program_call(
         }
     };
 
-    // Convert C arrays to Rust Vec
-    let mut accounts_vec = Vec::with_capacity(accounts_count);
-    for i in 0..accounts_count {
-        let account_ptr = unsafe { *accounts.add(i) };
-        let account_str = match unsafe { c_str_to_string(account_ptr) } {
-            Ok(s) => s,
+    match manager.sign_with(&label_str, &mut transaction) {
+        Ok(_) => 1,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// Returns a JSON array of {label, pubkey, watch_only} entries. Never includes private keys.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_wallet_manager_list_json(
+    manager: *const SolanaWalletManager,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    if manager.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null wallet manager pointer".to_string(),
+                ));
+            }
+        }
+        return ptr::null_mut();
+    }
+
+    let manager = match handle_ref(manager) {
+        Ok(manager) => manager,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match manager.export_metadata_json() {
+        Ok(value) => match CString::new(value) {
+            Ok(c_value) => c_value.into_raw(),
             Err(e) => {
                 if !error_out.is_null() {
                     unsafe {
-                        *error_out = error_to_c_string(&e);
+                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                            "Failed to convert wallet list to C string: {}",
+                            e
+                        )));
                     }
                 }
-                return 0;
+                ptr::null_mut()
             }
-        };
-        let is_signer = unsafe { *accounts_is_signer.add(i) } != 0;
-        let is_writable = unsafe { *accounts_is_writable.add(i) } != 0;
-        accounts_vec.push((account_str, is_signer, is_writable));
-    }
-
-    // Convert data to Vec<u8>
-    let data_vec = unsafe { slice::from_raw_parts(data, data_len) }.to_vec();
-
-    match unsafe {
-        (*transaction).build_program_call(
-            &program_id_str,
-            accounts_vec,
-            data_vec,
-            &blockhash_str,
-            &fee_payer_str,
-        )
-    } {
-        Ok(_) => 1,
+        },
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            0
+            ptr::null_mut()
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_get_account_data(
-    client: *mut RpcClient,
+// Registers a process-wide callback for native diagnostics: RPC request start, duration
+// and failure, transaction signing, and panics in request workers. Levels are 0 debug,
+// 1 info, 2 warn and 3 error; lines below `min_level` are dropped without being formatted.
+// The callback may run on any thread, and `message` is only valid until it returns, so
+// copy it before keeping it. A null callback clears the registration.
+#[ffi_export]
+pub extern "C" fn solana_set_log_callback(
+    callback: Option<LogCallback>,
+    user_data: *mut c_void,
+    min_level: c_int,
+) {
+    match callback {
+        Some(callback) => logging::set_log_callback(callback, user_data, min_level),
+        None => logging::clear_log_callback(),
+    }
+}
+
+#[ffi_export]
+pub extern "C" fn solana_clear_log_callback() {
+    logging::clear_log_callback();
+}
+
+// Routes the byte buffers this library hands out (solana_get_account_data, encoded
+// instructions) through the host's allocator, e.g. a console platform allocator. `alloc`
+// must return memory aligned like malloc's. Each buffer remembers the allocator that
+// produced it, so solana_free_bytes and friends always release it through the matching
+// `free`, even after the hooks are replaced. Pass null for both to go back to the system
+// allocator. Strings are not affected and are still released with solana_free_string.
+#[ffi_export]
+pub extern "C" fn solana_set_allocator(
+    alloc: Option<AllocFn>,
+    free: Option<FreeFn>,
+    user_data: *mut c_void,
+) {
+    match (alloc, free) {
+        (Some(alloc), Some(free)) => allocator::set_allocator(alloc, free, user_data),
+        _ => allocator::reset_allocator(),
+    }
+}
+
+// Registers a process-wide signer callback used by solana_sign_transaction_externally.
+// The callback must write a 64-byte signature and return 0 on success. Pass a null
+// callback to unregister. `user_data` is passed back to the callback untouched.
+// The transaction handle is locked while the callback runs, so the callback must not
+// call back into the library with the same transaction.
+#[ffi_export]
+pub extern "C" fn solana_set_external_signer(
+    callback: Option<ExternalSignerCallback>,
+    user_data: *mut c_void,
+) {
+    external_signer::set_external_signer(callback, user_data);
+}
+
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_sign_transaction_externally(
+    transaction: *mut SolanaTransaction,
     pubkey: *const c_char,
     error_out: *mut *mut c_char,
-) -> *mut c_uchar {
-    if client.is_null() {
+) -> c_int {
+    if transaction.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null client pointer".to_string(),
+                    "Null transaction pointer".to_string(),
                 ));
             }
         }
-        return std::ptr::null_mut();
+        return 0;
     }
 
     let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
@@ -1542,70 +3376,8432 @@ pub extern "C" fn solana_get_account_data(
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return std::ptr::null_mut();
+            return 0;
         }
     };
 
-    match unsafe { (*client).get_account_data(&pubkey_str) } {
-        Ok(data) => {
-            let len = data.len();
-            let ptr = unsafe { libc::malloc(len) as *mut c_uchar };
-            if !ptr.is_null() {
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
                 unsafe {
-                    std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, len);
+                    *error_out = error_to_c_string(&e);
                 }
             }
-            ptr
+            return 0;
         }
+    };
+
+    match transaction.sign_externally(&pubkey_str) {
+        Ok(_) => 1,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            std::ptr::null_mut()
+            0
         }
     }
 }
 
-#[no_mangle]
-pub extern "C" fn solana_confirm_transaction(
-    client: *mut RpcClient,
-    signature: *const c_char,
+// Phantom deeplink session functions
+
+#[ffi_export]
+pub extern "C" fn solana_deeplink_session_create() -> *mut SolanaDeeplinkSession {
+    into_handle(DeeplinkSession::new())
+}
+
+#[ffi_export]
+pub extern "C" fn solana_deeplink_session_destroy(session: *mut SolanaDeeplinkSession) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(session);
+}
+
+/// Returns the base58 dapp encryption public key
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_deeplink_session_public_key(
+    session: *const SolanaDeeplinkSession,
     error_out: *mut *mut c_char,
-) -> c_int {
-    if client.is_null() {
+) -> *mut c_char {
+    if session.is_null() {
         if !error_out.is_null() {
             unsafe {
                 *error_out = error_to_c_string(&SolanaUnityError::FfiError(
-                    "Null client pointer".to_string(),
+                    "Null session pointer".to_string(),
                 ));
             }
         }
-        return 0;
+        return ptr::null_mut();
     }
 
-    let signature_str = match unsafe { c_str_to_string(signature) } {
-        Ok(s) => s,
+    let session = match handle_ref(session) {
+        Ok(session) => session,
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
                     *error_out = error_to_c_string(&e);
                 }
             }
-            return 0;
+            return ptr::null_mut();
         }
     };
 
-    match unsafe { (*client).confirm_transaction(&signature_str) } {
-        Ok(confirmed) => confirmed as c_int,
+    let public_key = session.public_key_base58();
+    match CString::new(public_key) {
+        Ok(c_value) => c_value.into_raw(),
         Err(e) => {
             if !error_out.is_null() {
                 unsafe {
-                    *error_out = error_to_c_string(&e);
+                    *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                        "Failed to convert public key to C string: {}",
+                        e
+                    )));
                 }
             }
-            0
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Decrypts a wallet response. Pass the phantom_encryption_public_key on the connect
+/// response, or an empty string afterwards to reuse the shared secret.
+///
+/// # Safety
+///
+/// `wallet_public_key`, `nonce` and `data` must each be null or a NUL-terminated string.
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_deeplink_decrypt(
+    session: *mut SolanaDeeplinkSession,
+    wallet_public_key: *const c_char,
+    nonce: *const c_char,
+    data: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    if session.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null session pointer".to_string(),
+                ));
+            }
         }
+        return ptr::null_mut();
+    }
+
+    let wallet_public_key_str = match unsafe { c_str_to_string(wallet_public_key) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let nonce_str = match unsafe { c_str_to_string(nonce) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let data_str = match unsafe { c_str_to_string(data) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let mut session = match deref_handle(session) {
+        Ok(session) => session,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match session.open(&wallet_public_key_str, &nonce_str, &data_str) {
+        Ok(payload) => match CString::new(payload) {
+            Ok(c_value) => c_value.into_raw(),
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                            "Failed to convert payload to C string: {}",
+                            e
+                        )));
+                    }
+                }
+                ptr::null_mut()
+            }
+        },
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Encrypts a JSON payload for the wallet. Returns the base58 ciphertext and writes the
+/// base58 nonce to `nonce_out`; free both with solana_free_string.
+///
+/// # Safety
+///
+/// `payload_json` must be null or a NUL-terminated string. `nonce_out` and `error_out` must
+/// each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_deeplink_encrypt(
+    session: *const SolanaDeeplinkSession,
+    payload_json: *const c_char,
+    nonce_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    if session.is_null() || nonce_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return ptr::null_mut();
+    }
+
+    let payload_str = match unsafe { c_str_to_string(payload_json) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let session = match handle_ref(session) {
+        Ok(session) => session,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match session.seal(&payload_str) {
+        Ok((nonce, ciphertext)) => {
+            // Base58 output never contains NUL bytes
+            unsafe {
+                *nonce_out = CString::new(nonce).unwrap().into_raw();
+            }
+            CString::new(ciphertext).unwrap().into_raw()
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+// Returns build information as JSON: the crate version, the solana-sdk and solana-client
+// versions compiled in, the enabled feature flags and the build profile, e.g.
+// {"version": "0.1.0", "solanaSdk": "1.18.26", "solanaClient": "1.18.26",
+//  "features": ["bip39"], "profile": "release", "abiVersion": 1}.
+// Free the result with solana_free_string.
+#[ffi_export]
+pub extern "C" fn solana_get_version() -> *mut c_char {
+    let features: Vec<&str> = [
+        ("bip39", cfg!(feature = "bip39")),
+        ("rpc", cfg!(feature = "rpc")),
+        ("websocket", cfg!(feature = "websocket")),
+        ("symbol-prefix", cfg!(feature = "symbol-prefix")),
+        ("header", cfg!(feature = "header")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect();
+
+    let version = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "solanaSdk": env!("SOLANA_UNITY_SDK_VERSION"),
+        // null when solana-client is not compiled in
+        "solanaClient": cfg!(feature = "rpc").then_some(env!("SOLANA_UNITY_CLIENT_VERSION")),
+        "features": features,
+        "profile": env!("SOLANA_UNITY_PROFILE"),
+        "abiVersion": ABI_VERSION,
+    });
+    string_to_c(version.to_string())
+}
+
+/// Names a custom program error, e.g. 6001 or 0x7d6 from "custom program error: 0x7d6".
+/// `logs_json` (a JSON array of log lines) and `idl_json` (an Anchor IDL or its `errors`
+/// array) are optional. Returns JSON such as {"code": 2006, "name": "ConstraintSeeds",
+/// "message": "A seeds constraint was violated", "builtin": true}, or null without an
+/// error when nothing names the code. Free the result with solana_free_string.
+///
+/// # Safety
+///
+/// `logs_json` and `idl_json` must each be null or a NUL-terminated string. `error_out`
+/// must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_decode_program_error(
+    logs_json: *const c_char,
+    code: u32,
+    idl_json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let logs = if logs_json.is_null() {
+        Ok(Vec::new())
+    } else {
+        unsafe { c_str_to_string(logs_json) }.and_then(|json| {
+            serde_json::from_str::<Vec<String>>(&json).map_err(|e| {
+                SolanaUnityError::InvalidInput(format!(
+                    "logs must be a JSON array of strings: {}",
+                    e
+                ))
+            })
+        })
+    };
+    let idl = if idl_json.is_null() {
+        Ok(None)
+    } else {
+        unsafe { c_str_to_string(idl_json) }
+            .and_then(|idl| anchor::parse_idl_errors(&idl).map(|_| Some(idl)))
+    };
+
+    let result = logs.and_then(|logs| {
+        let idl = idl?;
+        let err_json = json!({ "Custom": code }).to_string();
+        let decoded = anchor::decode_error(&logs, &err_json, idl.as_deref());
+        Ok(decoded.map(|decoded| json!(decoded).to_string()))
+    });
+    handle_result(result, error_out)
+        .flatten()
+        .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Decodes the `data_len` bytes of a borsh account at `data` with the schema in
+/// `schema_json`, e.g. {"offset": 8, "fields": [{"name": "level", "type": "u16"}]}, into a
+/// JSON object of field values. Errors name the field and byte offset that failed.
+///
+/// # Safety
+///
+/// `schema_json` must be null or a NUL-terminated string. Unless `data_len` is 0, `data`
+/// must be valid for reads of that many bytes. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_decode_account_with_schema(
+    data: *const u8,
+    data_len: usize,
+    schema_json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = unsafe {
+        byte_slice(
+            data,
+            data_len,
+            "data",
+            solana_sdk::system_instruction::MAX_PERMITTED_DATA_LENGTH as usize,
+        )
+    }
+    .and_then(|data| {
+        let schema = unsafe { c_str_to_string(schema_json) }?;
+        decode::borsh_decode_with_schema(data, &schema)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+// Formats `raw` base units of a token with `decimals` decimals as a decimal string, e.g.
+// 1500000 with 6 decimals as "1.500000", or "1.5" when `trim_zeros` is nonzero.
+#[ffi_export]
+pub extern "C" fn solana_format_token_amount(
+    raw: u64,
+    decimals: u8,
+    trim_zeros: c_int,
+) -> *mut c_char {
+    let formatted = if trim_zeros != 0 {
+        amount::format_amount_trimmed(raw, decimals)
+    } else {
+        amount::format_amount(raw, decimals)
+    };
+    string_to_c(formatted)
+}
+
+/// Parses a decimal string such as "1.5" into base units of a token with `decimals`
+/// decimals, written to `raw_out`. Amounts finer than one base unit or too large for a u64
+/// are rejected. Returns 1 on success and 0 on error.
+///
+/// # Safety
+///
+/// `amount` must be null or a NUL-terminated string. `raw_out` and `error_out` must each be
+/// null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_parse_token_amount(
+    amount: *const c_char,
+    decimals: u8,
+    raw_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if raw_out.is_null() {
+        handle_result::<()>(
+            Err(SolanaUnityError::FfiError(
+                "Null pointer provided".to_string(),
+            )),
+            error_out,
+        );
+        return 0;
+    }
+
+    let result = unsafe { c_str_to_string(amount) }
+        .and_then(|amount| amount::parse_amount(&amount, decimals));
+    match handle_result(result, error_out) {
+        Some(raw) => {
+            unsafe {
+                *raw_out = raw;
+            }
+            1
+        }
+        None => 0,
+    }
+}
+
+/// GETs the label and icon of a Solana Pay transaction request, `url` being the solana: URL
+/// or the https link in it. Returns {"label", "icon"}.
+///
+/// # Safety
+///
+/// `url` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_pay_fetch_metadata(
+    url: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result =
+        unsafe { c_str_to_string(url) }.and_then(|url| TransactionRequest::fetch_metadata(&url));
+    handle_result(result, error_out).map_or(ptr::null_mut(), |metadata| {
+        string_to_c(json!(metadata).to_string())
+    })
+}
+
+/// POSTs `account_pubkey` to a Solana Pay transaction request and returns the merchant's
+/// answer, {"transaction", "message"}, once the transaction decodes and needs the account's
+/// signature. `message` may be null. Load the transaction with
+/// solana_deserialize_transaction_b64 to inspect and sign it.
+///
+/// # Safety
+///
+/// `url` and `account_pubkey` must each be null or a NUL-terminated string. `error_out`
+/// must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_pay_request_transaction(
+    url: *const c_char,
+    account_pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = unsafe { c_str_to_string(url) }.and_then(|url| {
+        let account = unsafe { c_str_to_string(account_pubkey) }?;
+        TransactionRequest::request_transaction(&url, &account)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), |response| {
+        string_to_c(json!(response).to_string())
+    })
+}
+
+// Subscription functions. Every solana_subscribe_* function returns a SolanaSubscription
+// with its own event queue, bounded by the queue policy it was subscribed with, and the
+// functions below work the same for all of them.
+
+/// The oldest queued event as JSON, or null when the queue is empty. Every event has a
+/// "type": "notification" {"id", "method", "result"}, "subscriptionError" {"id", "message"}
+/// or "connectionState" {"state", "attempt"}. `error_out` is only set for an invalid handle.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "websocket")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_subscription_poll_next(
+    subscription: *const SolanaSubscription,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(subscription).map(|subscription| subscription.poll_next());
+    match handle_result(result, error_out) {
+        Some(Some(event)) => string_to_c(json!(event).to_string()),
+        _ => ptr::null_mut(),
+    }
+}
+
+// Number of queued events, 0 for an invalid handle
+#[cfg(feature = "websocket")]
+#[ffi_export]
+pub extern "C" fn solana_subscription_pending_count(
+    subscription: *const SolanaSubscription,
+) -> usize {
+    handle_ref(subscription).map_or(0, |subscription| subscription.pending_count())
+}
+
+// 1 while active, 2 while waiting for a connection, 0 once closed (rejected by the node,
+// or the connection gave up) and -1 for an invalid handle. Queued events can still be
+// polled after it closes.
+#[cfg(feature = "websocket")]
+#[ffi_export]
+pub extern "C" fn solana_subscription_state(subscription: *const SolanaSubscription) -> c_int {
+    match handle_ref(subscription).map(|subscription| subscription.state()) {
+        Ok(SubscriptionState::Active) => 1,
+        Ok(SubscriptionState::Reconnecting) => 2,
+        Ok(SubscriptionState::Closed) => 0,
+        Err(_) => -1,
+    }
+}
+
+// Unsubscribes on the node and frees the handle, discarding any events still queued
+#[cfg(feature = "websocket")]
+#[ffi_export]
+pub extern "C" fn solana_subscription_destroy(subscription: *mut SolanaSubscription) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(subscription);
+}
+
+// Balance watcher functions. A watcher reads the balances of the addresses added to it on a
+// background thread and queues an event for every change.
+
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_balance_watcher_create() -> *mut SolanaBalanceWatcher {
+    into_handle(BalanceWatcher::new())
+}
+
+/// Watches `pubkey`: `kind` is 0 for its SOL balance in lamports and 1 for the amount held
+/// by a token account. Adding an address already watched as `kind` does nothing. Returns 1
+/// on success and 0 on error.
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_balance_watcher_add(
+    watcher: *const SolanaBalanceWatcher,
+    pubkey: *const c_char,
+    kind: c_int,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(watcher).and_then(|watcher| {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        let kind = match kind {
+            0 => BalanceKind::Sol,
+            1 => BalanceKind::TokenAccount,
+            _ => {
+                return Err(SolanaUnityError::InvalidInput(format!(
+                    "Invalid balance kind: {}",
+                    kind
+                )))
+            }
+        };
+        watcher.add_address(&pubkey, kind)
+    });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// Returns 1 if `pubkey` was watched and 0 if it was not or on error. No event for it is
+/// queued after this returns.
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_balance_watcher_remove(
+    watcher: *const SolanaBalanceWatcher,
+    pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(watcher).and_then(|watcher| {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        watcher.remove_address(&pubkey)
+    });
+    handle_result(result, error_out).map_or(0, |removed| removed as c_int)
+}
+
+/// Starts reading the balances with `client` every `poll_interval_ms`, the first time right
+/// away. The watcher keeps its own reference to the client, which may be destroyed while
+/// the watcher runs. Returns 1 on success and 0 on error, including when already running.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_balance_watcher_start(
+    watcher: *const SolanaBalanceWatcher,
+    client: *const SolanaRpcClient,
+    poll_interval_ms: u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(watcher).and_then(|watcher| {
+        let client = RpcClient::clone(&*handle_ref(client)?);
+        watcher.start(client, poll_interval_ms)
+    });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+// Stops reading, waiting for a read in progress to finish. Watched addresses and queued
+// events are kept. Returns 1, or 0 for an invalid handle.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_balance_watcher_stop(watcher: *const SolanaBalanceWatcher) -> c_int {
+    handle_ref(watcher).map_or(0, |watcher| {
+        watcher.stop();
+        1
+    })
+}
+
+/// The oldest queued change as JSON, or null when there is none, e.g.
+/// {"pubkey": "...", "kind": "sol", "old": 1000, "new": 2500, "slot": 250000000}. "old" is
+/// null on the first reading of an address and "new" is null once its account does not
+/// exist. `error_out` is only set for an invalid handle.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_balance_watcher_poll_event(
+    watcher: *const SolanaBalanceWatcher,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(watcher).map(|watcher| watcher.poll_event());
+    match handle_result(result, error_out) {
+        Some(Some(event)) => string_to_c(json!(event).to_string()),
+        _ => ptr::null_mut(),
+    }
+}
+
+// Stops the watcher and frees it, discarding any queued events
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_balance_watcher_destroy(watcher: *mut SolanaBalanceWatcher) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(watcher);
+}
+
+// Address book functions. An address book keeps friends' addresses under unique labels and
+// is saved as JSON with solana_address_book_save.
+
+#[ffi_export]
+pub extern "C" fn solana_address_book_create() -> *mut SolanaAddressBook {
+    into_handle(AddressBook::new())
+}
+
+/// Reads an address book saved by solana_address_book_save. A corrupted file fails with a
+/// SerializationError naming the line and column, and is left untouched.
+///
+/// # Safety
+///
+/// `path` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_address_book_load(
+    path: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAddressBook {
+    let book = unsafe { c_str_to_string(path) }.and_then(|path| AddressBook::load_from_file(&path));
+    handle_result(book, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+/// # Safety
+///
+/// `path` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_address_book_save(
+    book: *const SolanaAddressBook,
+    path: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(book).and_then(|book| {
+        let path = unsafe { c_str_to_string(path) }?;
+        book.save_to_file(&path)
+    });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// Saves `pubkey` under `label` with an optional `memo` (may be null). A label that is
+/// already used fails unless `replace` is non-zero, in which case its entry is replaced. An
+/// address saved under another label always fails.
+///
+/// # Safety
+///
+/// `label`, `pubkey` and `memo` must each be null or a NUL-terminated string. `error_out`
+/// must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_address_book_add(
+    book: *mut SolanaAddressBook,
+    label: *const c_char,
+    pubkey: *const c_char,
+    memo: *const c_char,
+    replace: c_int,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = deref_handle(book).and_then(|mut book| {
+        let label = unsafe { c_str_to_string(label) }?;
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        let memo = if memo.is_null() {
+            None
+        } else {
+            Some(unsafe { c_str_to_string(memo) }?)
+        };
+        if replace != 0 {
+            book.set(&label, &pubkey, memo.as_deref())
+        } else {
+            book.add(&label, &pubkey, memo.as_deref())
+        }
+    });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// Returns 1 if an entry was saved under `label` and 0 if there was none or on error
+///
+/// # Safety
+///
+/// `label` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_address_book_remove(
+    book: *mut SolanaAddressBook,
+    label: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = deref_handle(book).and_then(|mut book| {
+        let label = unsafe { c_str_to_string(label) }?;
+        Ok(book.remove(&label))
+    });
+    handle_result(result, error_out).unwrap_or(false) as c_int
+}
+
+/// The entry saved under `label`, ignoring case, as JSON such as
+/// {"label": "Alice", "pubkey": "...", "memo": "guild leader"}, or null without an error
+/// when there is none. Free the result with solana_free_string.
+///
+/// # Safety
+///
+/// `label` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_address_book_lookup(
+    book: *const SolanaAddressBook,
+    label: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(book).and_then(|book| {
+        let label = unsafe { c_str_to_string(label) }?;
+        book.lookup_by_label(&label).map(entry_json).transpose()
+    });
+    handle_result(result, error_out)
+        .flatten()
+        .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Like solana_address_book_lookup, for the entry that saves `pubkey`
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_address_book_reverse_lookup(
+    book: *const SolanaAddressBook,
+    pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(book).and_then(|book| {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        book.reverse_lookup(&pubkey).map(entry_json).transpose()
+    });
+    handle_result(result, error_out)
+        .flatten()
+        .map_or(ptr::null_mut(), string_to_c)
+}
+
+fn entry_json(entry: &crate::address_book::AddressBookEntry) -> Result<String, SolanaUnityError> {
+    serde_json::to_string(entry).map_err(|e| {
+        SolanaUnityError::SerializationError(format!("Failed to serialize entry: {}", e))
+    })
+}
+
+/// Every entry as a JSON array, in the order they were added
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_address_book_list_json(
+    book: *const SolanaAddressBook,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(book).and_then(|book| book.list_json());
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+#[ffi_export]
+pub extern "C" fn solana_address_book_destroy(book: *mut SolanaAddressBook) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(book);
+}
+
+// Encodes an instruction the way solana_create_token_transfer_instruction does, for
+// Instruction in the C# bindings
+fn encode_instruction(
+    instruction: &solana_sdk::instruction::Instruction,
+) -> Result<Vec<u8>, SolanaUnityError> {
+    bincode::serialize(instruction).map_err(|e| {
+        SolanaUnityError::SerializationError(format!("Failed to serialize instruction: {}", e))
+    })
+}
+
+/// Generates a session key delegated `allowance` of `owner_token_account`. With a `mint` the
+/// approve instruction is ApproveChecked against it and `decimals`, which is otherwise
+/// ignored. `expires_at` is in Unix seconds, or 0 for a session without an expiry. The
+/// encoded approve instruction, for `owner` to sign, is written to `approve_out`, which must
+/// be released with solana_buffer_free.
+///
+/// # Safety
+///
+/// `owner_token_account`, `owner` and `mint` must each be null or a NUL-terminated string.
+/// `approve_out` and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_session_create(
+    owner_token_account: *const c_char,
+    owner: *const c_char,
+    mint: *const c_char,
+    decimals: u8,
+    allowance: u64,
+    expires_at: i64,
+    approve_out: *mut SolanaBuffer,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaSessionKey {
+    if !approve_out.is_null() {
+        unsafe { *approve_out = SolanaBuffer::null() };
+    }
+    let result = unsafe { c_str_to_string(owner_token_account) }.and_then(|token_account| {
+        let owner = unsafe { c_str_to_string(owner) }?;
+        let (mut session, approve) = if mint.is_null() {
+            SessionKey::create(&token_account, &owner, allowance)?
+        } else {
+            let mint = unsafe { c_str_to_string(mint) }?;
+            SessionKey::create_checked(&token_account, &owner, &mint, decimals, allowance)?
+        };
+        session.set_expires_at((expires_at != 0).then_some(expires_at));
+        Ok((session, encode_instruction(&approve)?))
+    });
+
+    match handle_result(result, error_out) {
+        Some((session, approve)) => {
+            if !approve_out.is_null() {
+                unsafe { *approve_out = SolanaBuffer::from_vec(approve) };
+            }
+            into_handle(session)
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+/// Returns a copy of the session keypair, which signs as the delegate. Destroy it with
+/// solana_account_destroy.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_session_get_account(
+    session: *const SolanaSessionKey,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let account = handle_ref(session).and_then(|session| session.account().try_clone());
+    handle_result(account, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+/// Returns the encoded approve instruction again, e.g. for a deserialized session
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_session_approve_instruction(
+    session: *const SolanaSessionKey,
+    error_out: *mut *mut c_char,
+) -> SolanaBuffer {
+    let result =
+        handle_ref(session).and_then(|session| encode_instruction(&session.approve_instruction()?));
+    handle_result(result, error_out).map_or_else(SolanaBuffer::null, SolanaBuffer::from_vec)
+}
+
+/// Returns the encoded revoke instruction ending the session, for the owner to sign
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_session_revoke_instruction(
+    session: *const SolanaSessionKey,
+    error_out: *mut *mut c_char,
+) -> SolanaBuffer {
+    let result =
+        handle_ref(session).and_then(|session| encode_instruction(&session.revoke_instruction()?));
+    handle_result(result, error_out).map_or_else(SolanaBuffer::null, SolanaBuffer::from_vec)
+}
+
+/// Returns the session's expiry in Unix seconds, or 0 if it has none or on error
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_session_expires_at(
+    session: *const SolanaSessionKey,
+    error_out: *mut *mut c_char,
+) -> i64 {
+    let expires_at = handle_ref(session).map(|session| session.expires_at().unwrap_or(0));
+    handle_result(expires_at, error_out).unwrap_or(0)
+}
+
+/// Returns the session as JSON with its key encrypted under `password`, to be restored with
+/// solana_session_deserialize
+///
+/// # Safety
+///
+/// `password` must be null or a NUL-terminated string. `error_out` must be null or valid
+/// for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_session_serialize(
+    session: *const SolanaSessionKey,
+    password: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(session).and_then(|session| {
+        let password = Zeroizing::new(unsafe { c_str_to_string(password) }?);
+        session.serialize(&password)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// A wrong password fails with code 4001
+///
+/// # Safety
+///
+/// `json` and `password` must each be null or a NUL-terminated string. `error_out` must be
+/// null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_session_deserialize(
+    json: *const c_char,
+    password: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaSessionKey {
+    let result = unsafe { c_str_to_string(json) }.and_then(|json| {
+        let password = Zeroizing::new(unsafe { c_str_to_string(password) }?);
+        SessionKey::deserialize(&json, &password)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), into_handle)
+}
+
+#[ffi_export]
+pub extern "C" fn solana_session_destroy(session: *mut SolanaSessionKey) {
+    // Null, destroyed and mismatched handles are ignored
+    let _ = destroy_handle(session);
+}
+
+// Version of the exported C interface. It only changes when the interface breaks existing
+// callers, so bindings compare it against the version they were written for.
+#[ffi_export]
+pub extern "C" fn solana_get_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+/// Starts the async runtime RPC calls run on and the background workers used by the
+/// solana_request_* functions. Every RPC function fails with "Library not initialized"
+/// until it has been called. `config_json` may be null for the defaults, or e.g.
+/// {"requestWorkers": 2, "asyncWorkers": 2, "shutdownTimeoutMs": 5000}.
+/// Calling it again while initialized does nothing and returns 1.
+///
+/// # Safety
+///
+/// `config_json` must be null or a NUL-terminated string. `error_out` must be null or valid
+/// for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_init(
+    config_json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let config = if config_json.is_null() {
+        Ok(RuntimeConfig::default())
+    } else {
+        unsafe { c_str_to_string(config_json) }.and_then(|json| RuntimeConfig::from_json(&json))
+    };
+    let result = config.and_then(runtime::init);
+    handle_result(result, error_out).is_some() as c_int
+}
+
+// Cancels outstanding requests and stops the background workers and the async runtime.
+// Call it before a Unity domain reload. RPC functions fail until solana_init is called
+// again. Returns 0 if a worker was still busy with an RPC call when the shutdown timeout
+// passed; that thread exits by itself once the call returns. Safe to call when not
+// initialized.
+#[ffi_export]
+pub extern "C" fn solana_shutdown() -> c_int {
+    runtime::shutdown() as c_int
+}
+
+// Request queue functions. Each solana_request_* call returns immediately with a request
+// id (0 on failure) and runs the RPC call on a background worker. Poll the id with
+// solana_poll_request until it completes. The library must be initialized with
+// solana_init first.
+
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_request_get_balance(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> u64 {
+    let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let client = match handle_ref(client) {
+        Ok(client) => client.clone(),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let result = requests::submit("getBalance", move || {
+        Ok(json!(client.get_balance(&pubkey_str)?))
+    });
+    handle_result(result, error_out).unwrap_or(0)
+}
+
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_request_latest_blockhash(
+    client: *mut SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> u64 {
+    let client = match handle_ref(client) {
+        Ok(client) => client.clone(),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let result = requests::submit("getLatestBlockhash", move || {
+        Ok(json!(client.get_latest_blockhash()?))
+    });
+    handle_result(result, error_out).unwrap_or(0)
+}
+
+/// Sends a snapshot of the transaction as it is when the request is made
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_request_send_transaction(
+    client: *mut SolanaRpcClient,
+    transaction: *const SolanaTransaction,
+    error_out: *mut *mut c_char,
+) -> u64 {
+    let tx = match handle_ref(transaction)
+        .and_then(|transaction| transaction.get_transaction().cloned())
+    {
+        Ok(tx) => tx,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let client = match handle_ref(client) {
+        Ok(client) => client.clone(),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let result = requests::submit("sendTransaction", move || {
+        Ok(json!(client.send_transaction(&tx)?))
+    });
+    handle_result(result, error_out).unwrap_or(0)
+}
+
+/// # Safety
+///
+/// `signature` must be null or a NUL-terminated string. `error_out` must be null or valid
+/// for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_request_confirm_transaction(
+    client: *mut SolanaRpcClient,
+    signature: *const c_char,
+    error_out: *mut *mut c_char,
+) -> u64 {
+    let signature_str = match unsafe { c_str_to_string(signature) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let client = match handle_ref(client) {
+        Ok(client) => client.clone(),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let result = requests::submit("confirmTransaction", move || {
+        Ok(json!(client.confirm_transaction(&signature_str)?))
+    });
+    handle_result(result, error_out).unwrap_or(0)
+}
+
+/// Polls a queued request. `status_out` receives 0 while the request is pending, 1 when it
+/// completed, 2 when it failed and -1 for an id that is unknown, cancelled or already
+/// retrieved. Completed and failed requests return their result envelope exactly once;
+/// the string must be freed with solana_free_string. Every other status returns null.
+///
+/// # Safety
+///
+/// `status_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_poll_request(
+    request_id: u64,
+    status_out: *mut c_int,
+) -> *mut c_char {
+    let (status, json) = match requests::poll(request_id) {
+        RequestStatus::Pending => (0, None),
+        RequestStatus::Complete(json) => (1, Some(json)),
+        RequestStatus::Failed(json) => (2, Some(json)),
+        RequestStatus::Unknown => (-1, None),
+    };
+
+    if !status_out.is_null() {
+        unsafe {
+            *status_out = status;
+        }
+    }
+
+    json.and_then(|json| CString::new(json).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+// Cancels a queued request and discards its result. Returns 1 if the id was known.
+#[ffi_export]
+pub extern "C" fn solana_cancel_request(request_id: u64) -> c_int {
+    requests::cancel(request_id) as c_int
+}
+
+// Caller-buffer variants of the string-returning functions. Each writes UTF-8 plus a NUL
+// terminator into `buf` and the size needed (terminator included) into `written_out`, so
+// nothing has to be freed afterwards. A `buf_len` of 0 queries the size without writing;
+// a buffer that is too small fails with the required size in `written_out`.
+
+/// # Safety
+///
+/// Unless `buf_len` is 0, `buf` must be valid for writes of that many bytes. `written_out`
+/// and `error_out` must each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_latest_blockhash_buf(
+    client: *mut SolanaRpcClient,
+    buf: *mut c_char,
+    buf_len: usize,
+    written_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(client)
+        .and_then(|client| client.get_latest_blockhash())
+        .and_then(|blockhash| unsafe { write_str_to_buf(&blockhash, buf, buf_len, written_out) });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// # Safety
+///
+/// Unless `buf_len` is 0, `buf` must be valid for writes of that many bytes. `written_out`
+/// and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_get_pubkey_buf(
+    account: *const SolanaAccount,
+    buf: *mut c_char,
+    buf_len: usize,
+    written_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(account)
+        .and_then(|account| account.get_pubkey())
+        .and_then(|pubkey| unsafe { write_str_to_buf(&pubkey, buf, buf_len, written_out) });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. Unless `buf_len` is 0, `buf` must be
+/// valid for writes of that many bytes. `written_out` and `error_out` must each be null or
+/// valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_account_info_buf(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    buf: *mut c_char,
+    buf_len: usize,
+    written_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let envelope = rpc_envelope(
+        client,
+        pubkey,
+        "getAccountInfo",
+        error_out,
+        |client, pubkey| client.get_account_info(pubkey),
+    );
+    match envelope {
+        Some(envelope) => {
+            let result = unsafe { write_str_to_buf(&envelope, buf, buf_len, written_out) };
+            handle_result(result, error_out).is_some() as c_int
+        }
+        None => 0,
+    }
+}
+
+/// # Safety
+///
+/// `signature` must be null or a NUL-terminated string. Unless `buf_len` is 0, `buf` must
+/// be valid for writes of that many bytes. `written_out` and `error_out` must each be null
+/// or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_transaction_status_buf(
+    client: *mut SolanaRpcClient,
+    signature: *const c_char,
+    buf: *mut c_char,
+    buf_len: usize,
+    written_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let envelope = rpc_envelope(
+        client,
+        signature,
+        "getTransaction",
+        error_out,
+        |client, signature| client.get_transaction_status(signature),
+    );
+    match envelope {
+        Some(envelope) => {
+            let result = unsafe { write_str_to_buf(&envelope, buf, buf_len, written_out) };
+            handle_result(result, error_out).is_some() as c_int
+        }
+        None => 0,
+    }
+}
+
+// Base58 signatures are at most 88 characters
+#[cfg(feature = "rpc")]
+const SIGNATURE_BUF_LEN: usize = 89;
+
+/// Unlike the other variants the buffer is checked before anything happens, so the
+/// transaction is never sent without room for its signature. A size query returns 89
+/// without sending; a 89-byte buffer always fits.
+///
+/// # Safety
+///
+/// Unless `buf_len` is 0, `buf` must be valid for writes of that many bytes. `written_out`
+/// and `error_out` must each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_send_transaction_buf(
+    client: *mut SolanaRpcClient,
+    transaction: *const SolanaTransaction,
+    buf: *mut c_char,
+    buf_len: usize,
+    written_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if buf_len < SIGNATURE_BUF_LEN {
+        if !written_out.is_null() {
+            unsafe {
+                *written_out = SIGNATURE_BUF_LEN;
+            }
+        }
+        if buf_len == 0 {
+            return 1;
+        }
+        let result: Result<(), SolanaUnityError> = Err(SolanaUnityError::InvalidInput(format!(
+            "Buffer too small: {} bytes required, {} provided",
+            SIGNATURE_BUF_LEN, buf_len
+        )));
+        return handle_result(result, error_out).is_some() as c_int;
+    }
+
+    let result = handle_ref(client)
+        .and_then(|client| {
+            let transaction = handle_ref(transaction)?;
+            client
+                .send_transaction(transaction.get_transaction()?)
+                .map_err(|e| transaction.annotate_error(e))
+        })
+        .and_then(|signature| unsafe { write_str_to_buf(&signature, buf, buf_len, written_out) });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+// Struct-returning variants. Errors are reported through `error_out` as usual and the
+// returned struct is marked as failed (null `data`, or `ok` of 0).
+
+/// The caller owns the returned buffer and must release it with solana_buffer_free
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_account_data_buffer(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> SolanaBuffer {
+    let result = unsafe { c_str_to_string(pubkey) }
+        .and_then(|pubkey_str| handle_ref(client)?.get_account_data(&pubkey_str));
+    handle_result(result, error_out).map_or_else(SolanaBuffer::null, SolanaBuffer::from_vec)
+}
+
+/// Returns the bincode wire format of the transaction. The caller owns the returned buffer
+/// and must release it with solana_buffer_free.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_serialize_transaction_buffer(
+    transaction: *const SolanaTransaction,
+    error_out: *mut *mut c_char,
+) -> SolanaBuffer {
+    let result = handle_ref(transaction).and_then(|transaction| transaction.serialize());
+    handle_result(result, error_out).map_or_else(SolanaBuffer::null, SolanaBuffer::from_vec)
+}
+
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_balance_result(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> SolanaU64Result {
+    let result = unsafe { c_str_to_string(pubkey) }
+        .and_then(|pubkey_str| handle_ref(client)?.get_balance(&pubkey_str));
+    match handle_result(result, error_out) {
+        Some(value) => SolanaU64Result { ok: 1, value },
+        None => SolanaU64Result { ok: 0, value: 0 },
+    }
+}
+
+// Shared by the _v2 balance functions: checks `value_out` before making the call, and
+// writes through it only on success. Returns 1 on success and 0 on failure.
+#[cfg(feature = "rpc")]
+fn u64_out(
+    value_out: *mut u64,
+    error_out: *mut *mut c_char,
+    call: impl FnOnce() -> Result<u64, SolanaUnityError>,
+) -> c_int {
+    let result = if value_out.is_null() {
+        Err(SolanaUnityError::FfiError(
+            "Null output pointer".to_string(),
+        ))
+    } else {
+        call()
+    };
+    match handle_result(result, error_out) {
+        Some(value) => {
+            unsafe { *value_out = value };
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Returns 1 and writes the balance to `lamports_out` on success, 0 on failure. Unlike
+/// solana_get_balance, a zero balance and a failure can be told apart without error_out.
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `lamports_out` and `error_out` must
+/// each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_balance_v2(
+    client: *const SolanaRpcClient,
+    pubkey: *const c_char,
+    lamports_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    u64_out(lamports_out, error_out, || {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        handle_ref(client)?.get_balance(&pubkey)
+    })
+}
+
+/// Returns 1 and writes the raw token amount to `amount_out` on success, 0 on failure
+///
+/// # Safety
+///
+/// `token_account` must be null or a NUL-terminated string. `amount_out` and `error_out`
+/// must each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_token_account_balance_v2(
+    client: *const SolanaRpcClient,
+    token_account: *const c_char,
+    amount_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    u64_out(amount_out, error_out, || {
+        let token_account = unsafe { c_str_to_string(token_account) }?;
+        handle_ref(client)?.get_token_account_balance(&token_account)
+    })
+}
+
+// The _at_slot reads are only answered by a node that has reached `min_context_slot`, e.g.
+// the slot from solana_send_and_confirm_transaction, so they never show state from before
+// it. A node still behind fails with code 1007, which is retryable. 0 reads from any node.
+
+/// Like solana_get_balance_v2
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `lamports_out` and `error_out` must
+/// each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_balance_at_slot(
+    client: *const SolanaRpcClient,
+    pubkey: *const c_char,
+    min_context_slot: u64,
+    lamports_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    u64_out(lamports_out, error_out, || {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        handle_ref(client)?.get_balance_at_slot(&pubkey, optional_slot(min_context_slot))
+    })
+}
+
+/// Like solana_get_token_account_balance_v2
+///
+/// # Safety
+///
+/// `token_account` must be null or a NUL-terminated string. `amount_out` and `error_out`
+/// must each be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_token_account_balance_at_slot(
+    client: *const SolanaRpcClient,
+    token_account: *const c_char,
+    min_context_slot: u64,
+    amount_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    u64_out(amount_out, error_out, || {
+        let token_account = unsafe { c_str_to_string(token_account) }?;
+        handle_ref(client)?
+            .get_token_account_balance_at_slot(&token_account, optional_slot(min_context_slot))
+    })
+}
+
+/// Like solana_get_account_info
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_account_info_at_slot(
+    client: *const SolanaRpcClient,
+    pubkey: *const c_char,
+    min_context_slot: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        pubkey,
+        "getAccountInfo",
+        error_out,
+        |client, pubkey| client.get_account_info_at_slot(pubkey, optional_slot(min_context_slot)),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Like solana_get_account_data_buffer
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_account_data_at_slot(
+    client: *const SolanaRpcClient,
+    pubkey: *const c_char,
+    min_context_slot: u64,
+    error_out: *mut *mut c_char,
+) -> SolanaBuffer {
+    let result = unsafe { c_str_to_string(pubkey) }.and_then(|pubkey_str| {
+        handle_ref(client)?.get_account_data_at_slot(&pubkey_str, optional_slot(min_context_slot))
+    });
+    handle_result(result, error_out).map_or_else(SolanaBuffer::null, SolanaBuffer::from_vec)
+}
+
+/// The caller owns `value` on success and must free it with solana_free_string
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_latest_blockhash_result(
+    client: *mut SolanaRpcClient,
+    error_out: *mut *mut c_char,
+) -> SolanaStringResult {
+    let result = handle_ref(client)
+        .and_then(|client| client.get_latest_blockhash())
+        .and_then(|blockhash| {
+            CString::new(blockhash).map_err(|e| {
+                SolanaUnityError::FfiError(format!(
+                    "Failed to convert blockhash to C string: {}",
+                    e
+                ))
+            })
+        });
+    match handle_result(result, error_out) {
+        Some(value) => SolanaStringResult {
+            ok: 1,
+            value: value.into_raw(),
+        },
+        None => SolanaStringResult {
+            ok: 0,
+            value: ptr::null_mut(),
+        },
+    }
+}
+
+// Releases a buffer returned by one of the *_buffer functions. Null buffers are ignored.
+#[ffi_export]
+pub extern "C" fn solana_buffer_free(buffer: SolanaBuffer) {
+    if !buffer.data.is_null() {
+        unsafe {
+            let _ = Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len));
+        }
+    }
+}
+
+// Base64 variants of the functions that take binary input. Strings are decoded leniently
+// (whitespace ignored, padding optional) and then handled exactly like the byte versions.
+
+/// # Safety
+///
+/// `private_key_b64` must be null or a NUL-terminated string. `error_out` must be null or
+/// valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_sign_transaction_b64(
+    transaction: *mut SolanaTransaction,
+    private_key_b64: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let private_key = unsafe { c_str_to_string(private_key_b64) }
+        .map(Zeroizing::new)
+        .and_then(|encoded| decode_base64_lenient("private key", &encoded))
+        .map(Zeroizing::new);
+
+    match handle_result(private_key, error_out) {
+        Some(private_key) => solana_sign_transaction(
+            transaction,
+            private_key.as_ptr(),
+            private_key.len(),
+            error_out,
+        ),
+        None => 0,
+    }
+}
+
+/// # Safety
+///
+/// `private_key_b64` must be null or a NUL-terminated string. `error_out` must be null or
+/// valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_private_key_b64(
+    private_key_b64: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let private_key = unsafe { c_str_to_string(private_key_b64) }
+        .map(Zeroizing::new)
+        .and_then(|encoded| decode_base64_lenient("private key", &encoded))
+        .map(Zeroizing::new);
+
+    match handle_result(private_key, error_out) {
+        Some(private_key) => {
+            solana_account_from_private_key(private_key.as_ptr(), private_key.len(), error_out)
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `program_id`, `data_b64`, `recent_blockhash` and `fee_payer` must each be null or a
+/// NUL-terminated string. `accounts` must be valid for reads of `accounts_count` pointers,
+/// each null or a NUL-terminated string, and `accounts_is_signer` and
+/// `accounts_is_writable` for reads of `accounts_count` values. `error_out` must be null or
+/// valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_build_program_call_b64(
+    transaction: *mut SolanaTransaction,
+    program_id: *const c_char,
+    accounts: *const *const c_char,
+    accounts_is_signer: *const c_int,
+    accounts_is_writable: *const c_int,
+    accounts_count: usize,
+    data_b64: *const c_char,
+    recent_blockhash: *const c_char,
+    fee_payer: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let data = unsafe { c_str_to_string(data_b64) }
+        .and_then(|encoded| decode_base64_lenient("instruction data", &encoded));
+
+    match handle_result(data, error_out) {
+        Some(data) => solana_build_program_call(
+            transaction,
+            program_id,
+            accounts,
+            accounts_is_signer,
+            accounts_is_writable,
+            accounts_count,
+            data.as_ptr(),
+            data.len(),
+            recent_blockhash,
+            fee_payer,
+            error_out,
+        ),
+        None => 0,
+    }
+}
+
+/// Replaces the transaction held by `transaction` with one decoded from its base64 wire
+/// format, e.g. a transaction partially signed by a backend
+///
+/// # Safety
+///
+/// `data_b64` must be null or a NUL-terminated string. `error_out` must be null or valid
+/// for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_deserialize_transaction_b64(
+    transaction: *mut SolanaTransaction,
+    data_b64: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = unsafe { c_str_to_string(data_b64) }
+        .and_then(|encoded| decode_base64_lenient("transaction", &encoded))
+        .and_then(|data| deref_handle(transaction)?.from_serialized(&data));
+    handle_result(result, error_out).is_some() as c_int
+}
+
+// Free C string (exported for Unity to clean up strings)
+#[ffi_export]
+pub extern "C" fn solana_free_string(ptr: *mut c_char) {
+    unsafe {
+        free_c_string(ptr);
+    }
+}
+
+// Free a SolanaStringArray together with the strings in it. Null arrays are ignored.
+#[ffi_export]
+pub extern "C" fn solana_string_array_free(array: SolanaStringArray) {
+    unsafe {
+        solana_free_string_array(array.items, array.len);
+    }
+}
+
+// Like solana_string_array_free, zeroizing each string first. Use it for arrays of
+// secrets such as the shares from solana_account_split_key.
+#[ffi_export]
+pub extern "C" fn solana_string_array_secure_free(array: SolanaStringArray) {
+    if array.items.is_null() {
+        return;
+    }
+
+    unsafe {
+        let strings = Box::from_raw(ptr::slice_from_raw_parts_mut(array.items, array.len));
+        for string in strings.iter() {
+            solana_secure_free_string(*string);
+        }
+    }
+}
+
+/// Free an array of C strings together with the strings in it
+///
+/// # Safety
+///
+/// `ptr` must be null or an array of `count` strings returned by this library, and must not
+/// be used after this call.
+#[ffi_export]
+pub unsafe extern "C" fn solana_free_string_array(ptr: *mut *mut c_char, count: usize) {
+    if ptr.is_null() {
+        return;
+    }
+
+    unsafe {
+        let strings = Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, count));
+        for string in strings.iter() {
+            free_c_string(*string);
+        }
+    }
+}
+
+#[ffi_export]
+pub extern "C" fn solana_build_token_transfer(
+    transaction: *mut SolanaTransaction,
+    token_program_id: *const c_char,
+    source_pubkey: *const c_char,
+    destination_pubkey: *const c_char,
+    owner_pubkey: *const c_char,
+    amount: u64,
+    recent_blockhash: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if transaction.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null transaction pointer".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let token_program_str = match unsafe { c_str_to_string(token_program_id) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let source_str = match unsafe { c_str_to_string(source_pubkey) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let destination_str = match unsafe { c_str_to_string(destination_pubkey) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let owner_str = match unsafe { c_str_to_string(owner_pubkey) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let blockhash_str = match unsafe { c_str_to_string(recent_blockhash) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match transaction.build_token_transfer(
+        &token_program_str,
+        &source_str,
+        &destination_str,
+        &owner_str,
+        amount,
+        &blockhash_str,
+    ) {
+        Ok(_) => 1,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+#[cfg(feature = "bip39")]
+#[ffi_export]
+pub extern "C" fn solana_account_from_mnemonic(
+    mnemonic: *const c_char,
+    passphrase: *const c_char,
+    derivation_path: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let mnemonic_str = match unsafe { c_str_to_string(mnemonic) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let passphrase_str = match unsafe { c_str_to_string(passphrase) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let path_str = match unsafe { c_str_to_string(derivation_path) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match Account::from_mnemonic(&mnemonic_str, &passphrase_str, &path_str) {
+        Ok(account) => into_handle(account),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Derives an account from a mnemonic by index. `scheme` 0 uses m/44'/501'/{index}'/0',
+/// 1 uses m/44'/501'/{index}'.
+///
+/// # Safety
+///
+/// `mnemonic` and `passphrase` must each be null or a NUL-terminated string. `error_out`
+/// must be null or valid for writes.
+#[cfg(feature = "bip39")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_account_from_mnemonic_index(
+    mnemonic: *const c_char,
+    passphrase: *const c_char,
+    account_index: u32,
+    scheme: c_int,
+    error_out: *mut *mut c_char,
+) -> *mut SolanaAccount {
+    let mnemonic_str = match unsafe { c_str_to_string(mnemonic) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let passphrase_str = match unsafe { c_str_to_string(passphrase) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let scheme = match scheme {
+        0 => crate::account::DerivationScheme::Standard,
+        1 => crate::account::DerivationScheme::Short,
+        other => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&SolanaUnityError::InvalidInput(format!(
+                        "Unknown derivation scheme: {}",
+                        other
+                    )));
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    match Account::from_mnemonic_with_scheme(&mnemonic_str, &passphrase_str, account_index, scheme)
+    {
+        Ok(account) => into_handle(account),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_get_token_account_balance(
+    client: *mut SolanaRpcClient,
+    token_account: *const c_char,
+    error_out: *mut *mut c_char,
+) -> u64 {
+    if client.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null client pointer".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let token_account_str = match unsafe { c_str_to_string(token_account) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let client = match handle_ref(client) {
+        Ok(client) => client,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match client.get_token_account_balance(&token_account_str) {
+        Ok(balance) => balance,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+// Returns a getAccountInfo envelope; see rpc_envelope for when `error_out` is used instead
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_get_account_info(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        pubkey,
+        "getAccountInfo",
+        error_out,
+        |client, pubkey| client.get_account_info(pubkey),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns a getAccountInfo envelope whose result is the aggregate price of a Pyth price
+/// account: {"price": "145.23456789", "confidence", "rawPrice", "rawConfidence", "exponent",
+/// "status", "trading", "publishSlot", "validSlot"}. A price with "trading": false is the
+/// last one published before trading stopped.
+///
+/// # Safety
+///
+/// `price_account` must be null or a NUL-terminated string. `error_out` must be null or
+/// valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_pyth_price(
+    client: *const SolanaRpcClient,
+    price_account: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        price_account,
+        "getAccountInfo",
+        error_out,
+        |client, price_account| client.get_pyth_price(price_account),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns a getAccountInfo envelope whose result is the decoded stake account: {"state":
+/// "uninitialized" | "initialized" | "delegated" | "rewardsPool", "status": "activating" |
+/// "active" | "deactivating" | "inactive", "epoch", "meta", "delegation"}. The status is
+/// worked out against the current epoch.
+///
+/// # Safety
+///
+/// `stake_account` must be null or a NUL-terminated string. `error_out` must be null or
+/// valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_stake_account_state(
+    client: *const SolanaRpcClient,
+    stake_account: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        stake_account,
+        "getAccountInfo",
+        error_out,
+        |client, stake_account| client.get_stake_account(stake_account),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns a getAccountInfo envelope whose result is the decoded durable nonce account:
+/// {"version": "current" | "legacy", "authority", "nonce", "lamportsPerSignature"}. An
+/// uninitialized nonce account fails with code 5001 and any other account with 5002.
+///
+/// # Safety
+///
+/// `nonce_pubkey` must be null or a NUL-terminated string. `error_out` must be null or
+/// valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_nonce_account(
+    client: *const SolanaRpcClient,
+    nonce_pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        nonce_pubkey,
+        "getAccountInfo",
+        error_out,
+        |client, nonce_pubkey| client.get_nonce_state(nonce_pubkey),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns the nonce stored in a durable nonce account as base58, to use as the blockhash
+/// of a transaction that advances it, or null on error
+///
+/// # Safety
+///
+/// `nonce_pubkey` must be null or a NUL-terminated string. `error_out` must be null or
+/// valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_nonce_value(
+    client: *const SolanaRpcClient,
+    nonce_pubkey: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| {
+        let nonce_pubkey = unsafe { c_str_to_string(nonce_pubkey) }?;
+        client.get_nonce_value(&nonce_pubkey)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Writes the lamports of each of the `count` pubkeys to `out_lamports` and whether its
+/// account exists to `out_exists`, two caller-provided arrays of `count` entries. Missing
+/// accounts get 0 lamports and 0 in `out_exists`. An invalid pubkey fails the call with
+/// its index in the error and leaves the arrays untouched.
+///
+/// # Safety
+///
+/// Unless `count` is 0, `pubkeys` must point to that many pointers, each null or a
+/// NUL-terminated string. Unless `count` is 0, `out_lamports` and `out_exists` must be
+/// valid for writes of that many values. `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_balances(
+    client: *const SolanaRpcClient,
+    pubkeys: *const *const c_char,
+    count: usize,
+    out_lamports: *mut u64,
+    out_exists: *mut c_int,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(client).and_then(|client| {
+        if count > 0 && (out_lamports.is_null() || out_exists.is_null()) {
+            return Err(SolanaUnityError::FfiError(
+                "Null pointer(s) provided".to_string(),
+            ));
+        }
+        let pubkeys = unsafe { c_str_array(pubkeys, count) }?;
+        let pubkeys: Vec<&str> = pubkeys.iter().map(String::as_str).collect();
+        let balances = client.get_balances(&pubkeys)?;
+
+        for (index, balance) in balances.into_iter().enumerate() {
+            unsafe {
+                *out_lamports.add(index) = balance.unwrap_or(0);
+                *out_exists.add(index) = balance.is_some() as c_int;
+            }
+        }
+        Ok(())
+    });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// Returns a getMultipleAccounts envelope for `pubkeys_count` pubkeys, fetched in requests
+/// of at most 100. With `policy` 0 the first failed request fails the call and the result is
+/// an array; with 1 the result is {accounts, errors}, with null accounts for failed requests.
+///
+/// # Safety
+///
+/// Unless `pubkeys_count` is 0, `pubkeys` must point to that many pointers, each null or a
+/// NUL-terminated string. `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_multiple_accounts(
+    client: *const SolanaRpcClient,
+    pubkeys: *const *const c_char,
+    pubkeys_count: usize,
+    policy: c_int,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let prepared = handle_ref(client).and_then(|client| {
+        let pubkeys = unsafe { c_str_array(pubkeys, pubkeys_count) }?;
+        let policy = match policy {
+            0 => PartialFailure::FailFast,
+            1 => PartialFailure::ReturnSucceeded,
+            other => {
+                return Err(SolanaUnityError::InvalidInput(format!(
+                    "Unknown partial failure policy: {}",
+                    other
+                )))
+            }
+        };
+        Ok((client, pubkeys, policy))
+    });
+    let (client, pubkeys, policy) = match handle_result(prepared, error_out) {
+        Some(prepared) => prepared,
+        None => return ptr::null_mut(),
+    };
+
+    let pubkeys: Vec<&str> = pubkeys.iter().map(String::as_str).collect();
+    let envelope = match policy {
+        PartialFailure::FailFast => to_envelope_string(
+            "getMultipleAccounts",
+            client.get_multiple_accounts(&pubkeys),
+        ),
+        PartialFailure::ReturnSucceeded => to_envelope_string(
+            "getMultipleAccounts",
+            client.get_multiple_accounts_with_policy(&pubkeys, policy),
+        ),
+    };
+    string_to_c(envelope)
+}
+
+/// Returns a getSignatureStatuses envelope whose result maps each of the `signatures_count`
+/// signatures to its outcome: {"status": "confirmed", "slot": n}, {"status": "failed",
+/// "error": "..."} or {"status": "timedOut"}. Blocks until every signature has reached
+/// `commitment` or failed, or `timeout_ms` has passed.
+///
+/// # Safety
+///
+/// `commitment` must be null or a NUL-terminated string. Unless `signatures_count` is 0,
+/// `signatures` must point to that many pointers, each null or a NUL-terminated string.
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_wait_for_confirmations(
+    client: *const SolanaRpcClient,
+    signatures: *const *const c_char,
+    signatures_count: usize,
+    commitment: *const c_char,
+    timeout_ms: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let prepared = unsafe { c_str_array(signatures, signatures_count) };
+    let signatures = match handle_result(prepared, error_out) {
+        Some(signatures) => signatures,
+        None => return ptr::null_mut(),
+    };
+
+    rpc_envelope(
+        client,
+        commitment,
+        "getSignatureStatuses",
+        error_out,
+        |client, commitment| {
+            let signatures: Vec<&str> = signatures.iter().map(String::as_str).collect();
+            client.wait_for_confirmation_outcomes(&signatures, commitment, timeout_ms)
+        },
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Addresses of the accounts owned by `program_id`, without their data
+///
+/// # Safety
+///
+/// `program_id` must be null or a NUL-terminated string. `error_out` must be null or valid
+/// for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_program_account_pubkeys(
+    client: *const SolanaRpcClient,
+    program_id: *const c_char,
+    error_out: *mut *mut c_char,
+) -> SolanaStringArray {
+    let result = handle_ref(client).and_then(|client| {
+        let program_id = unsafe { c_str_to_string(program_id) }?;
+        client.get_program_account_pubkeys(&program_id)
+    });
+    handle_result(result, error_out)
+        .map_or_else(SolanaStringArray::null, SolanaStringArray::from_vec)
+}
+
+/// Signatures of the latest transactions involving `address`, newest first. A `limit` of 0
+/// uses the node's default of 1000.
+///
+/// # Safety
+///
+/// `address` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_signatures_for_address_list(
+    client: *const SolanaRpcClient,
+    address: *const c_char,
+    limit: usize,
+    error_out: *mut *mut c_char,
+) -> SolanaStringArray {
+    let result = handle_ref(client).and_then(|client| {
+        let address = unsafe { c_str_to_string(address) }?;
+        let signatures = client.get_signatures_for_address(&address, limit, None, None)?;
+        Ok(signatures.into_iter().map(|info| info.signature).collect())
+    });
+    handle_result(result, error_out)
+        .map_or_else(SolanaStringArray::null, SolanaStringArray::from_vec)
+}
+
+/// The latest transactions involving `address`, newest first, as a JSON array of
+/// {"signature", "slot", "blockTime", "err", "memo"}. At most `limit` (0 for the node's
+/// default of 1000). `before` and `until` are signatures or null: pass the last signature of
+/// a page as `before` to get the next one.
+///
+/// # Safety
+///
+/// `address`, `before` and `until` must each be null or a NUL-terminated string.
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_signatures_for_address(
+    client: *const SolanaRpcClient,
+    address: *const c_char,
+    limit: usize,
+    before: *const c_char,
+    until: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let optional = |signature: *const c_char| {
+        if signature.is_null() {
+            Ok(None)
+        } else {
+            unsafe { c_str_to_string(signature) }.map(Some)
+        }
+    };
+    let result = handle_ref(client).and_then(|client| {
+        let address = unsafe { c_str_to_string(address) }?;
+        let before = optional(before)?;
+        let until = optional(until)?;
+        client.get_signatures_for_address_json(&address, limit, before.as_deref(), until.as_deref())
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+// Returns a getProgramAccounts envelope
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_get_program_accounts(
+    client: *mut SolanaRpcClient,
+    program_id: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        program_id,
+        "getProgramAccounts",
+        error_out,
+        |client, program_id| client.get_program_accounts(program_id),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns a getProgramAccounts envelope with one page of accounts ordered by address,
+/// `{"accounts": [...], "nextCursor": "..."}`. `filters_json` is a JSON array of
+/// getProgramAccounts filters or null for none. Pass null as the cursor for the first page
+/// and the previous page's nextCursor after that; nextCursor is null on the last page.
+///
+/// # Safety
+///
+/// `program_id`, `filters_json` and `cursor` must each be null or a NUL-terminated string.
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_program_accounts_paged(
+    client: *const SolanaRpcClient,
+    program_id: *const c_char,
+    filters_json: *const c_char,
+    page_size: usize,
+    cursor: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let optional = |ptr: *const c_char| {
+        if ptr.is_null() {
+            Ok(None)
+        } else {
+            unsafe { c_str_to_string(ptr) }.map(Some)
+        }
+    };
+    let prepared = optional(filters_json).and_then(|filters| Ok((filters, optional(cursor)?)));
+    let (filters, cursor) = match handle_result(prepared, error_out) {
+        Some(prepared) => prepared,
+        None => return ptr::null_mut(),
+    };
+
+    rpc_envelope(
+        client,
+        program_id,
+        "getProgramAccounts",
+        error_out,
+        |client, program_id| {
+            client.get_program_accounts_page(
+                program_id,
+                filters.as_deref(),
+                page_size,
+                cursor.as_deref(),
+            )
+        },
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns a getProgramAccounts envelope with the accounts of the Anchor account type
+/// `account_name`, e.g. "PlayerState", owned by `program_id`. The type's discriminator is
+/// matched at offset 0, along with `filters_json`, a JSON array of getProgramAccounts
+/// filters or null for none.
+///
+/// # Safety
+///
+/// `program_id`, `account_name` and `filters_json` must each be null or a NUL-terminated
+/// string. `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_anchor_program_accounts(
+    client: *const SolanaRpcClient,
+    program_id: *const c_char,
+    account_name: *const c_char,
+    filters_json: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let prepared = unsafe { c_str_to_string(account_name) }.and_then(|account_name| {
+        let filters = if filters_json.is_null() {
+            None
+        } else {
+            Some(unsafe { c_str_to_string(filters_json) }?)
+        };
+        Ok((account_name, filters))
+    });
+    let (account_name, filters) = match handle_result(prepared, error_out) {
+        Some(prepared) => prepared,
+        None => return ptr::null_mut(),
+    };
+
+    rpc_envelope(
+        client,
+        program_id,
+        "getProgramAccounts",
+        error_out,
+        |client, program_id| {
+            client.get_anchor_accounts(program_id, &account_name, filters.as_deref())
+        },
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns a getTransaction envelope whose result is the events and messages the program
+/// `program_id` logged in the transaction, in order: [{"program", "discriminatorHex",
+/// "dataBase64", "rawMsg"}]. Events have the first two data fields and messages `rawMsg`.
+///
+/// # Safety
+///
+/// `signature` and `program_id` must each be null or a NUL-terminated string. `error_out`
+/// must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_transaction_events(
+    client: *const SolanaRpcClient,
+    signature: *const c_char,
+    program_id: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let program_id = match handle_result(unsafe { c_str_to_string(program_id) }, error_out) {
+        Some(program_id) => program_id,
+        None => return ptr::null_mut(),
+    };
+
+    rpc_envelope(
+        client,
+        signature,
+        "getTransaction",
+        error_out,
+        |client, signature| client.get_transaction_events(signature, &program_id),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns a getTokenAccountsByOwner envelope whose result is the NFTs `owner` holds under
+/// either token program, at most `limit` (0 for all): [{"mint", "tokenAccount", "name",
+/// "symbol", "uri"}]. With `fetch_metadata` nonzero the Metaplex name, symbol and URI are
+/// filled in, or left null for mints without metadata.
+///
+/// # Safety
+///
+/// `owner` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_nfts_by_owner(
+    client: *const SolanaRpcClient,
+    owner: *const c_char,
+    fetch_metadata: c_int,
+    limit: usize,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        owner,
+        "getTokenAccountsByOwner",
+        error_out,
+        |client, owner| client.get_nfts(owner, fetch_metadata != 0, limit),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns every token account holding `mint` with at least `min_amount` of it, largest
+/// first, as a JSON array of {"owner", "token_account", "amount"}. Token-2022 mints are
+/// detected and queried under that program.
+///
+/// # Safety
+///
+/// `mint` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_token_holders(
+    client: *const SolanaRpcClient,
+    mint: *const c_char,
+    min_amount: u64,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| {
+        let mint = unsafe { c_str_to_string(mint) }?;
+        client.get_token_holders(&mint, min_amount)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// Returns the total supply of a token mint as JSON: {"amount", "decimals",
+/// "uiAmountString"}
+///
+/// # Safety
+///
+/// `mint` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_token_supply(
+    client: *const SolanaRpcClient,
+    mint: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| {
+        let mint = unsafe { c_str_to_string(mint) }?;
+        client.get_token_supply_json(&mint)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+// Returns a getTransaction envelope
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_get_transaction_status(
+    client: *mut SolanaRpcClient,
+    signature: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    rpc_envelope(
+        client,
+        signature,
+        "getTransaction",
+        error_out,
+        |client, signature| client.get_transaction_status(signature),
+    )
+    .map_or(ptr::null_mut(), string_to_c)
+}
+
+// Add new FFI functions for PDA
+
+#[ffi_export]
+pub extern "C" fn solana_find_program_address(
+    seeds_ptr: *const *const c_char,
+    seeds_len: usize,
+    program_id: *const c_char,
+    address_out: *mut *mut c_char,
+    bump_out: *mut u8,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if seeds_ptr.is_null() || program_id.is_null() || address_out.is_null() || bump_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let program_id_str = match unsafe { c_str_to_string(program_id) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    // Convert C array of strings to Rust Vec of byte arrays
+    let mut seeds_vec = Vec::with_capacity(seeds_len);
+    for i in 0..seeds_len {
+        let seed_ptr = unsafe { *seeds_ptr.add(i) };
+        let seed_str = match unsafe { c_str_to_string(seed_ptr) } {
+            Ok(s) => s,
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&e);
+                    }
+                }
+                return 0;
+            }
+        };
+        seeds_vec.push(seed_str.into_bytes());
+    }
+
+    // Convert Vec<Vec<u8>> to Vec<&[u8]>
+    let seeds_slice: Vec<&[u8]> = seeds_vec.iter().map(|s| s.as_slice()).collect();
+
+    match ProgramDerivedAddress::find_program_address(&seeds_slice, &program_id_str) {
+        Ok((address, bump)) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                    *bump_out = bump;
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// Derives a PDA from a JSON array of typed seeds, e.g.
+/// [{"kind":"utf8","value":"player"},{"kind":"pubkey","value":"..."},{"kind":"u64le","value":42}]
+///
+/// # Safety
+///
+/// `seeds_json` and `program_id` must each be null or a NUL-terminated string.
+/// `address_out`, `bump_out` and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_find_program_address_spec(
+    seeds_json: *const c_char,
+    program_id: *const c_char,
+    address_out: *mut *mut c_char,
+    bump_out: *mut u8,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if seeds_json.is_null() || program_id.is_null() || address_out.is_null() || bump_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let seeds_json_str = match unsafe { c_str_to_string(seeds_json) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let program_id_str = match unsafe { c_str_to_string(program_id) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::find_with_seed_spec_json(&seeds_json_str, &program_id_str) {
+        Ok((address, bump)) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                    *bump_out = bump;
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// Derives a PDA from a seed template like "player:{wallet_pubkey}:inventory:{slot_u64}",
+/// resolving placeholders from a JSON object of bindings
+///
+/// # Safety
+///
+/// `template`, `bindings_json` and `program_id` must each be null or a NUL-terminated
+/// string. `address_out`, `bump_out` and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_find_pda_from_template(
+    template: *const c_char,
+    bindings_json: *const c_char,
+    program_id: *const c_char,
+    address_out: *mut *mut c_char,
+    bump_out: *mut u8,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if template.is_null()
+        || bindings_json.is_null()
+        || program_id.is_null()
+        || address_out.is_null()
+        || bump_out.is_null()
+    {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let template_str = match unsafe { c_str_to_string(template) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let bindings_str = match unsafe { c_str_to_string(bindings_json) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let program_id_str = match unsafe { c_str_to_string(program_id) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::find_from_template(&template_str, &bindings_str, &program_id_str) {
+        Ok((address, bump)) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                    *bump_out = bump;
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// Derives many PDAs in one call. `seeds_data` holds every seed's bytes back to back,
+/// `seed_lengths` gives the length of each seed in order, and `set_offsets` gives the index
+/// of the first seed of each set within `seed_lengths`. Returns a JSON array of
+/// {"address", "bump"} objects in set order.
+///
+/// # Safety
+///
+/// `program_id` must be null or a NUL-terminated string. Unless `seeds_data_len` is 0,
+/// `seeds_data` must be valid for reads of that many bytes. Unless `seed_count` is 0,
+/// `seed_lengths` must be valid for reads of that many values. Unless `set_count` is 0,
+/// `set_offsets` must be valid for reads of that many values. `error_out` must be null or
+/// valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_find_program_addresses_batch(
+    seeds_data: *const c_uchar,
+    seeds_data_len: usize,
+    seed_lengths: *const usize,
+    seed_count: usize,
+    set_offsets: *const usize,
+    set_count: usize,
+    program_id: *const c_char,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    if (set_offsets.is_null() && set_count > 0) || program_id.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return ptr::null_mut();
+    }
+
+    let program_id_str = match unsafe { c_str_to_string(program_id) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let seeds = match unsafe { split_seeds(seeds_data, seeds_data_len, seed_lengths, seed_count) } {
+        Ok(seeds) => seeds,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+    let offsets: &[usize] = if set_count == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(set_offsets, set_count) }
+    };
+
+    // Group seeds into sets using the offsets
+    let mut seed_sets = Vec::with_capacity(offsets.len());
+    for (i, start) in offsets.iter().enumerate() {
+        let end = offsets.get(i + 1).copied().unwrap_or(seeds.len());
+        if *start > end || end > seeds.len() {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&SolanaUnityError::InvalidInput(format!(
+                        "Seed set {}: invalid offset range {}..{}",
+                        i, start, end
+                    )));
+                }
+            }
+            return ptr::null_mut();
+        }
+        seed_sets.push(
+            seeds[*start..end]
+                .iter()
+                .map(|seed| seed.to_vec())
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let addresses = match ProgramDerivedAddress::find_program_addresses(&seed_sets, &program_id_str)
+    {
+        Ok(addresses) => addresses,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return ptr::null_mut();
+        }
+    };
+
+    let json: Vec<serde_json::Value> = addresses
+        .into_iter()
+        .map(|(address, bump)| serde_json::json!({ "address": address, "bump": bump }))
+        .collect();
+
+    match CString::new(serde_json::Value::Array(json).to_string()) {
+        Ok(c_json) => c_json.into_raw(),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                        "Failed to convert addresses to C string: {}",
+                        e
+                    )));
+                }
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Checks an address against byte seeds. `seeds_data` holds the seeds back to back and
+/// `seed_lengths` gives the length of each one. Writes 1 to `result_out` when the address
+/// is the canonical PDA, the one solana_find_program_address returns.
+///
+/// # Safety
+///
+/// `address` and `program_id` must each be null or a NUL-terminated string. Unless
+/// `seeds_data_len` is 0, `seeds_data` must be valid for reads of that many bytes. Unless
+/// `seed_count` is 0, `seed_lengths` must be valid for reads of that many values.
+/// `result_out` and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_validate_pda(
+    address: *const c_char,
+    seeds_data: *const c_uchar,
+    seeds_data_len: usize,
+    seed_lengths: *const usize,
+    seed_count: usize,
+    program_id: *const c_char,
+    result_out: *mut c_int,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    validate_pda(
+        address,
+        seeds_data,
+        seeds_data_len,
+        seed_lengths,
+        seed_count,
+        None,
+        program_id,
+        result_out,
+        error_out,
+    )
+}
+
+/// Like solana_validate_pda, matching only the address derived with `bump` appended
+///
+/// # Safety
+///
+/// `address` and `program_id` must each be null or a NUL-terminated string. Unless
+/// `seeds_data_len` is 0, `seeds_data` must be valid for reads of that many bytes. Unless
+/// `seed_count` is 0, `seed_lengths` must be valid for reads of that many values.
+/// `result_out` and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_validate_pda_with_bump(
+    address: *const c_char,
+    seeds_data: *const c_uchar,
+    seeds_data_len: usize,
+    seed_lengths: *const usize,
+    seed_count: usize,
+    bump: u8,
+    program_id: *const c_char,
+    result_out: *mut c_int,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    validate_pda(
+        address,
+        seeds_data,
+        seeds_data_len,
+        seed_lengths,
+        seed_count,
+        Some(bump),
+        program_id,
+        result_out,
+        error_out,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn validate_pda(
+    address: *const c_char,
+    seeds_data: *const c_uchar,
+    seeds_data_len: usize,
+    seed_lengths: *const usize,
+    seed_count: usize,
+    bump: Option<u8>,
+    program_id: *const c_char,
+    result_out: *mut c_int,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if result_out.is_null() {
+        handle_result::<()>(
+            Err(SolanaUnityError::FfiError(
+                "Null output pointer".to_string(),
+            )),
+            error_out,
+        );
+        return 0;
+    }
+    let result = unsafe { c_str_to_string(address) }.and_then(|address| {
+        let program_id = unsafe { c_str_to_string(program_id) }?;
+        let seeds = unsafe { split_seeds(seeds_data, seeds_data_len, seed_lengths, seed_count) }?;
+        match bump {
+            Some(bump) => {
+                ProgramDerivedAddress::validate_with_bump(&address, &seeds, bump, &program_id)
+            }
+            None => ProgramDerivedAddress::validate(&address, &seeds, &program_id),
+        }
+    });
+    match handle_result(result, error_out) {
+        Some(valid) => {
+            unsafe {
+                *result_out = valid as c_int;
+            }
+            1
+        }
+        None => 0,
+    }
+}
+
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. `result_out` and `error_out` must each
+/// be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_is_on_curve(
+    pubkey: *const c_char,
+    result_out: *mut c_int,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if pubkey.is_null() || result_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::is_on_curve(&pubkey_str) {
+        Ok(on_curve) => {
+            unsafe {
+                *result_out = on_curve as c_int;
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// # Safety
+///
+/// `program_id` must be null or a NUL-terminated string. Unless `seeds_data_len` is 0,
+/// `seeds_data` must be valid for reads of that many bytes. Unless `seed_count` is 0,
+/// `seed_lengths` must be valid for reads of that many values. `address_out` and
+/// `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_create_program_address_with_bump(
+    seeds_data: *const c_uchar,
+    seeds_data_len: usize,
+    seed_lengths: *const usize,
+    seed_count: usize,
+    bump: u8,
+    program_id: *const c_char,
+    address_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if program_id.is_null() || address_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let program_id_str = match unsafe { c_str_to_string(program_id) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let seeds = match unsafe { split_seeds(seeds_data, seeds_data_len, seed_lengths, seed_count) } {
+        Ok(seeds) => seeds,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::create_program_address_with_bump(&seeds, bump, &program_id_str) {
+        Ok(address) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// # Safety
+///
+/// `program_id` must be null or a NUL-terminated string. Unless `seeds_data_len` is 0,
+/// `seeds_data` must be valid for reads of that many bytes. Unless `seed_count` is 0,
+/// `seed_lengths` must be valid for reads of that many values. `address_out`, `bump_out`
+/// and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_try_find_program_address_from_bump(
+    seeds_data: *const c_uchar,
+    seeds_data_len: usize,
+    seed_lengths: *const usize,
+    seed_count: usize,
+    starting_bump: u8,
+    program_id: *const c_char,
+    address_out: *mut *mut c_char,
+    bump_out: *mut u8,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if program_id.is_null() || address_out.is_null() || bump_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let program_id_str = match unsafe { c_str_to_string(program_id) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let seeds = match unsafe { split_seeds(seeds_data, seeds_data_len, seed_lengths, seed_count) } {
+        Ok(seeds) => seeds,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::try_find_from_bump(&seeds, starting_bump, &program_id_str) {
+        Ok((address, bump)) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                    *bump_out = bump;
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+#[ffi_export]
+pub extern "C" fn solana_find_associated_token_address(
+    wallet_address: *const c_char,
+    token_mint: *const c_char,
+    address_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if wallet_address.is_null() || token_mint.is_null() || address_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let wallet_str = match unsafe { c_str_to_string(wallet_address) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let mint_str = match unsafe { c_str_to_string(token_mint) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::find_associated_token_address(&wallet_str, &mint_str) {
+        Ok(address) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// # Safety
+///
+/// `wallet_address`, `token_mint` and `token_program` must each be null or a NUL-terminated
+/// string. `address_out` and `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_find_associated_token_address_with_program(
+    wallet_address: *const c_char,
+    token_mint: *const c_char,
+    token_program: *const c_char,
+    strict: c_int,
+    address_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if wallet_address.is_null()
+        || token_mint.is_null()
+        || token_program.is_null()
+        || address_out.is_null()
+    {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let wallet_str = match unsafe { c_str_to_string(wallet_address) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let mint_str = match unsafe { c_str_to_string(token_mint) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let program_str = match unsafe { c_str_to_string(token_program) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let result = if strict != 0 {
+        ProgramDerivedAddress::find_associated_token_address_strict(
+            &wallet_str,
+            &mint_str,
+            &program_str,
+        )
+    } else {
+        ProgramDerivedAddress::find_associated_token_address_with_program(
+            &wallet_str,
+            &mint_str,
+            &program_str,
+        )
+    };
+
+    match result {
+        Ok(address) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// # Safety
+///
+/// `authority` must be null or a NUL-terminated string. `address_out`, `bump_out` and
+/// `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_derive_lookup_table_address(
+    authority: *const c_char,
+    recent_slot: u64,
+    address_out: *mut *mut c_char,
+    bump_out: *mut u8,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if authority.is_null() || address_out.is_null() || bump_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let authority_str = match unsafe { c_str_to_string(authority) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::derive_lookup_table_address(&authority_str, recent_slot) {
+        Ok((address, bump)) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                    *bump_out = bump;
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// # Safety
+///
+/// `mint` must be null or a NUL-terminated string. `address_out`, `bump_out` and
+/// `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_find_metadata_address(
+    mint: *const c_char,
+    address_out: *mut *mut c_char,
+    bump_out: *mut u8,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if mint.is_null() || address_out.is_null() || bump_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let mint_str = match unsafe { c_str_to_string(mint) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::find_metadata_address(&mint_str) {
+        Ok((address, bump)) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                    *bump_out = bump;
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// # Safety
+///
+/// `mint` must be null or a NUL-terminated string. `address_out`, `bump_out` and
+/// `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_find_master_edition_address(
+    mint: *const c_char,
+    address_out: *mut *mut c_char,
+    bump_out: *mut u8,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if mint.is_null() || address_out.is_null() || bump_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let mint_str = match unsafe { c_str_to_string(mint) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::find_master_edition_address(&mint_str) {
+        Ok((address, bump)) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                    *bump_out = bump;
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// # Safety
+///
+/// `mint` must be null or a NUL-terminated string. `address_out`, `bump_out` and
+/// `error_out` must each be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_find_edition_marker_address(
+    mint: *const c_char,
+    edition: u64,
+    address_out: *mut *mut c_char,
+    bump_out: *mut u8,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if mint.is_null() || address_out.is_null() || bump_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let mint_str = match unsafe { c_str_to_string(mint) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match ProgramDerivedAddress::find_edition_marker_address(&mint_str, edition) {
+        Ok((address, bump)) => {
+            // Set the output address
+            match CString::new(address) {
+                Ok(c_address) => unsafe {
+                    *address_out = c_address.into_raw();
+                    *bump_out = bump;
+                },
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::FfiError(format!(
+                                "Failed to convert address to C string: {}",
+                                e
+                            )));
+                        }
+                    }
+                    return 0;
+                }
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+// Add simulation function
+
+// Adds the transaction's instruction label to the failed instruction of a simulation
+#[cfg(feature = "rpc")]
+fn label_simulation(
+    transaction: &Transaction,
+    mut simulation: SimulationResult,
+) -> SimulationResult {
+    if let (Some(failed), Ok(tx)) = (
+        simulation.failed_instruction.as_mut(),
+        transaction.get_transaction(),
+    ) {
+        failed.resolve(&tx.message, transaction.instruction_labels());
+    }
+    simulation
+}
+
+// Returns a simulateTransaction envelope. A transaction that fails on chain is still
+// `"ok":true`; its error is in `result.err`.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_simulate_transaction(
+    client: *mut SolanaRpcClient,
+    transaction: *mut SolanaTransaction,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let prepared = handle_ref(client).and_then(|client| Ok((client, handle_ref(transaction)?)));
+    let (client, transaction) = match handle_result(prepared, error_out) {
+        Some(handles) => handles,
+        None => return ptr::null_mut(),
+    };
+
+    let result = transaction
+        .get_transaction()
+        .and_then(|tx| client.simulate_transaction(tx))
+        .map(|simulation| label_simulation(&transaction, simulation));
+    string_to_c(to_envelope_string("simulateTransaction", result))
+}
+
+/// Simulates a transaction and returns its outcome as separate values. Returns 1 if the
+/// simulated transaction succeeded and 0 if it failed on chain or the call itself failed;
+/// `err_out` is only set in the first case and `error_out` only in the second. Logs are
+/// returned even for failed transactions and must be freed with `solana_free_string_array`.
+///
+/// # Safety
+///
+/// `err_out`, `logs_out`, `logs_count_out`, `units_consumed_out` and `error_out` must each
+/// be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_simulate_transaction_detailed(
+    client: *mut SolanaRpcClient,
+    transaction: *mut SolanaTransaction,
+    err_out: *mut *mut c_char,
+    logs_out: *mut *mut *mut c_char,
+    logs_count_out: *mut usize,
+    units_consumed_out: *mut u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if !err_out.is_null() {
+        unsafe { *err_out = ptr::null_mut() };
+    }
+    if !logs_out.is_null() {
+        unsafe { *logs_out = ptr::null_mut() };
+    }
+    if !logs_count_out.is_null() {
+        unsafe { *logs_count_out = 0 };
+    }
+
+    let result = handle_ref(client).and_then(|client| {
+        let transaction = handle_ref(transaction)?;
+        let simulation = client.simulate_transaction(transaction.get_transaction()?)?;
+        Ok(label_simulation(&transaction, simulation))
+    });
+    let simulation = match handle_result(result, error_out) {
+        Some(simulation) => simulation,
+        None => return 0,
+    };
+
+    let failed = simulation.err.is_some();
+    if let Some(err) = simulation.err {
+        if !err_out.is_null() {
+            unsafe { *err_out = string_to_c(err) };
+        }
+    }
+    if !units_consumed_out.is_null() {
+        unsafe { *units_consumed_out = simulation.units_consumed };
+    }
+    if !logs_out.is_null() && !logs_count_out.is_null() {
+        let logs: Box<[*mut c_char]> = simulation.logs.into_iter().map(string_to_c).collect();
+        unsafe {
+            *logs_count_out = logs.len();
+            *logs_out = Box::into_raw(logs) as *mut *mut c_char;
+        }
+    }
+
+    !failed as c_int
+}
+
+/// Returns what sending the transaction would cost as JSON, see
+/// `Transaction::estimate_total_cost`
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_estimate_transaction_cost(
+    client: *mut SolanaRpcClient,
+    transaction: *mut SolanaTransaction,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result =
+        handle_ref(client).and_then(|client| handle_ref(transaction)?.estimate_total_cost(&client));
+    match handle_result(result, error_out) {
+        Some(json) => string_to_c(json),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Recent priority fees as a JSON array of {"slot", "prioritizationFee"}, oldest first, for
+/// transactions writing any of `addresses`. A count of 0 (with `addresses` allowed to be
+/// null) asks for fees across the cluster.
+///
+/// # Safety
+///
+/// Unless `addresses_count` is 0, `addresses` must point to that many pointers, each null
+/// or a NUL-terminated string. `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_recent_prioritization_fees(
+    client: *const SolanaRpcClient,
+    addresses: *const *const c_char,
+    addresses_count: usize,
+    error_out: *mut *mut c_char,
+) -> *mut c_char {
+    let result = handle_ref(client).and_then(|client| {
+        let addresses = unsafe { c_str_array(addresses, addresses_count) }?;
+        let addresses: Vec<&str> = addresses.iter().map(String::as_str).collect();
+        client.get_recent_prioritization_fees_json(&addresses)
+    });
+    handle_result(result, error_out).map_or(ptr::null_mut(), string_to_c)
+}
+
+/// The exact fee in lamports getFeeForMessage quotes for the transaction, priority fee
+/// included. Returns 0 on failure, with `*error_out` set.
+///
+/// # Safety
+///
+/// `error_out` must be null or valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_get_fee_for_transaction(
+    client: *mut SolanaRpcClient,
+    transaction: *mut SolanaTransaction,
+    error_out: *mut *mut c_char,
+) -> u64 {
+    let result = handle_ref(client).and_then(|client| {
+        let transaction = handle_ref(transaction)?;
+        client.get_fee_for_message(transaction.get_transaction()?)
+    });
+    handle_result(result, error_out).unwrap_or(0)
+}
+
+// Add instruction functions
+
+#[ffi_export]
+pub extern "C" fn solana_create_token_transfer_instruction(
+    source: *const c_char,
+    destination: *const c_char,
+    owner: *const c_char,
+    amount: u64,
+    encoded_data_out: *mut *mut c_uchar,
+    encoded_data_len_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if source.is_null()
+        || destination.is_null()
+        || owner.is_null()
+        || encoded_data_out.is_null()
+        || encoded_data_len_out.is_null()
+    {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let source_str = match unsafe { c_str_to_string(source) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let destination_str = match unsafe { c_str_to_string(destination) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let owner_str = match unsafe { c_str_to_string(owner) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match TokenInstructions::transfer(&source_str, &destination_str, &owner_str, amount) {
+        Ok(instruction) => {
+            // Encode the instruction for returning to C#
+            let encoded = match bincode::serialize(&instruction) {
+                Ok(data) => data,
+                Err(e) => {
+                    if !error_out.is_null() {
+                        unsafe {
+                            *error_out = error_to_c_string(&SolanaUnityError::SerializationError(
+                                format!("Failed to serialize instruction: {}", e),
+                            ));
+                        }
+                    }
+                    return 0;
+                }
+            };
+
+            // Allocate memory for the instruction data
+            let data_len = encoded.len();
+            let data_ptr = allocator::alloc(data_len);
+            if data_ptr.is_null() {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                            "Failed to allocate memory for instruction data".to_string(),
+                        ));
+                    }
+                }
+                return 0;
+            }
+
+            // Copy the data
+            unsafe {
+                std::ptr::copy_nonoverlapping(encoded.as_ptr(), data_ptr, data_len);
+                *encoded_data_out = data_ptr;
+                *encoded_data_len_out = data_len;
+            }
+
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+#[ffi_export]
+pub extern "C" fn solana_free_encoded_instruction(data_ptr: *mut c_uchar) {
+    unsafe {
+        allocator::free(data_ptr);
+    }
+}
+
+#[ffi_export]
+pub extern "C" fn solana_build_with_instructions(
+    transaction: *mut SolanaTransaction,
+    instructions_data: *const c_uchar,
+    instructions_data_len: usize,
+    instructions_count: usize,
+    fee_payer: *const c_char,
+    recent_blockhash: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if transaction.is_null()
+        || instructions_data.is_null()
+        || fee_payer.is_null()
+        || recent_blockhash.is_null()
+    {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let fee_payer_str = match unsafe { c_str_to_string(fee_payer) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let blockhash_str = match unsafe { c_str_to_string(recent_blockhash) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    // Deserialize the instructions
+    let instructions = match unsafe {
+        byte_slice(
+            instructions_data,
+            instructions_data_len,
+            "instructions_data",
+            MAX_ENCODED_INSTRUCTIONS_LEN,
+        )
+    }
+    .and_then(|bytes| decode_instruction_vec(bytes, instructions_count))
+    {
+        Ok(insts) => insts,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    // Build the transaction
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match transaction.build_with_instructions(&instructions, &fee_payer_str, &blockhash_str) {
+        Ok(_) => 1,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// Like solana_build_with_instructions, but takes each instruction in its own buffer, as
+/// returned by solana_create_token_transfer_instruction and the other instruction
+/// builders, so callers do not have to concatenate them into one encoded list.
+/// `instructions_data` and `instructions_lens` both hold `instructions_count` entries.
+///
+/// # Safety
+///
+/// `fee_payer` and `recent_blockhash` must each be null or a NUL-terminated string.
+/// `instructions_data` and `instructions_lens` must be null or valid for reads of
+/// `instructions_count` values, and each buffer in `instructions_data` for reads of its
+/// length in `instructions_lens`. `error_out` must be null or valid for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_build_with_instruction_list(
+    transaction: *mut SolanaTransaction,
+    instructions_data: *const *const c_uchar,
+    instructions_lens: *const usize,
+    instructions_count: usize,
+    fee_payer: *const c_char,
+    recent_blockhash: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if instructions_data.is_null() || instructions_lens.is_null() {
+        handle_result::<()>(
+            Err(SolanaUnityError::FfiError(
+                "Null pointer(s) provided".to_string(),
+            )),
+            error_out,
+        );
+        return 0;
+    }
+
+    // Every instruction takes at least a byte on the wire
+    if instructions_count > MAX_WIRE_LEN {
+        handle_result::<()>(
+            Err(SolanaUnityError::InvalidInput(format!(
+                "instructions_count is {}, more than a transaction can hold",
+                instructions_count
+            ))),
+            error_out,
+        );
+        return 0;
+    }
+
+    let buffers = unsafe { slice::from_raw_parts(instructions_data, instructions_count) };
+    let lens = unsafe { slice::from_raw_parts(instructions_lens, instructions_count) };
+    let result = buffers
+        .iter()
+        .zip(lens)
+        .enumerate()
+        .map(|(index, (&data, &len))| {
+            if data.is_null() {
+                return Err(SolanaUnityError::FfiError(format!(
+                    "Instruction {} is a null pointer",
+                    index
+                )));
+            }
+            let name = format!("instructions_data[{}]", index);
+            let bytes = unsafe { byte_slice(data, len, &name, MAX_ENCODED_INSTRUCTIONS_LEN) }?;
+            decode_strict(bytes).map_err(|e| {
+                SolanaUnityError::InvalidInput(format!("Instruction {}: {}", index, e))
+            })
+        })
+        .collect::<Result<Vec<solana_sdk::instruction::Instruction>, _>>()
+        .and_then(|instructions| {
+            let fee_payer = unsafe { c_str_to_string(fee_payer) }?;
+            let blockhash = unsafe { c_str_to_string(recent_blockhash) }?;
+            deref_handle(transaction)?.build_with_instructions(
+                &instructions,
+                &fee_payer,
+                &blockhash,
+            )
+        });
+    handle_result(result, error_out).map_or(0, |_| 1)
+}
+
+/// Names instruction `index` of a built transaction. Errors about a failed instruction carry
+/// the label in `context.failedInstruction.label`; transfers and token instructions get one
+/// from the builders.
+///
+/// # Safety
+///
+/// `label` must be null or a NUL-terminated string. `error_out` must be null or valid for
+/// writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_transaction_set_instruction_label(
+    transaction: *mut SolanaTransaction,
+    index: usize,
+    label: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = unsafe { c_str_to_string(label) }
+        .and_then(|label| deref_handle(transaction)?.set_instruction_label(index, &label));
+    handle_result(result, error_out).map_or(0, |_| 1)
+}
+
+/// Points a built transaction at `blockhash`, clearing its signatures, and records the last
+/// block height at which it is valid, from solana_get_latest_blockhash_with_expiry.
+/// solana_send_and_confirm_transaction then fails with code 2001 once the chain passes that
+/// height instead of waiting for the timeout.
+///
+/// # Safety
+///
+/// `blockhash` must be null or a NUL-terminated string. `error_out` must be null or valid
+/// for writes.
+#[ffi_export]
+pub unsafe extern "C" fn solana_transaction_set_blockhash_with_expiry(
+    transaction: *mut SolanaTransaction,
+    blockhash: *const c_char,
+    last_valid_block_height: u64,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = unsafe { c_str_to_string(blockhash) }.and_then(|blockhash| {
+        deref_handle(transaction)?.set_blockhash_with_expiry(&blockhash, last_valid_block_height)
+    });
+    handle_result(result, error_out).map_or(0, |_| 1)
+}
+
+// Strict bincode matching bincode::serialize, except that bytes left over after the value
+// are an error instead of being ignored
+fn decode_strict<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    use bincode::Options;
+
+    bincode::options()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+        .map_err(|e| match *e {
+            bincode::ErrorKind::Io(ref io) if io.kind() == std::io::ErrorKind::UnexpectedEof => {
+                format!(
+                    "data is truncated, {} bytes end mid-instruction",
+                    bytes.len()
+                )
+            }
+            _ => e.to_string(),
+        })
+}
+
+// Decodes the encoded instruction list passed to solana_build_with_instructions and checks
+// it holds as many instructions as the caller says it sent
+fn decode_instruction_vec(
+    bytes: &[u8],
+    count: usize,
+) -> Result<Vec<solana_sdk::instruction::Instruction>, SolanaUnityError> {
+    let instructions: Vec<solana_sdk::instruction::Instruction> =
+        decode_strict(bytes).map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Failed to decode instructions: {}", e))
+        })?;
+    if instructions.len() != count {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "instructions_count is {} but the buffer holds {} instructions",
+            count,
+            instructions.len()
+        )));
+    }
+    Ok(instructions)
+}
+
+// Add multiple signatures support
+#[ffi_export]
+pub extern "C" fn solana_sign_transaction_with_keypairs(
+    transaction: *mut SolanaTransaction,
+    private_keys_data: *const *const c_uchar,
+    private_keys_lengths: *const usize,
+    private_keys_count: usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if transaction.is_null() || private_keys_data.is_null() || private_keys_lengths.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    if private_keys_count > MAX_SIGNERS {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::InvalidInput(format!(
+                    "private_keys_count is {}, more than the {} signatures a transaction can hold",
+                    private_keys_count, MAX_SIGNERS
+                )));
+            }
+        }
+        return 0;
+    }
+
+    // Convert C array of byte arrays to Rust Vec of &[u8]
+    let mut private_keys = Vec::with_capacity(private_keys_count);
+    for i in 0..private_keys_count {
+        let key_ptr = unsafe { *private_keys_data.add(i) };
+        let key_len = unsafe { *private_keys_lengths.add(i) };
+        let name = format!("private_keys_data[{}]", i);
+        match unsafe { keypair_slice(key_ptr, key_len, &name) } {
+            Ok(key_slice) => private_keys.push(key_slice),
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&e);
+                    }
+                }
+                return 0;
+            }
+        }
+    }
+    // Sign the transaction
+    let key_slices: Vec<&[u8]> = private_keys.iter().map(|k| *k).collect();
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match transaction.sign_with_keypairs(&key_slices) {
+        Ok(_) => 1,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+// Copies the secret key into a caller-provided buffer and writes the number of bytes
+// copied to `written_out`. If `buf_len` is too small nothing is copied, `written_out`
+// receives the required size and the call fails.
+#[ffi_export]
+pub extern "C" fn solana_account_get_private_key(
+    account: *const SolanaAccount,
+    buf_out: *mut c_uchar,
+    buf_len: usize,
+    written_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if account.is_null() || buf_out.is_null() || written_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let account = match handle_ref(account) {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match account.get_keypair() {
+        Ok(keypair) => {
+            // Copy the secret straight out of the keypair rather than through a temporary
+            // array, so no unscrubbed copy of it is left behind on this side
+            let secret = keypair.secret().as_bytes();
+            let public_key = keypair.pubkey().to_bytes();
+            let len = secret.len() + public_key.len();
+            unsafe {
+                *written_out = len;
+            }
+
+            if buf_len < len {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&SolanaUnityError::InvalidInput(format!(
+                            "Buffer too small for private key: {} bytes required, {} provided",
+                            len, buf_len
+                        )));
+                    }
+                }
+                return 0;
+            }
+
+            unsafe {
+                ptr::copy_nonoverlapping(secret.as_ptr(), buf_out, secret.len());
+                ptr::copy_nonoverlapping(
+                    public_key.as_ptr(),
+                    buf_out.add(secret.len()),
+                    public_key.len(),
+                );
+            }
+            1
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+/// Zeroizes and frees a byte buffer returned by this library that may hold secret material
+///
+/// # Safety
+///
+/// `ptr` must be null or a buffer of `len` bytes returned by this library, and must not be
+/// used after this call.
+#[ffi_export]
+pub unsafe extern "C" fn solana_secure_free_bytes(ptr: *mut c_uchar, len: usize) {
+    if !ptr.is_null() {
+        unsafe {
+            slice::from_raw_parts_mut(ptr, len).zeroize();
+            allocator::free(ptr);
+        }
+    }
+}
+
+/// Zeroizes and frees a string holding secret material, such as the output of
+/// solana_account_get_private_key_base58 or solana_account_to_keypair_json
+///
+/// # Safety
+///
+/// `ptr` must be null or a string returned by this library, and must not be used after this
+/// call.
+#[ffi_export]
+pub unsafe extern "C" fn solana_secure_free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        unsafe {
+            CString::from_raw(ptr).into_bytes_with_nul().zeroize();
+        }
+    }
+}
+
+#[ffi_export]
+pub extern "C" fn solana_account_has_private_key(
+    account: *mut SolanaAccount,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if account.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null account pointer".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let account = match handle_ref(account) {
+        Ok(account) => account,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    account.has_private_key() as c_int
+}
+
+// Deprecated: this used to hand out a pointer into the account's keypair, which dangled
+// once the account was destroyed. It now always returns null; sign with
+// solana_sign_transaction_with_account and read the public key with
+// solana_account_get_pubkey_bytes instead.
+#[ffi_export]
+pub extern "C" fn solana_account_get_keypair(
+    _account: *mut SolanaAccount,
+    error_out: *mut *mut c_char,
+) -> *mut c_void {
+    if !error_out.is_null() {
+        unsafe {
+            *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                "solana_account_get_keypair has been removed, use solana_sign_transaction_with_account or solana_account_get_pubkey_bytes".to_string(),
+            ));
+        }
+    }
+    ptr::null_mut()
+}
+
+#[ffi_export]
+pub extern "C" fn solana_build_program_call(
+    transaction: *mut SolanaTransaction,
+    program_id: *const c_char,
+    accounts: *const *const c_char,
+    accounts_is_signer: *const c_int,
+    accounts_is_writable: *const c_int,
+    accounts_count: usize,
+    data: *const c_uchar,
+    data_len: usize,
+    recent_blockhash: *const c_char,
+    fee_payer: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if transaction.is_null()
+        || program_id.is_null()
+        || accounts.is_null()
+        || recent_blockhash.is_null()
+        || fee_payer.is_null()
+    {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let program_id_str = match unsafe { c_str_to_string(program_id) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let blockhash_str = match unsafe { c_str_to_string(recent_blockhash) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let fee_payer_str = match unsafe { c_str_to_string(fee_payer) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    // Convert C arrays to Rust Vec
+    let mut accounts_vec = Vec::with_capacity(accounts_count);
+    for i in 0..accounts_count {
+        let account_ptr = unsafe { *accounts.add(i) };
+        let account_str = match unsafe { c_str_to_string(account_ptr) } {
+            Ok(s) => s,
+            Err(e) => {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&e);
+                    }
+                }
+                return 0;
+            }
+        };
+        let is_signer = unsafe { *accounts_is_signer.add(i) } != 0;
+        let is_writable = unsafe { *accounts_is_writable.add(i) } != 0;
+        accounts_vec.push((account_str, is_signer, is_writable));
+    }
+
+    // Convert data to Vec<u8>
+    let data_vec = match unsafe { byte_slice(data, data_len, "data", MAX_WIRE_LEN) } {
+        Ok(data) => data.to_vec(),
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let mut transaction = match deref_handle(transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match transaction.build_program_call(
+        &program_id_str,
+        accounts_vec,
+        data_vec,
+        &blockhash_str,
+        &fee_payer_str,
+    ) {
+        Ok(_) => 1,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+// Returns a copy of the account data and writes its length to `data_len_out`.
+// Free the buffer with solana_free_bytes. Empty account data returns a non-null one-byte
+// sentinel with a length of 0, so a null return always means an error.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_get_account_data(
+    client: *mut SolanaRpcClient,
+    pubkey: *const c_char,
+    data_len_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> *mut c_uchar {
+    if client.is_null() || data_len_out.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null pointer(s) provided".to_string(),
+                ));
+            }
+        }
+        return std::ptr::null_mut();
+    }
+
+    let pubkey_str = match unsafe { c_str_to_string(pubkey) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return std::ptr::null_mut();
+        }
+    };
+
+    let client = match handle_ref(client) {
+        Ok(client) => client,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return std::ptr::null_mut();
+        }
+    };
+
+    match client.get_account_data(&pubkey_str) {
+        Ok(data) => {
+            let len = data.len();
+            let ptr = allocator::alloc(len.max(1));
+            if ptr.is_null() {
+                if !error_out.is_null() {
+                    unsafe {
+                        *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                            "Failed to allocate account data buffer".to_string(),
+                        ));
+                    }
+                }
+                return std::ptr::null_mut();
+            }
+
+            unsafe {
+                std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, len);
+                *data_len_out = len;
+            }
+            ptr
+        }
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Copies the account data of `pubkey` from `offset` to the end into `buf`, fetching only
+/// that range and decoding it straight into `buf`, so nothing is allocated for the caller.
+/// `written_out` (optional) always receives the number of bytes from `offset` to the end.
+/// If `buf_len` is too small nothing is copied and the call fails; a `buf_len` of 0 only
+/// queries the size.
+///
+/// # Safety
+///
+/// `pubkey` must be null or a NUL-terminated string. Unless `buf_len` is 0, `buf` must be
+/// valid for writes of that many bytes. `written_out` and `error_out` must each be null or
+/// valid for writes.
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub unsafe extern "C" fn solana_read_account_data_into(
+    client: *const SolanaRpcClient,
+    pubkey: *const c_char,
+    offset: usize,
+    buf: *mut c_uchar,
+    buf_len: usize,
+    written_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    let result = handle_ref(client).and_then(|client| {
+        let pubkey = unsafe { c_str_to_string(pubkey) }?;
+        if buf.is_null() && buf_len > 0 {
+            return Err(SolanaUnityError::FfiError(
+                "Null buffer pointer provided".to_string(),
+            ));
+        }
+        let buf: &mut [u8] = if buf_len == 0 {
+            &mut []
+        } else {
+            unsafe { std::slice::from_raw_parts_mut(buf, buf_len) }
+        };
+
+        let required = client.read_account_data_into(&pubkey, offset, buf)?;
+        if !written_out.is_null() {
+            unsafe {
+                *written_out = required;
+            }
+        }
+        if buf_len > 0 && required > buf_len {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Buffer too small: {} bytes required, {} provided",
+                required, buf_len
+            )));
+        }
+        Ok(())
+    });
+    handle_result(result, error_out).is_some() as c_int
+}
+
+/// Frees a byte buffer returned by this library, e.g. from solana_get_account_data.
+/// `len` is the length reported alongside the buffer.
+///
+/// # Safety
+///
+/// `ptr` must be null or a buffer returned by this library, and must not be used after this
+/// call.
+#[ffi_export]
+pub unsafe extern "C" fn solana_free_bytes(ptr: *mut c_uchar, _len: usize) {
+    unsafe {
+        allocator::free(ptr);
+    }
+}
+
+#[cfg(feature = "rpc")]
+#[ffi_export]
+pub extern "C" fn solana_confirm_transaction(
+    client: *mut SolanaRpcClient,
+    signature: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    if client.is_null() {
+        if !error_out.is_null() {
+            unsafe {
+                *error_out = error_to_c_string(&SolanaUnityError::FfiError(
+                    "Null client pointer".to_string(),
+                ));
+            }
+        }
+        return 0;
+    }
+
+    let signature_str = match unsafe { c_str_to_string(signature) } {
+        Ok(s) => s,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    let client = match handle_ref(client) {
+        Ok(client) => client,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            return 0;
+        }
+    };
+
+    match client.confirm_transaction(&signature_str) {
+        Ok(confirmed) => confirmed as c_int,
+        Err(e) => {
+            if !error_out.is_null() {
+                unsafe {
+                    *error_out = error_to_c_string(&e);
+                }
+            }
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn test_is_valid_pubkey_ffi() {
+        let valid = CString::new("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let invalid = CString::new("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1O").unwrap();
+        let empty = CString::new("").unwrap();
+
+        assert_eq!(unsafe { solana_is_valid_pubkey(valid.as_ptr()) }, 1);
+        assert_eq!(unsafe { solana_is_valid_pubkey(invalid.as_ptr()) }, 0);
+        assert_eq!(unsafe { solana_is_valid_pubkey(empty.as_ptr()) }, 0);
+        assert_eq!(unsafe { solana_is_valid_pubkey(ptr::null()) }, 0);
+    }
+
+    #[test]
+    fn test_is_valid_pubkey_ffi_embedded_nul() {
+        // C strings end at the first NUL, so only the text before it is validated
+        let truncated_valid = b"11111111111111111111111111111111\0garbage\0";
+        assert_eq!(
+            unsafe { solana_is_valid_pubkey(truncated_valid.as_ptr() as *const c_char) },
+            1
+        );
+
+        let truncated_invalid = b"1111\01111111111111111111111111111\0";
+        assert_eq!(
+            unsafe { solana_is_valid_pubkey(truncated_invalid.as_ptr() as *const c_char) },
+            0
+        );
+    }
+
+    #[test]
+    fn test_validate_pubkey_ffi_reports_reason() {
+        let invalid = CString::new("abc").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        assert_eq!(
+            unsafe { solana_validate_pubkey(invalid.as_ptr(), &mut error) },
+            0
+        );
+        assert!(!error.is_null());
+        let message = unsafe { CStr::from_ptr(error) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(message.contains("3 characters"));
+        solana_free_string(error);
+
+        let valid = CString::new("11111111111111111111111111111111").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        assert_eq!(
+            unsafe { solana_validate_pubkey(valid.as_ptr(), &mut error) },
+            1
+        );
+        assert!(error.is_null());
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_account_data_ffi_reports_length() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+
+        // The token program is a well-known account whose data never changes
+        let token_program = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+        let expected = handle_ref(client)
+            .unwrap()
+            .get_account_data(token_program)
+            .unwrap();
+
+        let pubkey = CString::new(token_program).unwrap();
+        let mut len: usize = 0;
+        let data = solana_get_account_data(client, pubkey.as_ptr(), &mut len, &mut error);
+        assert!(!data.is_null());
+        assert_eq!(len, expected.len());
+        assert_eq!(
+            unsafe { slice::from_raw_parts(data, len) },
+            expected.as_slice()
+        );
+
+        unsafe { solana_free_bytes(data, len) };
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_account_data_ffi_null_length_out() {
+        let _runtime = runtime::test_runtime();
+        let pubkey = CString::new("11111111111111111111111111111111").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let data = solana_get_account_data(
+            ptr::NonNull::<SolanaRpcClient>::dangling().as_ptr(),
+            pubkey.as_ptr(),
+            ptr::null_mut(),
+            &mut error,
+        );
+        assert!(data.is_null());
+        assert!(!error.is_null());
+        solana_free_string(error);
+    }
+
+    #[test]
+    fn test_get_private_key_ffi() {
+        let account = Account::generate();
+        let expected = account.get_private_key().unwrap();
+        let account = into_handle(account);
+        let mut buf = [0u8; 80];
+        let mut written: usize = 0;
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let result = solana_account_get_private_key(
+            account,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut written,
+            &mut error,
+        );
+        assert_eq!(result, 1);
+        assert!(error.is_null());
+        assert_eq!(written, 64);
+        assert_eq!(&buf[..written], expected.as_slice());
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_get_private_key_ffi_buffer_too_small() {
+        let account = solana_account_generate();
+        let mut buf = [0u8; 32];
+        let mut written: usize = 0;
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let result = solana_account_get_private_key(
+            account,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut written,
+            &mut error,
+        );
+        assert_eq!(result, 0);
+        assert_eq!(written, 64);
+        assert_eq!(buf, [0u8; 32]);
+
+        let message = unsafe { CStr::from_ptr(error) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(message.contains("64 bytes required"));
+        solana_free_string(error);
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_get_private_key_ffi_read_only_account() {
+        let account =
+            into_handle(Account::from_pubkey("11111111111111111111111111111111").unwrap());
+        let mut buf = [0u8; 64];
+        let mut written: usize = 0;
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let result = solana_account_get_private_key(
+            account,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut written,
+            &mut error,
+        );
+        assert_eq!(result, 0);
+        assert!(!error.is_null());
+        solana_free_string(error);
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_secure_free_bytes() {
+        let ptr = allocator::alloc(64);
+        assert!(!ptr.is_null());
+        unsafe {
+            std::ptr::write_bytes(ptr, 0xAB, 64);
+        }
+        unsafe { solana_secure_free_bytes(ptr, 64) };
+        unsafe { solana_secure_free_bytes(ptr::null_mut(), 0) };
+        unsafe { solana_secure_free_string(ptr::null_mut()) };
+
+        let secret = CString::new("secret").unwrap().into_raw();
+        unsafe { solana_secure_free_string(secret) };
+    }
+
+    #[test]
+    fn test_sign_transaction_with_account_ffi() {
+        let account = Account::generate();
+        let from = account.get_pubkey().unwrap();
+        let to = Account::generate().get_pubkey().unwrap();
+        let mut transaction = Transaction::new();
+        transaction
+            .build_transfer(
+                &from,
+                &to,
+                1000,
+                &solana_sdk::hash::Hash::default().to_string(),
+            )
+            .unwrap();
+
+        let transaction = into_handle(transaction);
+        let account = into_handle(account);
+
+        let mut error: *mut c_char = ptr::null_mut();
+        let result =
+            unsafe { solana_sign_transaction_with_account(transaction, account, &mut error) };
+        assert_eq!(result, 1);
+        assert!(error.is_null());
+        let signed = handle_ref(transaction).unwrap();
+        assert!(signed.get_transaction().unwrap().verify().is_ok());
+        drop(signed);
+
+        // A read-only account cannot sign
+        let watch_only = into_handle(Account::from_pubkey(&from).unwrap());
+        let result =
+            unsafe { solana_sign_transaction_with_account(transaction, watch_only, &mut error) };
+        assert_eq!(result, 0);
+        assert!(!error.is_null());
+        solana_free_string(error);
+
+        solana_destroy_account(watch_only);
+        solana_destroy_account(account);
+        solana_destroy_transaction(transaction);
+    }
+
+    #[test]
+    fn test_get_keypair_ffi_no_longer_returns_pointer() {
+        let account = solana_account_generate();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let keypair = solana_account_get_keypair(account, &mut error);
+        assert!(keypair.is_null());
+        assert!(!error.is_null());
+        let message = unsafe { CStr::from_ptr(error) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(message.contains("solana_sign_transaction_with_account"));
+        solana_free_string(error);
+        solana_destroy_account(account);
+    }
+
+    fn take_error(error: &mut *mut c_char) -> String {
+        assert!(!error.is_null());
+        let message = unsafe { CStr::from_ptr(*error) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        solana_free_string(*error);
+        *error = ptr::null_mut();
+        message
+    }
+
+    #[test]
+    fn test_mismatched_handle_rejected() {
+        let account = solana_account_generate();
+        let key = [0u8; 64];
+        let mut error: *mut c_char = ptr::null_mut();
+
+        // An Account handle passed where a Transaction is expected
+        let result = solana_sign_transaction(
+            account as *mut SolanaTransaction,
+            key.as_ptr(),
+            key.len(),
+            &mut error,
+        );
+        assert_eq!(result, 0);
+        assert!(take_error(&mut error).contains("expected a Transaction handle"));
+
+        // Destroying through the wrong destructor leaves the account intact
+        solana_destroy_transaction(account as *mut SolanaTransaction);
+        let pubkey = solana_account_get_pubkey(account, &mut error);
+        assert!(!pubkey.is_null());
+        solana_free_string(pubkey);
+
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_destroyed_handle_rejected() {
+        let transaction = solana_create_transaction();
+        let account = solana_account_generate();
+        solana_destroy_transaction(transaction);
+        solana_destroy_account(account);
+
+        let key = [0u8; 64];
+        let mut error: *mut c_char = ptr::null_mut();
+        let result = solana_sign_transaction(transaction, key.as_ptr(), key.len(), &mut error);
+        assert_eq!(result, 0);
+        assert!(take_error(&mut error).contains("destroyed"));
+
+        let pubkey = solana_account_get_pubkey(account, &mut error);
+        assert!(pubkey.is_null());
+        assert!(take_error(&mut error).contains("destroyed"));
+
+        // Double destroy is detected and ignored
+        solana_destroy_transaction(transaction);
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_transaction_handle_shared_across_threads() {
+        let payer = Account::generate();
+        let payer_key = payer.get_private_key().unwrap();
+        let from = CString::new(payer.get_pubkey().unwrap()).unwrap();
+        let blockhash = CString::new(solana_sdk::hash::Hash::default().to_string()).unwrap();
+        let transaction = solana_create_transaction();
+        let address = transaction as usize;
+
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let payer_key = payer_key.clone();
+                let from = from.clone();
+                let blockhash = blockhash.clone();
+                std::thread::spawn(move || {
+                    let transaction = address as *mut SolanaTransaction;
+                    let to = CString::new(Account::generate().get_pubkey().unwrap()).unwrap();
+                    for _ in 0..50 {
+                        let mut error: *mut c_char = ptr::null_mut();
+                        let built = solana_build_transfer(
+                            transaction,
+                            from.as_ptr(),
+                            to.as_ptr(),
+                            1000,
+                            blockhash.as_ptr(),
+                            &mut error,
+                        );
+                        assert_eq!(built, 1);
+
+                        // Every build uses the same payer, so signing always succeeds
+                        // whichever build it lands on
+                        let signed = solana_sign_transaction(
+                            transaction,
+                            payer_key.as_ptr(),
+                            payer_key.len(),
+                            &mut error,
+                        );
+                        assert_eq!(signed, 1);
+
+                        assert!(handle_ref(transaction).unwrap().serialize().is_ok());
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        // The final state is a whole transaction from one build, signed or not
+        let mut error: *mut c_char = ptr::null_mut();
+        assert_eq!(
+            solana_sign_transaction(transaction, payer_key.as_ptr(), payer_key.len(), &mut error),
+            1
+        );
+        let final_tx = handle_ref(transaction).unwrap();
+        let tx = final_tx.get_transaction().unwrap();
+        assert!(tx.verify().is_ok());
+        assert_eq!(
+            tx.message.account_keys[0].to_string(),
+            payer.get_pubkey().unwrap()
+        );
+        drop(final_tx);
+
+        solana_destroy_transaction(transaction);
+    }
+
+    #[cfg(feature = "rpc")]
+    fn poll_until_done(request_id: u64) -> (c_int, String) {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(60);
+        loop {
+            let mut status: c_int = 0;
+            let json = unsafe { solana_poll_request(request_id, &mut status) };
+            if status != 0 {
+                assert!(!json.is_null());
+                let value = unsafe { CStr::from_ptr(json) }
+                    .to_str()
+                    .unwrap()
+                    .to_string();
+                solana_free_string(json);
+                return (status, value);
+            }
+            assert!(json.is_null());
+            assert!(std::time::Instant::now() < deadline, "request timed out");
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_request_queue_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+
+        let pubkey = CString::new("11111111111111111111111111111111").unwrap();
+        let balance = unsafe { solana_request_get_balance(client, pubkey.as_ptr(), &mut error) };
+        let blockhash = unsafe { solana_request_latest_blockhash(client, &mut error) };
+        let bad_signature = CString::new("not-a-signature").unwrap();
+        let confirm = unsafe {
+            solana_request_confirm_transaction(client, bad_signature.as_ptr(), &mut error)
+        };
+        assert!(error.is_null());
+        assert!(balance != 0 && blockhash != 0 && confirm != 0);
+
+        // The client can go away while its requests are still queued
+        solana_destroy_rpc_client(client);
+
+        let (status, json) = poll_until_done(balance);
+        assert_eq!(status, 1);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["method"], "getBalance");
+        assert_eq!(value["ok"], true);
+        assert!(value["result"].is_u64());
+
+        let (status, json) = poll_until_done(blockhash);
+        assert_eq!(status, 1);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["result"].is_string());
+
+        let (status, json) = poll_until_done(confirm);
+        assert_eq!(status, 2);
+        assert!(json.contains("Invalid signature"));
+
+        // Results are handed out once
+        let mut status: c_int = 0;
+        assert!(unsafe { solana_poll_request(balance, &mut status) }.is_null());
+        assert_eq!(status, -1);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_request_queue_ffi_unknown_and_cancelled() {
+        let _runtime = runtime::test_runtime();
+        let mut status: c_int = 0;
+        assert!(unsafe { solana_poll_request(u64::MAX, &mut status) }.is_null());
+        assert_eq!(status, -1);
+        assert_eq!(solana_cancel_request(u64::MAX), 0);
+
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        let request_id = unsafe { solana_request_latest_blockhash(client, &mut error) };
+        solana_destroy_rpc_client(client);
+
+        assert_eq!(solana_cancel_request(request_id), 1);
+        assert_eq!(solana_cancel_request(request_id), 0);
+        assert!(unsafe { solana_poll_request(request_id, &mut status) }.is_null());
+        assert_eq!(status, -1);
+
+        // A destroyed client cannot queue new work
+        let request_id = unsafe { solana_request_latest_blockhash(client, &mut error) };
+        assert_eq!(request_id, 0);
+        assert!(!error.is_null());
+        solana_free_string(error);
+    }
+
+    #[test]
+    fn test_pubkey_buf_exact_fit_and_too_small() {
+        let account = into_handle(Account::from_seed(&[7u8; 32]).unwrap());
+        let expected = "GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB";
+        let mut error: *mut c_char = ptr::null_mut();
+        let mut written: usize = 0;
+
+        // Size query
+        let result = unsafe {
+            solana_account_get_pubkey_buf(account, ptr::null_mut(), 0, &mut written, &mut error)
+        };
+        assert_eq!(result, 1);
+        assert!(error.is_null());
+        assert_eq!(written, expected.len() + 1);
+
+        // Exact fit
+        let mut buf = vec![0x7f as c_char; written];
+        let result = unsafe {
+            solana_account_get_pubkey_buf(
+                account,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut written,
+                &mut error,
+            )
+        };
+        assert_eq!(result, 1);
+        assert_eq!(
+            unsafe { CStr::from_ptr(buf.as_ptr()) }.to_str().unwrap(),
+            expected
+        );
+
+        // One byte short: nothing written, required size reported
+        let mut small = vec![0x7f as c_char; expected.len()];
+        let result = unsafe {
+            solana_account_get_pubkey_buf(
+                account,
+                small.as_mut_ptr(),
+                small.len(),
+                &mut written,
+                &mut error,
+            )
+        };
+        assert_eq!(result, 0);
+        assert_eq!(written, expected.len() + 1);
+        assert!(small.iter().all(|c| *c == 0x7f));
+        assert!(take_error(&mut error).contains("45 bytes required"));
+
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_write_str_to_buf_multibyte() {
+        let value = "héllo ✓ 🦀";
+        let mut written: usize = 0;
+        let mut buf = vec![0 as c_char; value.len() + 1];
+
+        unsafe { write_str_to_buf(value, buf.as_mut_ptr(), buf.len(), &mut written) }.unwrap();
+        assert_eq!(written, value.len() + 1);
+        assert_eq!(
+            unsafe { CStr::from_ptr(buf.as_ptr()) }.to_str().unwrap(),
+            value
+        );
+
+        // Sizes are in bytes, not characters
+        let chars = value.chars().count() + 1;
+        let mut short = vec![0 as c_char; chars];
+        assert!(
+            unsafe { write_str_to_buf(value, short.as_mut_ptr(), short.len(), &mut written) }
+                .is_err()
+        );
+        assert_eq!(written, value.len() + 1);
+
+        assert!(
+            unsafe { write_str_to_buf("a\0b", buf.as_mut_ptr(), buf.len(), &mut written) }.is_err()
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_send_transaction_buf_checks_size_before_sending() {
+        let mut error: *mut c_char = ptr::null_mut();
+        let mut written: usize = 0;
+
+        // Neither handle is touched when the buffer is too small
+        let result = unsafe {
+            solana_send_transaction_buf(
+                ptr::null_mut(),
+                ptr::null(),
+                ptr::null_mut(),
+                0,
+                &mut written,
+                &mut error,
+            )
+        };
+        assert_eq!(result, 1);
+        assert_eq!(written, 89);
+
+        let mut buf = [0 as c_char; 64];
+        let result = unsafe {
+            solana_send_transaction_buf(
+                ptr::null_mut(),
+                ptr::null(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut written,
+                &mut error,
+            )
+        };
+        assert_eq!(result, 0);
+        assert!(take_error(&mut error).contains("89 bytes required"));
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_latest_blockhash_buf() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        let mut buf = [0 as c_char; 64];
+        let mut written: usize = 0;
+        let result = unsafe {
+            solana_get_latest_blockhash_buf(
+                client,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut written,
+                &mut error,
+            )
+        };
+        assert_eq!(result, 1);
+        let blockhash = unsafe { CStr::from_ptr(buf.as_ptr()) }.to_str().unwrap();
+        assert_eq!(written, blockhash.len() + 1);
+        assert!(blockhash.parse::<solana_sdk::hash::Hash>().is_ok());
+
+        solana_destroy_rpc_client(client);
+    }
+
+    fn field_offset<T, F>(base: *const T, field: *const F) -> usize {
+        field as usize - base as usize
+    }
+
+    #[test]
+    fn test_result_struct_layouts() {
+        let buffer = std::mem::MaybeUninit::<SolanaBuffer>::uninit();
+        let base = buffer.as_ptr();
+        assert_eq!(
+            field_offset(base, unsafe { ptr::addr_of!((*base).data) }),
+            0
+        );
+        assert_eq!(
+            field_offset(base, unsafe { ptr::addr_of!((*base).len) }),
+            std::mem::size_of::<usize>()
+        );
+
+        let result = std::mem::MaybeUninit::<SolanaU64Result>::uninit();
+        let base = result.as_ptr();
+        assert_eq!(field_offset(base, unsafe { ptr::addr_of!((*base).ok) }), 0);
+        assert_eq!(
+            field_offset(base, unsafe { ptr::addr_of!((*base).value) }),
+            8
+        );
+        assert_eq!(std::mem::size_of::<SolanaU64Result>(), 16);
+
+        let result = std::mem::MaybeUninit::<SolanaStringResult>::uninit();
+        let base = result.as_ptr();
+        assert_eq!(field_offset(base, unsafe { ptr::addr_of!((*base).ok) }), 0);
+        assert_eq!(
+            field_offset(base, unsafe { ptr::addr_of!((*base).value) }),
+            std::mem::size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_serialize_transaction_buffer() {
+        let payer = Account::generate();
+        let to = Account::generate().get_pubkey().unwrap();
+        let mut transaction = Transaction::new();
+        transaction
+            .build_transfer(
+                &payer.get_pubkey().unwrap(),
+                &to,
+                1000,
+                &solana_sdk::hash::Hash::default().to_string(),
+            )
+            .unwrap();
+        transaction.sign_with_account(&payer).unwrap();
+        let expected = transaction.serialize().unwrap();
+        let transaction = into_handle(transaction);
+
+        let mut error: *mut c_char = ptr::null_mut();
+        let buffer = unsafe { solana_serialize_transaction_buffer(transaction, &mut error) };
+        assert!(error.is_null());
+        assert!(!buffer.data.is_null());
+        assert_eq!(
+            unsafe { slice::from_raw_parts(buffer.data, buffer.len) },
+            expected.as_slice()
+        );
+        solana_buffer_free(buffer);
+
+        // An empty transaction cannot be serialized
+        let empty = solana_create_transaction();
+        let buffer = unsafe { solana_serialize_transaction_buffer(empty, &mut error) };
+        assert!(buffer.data.is_null());
+        assert_eq!(buffer.len, 0);
+        take_error(&mut error);
+        solana_buffer_free(buffer);
+
+        solana_destroy_transaction(empty);
+        solana_destroy_transaction(transaction);
+    }
+
+    #[test]
+    fn test_empty_buffer_is_not_null() {
+        let buffer = SolanaBuffer::from_vec(Vec::new());
+        assert!(!buffer.data.is_null());
+        assert_eq!(buffer.len, 0);
+        solana_buffer_free(buffer);
+        solana_buffer_free(SolanaBuffer::null());
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_struct_results_over_rpc() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        let token_program = CString::new("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        let buffer =
+            unsafe { solana_get_account_data_buffer(client, token_program.as_ptr(), &mut error) };
+        assert!(error.is_null());
+        assert!(!buffer.data.is_null());
+        assert!(buffer.len > 0);
+        solana_buffer_free(buffer);
+
+        let balance =
+            unsafe { solana_get_balance_result(client, token_program.as_ptr(), &mut error) };
+        assert_eq!(balance.ok, 1);
+        assert!(balance.value > 0);
+
+        let invalid = CString::new("not-a-pubkey").unwrap();
+        let balance = unsafe { solana_get_balance_result(client, invalid.as_ptr(), &mut error) };
+        assert_eq!(balance.ok, 0);
+        take_error(&mut error);
+
+        let blockhash = unsafe { solana_get_latest_blockhash_result(client, &mut error) };
+        assert_eq!(blockhash.ok, 1);
+        assert!(!blockhash.value.is_null());
+        solana_free_string(blockhash.value);
+
+        solana_destroy_rpc_client(client);
+    }
+
+    fn transfer_with_payer(payer: &Account) -> Transaction {
+        let to = Account::from_seed(&[9u8; 32])
+            .unwrap()
+            .get_pubkey()
+            .unwrap();
+        let mut transaction = Transaction::new();
+        transaction
+            .build_transfer(
+                &payer.get_pubkey().unwrap(),
+                &to,
+                1000,
+                &solana_sdk::hash::Hash::default().to_string(),
+            )
+            .unwrap();
+        transaction
+    }
+
+    #[test]
+    fn test_decode_base64_lenient() {
+        assert_eq!(decode_base64_lenient("data", "aGVsbG8=").unwrap(), b"hello");
+        // Missing padding and embedded whitespace are tolerated
+        assert_eq!(
+            decode_base64_lenient("data", "aGVs\n bG8").unwrap(),
+            b"hello"
+        );
+        assert_eq!(decode_base64_lenient("data", "").unwrap(), b"");
+
+        match decode_base64_lenient("data", "aGV*bG8=") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("position 3")),
+            _ => panic!("Expected InvalidInput for a bad character"),
+        }
+        // Positions refer to the original string, whitespace included
+        match decode_base64_lenient("data", "aG Vs\tb-8") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("position 7")),
+            _ => panic!("Expected InvalidInput for a bad character"),
+        }
+        match decode_base64_lenient("data", "aG=sbG8=") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("position 2")),
+            _ => panic!("Expected InvalidInput for padding in the middle"),
+        }
+        assert!(decode_base64_lenient("data", "a").is_err());
+    }
+
+    #[test]
+    fn test_base64_variants_match_byte_paths() {
+        use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+
+        let payer = Account::from_seed(&[7u8; 32]).unwrap();
+        let private_key = payer.get_private_key().unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        // Account from a base64 key matches the byte path
+        let encoded = CString::new(STANDARD_NO_PAD.encode(&private_key)).unwrap();
+        let account = unsafe { solana_account_from_private_key_b64(encoded.as_ptr(), &mut error) };
+        assert!(!account.is_null());
+        assert_eq!(
+            handle_ref(account).unwrap().get_pubkey().unwrap(),
+            payer.get_pubkey().unwrap()
+        );
+        solana_destroy_account(account);
+
+        // Signing with a base64 key produces the same transaction as the byte path
+        let mut expected = transfer_with_payer(&payer);
+        expected.sign(&private_key).unwrap();
+        let transaction = into_handle(transfer_with_payer(&payer));
+        let encoded = CString::new(STANDARD.encode(&private_key)).unwrap();
+        assert_eq!(
+            unsafe { solana_sign_transaction_b64(transaction, encoded.as_ptr(), &mut error) },
+            1
+        );
+        let signed = handle_ref(transaction).unwrap().serialize().unwrap();
+        assert_eq!(signed, expected.serialize().unwrap());
+
+        // Deserializing the base64 wire format restores the same transaction
+        let restored = solana_create_transaction();
+        let encoded = CString::new(STANDARD.encode(&signed)).unwrap();
+        assert_eq!(
+            unsafe { solana_deserialize_transaction_b64(restored, encoded.as_ptr(), &mut error) },
+            1
+        );
+        assert_eq!(handle_ref(restored).unwrap().serialize().unwrap(), signed);
+
+        let bad = CString::new("not base64!").unwrap();
+        assert_eq!(
+            unsafe { solana_deserialize_transaction_b64(restored, bad.as_ptr(), &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("position 10"));
+
+        solana_destroy_transaction(restored);
+        solana_destroy_transaction(transaction);
+    }
+
+    #[test]
+    fn test_byte_lengths_validated() {
+        let account = solana_account_generate();
+        let keypair = handle_ref(account).unwrap().get_private_key().unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        // Keypairs must be 64 bytes, and a 32-byte seed is called out as such
+        let restored = solana_account_from_private_key(keypair.as_ptr(), 32, &mut error);
+        assert!(restored.is_null());
+        assert!(take_error(&mut error).contains("ed25519 seed"));
+        let restored = solana_account_from_private_key(keypair.as_ptr(), 64 * 8, &mut error);
+        assert!(restored.is_null());
+        assert!(
+            take_error(&mut error).contains("private_key_bytes must be a 64-byte keypair, got 512")
+        );
+        let restored = solana_account_from_private_key(ptr::null(), 64, &mut error);
+        assert!(restored.is_null());
+        assert!(take_error(&mut error).contains("Null private_key_bytes pointer"));
+        let restored = unsafe { solana_account_from_seed(keypair.as_ptr(), 64, &mut error) };
+        assert!(restored.is_null());
+        assert!(take_error(&mut error).contains("which is a keypair"));
+        let restored = unsafe { solana_account_from_seed(keypair.as_ptr(), 1 << 20, &mut error) };
+        assert!(restored.is_null());
+        assert!(take_error(&mut error).contains("seed is 1048576 bytes"));
+
+        let transaction = solana_create_transaction();
+        assert_eq!(
+            solana_sign_transaction(transaction, keypair.as_ptr(), 32, &mut error),
+            0
+        );
+        assert!(take_error(&mut error).contains("ed25519 seed"));
+        assert_eq!(
+            solana_sign_transaction(transaction, ptr::null(), 64, &mut error),
+            0
+        );
+        assert!(take_error(&mut error).contains("Null private_key_bytes pointer"));
+
+        let keys = [keypair.as_ptr(), keypair.as_ptr()];
+        let lens = [64usize, 63];
+        assert_eq!(
+            solana_sign_transaction_with_keypairs(
+                transaction,
+                keys.as_ptr(),
+                lens.as_ptr(),
+                2,
+                &mut error
+            ),
+            0
+        );
+        assert!(take_error(&mut error)
+            .contains("private_keys_data[1] must be a 64-byte keypair, got 63"));
+        assert_eq!(
+            solana_sign_transaction_with_keypairs(
+                transaction,
+                keys.as_ptr(),
+                lens.as_ptr(),
+                usize::MAX,
+                &mut error
+            ),
+            0
+        );
+        assert!(take_error(&mut error).contains("private_keys_count"));
+
+        // Signatures must be 64 bytes
+        let pubkey = CString::new(handle_ref(account).unwrap().get_pubkey().unwrap()).unwrap();
+        let message = b"hello";
+        let signature = [0u8; 64];
+        let mut valid: c_int = 0;
+        assert_eq!(
+            unsafe {
+                solana_verify_signature(
+                    pubkey.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr(),
+                    63,
+                    &mut valid,
+                    &mut error,
+                )
+            },
+            0
+        );
+        assert!(take_error(&mut error).contains("signature must be 64 bytes, got 63"));
+        assert_eq!(
+            unsafe {
+                solana_verify_offchain_message(
+                    pubkey.as_ptr(),
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr(),
+                    512,
+                    &mut valid,
+                    &mut error,
+                )
+            },
+            0
+        );
+        assert!(take_error(&mut error).contains("signature is 512 bytes"));
+        let mut signature_out = [0u8; 64];
+        assert_eq!(
+            unsafe {
+                solana_account_sign_message(
+                    account,
+                    message.as_ptr(),
+                    MAX_SLICE_LEN + 1,
+                    signature_out.as_mut_ptr(),
+                    &mut error,
+                )
+            },
+            0
+        );
+        assert!(take_error(&mut error).contains("message is"));
+
+        // Instruction data is capped at what fits on the wire
+        let program_id = CString::new("11111111111111111111111111111111").unwrap();
+        let accounts = [pubkey.as_ptr()];
+        let flags = [1 as c_int];
+        let blockhash = CString::new(solana_sdk::hash::Hash::default().to_string()).unwrap();
+        let data = [0u8; 8];
+        assert_eq!(
+            solana_build_program_call(
+                transaction,
+                program_id.as_ptr(),
+                accounts.as_ptr(),
+                flags.as_ptr(),
+                flags.as_ptr(),
+                1,
+                data.as_ptr(),
+                MAX_WIRE_LEN + 1,
+                blockhash.as_ptr(),
+                pubkey.as_ptr(),
+                &mut error
+            ),
+            0
+        );
+        assert!(
+            take_error(&mut error).contains("data is 1233 bytes, more than the maximum of 1232")
+        );
+        assert_eq!(
+            solana_build_program_call(
+                transaction,
+                program_id.as_ptr(),
+                accounts.as_ptr(),
+                flags.as_ptr(),
+                flags.as_ptr(),
+                1,
+                ptr::null(),
+                8,
+                blockhash.as_ptr(),
+                pubkey.as_ptr(),
+                &mut error
+            ),
+            0
+        );
+        assert!(take_error(&mut error).contains("Null data pointer"));
+        // Empty data may come with a null pointer
+        assert_eq!(
+            solana_build_program_call(
+                transaction,
+                program_id.as_ptr(),
+                accounts.as_ptr(),
+                flags.as_ptr(),
+                flags.as_ptr(),
+                1,
+                ptr::null(),
+                0,
+                blockhash.as_ptr(),
+                pubkey.as_ptr(),
+                &mut error
+            ),
+            1,
+            "{}",
+            take_error(&mut error)
+        );
+
+        assert_eq!(
+            solana_build_with_instructions(
+                transaction,
+                data.as_ptr(),
+                MAX_ENCODED_INSTRUCTIONS_LEN + 1,
+                1,
+                pubkey.as_ptr(),
+                blockhash.as_ptr(),
+                &mut error
+            ),
+            0
+        );
+        assert!(take_error(&mut error).contains("instructions_data is"));
+        let buffers = [data.as_ptr()];
+        let buffer_lens = [usize::MAX];
+        assert_eq!(
+            unsafe {
+                solana_build_with_instruction_list(
+                    transaction,
+                    buffers.as_ptr(),
+                    buffer_lens.as_ptr(),
+                    1,
+                    pubkey.as_ptr(),
+                    blockhash.as_ptr(),
+                    &mut error,
+                )
+            },
+            0
+        );
+        assert!(take_error(&mut error).contains("instructions_data[0] is"));
+        assert_eq!(
+            unsafe {
+                solana_build_with_instruction_list(
+                    transaction,
+                    buffers.as_ptr(),
+                    buffer_lens.as_ptr(),
+                    MAX_WIRE_LEN + 1,
+                    pubkey.as_ptr(),
+                    blockhash.as_ptr(),
+                    &mut error,
+                )
+            },
+            0
+        );
+        assert!(take_error(&mut error).contains("instructions_count is 1233"));
+
+        let schema = CString::new("{}").unwrap();
+        let decoded = unsafe {
+            solana_decode_account_with_schema(ptr::null(), 4, schema.as_ptr(), &mut error)
+        };
+        assert!(decoded.is_null());
+        assert!(take_error(&mut error).contains("Null data pointer"));
+
+        solana_destroy_transaction(transaction);
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_build_program_call_b64_matches_byte_path() {
+        use base64::engine::general_purpose::STANDARD;
+
+        let program_id = CString::new("11111111111111111111111111111111").unwrap();
+        let payer = Account::from_seed(&[7u8; 32])
+            .unwrap()
+            .get_pubkey()
+            .unwrap();
+        let fee_payer = CString::new(payer.clone()).unwrap();
+        let account = CString::new(payer).unwrap();
+        let accounts = [account.as_ptr()];
+        let is_signer = [1 as c_int];
+        let is_writable = [1 as c_int];
+        let blockhash = CString::new(solana_sdk::hash::Hash::default().to_string()).unwrap();
+        let data = [2u8, 0, 0, 0, 0xe8, 0x03, 0, 0, 0, 0, 0, 0];
+        let data_b64 = CString::new(STANDARD.encode(data)).unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let raw = solana_create_transaction();
+        let result = solana_build_program_call(
+            raw,
+            program_id.as_ptr(),
+            accounts.as_ptr(),
+            is_signer.as_ptr(),
+            is_writable.as_ptr(),
+            accounts.len(),
+            data.as_ptr(),
+            data.len(),
+            blockhash.as_ptr(),
+            fee_payer.as_ptr(),
+            &mut error,
+        );
+        assert_eq!(result, 1);
+
+        let encoded = solana_create_transaction();
+        let result = unsafe {
+            solana_build_program_call_b64(
+                encoded,
+                program_id.as_ptr(),
+                accounts.as_ptr(),
+                is_signer.as_ptr(),
+                is_writable.as_ptr(),
+                accounts.len(),
+                data_b64.as_ptr(),
+                blockhash.as_ptr(),
+                fee_payer.as_ptr(),
+                &mut error,
+            )
+        };
+        assert_eq!(result, 1);
+
+        assert_eq!(
+            handle_ref(raw).unwrap().serialize().unwrap(),
+            handle_ref(encoded).unwrap().serialize().unwrap()
+        );
+
+        solana_destroy_transaction(raw);
+        solana_destroy_transaction(encoded);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_simulate_transaction_detailed_reports_failure() {
+        let _runtime = runtime::test_runtime();
+        use solana_sdk::message::Message;
+        use solana_sdk::pubkey::Pubkey;
+        use solana_sdk::system_instruction;
+        use solana_sdk::transaction::Transaction as SolanaTransaction;
+
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+        let blockhash = handle_ref(client).unwrap().get_latest_blockhash().unwrap();
+
+        // A fresh account has no lamports. The fee payer is the funded account used by the rpc
+        // tests, so the transfer instruction itself runs and fails. Signatures are not verified.
+        let unfunded = Pubkey::new_unique();
+        let fee_payer: Pubkey = "Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr"
+            .parse()
+            .unwrap();
+        let message = Message::new_with_blockhash(
+            &[system_instruction::transfer(
+                &unfunded,
+                &Pubkey::new_unique(),
+                1000,
+            )],
+            Some(&fee_payer),
+            &blockhash.parse().unwrap(),
+        );
+        let unsigned = SolanaTransaction::new_unsigned(message);
+        let mut tx = Transaction::new();
+        tx.from_serialized(&bincode::serialize(&unsigned).unwrap())
+            .unwrap();
+        let transaction = into_handle(tx);
+
+        let mut err: *mut c_char = ptr::null_mut();
+        let mut logs: *mut *mut c_char = ptr::null_mut();
+        let mut logs_count = 0usize;
+        let mut units_consumed = 0u64;
+        let result = unsafe {
+            solana_simulate_transaction_detailed(
+                client,
+                transaction,
+                &mut err,
+                &mut logs,
+                &mut logs_count,
+                &mut units_consumed,
+                &mut error,
+            )
+        };
+
+        assert_eq!(result, 0);
+        assert!(error.is_null());
+        assert!(!err.is_null());
+        let err_message = unsafe { CStr::from_ptr(err) }.to_str().unwrap().to_string();
+        // The transfer is the only instruction, and it fails with
+        // SystemError::ResultWithNegativeLamports
+        assert_eq!(
+            err_message,
+            "Error processing Instruction 0: custom program error: 0x1"
+        );
+
+        let log_lines: Vec<String> = unsafe { slice::from_raw_parts(logs, logs_count) }
+            .iter()
+            .map(|line| {
+                unsafe { CStr::from_ptr(*line) }
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert!(!log_lines.is_empty());
+        assert!(log_lines
+            .iter()
+            .any(|line| line.contains("insufficient lamports")));
+
+        solana_free_string(err);
+        unsafe { solana_free_string_array(logs, logs_count) };
+        solana_destroy_transaction(transaction);
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_simulate_transaction_detailed_invalid_handles() {
+        let _runtime = runtime::test_runtime();
+        let mut err: *mut c_char = ptr::null_mut();
+        let mut logs: *mut *mut c_char = ptr::null_mut();
+        let mut logs_count = 0usize;
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let result = unsafe {
+            solana_simulate_transaction_detailed(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut err,
+                &mut logs,
+                &mut logs_count,
+                ptr::null_mut(),
+                &mut error,
+            )
+        };
+        assert_eq!(result, 0);
+        assert!(err.is_null());
+        assert!(logs.is_null());
+        assert!(take_error(&mut error).contains("Null RpcClient handle"));
+
+        // Freeing a null array is a no-op
+        unsafe { solana_free_string_array(ptr::null_mut(), 0) };
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_json_functions_return_envelopes() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+
+        let read_envelope = |json: *mut c_char| {
+            assert!(!json.is_null());
+            let value: serde_json::Value =
+                serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+            solana_free_string(json);
+            assert_eq!(value["schema"], crate::envelope::SCHEMA_VERSION);
+            value
+        };
+
+        let token_program = CString::new("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        let value = read_envelope(solana_get_account_info(
+            client,
+            token_program.as_ptr(),
+            &mut error,
+        ));
+        assert_eq!(value["method"], "getAccountInfo");
+        assert_eq!(value["ok"], true);
+        assert_eq!(value["result"]["executable"], true);
+
+        // Errors from the method come back in the envelope, not through error_out
+        let bad_signature = CString::new("not-a-signature").unwrap();
+        let value = read_envelope(solana_get_transaction_status(
+            client,
+            bad_signature.as_ptr(),
+            &mut error,
+        ));
+        assert!(error.is_null());
+        assert_eq!(value["method"], "getTransaction");
+        assert_eq!(value["ok"], false);
+        assert_eq!(value["error"]["kind"], "InvalidInput");
+
+        // A transaction that was never built is a failed simulation
+        let transaction = solana_create_transaction();
+        let value = read_envelope(solana_simulate_transaction(client, transaction, &mut error));
+        assert_eq!(value["method"], "simulateTransaction");
+        assert_eq!(value["ok"], false);
+        solana_destroy_transaction(transaction);
+
+        // Calls that cannot be made still use error_out
+        assert!(solana_get_account_info(client, ptr::null(), &mut error).is_null());
+        assert!(!take_error(&mut error).is_empty());
+        solana_destroy_rpc_client(client);
+        assert!(solana_get_account_info(client, token_program.as_ptr(), &mut error).is_null());
+        assert!(take_error(&mut error).contains("destroyed"));
+    }
+
+    #[test]
+    fn test_exported_symbol_manifest() {
+        let _runtime = runtime::test_runtime();
+        let manifest: serde_json::Value = serde_json::from_str(EXPORTED_SYMBOLS_JSON).unwrap();
+        let prefix = env!("SOLANA_UNITY_SYMBOL_PREFIX");
+        assert_eq!(manifest["prefix"], prefix);
+
+        let symbols: Vec<&str> = manifest["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol| symbol.as_str().unwrap())
+            .collect();
+        assert!(symbols.len() > 100);
+        assert!(symbols.iter().all(|symbol| symbol.starts_with(prefix)));
+        let exported = |name: &str| symbols.contains(&format!("{}{}", prefix, name).as_str());
+        assert!(exported("solana_free_string"));
+        assert!(exported("solana_sign_transaction"));
+        // Functions behind disabled features are left out
+        for (name, enabled) in [
+            ("solana_account_from_mnemonic", cfg!(feature = "bip39")),
+            ("solana_get_balance", cfg!(feature = "rpc")),
+            ("solana_create_rpc_client", cfg!(feature = "rpc")),
+            ("solana_subscription_poll_next", cfg!(feature = "websocket")),
+        ] {
+            assert_eq!(exported(name), enabled, "{}", name);
+        }
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_init_and_shutdown_ffi() {
+        let _runtime = runtime::exclusive_test_runtime();
+        let mut error: *mut c_char = ptr::null_mut();
+        assert_eq!(solana_shutdown(), 1);
+        assert_eq!(solana_shutdown(), 1);
+
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert_eq!(
+            unsafe { solana_request_latest_blockhash(client, &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("not initialized"));
+        // Blocking calls too, without starting a runtime of their own
+        let mut slot = 0u64;
+        assert_eq!(unsafe { solana_get_slot(client, &mut slot, &mut error) }, 0);
+        assert!(take_error(&mut error).contains("not initialized"));
+        assert!(!runtime::is_async_runtime_running());
+
+        let bad_config = CString::new(r#"{"requestWorkers": 0}"#).unwrap();
+        assert_eq!(unsafe { solana_init(bad_config.as_ptr(), &mut error) }, 0);
+        assert!(take_error(&mut error).contains("requestWorkers"));
+
+        let config = CString::new(r#"{"requestWorkers": 2}"#).unwrap();
+        assert_eq!(unsafe { solana_init(config.as_ptr(), &mut error) }, 1);
+        assert_eq!(unsafe { solana_init(ptr::null(), &mut error) }, 1);
+        assert!(error.is_null());
+        assert!(runtime::live_threads() >= 2);
+
+        let request_id = unsafe { solana_request_latest_blockhash(client, &mut error) };
+        assert!(request_id != 0);
+        assert!(error.is_null());
+
+        // Shutting down cancels the outstanding request
+        solana_shutdown();
+        let mut status: c_int = 0;
+        assert!(unsafe { solana_poll_request(request_id, &mut status) }.is_null());
+        assert_eq!(status, -1);
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    fn shared_client(url: &str, commitment: &str) -> *mut SolanaRpcClient {
+        let url = CString::new(url).unwrap();
+        let commitment = CString::new(commitment).unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = unsafe {
+            solana_get_or_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error)
+        };
+        assert!(!client.is_null());
+        client
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_shared_rpc_client_refcount() {
+        let _runtime = runtime::test_runtime();
+        let url = "https://shared-refcount.invalid";
+        let first = shared_client(url, "confirmed");
+        let second = shared_client(url, "confirmed");
+        assert_eq!(first, second);
+
+        solana_release_rpc_client(first);
+        assert!(handle_ref(second).is_ok());
+
+        // The last release destroys the handle; the next request creates a fresh client
+        solana_release_rpc_client(second);
+        assert!(handle_ref(second).is_err());
+        let third = shared_client(url, "confirmed");
+        assert!(handle_ref(third).is_ok());
+        solana_release_rpc_client(third);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_shared_rpc_client_interleaved_release() {
+        let _runtime = runtime::test_runtime();
+        let url = "https://shared-interleaved.invalid";
+        let a = shared_client(url, "confirmed");
+        let b = shared_client(url, "confirmed");
+
+        // Destroying one reference must not invalidate the peer
+        solana_destroy_rpc_client(b);
+        assert!(handle_ref(a).is_ok());
+
+        let c = shared_client(url, "confirmed");
+        assert_eq!(a, c);
+        solana_release_rpc_client(a);
+        assert!(handle_ref(c).is_ok());
+        solana_release_rpc_client(c);
+        assert!(handle_ref(c).is_err());
+
+        // Extra releases are ignored
+        solana_release_rpc_client(c);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_shared_rpc_client_keyed_by_commitment() {
+        let _runtime = runtime::test_runtime();
+        let url = "https://shared-commitment.invalid";
+        let confirmed = shared_client(url, "confirmed");
+        let finalized = shared_client(url, "finalized");
+        let other_url = shared_client("https://shared-commitment-2.invalid", "confirmed");
+        assert_ne!(confirmed, finalized);
+        assert_ne!(confirmed, other_url);
+
+        solana_release_rpc_client(confirmed);
+        assert!(handle_ref(finalized).is_ok());
+        solana_release_rpc_client(finalized);
+        solana_release_rpc_client(other_url);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_clone_rpc_client_is_independent() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://clone.invalid").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        let clone = unsafe { solana_clone_rpc_client(client, &mut error) };
+        assert!(!clone.is_null());
+        assert_ne!(client, clone);
+
+        solana_destroy_rpc_client(client);
+        assert!(handle_ref(clone).is_ok());
+        solana_destroy_rpc_client(clone);
+
+        assert!(unsafe { solana_clone_rpc_client(client, &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("destroyed"));
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_slot_and_block_height_report_failure_apart_from_zero() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        let mut value = 7u64;
+        assert_eq!(
+            unsafe { solana_get_slot(client, &mut value, &mut error) },
+            0
+        );
+        assert!(!take_error(&mut error).is_empty());
+        assert_eq!(
+            unsafe { solana_get_block_height(client, &mut value, &mut error) },
+            0
+        );
+        assert!(!take_error(&mut error).is_empty());
+        // Nothing is written on failure
+        assert_eq!(value, 7);
+
+        assert_eq!(
+            unsafe { solana_get_slot(client, ptr::null_mut(), &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("Null output pointer"));
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_fee_for_unbuilt_transaction_fails_before_any_request() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        let transaction = solana_create_transaction();
+
+        assert_eq!(
+            unsafe { solana_get_fee_for_transaction(client, transaction, &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("No transaction available"));
+        solana_destroy_transaction(transaction);
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_latest_blockhash_with_expiry_out_params() {
+        use std::io::{Read, Write};
+
+        let _runtime = runtime::test_runtime();
+        let blockhash = solana_sdk::hash::Hash::new_unique().to_string();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "result": {
+                "context": {"slot": 1},
+                "value": {"blockhash": blockhash, "lastValidBlockHeight": 4242},
+            },
+            "id": 1,
+        })
+        .to_string();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = CString::new(format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 8192];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let body = if request.contains("\"getVersion\"") {
+                    json!({"jsonrpc": "2.0", "result": {"solana-core": "1.18.26"}, "id": 1})
+                        .to_string()
+                } else {
+                    body.clone()
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        let mut blockhash_out: *mut c_char = ptr::null_mut();
+        let mut height = 0u64;
+        let ok = unsafe {
+            solana_get_latest_blockhash_with_expiry(
+                client,
+                &mut blockhash_out,
+                &mut height,
+                &mut error,
+            )
+        };
+        assert_eq!(ok, 1, "{}", take_error(&mut error));
+        assert_eq!(
+            unsafe { CStr::from_ptr(blockhash_out) }.to_str().unwrap(),
+            blockhash
+        );
+        assert_eq!(height, 4242);
+        solana_free_string(blockhash_out);
+
+        let ok = unsafe {
+            solana_get_latest_blockhash_with_expiry(
+                client,
+                ptr::null_mut(),
+                &mut height,
+                &mut error,
+            )
+        };
+        assert_eq!(ok, 0);
+        assert!(take_error(&mut error).contains("Null output pointer"));
+
+        // The pair is recorded on the transaction
+        let mut built = Transaction::new();
+        built
+            .build_transfer(
+                &Account::generate().get_pubkey().unwrap(),
+                &Account::generate().get_pubkey().unwrap(),
+                1,
+                &solana_sdk::hash::Hash::default().to_string(),
+            )
+            .unwrap();
+        let transaction = into_handle(built);
+        let hash = CString::new(blockhash.clone()).unwrap();
+        assert_eq!(
+            unsafe {
+                solana_transaction_set_blockhash_with_expiry(
+                    transaction,
+                    hash.as_ptr(),
+                    4242,
+                    &mut error,
+                )
+            },
+            1
+        );
+        assert_eq!(
+            handle_ref(transaction).unwrap().last_valid_block_height(),
+            Some(4242)
+        );
+        solana_destroy_transaction(transaction);
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_timeout_variants_return_within_budget() {
+        let _runtime = runtime::test_runtime();
+        // Connections to a listener that never accepts are left hanging, like an
+        // unroutable endpoint
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = CString::new(format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let pubkey = CString::new("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        let budget = Duration::from_millis(200);
+        let tolerance = Duration::from_millis(1500);
+        let timeout_ms = budget.as_millis() as u64;
+
+        let start = std::time::Instant::now();
+        let balance =
+            unsafe { solana_get_balance_t(client, pubkey.as_ptr(), timeout_ms, &mut error) };
+        assert!(start.elapsed() < budget + tolerance);
+        assert_eq!(balance, 0);
+        assert_eq!(take_error(&mut error), "Timed out after 200 ms");
+
+        let start = std::time::Instant::now();
+        let blockhash = unsafe { solana_get_latest_blockhash_t(client, timeout_ms, &mut error) };
+        assert!(start.elapsed() < budget + tolerance);
+        assert!(blockhash.is_null());
+        assert!(take_error(&mut error).contains("Timed out"));
+
+        let start = std::time::Instant::now();
+        let json =
+            unsafe { solana_get_account_info_t(client, pubkey.as_ptr(), timeout_ms, &mut error) };
+        assert!(start.elapsed() < budget + tolerance);
+        assert!(error.is_null());
+        let envelope: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        solana_free_string(json);
+        assert_eq!(envelope["ok"], false);
+        assert_eq!(envelope["error"]["kind"], "Timeout");
+
+        // Closing the listener resets the hanging connections so the abandoned calls finish
+        drop(listener);
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_timeout_variants_succeed_within_budget() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        let blockhash = unsafe { solana_get_latest_blockhash_t(client, 20_000, &mut error) };
+        assert!(error.is_null());
+        let text = unsafe { CStr::from_ptr(blockhash) }.to_str().unwrap();
+        assert!(text.parse::<solana_sdk::hash::Hash>().is_ok());
+        solana_free_string(blockhash);
+
+        // 0 falls back to the client's own timeout
+        let blockhash = unsafe { solana_get_latest_blockhash_t(client, 0, &mut error) };
+        assert!(error.is_null());
+        solana_free_string(blockhash);
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    fn error_str(ptr: *const c_char) -> String {
+        assert!(!ptr.is_null());
+        unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string()
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_error_object_accessors() {
+        let mut error: *mut SolanaErrorObj = ptr::null_mut();
+        let failure: Result<(), _> = Err(SolanaUnityError::RpcErrorWithLogs(
+            "Transaction simulation failed: custom program error: 0x1",
+            vec![
+                "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+                "Transfer: insufficient lamports 0, need 1000".to_string(),
+            ],
+        ));
+        assert!(handle_result_obj(failure, &mut error).is_none());
+        assert!(!error.is_null());
+
+        let code = solana_error_get_code(error);
+        let message = solana_error_get_message(error);
+        assert_eq!(error_str(code), "RpcError");
+        assert_eq!(
+            error_str(message),
+            "RPC error: Transaction simulation failed: custom program error: 0x1"
+        );
+        let logs: Vec<String> =
+            serde_json::from_str(&error_str(solana_error_get_logs_json(error))).unwrap();
+        assert_eq!(logs.len(), 2);
+        assert!(logs[1].contains("insufficient lamports"));
+        assert_eq!(solana_error_is_retryable(error), 0);
+        assert_eq!(
+            solana_error_get_numeric_code(error),
+            crate::error::code::RPC_SIMULATION_FAILED
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&error_str(solana_error_get_json(error))).unwrap();
+        assert_eq!(json["kind"], "Rpc");
+        assert_eq!(json["code"], crate::error::code::RPC_SIMULATION_FAILED);
+        assert_eq!(json["name"], "RpcError");
+        assert_eq!(json["context"]["logs"].as_array().unwrap().len(), 2);
+        assert_eq!(json["retryable"], false);
+
+        // Strings handed out earlier are owned by the object, not by the accessor call
+        assert_eq!(solana_error_get_code(error), code);
+        assert_eq!(
+            error_str(message),
+            error_str(solana_error_get_message(error))
+        );
+
+        solana_error_destroy(error);
+        assert!(solana_error_get_code(error).is_null());
+        assert!(solana_error_get_message(error).is_null());
+        assert!(solana_error_get_logs_json(error).is_null());
+        assert!(solana_error_get_json(error).is_null());
+        assert_eq!(solana_error_get_numeric_code(error), 0);
+        assert_eq!(solana_error_is_retryable(error), 0);
+        solana_error_destroy(error);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_error_json_carries_pubkey_context() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut plain_error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut plain_error);
+        assert!(!client.is_null());
+
+        let pubkey = CString::new("not-a-pubkey").unwrap();
+        let mut error: *mut SolanaErrorObj = ptr::null_mut();
+        assert_eq!(
+            unsafe { solana_get_balance_e(client, pubkey.as_ptr(), &mut error) },
+            0
+        );
+        assert_eq!(
+            solana_error_get_numeric_code(error),
+            crate::error::code::INVALID_INPUT
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&error_str(solana_error_get_json(error))).unwrap();
+        assert_eq!(json["kind"], "InvalidInput");
+        assert_eq!(json["code"], crate::error::code::INVALID_INPUT);
+        assert_eq!(json["context"]["pubkey"], "not-a-pubkey");
+        assert!(json["display"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid input: Invalid pubkey"));
+
+        solana_error_destroy(error);
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_error_object_variants() {
+        let _runtime = runtime::test_runtime();
+        let mut error: *mut SolanaErrorObj = ptr::null_mut();
+
+        // Errors without logs report an empty array
+        assert_eq!(
+            unsafe { solana_get_balance_e(ptr::null_mut(), ptr::null(), &mut error) },
+            0
+        );
+        assert_eq!(error_str(solana_error_get_code(error)), "FfiError");
+        assert_eq!(error_str(solana_error_get_logs_json(error)), "[]");
+        assert_eq!(solana_error_is_retryable(error), 0);
+        solana_error_destroy(error);
+
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut string_error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut string_error);
+        let mut error: *mut SolanaErrorObj = ptr::null_mut();
+        let blockhash = unsafe { solana_get_latest_blockhash_e(client, &mut error) };
+        assert!(error.is_null());
+        assert!(!blockhash.is_null());
+        solana_free_string(blockhash);
+
+        // An unsigned transaction fails before anything is sent
+        let transaction = into_handle(Transaction::new());
+        let signature = unsafe { solana_send_transaction_e(client, transaction, &mut error) };
+        assert!(signature.is_null());
+        assert!(!error.is_null());
+        assert_eq!(error_str(solana_error_get_code(error)), "TransactionError");
+        solana_error_destroy(error);
+
+        solana_destroy_transaction(transaction);
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    static LOG_LINES: Mutex<Vec<(c_int, String)>> = Mutex::new(Vec::new());
+
+    #[cfg(feature = "rpc")]
+    extern "C" fn collect_log(level: c_int, message: *const c_char, _user_data: *mut c_void) {
+        // The message is only valid during the call, so it is copied here
+        let message = unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned();
+        LOG_LINES.lock().unwrap().push((level, message));
+    }
+
+    #[cfg(feature = "rpc")]
+    fn log_lines_containing(text: &str) -> Vec<(c_int, String)> {
+        LOG_LINES
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, line)| line.contains(text))
+            .cloned()
+            .collect()
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_log_callback_reports_failing_rpc_call() {
+        let _runtime = runtime::test_runtime();
+        // Nothing listens on port 1, so the request fails straight away
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let pubkey = CString::new("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        // Warnings only: the failure is reported but not the start of the request
+        solana_set_log_callback(Some(collect_log), ptr::null_mut(), logging::LOG_WARN);
+        assert_eq!(solana_get_balance(client, pubkey.as_ptr(), &mut error), 0);
+        assert!(!take_error(&mut error).is_empty());
+        let failed = log_lines_containing("getBalance http://127.0.0.1:1 failed after");
+        assert!(!failed.is_empty());
+        assert!(failed.iter().all(|(level, _)| *level == logging::LOG_WARN));
+        assert!(log_lines_containing("getBalance http://127.0.0.1:1 started").is_empty());
+
+        solana_set_log_callback(Some(collect_log), ptr::null_mut(), logging::LOG_DEBUG);
+        assert_eq!(solana_get_balance(client, pubkey.as_ptr(), &mut error), 0);
+        take_error(&mut error);
+        let started = log_lines_containing("getBalance http://127.0.0.1:1 started");
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].0, logging::LOG_DEBUG);
+        assert_eq!(
+            log_lines_containing("getBalance http://127.0.0.1:1 failed after").len(),
+            2
+        );
+
+        // Once cleared nothing more is logged
+        solana_clear_log_callback();
+        let count = LOG_LINES.lock().unwrap().len();
+        assert_eq!(solana_get_balance(client, pubkey.as_ptr(), &mut error), 0);
+        take_error(&mut error);
+        assert_eq!(LOG_LINES.lock().unwrap().len(), count);
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[test]
+    fn test_keypair_file_ffi() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = CString::new(dir.path().join("id.json").to_str().unwrap()).unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        assert!(unsafe { solana_account_from_keypair_file(path.as_ptr(), &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("Keypair file not found"));
+
+        let account = into_handle(Account::generate());
+        assert_eq!(
+            unsafe { solana_account_write_keypair_file(account, path.as_ptr(), 0, &mut error) },
+            1
+        );
+        let loaded = unsafe { solana_account_from_keypair_file(path.as_ptr(), &mut error) };
+        assert!(!loaded.is_null());
+        assert_eq!(
+            handle_ref(loaded).unwrap().get_private_key().unwrap(),
+            handle_ref(account).unwrap().get_private_key().unwrap()
+        );
+
+        // The existing file is kept unless overwriting is asked for
+        let other = into_handle(Account::generate());
+        assert_eq!(
+            unsafe { solana_account_write_keypair_file(other, path.as_ptr(), 0, &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("already exists"));
+        assert_eq!(
+            unsafe { solana_account_write_keypair_file(other, path.as_ptr(), 1, &mut error) },
+            1
+        );
+        let reloaded = unsafe { solana_account_from_keypair_file(path.as_ptr(), &mut error) };
+        assert_eq!(
+            handle_ref(reloaded).unwrap().get_pubkey().unwrap(),
+            handle_ref(other).unwrap().get_pubkey().unwrap()
+        );
+
+        for handle in [account, loaded, other, reloaded] {
+            solana_destroy_account(handle);
+        }
+    }
+
+    #[test]
+    fn test_pkcs8_pem_ffi() {
+        let mut error: *mut c_char = ptr::null_mut();
+        let pem = CString::new(include_str!("../tests/fixtures/pkcs8/ed25519.pem")).unwrap();
+        let account = unsafe { solana_account_from_pkcs8_pem(pem.as_ptr(), &mut error) };
+        assert!(!account.is_null());
+        assert_eq!(
+            handle_ref(account).unwrap().get_pubkey().unwrap(),
+            "4NHdUBwjpvre1mjV1kaVmdBvNYRtUYJGQrEWWoLkRXQG"
+        );
+
+        let exported = unsafe { solana_account_to_pkcs8_pem(account, &mut error) };
+        assert!(error.is_null());
+        let exported_pem = unsafe { CStr::from_ptr(exported) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { solana_secure_free_string(exported) };
+        assert_eq!(
+            exported_pem,
+            include_str!("../tests/fixtures/pkcs8/ed25519_v2.pem")
+        );
+
+        let ec = CString::new(include_str!("../tests/fixtures/pkcs8/p256.pem")).unwrap();
+        assert!(unsafe { solana_account_from_pkcs8_pem(ec.as_ptr(), &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("Expected an Ed25519 key"));
+        assert!(unsafe { solana_account_from_pkcs8_pem(ptr::null(), &mut error) }.is_null());
+        assert!(!take_error(&mut error).is_empty());
+
+        let read_only =
+            into_handle(Account::from_pubkey("11111111111111111111111111111111").unwrap());
+        assert!(unsafe { solana_account_to_pkcs8_pem(read_only, &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("No keypair"));
+
+        solana_destroy_account(read_only);
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_split_key_ffi() {
+        let mut error: *mut c_char = ptr::null_mut();
+        let account = into_handle(Account::generate());
+        let shares = unsafe { solana_account_split_key(account, 2, 3, &mut error) };
+        assert!(error.is_null());
+        let items = string_array_items(&shares);
+        assert_eq!(items.len(), 3);
+
+        let chosen = [shares_ptr(&shares, 2), shares_ptr(&shares, 0)];
+        let restored =
+            unsafe { solana_account_from_shares(chosen.as_ptr(), chosen.len(), &mut error) };
+        assert!(!restored.is_null(), "{}", take_error(&mut error));
+        assert_eq!(
+            handle_ref(restored).unwrap().get_pubkey().unwrap(),
+            handle_ref(account).unwrap().get_pubkey().unwrap()
+        );
+
+        assert!(unsafe { solana_account_from_shares(chosen.as_ptr(), 1, &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("Not enough key shares"));
+        assert!(unsafe { solana_account_from_shares(ptr::null(), 2, &mut error) }.is_null());
+        assert!(!take_error(&mut error).is_empty());
+
+        let rejected = unsafe { solana_account_split_key(account, 4, 3, &mut error) };
+        assert!(rejected.items.is_null());
+        assert!(take_error(&mut error).contains("Threshold"));
+
+        solana_string_array_secure_free(shares);
+        solana_destroy_account(restored);
+        solana_destroy_account(account);
+    }
+
+    #[test]
+    fn test_session_ffi() {
+        let mut error: *mut c_char = ptr::null_mut();
+        let token_account = CString::new(Account::generate().get_pubkey().unwrap()).unwrap();
+        let owner = CString::new(Account::generate().get_pubkey().unwrap()).unwrap();
+        let mut approve = SolanaBuffer::null();
+        let session = unsafe {
+            solana_session_create(
+                token_account.as_ptr(),
+                owner.as_ptr(),
+                ptr::null(),
+                0,
+                500,
+                1_700_000_000,
+                &mut approve,
+                &mut error,
+            )
+        };
+        assert!(!session.is_null(), "{}", take_error(&mut error));
+        let approve_bytes = unsafe { slice::from_raw_parts(approve.data, approve.len) }.to_vec();
+        solana_buffer_free(approve);
+        let instruction: solana_sdk::instruction::Instruction =
+            bincode::deserialize(&approve_bytes).unwrap();
+        assert_eq!(instruction.data[0], 4);
+
+        let account = unsafe { solana_session_get_account(session, &mut error) };
+        assert!(!account.is_null(), "{}", take_error(&mut error));
+        assert_eq!(
+            instruction.accounts[1].pubkey.to_string(),
+            handle_ref(account).unwrap().get_pubkey().unwrap()
+        );
+        assert_eq!(
+            unsafe { solana_session_expires_at(session, &mut error) },
+            1_700_000_000
+        );
+
+        let password = CString::new("hunter2").unwrap();
+        let json = unsafe { solana_session_serialize(session, password.as_ptr(), &mut error) };
+        assert!(!json.is_null(), "{}", take_error(&mut error));
+        let restored = unsafe { solana_session_deserialize(json, password.as_ptr(), &mut error) };
+        assert!(!restored.is_null(), "{}", take_error(&mut error));
+        let again = unsafe { solana_session_approve_instruction(restored, &mut error) };
+        assert_eq!(
+            unsafe { slice::from_raw_parts(again.data, again.len) },
+            &approve_bytes[..]
+        );
+        solana_buffer_free(again);
+        let revoke = unsafe { solana_session_revoke_instruction(restored, &mut error) };
+        assert!(!revoke.data.is_null(), "{}", take_error(&mut error));
+        solana_buffer_free(revoke);
+
+        let wrong = CString::new("wrong").unwrap();
+        assert!(unsafe { solana_session_deserialize(json, wrong.as_ptr(), &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("Decryption failed"));
+
+        let mut rejected = SolanaBuffer::null();
+        let session_missing_owner = unsafe {
+            solana_session_create(
+                token_account.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+                0,
+                500,
+                0,
+                &mut rejected,
+                &mut error,
+            )
+        };
+        assert!(session_missing_owner.is_null());
+        assert!(rejected.data.is_null());
+        assert!(!take_error(&mut error).is_empty());
+
+        solana_free_string(json);
+        solana_destroy_account(account);
+        solana_session_destroy(restored);
+        solana_session_destroy(session);
+    }
+
+    fn shares_ptr(array: &SolanaStringArray, index: usize) -> *const c_char {
+        unsafe { *array.items.add(index) }
+    }
+
+    #[test]
+    fn test_address_book_ffi() {
+        let mut error: *mut c_char = ptr::null_mut();
+        let dir = tempfile::tempdir().unwrap();
+        let path = CString::new(dir.path().join("friends.json").to_str().unwrap()).unwrap();
+        let alice = CString::new(Account::generate().get_pubkey().unwrap()).unwrap();
+        let label = CString::new("Alice").unwrap();
+        let memo = CString::new("guild leader").unwrap();
+
+        let book = solana_address_book_create();
+        let added = unsafe {
+            solana_address_book_add(
+                book,
+                label.as_ptr(),
+                alice.as_ptr(),
+                memo.as_ptr(),
+                0,
+                &mut error,
+            )
+        };
+        assert_eq!(added, 1, "{}", take_error(&mut error));
+        let again = unsafe {
+            solana_address_book_add(
+                book,
+                label.as_ptr(),
+                alice.as_ptr(),
+                ptr::null(),
+                0,
+                &mut error,
+            )
+        };
+        assert_eq!(again, 0);
+        assert!(take_error(&mut error).contains("already used"));
+        assert_eq!(
+            unsafe { solana_address_book_save(book, path.as_ptr(), &mut error) },
+            1
+        );
+        solana_address_book_destroy(book);
+
+        let book = unsafe { solana_address_book_load(path.as_ptr(), &mut error) };
+        assert!(!book.is_null(), "{}", take_error(&mut error));
+        let entry = unsafe { solana_address_book_reverse_lookup(book, alice.as_ptr(), &mut error) };
+        let entry: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(entry) }.to_str().unwrap()).unwrap();
+        assert_eq!(entry["label"], "Alice");
+        assert_eq!(entry["memo"], "guild leader");
+
+        assert_eq!(
+            unsafe { solana_address_book_remove(book, label.as_ptr(), &mut error) },
+            1
+        );
+        let missing = unsafe { solana_address_book_lookup(book, label.as_ptr(), &mut error) };
+        assert!(missing.is_null());
+        assert!(error.is_null());
+        solana_address_book_destroy(book);
+
+        std::fs::write(dir.path().join("friends.json"), "{\"version\": 1,").unwrap();
+        assert!(unsafe { solana_address_book_load(path.as_ptr(), &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("line 1"));
+    }
+
+    fn string_array_items(array: &SolanaStringArray) -> Vec<String> {
+        assert!(!array.items.is_null());
+        unsafe { slice::from_raw_parts(array.items, array.len) }
+            .iter()
+            .map(|item| {
+                unsafe { CStr::from_ptr(*item) }
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_string_array_round_trip() {
+        let strings = vec!["first".to_string(), "sécond".to_string(), String::new()];
+        let array = SolanaStringArray::from_vec(strings.clone());
+        assert_eq!(array.len, 3);
+        assert_eq!(string_array_items(&array), strings);
+        solana_string_array_free(array);
+
+        // Empty arrays still have a non-null items pointer
+        let empty = SolanaStringArray::from_vec(Vec::new());
+        assert_eq!(empty.len, 0);
+        assert!(string_array_items(&empty).is_empty());
+        solana_string_array_free(empty);
+
+        solana_string_array_free(SolanaStringArray::null());
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_list_returning_rpc_calls() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        let address = CString::new("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr").unwrap();
+        let signatures = unsafe {
+            solana_get_signatures_for_address_list(client, address.as_ptr(), 5, &mut error)
+        };
+        assert!(error.is_null());
+        assert!(signatures.len <= 5);
+        for signature in string_array_items(&signatures) {
+            assert!(signature
+                .parse::<solana_sdk::signature::Signature>()
+                .is_ok());
+        }
+        solana_string_array_free(signatures);
+
+        // A program nobody has deployed owns no accounts
+        let program_id =
+            CString::new(solana_sdk::pubkey::Pubkey::new_unique().to_string()).unwrap();
+        let pubkeys =
+            unsafe { solana_get_program_account_pubkeys(client, program_id.as_ptr(), &mut error) };
+        assert!(error.is_null());
+        assert_eq!(pubkeys.len, 0);
+        assert!(!pubkeys.items.is_null());
+        solana_string_array_free(pubkeys);
+
+        let invalid = CString::new("not-a-pubkey").unwrap();
+        let pubkeys =
+            unsafe { solana_get_program_account_pubkeys(client, invalid.as_ptr(), &mut error) };
+        assert!(pubkeys.items.is_null());
+        assert_eq!(pubkeys.len, 0);
+        assert!(take_error(&mut error).contains("Invalid program ID"));
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_balance_v2_distinguishes_zero_from_failure() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        // A fresh address has a legitimate balance of zero
+        let empty = CString::new(solana_sdk::pubkey::Pubkey::new_unique().to_string()).unwrap();
+        let mut lamports = u64::MAX;
+        assert_eq!(
+            unsafe { solana_get_balance_v2(client, empty.as_ptr(), &mut lamports, &mut error) },
+            1
+        );
+        assert!(error.is_null());
+        assert_eq!(lamports, 0);
+
+        // Failures leave the output untouched
+        let invalid = CString::new("not-a-pubkey").unwrap();
+        let mut lamports = 42;
+        assert_eq!(
+            unsafe { solana_get_balance_v2(client, invalid.as_ptr(), &mut lamports, &mut error) },
+            0
+        );
+        assert_eq!(lamports, 42);
+        assert!(take_error(&mut error).contains("Invalid pubkey"));
+
+        let mut amount = 42;
+        assert_eq!(
+            unsafe {
+                solana_get_token_account_balance_v2(
+                    client,
+                    invalid.as_ptr(),
+                    &mut amount,
+                    &mut error,
+                )
+            },
+            0
+        );
+        assert_eq!(amount, 42);
+        assert!(take_error(&mut error).contains("Invalid pubkey"));
+
+        // Null out-pointers and a null error_out are both handled
+        assert_eq!(
+            unsafe { solana_get_balance_v2(client, empty.as_ptr(), ptr::null_mut(), &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("Null output pointer"));
+        assert_eq!(
+            unsafe {
+                solana_get_balance_v2(client, invalid.as_ptr(), &mut lamports, ptr::null_mut())
+            },
+            0
+        );
+        solana_destroy_rpc_client(client);
+
+        let unreachable = CString::new("http://127.0.0.1:1").unwrap();
+        let client =
+            solana_create_rpc_client(unreachable.as_ptr(), commitment.as_ptr(), &mut error);
+        assert_eq!(
+            unsafe { solana_get_balance_v2(client, empty.as_ptr(), &mut lamports, &mut error) },
+            0
+        );
+        assert_eq!(lamports, 42);
+        assert!(take_error(&mut error).starts_with("RPC error"));
+        assert_eq!(
+            unsafe {
+                solana_get_token_account_balance_v2(client, empty.as_ptr(), &mut amount, &mut error)
+            },
+            0
+        );
+        assert_eq!(amount, 42);
+        assert!(!take_error(&mut error).is_empty());
+        solana_destroy_rpc_client(client);
+    }
+
+    #[test]
+    fn test_get_version_reports_manifest_version() {
+        let manifest = include_str!("../Cargo.toml");
+        let package_version = manifest
+            .lines()
+            .skip_while(|line| *line != "[package]")
+            .find_map(|line| line.strip_prefix("version = \""))
+            .and_then(|rest| rest.strip_suffix('"'))
+            .unwrap();
+
+        let ptr = solana_get_version();
+        assert!(!ptr.is_null());
+        let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        solana_free_string(ptr);
+
+        let version: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(version["version"], package_version);
+        assert_eq!(version["abiVersion"], solana_get_abi_version());
+        assert!(version["solanaSdk"].as_str().unwrap().starts_with("1."));
+        if cfg!(feature = "rpc") {
+            assert!(version["solanaClient"].as_str().unwrap().starts_with("1."));
+        } else {
+            assert!(version["solanaClient"].is_null());
+        }
+        assert!(version["profile"].is_string());
+
+        let features = version["features"].as_array().unwrap();
+        for (name, enabled) in [
+            ("bip39", cfg!(feature = "bip39")),
+            ("rpc", cfg!(feature = "rpc")),
+            ("websocket", cfg!(feature = "websocket")),
+        ] {
+            assert_eq!(
+                features.iter().any(|feature| feature == name),
+                enabled,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_create_configured_rpc_client() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let client = unsafe {
+            solana_create_rpc_client_configured(
+                url.as_ptr(),
+                commitment.as_ptr(),
+                ptr::null(),
+                &mut error,
+            )
+        };
+        assert!(!client.is_null());
+        assert!(error.is_null());
+        solana_destroy_rpc_client(client);
+
+        let config = CString::new(r#"{"maxIdlePerHost": 2, "tcpKeepaliveMs": 30000}"#).unwrap();
+        let client = unsafe {
+            solana_create_rpc_client_configured(
+                url.as_ptr(),
+                commitment.as_ptr(),
+                config.as_ptr(),
+                &mut error,
+            )
+        };
+        assert!(!client.is_null());
+        solana_destroy_rpc_client(client);
+
+        let config = CString::new(r#"{"tcpKeepaliveMs": 1}"#).unwrap();
+        let client = unsafe {
+            solana_create_rpc_client_configured(
+                url.as_ptr(),
+                commitment.as_ptr(),
+                config.as_ptr(),
+                &mut error,
+            )
+        };
+        assert!(client.is_null());
+        assert!(take_error(&mut error).contains("tcpKeepaliveMs"));
+    }
+
+    #[test]
+    fn test_decode_program_error() {
+        let decode = |logs: Option<&str>, code: u32, idl: Option<&str>| {
+            let logs = logs.map(|logs| CString::new(logs).unwrap());
+            let idl = idl.map(|idl| CString::new(idl).unwrap());
+            let mut error: *mut c_char = ptr::null_mut();
+            let ptr = unsafe {
+                solana_decode_program_error(
+                    logs.as_ref().map_or(ptr::null(), |logs| logs.as_ptr()),
+                    code,
+                    idl.as_ref().map_or(ptr::null(), |idl| idl.as_ptr()),
+                    &mut error,
+                )
+            };
+            if !error.is_null() {
+                return Err(take_error(&mut error));
+            }
+            if ptr.is_null() {
+                return Ok(None);
+            }
+            let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+            solana_free_string(ptr);
+            Ok(Some(
+                serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+            ))
+        };
+
+        let decoded = decode(
+            Some(include_str!(
+                "../tests/fixtures/anchor/constraint_seeds.json"
+            )),
+            0x7d6,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(decoded["name"], "ConstraintSeeds");
+        assert_eq!(decoded["builtin"], true);
+
+        let idl = include_str!("../tests/fixtures/anchor/idl.json");
+        let decoded = decode(None, 6001, Some(idl)).unwrap().unwrap();
+        assert_eq!(decoded["name"], "LobbyFull");
+        assert_eq!(decoded["message"], "The lobby has no free seats");
+
+        // Nothing names it, which is not an error
+        assert_eq!(decode(None, 6001, None), Ok(None));
+
+        let message = decode(Some("not json"), 6001, None).unwrap_err();
+        assert!(message.contains("logs must be a JSON array"), "{}", message);
+        assert!(decode(None, 6001, Some("{\"errors\": 1}")).is_err());
+    }
+
+    fn transfer_instructions(count: u64) -> Vec<solana_sdk::instruction::Instruction> {
+        let from = Account::generate().get_pubkey().unwrap();
+        (0..count)
+            .map(|lamports| {
+                let to = Account::generate().get_pubkey().unwrap();
+                solana_sdk::system_instruction::transfer(
+                    &from.parse().unwrap(),
+                    &to.parse().unwrap(),
+                    lamports + 1,
+                )
+            })
+            .collect()
+    }
+
+    fn build_with_encoded(encoded: &[u8], count: usize) -> Result<(), String> {
+        let fee_payer = CString::new(Account::generate().get_pubkey().unwrap()).unwrap();
+        let blockhash = CString::new(solana_sdk::hash::Hash::default().to_string()).unwrap();
+        let transaction = solana_create_transaction();
+        let mut error: *mut c_char = ptr::null_mut();
+        let result = solana_build_with_instructions(
+            transaction,
+            encoded.as_ptr(),
+            encoded.len(),
+            count,
+            fee_payer.as_ptr(),
+            blockhash.as_ptr(),
+            &mut error,
+        );
+        solana_destroy_transaction(transaction);
+        if result == 1 {
+            assert!(error.is_null());
+            Ok(())
+        } else {
+            Err(take_error(&mut error))
+        }
+    }
+
+    #[test]
+    fn test_build_with_instructions_checks_count() {
+        let encoded = bincode::serialize(&transfer_instructions(2)).unwrap();
+        assert!(build_with_encoded(&encoded, 2).is_ok());
+
+        let message = build_with_encoded(&encoded, 3).unwrap_err();
+        assert!(
+            message.contains("instructions_count is 3 but the buffer holds 2 instructions"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_build_with_instructions_rejects_truncated_and_trailing_data() {
+        let encoded = bincode::serialize(&transfer_instructions(2)).unwrap();
+
+        let message = build_with_encoded(&encoded[..encoded.len() - 5], 2).unwrap_err();
+        assert!(message.contains("truncated"), "{}", message);
+
+        let mut padded = encoded.clone();
+        padded.extend_from_slice(&[0; 4]);
+        assert!(build_with_encoded(&padded, 2).is_err());
+    }
+
+    #[test]
+    fn test_build_with_instruction_list() {
+        let instructions = transfer_instructions(3);
+        let encoded: Vec<Vec<u8>> = instructions
+            .iter()
+            .map(|instruction| bincode::serialize(instruction).unwrap())
+            .collect();
+        let pointers: Vec<*const c_uchar> = encoded.iter().map(|data| data.as_ptr()).collect();
+        let lens: Vec<usize> = encoded.iter().map(Vec::len).collect();
+
+        let fee_payer = CString::new(Account::generate().get_pubkey().unwrap()).unwrap();
+        let blockhash = CString::new(solana_sdk::hash::Hash::default().to_string()).unwrap();
+        let transaction = solana_create_transaction();
+        let mut error: *mut c_char = ptr::null_mut();
+        let result = unsafe {
+            solana_build_with_instruction_list(
+                transaction,
+                pointers.as_ptr(),
+                lens.as_ptr(),
+                pointers.len(),
+                fee_payer.as_ptr(),
+                blockhash.as_ptr(),
+                &mut error,
+            )
+        };
+        assert_eq!(result, 1);
+        assert!(error.is_null());
+        let built = handle_ref(transaction)
+            .unwrap()
+            .get_transaction()
+            .unwrap()
+            .message
+            .instructions
+            .len();
+        assert_eq!(built, 3);
+
+        // A short buffer is reported with its position in the list
+        let short = [lens[0], lens[1] - 1, lens[2]];
+        let result = unsafe {
+            solana_build_with_instruction_list(
+                transaction,
+                pointers.as_ptr(),
+                short.as_ptr(),
+                pointers.len(),
+                fee_payer.as_ptr(),
+                blockhash.as_ptr(),
+                &mut error,
+            )
+        };
+        assert_eq!(result, 0);
+        let message = take_error(&mut error);
+        assert!(message.contains("Instruction 1"), "{}", message);
+        assert!(message.contains("truncated"), "{}", message);
+
+        solana_destroy_transaction(transaction);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_multiple_accounts_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        let pubkeys: Vec<CString> = (0..250)
+            .map(|_| CString::new(solana_sdk::pubkey::Pubkey::new_unique().to_string()).unwrap())
+            .collect();
+        let pointers: Vec<*const c_char> = pubkeys.iter().map(|p| p.as_ptr()).collect();
+        let read_envelope = |json: *mut c_char| {
+            assert!(!json.is_null());
+            let value: serde_json::Value =
+                serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+            solana_free_string(json);
+            assert_eq!(value["method"], "getMultipleAccounts");
+            value
+        };
+
+        let envelope = read_envelope(unsafe {
+            solana_get_multiple_accounts(client, pointers.as_ptr(), 250, 0, &mut error)
+        });
+        assert_eq!(envelope["ok"], true, "{}", envelope);
+        assert_eq!(envelope["result"].as_array().unwrap().len(), 250);
+
+        let envelope = read_envelope(unsafe {
+            solana_get_multiple_accounts(client, pointers.as_ptr(), 250, 1, &mut error)
+        });
+        assert_eq!(
+            envelope["result"]["accounts"].as_array().unwrap().len(),
+            250
+        );
+        assert_eq!(envelope["result"]["errors"], serde_json::json!([]));
+
+        let json =
+            unsafe { solana_get_multiple_accounts(client, pointers.as_ptr(), 250, 2, &mut error) };
+        assert!(json.is_null());
+        assert!(take_error(&mut error).contains("policy"));
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_program_accounts_paged_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        // A program nobody has deployed owns no accounts
+        let program_id =
+            CString::new(solana_sdk::pubkey::Pubkey::new_unique().to_string()).unwrap();
+        let json = unsafe {
+            solana_get_program_accounts_paged(
+                client,
+                program_id.as_ptr(),
+                ptr::null(),
+                50,
+                ptr::null(),
+                &mut error,
+            )
+        };
+        assert!(error.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        solana_free_string(json);
+        assert_eq!(value["method"], "getProgramAccounts");
+        assert_eq!(value["ok"], true, "{}", value);
+        assert_eq!(value["result"]["accounts"], serde_json::json!([]));
+        assert_eq!(value["result"]["nextCursor"], serde_json::Value::Null);
+
+        // Bad arguments come back in the envelope
+        let cursor = CString::new("not-a-cursor").unwrap();
+        let json = unsafe {
+            solana_get_program_accounts_paged(
+                client,
+                program_id.as_ptr(),
+                ptr::null(),
+                50,
+                cursor.as_ptr(),
+                &mut error,
+            )
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        solana_free_string(json);
+        assert_eq!(value["ok"], false);
+        assert!(value["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid cursor"));
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_read_account_data_into_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        // The clock sysvar is 40 bytes and ends with the unix timestamp
+        let clock = CString::new(solana_sdk::sysvar::clock::id().to_string()).unwrap();
+        let mut buf = [0u8; 64];
+        let mut written = 0usize;
+
+        // Size query
+        let ok = unsafe {
+            solana_read_account_data_into(
+                client,
+                clock.as_ptr(),
+                0,
+                ptr::null_mut(),
+                0,
+                &mut written,
+                &mut error,
+            )
+        };
+        assert_eq!(ok, 1, "{}", take_error(&mut error));
+        assert_eq!(written, 40);
+
+        // Repeated reads into the same buffer
+        for _ in 0..3 {
+            let ok = unsafe {
+                solana_read_account_data_into(
+                    client,
+                    clock.as_ptr(),
+                    0,
+                    buf.as_mut_ptr(),
+                    buf.len(),
+                    &mut written,
+                    &mut error,
+                )
+            };
+            assert_eq!(ok, 1, "{}", take_error(&mut error));
+            assert_eq!(written, 40);
+            let clock: solana_sdk::clock::Clock = bincode::deserialize(&buf[..40]).unwrap();
+            assert!(clock.unix_timestamp > 0);
+        }
+
+        let ok = unsafe {
+            solana_read_account_data_into(
+                client,
+                clock.as_ptr(),
+                32,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut written,
+                &mut error,
+            )
+        };
+        assert_eq!(ok, 1, "{}", take_error(&mut error));
+        assert_eq!(written, 8);
+        assert!(i64::from_le_bytes(buf[..8].try_into().unwrap()) > 0);
+
+        // Too small: the required size is reported and the buffer is untouched
+        let mut small = [0xAAu8; 16];
+        let ok = unsafe {
+            solana_read_account_data_into(
+                client,
+                clock.as_ptr(),
+                0,
+                small.as_mut_ptr(),
+                small.len(),
+                &mut written,
+                &mut error,
+            )
+        };
+        assert_eq!(ok, 0);
+        assert_eq!(written, 40);
+        assert!(take_error(&mut error).contains("Buffer too small: 40 bytes required"));
+        assert_eq!(small, [0xAA; 16]);
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_wait_for_confirmations_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        // Signatures nobody has sent never confirm
+        let unknown =
+            CString::new(solana_sdk::signature::Signature::new_unique().to_string()).unwrap();
+        let invalid = CString::new("not-a-signature").unwrap();
+        let signatures = [unknown.as_ptr(), invalid.as_ptr()];
+        let json = unsafe {
+            solana_wait_for_confirmations(
+                client,
+                signatures.as_ptr(),
+                signatures.len(),
+                commitment.as_ptr(),
+                1000,
+                &mut error,
+            )
+        };
+        assert!(error.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        solana_free_string(json);
+        assert_eq!(value["method"], "getSignatureStatuses");
+        assert_eq!(value["ok"], true, "{}", value);
+        let outcomes = &value["result"];
+        assert_eq!(
+            outcomes[unknown.to_str().unwrap()]["status"],
+            "timedOut",
+            "{}",
+            outcomes
+        );
+        assert_eq!(outcomes["not-a-signature"]["status"], "failed");
+
+        let json = unsafe {
+            solana_wait_for_confirmations(
+                client,
+                ptr::null(),
+                1,
+                commitment.as_ptr(),
+                1000,
+                &mut error,
+            )
+        };
+        assert!(json.is_null());
+        assert!(take_error(&mut error).contains("Null pointer"));
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_account_cache_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        let pubkey = CString::new("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr").unwrap();
+        let invalid = CString::new("not-a-pubkey").unwrap();
+
+        assert_eq!(
+            unsafe { solana_enable_account_cache(client, 5000, 64, &mut error) },
+            1
+        );
+        assert_eq!(
+            unsafe { solana_invalidate_account(client, pubkey.as_ptr(), &mut error) },
+            1
+        );
+        assert_eq!(
+            unsafe { solana_invalidate_all_accounts(client, &mut error) },
+            1
+        );
+        assert_eq!(
+            unsafe { solana_disable_account_cache(client, &mut error) },
+            1
+        );
+        assert!(error.is_null());
+
+        assert_eq!(
+            unsafe { solana_enable_account_cache(client, 0, 64, &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("ttl_ms"));
+        assert_eq!(
+            unsafe { solana_invalidate_account(client, invalid.as_ptr(), &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("Invalid pubkey"));
+
+        solana_destroy_rpc_client(client);
+        assert_eq!(
+            unsafe { solana_invalidate_all_accounts(client, &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("destroyed"));
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_metrics_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        let pubkey = CString::new("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr").unwrap();
+        let read_metrics = |client: *mut SolanaRpcClient| {
+            let mut error: *mut c_char = ptr::null_mut();
+            let json = unsafe { solana_get_metrics_json(client, &mut error) };
+            assert!(error.is_null());
+            let value: serde_json::Value =
+                serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+            solana_free_string(json);
+            value
+        };
+
+        assert_eq!(unsafe { solana_enable_metrics(client, &mut error) }, 1);
+        // Nothing listens on the port, so the call fails
+        solana_get_balance(client, pubkey.as_ptr(), &mut error);
+        assert!(!take_error(&mut error).is_empty());
+        let metrics = read_metrics(client);
+        assert_eq!(metrics["enabled"], true);
+        assert_eq!(metrics["methods"]["getBalance"]["calls"], 1);
+        assert_eq!(
+            metrics["methods"]["getBalance"]["failures"]
+                .as_object()
+                .unwrap()
+                .values()
+                .map(|count| count.as_u64().unwrap())
+                .sum::<u64>(),
+            1
+        );
+
+        assert_eq!(solana_reset_metrics(client), 1);
+        assert_eq!(unsafe { solana_disable_metrics(client, &mut error) }, 1);
+        let metrics = read_metrics(client);
+        assert_eq!(
+            metrics,
+            serde_json::json!({"enabled": false, "methods": {}})
+        );
+
+        solana_destroy_rpc_client(client);
+        assert_eq!(solana_reset_metrics(client), 0);
+        assert!(unsafe { solana_get_metrics_json(client, &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("destroyed"));
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_balances_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+
+        let token_program = CString::new("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        let missing = CString::new(solana_sdk::pubkey::Pubkey::new_unique().to_string()).unwrap();
+        let invalid = CString::new("not-a-pubkey").unwrap();
+
+        let pubkeys = [token_program.as_ptr(), missing.as_ptr()];
+        let mut lamports = [u64::MAX; 2];
+        let mut exists = [-1 as c_int; 2];
+        let ok = unsafe {
+            solana_get_balances(
+                client,
+                pubkeys.as_ptr(),
+                pubkeys.len(),
+                lamports.as_mut_ptr(),
+                exists.as_mut_ptr(),
+                &mut error,
+            )
+        };
+        assert_eq!(ok, 1, "{}", take_error(&mut error));
+        assert_eq!(exists, [1, 0]);
+        assert!(lamports[0] > 0);
+        assert_eq!(lamports[1], 0);
+
+        let pubkeys = [token_program.as_ptr(), missing.as_ptr(), invalid.as_ptr()];
+        let mut lamports = [u64::MAX; 3];
+        let mut exists = [-1 as c_int; 3];
+        let ok = unsafe {
+            solana_get_balances(
+                client,
+                pubkeys.as_ptr(),
+                pubkeys.len(),
+                lamports.as_mut_ptr(),
+                exists.as_mut_ptr(),
+                &mut error,
+            )
+        };
+        assert_eq!(ok, 0);
+        assert!(take_error(&mut error).contains("index 2"));
+        assert_eq!(lamports, [u64::MAX; 3]);
+        assert_eq!(exists, [-1; 3]);
+
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_probe_endpoints_ffi() {
+        let _runtime = runtime::test_runtime();
+        let dead = CString::new("http://127.0.0.1:1").unwrap();
+        let devnet = CString::new("https://api.devnet.solana.com").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let urls = [dead.as_ptr(), devnet.as_ptr()];
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let json = unsafe { solana_probe_endpoints(urls.as_ptr(), urls.len(), 10_000, &mut error) };
+        assert!(error.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        solana_free_string(json);
+        assert_eq!(value["method"], "getHealth");
+        assert_eq!(value["ok"], true, "{}", value);
+        let ranking = value["result"].as_array().unwrap();
+        assert_eq!(ranking.len(), 2);
+        assert_eq!(ranking[0]["index"], 1);
+        assert_eq!(ranking[0]["ok"], true, "{}", value);
+        assert_eq!(ranking[1]["index"], 0);
+        assert!(ranking[1]["error"]["message"].is_string());
+
+        let client = unsafe {
+            solana_create_rpc_client_fastest(
+                urls.as_ptr(),
+                urls.len(),
+                commitment.as_ptr(),
+                10_000,
+                &mut error,
+            )
+        };
+        assert!(!client.is_null(), "{}", take_error(&mut error));
+        let blockhash = solana_get_latest_blockhash(client, &mut error);
+        assert!(!blockhash.is_null());
+        solana_free_string(blockhash);
+        solana_destroy_rpc_client(client);
+
+        let client = unsafe {
+            solana_create_rpc_client_fastest(
+                urls.as_ptr(),
+                1,
+                commitment.as_ptr(),
+                10_000,
+                &mut error,
+            )
+        };
+        assert!(client.is_null());
+        assert!(take_error(&mut error).contains("No usable endpoint"));
+    }
+
+    #[test]
+    fn test_decode_account_with_schema_ffi() {
+        #[derive(borsh::BorshSerialize)]
+        struct Player {
+            level: u16,
+            name: String,
+        }
+
+        let mut data = vec![0; 8];
+        data.extend(
+            borsh::to_vec(&Player {
+                level: 7,
+                name: "ada".to_string(),
+            })
+            .unwrap(),
+        );
+        let schema = CString::new(
+            r#"{"offset": 8, "fields": [{"name": "level", "type": "u16"}, {"name": "name", "type": "string"}]}"#,
+        )
+        .unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let json = unsafe {
+            solana_decode_account_with_schema(
+                data.as_ptr(),
+                data.len(),
+                schema.as_ptr(),
+                &mut error,
+            )
+        };
+        assert!(error.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        solana_free_string(json);
+        assert_eq!(value, serde_json::json!({"level": 7, "name": "ada"}));
+
+        let json = unsafe {
+            solana_decode_account_with_schema(data.as_ptr(), 12, schema.as_ptr(), &mut error)
+        };
+        assert!(json.is_null());
+        assert_eq!(
+            take_error(&mut error),
+            "Serialization error: Field name at byte 10: needs 4 bytes, 2 left"
+        );
+
+        let json = unsafe {
+            solana_decode_account_with_schema(ptr::null(), 4, schema.as_ptr(), &mut error)
+        };
+        assert!(json.is_null());
+        assert!(take_error(&mut error).contains("Null data pointer with a length of 4"));
+    }
+
+    #[test]
+    fn test_token_amount_ffi() {
+        let formatted = solana_format_token_amount(1_500_000, 6, 0);
+        assert_eq!(
+            unsafe { CStr::from_ptr(formatted) }.to_str().unwrap(),
+            "1.500000"
+        );
+        solana_free_string(formatted);
+        let formatted = solana_format_token_amount(1_500_000, 6, 1);
+        assert_eq!(
+            unsafe { CStr::from_ptr(formatted) }.to_str().unwrap(),
+            "1.5"
+        );
+        solana_free_string(formatted);
+
+        let mut error: *mut c_char = ptr::null_mut();
+        let mut raw = 0u64;
+        let amount = CString::new("18446744073.709551615").unwrap();
+        assert_eq!(
+            unsafe { solana_parse_token_amount(amount.as_ptr(), 9, &mut raw, &mut error) },
+            1
+        );
+        assert!(error.is_null());
+        assert_eq!(raw, u64::MAX);
+
+        let amount = CString::new("1.000000001").unwrap();
+        assert_eq!(
+            unsafe { solana_parse_token_amount(amount.as_ptr(), 6, &mut raw, &mut error) },
+            0
+        );
+        assert_eq!(
+            take_error(&mut error),
+            "Invalid input: Amount 1.000000001 has more than 6 decimal places"
+        );
+        // Left alone on failure
+        assert_eq!(raw, u64::MAX);
+
+        assert_eq!(
+            unsafe { solana_parse_token_amount(amount.as_ptr(), 6, ptr::null_mut(), &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("Null pointer"));
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_solana_pay_ffi_rejects_transfer_urls() {
+        let _runtime = runtime::test_runtime();
+        let mut error: *mut c_char = ptr::null_mut();
+        let url =
+            CString::new("solana:mvines9iiHiQTysrwkJjGf2gb9Ex9jXJX8ns3qwf2kN?amount=1").unwrap();
+        assert!(unsafe { solana_pay_fetch_metadata(url.as_ptr(), &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("Not a Solana Pay transaction request"));
+
+        let account = CString::new(Account::generate().get_pubkey().unwrap()).unwrap();
+        assert!(unsafe {
+            solana_pay_request_transaction(url.as_ptr(), account.as_ptr(), &mut error)
+        }
+        .is_null());
+        assert!(!take_error(&mut error).is_empty());
+
+        assert!(
+            unsafe { solana_pay_request_transaction(url.as_ptr(), ptr::null(), &mut error) }
+                .is_null()
+        );
+        assert!(take_error(&mut error).contains("Null pointer"));
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_nfts_by_owner_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+
+        assert!(
+            unsafe { solana_get_nfts_by_owner(client, ptr::null(), 1, 0, &mut error) }.is_null()
+        );
+        assert!(take_error(&mut error).contains("Null pointer"));
+
+        // Rejected before anything is sent, as an envelope
+        let owner = CString::new("not-an-owner").unwrap();
+        let envelope =
+            unsafe { solana_get_nfts_by_owner(client, owner.as_ptr(), 1, 0, &mut error) };
+        assert!(error.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(envelope) }.to_str().unwrap()).unwrap();
+        solana_free_string(envelope);
+        assert_eq!(value["method"], "getTokenAccountsByOwner");
+        assert_eq!(value["error"]["kind"], "InvalidInput");
+        solana_destroy_rpc_client(client);
+    }
+
+    // The next event of a subscription handle as JSON, waiting for it to arrive
+    #[cfg(feature = "websocket")]
+    fn next_subscription_event(subscription: *const SolanaSubscription) -> serde_json::Value {
+        let deadline = std::time::Instant::now() + crate::subscription::fake::WAIT;
+        loop {
+            let mut error = ptr::null_mut();
+            let event = unsafe { solana_subscription_poll_next(subscription, &mut error) };
+            assert!(error.is_null());
+            if !event.is_null() {
+                let json = unsafe { CStr::from_ptr(event) }
+                    .to_str()
+                    .unwrap()
+                    .to_string();
+                solana_free_string(event);
+                return serde_json::from_str(&json).unwrap();
+            }
+            assert!(std::time::Instant::now() < deadline, "no event");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn test_subscription_ffi() {
+        use crate::subscription::fake::{self, WAIT};
+        use crate::subscription::{ConnectionState, QueuePolicy, SubscriptionClient};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::{mpsc, Arc};
+
+        let (nodes, node_receiver) = mpsc::channel();
+        let accept = Arc::new(AtomicBool::new(true));
+        let connector = fake::connector(nodes, Arc::clone(&accept));
+        let client = SubscriptionClient::start(connector, fake::fast_policy(1000)).unwrap();
+        let node = node_receiver.recv_timeout(WAIT).unwrap();
+        let deadline = std::time::Instant::now() + WAIT;
+        while client.state() != ConnectionState::Connected {
+            assert!(std::time::Instant::now() < deadline, "never connected");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let subscription = client
+            .open("accountSubscribe", json!(["Acc1"]), QueuePolicy::default())
+            .unwrap();
+        let id = subscription.id();
+        let subscription = into_handle(subscription);
+        assert_eq!(solana_subscription_state(subscription), 1);
+
+        // Notifications come out in the order the node sent them, then null once drained
+        let request = node.request();
+        node.respond(&request, json!(100));
+        for lamports in 1..=3 {
+            node.notify("accountNotification", 100, json!({"lamports": lamports}));
+        }
+        for lamports in 1..=3 {
+            assert_eq!(
+                next_subscription_event(subscription),
+                json!({
+                    "type": "notification",
+                    "id": id,
+                    "method": "accountNotification",
+                    "result": {"lamports": lamports},
+                })
+            );
+        }
+        let mut error = ptr::null_mut();
+        assert!(unsafe { solana_subscription_poll_next(subscription, &mut error) }.is_null());
+        assert!(error.is_null());
+        assert_eq!(solana_subscription_pending_count(subscription), 0);
+
+        // The connection drops and stays down for a while, then comes back
+        accept.store(false, Ordering::SeqCst);
+        node.disconnect();
+        assert_eq!(
+            next_subscription_event(subscription),
+            json!({"type": "connectionState", "state": "reconnecting", "attempt": 1})
+        );
+        assert_eq!(solana_subscription_state(subscription), 2);
+        accept.store(true, Ordering::SeqCst);
+        let node = node_receiver.recv_timeout(WAIT).unwrap();
+        loop {
+            let event = next_subscription_event(subscription);
+            assert_eq!(event["type"], "connectionState");
+            if event["state"] == "connected" {
+                break;
+            }
+            assert_eq!(event["state"], "reconnecting");
+        }
+        assert_eq!(solana_subscription_state(subscription), 1);
+
+        // Destroyed with events still queued: they are discarded and the node is told
+        let request = node.request();
+        assert_eq!(request["method"], "accountSubscribe");
+        node.respond(&request, json!(200));
+        node.notify("accountNotification", 200, json!({"lamports": 4}));
+        node.notify("accountNotification", 200, json!({"lamports": 5}));
+        let deadline = std::time::Instant::now() + WAIT;
+        while solana_subscription_pending_count(subscription) < 2 {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "notifications never queued"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        solana_subscription_destroy(subscription);
+        let request = node.request();
+        assert_eq!(request["method"], "accountUnsubscribe");
+        assert_eq!(request["params"], json!([200]));
+        assert!(client.subscriptions().is_empty());
+
+        assert_eq!(solana_subscription_state(subscription), -1);
+        assert_eq!(solana_subscription_pending_count(subscription), 0);
+        assert!(unsafe { solana_subscription_poll_next(subscription, &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("destroyed"));
+        solana_subscription_destroy(subscription);
+
+        // Closing the client closes its subscriptions
+        let slots = into_handle(
+            client
+                .open("slotSubscribe", json!([]), QueuePolicy::default())
+                .unwrap(),
+        );
+        drop(client);
+        assert_eq!(solana_subscription_state(slots), 0);
+        assert_eq!(
+            next_subscription_event(slots),
+            json!({"type": "connectionState", "state": "closed", "attempt": 0})
+        );
+        solana_subscription_destroy(slots);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_stake_account_state_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+
+        assert!(
+            unsafe { solana_get_stake_account_state(client, ptr::null(), &mut error) }.is_null()
+        );
+        assert!(take_error(&mut error).contains("Null pointer"));
+
+        let stake_account = CString::new("not-a-stake-account").unwrap();
+        let envelope =
+            unsafe { solana_get_stake_account_state(client, stake_account.as_ptr(), &mut error) };
+        assert!(error.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(envelope) }.to_str().unwrap()).unwrap();
+        solana_free_string(envelope);
+        assert_eq!(value["method"], "getAccountInfo");
+        assert_eq!(value["error"]["kind"], "InvalidInput");
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_get_nonce_account_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+
+        let nonce_pubkey = CString::new("not-a-nonce-account").unwrap();
+        let envelope =
+            unsafe { solana_get_nonce_account(client, nonce_pubkey.as_ptr(), &mut error) };
+        assert!(error.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(envelope) }.to_str().unwrap()).unwrap();
+        solana_free_string(envelope);
+        assert_eq!(value["method"], "getAccountInfo");
+        assert_eq!(value["error"]["kind"], "InvalidInput");
+
+        assert!(
+            unsafe { solana_get_nonce_value(client, nonce_pubkey.as_ptr(), &mut error) }.is_null()
+        );
+        assert!(take_error(&mut error).contains("Invalid pubkey"));
+        assert!(unsafe { solana_get_nonce_value(client, ptr::null(), &mut error) }.is_null());
+        assert!(take_error(&mut error).contains("Null pointer"));
+        solana_destroy_rpc_client(client);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_reads_at_slot_ffi() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+
+        // Inputs are checked before any slot is asked for
+        let invalid = CString::new("not-a-pubkey").unwrap();
+        let mut lamports = 42;
+        assert_eq!(
+            unsafe {
+                solana_get_balance_at_slot(client, invalid.as_ptr(), 100, &mut lamports, &mut error)
+            },
+            0
+        );
+        assert_eq!(lamports, 42);
+        assert!(take_error(&mut error).contains("Invalid pubkey"));
+        assert_eq!(
+            unsafe {
+                solana_get_token_account_balance_at_slot(
+                    client,
+                    invalid.as_ptr(),
+                    0,
+                    &mut lamports,
+                    &mut error,
+                )
+            },
+            0
+        );
+        assert!(take_error(&mut error).contains("Invalid pubkey"));
+
+        let envelope =
+            unsafe { solana_get_account_info_at_slot(client, invalid.as_ptr(), 100, &mut error) };
+        assert!(error.is_null());
+        let value: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(envelope) }.to_str().unwrap()).unwrap();
+        solana_free_string(envelope);
+        assert_eq!(value["method"], "getAccountInfo");
+        assert_eq!(value["error"]["kind"], "InvalidInput");
+
+        let buffer =
+            unsafe { solana_get_account_data_at_slot(client, invalid.as_ptr(), 100, &mut error) };
+        assert!(buffer.data.is_null());
+        assert!(take_error(&mut error).contains("Invalid pubkey"));
+
+        let mut slot = 7;
+        let signature = unsafe {
+            solana_send_and_confirm_transaction(client, ptr::null(), 1000, &mut slot, &mut error)
+        };
+        assert!(signature.is_null());
+        assert_eq!(slot, 7);
+        assert!(!take_error(&mut error).is_empty());
+        solana_destroy_rpc_client(client);
+
+        assert_eq!(optional_slot(0), None);
+        assert_eq!(optional_slot(250_000_000), Some(250_000_000));
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_create_rpc_client_with_proxy() {
+        let _runtime = runtime::test_runtime();
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let proxy = CString::new("socks5h://127.0.0.1:1080").unwrap();
+        let username = CString::new("player").unwrap();
+        let password = CString::new("hunter2").unwrap();
+        let mut error: *mut c_char = ptr::null_mut();
+
+        let client = unsafe {
+            solana_create_rpc_client_with_proxy(
+                url.as_ptr(),
+                commitment.as_ptr(),
+                proxy.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+                &mut error,
+            )
+        };
+        assert!(!client.is_null());
+        solana_destroy_rpc_client(client);
+
+        let client = unsafe {
+            solana_create_rpc_client_with_proxy(
+                url.as_ptr(),
+                commitment.as_ptr(),
+                proxy.as_ptr(),
+                username.as_ptr(),
+                password.as_ptr(),
+                &mut error,
+            )
+        };
+        assert!(!client.is_null());
+        assert!(error.is_null());
+        solana_destroy_rpc_client(client);
+
+        let invalid = CString::new("proxy.corp:3128").unwrap();
+        let client = unsafe {
+            solana_create_rpc_client_with_proxy(
+                url.as_ptr(),
+                commitment.as_ptr(),
+                invalid.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+                &mut error,
+            )
+        };
+        assert!(client.is_null());
+        assert!(take_error(&mut error).contains("proxy scheme"));
+
+        let client = unsafe {
+            solana_create_rpc_client_with_proxy(
+                url.as_ptr(),
+                commitment.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+                ptr::null(),
+                &mut error,
+            )
+        };
+        assert!(client.is_null());
+        assert!(take_error(&mut error).contains("Null pointer"));
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_balance_watcher_ffi() {
+        let _runtime = runtime::test_runtime();
+        let mut error = ptr::null_mut();
+        let watcher = solana_balance_watcher_create();
+        let wallet = CString::new("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").unwrap();
+
+        let invalid = CString::new("not a pubkey").unwrap();
+        assert_eq!(
+            unsafe { solana_balance_watcher_add(watcher, invalid.as_ptr(), 0, &mut error) },
+            0
+        );
+        assert!(!take_error(&mut error).is_empty());
+        assert_eq!(
+            unsafe { solana_balance_watcher_add(watcher, wallet.as_ptr(), 2, &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("Invalid balance kind"));
+
+        assert_eq!(
+            unsafe { solana_balance_watcher_add(watcher, wallet.as_ptr(), 0, &mut error) },
+            1
+        );
+        assert_eq!(
+            unsafe { solana_balance_watcher_remove(watcher, wallet.as_ptr(), &mut error) },
+            1
+        );
+        assert_eq!(
+            unsafe { solana_balance_watcher_remove(watcher, wallet.as_ptr(), &mut error) },
+            0
+        );
+        assert!(error.is_null());
+        assert_eq!(
+            unsafe { solana_balance_watcher_add(watcher, wallet.as_ptr(), 1, &mut error) },
+            1
+        );
+
+        // Nothing listens on the port, so every read fails and no event is queued
+        let url = CString::new("http://127.0.0.1:1").unwrap();
+        let commitment = CString::new("confirmed").unwrap();
+        let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+        assert!(!client.is_null());
+        assert_eq!(
+            unsafe { solana_balance_watcher_start(watcher, client, 0, &mut error) },
+            0
+        );
+        assert!(take_error(&mut error).contains("at least 1 ms"));
+        assert_eq!(
+            unsafe { solana_balance_watcher_start(watcher, client, 10, &mut error) },
+            1
+        );
+        // The watcher holds its own client
+        solana_destroy_rpc_client(client);
+        assert_eq!(
+            unsafe { solana_balance_watcher_start(watcher, ptr::null(), 10, &mut error) },
+            0
+        );
+        assert!(!take_error(&mut error).is_empty());
+        assert!(unsafe { solana_balance_watcher_poll_event(watcher, &mut error) }.is_null());
+        assert!(error.is_null());
+
+        assert_eq!(solana_balance_watcher_stop(watcher), 1);
+        solana_balance_watcher_destroy(watcher);
+        assert_eq!(solana_balance_watcher_stop(watcher), 0);
+        assert!(unsafe { solana_balance_watcher_poll_event(watcher, &mut error) }.is_null());
+        assert!(!take_error(&mut error).is_empty());
     }
 }
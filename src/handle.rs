@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread::{self, ThreadId};
+
+use crate::account::Account;
+use crate::address_book::AddressBook;
+#[cfg(feature = "rpc")]
+use crate::balance_watcher::BalanceWatcher;
+use crate::deeplink::DeeplinkSession;
+use crate::error::{ErrorObject, SolanaUnityError};
+#[cfg(feature = "rpc")]
+use crate::rpc::RpcClient;
+use crate::session::SessionKey;
+#[cfg(feature = "websocket")]
+use crate::subscription::Subscription;
+use crate::transaction::Transaction;
+use crate::vanity::GrindHandle;
+use crate::wallet_manager::WalletManager;
+
+/// Written at the start of every live handle
+const HANDLE_MAGIC: u32 = 0x534f_4c55;
+
+/// Types that are handed across the FFI boundary as opaque handles
+pub trait HandleType {
+    const TYPE_ID: u32;
+    const NAME: &'static str;
+}
+
+// Type ids stay fixed whichever features are enabled, so a handle from one build is never
+// mistaken for another type by a build with different features
+#[cfg(feature = "rpc")]
+impl HandleType for RpcClient {
+    const TYPE_ID: u32 = 1;
+    const NAME: &'static str = "RpcClient";
+}
+
+impl HandleType for Transaction {
+    const TYPE_ID: u32 = 2;
+    const NAME: &'static str = "Transaction";
+}
+
+impl HandleType for Account {
+    const TYPE_ID: u32 = 3;
+    const NAME: &'static str = "Account";
+}
+
+impl HandleType for GrindHandle {
+    const TYPE_ID: u32 = 4;
+    const NAME: &'static str = "GrindHandle";
+}
+
+impl HandleType for WalletManager {
+    const TYPE_ID: u32 = 5;
+    const NAME: &'static str = "WalletManager";
+}
+
+impl HandleType for DeeplinkSession {
+    const TYPE_ID: u32 = 6;
+    const NAME: &'static str = "DeeplinkSession";
+}
+
+impl HandleType for ErrorObject {
+    const TYPE_ID: u32 = 7;
+    const NAME: &'static str = "ErrorObject";
+}
+
+#[cfg(feature = "websocket")]
+impl HandleType for Subscription {
+    const TYPE_ID: u32 = 8;
+    const NAME: &'static str = "Subscription";
+}
+
+#[cfg(feature = "rpc")]
+impl HandleType for BalanceWatcher {
+    const TYPE_ID: u32 = 9;
+    const NAME: &'static str = "BalanceWatcher";
+}
+
+impl HandleType for AddressBook {
+    const TYPE_ID: u32 = 10;
+    const NAME: &'static str = "AddressBook";
+}
+
+impl HandleType for SessionKey {
+    const TYPE_ID: u32 = 11;
+    const NAME: &'static str = "SessionKey";
+}
+
+/// A value boxed for the FFI boundary, tagged with its type so a pointer of the wrong kind
+/// is rejected instead of being reinterpreted. The value sits behind a read-write lock so
+/// one handle can be used from several threads.
+#[repr(C)]
+pub struct Handle<T> {
+    magic: u32,
+    type_id: u32,
+    inner: RwLock<T>,
+}
+
+struct Entry {
+    type_id: u32,
+    // The threads of the calls currently holding a guard on the handle, once per call
+    users: Vec<ThreadId>,
+    destroying: bool,
+}
+
+// Every handle that has been created and not yet destroyed, keyed by address. Checking here
+// first means a destroyed handle is rejected without reading freed memory, and the users
+// keep a handle alive until calls that are already running have finished. Once the
+// allocator hands the address to a new handle of the same type, a stale pointer to the old
+// one looks live again; only never using a handle after destroying it avoids that.
+static LIVE_HANDLES: Mutex<BTreeMap<usize, Entry>> = Mutex::new(BTreeMap::new());
+static HANDLE_RELEASED: Condvar = Condvar::new();
+
+fn live_handles() -> MutexGuard<'static, BTreeMap<usize, Entry>> {
+    LIVE_HANDLES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Boxes `value` as a tagged handle and registers it
+pub fn into_handle<T: HandleType>(value: T) -> *mut Handle<T> {
+    let ptr = Box::into_raw(Box::new(Handle {
+        magic: HANDLE_MAGIC,
+        type_id: T::TYPE_ID,
+        inner: RwLock::new(value),
+    }));
+    live_handles().insert(
+        ptr as usize,
+        Entry {
+            type_id: T::TYPE_ID,
+            users: Vec::new(),
+            destroying: false,
+        },
+    );
+    ptr
+}
+
+fn check_handle<T: HandleType>(
+    handles: &BTreeMap<usize, Entry>,
+    ptr: *const Handle<T>,
+) -> Result<(), SolanaUnityError> {
+    if ptr.is_null() {
+        return Err(SolanaUnityError::FfiError(format!(
+            "Null {} handle",
+            T::NAME
+        )));
+    }
+
+    match handles.get(&(ptr as usize)) {
+        Some(entry) if entry.destroying => {
+            return Err(SolanaUnityError::FfiError(format!(
+                "Invalid or destroyed {} handle",
+                T::NAME
+            )))
+        }
+        Some(entry) if entry.type_id == T::TYPE_ID => {}
+        Some(_) => {
+            return Err(SolanaUnityError::FfiError(format!(
+                "Invalid handle: expected a {} handle",
+                T::NAME
+            )))
+        }
+        None => {
+            return Err(SolanaUnityError::FfiError(format!(
+                "Invalid or destroyed {} handle",
+                T::NAME
+            )))
+        }
+    }
+
+    // Registered handles are live, so the tag can be read safely
+    let (magic, type_id) = unsafe { ((*ptr).magic, (*ptr).type_id) };
+    if magic != HANDLE_MAGIC || type_id != T::TYPE_ID {
+        return Err(SolanaUnityError::FfiError(format!(
+            "Corrupted {} handle",
+            T::NAME
+        )));
+    }
+
+    Ok(())
+}
+
+// Keeps a handle registered and its memory alive while a call is using it
+struct Pin {
+    address: usize,
+}
+
+impl Pin {
+    fn new<T: HandleType>(ptr: *const Handle<T>) -> Result<Self, SolanaUnityError> {
+        let mut handles = live_handles();
+        check_handle(&handles, ptr)?;
+        if let Some(entry) = handles.get_mut(&(ptr as usize)) {
+            entry.users.push(thread::current().id());
+        }
+        Ok(Self {
+            address: ptr as usize,
+        })
+    }
+}
+
+impl Drop for Pin {
+    fn drop(&mut self) {
+        if let Some(entry) = live_handles().get_mut(&self.address) {
+            let current = thread::current().id();
+            if let Some(index) = entry.users.iter().position(|user| *user == current) {
+                entry.users.swap_remove(index);
+            }
+        }
+        HANDLE_RELEASED.notify_all();
+    }
+}
+
+fn poisoned<T: HandleType>() -> SolanaUnityError {
+    SolanaUnityError::FfiError(format!(
+        "{} handle is unusable, a previous call panicked while using it",
+        T::NAME
+    ))
+}
+
+/// Shared access to the value behind a handle. Other readers may run at the same time.
+pub struct HandleRef<'a, T> {
+    // Declared first so the lock is released before the handle is unpinned
+    guard: RwLockReadGuard<'a, T>,
+    _pin: Pin,
+}
+
+impl<T> Deref for HandleRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+/// Exclusive access to the value behind a handle
+pub struct HandleMut<'a, T> {
+    guard: RwLockWriteGuard<'a, T>,
+    _pin: Pin,
+}
+
+impl<T> Deref for HandleMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for HandleMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+/// Checks that `ptr` is a live handle of type `T` and takes a read lock on it. Blocks
+/// while another thread holds the handle through `deref_handle`.
+// Safe to call with any pointer: one that is not a live handle in the registry is
+// rejected before it is dereferenced. The same goes for the two functions below.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn handle_ref<'a, T: HandleType>(
+    ptr: *const Handle<T>,
+) -> Result<HandleRef<'a, T>, SolanaUnityError> {
+    let pin = Pin::new(ptr)?;
+    // The pin keeps the allocation alive for as long as the guard exists
+    let lock = unsafe { &(*ptr).inner };
+    let guard = lock.read().map_err(|_| poisoned::<T>())?;
+    Ok(HandleRef { guard, _pin: pin })
+}
+
+/// Checks that `ptr` is a live handle of type `T` and takes the write lock on it. Blocks
+/// while any other thread is using the handle.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn deref_handle<'a, T: HandleType>(
+    ptr: *mut Handle<T>,
+) -> Result<HandleMut<'a, T>, SolanaUnityError> {
+    let pin = Pin::new(ptr)?;
+    let lock = unsafe { &(*ptr).inner };
+    let guard = lock.write().map_err(|_| poisoned::<T>())?;
+    Ok(HandleMut { guard, _pin: pin })
+}
+
+/// Unregisters a handle and returns the value it held. New calls on the handle are
+/// rejected straight away; this blocks until calls already using it return. Destroying a
+/// handle twice, destroying a handle of the wrong type, or destroying a handle from a
+/// thread that is still using it fails instead of freeing memory or waiting forever.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn destroy_handle<T: HandleType>(ptr: *mut Handle<T>) -> Result<T, SolanaUnityError> {
+    let mut handles = live_handles();
+    check_handle(&handles, ptr)?;
+    let address = ptr as usize;
+    if let Some(entry) = handles.get_mut(&address) {
+        // The wait below would never end, as the guard is only dropped once this returns
+        if entry.users.contains(&thread::current().id()) {
+            return Err(SolanaUnityError::FfiError(format!(
+                "Cannot destroy a {} handle while the calling thread is using it",
+                T::NAME
+            )));
+        }
+        entry.destroying = true;
+    }
+
+    while handles
+        .get(&address)
+        .is_some_and(|entry| !entry.users.is_empty())
+    {
+        handles = HANDLE_RELEASED
+            .wait(handles)
+            .unwrap_or_else(|e| e.into_inner());
+    }
+    handles.remove(&address);
+    drop(handles);
+
+    let handle = unsafe { Box::from_raw(ptr) };
+    Ok(handle.inner.into_inner().unwrap_or_else(|e| e.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn test_handle_round_trip() {
+        let ptr = into_handle(Transaction::new());
+        assert!(deref_handle(ptr).is_ok());
+        assert!(handle_ref(ptr as *const Handle<Transaction>).is_ok());
+        assert!(destroy_handle(ptr).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_handle_type_rejected() {
+        let account = into_handle(Account::generate());
+        let as_transaction = account as *mut Handle<Transaction>;
+
+        match deref_handle(as_transaction) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Ffi,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("Transaction")),
+            _ => panic!("Expected FfiError for a mismatched handle"),
+        }
+        assert!(destroy_handle(as_transaction).is_err());
+
+        // The account handle is untouched
+        assert!(deref_handle(account).is_ok());
+        destroy_handle(account).unwrap();
+    }
+
+    #[test]
+    fn test_destroyed_handle_rejected() {
+        let ptr = into_handle(Transaction::new());
+        destroy_handle(ptr).unwrap();
+
+        match deref_handle(ptr) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Ffi,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("destroyed")),
+            _ => panic!("Expected FfiError for a destroyed handle"),
+        }
+        assert!(destroy_handle(ptr).is_err());
+    }
+
+    #[test]
+    fn test_null_and_foreign_pointers_rejected() {
+        assert!(deref_handle::<Account>(std::ptr::null_mut()).is_err());
+
+        let mut not_a_handle = 0u64;
+        let foreign = &mut not_a_handle as *mut u64 as *mut Handle<Account>;
+        assert!(deref_handle(foreign).is_err());
+        assert!(destroy_handle(foreign).is_err());
+    }
+
+    #[test]
+    fn test_poisoned_handle_reports_error() {
+        let ptr = into_handle(Transaction::new());
+        let address = ptr as usize;
+
+        let result = std::thread::spawn(move || {
+            let _guard = deref_handle(address as *mut Handle<Transaction>).unwrap();
+            panic!("simulated panic while holding the handle");
+        })
+        .join();
+        assert!(result.is_err());
+
+        match deref_handle(ptr) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Ffi,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("unusable")),
+            _ => panic!("Expected FfiError for a poisoned handle"),
+        }
+        assert!(destroy_handle(ptr).is_ok());
+    }
+
+    #[test]
+    fn test_destroy_waits_for_running_calls() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let ptr = into_handle(Transaction::new());
+        let address = ptr as usize;
+        let destroyed = Arc::new(AtomicBool::new(false));
+
+        let guard = handle_ref(ptr).unwrap();
+        let destroyer = {
+            let destroyed = Arc::clone(&destroyed);
+            std::thread::spawn(move || {
+                destroy_handle(address as *mut Handle<Transaction>).unwrap();
+                destroyed.store(true, Ordering::SeqCst);
+            })
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(!destroyed.load(Ordering::SeqCst));
+        // New calls are already turned away while the destroy is pending
+        assert!(handle_ref(ptr).is_err());
+
+        drop(guard);
+        destroyer.join().unwrap();
+        assert!(destroyed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_destroy_while_using_handle_fails() {
+        let ptr = into_handle(Transaction::new());
+
+        let guard = handle_ref(ptr).unwrap();
+        match destroy_handle(ptr) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Ffi,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("calling thread")),
+            _ => panic!("Expected FfiError when destroying a handle in use"),
+        }
+
+        // The handle is still usable, and can be destroyed once the guard is gone
+        assert!(handle_ref(ptr).is_ok());
+        drop(guard);
+        assert!(destroy_handle(ptr).is_ok());
+    }
+}
@@ -71,6 +71,9 @@ impl TokenInstructions {
     // SPL Token Program ID
     pub const TOKEN_PROGRAM_ID: &'static str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
 
+    // SPL Token-2022 Program ID
+    pub const TOKEN_2022_PROGRAM_ID: &'static str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
     // Associated Token Program ID
     pub const ASSOCIATED_TOKEN_PROGRAM_ID: &'static str =
         "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
@@ -82,6 +85,7 @@ impl TokenInstructions {
     const TOKEN_MINT_TO_INDEX: u8 = 7;
     const TOKEN_BURN_INDEX: u8 = 8;
     const TOKEN_CLOSE_ACCOUNT_INDEX: u8 = 9;
+    const TOKEN_APPROVE_CHECKED_INDEX: u8 = 13;
 
     // Build a token transfer instruction
     pub fn transfer(
@@ -125,6 +129,31 @@ impl TokenInstructions {
         builder.build()
     }
 
+    // Build a token approve instruction that also checks the mint and its decimals
+    pub fn approve_checked(
+        source: &str,
+        mint: &str,
+        delegate: &str,
+        owner: &str,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<Instruction, SolanaUnityError> {
+        let mut data = Vec::with_capacity(10);
+        data.push(Self::TOKEN_APPROVE_CHECKED_INDEX);
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(decimals);
+
+        let mut builder = InstructionBuilder::new(Self::TOKEN_PROGRAM_ID);
+        builder
+            .add_account(source, false, true)
+            .add_account(mint, false, false)
+            .add_account(delegate, false, false)
+            .add_account(owner, true, false)
+            .set_data(data);
+
+        builder.build()
+    }
+
     // Build a token revoke instruction
     pub fn revoke(source: &str, owner: &str) -> Result<Instruction, SolanaUnityError> {
         let mut data = Vec::with_capacity(1);
@@ -201,6 +230,37 @@ impl TokenInstructions {
     }
 }
 
+// Readable name of an instruction `TokenInstructions` or `Transaction`'s builders make, such
+// as "token transfer", recorded so a failed transaction can say which of its steps failed.
+// None for instructions of other programs.
+pub(crate) fn instruction_label(instruction: &Instruction) -> Option<&'static str> {
+    if instruction.program_id == solana_sdk::system_program::id() {
+        // System instructions start with a little-endian u32; 2 is a transfer
+        return match instruction.data.get(..4) {
+            Some([2, 0, 0, 0]) => Some("system transfer"),
+            _ => None,
+        };
+    }
+
+    let program_id = instruction.program_id.to_string();
+    if program_id != TokenInstructions::TOKEN_PROGRAM_ID
+        && program_id != TokenInstructions::TOKEN_2022_PROGRAM_ID
+    {
+        return None;
+    }
+    match instruction.data.first().copied()? {
+        TokenInstructions::TOKEN_TRANSFER_INDEX => Some("token transfer"),
+        TokenInstructions::TOKEN_APPROVE_INDEX | TokenInstructions::TOKEN_APPROVE_CHECKED_INDEX => {
+            Some("token approve")
+        }
+        TokenInstructions::TOKEN_REVOKE_INDEX => Some("token revoke"),
+        TokenInstructions::TOKEN_MINT_TO_INDEX => Some("token mint to"),
+        TokenInstructions::TOKEN_BURN_INDEX => Some("token burn"),
+        TokenInstructions::TOKEN_CLOSE_ACCOUNT_INDEX => Some("token close account"),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,257 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::error::SolanaUnityError;
+
+/// Current keystore format version. Keystores with a newer version are rejected so that
+/// older builds never misinterpret a format they do not understand.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+pub const KDF_NAME: &str = "pbkdf2-hmac-sha256";
+pub const CIPHER_NAME: &str = "aes-256-gcm";
+pub const DEFAULT_KDF_ITERATIONS: u32 = 210_000;
+
+// Bounds applied when reading a keystore so a tampered file cannot make derivation
+// trivially cheap or hang the game thread
+const MIN_KDF_ITERATIONS: u32 = 1_000;
+const MAX_KDF_ITERATIONS: u32 = 10_000_000;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub name: String,
+    pub iterations: u32,
+    pub salt: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub name: String,
+    pub nonce: String,
+}
+
+/// Password-encrypted secret key, serialized as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub pubkey: String,
+    pub kdf: KdfParams,
+    pub cipher: CipherParams,
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    /// Encrypts `secret` under a key derived from `password`. The public key is stored in
+    /// clear text and authenticated as associated data.
+    pub fn encrypt(
+        secret: &[u8],
+        pubkey: &str,
+        password: &str,
+        iterations: u32,
+    ) -> Result<Self, SolanaUnityError> {
+        if !(MIN_KDF_ITERATIONS..=MAX_KDF_ITERATIONS).contains(&iterations) {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "KDF iterations must be between {} and {}",
+                MIN_KDF_ITERATIONS, MAX_KDF_ITERATIONS
+            )));
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        let mut rng = rand::thread_rng();
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        let key = derive_key(password, &salt, iterations);
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| SolanaUnityError::WalletError(format!("Invalid key length: {}", e)))?;
+
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: secret,
+                    aad: pubkey.as_bytes(),
+                },
+            )
+            .map_err(|_| SolanaUnityError::WalletError("Encryption failed".to_string()))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            pubkey: pubkey.to_string(),
+            kdf: KdfParams {
+                name: KDF_NAME.to_string(),
+                iterations,
+                salt: BASE64.encode(salt),
+            },
+            cipher: CipherParams {
+                name: CIPHER_NAME.to_string(),
+                nonce: BASE64.encode(nonce),
+            },
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    /// Decrypts the secret. A wrong password and a tampered keystore are deliberately
+    /// indistinguishable and both return `SolanaUnityError::DecryptionFailed()`.
+    pub fn decrypt(&self, password: &str) -> Result<Vec<u8>, SolanaUnityError> {
+        if self.version > KEYSTORE_VERSION {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Unsupported keystore version {} (this build supports up to {})",
+                self.version, KEYSTORE_VERSION
+            )));
+        }
+        if self.version == 0 {
+            return Err(SolanaUnityError::InvalidInput(
+                "Invalid keystore version 0".to_string(),
+            ));
+        }
+        if self.kdf.name != KDF_NAME {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Unsupported key derivation function: {}",
+                self.kdf.name
+            )));
+        }
+        if self.cipher.name != CIPHER_NAME {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Unsupported cipher: {}",
+                self.cipher.name
+            )));
+        }
+        if !(MIN_KDF_ITERATIONS..=MAX_KDF_ITERATIONS).contains(&self.kdf.iterations) {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "KDF iterations must be between {} and {}",
+                MIN_KDF_ITERATIONS, MAX_KDF_ITERATIONS
+            )));
+        }
+
+        let salt = decode_field("salt", &self.kdf.salt)?;
+        let nonce = decode_field("nonce", &self.cipher.nonce)?;
+        let ciphertext = decode_field("ciphertext", &self.ciphertext)?;
+
+        if nonce.len() != NONCE_LEN {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Keystore nonce must be {} bytes",
+                NONCE_LEN
+            )));
+        }
+
+        let key = derive_key(password, &salt, self.kdf.iterations);
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| SolanaUnityError::WalletError(format!("Invalid key length: {}", e)))?;
+
+        cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: self.pubkey.as_bytes(),
+                },
+            )
+            .map_err(|_| SolanaUnityError::DecryptionFailed())
+    }
+
+    pub fn to_json(&self) -> Result<String, SolanaUnityError> {
+        serde_json::to_string(self).map_err(|e| {
+            SolanaUnityError::SerializationError(format!("Failed to serialize keystore: {}", e))
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SolanaUnityError> {
+        serde_json::from_str(json)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid keystore JSON: {}", e)))
+    }
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+fn decode_field(name: &str, value: &str) -> Result<Vec<u8>, SolanaUnityError> {
+    BASE64
+        .decode(value)
+        .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid keystore {}: {}", name, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{code, ErrorKind};
+
+    const TEST_ITERATIONS: u32 = MIN_KDF_ITERATIONS;
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let secret = [9u8; 64];
+        let keystore = Keystore::encrypt(&secret, "pubkey", "hunter2", TEST_ITERATIONS).unwrap();
+        assert_eq!(keystore.version, KEYSTORE_VERSION);
+
+        let json = keystore.to_json().unwrap();
+        let restored = Keystore::from_json(&json).unwrap();
+        assert_eq!(restored.decrypt("hunter2").unwrap(), secret.to_vec());
+    }
+
+    #[test]
+    fn test_keystore_uses_random_salt_and_nonce() {
+        let a = Keystore::encrypt(&[1u8; 64], "pubkey", "pw", TEST_ITERATIONS).unwrap();
+        let b = Keystore::encrypt(&[1u8; 64], "pubkey", "pw", TEST_ITERATIONS).unwrap();
+        assert_ne!(a.kdf.salt, b.kdf.salt);
+        assert_ne!(a.cipher.nonce, b.cipher.nonce);
+        assert_ne!(a.ciphertext, b.ciphertext);
+    }
+
+    #[test]
+    fn test_keystore_rejects_newer_version() {
+        let mut keystore = Keystore::encrypt(&[1u8; 64], "pubkey", "pw", TEST_ITERATIONS).unwrap();
+        keystore.version = KEYSTORE_VERSION + 1;
+
+        match keystore.decrypt("pw") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("version")),
+            _ => panic!("Expected InvalidInput for an unsupported version"),
+        }
+    }
+
+    #[test]
+    fn test_keystore_rejects_tampered_pubkey() {
+        let mut keystore = Keystore::encrypt(&[1u8; 64], "pubkey", "pw", TEST_ITERATIONS).unwrap();
+        keystore.pubkey = "other".to_string();
+
+        match keystore.decrypt("pw") {
+            Err(SolanaUnityError {
+                code: code::DECRYPTION_FAILED,
+                ..
+            }) => {}
+            _ => panic!("Expected DecryptionFailed for a tampered pubkey"),
+        }
+    }
+
+    #[test]
+    fn test_keystore_rejects_out_of_range_iterations() {
+        let mut keystore = Keystore::encrypt(&[1u8; 64], "pubkey", "pw", TEST_ITERATIONS).unwrap();
+        keystore.kdf.iterations = 1;
+
+        match keystore.decrypt("pw") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput for out of range iterations"),
+        }
+
+        assert!(Keystore::encrypt(&[1u8; 64], "pubkey", "pw", u32::MAX).is_err());
+    }
+}
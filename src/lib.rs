@@ -1,19 +1,79 @@
 pub mod account;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod account_cache;
+pub mod address_book;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod allocator;
+pub mod amount;
+pub mod anchor;
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+pub mod balance_watcher;
+pub mod cost;
+pub mod decode;
+pub mod deeplink;
+pub mod envelope;
 pub mod error;
+pub mod external_signer;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod ffi;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod handle;
 pub mod instruction;
+pub mod keystore;
+pub mod logging;
+pub mod logs;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod metrics;
+pub mod nft;
+pub mod nonce;
+pub mod offchain_message;
+pub mod oracle;
 pub mod pda;
+pub mod pkcs8;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod requests;
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
 pub mod rpc;
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+pub mod rpc_sender;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod runtime;
+pub mod secure_memory;
+pub mod session;
+pub mod shamir;
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+pub mod solana_pay;
+pub mod stake;
+#[cfg(all(not(target_arch = "wasm32"), feature = "websocket"))]
+pub mod subscription;
+pub mod token_holders;
 pub mod transaction;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod vanity;
+pub mod wallet_manager;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub mod wasm;
 
-pub use account::Account;
-pub use error::SolanaUnityError;
+pub use account::{Account, DerivationScheme};
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+pub use balance_watcher::{BalanceChange, BalanceKind, BalanceWatcher};
+pub use deeplink::DeeplinkSession;
+pub use error::{ErrorKind, SolanaUnityError};
 pub use instruction::{InstructionBuilder, TokenInstructions};
-pub use pda::ProgramDerivedAddress;
-pub use rpc::RpcClient;
+pub use keystore::Keystore;
+pub use pda::{ProgramDerivedAddress, Seed};
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+pub use rpc::{
+    EndpointProbe, MultipleAccounts, PartialFailure, ProgramAccountsPage, RpcClient,
+    RpcClientConfig,
+};
 pub use transaction::Transaction;
+#[cfg(not(target_arch = "wasm32"))]
+pub use vanity::GrindHandle;
+pub use wallet_manager::WalletManager;
 
-// Re-export the FFI functions for use in Unity
+// Re-export the FFI functions for use in Unity. WebGL builds use the wasm module instead.
+#[cfg(not(target_arch = "wasm32"))]
 pub use ffi::*;
 
 #[cfg(test)]
@@ -0,0 +1,81 @@
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Mutex;
+
+pub const LOG_DEBUG: c_int = 0;
+pub const LOG_INFO: c_int = 1;
+pub const LOG_WARN: c_int = 2;
+pub const LOG_ERROR: c_int = 3;
+
+/// Receives a log line. `message` is only valid for the duration of the call, and the
+/// callback may be invoked from any thread, including the request workers.
+pub type LogCallback = extern "C" fn(level: c_int, message: *const c_char, user_data: *mut c_void);
+
+#[derive(Clone, Copy)]
+struct Logger {
+    callback: LogCallback,
+    user_data: *mut c_void,
+}
+
+// The user data pointer is opaque to us and only ever handed back to the callback
+unsafe impl Send for Logger {}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+// Lowest level passed to the callback. Above every level while no callback is set, so
+// `enabled` is a single atomic load and messages are never formatted.
+static MIN_LEVEL: AtomicI32 = AtomicI32::new(i32::MAX);
+
+/// Registers the process-wide log callback, replacing any previous one. Lines below
+/// `min_level` are dropped before they are formatted.
+pub fn set_log_callback(callback: LogCallback, user_data: *mut c_void, min_level: c_int) {
+    let mut logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    *logger = Some(Logger {
+        callback,
+        user_data,
+    });
+    MIN_LEVEL.store(min_level, Ordering::SeqCst);
+}
+
+pub fn clear_log_callback() {
+    let mut logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    MIN_LEVEL.store(i32::MAX, Ordering::SeqCst);
+    *logger = None;
+}
+
+/// Whether a line at `level` would reach the callback
+#[inline]
+pub fn enabled(level: c_int) -> bool {
+    level >= MIN_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Passes `message` to the registered callback. Use the `native_log!` macro, which skips
+/// formatting when the level is disabled.
+pub fn log(level: c_int, message: &str) {
+    if !enabled(level) {
+        return;
+    }
+
+    // Copy the logger out so the lock is not held while calling back into the host,
+    // which may itself change the callback
+    let logger = match *LOGGER.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some(logger) => logger,
+        None => return,
+    };
+
+    let mut bytes = message.as_bytes().to_vec();
+    bytes.retain(|b| *b != 0);
+    let message = CString::new(bytes).unwrap_or_default();
+    (logger.callback)(level, message.as_ptr(), logger.user_data);
+}
+
+/// Logs a formatted line, e.g. `native_log!(LOG_WARN, "{} failed: {}", method, e)`
+#[macro_export]
+macro_rules! native_log {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::logging::enabled($level) {
+            $crate::logging::log($level, &format!($($arg)*));
+        }
+    };
+}
@@ -0,0 +1,237 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::error::SolanaUnityError;
+
+/// Bytes of an Anchor event's discriminator, sha256("event:<Name>") cut to 8
+pub const EVENT_DISCRIMINATOR_LEN: usize = 8;
+
+const DATA_PREFIX: &str = "Program data: ";
+const LOG_PREFIX: &str = "Program log: ";
+
+/// An event or message a program logged. Events have `discriminator_hex` and
+/// `data_base64`; messages only `raw_msg`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramEvent {
+    pub program: String,
+    /// The first 8 bytes of an event, which name its type; None for messages and for
+    /// events shorter than that
+    pub discriminator_hex: Option<String>,
+    /// The event's bytes after the discriminator
+    pub data_base64: Option<String>,
+    /// The text of a `msg!` line
+    pub raw_msg: Option<String>,
+}
+
+/// The events (`emit!`, logged as "Program data:") and messages (`msg!`, logged as
+/// "Program log:") that `program_id` itself logged, as a JSON array of `ProgramEvent`, in
+/// log order. The invoke and success lines are followed so a line is credited to the
+/// program running at that point, including when it runs inside another program's CPI.
+pub fn extract_events(meta_logs: &[String], program_id: &str) -> Result<String, SolanaUnityError> {
+    let events = program_events(meta_logs, program_id)?;
+    serde_json::to_string(&events).map_err(|e| {
+        SolanaUnityError::SerializationError(format!("Failed to serialize events: {}", e))
+    })
+}
+
+pub fn program_events(
+    meta_logs: &[String],
+    program_id: &str,
+) -> Result<Vec<ProgramEvent>, SolanaUnityError> {
+    Pubkey::from_str(program_id).map_err(|e| {
+        SolanaUnityError::InvalidInput(format!("Invalid program ID: {}", e)).with_pubkey(program_id)
+    })?;
+
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for (index, line) in meta_logs.iter().enumerate() {
+        if let Some((program, depth)) = invoked(line) {
+            // Truncated logs can skip returns; the depth says where this call sits
+            stack.truncate(depth.saturating_sub(1));
+            stack.push(program);
+        } else if returned(line) {
+            stack.pop();
+        } else if stack.last() == Some(&program_id) {
+            if let Some(data) = line.strip_prefix(DATA_PREFIX) {
+                events.push(event(program_id, data, index)?);
+            } else if let Some(message) = line.strip_prefix(LOG_PREFIX) {
+                events.push(ProgramEvent {
+                    program: program_id.to_string(),
+                    discriminator_hex: None,
+                    data_base64: None,
+                    raw_msg: Some(message.to_string()),
+                });
+            }
+        }
+    }
+    Ok(events)
+}
+
+// "Program <id> invoke [<depth>]"
+fn invoked(line: &str) -> Option<(&str, usize)> {
+    let rest = line.strip_prefix("Program ")?;
+    let (program, depth) = rest.split_once(" invoke [")?;
+    let depth = depth.strip_suffix(']')?.parse().ok()?;
+    Some((program, depth))
+}
+
+// "Program <id> success" or "Program <id> failed: <error>"
+fn returned(line: &str) -> bool {
+    match line.strip_prefix("Program ") {
+        Some(rest) => match rest.split_once(' ') {
+            Some((program, outcome)) => {
+                Pubkey::from_str(program).is_ok()
+                    && (outcome == "success" || outcome.starts_with("failed"))
+            }
+            None => false,
+        },
+        None => false,
+    }
+}
+
+fn event(program_id: &str, data: &str, index: usize) -> Result<ProgramEvent, SolanaUnityError> {
+    // sol_log_data logs each slice as its own base64 word; emit! passes one slice
+    let mut bytes = Vec::new();
+    for word in data.split_whitespace() {
+        let decoded = BASE64.decode(word).map_err(|e| {
+            SolanaUnityError::SerializationError(format!(
+                "Invalid base64 in log line {}: {}",
+                index, e
+            ))
+        })?;
+        bytes.extend(decoded);
+    }
+
+    let (discriminator, data) = if bytes.len() >= EVENT_DISCRIMINATOR_LEN {
+        let (discriminator, data) = bytes.split_at(EVENT_DISCRIMINATOR_LEN);
+        (Some(hex(discriminator)), data)
+    } else {
+        (None, &bytes[..])
+    };
+    Ok(ProgramEvent {
+        program: program_id.to_string(),
+        discriminator_hex: discriminator,
+        data_base64: Some(BASE64.encode(data)),
+        raw_msg: None,
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture {
+        game: String,
+        market: String,
+        logs: Vec<String>,
+    }
+
+    // A game program that resolves a battle: it transfers tokens, then calls a market
+    // program, which calls back into the game to grant a reward
+    fn nested_cpi() -> Fixture {
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/fixtures/logs/nested_cpi.json")).unwrap();
+        Fixture {
+            game: fixture["game"].as_str().unwrap().to_string(),
+            market: fixture["market"].as_str().unwrap().to_string(),
+            logs: serde_json::from_value(fixture["logs"].clone()).unwrap(),
+        }
+    }
+
+    fn message(program: &str, text: &str) -> ProgramEvent {
+        ProgramEvent {
+            program: program.to_string(),
+            discriminator_hex: None,
+            data_base64: None,
+            raw_msg: Some(text.to_string()),
+        }
+    }
+
+    fn emitted(program: &str, discriminator: &str, data: &str) -> ProgramEvent {
+        ProgramEvent {
+            program: program.to_string(),
+            discriminator_hex: Some(discriminator.to_string()),
+            data_base64: Some(data.to_string()),
+            raw_msg: None,
+        }
+    }
+
+    #[test]
+    fn test_events_follow_nested_cpis() {
+        let Fixture { game, market, logs } = nested_cpi();
+
+        assert_eq!(
+            program_events(&logs, &game).unwrap(),
+            vec![
+                message(&game, "Instruction: ResolveBattle"),
+                // BattleResolved { battle: 42, won: true }
+                emitted(&game, "2f9ce25ea3b0a2f1", "KgAAAAAAAAAB"),
+                // Logged while the market called back into the game
+                message(&game, "Instruction: GrantReward"),
+                emitted(&game, "72c7d7a9e0133846", "+gAAAAAAAAA="),
+                // Back in the game after the market returned
+                message(&game, "Battle 42 resolved"),
+            ]
+        );
+
+        let market_events = program_events(&logs, &market).unwrap();
+        assert_eq!(market_events.len(), 2);
+        assert_eq!(
+            market_events[0],
+            message(&market, "Instruction: FillListing")
+        );
+        assert_eq!(
+            market_events[1].discriminator_hex.as_deref(),
+            Some("f24dcc1db9d20e25")
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&extract_events(&logs, &game).unwrap()).unwrap();
+        assert_eq!(json[1]["discriminatorHex"], "2f9ce25ea3b0a2f1");
+        assert_eq!(json[1]["rawMsg"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_failed_and_truncated_logs() {
+        let Fixture { game, logs, .. } = nested_cpi();
+        let token = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+        // The token program fails and the game never logs its success
+        let mut failed: Vec<String> = logs[..6].to_vec();
+        failed.push(format!(
+            "Program {} failed: custom program error: 0x1",
+            token
+        ));
+        failed.push("Program log: after the failure".to_string());
+        let events = program_events(&failed, &game).unwrap();
+        assert_eq!(events.last(), Some(&message(&game, "after the failure")));
+
+        // A return line lost to truncation: the next invoke's depth resyncs the stack
+        let mut truncated: Vec<String> = logs[..6].to_vec();
+        truncated.push(format!("Program {} invoke [2]", game));
+        truncated.push("Program data: AQID".to_string());
+        let events = program_events(&truncated, &game).unwrap();
+        // Too short for a discriminator, so it is all data
+        assert_eq!(events.last().unwrap().data_base64.as_deref(), Some("AQID"));
+        assert_eq!(events.last().unwrap().discriminator_hex, None);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let Fixture { game, logs, .. } = nested_cpi();
+        assert!(program_events(&logs, "not-a-program").is_err());
+
+        let mut corrupt = logs[..3].to_vec();
+        corrupt.push("Program data: !!!".to_string());
+        let error = program_events(&corrupt, &game).unwrap_err();
+        assert!(error.message.contains("log line 3"));
+    }
+}
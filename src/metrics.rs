@@ -0,0 +1,221 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::error::{ErrorKind, SolanaUnityError};
+
+/// Upper bounds of the latency histogram buckets, in milliseconds. Slower calls land in a
+/// last, unbounded bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+const KINDS: [ErrorKind; 9] = [
+    ErrorKind::Rpc,
+    ErrorKind::Transaction,
+    ErrorKind::Serialization,
+    ErrorKind::Wallet,
+    ErrorKind::InvalidInput,
+    ErrorKind::Ffi,
+    ErrorKind::Timeout,
+    ErrorKind::NotFound,
+    ErrorKind::RateLimited,
+];
+
+/// Call counts, failures and latencies of the RPC methods an `RpcClient` called, per
+/// method. Off until enabled; while off, recording a call costs one atomic load.
+#[derive(Debug, Default)]
+pub struct RpcMetrics {
+    enabled: AtomicBool,
+    methods: RwLock<HashMap<String, Arc<MethodMetrics>>>,
+}
+
+#[derive(Debug, Default)]
+struct MethodMetrics {
+    failures: [AtomicU64; KINDS.len()],
+    total_ms: AtomicU64,
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+/// The metrics at one moment, as returned by `RpcClient::metrics_snapshot_json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSnapshot {
+    pub enabled: bool,
+    pub methods: BTreeMap<String, MethodSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodSnapshot {
+    pub calls: u64,
+    /// Failed calls by error kind, e.g. "RpcError"; kinds that never failed are left out
+    pub failures: BTreeMap<&'static str, u64>,
+    pub total_ms: u64,
+    /// The upper bound of the bucket holding the 95th percentile call, or None when it is
+    /// the unbounded bucket or there were no calls
+    pub p95_ms: Option<u64>,
+    /// Calls per bucket of `LATENCY_BUCKETS_MS`, then the calls slower than all of them
+    pub latency_buckets: Vec<u64>,
+}
+
+impl RpcMetrics {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Counts one call to `method` that took `elapsed` and failed with `error`, if any
+    pub fn record(&self, method: &str, elapsed: Duration, error: Option<&SolanaUnityError>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let existing = self.read_methods().get(method).cloned();
+        let metrics = match existing {
+            Some(metrics) => metrics,
+            None => {
+                let mut methods = self.methods.write().unwrap_or_else(|e| e.into_inner());
+                Arc::clone(methods.entry(method.to_string()).or_default())
+            }
+        };
+
+        // The bucket is counted last: calls are the sum of the buckets, so a snapshot,
+        // which reads the failures first, never sees more failures than calls
+        let elapsed_ms = elapsed.as_millis().min(u64::MAX as u128) as u64;
+        if let Some(error) = error {
+            let kind = KINDS.iter().position(|kind| *kind == error.kind).unwrap();
+            metrics.failures[kind].fetch_add(1, Ordering::SeqCst);
+        }
+        metrics.total_ms.fetch_add(elapsed_ms, Ordering::SeqCst);
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| elapsed_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        metrics.buckets[bucket].fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let methods = self
+            .read_methods()
+            .iter()
+            .map(|(method, metrics)| (method.clone(), metrics.snapshot()))
+            .collect();
+        MetricsSnapshot {
+            enabled: self.is_enabled(),
+            methods,
+        }
+    }
+
+    /// Forgets every recorded call, keeping metrics enabled or disabled
+    pub fn reset(&self) {
+        self.methods
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    fn read_methods(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, Arc<MethodMetrics>>> {
+        // Every write leaves the map consistent, so a panic elsewhere does not spoil it
+        self.methods.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MethodMetrics {
+    fn snapshot(&self) -> MethodSnapshot {
+        let failures = KINDS
+            .iter()
+            .zip(&self.failures)
+            .map(|(kind, count)| (kind.name(), count.load(Ordering::SeqCst)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        let total_ms = self.total_ms.load(Ordering::SeqCst);
+        let latency_buckets: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|count| count.load(Ordering::SeqCst))
+            .collect();
+        let calls: u64 = latency_buckets.iter().sum();
+
+        // The smallest bucket at or below which 95% of the calls finished
+        let mut p95_ms = None;
+        let mut seen = 0;
+        for (bucket, count) in latency_buckets.iter().enumerate() {
+            seen += count;
+            if calls > 0 && seen * 100 >= calls * 95 {
+                p95_ms = LATENCY_BUCKETS_MS.get(bucket).copied();
+                break;
+            }
+        }
+
+        MethodSnapshot {
+            calls,
+            failures,
+            total_ms,
+            p95_ms,
+            latency_buckets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_disabled_records_nothing() {
+        let metrics = RpcMetrics::default();
+        metrics.record("getBalance", ms(5), None);
+        assert!(metrics.snapshot().methods.is_empty());
+    }
+
+    #[test]
+    fn test_calls_land_in_buckets() {
+        let metrics = RpcMetrics::default();
+        metrics.set_enabled(true);
+        for elapsed in [0, 10, 11, 300, 20_000] {
+            metrics.record("getBalance", ms(elapsed), None);
+        }
+        let timeout = SolanaUnityError::Timeout(100);
+        metrics.record("getBalance", ms(100), Some(&timeout));
+
+        let snapshot = metrics.snapshot();
+        let balance = &snapshot.methods["getBalance"];
+        assert_eq!(balance.calls, 6);
+        assert_eq!(balance.total_ms, 20_421);
+        assert_eq!(
+            balance.latency_buckets,
+            vec![2, 1, 0, 1, 0, 1, 0, 0, 0, 0, 1]
+        );
+        assert_eq!(balance.failures, BTreeMap::from([("Timeout", 1)]));
+        // The slowest call is past the last bound
+        assert_eq!(balance.p95_ms, None);
+
+        metrics.reset();
+        assert!(metrics.snapshot().methods.is_empty());
+        assert!(metrics.snapshot().enabled);
+    }
+
+    #[test]
+    fn test_p95() {
+        let metrics = RpcMetrics::default();
+        metrics.set_enabled(true);
+        for _ in 0..95 {
+            metrics.record("getSlot", ms(20), None);
+        }
+        for _ in 0..5 {
+            metrics.record("getSlot", ms(900), None);
+        }
+        assert_eq!(metrics.snapshot().methods["getSlot"].p95_ms, Some(25));
+
+        metrics.record("getSlot", ms(900), None);
+        assert_eq!(metrics.snapshot().methods["getSlot"].p95_ms, Some(1000));
+    }
+}
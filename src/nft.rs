@@ -0,0 +1,138 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// `Key::MetadataV1`, the first byte of a Metaplex metadata account
+const METADATA_V1: u8 = 4;
+// The name follows the key, update authority and mint
+const METADATA_NAME_OFFSET: usize = 1 + 32 + 32;
+
+/// An NFT a wallet holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Nft {
+    pub mint: String,
+    /// The token account holding it
+    pub token_account: String,
+    /// None when metadata was not fetched or the mint has no Metaplex metadata
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+}
+
+/// The name, symbol and URI of a Metaplex metadata account, without the padding Metaplex
+/// stores them with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+/// The mint of a token account holding an NFT: exactly one token of a mint without
+/// decimals. `parsed` is the account's jsonParsed data, as getTokenAccountsByOwner returns
+/// it for both token programs. Frozen accounts count, since staked NFTs are often frozen in
+/// place.
+pub fn nft_mint(parsed: &Value) -> Option<&str> {
+    if parsed["type"] != "account" {
+        return None;
+    }
+    let info = &parsed["info"];
+    let amount = &info["tokenAmount"];
+    if amount["amount"] != "1" || amount["decimals"] != 0 {
+        return None;
+    }
+    info["mint"].as_str()
+}
+
+/// Reads a Metaplex metadata account, or None when `data` is not one
+pub fn parse_metadata(data: &[u8]) -> Option<NftMetadata> {
+    if data.first() != Some(&METADATA_V1) {
+        return None;
+    }
+
+    let mut offset = METADATA_NAME_OFFSET;
+    let mut string = || {
+        let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize;
+        let bytes = data.get(offset + 4..offset + 4 + len)?;
+        offset += 4 + len;
+        let value = std::str::from_utf8(bytes).ok()?;
+        Some(value.trim_end_matches('\0').to_string())
+    };
+    Some(NftMetadata {
+        name: string()?,
+        symbol: string()?,
+        uri: string()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The owner's token accounts as getTokenAccountsByOwner returns them with jsonParsed
+    // encoding: under the token program an NFT, a fungible balance, an NFT that was sold
+    // (0 left), 1 USDC, a stack of 2 potions and a frozen NFT; under Token-2022 an NFT
+    fn fixture() -> Value {
+        serde_json::from_str(include_str!("../tests/fixtures/nft/token_accounts.json")).unwrap()
+    }
+
+    // Metadata as Metaplex stores it: fixed-size strings padded with NULs
+    fn metadata_account(name: &str, symbol: &str, uri: &str) -> Vec<u8> {
+        let mut data = vec![METADATA_V1];
+        data.extend([7; 64]);
+        for (value, size) in [(name, 32), (symbol, 10), (uri, 200)] {
+            let mut padded = value.as_bytes().to_vec();
+            padded.resize(size, 0);
+            data.extend((size as u32).to_le_bytes());
+            data.extend(padded);
+        }
+        // Royalties, creators and the rest, which are not read
+        data.extend([0; 300]);
+        data
+    }
+
+    #[test]
+    fn test_nft_holdings() {
+        let fixture = fixture();
+        let mints = &fixture["mints"];
+        let held: Vec<&str> = ["tokenProgram", "token2022"]
+            .iter()
+            .flat_map(|program| fixture[program].as_array().unwrap())
+            .filter_map(|keyed| nft_mint(&keyed["account"]["data"]["parsed"]))
+            .collect();
+        let expected: Vec<&str> = ["sword", "helmet", "badge"]
+            .iter()
+            .map(|name| mints[name].as_str().unwrap())
+            .collect();
+        assert_eq!(held, expected);
+    }
+
+    #[test]
+    fn test_non_accounts_are_not_nfts() {
+        let mut mint = fixture()["tokenProgram"][0]["account"]["data"]["parsed"].clone();
+        assert!(nft_mint(&mint).is_some());
+        mint["type"] = "mint".into();
+        assert_eq!(nft_mint(&mint), None);
+        assert_eq!(nft_mint(&Value::Null), None);
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let data = metadata_account("Sword #12", "SWRD", "https://arweave.net/sword-12");
+        assert_eq!(
+            parse_metadata(&data),
+            Some(NftMetadata {
+                name: "Sword #12".to_string(),
+                symbol: "SWRD".to_string(),
+                uri: "https://arweave.net/sword-12".to_string(),
+            })
+        );
+
+        // Another account type, and an account cut off in the URI
+        let mut edition = data.clone();
+        edition[0] = 6;
+        assert_eq!(parse_metadata(&edition), None);
+        assert_eq!(parse_metadata(&data[..150]), None);
+        assert_eq!(parse_metadata(&[]), None);
+    }
+}
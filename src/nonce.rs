@@ -0,0 +1,133 @@
+use serde::Serialize;
+use solana_sdk::nonce::state::{State, Versions};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+
+use crate::error::{code, SolanaUnityError};
+
+/// An initialized durable nonce account, decoded from its `nonce::state::Versions`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceAccount {
+    /// "current", or "legacy" for accounts created before durable nonces were separated
+    /// from blockhashes. Legacy nonces must be advanced once before they can be used.
+    pub version: &'static str,
+    /// The account that must sign AdvanceNonceAccount
+    pub authority: String,
+    /// The stored nonce, used in place of the recent blockhash of a transaction whose
+    /// first instruction advances this account
+    pub nonce: String,
+    /// The fee per signature when the nonce was last advanced
+    pub lamports_per_signature: u64,
+}
+
+/// Decodes a nonce account owned by `owner` from its data. Accounts that are not nonce
+/// accounts fail with `code::NOT_A_NONCE_ACCOUNT` and nonce accounts that were allocated
+/// but not initialized with `code::NONCE_UNINITIALIZED`.
+pub fn parse_nonce_account(owner: &Pubkey, data: &[u8]) -> Result<NonceAccount, SolanaUnityError> {
+    if *owner != system_program::id() {
+        return Err(not_a_nonce_account(format!("owned by {}", owner)));
+    }
+    if data.len() != State::size() {
+        return Err(not_a_nonce_account(format!(
+            "{} bytes of data, expected {}",
+            data.len(),
+            State::size()
+        )));
+    }
+    let versions: Versions = bincode::deserialize(data)
+        .map_err(|e| not_a_nonce_account(format!("invalid data: {}", e)))?;
+
+    let version = match versions {
+        Versions::Legacy(_) => "legacy",
+        Versions::Current(_) => "current",
+    };
+    match versions.state() {
+        State::Uninitialized => Err(SolanaUnityError::InvalidInput(
+            "Nonce account is not initialized",
+        )
+        .with_code(code::NONCE_UNINITIALIZED)),
+        State::Initialized(data) => Ok(NonceAccount {
+            version,
+            authority: data.authority.to_string(),
+            nonce: data.blockhash().to_string(),
+            lamports_per_signature: data.fee_calculator.lamports_per_signature,
+        }),
+    }
+}
+
+fn not_a_nonce_account(reason: String) -> SolanaUnityError {
+    SolanaUnityError::InvalidInput(format!("Not a nonce account: {}", reason))
+        .with_code(code::NOT_A_NONCE_ACCOUNT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+    use serde_json::Value;
+    use std::str::FromStr;
+
+    // Nonce accounts as getAccountInfo returns them: initialized current and legacy
+    // versions, one that is allocated but uninitialized, and two accounts that are not
+    // nonce accounts, a wallet and a stake account
+    fn fixture() -> Value {
+        serde_json::from_str(include_str!("../tests/fixtures/nonce/nonce_accounts.json")).unwrap()
+    }
+
+    fn parse(fixture: &Value, name: &str) -> Result<NonceAccount, SolanaUnityError> {
+        let account = &fixture["accounts"][name]["account"];
+        let owner = Pubkey::from_str(account["owner"].as_str().unwrap()).unwrap();
+        let data = BASE64.decode(account["data"][0].as_str().unwrap()).unwrap();
+        parse_nonce_account(&owner, &data)
+    }
+
+    #[test]
+    fn test_initialized_nonce_accounts() {
+        let fixture = fixture();
+        assert_eq!(
+            parse(&fixture, "current").unwrap(),
+            NonceAccount {
+                version: "current",
+                authority: fixture["authority"].as_str().unwrap().to_string(),
+                nonce: fixture["nonce"].as_str().unwrap().to_string(),
+                lamports_per_signature: 5000,
+            }
+        );
+        assert_eq!(
+            parse(&fixture, "legacy").unwrap(),
+            NonceAccount {
+                version: "legacy",
+                authority: fixture["legacyAuthority"].as_str().unwrap().to_string(),
+                nonce: fixture["legacyNonce"].as_str().unwrap().to_string(),
+                lamports_per_signature: 10000,
+            }
+        );
+
+        let json = serde_json::to_value(parse(&fixture, "current").unwrap()).unwrap();
+        assert_eq!(json["nonce"], fixture["nonce"]);
+        assert_eq!(json["lamportsPerSignature"], 5000);
+    }
+
+    #[test]
+    fn test_uninitialized_and_other_accounts() {
+        let fixture = fixture();
+        let error = parse(&fixture, "uninitialized").unwrap_err();
+        assert_eq!(error.code, code::NONCE_UNINITIALIZED);
+
+        for name in ["wallet", "stake"] {
+            let error = parse(&fixture, name).unwrap_err();
+            assert_eq!(error.code, code::NOT_A_NONCE_ACCOUNT, "{}", name);
+            assert!(
+                error.message.starts_with("Not a nonce account"),
+                "{}",
+                error
+            );
+        }
+
+        // Nonce sized, but not a nonce state
+        let error = parse_nonce_account(&system_program::id(), &[7; 80]).unwrap_err();
+        assert_eq!(error.code, code::NOT_A_NONCE_ACCOUNT);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::SolanaUnityError;
+
+/// Prefix that keeps off-chain messages from ever being valid transaction messages
+pub const SIGNING_DOMAIN: &[u8; 16] = b"\xffsolana offchain";
+
+/// Only version 0 of the off-chain message format is defined
+pub const VERSION: u8 = 0;
+
+// Signing domain (16) + version (1)
+const BASE_HEADER_LEN: usize = SIGNING_DOMAIN.len() + 1;
+// Message format (1) + message length (2)
+const V0_HEADER_LEN: usize = 3;
+
+/// Longest message that fits the u16 length field
+pub const MAX_LEN: usize = u16::MAX as usize - BASE_HEADER_LEN - V0_HEADER_LEN;
+
+/// Longest message hardware wallets accept (one packet)
+pub const MAX_LEN_LEDGER: usize = 1232 - BASE_HEADER_LEN - V0_HEADER_LEN;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MessageFormat {
+    /// Printable ASCII (0x20..=0x7e), at most `MAX_LEN_LEDGER` bytes
+    RestrictedAscii = 0,
+    /// UTF-8, at most `MAX_LEN_LEDGER` bytes
+    LimitedUtf8 = 1,
+    /// UTF-8, at most `MAX_LEN` bytes
+    ExtendedUtf8 = 2,
+}
+
+/// Picks the message format the spec requires for `message`
+pub fn message_format(message: &[u8]) -> Result<MessageFormat, SolanaUnityError> {
+    if message.is_empty() {
+        return Err(SolanaUnityError::InvalidInput(
+            "Off-chain message must not be empty".to_string(),
+        ));
+    }
+
+    if message.len() > MAX_LEN {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Off-chain message is {} bytes, the maximum is {}",
+            message.len(),
+            MAX_LEN
+        )));
+    }
+
+    let is_utf8 = std::str::from_utf8(message).is_ok();
+
+    if message.len() <= MAX_LEN_LEDGER {
+        if message.iter().all(|c| (0x20..=0x7e).contains(c)) {
+            Ok(MessageFormat::RestrictedAscii)
+        } else if is_utf8 {
+            Ok(MessageFormat::LimitedUtf8)
+        } else {
+            Err(SolanaUnityError::InvalidInput(
+                "Off-chain message must be valid UTF-8".to_string(),
+            ))
+        }
+    } else if is_utf8 {
+        Ok(MessageFormat::ExtendedUtf8)
+    } else {
+        Err(SolanaUnityError::InvalidInput(
+            "Off-chain message must be valid UTF-8".to_string(),
+        ))
+    }
+}
+
+/// Wraps `message` in the version 0 envelope: signing domain, version, format,
+/// little-endian u16 length and the message itself. These are the bytes that get signed.
+pub fn serialize(message: &[u8]) -> Result<Vec<u8>, SolanaUnityError> {
+    let format = message_format(message)?;
+
+    let mut data = Vec::with_capacity(BASE_HEADER_LEN + V0_HEADER_LEN + message.len());
+    data.extend_from_slice(SIGNING_DOMAIN);
+    data.push(VERSION);
+    data.push(format as u8);
+    data.extend_from_slice(&(message.len() as u16).to_le_bytes());
+    data.extend_from_slice(message);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn test_limits_match_spec() {
+        assert_eq!(MAX_LEN, 65515);
+        assert_eq!(MAX_LEN_LEDGER, 1212);
+    }
+
+    #[test]
+    fn test_serialize_ascii_spec_vector() {
+        // Vector from the off-chain message signing spec for "Test Message"
+        let expected: Vec<u8> = vec![
+            255, 115, 111, 108, 97, 110, 97, 32, 111, 102, 102, 99, 104, 97, 105, 110, 0, 0, 12, 0,
+            84, 101, 115, 116, 32, 77, 101, 115, 115, 97, 103, 101,
+        ];
+        assert_eq!(serialize(b"Test Message").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_message_formats() {
+        assert_eq!(
+            message_format(b"Test Message").unwrap(),
+            MessageFormat::RestrictedAscii
+        );
+        assert_eq!(
+            message_format("Тестовое сообщение".as_bytes()).unwrap(),
+            MessageFormat::LimitedUtf8
+        );
+        // Newlines are not printable ASCII
+        assert_eq!(
+            message_format(b"line one\nline two").unwrap(),
+            MessageFormat::LimitedUtf8
+        );
+        assert_eq!(
+            message_format(&vec![b'a'; MAX_LEN_LEDGER + 1]).unwrap(),
+            MessageFormat::ExtendedUtf8
+        );
+
+        let header = serialize("é".as_bytes()).unwrap();
+        assert_eq!(&header[16..20], &[0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_messages() {
+        assert!(message_format(b"").is_err());
+        assert!(message_format(&[0xff, 0xfe]).is_err());
+
+        match message_format(&vec![b'a'; MAX_LEN + 1]) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("65515")),
+            _ => panic!("Expected InvalidInput for an oversized message"),
+        }
+        assert!(message_format(&vec![b'a'; MAX_LEN]).is_ok());
+    }
+}
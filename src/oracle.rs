@@ -0,0 +1,216 @@
+use serde::Serialize;
+
+use crate::error::SolanaUnityError;
+
+/// First four bytes of every Pyth account
+pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+/// The account layout this module reads
+pub const PYTH_VERSION: u32 = 2;
+const PYTH_PRICE_ACCOUNT: u32 = 3;
+
+// Offsets into a version 2 price account
+const EXPONENT_OFFSET: usize = 20;
+const VALID_SLOT_OFFSET: usize = 40;
+const AGGREGATE_OFFSET: usize = 208;
+// The aggregate price ends where the publishers' components start
+const HEADER_LEN: usize = 240;
+
+// Prices are whole numbers scaled by 10^exponent; Pyth keeps exponents well inside this
+const MAX_EXPONENT: i32 = 32;
+
+/// The aggregate price of a Pyth price account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PythPrice {
+    /// `raw_price` scaled by the exponent, e.g. "145.23456789"
+    pub price: String,
+    pub confidence: String,
+    pub raw_price: i64,
+    pub raw_confidence: u64,
+    pub exponent: i32,
+    /// "trading", "halted", "auction", "ignored" or "unknown"
+    pub status: &'static str,
+    /// False unless the status is "trading". The price is then the last one published
+    /// while trading, and should not be shown as current.
+    pub trading: bool,
+    pub publish_slot: u64,
+    /// The slot of the last aggregation, which may be later than `publish_slot`
+    pub valid_slot: u64,
+}
+
+/// Reads the aggregate price of a Pyth price account as JSON, see `PythPrice`
+pub fn decode_pyth_price(account_data: &[u8]) -> Result<String, SolanaUnityError> {
+    let price = parse_pyth_price(account_data)?;
+    serde_json::to_string(&price).map_err(|e| {
+        SolanaUnityError::SerializationError(format!("Failed to serialize Pyth price: {}", e))
+    })
+}
+
+pub fn parse_pyth_price(account_data: &[u8]) -> Result<PythPrice, SolanaUnityError> {
+    if account_data.len() < HEADER_LEN {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Pyth price account is {} bytes, expected at least {}",
+            account_data.len(),
+            HEADER_LEN
+        )));
+    }
+
+    let magic = read_u32(account_data, 0);
+    if magic != PYTH_MAGIC {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Not a Pyth account: magic is {:#010x}",
+            magic
+        )));
+    }
+    let version = read_u32(account_data, 4);
+    if version != PYTH_VERSION {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Unsupported Pyth account version {}, expected {}",
+            version, PYTH_VERSION
+        )));
+    }
+    let account_type = read_u32(account_data, 8);
+    if account_type != PYTH_PRICE_ACCOUNT {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Not a Pyth price account: account type is {}",
+            account_type
+        )));
+    }
+
+    let exponent = read_u32(account_data, EXPONENT_OFFSET) as i32;
+    if exponent.abs() > MAX_EXPONENT {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Pyth price exponent {} is out of range",
+            exponent
+        )));
+    }
+    let raw_price = read_u64(account_data, AGGREGATE_OFFSET) as i64;
+    let raw_confidence = read_u64(account_data, AGGREGATE_OFFSET + 8);
+    let status = match read_u32(account_data, AGGREGATE_OFFSET + 16) {
+        1 => "trading",
+        2 => "halted",
+        3 => "auction",
+        4 => "ignored",
+        _ => "unknown",
+    };
+
+    Ok(PythPrice {
+        price: scale(raw_price as i128, exponent),
+        confidence: scale(raw_confidence as i128, exponent),
+        raw_price,
+        raw_confidence,
+        exponent,
+        status,
+        trading: status == "trading",
+        publish_slot: read_u64(account_data, AGGREGATE_OFFSET + 24),
+        valid_slot: read_u64(account_data, VALID_SLOT_OFFSET),
+    })
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+// `value` × 10^`exponent` as an exact decimal string, e.g. (-1234, -2) -> "-12.34"
+fn scale(value: i128, exponent: i32) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let digits = value.unsigned_abs().to_string();
+    if exponent >= 0 {
+        if value == 0 {
+            return digits;
+        }
+        return format!("{}{}{}", sign, digits, "0".repeat(exponent as usize));
+    }
+
+    let decimals = exponent.unsigned_abs() as usize;
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    format!("{}{}.{}", sign, whole, fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+
+    // A SOL/USD price account in the mainnet layout: 19 publishers, exponent -8
+    fn fixture() -> Vec<u8> {
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/fixtures/pyth/sol_usd_price.json"))
+                .unwrap();
+        let encoded = fixture["account"]["data"][0].as_str().unwrap();
+        BASE64.decode(encoded).unwrap()
+    }
+
+    #[test]
+    fn test_decode_price_account() {
+        let data = fixture();
+        assert_eq!(data.len(), 3312);
+
+        let price = parse_pyth_price(&data).unwrap();
+        assert_eq!(price.raw_price, 14_523_456_789);
+        assert_eq!(price.exponent, -8);
+        assert_eq!(price.price, "145.23456789");
+        assert_eq!(price.raw_confidence, 7_891_234);
+        assert_eq!(price.confidence, "0.07891234");
+        assert_eq!(price.status, "trading");
+        assert!(price.trading);
+        assert_eq!(price.publish_slot, 298_765_432);
+        assert_eq!(price.valid_slot, 298_765_432);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&decode_pyth_price(&data).unwrap()).unwrap();
+        assert_eq!(json["price"], "145.23456789");
+        assert_eq!(json["publishSlot"], 298_765_432);
+    }
+
+    #[test]
+    fn test_non_trading_price_is_flagged() {
+        let mut data = fixture();
+        data[AGGREGATE_OFFSET + 16..AGGREGATE_OFFSET + 20].copy_from_slice(&2u32.to_le_bytes());
+
+        let price = parse_pyth_price(&data).unwrap();
+        assert_eq!(price.status, "halted");
+        assert!(!price.trading);
+        // The last trading price is still there, for callers that label it as such
+        assert_eq!(price.price, "145.23456789");
+    }
+
+    #[test]
+    fn test_rejects_other_accounts() {
+        let data = fixture();
+        assert!(parse_pyth_price(&data[..HEADER_LEN - 1]).is_err());
+
+        let mut wrong_magic = data.clone();
+        wrong_magic[0] ^= 1;
+        let error = parse_pyth_price(&wrong_magic).unwrap_err();
+        assert!(error.message.contains("Not a Pyth account"));
+
+        let mut wrong_version = data.clone();
+        wrong_version[4] = 1;
+        assert!(parse_pyth_price(&wrong_version).is_err());
+
+        // A product account
+        let mut product = data;
+        product[8] = 2;
+        assert!(parse_pyth_price(&product).is_err());
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(scale(14_523_456_789, -8), "145.23456789");
+        assert_eq!(scale(5, -3), "0.005");
+        assert_eq!(scale(-1234, -2), "-12.34");
+        assert_eq!(scale(-5, -3), "-0.005");
+        assert_eq!(scale(0, -2), "0.00");
+        assert_eq!(scale(42, 0), "42");
+        assert_eq!(scale(42, 3), "42000");
+        assert_eq!(scale(0, 3), "0");
+        assert_eq!(scale(i64::MIN as i128, -8), "-92233720368.54775808");
+    }
+}
@@ -1,10 +1,76 @@
 use crate::error::SolanaUnityError;
-use solana_sdk::pubkey::Pubkey;
+use crate::instruction::TokenInstructions;
+use serde::Deserialize;
+use solana_sdk::pubkey::{Pubkey, PubkeyError, MAX_SEEDS, MAX_SEED_LEN};
 use std::str::FromStr;
 
+/// A typed PDA seed that is converted to bytes before derivation
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum Seed {
+    Utf8(String),
+    Pubkey(String),
+    Hex(String),
+    U64Le(u64),
+    U8(u8),
+}
+
+impl Seed {
+    /// Converts the seed to the bytes used for derivation
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SolanaUnityError> {
+        match self {
+            Seed::Utf8(value) => Ok(value.as_bytes().to_vec()),
+            Seed::Pubkey(value) => Pubkey::from_str(value)
+                .map(|pubkey| pubkey.to_bytes().to_vec())
+                .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid pubkey seed: {}", e))),
+            Seed::Hex(value) => decode_hex(value),
+            Seed::U64Le(value) => Ok(value.to_le_bytes().to_vec()),
+            Seed::U8(value) => Ok(vec![*value]),
+        }
+    }
+}
+
+// Decodes a hex string, with or without a 0x prefix
+fn decode_hex(value: &str) -> Result<Vec<u8>, SolanaUnityError> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if !digits.len().is_multiple_of(2) {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Invalid hex seed: odd number of digits in '{}'",
+            value
+        )));
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            digits
+                .get(i..i + 2)
+                .filter(|pair| pair.chars().all(|c| c.is_ascii_hexdigit()))
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| {
+                    SolanaUnityError::InvalidInput(format!(
+                        "Invalid hex seed: bad digit at position {} in '{}'",
+                        i, value
+                    ))
+                })
+        })
+        .collect()
+}
+
 pub struct ProgramDerivedAddress {}
 
 impl ProgramDerivedAddress {
+    // Metaplex Token Metadata Program ID
+    pub const TOKEN_METADATA_PROGRAM_ID: &'static str =
+        "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+    // Address Lookup Table Program ID
+    pub const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: &'static str =
+        "AddressLookupTab1e1111111111111111111111111";
+
+    // Number of editions tracked by a single edition marker account
+    const EDITION_MARKER_BIT_SIZE: u64 = 248;
+
     /// Finds a program derived address and bump seed for the given seeds and program ID
     pub fn find_program_address(
         seeds: &[&[u8]],
@@ -33,10 +99,306 @@ impl ProgramDerivedAddress {
         Ok(address.to_string())
     }
 
+    /// Creates the program address for the given seeds with a specific bump appended
+    pub fn create_program_address_with_bump(
+        seeds: &[&[u8]],
+        bump: u8,
+        program_id: &str,
+    ) -> Result<String, SolanaUnityError> {
+        let bump_seed = [bump];
+        let mut seeds_with_bump = seeds.to_vec();
+        seeds_with_bump.push(&bump_seed);
+
+        Self::create_program_address(&seeds_with_bump, program_id)
+    }
+
+    /// Searches downward from `starting_bump` and returns the first valid program address
+    /// and its bump seed
+    pub fn try_find_from_bump(
+        seeds: &[&[u8]],
+        starting_bump: u8,
+        program_id: &str,
+    ) -> Result<(String, u8), SolanaUnityError> {
+        let program_pubkey = Pubkey::from_str(program_id)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid program ID: {}", e)))?;
+
+        for bump in (0..=starting_bump).rev() {
+            let bump_seed = [bump];
+            let mut seeds_with_bump = seeds.to_vec();
+            seeds_with_bump.push(&bump_seed);
+
+            match Pubkey::create_program_address(&seeds_with_bump, &program_pubkey) {
+                Ok(address) => return Ok((address.to_string(), bump)),
+                Err(PubkeyError::InvalidSeeds) => continue,
+                Err(e) => {
+                    return Err(SolanaUnityError::InvalidInput(format!(
+                        "Failed to create program address: {}",
+                        e
+                    )))
+                }
+            }
+        }
+
+        Err(SolanaUnityError::InvalidInput(format!(
+            "No valid program address found at or below bump {}",
+            starting_bump
+        )))
+    }
+
+    /// Finds program derived addresses and bump seeds for many seed sets in one pass.
+    /// Errors identify the index of the seed set that could not be derived.
+    pub fn find_program_addresses(
+        seed_sets: &[Vec<Vec<u8>>],
+        program_id: &str,
+    ) -> Result<Vec<(String, u8)>, SolanaUnityError> {
+        let program_pubkey = Pubkey::from_str(program_id)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid program ID: {}", e)))?;
+
+        let mut results = Vec::with_capacity(seed_sets.len());
+        for (index, seed_set) in seed_sets.iter().enumerate() {
+            // One seed slot is reserved for the bump
+            if seed_set.len() >= MAX_SEEDS {
+                return Err(SolanaUnityError::InvalidInput(format!(
+                    "Seed set {}: too many seeds ({}, max {})",
+                    index,
+                    seed_set.len(),
+                    MAX_SEEDS - 1
+                )));
+            }
+
+            if let Some(seed) = seed_set.iter().find(|seed| seed.len() > MAX_SEED_LEN) {
+                return Err(SolanaUnityError::InvalidInput(format!(
+                    "Seed set {}: seed of {} bytes exceeds max seed length {}",
+                    index,
+                    seed.len(),
+                    MAX_SEED_LEN
+                )));
+            }
+
+            let seeds: Vec<&[u8]> = seed_set.iter().map(|seed| seed.as_slice()).collect();
+            let (address, bump) = Pubkey::try_find_program_address(&seeds, &program_pubkey)
+                .ok_or_else(|| {
+                    SolanaUnityError::InvalidInput(format!(
+                        "Seed set {}: unable to find a viable bump seed",
+                        index
+                    ))
+                })?;
+
+            results.push((address.to_string(), bump));
+        }
+
+        Ok(results)
+    }
+
+    /// Checks that an address is the canonical program derived address for the given seeds
+    /// and program, the one `find_program_address` returns. Addresses derived from any
+    /// other bump do not match; use `validate_with_bump` when the bump is known.
+    pub fn validate(
+        address: &str,
+        seeds: &[&[u8]],
+        program_id: &str,
+    ) -> Result<bool, SolanaUnityError> {
+        let (address_pubkey, program_pubkey) = Self::parse_validation_keys(address, program_id)?;
+        Ok(Pubkey::try_find_program_address(seeds, &program_pubkey)
+            .is_some_and(|(canonical, _)| canonical == address_pubkey))
+    }
+
+    /// Checks that an address is the program derived address for the given seeds with
+    /// `bump` appended, and no other bump
+    pub fn validate_with_bump(
+        address: &str,
+        seeds: &[&[u8]],
+        bump: u8,
+        program_id: &str,
+    ) -> Result<bool, SolanaUnityError> {
+        let (address_pubkey, program_pubkey) = Self::parse_validation_keys(address, program_id)?;
+        let bump_seed = [bump];
+        let mut seeds_with_bump = seeds.to_vec();
+        seeds_with_bump.push(&bump_seed);
+
+        // Addresses on the ed25519 curve are never derived, so they fail here too
+        Ok(
+            Pubkey::create_program_address(&seeds_with_bump, &program_pubkey)
+                .is_ok_and(|derived| derived == address_pubkey),
+        )
+    }
+
+    fn parse_validation_keys(
+        address: &str,
+        program_id: &str,
+    ) -> Result<(Pubkey, Pubkey), SolanaUnityError> {
+        let address_pubkey = Pubkey::from_str(address)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid address: {}", e)))?;
+        let program_pubkey = Pubkey::from_str(program_id)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid program ID: {}", e)))?;
+        Ok((address_pubkey, program_pubkey))
+    }
+
+    /// Finds a program derived address from typed seeds
+    pub fn find_with_seed_spec(
+        seeds: &[Seed],
+        program_id: &str,
+    ) -> Result<(String, u8), SolanaUnityError> {
+        let seed_bytes = seeds
+            .iter()
+            .map(|seed| seed.to_bytes())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut results = Self::find_program_addresses(&[seed_bytes], program_id)?;
+        Ok(results.remove(0))
+    }
+
+    /// Finds a program derived address from a JSON array of typed seeds, e.g.
+    /// `[{"kind":"utf8","value":"player"},{"kind":"u64le","value":42}]`
+    pub fn find_with_seed_spec_json(
+        seeds_json: &str,
+        program_id: &str,
+    ) -> Result<(String, u8), SolanaUnityError> {
+        let seeds: Vec<Seed> = serde_json::from_str(seeds_json).map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Invalid seed specification: {}", e))
+        })?;
+
+        Self::find_with_seed_spec(&seeds, program_id)
+    }
+
+    /// Returns whether the address lies on the ed25519 curve. Wallet addresses are on the
+    /// curve, program derived addresses are not.
+    pub fn is_on_curve(pubkey: &str) -> Result<bool, SolanaUnityError> {
+        let pubkey = Pubkey::from_str(pubkey)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid pubkey: {}", e)))?;
+
+        Ok(pubkey.is_on_curve())
+    }
+
+    /// Derives the address lookup table address and bump seed for an authority and the
+    /// recent slot passed to the create-lookup-table instruction
+    pub fn derive_lookup_table_address(
+        authority: &str,
+        recent_slot: u64,
+    ) -> Result<(String, u8), SolanaUnityError> {
+        let authority_pubkey = Pubkey::from_str(authority)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid authority: {}", e)))?;
+
+        let lookup_table_program_id =
+            Pubkey::from_str(Self::ADDRESS_LOOKUP_TABLE_PROGRAM_ID).unwrap();
+
+        let slot_bytes = recent_slot.to_le_bytes();
+        let seeds = &[authority_pubkey.as_ref(), slot_bytes.as_ref()];
+
+        let (address, bump) = Pubkey::find_program_address(seeds, &lookup_table_program_id);
+
+        Ok((address.to_string(), bump))
+    }
+
+    /// Finds a program derived address from a seed template such as
+    /// `player:{wallet_pubkey}:inventory:{slot_u64}`. Literal segments become UTF-8 seeds and
+    /// placeholders are resolved from a JSON object of bindings, keyed either by the full
+    /// placeholder name or by the name without its type suffix. The suffix (`_u64`, `_u8`,
+    /// `_pubkey`) selects the byte conversion; placeholders without one are UTF-8.
+    pub fn find_from_template(
+        template: &str,
+        bindings_json: &str,
+        program_id: &str,
+    ) -> Result<(String, u8), SolanaUnityError> {
+        let bindings: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(bindings_json).map_err(|e| {
+                SolanaUnityError::InvalidInput(format!("Invalid template bindings: {}", e))
+            })?;
+
+        let mut seeds = Vec::new();
+        for segment in template.split(':') {
+            let placeholder = match segment
+                .strip_prefix('{')
+                .and_then(|rest| rest.strip_suffix('}'))
+            {
+                Some(placeholder) => placeholder,
+                None => {
+                    if segment.contains('{') || segment.contains('}') {
+                        return Err(SolanaUnityError::InvalidInput(format!(
+                            "Malformed template segment '{}'",
+                            segment
+                        )));
+                    }
+                    seeds.push(Seed::Utf8(segment.to_string()));
+                    continue;
+                }
+            };
+
+            let (name, kind) = if let Some(name) = placeholder.strip_suffix("_u64") {
+                (name, "u64")
+            } else if let Some(name) = placeholder.strip_suffix("_u8") {
+                (name, "u8")
+            } else if let Some(name) = placeholder.strip_suffix("_pubkey") {
+                (name, "pubkey")
+            } else {
+                (placeholder, "utf8")
+            };
+
+            let value = bindings
+                .get(placeholder)
+                .or_else(|| bindings.get(name))
+                .ok_or_else(|| {
+                    SolanaUnityError::InvalidInput(format!(
+                        "Unresolved template placeholder '{{{}}}'",
+                        placeholder
+                    ))
+                })?;
+
+            let mismatch = || {
+                SolanaUnityError::InvalidInput(format!(
+                    "Template placeholder '{{{}}}' expects a {} value, got {}",
+                    placeholder, kind, value
+                ))
+            };
+
+            let seed = match kind {
+                "u64" => Seed::U64Le(match value {
+                    serde_json::Value::String(s) => s.parse().map_err(|_| mismatch())?,
+                    _ => value.as_u64().ok_or_else(mismatch)?,
+                }),
+                "u8" => Seed::U8(match value {
+                    serde_json::Value::String(s) => s.parse().map_err(|_| mismatch())?,
+                    _ => value
+                        .as_u64()
+                        .and_then(|v| u8::try_from(v).ok())
+                        .ok_or_else(mismatch)?,
+                }),
+                "pubkey" => {
+                    let pubkey = value.as_str().ok_or_else(mismatch)?;
+                    Pubkey::from_str(pubkey).map_err(|_| mismatch())?;
+                    Seed::Pubkey(pubkey.to_string())
+                }
+                _ => match value {
+                    serde_json::Value::String(s) => Seed::Utf8(s.clone()),
+                    serde_json::Value::Number(n) => Seed::Utf8(n.to_string()),
+                    _ => return Err(mismatch()),
+                },
+            };
+
+            seeds.push(seed);
+        }
+
+        Self::find_with_seed_spec(&seeds, program_id)
+    }
+
     /// Finds an associated token account address for a wallet address and token mint
     pub fn find_associated_token_address(
         wallet_address: &str,
         token_mint: &str,
+    ) -> Result<String, SolanaUnityError> {
+        Self::find_associated_token_address_with_program(
+            wallet_address,
+            token_mint,
+            TokenInstructions::TOKEN_PROGRAM_ID,
+        )
+    }
+
+    /// Finds an associated token account address owned by the given token program
+    /// (e.g. Token-2022). Any parseable program ID is accepted.
+    pub fn find_associated_token_address_with_program(
+        wallet_address: &str,
+        token_mint: &str,
+        token_program: &str,
     ) -> Result<String, SolanaUnityError> {
         let wallet_pubkey = Pubkey::from_str(wallet_address).map_err(|e| {
             SolanaUnityError::InvalidInput(format!("Invalid wallet address: {}", e))
@@ -45,13 +407,12 @@ impl ProgramDerivedAddress {
         let token_mint_pubkey = Pubkey::from_str(token_mint)
             .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid token mint: {}", e)))?;
 
-        // SPL Token Program ID
-        let token_program_id =
-            Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        let token_program_id = Pubkey::from_str(token_program)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid token program: {}", e)))?;
 
         // Associated Token Program ID
         let associated_token_program_id =
-            Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+            Pubkey::from_str(TokenInstructions::ASSOCIATED_TOKEN_PROGRAM_ID).unwrap();
 
         let seeds = &[
             wallet_pubkey.as_ref(),
@@ -63,11 +424,95 @@ impl ProgramDerivedAddress {
 
         Ok(address.to_string())
     }
+
+    /// Like `find_associated_token_address_with_program`, but rejects token programs other
+    /// than SPL Token and Token-2022
+    pub fn find_associated_token_address_strict(
+        wallet_address: &str,
+        token_mint: &str,
+        token_program: &str,
+    ) -> Result<String, SolanaUnityError> {
+        if token_program != TokenInstructions::TOKEN_PROGRAM_ID
+            && token_program != TokenInstructions::TOKEN_2022_PROGRAM_ID
+        {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Unrecognized token program: {}",
+                token_program
+            )));
+        }
+
+        Self::find_associated_token_address_with_program(wallet_address, token_mint, token_program)
+    }
+
+    /// Finds the Metaplex metadata account address and bump seed for a token mint
+    pub fn find_metadata_address(mint: &str) -> Result<(String, u8), SolanaUnityError> {
+        let mint_pubkey = Pubkey::from_str(mint)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid mint: {}", e)))?;
+
+        let metadata_program_id = Pubkey::from_str(Self::TOKEN_METADATA_PROGRAM_ID).unwrap();
+
+        let seeds = &[
+            b"metadata".as_ref(),
+            metadata_program_id.as_ref(),
+            mint_pubkey.as_ref(),
+        ];
+
+        let (address, bump) = Pubkey::find_program_address(seeds, &metadata_program_id);
+
+        Ok((address.to_string(), bump))
+    }
+
+    /// Finds the Metaplex master edition account address and bump seed for a token mint
+    pub fn find_master_edition_address(mint: &str) -> Result<(String, u8), SolanaUnityError> {
+        let mint_pubkey = Pubkey::from_str(mint)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid mint: {}", e)))?;
+
+        let metadata_program_id = Pubkey::from_str(Self::TOKEN_METADATA_PROGRAM_ID).unwrap();
+
+        let seeds = &[
+            b"metadata".as_ref(),
+            metadata_program_id.as_ref(),
+            mint_pubkey.as_ref(),
+            b"edition".as_ref(),
+        ];
+
+        let (address, bump) = Pubkey::find_program_address(seeds, &metadata_program_id);
+
+        Ok((address.to_string(), bump))
+    }
+
+    /// Finds the Metaplex edition marker account address and bump seed for a printed edition.
+    /// Each marker tracks 248 editions, so the final seed is the edition number divided by 248
+    /// rendered as a decimal string.
+    pub fn find_edition_marker_address(
+        mint: &str,
+        edition: u64,
+    ) -> Result<(String, u8), SolanaUnityError> {
+        let mint_pubkey = Pubkey::from_str(mint)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid mint: {}", e)))?;
+
+        let metadata_program_id = Pubkey::from_str(Self::TOKEN_METADATA_PROGRAM_ID).unwrap();
+
+        let edition_bucket = (edition / Self::EDITION_MARKER_BIT_SIZE).to_string();
+
+        let seeds = &[
+            b"metadata".as_ref(),
+            metadata_program_id.as_ref(),
+            mint_pubkey.as_ref(),
+            b"edition".as_ref(),
+            edition_bucket.as_bytes(),
+        ];
+
+        let (address, bump) = Pubkey::find_program_address(seeds, &metadata_program_id);
+
+        Ok((address.to_string(), bump))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ErrorKind;
     use solana_sdk::pubkey::Pubkey;
 
     #[test]
@@ -82,8 +527,10 @@ mod tests {
         assert!(result.is_ok());
 
         let (address, bump) = result.unwrap();
-        assert!(!address.is_empty());
-        assert!(bump <= 255);
+        let (expected_address, expected_bump) =
+            Pubkey::find_program_address(seeds, &Pubkey::from_str(program_id).unwrap());
+        assert_eq!(address, expected_address.to_string());
+        assert_eq!(bump, expected_bump);
     }
 
     #[test]
@@ -122,4 +569,430 @@ mod tests {
         let address = result.unwrap();
         assert!(!address.is_empty());
     }
+
+    #[test]
+    fn test_find_metadata_address() {
+        // USDC mint and its metadata account on mainnet
+        let mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+        let (address, bump) = ProgramDerivedAddress::find_metadata_address(mint).unwrap();
+        assert_eq!(address, "5x38Kp4hvdomTCnCrAny4UtMUt5rQBdB6px2K1Ui45Wq");
+        assert_eq!(bump, 255);
+
+        let result = ProgramDerivedAddress::find_metadata_address("not-a-valid-mint");
+        match result {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput error for invalid mint"),
+        }
+    }
+
+    #[test]
+    fn test_find_master_edition_address() {
+        let mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+        let (address, bump) = ProgramDerivedAddress::find_master_edition_address(mint).unwrap();
+        assert_eq!(address, "A7FGB2kzjpDPRLMeqRLgW9XZ3JQ2RYRL4w5kUZv64ZB");
+        assert_eq!(bump, 252);
+    }
+
+    #[test]
+    fn test_find_edition_marker_address() {
+        let mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+        // Editions 0 through 247 share the first marker ("0")
+        let first_marker = (
+            "D6qU7Y8AJHZuQqZLvKW75ZkSjHatVpDUVrrHxMbGeKoC".to_string(),
+            255,
+        );
+        assert_eq!(
+            ProgramDerivedAddress::find_edition_marker_address(mint, 0).unwrap(),
+            first_marker
+        );
+        assert_eq!(
+            ProgramDerivedAddress::find_edition_marker_address(mint, 247).unwrap(),
+            first_marker
+        );
+
+        // Edition 248 rolls over to marker "1"
+        assert_eq!(
+            ProgramDerivedAddress::find_edition_marker_address(mint, 248).unwrap(),
+            (
+                "6JQi7YLxAZ42FAx1KBJQappuMDruVVMFRxfiMbZnczZm".to_string(),
+                255
+            )
+        );
+
+        // Edition 1000 lives in marker "4"
+        assert_eq!(
+            ProgramDerivedAddress::find_edition_marker_address(mint, 1000).unwrap(),
+            (
+                "5cPdvrqwSe95PbRXVvcz5Vxsf63piHRJTX1UMY8F7Jhv".to_string(),
+                253
+            )
+        );
+    }
+
+    #[test]
+    fn test_find_associated_token_address_with_program() {
+        let wallet = Pubkey::new_unique().to_string();
+        let mint = Pubkey::new_unique().to_string();
+
+        let legacy = ProgramDerivedAddress::find_associated_token_address_with_program(
+            &wallet,
+            &mint,
+            TokenInstructions::TOKEN_PROGRAM_ID,
+        )
+        .unwrap();
+        let token_2022 = ProgramDerivedAddress::find_associated_token_address_with_program(
+            &wallet,
+            &mint,
+            TokenInstructions::TOKEN_2022_PROGRAM_ID,
+        )
+        .unwrap();
+
+        // The default derivation uses the legacy token program
+        let default = ProgramDerivedAddress::find_associated_token_address(&wallet, &mint).unwrap();
+        assert_eq!(default, legacy);
+        assert_ne!(legacy, token_2022);
+
+        // Unknown programs derive as asked unless strict mode is used
+        let other_program = Pubkey::new_unique().to_string();
+        let result = ProgramDerivedAddress::find_associated_token_address_with_program(
+            &wallet,
+            &mint,
+            &other_program,
+        );
+        assert!(result.is_ok());
+
+        let result = ProgramDerivedAddress::find_associated_token_address_strict(
+            &wallet,
+            &mint,
+            &other_program,
+        );
+        match result {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput error for unrecognized token program"),
+        }
+
+        let strict = ProgramDerivedAddress::find_associated_token_address_strict(
+            &wallet,
+            &mint,
+            TokenInstructions::TOKEN_2022_PROGRAM_ID,
+        )
+        .unwrap();
+        assert_eq!(strict, token_2022);
+    }
+
+    #[test]
+    fn test_find_program_addresses_batch() {
+        let program_id = Pubkey::new_unique().to_string();
+
+        let seed_sets: Vec<Vec<Vec<u8>>> = (0..100u64)
+            .map(|i| {
+                vec![
+                    b"player".to_vec(),
+                    Pubkey::new_unique().to_bytes().to_vec(),
+                    i.to_le_bytes().to_vec(),
+                ]
+            })
+            .collect();
+
+        let results =
+            ProgramDerivedAddress::find_program_addresses(&seed_sets, &program_id).unwrap();
+        assert_eq!(results.len(), seed_sets.len());
+
+        for (seed_set, result) in seed_sets.iter().zip(results.iter()) {
+            let seeds: Vec<&[u8]> = seed_set.iter().map(|seed| seed.as_slice()).collect();
+            let expected =
+                ProgramDerivedAddress::find_program_address(&seeds, &program_id).unwrap();
+            assert_eq!(result, &expected);
+        }
+    }
+
+    #[test]
+    fn test_find_program_addresses_reports_failing_index() {
+        let program_id = Pubkey::new_unique().to_string();
+
+        let seed_sets = vec![
+            vec![b"ok".to_vec()],
+            vec![b"ok".to_vec()],
+            vec![vec![0u8; MAX_SEED_LEN + 1]],
+        ];
+
+        match ProgramDerivedAddress::find_program_addresses(&seed_sets, &program_id) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message,
+                ..
+            }) => {
+                assert!(message.starts_with("Seed set 2:"), "{}", message);
+            }
+            _ => panic!("Expected InvalidInput error for oversized seed"),
+        }
+    }
+
+    #[test]
+    fn test_validate_pda() {
+        let program_id = Pubkey::new_unique().to_string();
+        let owner = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault", owner.as_ref()];
+
+        let (address, bump) =
+            ProgramDerivedAddress::find_program_address(seeds, &program_id).unwrap();
+        assert!(ProgramDerivedAddress::validate(&address, seeds, &program_id).unwrap());
+
+        assert!(
+            ProgramDerivedAddress::validate_with_bump(&address, seeds, bump, &program_id).unwrap()
+        );
+
+        // An address from a lower, non-canonical bump is only accepted for that bump
+        let (other_address, other_bump) =
+            ProgramDerivedAddress::try_find_from_bump(seeds, bump - 1, &program_id).unwrap();
+        assert!(!ProgramDerivedAddress::validate(&other_address, seeds, &program_id).unwrap());
+        assert!(ProgramDerivedAddress::validate_with_bump(
+            &other_address,
+            seeds,
+            other_bump,
+            &program_id
+        )
+        .unwrap());
+        assert!(!ProgramDerivedAddress::validate_with_bump(
+            &other_address,
+            seeds,
+            bump,
+            &program_id
+        )
+        .unwrap());
+        assert!(!ProgramDerivedAddress::validate_with_bump(
+            &address,
+            seeds,
+            other_bump,
+            &program_id
+        )
+        .unwrap());
+
+        // Same seeds under another program do not match
+        let other_program = Pubkey::new_unique().to_string();
+        assert!(!ProgramDerivedAddress::validate(&address, seeds, &other_program).unwrap());
+
+        // A wallet address is on the curve and can never be a PDA
+        let wallet = solana_sdk::signature::Keypair::new();
+        let wallet_address = solana_sdk::signer::Signer::pubkey(&wallet).to_string();
+        assert!(!ProgramDerivedAddress::validate(&wallet_address, seeds, &program_id).unwrap());
+
+        match ProgramDerivedAddress::validate("not-a-valid-address", seeds, &program_id) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput error for invalid address"),
+        }
+    }
+
+    #[test]
+    fn test_find_with_seed_spec() {
+        let program_id = Pubkey::new_unique().to_string();
+        let player = Pubkey::new_unique();
+
+        let seeds = vec![
+            Seed::Utf8("player".to_string()),
+            Seed::Pubkey(player.to_string()),
+            Seed::U64Le(42),
+            Seed::U8(7),
+            Seed::Hex("0xdeadbeef".to_string()),
+        ];
+
+        let index = 42u64.to_le_bytes();
+        let manual: &[&[u8]] = &[
+            b"player",
+            player.as_ref(),
+            &index,
+            &[7],
+            &[0xde, 0xad, 0xbe, 0xef],
+        ];
+        let expected = ProgramDerivedAddress::find_program_address(manual, &program_id).unwrap();
+
+        let result = ProgramDerivedAddress::find_with_seed_spec(&seeds, &program_id).unwrap();
+        assert_eq!(result, expected);
+
+        let json = format!(
+            r#"[{{"kind":"utf8","value":"player"}},{{"kind":"pubkey","value":"{}"}},{{"kind":"u64le","value":42}},{{"kind":"u8","value":7}},{{"kind":"hex","value":"deadbeef"}}]"#,
+            player
+        );
+        let result = ProgramDerivedAddress::find_with_seed_spec_json(&json, &program_id).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_find_with_seed_spec_invalid_seeds() {
+        let program_id = Pubkey::new_unique().to_string();
+
+        let invalid_seeds = [
+            Seed::Pubkey("not-a-valid-pubkey".to_string()),
+            Seed::Hex("abc".to_string()),
+            Seed::Hex("zz".to_string()),
+        ];
+
+        for seed in invalid_seeds {
+            match ProgramDerivedAddress::find_with_seed_spec(&[seed.clone()], &program_id) {
+                Err(SolanaUnityError {
+                    kind: ErrorKind::InvalidInput,
+                    ..
+                }) => {}
+                _ => panic!("Expected InvalidInput error for seed: {:?}", seed),
+            }
+        }
+
+        let result = ProgramDerivedAddress::find_with_seed_spec_json(
+            r#"[{"kind":"float","value":1.5}]"#,
+            &program_id,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_is_on_curve() {
+        let wallet = solana_sdk::signature::Keypair::new();
+        let wallet_address = solana_sdk::signer::Signer::pubkey(&wallet).to_string();
+        assert!(ProgramDerivedAddress::is_on_curve(&wallet_address).unwrap());
+
+        let program_id = Pubkey::new_unique().to_string();
+        let (pda, _) =
+            ProgramDerivedAddress::find_program_address(&[b"vault"], &program_id).unwrap();
+        assert!(!ProgramDerivedAddress::is_on_curve(&pda).unwrap());
+
+        match ProgramDerivedAddress::is_on_curve("not-a-valid-pubkey") {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput error for invalid pubkey"),
+        }
+    }
+
+    #[test]
+    fn test_derive_lookup_table_address() {
+        let authority = Pubkey::from_str("GsbwXfJraMomNxBcjK7tY82aT7ZUJNf6BA9wRx4GfDHP").unwrap();
+        let recent_slot = 250_000_000u64;
+
+        let (expected_address, expected_bump) =
+            solana_sdk::address_lookup_table::instruction::derive_lookup_table_address(
+                &authority,
+                recent_slot,
+            );
+
+        let (address, bump) =
+            ProgramDerivedAddress::derive_lookup_table_address(&authority.to_string(), recent_slot)
+                .unwrap();
+        assert_eq!(address, expected_address.to_string());
+        assert_eq!(bump, expected_bump);
+
+        assert_eq!(
+            ProgramDerivedAddress::ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+            solana_sdk::address_lookup_table::program::id().to_string()
+        );
+    }
+
+    #[test]
+    fn test_create_program_address_with_bump() {
+        let program_id = Pubkey::new_unique().to_string();
+        let owner = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault", owner.as_ref()];
+
+        let (address, bump) =
+            ProgramDerivedAddress::find_program_address(seeds, &program_id).unwrap();
+
+        // The canonical bump reproduces the same address through the with-bump path
+        let with_bump =
+            ProgramDerivedAddress::create_program_address_with_bump(seeds, bump, &program_id)
+                .unwrap();
+        assert_eq!(with_bump, address);
+
+        // Searching from the top finds the canonical bump
+        let found = ProgramDerivedAddress::try_find_from_bump(seeds, u8::MAX, &program_id).unwrap();
+        assert_eq!(found, (address.clone(), bump));
+
+        // Searching below the canonical bump finds a different, non-canonical address
+        if bump > 0 {
+            if let Ok((other_address, other_bump)) =
+                ProgramDerivedAddress::try_find_from_bump(seeds, bump - 1, &program_id)
+            {
+                assert!(other_bump < bump);
+                assert_ne!(other_address, address);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_from_template() {
+        let program_id = Pubkey::new_unique().to_string();
+        let wallet = Pubkey::new_unique();
+
+        let bindings = format!(r#"{{"wallet": "{}", "slot": 3, "name": "sword"}}"#, wallet);
+        let result = ProgramDerivedAddress::find_from_template(
+            "player:{wallet_pubkey}:inventory:{slot_u64}:{name}",
+            &bindings,
+            &program_id,
+        )
+        .unwrap();
+
+        let slot = 3u64.to_le_bytes();
+        let manual: &[&[u8]] = &[b"player", wallet.as_ref(), b"inventory", &slot, b"sword"];
+        let expected = ProgramDerivedAddress::find_program_address(manual, &program_id).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_find_from_template_errors() {
+        let program_id = Pubkey::new_unique().to_string();
+
+        // Unresolved placeholder
+        match ProgramDerivedAddress::find_from_template("player:{slot_u64}", "{}", &program_id) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message,
+                ..
+            }) => {
+                assert!(message.contains("{slot_u64}"), "{}", message);
+            }
+            _ => panic!("Expected InvalidInput error for unresolved placeholder"),
+        }
+
+        // Type mismatch
+        match ProgramDerivedAddress::find_from_template(
+            "player:{slot_u64}",
+            r#"{"slot": "not-a-number"}"#,
+            &program_id,
+        ) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message,
+                ..
+            }) => {
+                assert!(message.contains("{slot_u64}"), "{}", message);
+            }
+            _ => panic!("Expected InvalidInput error for type mismatch"),
+        }
+
+        match ProgramDerivedAddress::find_from_template(
+            "owner:{owner_pubkey}",
+            r#"{"owner": "not-a-valid-pubkey"}"#,
+            &program_id,
+        ) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message,
+                ..
+            }) => {
+                assert!(message.contains("{owner_pubkey}"), "{}", message);
+            }
+            _ => panic!("Expected InvalidInput error for invalid pubkey binding"),
+        }
+    }
 }
@@ -0,0 +1,440 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::envelope::to_envelope;
+use crate::error::SolanaUnityError;
+use crate::logging::LOG_ERROR;
+use crate::native_log;
+use crate::runtime;
+
+type Job = Box<dyn FnOnce() -> Result<Value, SolanaUnityError> + Send>;
+
+/// State of a queued request as seen by `poll`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestStatus {
+    /// Still queued or running
+    Pending,
+    /// Finished successfully, an envelope with `"ok":true`
+    Complete(String),
+    /// Finished with an error, an envelope with `"ok":false`
+    Failed(String),
+    /// Never issued, cancelled, or already retrieved
+    Unknown,
+}
+
+enum Entry {
+    Pending,
+    Done { ok: bool, envelope: String },
+}
+
+struct Requests {
+    next_id: u64,
+    entries: BTreeMap<u64, Entry>,
+}
+
+static REQUESTS: Mutex<Requests> = Mutex::new(Requests {
+    next_id: 1,
+    entries: BTreeMap::new(),
+});
+
+type Message = (u64, &'static str, Job);
+
+struct Pool {
+    sender: Sender<Message>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+// Created by `start` and torn down by `stop`, both driven by runtime::init and
+// runtime::shutdown
+static POOL: Mutex<Option<Pool>> = Mutex::new(None);
+
+fn requests() -> MutexGuard<'static, Requests> {
+    REQUESTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn pool() -> MutexGuard<'static, Option<Pool>> {
+    POOL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Queues `job` on the worker pool and returns its request id. Ids start at 1 and are
+/// never reused. `method` names the request in the result envelope. Fails if the library
+/// has not been initialized with `runtime::init`.
+pub fn submit<F>(method: &'static str, job: F) -> Result<u64, SolanaUnityError>
+where
+    F: FnOnce() -> Result<Value, SolanaUnityError> + Send + 'static,
+{
+    // Held until the job is queued so a concurrent stop cannot miss it
+    let pool = pool();
+    let pool = pool.as_ref().ok_or_else(runtime::not_initialized)?;
+
+    let id = {
+        let mut requests = requests();
+        let id = requests.next_id;
+        requests.next_id += 1;
+        requests.entries.insert(id, Entry::Pending);
+        id
+    };
+
+    if pool.sender.send((id, method, Box::new(job))).is_err() {
+        requests().entries.remove(&id);
+        return Err(SolanaUnityError::FfiError(
+            "Request workers have stopped".to_string(),
+        ));
+    }
+
+    Ok(id)
+}
+
+/// Runs `job` on the worker pool and waits up to `timeout` for its result. When the
+/// deadline passes the request is cancelled and `Timeout` returned: a queued job never
+/// runs, and a running one is left to finish on its worker with its result dropped.
+pub fn run_with_timeout<T, F>(timeout: Duration, job: F) -> Result<T, SolanaUnityError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, SolanaUnityError> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let id = submit("timeout", move || {
+        // The caller is gone if it already timed out
+        let _ = sender.send(job());
+        Ok(Value::Null)
+    })?;
+
+    let result = receiver.recv_timeout(timeout);
+    // The result travels over the channel, so the entry is never polled. The worker
+    // skips storing it once it is gone, whichever side gets there first.
+    cancel(id);
+    match result {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(SolanaUnityError::Timeout(
+            timeout.as_millis().try_into().unwrap_or(u64::MAX),
+        )),
+        // The job panicked or the pool was stopped before it ran
+        Err(RecvTimeoutError::Disconnected) => Err(SolanaUnityError::FfiError(
+            "Request did not complete".to_string(),
+        )),
+    }
+}
+
+/// Starts `workers` worker threads. Does nothing if the pool is already running.
+pub(crate) fn start(workers: usize) -> Result<(), SolanaUnityError> {
+    let mut pool = pool();
+    if pool.is_some() {
+        return Ok(());
+    }
+
+    let (sender, receiver) = mpsc::channel::<Message>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let mut handles = Vec::with_capacity(workers);
+    for index in 0..workers {
+        let receiver = Arc::clone(&receiver);
+        let name = format!("solana-unity-request-{}", index);
+        match runtime::spawn_thread(&name, move || run_worker(&receiver)) {
+            Ok(handle) => handles.push(handle),
+            Err(e) => {
+                // Dropping the sender lets the workers that did start exit
+                drop(sender);
+                join_until(handles, Instant::now() + Duration::from_secs(1));
+                return Err(e);
+            }
+        }
+    }
+
+    *pool = Some(Pool {
+        sender,
+        workers: handles,
+    });
+    Ok(())
+}
+
+/// Stops the pool: every queued and running request is cancelled, and the workers are
+/// given until `timeout` to exit. Returns false if a worker was still busy with a request
+/// at the deadline; that thread is left to finish on its own and its result is dropped.
+pub(crate) fn stop(timeout: Duration) -> bool {
+    let pool = match pool().take() {
+        Some(pool) => pool,
+        None => return true,
+    };
+
+    requests().entries.clear();
+    // Closing the channel ends each worker's loop once it is idle
+    drop(pool.sender);
+    join_until(pool.workers, Instant::now() + timeout)
+}
+
+fn join_until(workers: Vec<JoinHandle<()>>, deadline: Instant) -> bool {
+    let mut workers = workers;
+    loop {
+        let (finished, running): (Vec<_>, Vec<_>) =
+            workers.into_iter().partition(|worker| worker.is_finished());
+        for worker in finished {
+            let _ = worker.join();
+        }
+        if running.is_empty() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        workers = running;
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn run_worker(receiver: &Mutex<Receiver<Message>>) {
+    loop {
+        let message = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+        let (id, method, job) = match message {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+
+        // Skip requests that were cancelled while queued
+        if !requests().entries.contains_key(&id) {
+            continue;
+        }
+
+        let result = match panic::catch_unwind(AssertUnwindSafe(job)) {
+            Ok(result) => result,
+            Err(payload) => {
+                let reason = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                native_log!(
+                    LOG_ERROR,
+                    "Request {} ({}) panicked: {}",
+                    id,
+                    method,
+                    reason
+                );
+                Err(SolanaUnityError::FfiError("Request panicked".to_string()))
+            }
+        };
+        let ok = result.is_ok();
+        let envelope = to_envelope(method, result).to_string();
+
+        // A request cancelled while running has no entry any more and its result is
+        // dropped here
+        if let Some(entry) = requests().entries.get_mut(&id) {
+            *entry = Entry::Done { ok, envelope };
+        }
+    }
+}
+
+/// Returns the state of a request. A finished result is handed out exactly once; the
+/// entry is removed as it is returned.
+pub fn poll(id: u64) -> RequestStatus {
+    let mut requests = requests();
+
+    match requests.entries.get(&id) {
+        None => return RequestStatus::Unknown,
+        Some(Entry::Pending) => return RequestStatus::Pending,
+        Some(Entry::Done { .. }) => {}
+    }
+
+    match requests.entries.remove(&id) {
+        Some(Entry::Done { ok: true, envelope }) => RequestStatus::Complete(envelope),
+        Some(Entry::Done {
+            ok: false,
+            envelope,
+        }) => RequestStatus::Failed(envelope),
+        _ => RequestStatus::Unknown,
+    }
+}
+
+/// Forgets a request. A queued request is never run; a running one finishes but its result
+/// is discarded. Returns false for unknown ids.
+pub fn cancel(id: u64) -> bool {
+    requests().entries.remove(&id).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::runtime::RuntimeConfig;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::RwLockWriteGuard;
+
+    // These tests look at the whole queue and rely on there being one worker, so each has
+    // the runtime to itself, restarted with the default config
+    fn fresh_runtime() -> RwLockWriteGuard<'static, ()> {
+        let guard = runtime::exclusive_test_runtime();
+        runtime::shutdown();
+        runtime::init(RuntimeConfig::default()).unwrap();
+        guard
+    }
+
+    fn wait_for(id: u64) -> RequestStatus {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            match poll(id) {
+                RequestStatus::Pending if Instant::now() < deadline => {
+                    thread::sleep(Duration::from_millis(5))
+                }
+                status => return status,
+            }
+        }
+    }
+
+    // Submits a job that blocks the worker until the returned sender is used or dropped,
+    // and waits for the worker to pick it up
+    fn blocking_job() -> (u64, Sender<()>) {
+        let (release, wait): (Sender<()>, Receiver<()>) = mpsc::channel();
+        let (started, running) = mpsc::channel();
+        let id = submit("test", move || {
+            let _ = started.send(());
+            let _ = wait.recv();
+            Ok(Value::Null)
+        })
+        .unwrap();
+        running.recv_timeout(Duration::from_secs(10)).unwrap();
+        (id, release)
+    }
+
+    #[test]
+    fn test_results_are_retrievable_once() {
+        let _runtime = fresh_runtime();
+        let ids: Vec<u64> = (0..5u64)
+            .map(|i| submit("getBalance", move || Ok(json!(i * 10))).unwrap())
+            .collect();
+        let failing = submit("getBalance", || {
+            Err(SolanaUnityError::RpcError("node unavailable".to_string()))
+        })
+        .unwrap();
+
+        for (i, id) in ids.iter().enumerate() {
+            let expected = to_envelope("getBalance", Ok(i as u64 * 10)).to_string();
+            assert_eq!(wait_for(*id), RequestStatus::Complete(expected));
+            assert_eq!(poll(*id), RequestStatus::Unknown);
+        }
+
+        match wait_for(failing) {
+            RequestStatus::Failed(json) => {
+                let envelope: Value = serde_json::from_str(&json).unwrap();
+                assert_eq!(envelope["ok"], false);
+                assert_eq!(envelope["error"]["kind"], "RpcError");
+                assert!(json.contains("node unavailable"));
+            }
+            status => panic!("Expected a failed request, got {:?}", status),
+        }
+        assert_eq!(poll(failing), RequestStatus::Unknown);
+    }
+
+    #[test]
+    fn test_unknown_ids() {
+        assert_eq!(poll(0), RequestStatus::Unknown);
+        assert_eq!(poll(u64::MAX), RequestStatus::Unknown);
+        assert!(!cancel(u64::MAX));
+    }
+
+    #[test]
+    fn test_cancel_queued_and_running_requests() {
+        let _runtime = fresh_runtime();
+        let ran = Arc::new(AtomicBool::new(false));
+        let (running, release) = blocking_job();
+        let queued = {
+            let ran = Arc::clone(&ran);
+            submit("test", move || {
+                ran.store(true, Ordering::SeqCst);
+                Ok(Value::Null)
+            })
+            .unwrap()
+        };
+
+        assert_eq!(poll(queued), RequestStatus::Pending);
+        assert!(cancel(queued));
+        assert!(cancel(running));
+        assert!(!cancel(running));
+        drop(release);
+
+        // Both ids stay unknown once the worker has moved past them
+        let after = submit("test", || Ok(Value::Bool(true))).unwrap();
+        assert!(matches!(wait_for(after), RequestStatus::Complete(_)));
+        assert_eq!(poll(running), RequestStatus::Unknown);
+        assert_eq!(poll(queued), RequestStatus::Unknown);
+        assert!(!ran.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_panicking_job_reports_failure() {
+        let _runtime = fresh_runtime();
+        let id = submit("test", || panic!("boom")).unwrap();
+        match wait_for(id) {
+            RequestStatus::Failed(json) => assert!(json.contains("panicked")),
+            status => panic!("Expected a failed request, got {:?}", status),
+        }
+    }
+
+    #[test]
+    fn test_stop_cancels_outstanding_requests() {
+        let _runtime = fresh_runtime();
+        let (running, release) = blocking_job();
+        let queued = submit("test", || Ok(Value::Null)).unwrap();
+
+        // The running request is stuck, so the worker cannot be joined in time
+        assert!(!stop(Duration::from_millis(50)));
+        assert_eq!(poll(running), RequestStatus::Unknown);
+        assert_eq!(poll(queued), RequestStatus::Unknown);
+        match submit("test", || Ok(Value::Null)) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Ffi,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("not initialized")),
+            _ => panic!("Expected FfiError after the pool was stopped"),
+        }
+
+        // Once released the old worker exits on its own and a new pool can start
+        drop(release);
+        start(1).unwrap();
+        let after = submit("test", || Ok(Value::Bool(true))).unwrap();
+        assert!(matches!(wait_for(after), RequestStatus::Complete(_)));
+        assert_eq!(poll(running), RequestStatus::Unknown);
+    }
+
+    #[test]
+    fn test_run_with_timeout() {
+        let _runtime = fresh_runtime();
+        let value = run_with_timeout(Duration::from_secs(5), || Ok(42u64)).unwrap();
+        assert_eq!(value, 42);
+
+        match run_with_timeout::<u64, _>(Duration::from_secs(5), || {
+            Err(SolanaUnityError::RpcError("node unavailable".to_string()))
+        }) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Rpc,
+                message: msg,
+                ..
+            }) => assert_eq!(msg, "node unavailable"),
+            _ => panic!("Expected the job's RpcError"),
+        }
+
+        let (release, wait): (Sender<()>, Receiver<()>) = mpsc::channel();
+        let start = Instant::now();
+        match run_with_timeout(Duration::from_millis(100), move || {
+            let _ = wait.recv();
+            Ok(())
+        }) {
+            Err(e) if e.kind == ErrorKind::Timeout => {
+                assert_eq!(e.context.timeout_ms, Some(100));
+            }
+            _ => panic!("Expected Timeout"),
+        }
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        // The abandoned job finishes in the background without leaving a result behind
+        drop(release);
+        let after = submit("test", || Ok(Value::Bool(true))).unwrap();
+        assert!(matches!(wait_for(after), RequestStatus::Complete(_)));
+        assert!(requests().entries.is_empty());
+    }
+}
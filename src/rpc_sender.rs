@@ -0,0 +1,370 @@
+use async_trait::async_trait;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use solana_client::client_error::Result as ClientResult;
+use solana_client::rpc_custom_error::{
+    NodeUnhealthyErrorData, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+};
+use solana_client::rpc_request::{RpcError, RpcRequest, RpcResponseErrorData};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// How often a 429 response is retried before it is returned, as in solana-client
+const TOO_MANY_REQUESTS_RETRIES: usize = 5;
+// The wait before retrying a 429 that has no usable Retry-After
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_millis(500);
+// Longer Retry-After hints are ignored in favour of the default
+const MAX_RETRY_AFTER_SECS: u64 = 120;
+
+#[derive(Deserialize)]
+struct RpcErrorObject {
+    code: i64,
+    message: String,
+}
+
+/// The JSON-RPC transport of `RpcClient`. It behaves like solana-client's `HttpSender`,
+/// retrying rate limited requests and decoding JSON-RPC errors the same way, but
+/// decompresses the response body itself so that a body which fails to decompress is
+/// reported with the Content-Encoding the node sent. With `compression` it asks for gzip
+/// or deflate. The `reqwest::Client` it is given must have its own decompression turned
+/// off, as `RpcClientConfig::http_client` does.
+pub struct RpcHttpSender {
+    client: reqwest::Client,
+    url: String,
+    compression: bool,
+    request_id: AtomicU64,
+    stats: Mutex<RpcTransportStats>,
+}
+
+impl RpcHttpSender {
+    pub fn new(url: String, client: reqwest::Client, compression: bool) -> Self {
+        Self {
+            client,
+            url,
+            compression,
+            request_id: AtomicU64::new(0),
+            stats: Mutex::default(),
+        }
+    }
+
+    // Posts the request, waiting out rate limiting, and adds the time spent waiting to
+    // `rate_limited`
+    async fn post(
+        &self,
+        request_json: &str,
+        rate_limited: &mut Duration,
+    ) -> ClientResult<serde_json::Value> {
+        let mut retries = TOO_MANY_REQUESTS_RETRIES;
+        loop {
+            let mut request = self
+                .client
+                .post(&self.url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(request_json.to_string());
+            if self.compression {
+                request = request.header(ACCEPT_ENCODING, "gzip, deflate");
+            }
+            let response = request.send().await?;
+
+            if !response.status().is_success() {
+                if response.status() == StatusCode::TOO_MANY_REQUESTS && retries > 0 {
+                    let wait = retry_after(&response).unwrap_or(DEFAULT_RETRY_AFTER);
+                    retries -= 1;
+                    tokio::time::sleep(wait).await;
+                    *rate_limited += wait;
+                    continue;
+                }
+                return Err(response.error_for_status().unwrap_err().into());
+            }
+
+            let encoding = response
+                .headers()
+                .get(CONTENT_ENCODING)
+                .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+            let body = response.bytes().await?;
+            let body = decompress(encoding.as_deref(), &body).map_err(|e| {
+                RpcError::RpcRequestError(format!(
+                    "Failed to decompress the response (Content-Encoding: {}): {}",
+                    encoding.as_deref().unwrap_or("none"),
+                    e
+                ))
+            })?;
+            return Ok(serde_json::from_slice(&body)?);
+        }
+    }
+}
+
+// Decompresses a response body sent with `encoding`. HTTP's deflate is a zlib stream.
+fn decompress(encoding: Option<&str>, body: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    match encoding.map(str::trim) {
+        None | Some("identity") => return Ok(body.to_vec()),
+        Some(encoding) if encoding.eq_ignore_ascii_case("gzip") => {
+            GzDecoder::new(body).read_to_end(&mut decoded)?
+        }
+        Some(encoding) if encoding.eq_ignore_ascii_case("deflate") => {
+            ZlibDecoder::new(body).read_to_end(&mut decoded)?
+        }
+        Some(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "unsupported encoding",
+            ))
+        }
+    };
+    Ok(decoded)
+}
+
+// The Retry-After of a 429 response, when it is a number of seconds
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let seconds: u64 = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+    (seconds < MAX_RETRY_AFTER_SECS).then_some(Duration::from_secs(seconds))
+}
+
+// The error of a JSON-RPC error response, with the data solana-client decodes for it
+fn response_error(error: &serde_json::Value) -> RpcError {
+    let object: RpcErrorObject = match serde_json::from_value(error.clone()) {
+        Ok(object) => object,
+        Err(e) => {
+            return RpcError::RpcRequestError(format!(
+                "Failed to deserialize RPC error response: {} [{}]",
+                error, e
+            ))
+        }
+    };
+    let data = match object.code {
+        JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+            serde_json::from_value::<RpcSimulateTransactionResult>(error["data"].clone())
+                .map_or(RpcResponseErrorData::Empty, |result| {
+                    RpcResponseErrorData::SendTransactionPreflightFailure(result)
+                })
+        }
+        JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
+            serde_json::from_value::<NodeUnhealthyErrorData>(error["data"].clone()).map_or(
+                RpcResponseErrorData::Empty,
+                |data| RpcResponseErrorData::NodeUnhealthy {
+                    num_slots_behind: data.num_slots_behind,
+                },
+            )
+        }
+        _ => RpcResponseErrorData::Empty,
+    };
+    RpcError::RpcResponseError {
+        code: object.code,
+        message: object.message,
+        data,
+    }
+}
+
+#[async_trait]
+impl RpcSender for RpcHttpSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let request_json = request.build_request_json(request_id, params).to_string();
+
+        let start = Instant::now();
+        let mut rate_limited = Duration::ZERO;
+        let result = self.post(&request_json, &mut rate_limited).await;
+        {
+            let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+            stats.request_count += 1;
+            stats.elapsed_time += start.elapsed();
+            stats.rate_limited_time += rate_limited;
+        }
+
+        let mut json = result?;
+        if json["error"].is_object() {
+            return Err(response_error(&json["error"]).into());
+        }
+        Ok(json["result"].take())
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::rpc::{RpcClient, RpcClientConfig};
+    use crate::runtime;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use solana_client::nonblocking::rpc_client::RpcClient as SolanaRpcClient;
+    use solana_client::rpc_client::RpcClientConfig as SolanaRpcClientConfig;
+    use solana_sdk::commitment_config::CommitmentConfig;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::sync::mpsc;
+
+    const VERSION_RESPONSE: &str =
+        r#"{"jsonrpc":"2.0","result":{"feature-set":3580551090,"solana-core":"1.17.7"},"id":0}"#;
+
+    // Answers every request on a local port with `body`, sent with the given
+    // Content-Encoding, and passes on the Accept-Encoding of each request
+    fn serve_encoded(
+        encoding: Option<&'static str>,
+        body: Vec<u8>,
+    ) -> (String, mpsc::Receiver<Option<String>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (accepted, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut stream = stream;
+                let (mut content_length, mut accept_encoding) = (0, None);
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                        break;
+                    }
+                    let line = line.trim_end().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(length) = line.strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                    if let Some(value) = line.strip_prefix("accept-encoding:") {
+                        accept_encoding = Some(value.trim().to_string());
+                    }
+                }
+                let mut request = vec![0; content_length];
+                let _ = reader.read_exact(&mut request);
+                let _ = accepted.send(accept_encoding);
+
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    body.len()
+                );
+                if let Some(encoding) = encoding {
+                    response.push_str(&format!("Content-Encoding: {}\r\n", encoding));
+                }
+                response.push_str("\r\n");
+                let _ = stream.write_all(response.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+        (url, receiver)
+    }
+
+    fn gzip(body: &str) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn get_version(url: &str, config: &RpcClientConfig) -> ClientResult<String> {
+        let sender = RpcHttpSender::new(
+            url.to_string(),
+            config.http_client().unwrap(),
+            config.compression,
+        );
+        let client = SolanaRpcClient::new_sender(
+            sender,
+            SolanaRpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        );
+        runtime::block_on(client.get_version())
+            .unwrap()
+            .map(|version| version.solana_core)
+    }
+
+    #[test]
+    fn test_compressed_responses() {
+        let _runtime = runtime::test_runtime();
+        let config = RpcClientConfig::default();
+        assert!(config.compression);
+
+        let (url, accepted) = serve_encoded(Some("gzip"), gzip(VERSION_RESPONSE));
+        assert_eq!(get_version(&url, &config).unwrap(), "1.17.7");
+        let accept_encoding = accepted.recv().unwrap().unwrap();
+        assert!(accept_encoding.contains("gzip"), "{}", accept_encoding);
+        assert!(accept_encoding.contains("deflate"), "{}", accept_encoding);
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(VERSION_RESPONSE.as_bytes()).unwrap();
+        let (url, _) = serve_encoded(Some("deflate"), encoder.finish().unwrap());
+        assert_eq!(get_version(&url, &config).unwrap(), "1.17.7");
+
+        // Uncompressed responses still work, and without compression nothing is asked for
+        let (url, _) = serve_encoded(None, VERSION_RESPONSE.as_bytes().to_vec());
+        assert_eq!(get_version(&url, &config).unwrap(), "1.17.7");
+        let config = RpcClientConfig::from_json(r#"{"compression": false}"#).unwrap();
+        let (url, accepted) = serve_encoded(None, VERSION_RESPONSE.as_bytes().to_vec());
+        assert_eq!(get_version(&url, &config).unwrap(), "1.17.7");
+        assert_eq!(accepted.recv().unwrap(), None);
+    }
+
+    #[test]
+    fn test_decompression_failure_names_encoding() {
+        let _runtime = runtime::test_runtime();
+        // Claims gzip but is not
+        let (url, _) = serve_encoded(Some("gzip"), VERSION_RESPONSE.as_bytes().to_vec());
+        let client = RpcClient::new(&url, "confirmed").unwrap();
+        let error = client
+            .get_balance("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr")
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Rpc);
+        assert!(
+            error.message.contains("Content-Encoding: gzip"),
+            "{}",
+            error.message
+        );
+
+        // A truncated stream fails the same way
+        let mut body = gzip(VERSION_RESPONSE);
+        body.truncate(body.len() / 2);
+        let (url, _) = serve_encoded(Some("gzip"), body);
+        let error = get_version(&url, &RpcClientConfig::default()).unwrap_err();
+        assert!(
+            error.to_string().contains("Content-Encoding: gzip"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_json_rpc_errors_keep_their_data() {
+        let _runtime = runtime::test_runtime();
+        let body = r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Node is behind by 42 slots","data":{"numSlotsBehind":42}},"id":0}"#;
+        let (url, _) = serve_encoded(Some("gzip"), gzip(body));
+        let error = get_version(&url, &RpcClientConfig::default()).unwrap_err();
+        match error.kind() {
+            solana_client::client_error::ClientErrorKind::RpcError(
+                RpcError::RpcResponseError { code, data, .. },
+            ) => {
+                assert_eq!(*code, -32005);
+                assert!(matches!(
+                    data,
+                    RpcResponseErrorData::NodeUnhealthy {
+                        num_slots_behind: Some(42)
+                    }
+                ));
+            }
+            _ => panic!("Expected a JSON-RPC error, got {}", error),
+        }
+    }
+}
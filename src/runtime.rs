@@ -0,0 +1,418 @@
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+#[cfg(test)]
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime, RuntimeFlavor};
+
+use crate::error::SolanaUnityError;
+use crate::requests;
+
+/// Settings for the background machinery started by `init`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct RuntimeConfig {
+    /// Threads serving the request queue, 1 to 64
+    pub request_workers: usize,
+    /// Worker threads of the async runtime that RPC calls run on, 1 to 64
+    pub async_workers: usize,
+    /// How long `shutdown` waits for workers busy with a request
+    pub shutdown_timeout_ms: u64,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            request_workers: 1,
+            async_workers: 2,
+            shutdown_timeout_ms: 5000,
+        }
+    }
+}
+
+impl RuntimeConfig {
+    const MAX_REQUEST_WORKERS: usize = 64;
+    const MAX_ASYNC_WORKERS: usize = 64;
+
+    /// Parses a config such as `{"requestWorkers": 2, "shutdownTimeoutMs": 1000}`. Missing
+    /// fields keep their defaults; unknown fields are rejected.
+    pub fn from_json(json: &str) -> Result<Self, SolanaUnityError> {
+        let config: Self = serde_json::from_str(json).map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Invalid runtime config: {}", e))
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), SolanaUnityError> {
+        if self.request_workers == 0 || self.request_workers > Self::MAX_REQUEST_WORKERS {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "requestWorkers must be between 1 and {}, got {}",
+                Self::MAX_REQUEST_WORKERS,
+                self.request_workers
+            )));
+        }
+        if self.async_workers == 0 || self.async_workers > Self::MAX_ASYNC_WORKERS {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "asyncWorkers must be between 1 and {}, got {}",
+                Self::MAX_ASYNC_WORKERS,
+                self.async_workers
+            )));
+        }
+        Ok(())
+    }
+}
+
+// The config the library was initialized with, None while it is shut down
+static STATE: Mutex<Option<RuntimeConfig>> = Mutex::new(None);
+
+// Threads started through spawn_thread that have not exited yet
+static LIVE_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+// Worker count for the next async runtime, 0 while the library is not initialized. Kept
+// apart from STATE so starting the runtime never waits on an init or shutdown in progress.
+static ASYNC_WORKERS: AtomicUsize = AtomicUsize::new(0);
+
+// The async runtime, started on first use after init and stopped by shutdown. Calls in
+// flight hold their own reference, so a shutdown never pulls the runtime out from under
+// them; the last one to finish stops it.
+static ASYNC_RUNTIME: Mutex<Option<Arc<Runtime>>> = Mutex::new(None);
+
+fn state() -> MutexGuard<'static, Option<RuntimeConfig>> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn async_runtime() -> MutexGuard<'static, Option<Arc<Runtime>>> {
+    ASYNC_RUNTIME.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The async runtime, started with the configured worker count if it is not running.
+/// Fails with `not_initialized` before `init` and after `shutdown`, so nothing is left
+/// running once the library has been shut down.
+pub fn handle() -> Result<Arc<Runtime>, SolanaUnityError> {
+    let mut runtime = async_runtime();
+    // Read under the runtime lock: shutdown clears it before taking the runtime, so a
+    // runtime started here is always one shutdown will stop
+    let workers = match ASYNC_WORKERS.load(Ordering::SeqCst) {
+        0 => return Err(not_initialized()),
+        workers => workers,
+    };
+    if let Some(runtime) = runtime.as_ref() {
+        return Ok(Arc::clone(runtime));
+    }
+    let started = Arc::new(
+        Builder::new_multi_thread()
+            .worker_threads(workers)
+            .thread_name("solana-unity-async")
+            .enable_all()
+            .build()
+            .map_err(|e| {
+                SolanaUnityError::FfiError(format!("Failed to start async runtime: {}", e))
+            })?,
+    );
+    *runtime = Some(Arc::clone(&started));
+    Ok(started)
+}
+
+/// Runs `future` to completion on the async runtime, blocking the calling thread. This is
+/// how the blocking API drives the async one. Fails on a thread driving a current-thread
+/// runtime, e.g. in a `#[tokio::test]`, which cannot be blocked; use the `*_async`
+/// methods there.
+pub fn block_on<F: Future>(future: F) -> Result<F::Output, SolanaUnityError> {
+    let runtime = handle()?;
+    // block_on panics on a thread that is already driving async code, e.g. a blocking
+    // call made from inside a task. block_in_place gets around that on a multi-thread
+    // runtime and panics on a current-thread one.
+    let output = match tokio::runtime::Handle::try_current() {
+        Ok(current) if current.runtime_flavor() == RuntimeFlavor::CurrentThread => {
+            release(runtime);
+            return Err(SolanaUnityError::FfiError(
+                "Blocking calls cannot be made from a current-thread tokio runtime; use the \
+                 async methods instead"
+                    .to_string(),
+            ));
+        }
+        Ok(_) => tokio::task::block_in_place(|| runtime.block_on(future)),
+        Err(_) => runtime.block_on(future),
+    };
+    release(runtime);
+    Ok(output)
+}
+
+// Drops a reference to a runtime, stopping it if shutdown already let go of it. Stopping
+// in the background is the only way that is allowed from inside async code.
+fn release(runtime: Arc<Runtime>) {
+    if let Ok(runtime) = Arc::try_unwrap(runtime) {
+        runtime.shutdown_background();
+    }
+}
+
+pub fn is_async_runtime_running() -> bool {
+    async_runtime().is_some()
+}
+
+// Stops the async runtime, giving its tasks until `timeout` to finish. A runtime that
+// blocking calls are still using is left to the last of them to stop; later calls start a
+// new one.
+fn stop_async_runtime(timeout: Duration) {
+    let runtime = async_runtime().take();
+    if let Some(runtime) = runtime {
+        match Arc::try_unwrap(runtime) {
+            Ok(runtime) => runtime.shutdown_timeout(timeout),
+            Err(shared) => release(shared),
+        }
+    }
+}
+
+/// Starts the background workers. Calling it again while initialized does nothing, even
+/// with a different config; shut down first to change it.
+pub fn init(config: RuntimeConfig) -> Result<(), SolanaUnityError> {
+    let mut state = state();
+    if state.is_some() {
+        return Ok(());
+    }
+
+    config.validate()?;
+    requests::start(config.request_workers)?;
+    ASYNC_WORKERS.store(config.async_workers, Ordering::SeqCst);
+    *state = Some(config);
+    Ok(())
+}
+
+/// Cancels outstanding requests and stops the background workers and the async runtime,
+/// waiting up to the configured timeout for them to exit. Returns false if some were still
+/// busy at the deadline. Does nothing if the library is not initialized. Blocking calls
+/// made afterwards fail with `not_initialized` until `init` is called again.
+pub fn shutdown() -> bool {
+    let mut state = state();
+    let config = match state.take() {
+        Some(config) => config,
+        None => return true,
+    };
+
+    ASYNC_WORKERS.store(0, Ordering::SeqCst);
+    let timeout = Duration::from_millis(config.shutdown_timeout_ms);
+    let stopped = requests::stop(timeout);
+    stop_async_runtime(timeout);
+    stopped
+}
+
+pub fn is_initialized() -> bool {
+    state().is_some()
+}
+
+/// The error returned by functions that need `init` to have been called
+pub fn not_initialized() -> SolanaUnityError {
+    SolanaUnityError::FfiError("Library not initialized: call solana_init first".to_string())
+}
+
+/// Number of threads started by the library that are still running
+pub fn live_threads() -> usize {
+    LIVE_THREADS.load(Ordering::SeqCst)
+}
+
+// Decrements the live thread count when its thread exits, panics included
+struct ThreadGuard;
+
+impl Drop for ThreadGuard {
+    fn drop(&mut self) {
+        LIVE_THREADS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Spawns a named thread that is counted in `live_threads` until it exits
+pub(crate) fn spawn_thread<F>(name: &str, f: F) -> Result<JoinHandle<()>, SolanaUnityError>
+where
+    F: FnOnce() + Send + 'static,
+{
+    LIVE_THREADS.fetch_add(1, Ordering::SeqCst);
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            let _guard = ThreadGuard;
+            f()
+        })
+        .map_err(|e| {
+            LIVE_THREADS.fetch_sub(1, Ordering::SeqCst);
+            SolanaUnityError::FfiError(format!("Failed to start thread {}: {}", name, e))
+        })
+}
+
+// Tests that use the runtime share one process-wide instance. Tests that only make calls
+// on it share this lock; those that shut it down or re-initialize it hold it alone, so
+// they never pull it out from under the others.
+#[cfg(test)]
+static TEST_LOCK: RwLock<()> = RwLock::new(());
+
+/// Makes sure the runtime is initialized with the default config and keeps it from being
+/// shut down until the guard is dropped. For tests that make blocking calls.
+#[cfg(test)]
+pub(crate) fn test_runtime() -> RwLockReadGuard<'static, ()> {
+    let guard = TEST_LOCK.read().unwrap_or_else(|e| e.into_inner());
+    init(RuntimeConfig::default()).unwrap();
+    guard
+}
+
+/// Like `test_runtime`, for tests that shut the runtime down or re-initialize it
+#[cfg(test)]
+pub(crate) fn exclusive_test_runtime() -> RwLockWriteGuard<'static, ()> {
+    let guard = TEST_LOCK.write().unwrap_or_else(|e| e.into_inner());
+    init(RuntimeConfig::default()).unwrap();
+    guard
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use serde_json::Value;
+    use std::time::Instant;
+
+    fn wait_for_threads(count: usize) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while live_threads() != count {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        true
+    }
+
+    #[test]
+    fn test_init_shutdown_cycles_do_not_leak_threads() {
+        let _runtime = exclusive_test_runtime();
+        assert!(shutdown());
+        assert!(wait_for_threads(0));
+
+        let config = RuntimeConfig::from_json(r#"{"requestWorkers": 3}"#).unwrap();
+        for _ in 0..3 {
+            init(config.clone()).unwrap();
+            // A second init is a no-op and starts no threads
+            init(config.clone()).unwrap();
+            assert!(is_initialized());
+            assert_eq!(live_threads(), 3);
+
+            let id = requests::submit("test", || Ok(Value::Null)).unwrap();
+            assert!(id > 0);
+
+            assert!(shutdown());
+            assert!(shutdown());
+            assert!(!is_initialized());
+            assert!(wait_for_threads(0));
+        }
+    }
+
+    #[test]
+    fn test_requests_fail_when_not_initialized() {
+        let _runtime = exclusive_test_runtime();
+        shutdown();
+
+        match requests::submit("test", || Ok(Value::Null)) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Ffi,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("not initialized")),
+            _ => panic!("Expected FfiError before init"),
+        }
+        // Blocking calls do not start the async runtime behind the library's back
+        match block_on(async { 1 }) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Ffi,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("not initialized")),
+            _ => panic!("Expected FfiError before init"),
+        }
+        assert!(!is_async_runtime_running());
+    }
+
+    #[test]
+    fn test_config_parsing() {
+        assert_eq!(
+            RuntimeConfig::from_json("{}").unwrap(),
+            RuntimeConfig::default()
+        );
+        let config =
+            RuntimeConfig::from_json(r#"{"requestWorkers": 4, "shutdownTimeoutMs": 250}"#).unwrap();
+        assert_eq!(config.request_workers, 4);
+        assert_eq!(config.shutdown_timeout_ms, 250);
+        let config = RuntimeConfig::from_json(r#"{"asyncWorkers": 8}"#).unwrap();
+        assert_eq!(config.async_workers, 8);
+
+        for invalid in [
+            r#"{"requestWorkers": 0}"#,
+            r#"{"requestWorkers": 65}"#,
+            r#"{"asyncWorkers": 0}"#,
+            r#"{"asyncWorkers": 65}"#,
+            r#"{"workers": 2}"#,
+            "not json",
+        ] {
+            match RuntimeConfig::from_json(invalid) {
+                Err(SolanaUnityError {
+                    kind: ErrorKind::InvalidInput,
+                    ..
+                }) => {}
+                _ => panic!("Expected InvalidInput for {}", invalid),
+            }
+        }
+    }
+
+    #[test]
+    fn test_async_runtime_restarts_after_init() {
+        let _runtime = exclusive_test_runtime();
+        assert_eq!(block_on(async { 7 }).unwrap(), 7);
+        let first = handle().unwrap();
+        assert_eq!(
+            first.metrics().num_workers(),
+            RuntimeConfig::default().async_workers
+        );
+
+        // Shutdown stops the runtime once the last reference to it is gone, and nothing
+        // starts it again before the next init
+        assert!(shutdown());
+        assert!(!is_async_runtime_running());
+        assert!(handle().is_err());
+        assert_eq!(first.block_on(async { 8 }), 8);
+        release(first);
+
+        init(RuntimeConfig::from_json(r#"{"asyncWorkers": 3}"#).unwrap()).unwrap();
+        let configured = handle().unwrap();
+        assert_eq!(configured.metrics().num_workers(), 3);
+        release(configured);
+        assert_eq!(block_on(async { 9 }).unwrap(), 9);
+        assert!(shutdown());
+        assert!(block_on(async { 10 }).is_err());
+    }
+
+    #[test]
+    fn test_block_on_inside_async_code() {
+        let _runtime = test_runtime();
+        let runtime = handle().unwrap();
+        let value = runtime.block_on(async {
+            tokio::spawn(async { block_on(async { 10 }).unwrap() })
+                .await
+                .unwrap()
+        });
+        assert_eq!(value, 10);
+        release(runtime);
+    }
+
+    #[test]
+    fn test_block_on_fails_on_current_thread_runtime() {
+        let _runtime = test_runtime();
+        let current = Builder::new_current_thread().enable_all().build().unwrap();
+        match current.block_on(async { block_on(async { 11 }) }) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Ffi,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("current-thread")),
+            _ => panic!("Expected FfiError on a current-thread runtime"),
+        }
+    }
+}
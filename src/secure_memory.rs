@@ -0,0 +1,213 @@
+use std::alloc::{self, Layout};
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::ptr::NonNull;
+use zeroize::Zeroize;
+
+/// Heap allocation on its own page(s) that is locked into physical memory where the OS
+/// allows it (mlock on Unix, VirtualLock on Windows), so the contents are never written
+/// to swap. The whole region is zeroized before it is released.
+///
+/// Locking can fail, most commonly because RLIMIT_MEMLOCK is exhausted; the value is then
+/// still stored on dedicated pages and wiped on drop, and `is_locked` reports false.
+pub struct SecureBox<T> {
+    ptr: NonNull<T>,
+    layout: Layout,
+    locked: bool,
+}
+
+// SecureBox owns its T exactly like Box<T>
+unsafe impl<T: Send> Send for SecureBox<T> {}
+unsafe impl<T: Sync> Sync for SecureBox<T> {}
+
+impl<T> SecureBox<T> {
+    pub fn new(value: T) -> Self {
+        Self::with_lock(value, true)
+    }
+
+    pub(crate) fn with_lock(value: T, try_lock: bool) -> Self {
+        let page = page_size();
+        let size = round_up(std::mem::size_of::<T>().max(1), page);
+        let align = page.max(std::mem::align_of::<T>());
+        let layout = Layout::from_size_align(size, align).expect("invalid secure memory layout");
+
+        let raw = unsafe { alloc::alloc_zeroed(layout) };
+        if raw.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+
+        let locked = try_lock && unsafe { lock(raw, size) };
+
+        let ptr = raw as *mut T;
+        unsafe {
+            ptr.write(value);
+        }
+
+        Self {
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            layout,
+            locked,
+        }
+    }
+
+    /// Whether the pages are locked into physical memory
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl<T> Deref for SecureBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> Drop for SecureBox<T> {
+    fn drop(&mut self) {
+        let raw = self.ptr.as_ptr() as *mut u8;
+        unsafe {
+            std::ptr::drop_in_place(self.ptr.as_ptr());
+            std::slice::from_raw_parts_mut(raw, self.layout.size()).zeroize();
+            if self.locked {
+                unlock(raw, self.layout.size());
+            }
+            alloc::dealloc(raw, self.layout);
+        }
+    }
+}
+
+/// Ordinary heap allocation whose contents are zeroized before it is released, for secrets
+/// that do not need locked pages
+pub struct ZeroizingBox<T>(Box<ManuallyDrop<T>>);
+
+impl<T> ZeroizingBox<T> {
+    pub fn new(value: T) -> Self {
+        Self(Box::new(ManuallyDrop::new(value)))
+    }
+}
+
+impl<T> Deref for ZeroizingBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Drop for ZeroizingBox<T> {
+    fn drop(&mut self) {
+        let value: &mut ManuallyDrop<T> = &mut self.0;
+        let raw = value as *mut ManuallyDrop<T> as *mut u8;
+        unsafe {
+            ManuallyDrop::drop(value);
+            // The Box frees the memory afterwards without dropping the value again
+            std::slice::from_raw_parts_mut(raw, std::mem::size_of::<T>()).zeroize();
+        }
+    }
+}
+
+fn round_up(size: usize, page: usize) -> usize {
+    size.div_ceil(page) * page
+}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as usize
+    } else {
+        4096
+    }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> usize {
+    4096
+}
+
+#[cfg(unix)]
+unsafe fn lock(ptr: *mut u8, len: usize) -> bool {
+    unsafe { libc::mlock(ptr as *const libc::c_void, len) == 0 }
+}
+
+#[cfg(unix)]
+unsafe fn unlock(ptr: *mut u8, len: usize) {
+    unsafe {
+        libc::munlock(ptr as *const libc::c_void, len);
+    }
+}
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn VirtualLock(address: *mut std::ffi::c_void, size: usize) -> i32;
+    fn VirtualUnlock(address: *mut std::ffi::c_void, size: usize) -> i32;
+}
+
+#[cfg(windows)]
+unsafe fn lock(ptr: *mut u8, len: usize) -> bool {
+    unsafe { VirtualLock(ptr as *mut std::ffi::c_void, len) != 0 }
+}
+
+#[cfg(windows)]
+unsafe fn unlock(ptr: *mut u8, len: usize) {
+    unsafe {
+        VirtualUnlock(ptr as *mut std::ffi::c_void, len);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+unsafe fn lock(_ptr: *mut u8, _len: usize) -> bool {
+    false
+}
+
+#[cfg(not(any(unix, windows)))]
+unsafe fn unlock(_ptr: *mut u8, _len: usize) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_secure_box_stores_value_page_aligned() {
+        let secure = SecureBox::new([42u8; 64]);
+        assert_eq!(*secure, [42u8; 64]);
+        assert_eq!(&*secure as *const _ as usize % page_size(), 0);
+    }
+
+    #[test]
+    fn test_secure_box_fallback_when_not_locked() {
+        let secure = SecureBox::with_lock([7u8; 64], false);
+        assert!(!secure.is_locked());
+        assert_eq!(*secure, [7u8; 64]);
+    }
+
+    #[test]
+    fn test_secure_box_drops_inner_value() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let secure = SecureBox::new(DropCounter(Arc::clone(&drops)));
+        drop(secure);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_zeroizing_box_drops_inner_value_once() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let boxed = ZeroizingBox::new(DropCounter(Arc::clone(&drops)));
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+        drop(boxed);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,352 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::instruction::Instruction;
+
+use crate::account::Account;
+use crate::error::SolanaUnityError;
+use crate::instruction::TokenInstructions;
+use crate::keystore::{Keystore, DEFAULT_KDF_ITERATIONS};
+
+/// Current session format version. Newer sessions are rejected.
+pub const SESSION_VERSION: u32 = 1;
+
+/// An ephemeral keypair the owner of a token account approves as its delegate for a limited
+/// allowance, so small actions can be signed without prompting the owner's wallet each
+/// time. The owner signs the approve instruction once and the revoke instruction when the
+/// session ends.
+pub struct SessionKey {
+    account: Account,
+    owner_token_account: String,
+    owner: String,
+    allowance: u64,
+    // With ApproveChecked, the mint and its decimals
+    mint: Option<(String, u8)>,
+    expires_at: Option<i64>,
+}
+
+// What `SessionKey::serialize` writes
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionFile {
+    version: u32,
+    pubkey: String,
+    owner_token_account: String,
+    owner: String,
+    allowance: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decimals: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<i64>,
+    keystore: Keystore,
+}
+
+impl SessionKey {
+    /// Generates a session key and the Approve instruction delegating `allowance` of
+    /// `owner_token_account` to it, which `owner` must sign
+    pub fn create(
+        owner_token_account: &str,
+        owner: &str,
+        allowance: u64,
+    ) -> Result<(Self, Instruction), SolanaUnityError> {
+        Self::new(owner_token_account, owner, allowance, None)
+    }
+
+    /// Like `create`, with ApproveChecked, which fails on chain unless the token account
+    /// holds `mint` and the mint has `decimals`
+    pub fn create_checked(
+        owner_token_account: &str,
+        owner: &str,
+        mint: &str,
+        decimals: u8,
+        allowance: u64,
+    ) -> Result<(Self, Instruction), SolanaUnityError> {
+        Account::validate_pubkey(mint)?;
+        Self::new(
+            owner_token_account,
+            owner,
+            allowance,
+            Some((mint.to_string(), decimals)),
+        )
+    }
+
+    fn new(
+        owner_token_account: &str,
+        owner: &str,
+        allowance: u64,
+        mint: Option<(String, u8)>,
+    ) -> Result<(Self, Instruction), SolanaUnityError> {
+        Account::validate_pubkey(owner_token_account)?;
+        Account::validate_pubkey(owner)?;
+        if allowance == 0 {
+            return Err(SolanaUnityError::InvalidInput(
+                "Session allowance must be greater than 0".to_string(),
+            ));
+        }
+
+        let session = Self {
+            account: Account::generate(),
+            owner_token_account: owner_token_account.to_string(),
+            owner: owner.to_string(),
+            allowance,
+            mint,
+            expires_at: None,
+        };
+        let approve = session.approve_instruction()?;
+        Ok((session, approve))
+    }
+
+    /// The session keypair, which signs as the delegate
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    pub fn pubkey(&self) -> Result<String, SolanaUnityError> {
+        self.account.get_pubkey()
+    }
+
+    pub fn owner_token_account(&self) -> &str {
+        &self.owner_token_account
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub fn allowance(&self) -> u64 {
+        self.allowance
+    }
+
+    /// The Approve, or ApproveChecked, instruction delegating the allowance to the session key
+    pub fn approve_instruction(&self) -> Result<Instruction, SolanaUnityError> {
+        let delegate = self.pubkey()?;
+        match &self.mint {
+            Some((mint, decimals)) => TokenInstructions::approve_checked(
+                &self.owner_token_account,
+                mint,
+                &delegate,
+                &self.owner,
+                self.allowance,
+                *decimals,
+            ),
+            None => TokenInstructions::approve(
+                &self.owner_token_account,
+                &delegate,
+                &self.owner,
+                self.allowance,
+            ),
+        }
+    }
+
+    /// The Revoke instruction ending the delegation, which the owner must sign. It revokes
+    /// whichever delegate the token account has, so it also ends sessions approved since.
+    pub fn revoke_instruction(&self) -> Result<Instruction, SolanaUnityError> {
+        TokenInstructions::revoke(&self.owner_token_account, &self.owner)
+    }
+
+    /// When the session should no longer be used, in Unix seconds. Only recorded; the
+    /// delegation itself lasts until it is revoked or the allowance is spent.
+    pub fn expires_at(&self) -> Option<i64> {
+        self.expires_at
+    }
+
+    pub fn set_expires_at(&mut self, expires_at: Option<i64>) {
+        self.expires_at = expires_at;
+    }
+
+    /// True if the session has an expiry and `now`, in Unix seconds, is at or past it
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// The session as JSON, with the session key encrypted under `password` as in
+    /// `Account::export_encrypted`, to be restored with `deserialize`
+    pub fn serialize(&self, password: &str) -> Result<String, SolanaUnityError> {
+        self.serialize_with_iterations(password, DEFAULT_KDF_ITERATIONS)
+    }
+
+    /// Like `serialize`, with a custom PBKDF2 iteration count
+    pub fn serialize_with_iterations(
+        &self,
+        password: &str,
+        iterations: u32,
+    ) -> Result<String, SolanaUnityError> {
+        let keystore = Keystore::from_json(
+            &self
+                .account
+                .export_encrypted_with_iterations(password, iterations)?,
+        )?;
+        let file = SessionFile {
+            version: SESSION_VERSION,
+            pubkey: self.pubkey()?,
+            owner_token_account: self.owner_token_account.clone(),
+            owner: self.owner.clone(),
+            allowance: self.allowance,
+            mint: self.mint.as_ref().map(|(mint, _)| mint.clone()),
+            decimals: self.mint.as_ref().map(|(_, decimals)| *decimals),
+            expires_at: self.expires_at,
+            keystore,
+        };
+        serde_json::to_string(&file).map_err(|e| {
+            SolanaUnityError::SerializationError(format!("Failed to serialize session: {}", e))
+        })
+    }
+
+    /// Restores a session written by `serialize`. A wrong password fails with
+    /// `SolanaUnityError::DecryptionFailed()`.
+    pub fn deserialize(json: &str, password: &str) -> Result<Self, SolanaUnityError> {
+        let file: SessionFile = serde_json::from_str(json)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid session JSON: {}", e)))?;
+        if file.version > SESSION_VERSION || file.version == 0 {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Unsupported session version {} (this build supports up to {})",
+                file.version, SESSION_VERSION
+            )));
+        }
+        let mint = match (file.mint, file.decimals) {
+            (Some(mint), Some(decimals)) => Some((mint, decimals)),
+            (None, None) => None,
+            _ => {
+                return Err(SolanaUnityError::InvalidInput(
+                    "Session mint and decimals must be given together".to_string(),
+                ))
+            }
+        };
+        Account::validate_pubkey(&file.owner_token_account)?;
+        Account::validate_pubkey(&file.owner)?;
+
+        let account = Account::from_encrypted(&file.keystore.to_json()?, password)?;
+        if account.get_pubkey()? != file.pubkey {
+            return Err(SolanaUnityError::InvalidInput(
+                "Session key does not match the session pubkey".to_string(),
+            ));
+        }
+        Ok(Self {
+            account,
+            owner_token_account: file.owner_token_account,
+            owner: file.owner,
+            allowance: file.allowance,
+            mint,
+            expires_at: file.expires_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    const TEST_ITERATIONS: u32 = 1_000;
+
+    fn pubkey(value: &str) -> Pubkey {
+        Pubkey::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_approve_delegates_allowance_to_session_key() {
+        let token_account = Pubkey::new_unique().to_string();
+        let owner = Pubkey::new_unique().to_string();
+        let (session, approve) = SessionKey::create(&token_account, &owner, 250).unwrap();
+        let delegate = session.pubkey().unwrap();
+        assert!(session.account().has_private_key());
+
+        assert_eq!(
+            approve.program_id,
+            pubkey(TokenInstructions::TOKEN_PROGRAM_ID)
+        );
+        let mut data = vec![4];
+        data.extend_from_slice(&250u64.to_le_bytes());
+        assert_eq!(approve.data, data);
+        let accounts: Vec<_> = approve
+            .accounts
+            .iter()
+            .map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable))
+            .collect();
+        assert_eq!(
+            accounts,
+            [
+                (pubkey(&token_account), false, true),
+                (pubkey(&delegate), false, false),
+                (pubkey(&owner), true, false),
+            ]
+        );
+
+        // The revoke undoes the approve on the same token account, signed by the same owner
+        let revoke = session.revoke_instruction().unwrap();
+        assert_eq!(revoke.program_id, approve.program_id);
+        assert_eq!(revoke.data, [5]);
+        assert_eq!(revoke.accounts[0], approve.accounts[0]);
+        assert_eq!(revoke.accounts[1], approve.accounts[2]);
+        assert_eq!(revoke.accounts.len(), 2);
+    }
+
+    #[test]
+    fn test_checked_approve_names_mint_and_decimals() {
+        let token_account = Pubkey::new_unique().to_string();
+        let owner = Pubkey::new_unique().to_string();
+        let mint = Pubkey::new_unique().to_string();
+        let (session, approve) =
+            SessionKey::create_checked(&token_account, &owner, &mint, 6, 1_000_000).unwrap();
+
+        let mut data = vec![13];
+        data.extend_from_slice(&1_000_000u64.to_le_bytes());
+        data.push(6);
+        assert_eq!(approve.data, data);
+        assert_eq!(approve.accounts[1].pubkey, pubkey(&mint));
+        assert_eq!(
+            approve.accounts[2].pubkey,
+            pubkey(&session.pubkey().unwrap())
+        );
+        assert!(approve.accounts[3].is_signer);
+        assert_eq!(session.approve_instruction().unwrap(), approve);
+    }
+
+    #[test]
+    fn test_rejects_invalid_input() {
+        let valid = Pubkey::new_unique().to_string();
+        assert!(SessionKey::create("not a pubkey", &valid, 1).is_err());
+        assert!(SessionKey::create(&valid, "not a pubkey", 1).is_err());
+        assert!(SessionKey::create(&valid, &valid, 0).is_err());
+        assert!(SessionKey::create_checked(&valid, &valid, "not a mint", 6, 1).is_err());
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let token_account = Pubkey::new_unique().to_string();
+        let owner = Pubkey::new_unique().to_string();
+        let mint = Pubkey::new_unique().to_string();
+        let (mut session, approve) =
+            SessionKey::create_checked(&token_account, &owner, &mint, 9, 42).unwrap();
+        session.set_expires_at(Some(1_700_000_000));
+        assert!(!session.is_expired(1_699_999_999));
+        assert!(session.is_expired(1_700_000_000));
+
+        let json = session
+            .serialize_with_iterations("hunter2", TEST_ITERATIONS)
+            .unwrap();
+        assert!(!json.contains(&session.account().to_base58_private_key().unwrap()));
+
+        let restored = SessionKey::deserialize(&json, "hunter2").unwrap();
+        assert_eq!(restored.pubkey().unwrap(), session.pubkey().unwrap());
+        assert_eq!(
+            restored.account().get_private_key().unwrap(),
+            session.account().get_private_key().unwrap()
+        );
+        assert_eq!(restored.allowance(), 42);
+        assert_eq!(restored.expires_at(), Some(1_700_000_000));
+        assert_eq!(restored.approve_instruction().unwrap(), approve);
+        assert_eq!(
+            restored.revoke_instruction().unwrap(),
+            session.revoke_instruction().unwrap()
+        );
+
+        assert!(matches!(
+            SessionKey::deserialize(&json, "wrong"),
+            Err(e) if e.code == crate::error::code::DECRYPTION_FAILED
+        ));
+        let newer = json.replacen("\"version\":1", "\"version\":2", 1);
+        assert!(SessionKey::deserialize(&newer, "hunter2").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::error::{code, SolanaUnityError};
+
+/// Most shares a key can be split into
+pub const MAX_SHARES: u8 = 16;
+
+const SHARE_VERSION: u8 = 1;
+const SECRET_LEN: usize = 32;
+const SET_ID_LEN: usize = 4;
+const CHECKSUM_LEN: usize = 4;
+// version, set id, threshold, index, the share of the secret and a checksum
+const SHARE_LEN: usize = 1 + SET_ID_LEN + 1 + 1 + SECRET_LEN + CHECKSUM_LEN;
+
+/// One decoded share of a split secret
+struct Share {
+    // Random per split, so shares of different splits are not combined
+    set_id: [u8; SET_ID_LEN],
+    threshold: u8,
+    // The x coordinate, 1 to MAX_SHARES
+    index: u8,
+    value: Zeroizing<[u8; SECRET_LEN]>,
+}
+
+fn invalid_share(message: impl std::fmt::Display) -> SolanaUnityError {
+    SolanaUnityError::InvalidInput(format!("Invalid key share: {}", message))
+        .with_code(code::INVALID_KEY_SHARE)
+}
+
+// Multiplication in GF(256) with the AES polynomial x^8 + x^4 + x^3 + x + 1, without
+// branching on the operands
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+// a^254, which is the inverse of a non-zero a
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let hash = Sha256::digest(bytes);
+    let mut checksum = [0; CHECKSUM_LEN];
+    checksum.copy_from_slice(&hash[..CHECKSUM_LEN]);
+    checksum
+}
+
+/// Splits a 32-byte `secret` into `shares` base58 shares, any `threshold` of which
+/// recover it with `combine`. Fewer reveal nothing about it.
+pub fn split(
+    secret: &[u8; 32],
+    threshold: u8,
+    shares: u8,
+) -> Result<Vec<String>, SolanaUnityError> {
+    if shares > MAX_SHARES {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "A key can be split into at most {} shares, not {}",
+            MAX_SHARES, shares
+        )));
+    }
+    if threshold > shares {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Threshold {} is more than the {} shares",
+            threshold, shares
+        )));
+    }
+    if threshold < 2 {
+        return Err(SolanaUnityError::InvalidInput(
+            "Threshold must be at least 2; a single share would hold the key itself",
+        ));
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut set_id = [0; SET_ID_LEN];
+    rng.fill_bytes(&mut set_id);
+
+    // One polynomial per byte of the secret, whose constant term is that byte
+    let mut coefficients = Zeroizing::new(vec![[0u8; SECRET_LEN]; threshold as usize]);
+    coefficients[0] = *secret;
+    for coefficient in coefficients.iter_mut().skip(1) {
+        rng.fill_bytes(coefficient);
+    }
+
+    Ok((1..=shares)
+        .map(|index| {
+            let mut share = Zeroizing::new(Vec::with_capacity(SHARE_LEN));
+            share.push(SHARE_VERSION);
+            share.extend_from_slice(&set_id);
+            share.push(threshold);
+            share.push(index);
+            for byte in 0..SECRET_LEN {
+                // Horner's rule from the highest coefficient down
+                let value = coefficients.iter().rev().fold(0, |value, coefficient| {
+                    gf_mul(value, index) ^ coefficient[byte]
+                });
+                share.push(value);
+            }
+            let checksum = checksum(&share);
+            share.extend_from_slice(&checksum);
+            bs58::encode(&share[..]).into_string()
+        })
+        .collect())
+}
+
+fn decode(encoded: &str, position: usize) -> Result<Share, SolanaUnityError> {
+    let bytes =
+        Zeroizing::new(bs58::decode(encoded.trim()).into_vec().map_err(|e| {
+            invalid_share(format!("share {} is not valid base58: {}", position, e))
+        })?);
+    if bytes.len() != SHARE_LEN {
+        return Err(invalid_share(format!(
+            "share {} is {} bytes, expected {}",
+            position,
+            bytes.len(),
+            SHARE_LEN
+        )));
+    }
+    let (body, expected) = bytes.split_at(SHARE_LEN - CHECKSUM_LEN);
+    if checksum(body) != expected {
+        return Err(invalid_share(format!(
+            "share {} fails its checksum; it was mistyped or corrupted",
+            position
+        )));
+    }
+    if body[0] != SHARE_VERSION {
+        return Err(invalid_share(format!(
+            "share {} has unsupported version {}",
+            position, body[0]
+        )));
+    }
+
+    let mut set_id = [0; SET_ID_LEN];
+    set_id.copy_from_slice(&body[1..1 + SET_ID_LEN]);
+    let threshold = body[1 + SET_ID_LEN];
+    let index = body[2 + SET_ID_LEN];
+    if index == 0 || index > MAX_SHARES || !(2..=MAX_SHARES).contains(&threshold) {
+        return Err(invalid_share(format!(
+            "share {} has index {} of threshold {}",
+            position, index, threshold
+        )));
+    }
+    let mut value = Zeroizing::new([0; SECRET_LEN]);
+    value.copy_from_slice(&body[3 + SET_ID_LEN..]);
+    Ok(Share {
+        set_id,
+        threshold,
+        index,
+        value,
+    })
+}
+
+/// Recovers the secret `split` produced from at least its threshold of shares, in any
+/// order. Shares that are corrupted, repeated or from another split are rejected.
+pub fn combine(shares: &[&str]) -> Result<Zeroizing<[u8; 32]>, SolanaUnityError> {
+    let shares = shares
+        .iter()
+        .enumerate()
+        .map(|(position, share)| decode(share, position))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = shares
+        .first()
+        .ok_or_else(|| invalid_share("no shares were given"))?;
+
+    for (position, share) in shares.iter().enumerate() {
+        if share.set_id != first.set_id || share.threshold != first.threshold {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Key share {} belongs to a different key or split than share 0",
+                position
+            ))
+            .with_code(code::KEY_SHARE_MISMATCH));
+        }
+        if shares[..position]
+            .iter()
+            .any(|other| other.index == share.index)
+        {
+            return Err(invalid_share(format!(
+                "share {} repeats index {}",
+                position, share.index
+            )));
+        }
+    }
+    if shares.len() < first.threshold as usize {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Not enough key shares: {} of the {} needed",
+            shares.len(),
+            first.threshold
+        ))
+        .with_code(code::NOT_ENOUGH_KEY_SHARES));
+    }
+
+    // Lagrange interpolation at x = 0 over the first `threshold` shares
+    let shares = &shares[..first.threshold as usize];
+    let mut secret = Zeroizing::new([0u8; SECRET_LEN]);
+    for (i, share) in shares.iter().enumerate() {
+        let mut numerator = 1;
+        let mut denominator = 1;
+        for (j, other) in shares.iter().enumerate() {
+            if i != j {
+                numerator = gf_mul(numerator, other.index);
+                denominator = gf_mul(denominator, other.index ^ share.index);
+            }
+        }
+        let basis = gf_mul(numerator, gf_inv(denominator));
+        for (byte, value) in secret.iter_mut().zip(share.value.iter()) {
+            *byte ^= gf_mul(basis, *value);
+        }
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_arithmetic() {
+        // The worked example of FIPS 197, section 4.2
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_any_threshold_of_shares_recover_the_secret() {
+        let secret = [7u8; 32];
+        let shares = split(&secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for combination in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let chosen: Vec<&str> = combination.iter().map(|&i| shares[i].as_str()).collect();
+            assert_eq!(*combine(&chosen).unwrap(), secret);
+        }
+        let all: Vec<&str> = shares.iter().map(String::as_str).collect();
+        assert_eq!(*combine(&all).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_rejects_bad_parameters() {
+        let secret = [1u8; 32];
+        assert!(split(&secret, 3, 2).is_err());
+        assert!(split(&secret, 2, 17).is_err());
+        assert!(split(&secret, 1, 3).is_err());
+        assert!(split(&secret, 16, 16).is_ok());
+    }
+}
@@ -0,0 +1,420 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction as SolanaTransaction;
+use std::str::FromStr;
+
+use crate::error::{code, ErrorKind, SolanaUnityError};
+use crate::rpc::RpcClientConfig;
+use crate::runtime;
+
+/// What a transaction request's GET returns: the merchant's name and icon, for the wallet
+/// to show before asking for a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayMetadata {
+    pub label: String,
+    /// An absolute https URL of an SVG, PNG or WebP image
+    pub icon: String,
+}
+
+/// What a transaction request's POST returns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayTransaction {
+    /// The transaction in base64 wire format, for `Transaction::from_serialized` (or
+    /// `solana_deserialize_transaction_b64`) to inspect and sign
+    pub transaction: String,
+    /// Text the wallet should show with the transaction
+    pub message: Option<String>,
+}
+
+/// The interactive Solana Pay flow, where a `solana:` URL points at a merchant's server:
+/// the wallet GETs its label and icon, then POSTs its account and gets a transaction back.
+/// Requests go through the HTTP client `RpcClient` uses, with the same timeouts.
+pub struct TransactionRequest;
+
+impl TransactionRequest {
+    /// GETs the label and icon of the transaction request at `url`, a `solana:` URL or
+    /// the https link inside one
+    pub async fn fetch_metadata_async(url: &str) -> Result<PayMetadata, SolanaUnityError> {
+        let link = link(url)?;
+        let request = http_client()?.get(link).header(ACCEPT, "application/json");
+        let body = send(request).await?;
+
+        let metadata: PayMetadata = parse(&body)?;
+        if metadata.label.trim().is_empty() {
+            return Err(invalid_response("label is empty"));
+        }
+        match Url::parse(&metadata.icon) {
+            Ok(icon) if icon.scheme() == "https" => {}
+            _ => {
+                return Err(invalid_response(&format!(
+                    "icon {:?} is not an absolute https URL",
+                    metadata.icon
+                )))
+            }
+        }
+        Ok(metadata)
+    }
+
+    /// POSTs `account_pubkey` to the transaction request at `url` and returns the
+    /// transaction it answers with, after checking that it decodes and that the account
+    /// is one of its signers
+    pub async fn request_transaction_async(
+        url: &str,
+        account_pubkey: &str,
+    ) -> Result<PayTransaction, SolanaUnityError> {
+        let link = link(url)?;
+        let account = Pubkey::from_str(account_pubkey).map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Invalid account pubkey: {}", e))
+                .with_pubkey(account_pubkey)
+        })?;
+
+        let body = serde_json::json!({ "account": account.to_string() }).to_string();
+        let request = http_client()?
+            .post(link)
+            .header(ACCEPT, "application/json")
+            .header(CONTENT_TYPE, "application/json")
+            .body(body);
+        let body = send(request).await?;
+
+        let response: PayTransaction = parse(&body)?;
+        let data = BASE64
+            .decode(&response.transaction)
+            .map_err(|e| invalid_response(&format!("transaction is not base64: {}", e)))?;
+        let transaction: SolanaTransaction = bincode::deserialize(&data)
+            .map_err(|e| invalid_response(&format!("transaction does not decode: {}", e)))?;
+        let message = &transaction.message;
+        let signers = &message.account_keys
+            [..(message.header.num_required_signatures as usize).min(message.account_keys.len())];
+        if !signers.contains(&account) {
+            return Err(invalid_response(&format!(
+                "transaction does not need a signature from {}",
+                account
+            )));
+        }
+        Ok(response)
+    }
+
+    // Blocking versions
+
+    pub fn fetch_metadata(url: &str) -> Result<PayMetadata, SolanaUnityError> {
+        runtime::block_on(Self::fetch_metadata_async(url))?
+    }
+
+    pub fn request_transaction(
+        url: &str,
+        account_pubkey: &str,
+    ) -> Result<PayTransaction, SolanaUnityError> {
+        runtime::block_on(Self::request_transaction_async(url, account_pubkey))?
+    }
+}
+
+// The https link of a transaction request, from "solana:<link>" or the bare link. The
+// link is URL-encoded inside a solana: URL when it has query parameters.
+fn link(url: &str) -> Result<Url, SolanaUnityError> {
+    let target = match url.strip_prefix("solana:") {
+        Some(target) if target.starts_with("https:") || target.starts_with("http:") => {
+            target.to_string()
+        }
+        Some(target) => percent_decode(target)?,
+        None => url.to_string(),
+    };
+
+    let link = Url::parse(&target).map_err(|_| {
+        SolanaUnityError::InvalidInput(format!("Not a Solana Pay transaction request: {}", url))
+    })?;
+    // Plain http is only for servers on this machine, e.g. during development
+    let loopback = matches!(link.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    match link.scheme() {
+        "https" => Ok(link),
+        "http" if loopback => Ok(link),
+        _ => Err(SolanaUnityError::InvalidInput(format!(
+            "Solana Pay transaction requests must use https: {}",
+            url
+        ))),
+    }
+}
+
+fn percent_decode(value: &str) -> Result<String, SolanaUnityError> {
+    let invalid = || SolanaUnityError::InvalidInput(format!("Invalid URL encoding: {}", value));
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3).ok_or_else(invalid)?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+fn http_client() -> Result<reqwest::Client, SolanaUnityError> {
+    RpcClientConfig::default().http_client()
+}
+
+// The body of a successful response
+async fn send(request: reqwest::RequestBuilder) -> Result<String, SolanaUnityError> {
+    let response = request.send().await.map_err(request_error)?;
+    let status = response.status();
+    let body = response.text().await.map_err(request_error)?;
+    if !status.is_success() {
+        return Err(SolanaUnityError::RpcError(format!(
+            "Solana Pay request failed with HTTP {}: {}",
+            status.as_u16(),
+            body.chars().take(200).collect::<String>()
+        )));
+    }
+    Ok(body)
+}
+
+fn request_error(error: reqwest::Error) -> SolanaUnityError {
+    let message = format!("Solana Pay request failed: {}", error);
+    if error.is_timeout() {
+        SolanaUnityError::new(ErrorKind::Timeout, message).with_code(code::RPC_TIMEOUT)
+    } else {
+        SolanaUnityError::RpcError(message)
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T, SolanaUnityError> {
+    serde_json::from_str(body).map_err(|e| invalid_response(&e.to_string()))
+}
+
+fn invalid_response(reason: &str) -> SolanaUnityError {
+    SolanaUnityError::SerializationError(format!("Invalid Solana Pay response: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::message::Message;
+    use solana_sdk::system_instruction;
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    const ICON: &str = "https://example.com/icon.svg";
+
+    struct Request {
+        method: String,
+        path: String,
+        content_type: Option<String>,
+        body: String,
+    }
+
+    // A merchant server answering every request with `handler`'s status and body
+    fn serve(handler: impl Fn(&Request) -> (u16, String) + Send + Sync + 'static) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handler = std::sync::Arc::new(handler);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = std::sync::Arc::clone(&handler);
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut stream = stream;
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut parts = request_line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default().to_string();
+
+                    let mut content_length = 0;
+                    let mut content_type = None;
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let line = line.trim_end().to_ascii_lowercase();
+                        if line.is_empty() {
+                            break;
+                        }
+                        if let Some(length) = line.strip_prefix("content-length:") {
+                            content_length = length.trim().parse().unwrap();
+                        }
+                        if let Some(value) = line.strip_prefix("content-type:") {
+                            content_type = Some(value.trim().to_string());
+                        }
+                    }
+                    let mut body = vec![0; content_length];
+                    reader.read_exact(&mut body).unwrap();
+
+                    let request = Request {
+                        method,
+                        path,
+                        content_type,
+                        body: String::from_utf8(body).unwrap(),
+                    };
+                    let (status, body) = handler(&request);
+                    let response = format!(
+                        "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes());
+                });
+            }
+        });
+        url
+    }
+
+    // A transfer from `payer` to the merchant, as a server would build it
+    fn payment(payer: &Pubkey) -> String {
+        let merchant = Pubkey::new_unique();
+        let instruction = system_instruction::transfer(payer, &merchant, 1_000);
+        let transaction =
+            SolanaTransaction::new_unsigned(Message::new(&[instruction], Some(payer)));
+        BASE64.encode(bincode::serialize(&transaction).unwrap())
+    }
+
+    // A merchant following the spec: label and icon on GET, a payment on POST
+    fn merchant() -> String {
+        serve(|request| match request.method.as_str() {
+            "GET" => (
+                200,
+                serde_json::json!({"label": "Potion Shop", "icon": ICON}).to_string(),
+            ),
+            "POST" => {
+                assert_eq!(request.path, "/checkout?item=potion");
+                assert_eq!(request.content_type.as_deref(), Some("application/json"));
+                let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+                let payer = Pubkey::from_str(body["account"].as_str().unwrap()).unwrap();
+                let response = serde_json::json!({
+                    "transaction": payment(&payer),
+                    "message": "Thanks for your purchase",
+                });
+                (200, response.to_string())
+            }
+            _ => (405, String::new()),
+        })
+    }
+
+    #[test]
+    fn test_transaction_request_flow() {
+        let _runtime = runtime::test_runtime();
+        let url = format!("{}/checkout?item=potion", merchant());
+        // Query parameters make the link URL-encoded inside the solana: URL
+        let pay_url = format!(
+            "solana:{}",
+            url.replace(':', "%3A")
+                .replace('/', "%2F")
+                .replace('?', "%3F")
+                .replace('=', "%3D")
+        );
+
+        let metadata = TransactionRequest::fetch_metadata(&pay_url).unwrap();
+        assert_eq!(
+            metadata,
+            PayMetadata {
+                label: "Potion Shop".to_string(),
+                icon: ICON.to_string(),
+            }
+        );
+
+        let payer = Pubkey::new_unique();
+        let response =
+            TransactionRequest::request_transaction(&pay_url, &payer.to_string()).unwrap();
+        assert_eq!(
+            response.message.as_deref(),
+            Some("Thanks for your purchase")
+        );
+
+        // The existing path takes over from here
+        let mut transaction = crate::transaction::Transaction::new();
+        transaction
+            .from_serialized(&BASE64.decode(&response.transaction).unwrap())
+            .unwrap();
+        assert_eq!(
+            transaction.get_transaction().unwrap().message.account_keys[0],
+            payer
+        );
+
+        // The bare link works too
+        assert_eq!(TransactionRequest::fetch_metadata(&url).unwrap(), metadata);
+    }
+
+    #[test]
+    fn test_invalid_responses() {
+        let _runtime = runtime::test_runtime();
+        let url = serve(|request| match request.path.as_str() {
+            "/no-label" => (200, serde_json::json!({"icon": ICON}).to_string()),
+            "/http-icon" => (
+                200,
+                serde_json::json!({"label": "Shop", "icon": "http://example.com/i.png"})
+                    .to_string(),
+            ),
+            "/not-json" => (200, "<html></html>".to_string()),
+            "/garbage" => (
+                200,
+                serde_json::json!({"transaction": BASE64.encode([1, 2, 3])}).to_string(),
+            ),
+            "/other-payer" => (
+                200,
+                serde_json::json!({"transaction": payment(&Pubkey::new_unique())}).to_string(),
+            ),
+            _ => (500, "{\"error\": \"out of potions\"}".to_string()),
+        });
+        let account = Pubkey::new_unique().to_string();
+
+        let error = TransactionRequest::fetch_metadata(&format!("{}/no-label", url)).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Serialization);
+        assert!(error.message.contains("missing field `label`"));
+        let error = TransactionRequest::fetch_metadata(&format!("{}/http-icon", url)).unwrap_err();
+        assert!(error.message.contains("not an absolute https URL"));
+        assert!(TransactionRequest::fetch_metadata(&format!("{}/not-json", url)).is_err());
+
+        let error = TransactionRequest::request_transaction(&format!("{}/garbage", url), &account)
+            .unwrap_err();
+        assert!(error.message.contains("does not decode"));
+        let error =
+            TransactionRequest::request_transaction(&format!("{}/other-payer", url), &account)
+                .unwrap_err();
+        assert!(error.message.contains("does not need a signature"));
+
+        let error = TransactionRequest::request_transaction(&format!("{}/sold-out", url), &account)
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Rpc);
+        assert!(error.message.contains("HTTP 500"));
+        assert!(error.message.contains("out of potions"));
+    }
+
+    #[test]
+    fn test_links() {
+        let _runtime = runtime::test_runtime();
+        assert_eq!(
+            link("solana:https%3A%2F%2Fshop.example%2Fpay%3Fitem%3D1")
+                .unwrap()
+                .as_str(),
+            "https://shop.example/pay?item=1"
+        );
+        assert_eq!(
+            link("solana:https://shop.example/pay").unwrap().as_str(),
+            "https://shop.example/pay"
+        );
+
+        // A transfer request, not a transaction request
+        let error =
+            link("solana:mvines9iiHiQTysrwkJjGf2gb9Ex9jXJX8ns3qwf2kN?amount=1").unwrap_err();
+        assert!(error
+            .message
+            .contains("Not a Solana Pay transaction request"));
+        assert!(link("solana:http%3A%2F%2Fshop.example%2Fpay")
+            .unwrap_err()
+            .message
+            .contains("must use https"));
+        assert!(link("solana:https%3A%2F%2Fshop%ZZ").is_err());
+        assert!(TransactionRequest::request_transaction(
+            "https://shop.example/pay",
+            "not-a-pubkey"
+        )
+        .is_err());
+    }
+}
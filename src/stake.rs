@@ -0,0 +1,264 @@
+use serde::Serialize;
+use solana_sdk::stake::state::{Delegation, Meta, StakeStateV2};
+
+use crate::error::SolanaUnityError;
+
+/// A stake account, decoded from its `StakeStateV2`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeAccount {
+    /// "uninitialized", "initialized", "delegated" or "rewardsPool"
+    pub state: &'static str,
+    /// "activating", "active", "deactivating" or "inactive" as of `epoch`. Uninitialized
+    /// and rewards pool accounts repeat their state here, since neither can hold stake.
+    pub status: &'static str,
+    /// The epoch `status` was worked out for
+    pub epoch: u64,
+    /// None for uninitialized and rewards pool accounts
+    pub meta: Option<StakeMeta>,
+    /// Set once the stake is delegated, which it stays after deactivating
+    pub delegation: Option<StakeDelegation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeMeta {
+    pub rent_exempt_reserve: u64,
+    pub staker: String,
+    pub withdrawer: String,
+    pub lockup: StakeLockup,
+}
+
+/// Until both `unix_timestamp` and `epoch` have passed, only `custodian` may lift the
+/// lockup. All zeros means no lockup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeLockup {
+    pub unix_timestamp: i64,
+    pub epoch: u64,
+    pub custodian: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeDelegation {
+    /// The vote account of the validator the stake is delegated to
+    pub voter: String,
+    pub stake: u64,
+    /// u64::MAX for stake that was active from genesis
+    pub activation_epoch: u64,
+    /// None until the stake is deactivated
+    pub deactivation_epoch: Option<u64>,
+    pub credits_observed: u64,
+}
+
+/// Decodes the data of a stake account and works out its status in `epoch`, the current
+/// epoch. The status goes by the delegation's epochs alone: while the cluster limits how
+/// much stake may warm up or cool down per epoch, a large delegation can stay partly
+/// activating or deactivating for a few epochs longer.
+pub fn parse_stake_account(data: &[u8], epoch: u64) -> Result<StakeAccount, SolanaUnityError> {
+    let state: StakeStateV2 = bincode::deserialize(data).map_err(|e| {
+        SolanaUnityError::InvalidInput(format!("Invalid stake account data: {}", e))
+    })?;
+
+    let account = match state {
+        StakeStateV2::Uninitialized => StakeAccount {
+            state: "uninitialized",
+            status: "uninitialized",
+            epoch,
+            meta: None,
+            delegation: None,
+        },
+        StakeStateV2::Initialized(meta) => StakeAccount {
+            state: "initialized",
+            status: "inactive",
+            epoch,
+            meta: Some(meta_of(&meta)),
+            delegation: None,
+        },
+        StakeStateV2::Stake(meta, stake, _) => StakeAccount {
+            state: "delegated",
+            status: status(&stake.delegation, epoch),
+            epoch,
+            meta: Some(meta_of(&meta)),
+            delegation: Some(StakeDelegation {
+                voter: stake.delegation.voter_pubkey.to_string(),
+                stake: stake.delegation.stake,
+                activation_epoch: stake.delegation.activation_epoch,
+                deactivation_epoch: Some(stake.delegation.deactivation_epoch)
+                    .filter(|deactivation| *deactivation != u64::MAX),
+                credits_observed: stake.credits_observed,
+            }),
+        },
+        StakeStateV2::RewardsPool => StakeAccount {
+            state: "rewardsPool",
+            status: "rewardsPool",
+            epoch,
+            meta: None,
+            delegation: None,
+        },
+    };
+    Ok(account)
+}
+
+fn meta_of(meta: &Meta) -> StakeMeta {
+    StakeMeta {
+        rent_exempt_reserve: meta.rent_exempt_reserve,
+        staker: meta.authorized.staker.to_string(),
+        withdrawer: meta.authorized.withdrawer.to_string(),
+        lockup: StakeLockup {
+            unix_timestamp: meta.lockup.unix_timestamp,
+            epoch: meta.lockup.epoch,
+            custodian: meta.lockup.custodian.to_string(),
+        },
+    }
+}
+
+// Stake delegated in epoch N activates at the start of N + 1, and stake deactivated in N
+// is inactive from N + 1
+fn status(delegation: &Delegation, epoch: u64) -> &'static str {
+    if delegation.deactivation_epoch != u64::MAX {
+        // Deactivated in the epoch it was delegated in, so it never activated
+        if delegation.activation_epoch == delegation.deactivation_epoch
+            || epoch > delegation.deactivation_epoch
+        {
+            "inactive"
+        } else {
+            "deactivating"
+        }
+    } else if delegation.activation_epoch != u64::MAX && epoch <= delegation.activation_epoch {
+        "activating"
+    } else {
+        "active"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+    use serde_json::Value;
+
+    // Stake accounts in every state as getAccountInfo returns them, with their
+    // authorities and the epoch they were read in: delegations made in that epoch
+    // (activating), earlier (active, with a lockup), deactivated in that epoch
+    // (deactivating) and earlier (inactive)
+    fn fixture() -> Value {
+        serde_json::from_str(include_str!("../tests/fixtures/stake/stake_accounts.json")).unwrap()
+    }
+
+    fn account_data(fixture: &Value, name: &str) -> Vec<u8> {
+        let encoded = fixture["accounts"][name]["account"]["data"][0]
+            .as_str()
+            .unwrap();
+        BASE64.decode(encoded).unwrap()
+    }
+
+    fn decode(name: &str) -> StakeAccount {
+        let fixture = fixture();
+        let epoch = fixture["epoch"].as_u64().unwrap();
+        parse_stake_account(&account_data(&fixture, name), epoch).unwrap()
+    }
+
+    #[test]
+    fn test_delegation_lifecycle() {
+        let fixture = fixture();
+        for (name, status) in [
+            ("activating", "activating"),
+            ("active", "active"),
+            ("deactivating", "deactivating"),
+            ("inactive", "inactive"),
+        ] {
+            let account = decode(name);
+            assert_eq!(account.state, "delegated", "{}", name);
+            assert_eq!(account.status, status, "{}", name);
+            assert_eq!(account.epoch, 612);
+
+            let meta = account.meta.unwrap();
+            assert_eq!(meta.rent_exempt_reserve, 2_282_880);
+            assert_eq!(meta.staker, fixture["staker"]);
+            assert_eq!(meta.withdrawer, fixture["withdrawer"]);
+            let delegation = account.delegation.unwrap();
+            assert_eq!(delegation.voter, fixture["voter"]);
+            assert_eq!(delegation.stake, 5_000_000_000);
+        }
+
+        let active = decode("active");
+        let delegation = active.delegation.as_ref().unwrap();
+        assert_eq!(delegation.activation_epoch, 590);
+        assert_eq!(delegation.deactivation_epoch, None);
+        assert_eq!(delegation.credits_observed, 123_456);
+        let lockup = &active.meta.as_ref().unwrap().lockup;
+        assert_eq!(lockup.epoch, 640);
+        assert_eq!(lockup.custodian, fixture["custodian"]);
+
+        let deactivating = decode("deactivating").delegation.unwrap();
+        assert_eq!(deactivating.activation_epoch, 500);
+        assert_eq!(deactivating.deactivation_epoch, Some(612));
+    }
+
+    #[test]
+    fn test_undelegated_states() {
+        let initialized = decode("initialized");
+        assert_eq!(initialized.state, "initialized");
+        assert_eq!(initialized.status, "inactive");
+        assert!(initialized.meta.is_some());
+        assert_eq!(initialized.delegation, None);
+
+        for (name, state) in [
+            ("uninitialized", "uninitialized"),
+            ("rewardsPool", "rewardsPool"),
+        ] {
+            let account = decode(name);
+            assert_eq!(account.state, state);
+            assert_eq!(account.status, state);
+            assert_eq!(account.meta, None);
+            assert_eq!(account.delegation, None);
+        }
+    }
+
+    #[test]
+    fn test_status_by_epoch() {
+        let fixture = fixture();
+        let data = account_data(&fixture, "deactivating");
+        let status = |epoch| parse_stake_account(&data, epoch).unwrap().status;
+        assert_eq!(status(612), "deactivating");
+        assert_eq!(status(613), "inactive");
+
+        let data = account_data(&fixture, "activating");
+        let status = |epoch| parse_stake_account(&data, epoch).unwrap().status;
+        assert_eq!(status(612), "activating");
+        assert_eq!(status(613), "active");
+
+        // Deactivated in the epoch it was delegated in
+        let mut state: StakeStateV2 = bincode::deserialize(&data).unwrap();
+        if let StakeStateV2::Stake(_, stake, _) = &mut state {
+            stake.delegation.deactivation_epoch = 612;
+        }
+        let data = bincode::serialize(&state).unwrap();
+        assert_eq!(parse_stake_account(&data, 612).unwrap().status, "inactive");
+    }
+
+    #[test]
+    fn test_json() {
+        let json = serde_json::to_value(decode("deactivating")).unwrap();
+        assert_eq!(json["state"], "delegated");
+        assert_eq!(json["meta"]["rentExemptReserve"], 2_282_880);
+        assert_eq!(json["meta"]["lockup"]["unixTimestamp"], 0);
+        assert_eq!(json["delegation"]["activationEpoch"], 500);
+        assert_eq!(json["delegation"]["deactivationEpoch"], 612);
+
+        let json = serde_json::to_value(decode("active")).unwrap();
+        assert_eq!(json["delegation"]["deactivationEpoch"], Value::Null);
+    }
+
+    #[test]
+    fn test_rejects_other_data() {
+        assert!(parse_stake_account(&[], 612).is_err());
+        // An unknown variant
+        assert!(parse_stake_account(&[4, 0, 0, 0], 612).is_err());
+        let fixture = fixture();
+        assert!(parse_stake_account(&account_data(&fixture, "active")[..100], 612).is_err());
+    }
+}
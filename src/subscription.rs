@@ -0,0 +1,985 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::error::SolanaUnityError;
+use crate::logging::{LOG_DEBUG, LOG_WARN};
+use crate::native_log;
+
+// How long the worker waits for a message before checking for work and shutdown
+const RECV_INTERVAL: Duration = Duration::from_millis(50);
+
+/// One connection to a pubsub endpoint, carrying the JSON-RPC text messages of Solana's
+/// websocket API. A websocket implements it by sending and receiving text frames.
+pub trait PubsubConnection: Send {
+    fn send(&mut self, message: &str) -> Result<(), SolanaUnityError>;
+
+    /// The next message, or None when none arrived within `timeout`. An error means the
+    /// connection is gone.
+    fn recv(&mut self, timeout: Duration) -> Result<Option<String>, SolanaUnityError>;
+}
+
+/// Opens connections: once at the start and again after every disconnect. Closures
+/// returning a boxed connection are connectors.
+pub trait PubsubConnector: Send + 'static {
+    fn connect(&mut self) -> Result<Box<dyn PubsubConnection>, SolanaUnityError>;
+}
+
+impl<F> PubsubConnector for F
+where
+    F: FnMut() -> Result<Box<dyn PubsubConnection>, SolanaUnityError> + Send + 'static,
+{
+    fn connect(&mut self) -> Result<Box<dyn PubsubConnection>, SolanaUnityError> {
+        self()
+    }
+}
+
+/// How a `SubscriptionClient` reconnects, e.g. `{"maxAttempts": 5, "maxBackoffMs": 10000}`.
+/// Missing fields keep their defaults; unknown fields are rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct ReconnectPolicy {
+    /// Wait before the first retry, doubled for each retry after it
+    pub initial_backoff_ms: u64,
+    /// Longest wait between retries
+    pub max_backoff_ms: u64,
+    /// Reconnection attempts in a row that may fail before giving up, 0 to never reconnect
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            max_attempts: 10,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn from_json(json: &str) -> Result<Self, SolanaUnityError> {
+        let policy: Self = serde_json::from_str(json).map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Invalid reconnect policy: {}", e))
+        })?;
+        if policy.initial_backoff_ms > policy.max_backoff_ms {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "initialBackoffMs ({}) must not exceed maxBackoffMs ({})",
+                policy.initial_backoff_ms, policy.max_backoff_ms
+            )));
+        }
+        Ok(policy)
+    }
+
+    /// The wait before retry number `attempt`, counting from 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u64
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(self.max_backoff_ms);
+        Duration::from_millis(backoff)
+    }
+}
+
+/// How many events a `Subscription` holds for its poller, e.g. `{"capacity": 256}`.
+/// Missing fields keep their defaults; unknown fields are rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct QueuePolicy {
+    /// Most events queued at once
+    pub capacity: usize,
+    /// Which event is dropped when one arrives at a full queue
+    pub overflow: Overflow,
+}
+
+impl Default for QueuePolicy {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow: Overflow::DropOldest,
+        }
+    }
+}
+
+impl QueuePolicy {
+    pub fn from_json(json: &str) -> Result<Self, SolanaUnityError> {
+        let policy: Self = serde_json::from_str(json)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid queue policy: {}", e)))?;
+        if policy.capacity == 0 {
+            return Err(SolanaUnityError::InvalidInput(
+                "Queue capacity must be at least 1".to_string(),
+            ));
+        }
+        Ok(policy)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Overflow {
+    /// Keep the newest events, for subscriptions where only the latest state matters
+    DropOldest,
+    /// Keep the events already queued
+    DropNewest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionState {
+    /// Opening the first connection
+    Connecting,
+    Connected,
+    /// The connection dropped or could not be opened, and another attempt is coming
+    Reconnecting,
+    /// `max_attempts` attempts in a row failed; subscriptions are dead
+    GaveUp,
+    /// Closed locally
+    Closed,
+}
+
+/// What `SubscriptionClient::poll_event` returns, as JSON with a `type` field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SubscriptionEvent {
+    /// A notification for subscription `id`, e.g. method "accountNotification"
+    #[serde(rename_all = "camelCase")]
+    Notification {
+        id: u64,
+        method: String,
+        result: Value,
+    },
+    /// The node rejected subscription `id`, which is then removed
+    #[serde(rename_all = "camelCase")]
+    SubscriptionError { id: u64, message: String },
+    /// The connection changed state. `attempt` counts failed attempts in a row while
+    /// reconnecting and is 0 otherwise.
+    #[serde(rename_all = "camelCase")]
+    ConnectionState {
+        state: ConnectionState,
+        attempt: u32,
+    },
+}
+
+/// The state of one `Subscription`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionState {
+    /// Connected, with the subscription requested or in place
+    Active,
+    /// Waiting for a connection, the first one included
+    Reconnecting,
+    /// Rejected by the node, unsubscribed, or the client gave up or was dropped
+    Closed,
+}
+
+// A bounded event queue of one Subscription
+struct EventQueue {
+    events: VecDeque<SubscriptionEvent>,
+    policy: QueuePolicy,
+    dropped: u64,
+}
+
+impl EventQueue {
+    fn push(&mut self, event: SubscriptionEvent) {
+        if self.events.len() >= self.policy.capacity {
+            self.dropped += 1;
+            match self.policy.overflow {
+                Overflow::DropOldest => {
+                    self.events.pop_front();
+                }
+                Overflow::DropNewest => return,
+            }
+        }
+        self.events.push_back(event);
+    }
+}
+
+struct Entry {
+    method: String,
+    params: Value,
+    // Set once the subscribe request went out on the current connection
+    requested: bool,
+    server_id: Option<u64>,
+}
+
+enum Pending {
+    Subscribe { id: u64, method: String },
+    Unsubscribe,
+}
+
+struct Inner {
+    next_id: u64,
+    next_request_id: u64,
+    subscriptions: BTreeMap<u64, Entry>,
+    // Server subscription ids of the current connection
+    server_ids: HashMap<u64, u64>,
+    pending: HashMap<u64, Pending>,
+    // (method, server id) pairs to unsubscribe on the current connection
+    unsubscribes: Vec<(String, u64)>,
+    events: VecDeque<SubscriptionEvent>,
+    // Queues of the subscriptions opened as `Subscription`s. They outlive the entries, so a
+    // rejected subscription's error can still be polled.
+    queues: HashMap<u64, EventQueue>,
+    state: ConnectionState,
+}
+
+impl Inner {
+    // Queues an event of subscription `id`, in its own queue if it has one
+    fn push_event(&mut self, id: u64, event: SubscriptionEvent) {
+        match self.queues.get_mut(&id) {
+            Some(queue) => queue.push(event),
+            None => self.events.push_back(event),
+        }
+    }
+
+    fn unsubscribe(&mut self, id: u64) -> bool {
+        let entry = match self.subscriptions.remove(&id) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        if let Some(server_id) = entry.server_id {
+            self.server_ids.remove(&server_id);
+            self.unsubscribes.push((entry.method, server_id));
+        }
+        self.events.retain(|event| match event {
+            SubscriptionEvent::Notification { id: event_id, .. } => *event_id != id,
+            _ => true,
+        });
+        true
+    }
+}
+
+struct Shared {
+    inner: Mutex<Inner>,
+    stop: AtomicBool,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // Every update leaves the state consistent, so a panic elsewhere does not spoil it
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn set_state(&self, state: ConnectionState, attempt: u32) {
+        let mut inner = self.lock();
+        inner.state = state;
+        let event = SubscriptionEvent::ConnectionState { state, attempt };
+        for queue in inner.queues.values_mut() {
+            queue.push(event.clone());
+        }
+        inner.events.push_back(event);
+    }
+}
+
+/// Subscriptions over a pubsub connection that survive disconnects. A background thread
+/// keeps the connection open: when it drops, it reconnects with exponential backoff and
+/// subscribes everything again, and connection state changes are queued as events next to
+/// the notifications.
+///
+/// Ids returned by `subscribe` are local and stay the same across reconnects, while the
+/// node hands out new subscription ids on every connection.
+pub struct SubscriptionClient {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SubscriptionClient {
+    pub fn start(
+        connector: impl PubsubConnector,
+        policy: ReconnectPolicy,
+    ) -> Result<Self, SolanaUnityError> {
+        let shared = Arc::new(Shared {
+            inner: Mutex::new(Inner {
+                next_id: 1,
+                next_request_id: 1,
+                subscriptions: BTreeMap::new(),
+                server_ids: HashMap::new(),
+                pending: HashMap::new(),
+                unsubscribes: Vec::new(),
+                events: VecDeque::new(),
+                queues: HashMap::new(),
+                state: ConnectionState::Connecting,
+            }),
+            stop: AtomicBool::new(false),
+        });
+
+        let worker_shared = Arc::clone(&shared);
+        let worker = thread::Builder::new()
+            .name("solana-unity-pubsub".to_string())
+            .spawn(move || run(&worker_shared, connector, &policy))
+            .map_err(|e| {
+                SolanaUnityError::FfiError(format!("Failed to start pubsub thread: {}", e))
+            })?;
+
+        Ok(Self {
+            shared,
+            worker: Some(worker),
+        })
+    }
+
+    /// Subscribes with `method`, e.g. "accountSubscribe", and `params`, e.g.
+    /// `["<pubkey>", {"encoding": "base64"}]`. The request is sent once connected.
+    pub fn subscribe(&self, method: &str, params: Value) -> Result<u64, SolanaUnityError> {
+        self.add(method, params, None)
+    }
+
+    /// Like `subscribe`, but the subscription's events go to a queue of its own, bounded by
+    /// `queue`, which the returned `Subscription` polls. Dropping it unsubscribes.
+    pub fn open(
+        &self,
+        method: &str,
+        params: Value,
+        queue: QueuePolicy,
+    ) -> Result<Subscription, SolanaUnityError> {
+        let id = self.add(method, params, Some(queue))?;
+        Ok(Subscription {
+            shared: Arc::clone(&self.shared),
+            id,
+        })
+    }
+
+    fn add(
+        &self,
+        method: &str,
+        params: Value,
+        queue: Option<QueuePolicy>,
+    ) -> Result<u64, SolanaUnityError> {
+        if !method.ends_with("Subscribe") {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Not a subscribe method: {}",
+                method
+            )));
+        }
+
+        let mut inner = self.shared.lock();
+        if inner.state == ConnectionState::GaveUp {
+            return Err(SolanaUnityError::RpcError(
+                "Pubsub connection gave up reconnecting".to_string(),
+            ));
+        }
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.subscriptions.insert(
+            id,
+            Entry {
+                method: method.to_string(),
+                params,
+                requested: false,
+                server_id: None,
+            },
+        );
+        if let Some(policy) = queue {
+            inner.queues.insert(
+                id,
+                EventQueue {
+                    events: VecDeque::new(),
+                    policy,
+                    dropped: 0,
+                },
+            );
+        }
+        Ok(id)
+    }
+
+    /// Ends subscription `id`. Its queued notifications are discarded, as is anything the
+    /// node sends for it afterwards. Returns false for unknown ids.
+    pub fn unsubscribe(&self, id: u64) -> bool {
+        self.shared.lock().unsubscribe(id)
+    }
+
+    /// The oldest queued event, or None when there is none
+    pub fn poll_event(&self) -> Option<SubscriptionEvent> {
+        self.shared.lock().events.pop_front()
+    }
+
+    pub fn pending_events(&self) -> usize {
+        self.shared.lock().events.len()
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.shared.lock().state
+    }
+
+    /// Ids of the active subscriptions
+    pub fn subscriptions(&self) -> Vec<u64> {
+        self.shared.lock().subscriptions.keys().copied().collect()
+    }
+}
+
+/// A subscription opened with `SubscriptionClient::open`. Its queue receives the
+/// subscription's notifications and errors along with every connection state change, in
+/// the order they happened, so one poller can follow everything about it.
+///
+/// It keeps working across reconnects for as long as the client lives. Dropping it
+/// discards the queue and unsubscribes on the node.
+pub struct Subscription {
+    shared: Arc<Shared>,
+    id: u64,
+}
+
+impl Subscription {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The oldest queued event, or None when there is none
+    pub fn poll_next(&self) -> Option<SubscriptionEvent> {
+        let mut inner = self.shared.lock();
+        inner.queues.get_mut(&self.id)?.events.pop_front()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        let inner = self.shared.lock();
+        inner
+            .queues
+            .get(&self.id)
+            .map_or(0, |queue| queue.events.len())
+    }
+
+    /// Events dropped so far because the queue was full
+    pub fn dropped_count(&self) -> u64 {
+        let inner = self.shared.lock();
+        inner.queues.get(&self.id).map_or(0, |queue| queue.dropped)
+    }
+
+    pub fn state(&self) -> SubscriptionState {
+        let inner = self.shared.lock();
+        if !inner.subscriptions.contains_key(&self.id) {
+            return SubscriptionState::Closed;
+        }
+        match inner.state {
+            ConnectionState::Connected => SubscriptionState::Active,
+            ConnectionState::Connecting | ConnectionState::Reconnecting => {
+                SubscriptionState::Reconnecting
+            }
+            ConnectionState::GaveUp | ConnectionState::Closed => SubscriptionState::Closed,
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let mut inner = self.shared.lock();
+        inner.queues.remove(&self.id);
+        inner.unsubscribe(self.id);
+    }
+}
+
+impl Drop for SubscriptionClient {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+// The worker: connects, serves the connection until it drops, and reconnects until
+// stopped or out of attempts
+fn run(shared: &Shared, mut connector: impl PubsubConnector, policy: &ReconnectPolicy) {
+    let mut failures = 0;
+    while !shared.stop.load(Ordering::SeqCst) {
+        match connector.connect() {
+            Ok(mut connection) => {
+                failures = 0;
+                resubscribe(shared);
+                shared.set_state(ConnectionState::Connected, 0);
+                match serve(shared, connection.as_mut()) {
+                    Ok(()) => break,
+                    Err(e) => native_log!(LOG_WARN, "Pubsub connection lost: {}", e),
+                }
+            }
+            Err(e) => native_log!(LOG_WARN, "Pubsub connection failed: {}", e),
+        }
+
+        failures += 1;
+        if failures > policy.max_attempts {
+            shared.set_state(ConnectionState::GaveUp, failures - 1);
+            return;
+        }
+        shared.set_state(ConnectionState::Reconnecting, failures);
+
+        let resume = Instant::now() + policy.backoff(failures);
+        while Instant::now() < resume && !shared.stop.load(Ordering::SeqCst) {
+            thread::sleep(RECV_INTERVAL.min(resume - Instant::now()));
+        }
+    }
+    shared.set_state(ConnectionState::Closed, 0);
+}
+
+// Forgets everything tied to the previous connection, so every subscription is requested
+// again on the new one
+fn resubscribe(shared: &Shared) {
+    let mut inner = shared.lock();
+    inner.server_ids.clear();
+    inner.pending.clear();
+    inner.unsubscribes.clear();
+    for subscription in inner.subscriptions.values_mut() {
+        subscription.requested = false;
+        subscription.server_id = None;
+    }
+}
+
+// Sends requests and handles messages until the connection drops (Err) or the client
+// closes (Ok)
+fn serve(shared: &Shared, connection: &mut dyn PubsubConnection) -> Result<(), SolanaUnityError> {
+    while !shared.stop.load(Ordering::SeqCst) {
+        for message in outgoing(shared) {
+            connection.send(&message)?;
+        }
+        if let Some(message) = connection.recv(RECV_INTERVAL)? {
+            handle_message(shared, &message);
+        }
+    }
+    Ok(())
+}
+
+// The subscribe and unsubscribe requests not sent yet on this connection
+fn outgoing(shared: &Shared) -> Vec<String> {
+    let mut guard = shared.lock();
+    let inner = &mut *guard;
+    let mut messages = Vec::new();
+
+    for (method, server_id) in std::mem::take(&mut inner.unsubscribes) {
+        let request_id = inner.next_request_id;
+        inner.next_request_id += 1;
+        inner.pending.insert(request_id, Pending::Unsubscribe);
+        let method = method.replace("Subscribe", "Unsubscribe");
+        messages.push(request(request_id, &method, json!([server_id])));
+    }
+
+    for (id, subscription) in inner.subscriptions.iter_mut() {
+        if subscription.requested {
+            continue;
+        }
+        subscription.requested = true;
+        let request_id = inner.next_request_id;
+        inner.next_request_id += 1;
+        inner.pending.insert(
+            request_id,
+            Pending::Subscribe {
+                id: *id,
+                method: subscription.method.clone(),
+            },
+        );
+        messages.push(request(
+            request_id,
+            &subscription.method,
+            subscription.params.clone(),
+        ));
+    }
+    messages
+}
+
+fn request(id: u64, method: &str, params: Value) -> String {
+    json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}).to_string()
+}
+
+fn handle_message(shared: &Shared, message: &str) {
+    let message: Value = match serde_json::from_str(message) {
+        Ok(message) => message,
+        Err(e) => {
+            native_log!(LOG_WARN, "Ignoring unreadable pubsub message: {}", e);
+            return;
+        }
+    };
+    let mut guard = shared.lock();
+    let inner = &mut *guard;
+
+    // A notification: {"method": "accountNotification", "params": {"subscription", "result"}}
+    if let Some(method) = message["method"].as_str() {
+        let server_id = message["params"]["subscription"].as_u64();
+        match server_id.and_then(|server_id| inner.server_ids.get(&server_id).copied()) {
+            Some(id) => inner.push_event(
+                id,
+                SubscriptionEvent::Notification {
+                    id,
+                    method: method.to_string(),
+                    result: message["params"]["result"].clone(),
+                },
+            ),
+            // Unsubscribed locally, or from a previous connection
+            None => native_log!(LOG_DEBUG, "Dropping {} for {:?}", method, server_id),
+        }
+        return;
+    }
+
+    // A response: {"id", "result"} or {"id", "error"}
+    let pending = message["id"]
+        .as_u64()
+        .and_then(|request_id| inner.pending.remove(&request_id));
+    let (id, method) = match pending {
+        Some(Pending::Subscribe { id, method }) => (id, method),
+        Some(Pending::Unsubscribe) | None => return,
+    };
+    match message["result"].as_u64() {
+        Some(server_id) => match inner.subscriptions.get_mut(&id) {
+            Some(subscription) => {
+                subscription.server_id = Some(server_id);
+                inner.server_ids.insert(server_id, id);
+            }
+            // Unsubscribed while the request was in flight
+            None => inner.unsubscribes.push((method, server_id)),
+        },
+        None => {
+            inner.subscriptions.remove(&id);
+            let message = message["error"]["message"]
+                .as_str()
+                .unwrap_or("Subscription failed")
+                .to_string();
+            inner.push_event(id, SubscriptionEvent::SubscriptionError { id, message });
+        }
+    }
+}
+
+// A fake node for tests, here and in ffi.rs, to drive a SubscriptionClient with
+#[cfg(test)]
+pub(crate) mod fake {
+    use super::*;
+    use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+
+    pub const WAIT: Duration = Duration::from_secs(5);
+
+    // The client's end of a fake connection. None from the node, or the node going
+    // away, is a disconnect.
+    pub struct FakeConnection {
+        incoming: Receiver<Option<String>>,
+        sent: Sender<String>,
+    }
+
+    impl PubsubConnection for FakeConnection {
+        fn send(&mut self, message: &str) -> Result<(), SolanaUnityError> {
+            self.sent
+                .send(message.to_string())
+                .map_err(|_| SolanaUnityError::RpcError("Connection reset".to_string()))
+        }
+
+        fn recv(&mut self, timeout: Duration) -> Result<Option<String>, SolanaUnityError> {
+            match self.incoming.recv_timeout(timeout) {
+                Ok(Some(message)) => Ok(Some(message)),
+                Err(RecvTimeoutError::Timeout) => Ok(None),
+                Ok(None) | Err(RecvTimeoutError::Disconnected) => {
+                    Err(SolanaUnityError::RpcError("Connection reset".to_string()))
+                }
+            }
+        }
+    }
+
+    // The node's end of a fake connection
+    pub struct FakeNode {
+        pub to_client: Sender<Option<String>>,
+        from_client: Receiver<String>,
+    }
+
+    impl FakeNode {
+        pub fn request(&self) -> Value {
+            serde_json::from_str(&self.from_client.recv_timeout(WAIT).unwrap()).unwrap()
+        }
+
+        pub fn respond(&self, request: &Value, result: Value) {
+            let response = json!({"jsonrpc": "2.0", "result": result, "id": request["id"]});
+            self.to_client.send(Some(response.to_string())).unwrap();
+        }
+
+        pub fn notify(&self, method: &str, subscription: u64, result: Value) {
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": {"result": result, "subscription": subscription},
+            });
+            self.to_client.send(Some(notification.to_string())).unwrap();
+        }
+
+        pub fn disconnect(&self) {
+            self.to_client.send(None).unwrap();
+        }
+    }
+
+    // Hands the node end of every connection to the test, or fails to connect while
+    // `accept` is false
+    pub fn connector(nodes: Sender<FakeNode>, accept: Arc<AtomicBool>) -> impl PubsubConnector {
+        move || -> Result<Box<dyn PubsubConnection>, SolanaUnityError> {
+            if !accept.load(Ordering::SeqCst) {
+                return Err(SolanaUnityError::RpcError("Connection refused".to_string()));
+            }
+            let (to_client, incoming) = mpsc::channel();
+            let (sent, from_client) = mpsc::channel();
+            let _ = nodes.send(FakeNode {
+                to_client,
+                from_client,
+            });
+            Ok(Box::new(FakeConnection { incoming, sent }))
+        }
+    }
+
+    pub fn fast_policy(max_attempts: u32) -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_backoff_ms: 1,
+            max_backoff_ms: 4,
+            max_attempts,
+        }
+    }
+
+    pub fn start(max_attempts: u32, accept: bool) -> (SubscriptionClient, Receiver<FakeNode>) {
+        let (nodes, node_receiver) = mpsc::channel();
+        let accept = Arc::new(AtomicBool::new(accept));
+        let client =
+            SubscriptionClient::start(connector(nodes, accept), fast_policy(max_attempts)).unwrap();
+        (client, node_receiver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fake::*;
+    use super::*;
+
+    fn next_event(client: &SubscriptionClient) -> SubscriptionEvent {
+        let deadline = Instant::now() + WAIT;
+        loop {
+            if let Some(event) = client.poll_event() {
+                return event;
+            }
+            assert!(Instant::now() < deadline, "no event");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn state(state: ConnectionState, attempt: u32) -> SubscriptionEvent {
+        SubscriptionEvent::ConnectionState { state, attempt }
+    }
+
+    fn notification(id: u64, method: &str, result: Value) -> SubscriptionEvent {
+        SubscriptionEvent::Notification {
+            id,
+            method: method.to_string(),
+            result,
+        }
+    }
+
+    #[test]
+    fn test_resubscribes_after_disconnect() {
+        let (client, nodes) = start(3, true);
+        let node = nodes.recv_timeout(WAIT).unwrap();
+        assert_eq!(next_event(&client), state(ConnectionState::Connected, 0));
+
+        let params = json!(["Acc1", {"encoding": "base64"}]);
+        let id = client
+            .subscribe("accountSubscribe", params.clone())
+            .unwrap();
+        let request = node.request();
+        assert_eq!(request["method"], "accountSubscribe");
+        assert_eq!(request["params"], params);
+        node.respond(&request, json!(100));
+        node.notify("accountNotification", 100, json!({"lamports": 1}));
+        assert_eq!(
+            next_event(&client),
+            notification(id, "accountNotification", json!({"lamports": 1}))
+        );
+
+        // The connection drops mid-stream
+        node.disconnect();
+        assert_eq!(next_event(&client), state(ConnectionState::Reconnecting, 1));
+        let node = nodes.recv_timeout(WAIT).unwrap();
+        assert_eq!(next_event(&client), state(ConnectionState::Connected, 0));
+        assert_eq!(client.state(), ConnectionState::Connected);
+
+        // The subscription is replayed and gets a new server id, under the same local id
+        let request = node.request();
+        assert_eq!(request["method"], "accountSubscribe");
+        assert_eq!(request["params"], params);
+        node.respond(&request, json!(200));
+        // The old server id means nothing on this connection
+        node.notify("accountNotification", 100, json!({"lamports": 99}));
+        node.notify("accountNotification", 200, json!({"lamports": 2}));
+        assert_eq!(
+            next_event(&client),
+            notification(id, "accountNotification", json!({"lamports": 2}))
+        );
+
+        // Unsubscribing discards the queued notifications and drops later ones
+        node.notify("accountNotification", 200, json!({"lamports": 3}));
+        thread::sleep(Duration::from_millis(100));
+        assert!(client.unsubscribe(id));
+        assert_eq!(client.pending_events(), 0);
+        let request = node.request();
+        assert_eq!(request["method"], "accountUnsubscribe");
+        assert_eq!(request["params"], json!([200]));
+        node.notify("accountNotification", 200, json!({"lamports": 4}));
+
+        let slot = client.subscribe("slotSubscribe", json!([])).unwrap();
+        let request = node.request();
+        node.respond(&request, json!(300));
+        node.notify("slotNotification", 300, json!({"slot": 7}));
+        // The late account notification was handled first, and dropped
+        assert_eq!(
+            next_event(&client),
+            notification(slot, "slotNotification", json!({"slot": 7}))
+        );
+        assert!(!client.unsubscribe(id));
+        assert_eq!(client.subscriptions(), vec![slot]);
+    }
+
+    #[test]
+    fn test_gives_up() {
+        let (client, _nodes) = start(2, false);
+        assert_eq!(next_event(&client), state(ConnectionState::Reconnecting, 1));
+        assert_eq!(next_event(&client), state(ConnectionState::Reconnecting, 2));
+        assert_eq!(next_event(&client), state(ConnectionState::GaveUp, 2));
+        assert_eq!(client.state(), ConnectionState::GaveUp);
+        assert!(client.subscribe("slotSubscribe", json!([])).is_err());
+
+        let (client, nodes) = start(0, true);
+        nodes.recv_timeout(WAIT).unwrap().disconnect();
+        assert_eq!(next_event(&client), state(ConnectionState::Connected, 0));
+        assert_eq!(next_event(&client), state(ConnectionState::GaveUp, 0));
+    }
+
+    #[test]
+    fn test_rejected_and_in_flight_subscriptions() {
+        let (client, nodes) = start(3, true);
+        let node = nodes.recv_timeout(WAIT).unwrap();
+        assert_eq!(next_event(&client), state(ConnectionState::Connected, 0));
+        assert!(client.subscribe("accountInfo", json!([])).is_err());
+
+        let rejected = client.subscribe("logsSubscribe", json!(["bogus"])).unwrap();
+        let request = node.request();
+        let error = json!({
+            "jsonrpc": "2.0",
+            "error": {"code": -32602, "message": "Invalid params"},
+            "id": request["id"],
+        });
+        node.to_client.send(Some(error.to_string())).unwrap();
+        assert_eq!(
+            next_event(&client),
+            SubscriptionEvent::SubscriptionError {
+                id: rejected,
+                message: "Invalid params".to_string(),
+            }
+        );
+        assert!(client.subscriptions().is_empty());
+
+        // Unsubscribed before the node answered: the node's subscription is ended too
+        let id = client
+            .subscribe("signatureSubscribe", json!(["Sig1"]))
+            .unwrap();
+        let request = node.request();
+        assert!(client.unsubscribe(id));
+        node.respond(&request, json!(400));
+        let request = node.request();
+        assert_eq!(request["method"], "signatureUnsubscribe");
+        assert_eq!(request["params"], json!([400]));
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = notification(3, "slotNotification", json!({"slot": 7}));
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({"type": "notification", "id": 3, "method": "slotNotification", "result": {"slot": 7}})
+        );
+        assert_eq!(
+            serde_json::to_value(state(ConnectionState::GaveUp, 10)).unwrap(),
+            json!({"type": "connectionState", "state": "gaveUp", "attempt": 10})
+        );
+    }
+
+    #[test]
+    fn test_reconnect_policy() {
+        let policy = ReconnectPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(7), Duration::from_millis(30_000));
+        assert_eq!(policy.backoff(200), Duration::from_millis(30_000));
+
+        let policy = ReconnectPolicy::from_json(r#"{"maxAttempts": 3}"#).unwrap();
+        assert_eq!(policy.max_attempts, 3);
+        assert_eq!(policy.initial_backoff_ms, 500);
+        assert!(ReconnectPolicy::from_json(r#"{"retries": 3}"#).is_err());
+        assert!(
+            ReconnectPolicy::from_json(r#"{"initialBackoffMs": 10, "maxBackoffMs": 5}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_subscription_queue() {
+        let (client, nodes) = start(3, true);
+        let node = nodes.recv_timeout(WAIT).unwrap();
+        assert_eq!(next_event(&client), state(ConnectionState::Connected, 0));
+
+        let policy = QueuePolicy {
+            capacity: 2,
+            overflow: Overflow::DropOldest,
+        };
+        let slots = client.open("slotSubscribe", json!([]), policy).unwrap();
+        assert_eq!(slots.state(), SubscriptionState::Active);
+        let request = node.request();
+        node.respond(&request, json!(10));
+        for slot in 1..=3 {
+            node.notify("slotNotification", 10, json!({"slot": slot}));
+        }
+        let deadline = Instant::now() + WAIT;
+        while slots.dropped_count() == 0 {
+            assert!(Instant::now() < deadline, "queue never filled");
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // The oldest notification made room, and none reached the client's queue
+        assert_eq!(slots.pending_count(), 2);
+        assert_eq!(
+            slots.poll_next(),
+            Some(notification(
+                slots.id(),
+                "slotNotification",
+                json!({"slot": 2})
+            ))
+        );
+        assert_eq!(
+            slots.poll_next(),
+            Some(notification(
+                slots.id(),
+                "slotNotification",
+                json!({"slot": 3})
+            ))
+        );
+        assert_eq!(slots.poll_next(), None);
+        assert_eq!(client.poll_event(), None);
+
+        // Dropping it unsubscribes
+        drop(slots);
+        let request = node.request();
+        assert_eq!(request["method"], "slotUnsubscribe");
+        assert_eq!(request["params"], json!([10]));
+        assert!(client.subscriptions().is_empty());
+    }
+
+    #[test]
+    fn test_queue_policy() {
+        let mut queue = EventQueue {
+            events: VecDeque::new(),
+            policy: QueuePolicy::from_json(r#"{"capacity": 1, "overflow": "dropNewest"}"#).unwrap(),
+            dropped: 0,
+        };
+        queue.push(state(ConnectionState::Connected, 0));
+        queue.push(state(ConnectionState::Reconnecting, 1));
+        assert_eq!(
+            queue.events,
+            VecDeque::from([state(ConnectionState::Connected, 0)])
+        );
+        assert_eq!(queue.dropped, 1);
+
+        assert_eq!(
+            QueuePolicy::from_json("{}").unwrap(),
+            QueuePolicy::default()
+        );
+        assert!(QueuePolicy::from_json(r#"{"capacity": 0}"#).is_err());
+        assert!(QueuePolicy::from_json(r#"{"overflow": "block"}"#).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::cost::TOKEN_ACCOUNT_LEN;
+
+// Offsets into an SPL token account: the mint, owner and amount, then the delegate, the
+// state and the rest of the base layout
+const OWNER_OFFSET: usize = 32;
+const AMOUNT_OFFSET: usize = 64;
+const STATE_OFFSET: usize = 108;
+/// Where a Token-2022 account with extensions stores its account type, right after the
+/// base layout
+pub const ACCOUNT_TYPE_OFFSET: usize = TOKEN_ACCOUNT_LEN;
+/// The Token-2022 account type of token accounts, as opposed to mints (1)
+pub const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// A token account holding a mint, as `RpcClient::get_token_holders` lists them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenHolder {
+    /// The wallet that owns the token account
+    pub owner: String,
+    pub token_account: String,
+    /// In base units of the mint
+    pub amount: u64,
+}
+
+/// The fields of a token account a holder list needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenAccountData {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+/// Reads an initialized SPL Token or Token-2022 token account, or None when `data` is not
+/// one. Token-2022 accounts with extensions are longer than the base layout and must be
+/// marked as token accounts after it.
+pub fn parse_token_account(data: &[u8]) -> Option<TokenAccountData> {
+    if data.len() < TOKEN_ACCOUNT_LEN {
+        return None;
+    }
+    if data.len() > TOKEN_ACCOUNT_LEN && data[ACCOUNT_TYPE_OFFSET] != ACCOUNT_TYPE_ACCOUNT {
+        return None;
+    }
+    // 0 is uninitialized; 1 initialized and 2 frozen
+    if data[STATE_OFFSET] == 0 {
+        return None;
+    }
+    let pubkey_at = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).ok();
+    Some(TokenAccountData {
+        mint: pubkey_at(0)?,
+        owner: pubkey_at(OWNER_OFFSET)?,
+        amount: u64::from_le_bytes(data[AMOUNT_OFFSET..AMOUNT_OFFSET + 8].try_into().ok()?),
+    })
+}
+
+/// The holders of `mint` among `accounts`, pairs of address and data, that hold at least
+/// `min_amount`, largest first. Accounts that are not token accounts of `mint` are
+/// skipped.
+pub fn token_holders<'a>(
+    mint: &Pubkey,
+    accounts: impl IntoIterator<Item = (&'a Pubkey, &'a [u8])>,
+    min_amount: u64,
+) -> Vec<TokenHolder> {
+    let mut holders: Vec<TokenHolder> = accounts
+        .into_iter()
+        .filter_map(|(address, data)| {
+            let account = parse_token_account(data)?;
+            if account.mint != *mint || account.amount < min_amount {
+                return None;
+            }
+            Some(TokenHolder {
+                owner: account.owner.to_string(),
+                token_account: address.to_string(),
+                amount: account.amount,
+            })
+        })
+        .collect();
+    // Ties in address order, so the list is the same on every call
+    holders.sort_by(|a, b| {
+        b.amount
+            .cmp(&a.amount)
+            .then_with(|| a.token_account.cmp(&b.token_account))
+    });
+    holders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64, state: u8) -> Vec<u8> {
+        let mut data = vec![0; TOKEN_ACCOUNT_LEN];
+        data[..32].copy_from_slice(mint.as_ref());
+        data[OWNER_OFFSET..OWNER_OFFSET + 32].copy_from_slice(owner.as_ref());
+        data[AMOUNT_OFFSET..AMOUNT_OFFSET + 8].copy_from_slice(&amount.to_le_bytes());
+        data[STATE_OFFSET] = state;
+        data
+    }
+
+    #[test]
+    fn test_parse_token_account() {
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let data = token_account(&mint, &owner, 500, 1);
+        assert_eq!(
+            parse_token_account(&data),
+            Some(TokenAccountData {
+                mint,
+                owner,
+                amount: 500
+            })
+        );
+        // Frozen accounts still hold their tokens
+        assert!(parse_token_account(&token_account(&mint, &owner, 500, 2)).is_some());
+        assert_eq!(
+            parse_token_account(&token_account(&mint, &owner, 500, 0)),
+            None
+        );
+        assert_eq!(parse_token_account(&data[..100]), None);
+
+        // Token-2022 extensions follow the account type
+        let mut extended = data.clone();
+        extended.extend_from_slice(&[ACCOUNT_TYPE_ACCOUNT, 7, 0, 2, 0, 1, 1]);
+        assert_eq!(parse_token_account(&extended), parse_token_account(&data));
+        extended[ACCOUNT_TYPE_OFFSET] = 1;
+        assert_eq!(parse_token_account(&extended), None);
+    }
+
+    #[test]
+    fn test_holders_are_filtered_and_sorted() {
+        let mint = Pubkey::new_unique();
+        let other_mint = Pubkey::new_unique();
+        let owners: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let addresses: Vec<Pubkey> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        let mut extended = token_account(&mint, &owners[4], 900, 1);
+        extended.extend_from_slice(&[ACCOUNT_TYPE_ACCOUNT, 0, 0]);
+        let accounts = vec![
+            (&addresses[0], token_account(&mint, &owners[0], 10, 1)),
+            (&addresses[1], token_account(&mint, &owners[1], 3_000, 2)),
+            (&addresses[2], token_account(&mint, &owners[2], 0, 1)),
+            (
+                &addresses[3],
+                token_account(&other_mint, &owners[3], 5_000, 1),
+            ),
+            (&addresses[4], extended),
+            (&addresses[5], vec![1, 2, 3]),
+        ];
+        let iter = || accounts.iter().map(|(a, d)| (*a, d.as_slice()));
+
+        let holders = token_holders(&mint, iter(), 10);
+        let expected = [
+            (&owners[1], &addresses[1], 3_000),
+            (&owners[4], &addresses[4], 900),
+            (&owners[0], &addresses[0], 10),
+        ];
+        assert_eq!(holders.len(), expected.len());
+        for (holder, (owner, address, amount)) in holders.iter().zip(expected) {
+            assert_eq!(holder.owner, owner.to_string());
+            assert_eq!(holder.token_account, address.to_string());
+            assert_eq!(holder.amount, amount);
+        }
+
+        // Empty accounts only count with a minimum of 0
+        assert_eq!(token_holders(&mint, iter(), 0).len(), 4);
+        assert!(token_holders(&mint, iter(), 3_001).is_empty());
+
+        let json = serde_json::to_value(&holders[0]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "owner": owners[1].to_string(),
+                "token_account": addresses[1].to_string(),
+                "amount": 3_000,
+            })
+        );
+    }
+}
@@ -2,21 +2,36 @@ use solana_sdk::hash::Hash;
 use solana_sdk::instruction::Instruction;
 use solana_sdk::message::Message;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::Keypair;
+use solana_sdk::signature::{Keypair, Signature, Signer};
 use solana_sdk::transaction::Transaction as SolanaTransaction;
 use std::str::FromStr;
 
+use crate::account::Account;
 use crate::error::SolanaUnityError;
+use crate::external_signer;
+use crate::instruction::instruction_label;
+use crate::logging::{LOG_DEBUG, LOG_WARN};
+use crate::native_log;
+#[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+use crate::rpc::RpcClient;
 
 const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
 
 pub struct Transaction {
     tx: Option<SolanaTransaction>,
+    // One per instruction, naming the ones the builders recognise
+    labels: Vec<Option<String>>,
+    // Of the blockhash, when it was set with set_blockhash_with_expiry
+    last_valid_block_height: Option<u64>,
 }
 
 impl Transaction {
     pub fn new() -> Self {
-        Self { tx: None }
+        Self {
+            tx: None,
+            labels: Vec::new(),
+            last_valid_block_height: None,
+        }
     }
 
     pub fn build_transfer(
@@ -37,10 +52,12 @@ impl Transaction {
         let blockhash = Hash::from_str(recent_blockhash)
             .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid blockhash: {}", e)))?;
 
-        let message = Message::new(&[instruction], Some(&from));
+        let message = Message::new_with_blockhash(&[instruction], Some(&from), &blockhash);
         let tx = SolanaTransaction::new_unsigned(message);
 
         self.tx = Some(tx);
+        self.labels = vec![Some("system transfer".to_string())];
+        self.last_valid_block_height = None;
         Ok(())
     }
 
@@ -128,10 +145,14 @@ impl Transaction {
         let blockhash = Hash::from_str(recent_blockhash)
             .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid blockhash: {}", e)))?;
 
-        let message = Message::new(&[instruction], Some(&fee_payer_pubkey));
+        let label = instruction_label(&instruction).map(str::to_string);
+        let message =
+            Message::new_with_blockhash(&[instruction], Some(&fee_payer_pubkey), &blockhash);
         let tx = SolanaTransaction::new_unsigned(message);
 
         self.tx = Some(tx);
+        self.labels = vec![label];
+        self.last_valid_block_height = None;
         Ok(())
     }
 
@@ -147,10 +168,16 @@ impl Transaction {
         let blockhash = Hash::from_str(recent_blockhash)
             .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid blockhash: {}", e)))?;
 
-        let message = Message::new(instructions, Some(&fee_payer_pubkey));
+        let message =
+            Message::new_with_blockhash(instructions, Some(&fee_payer_pubkey), &blockhash);
         let tx = SolanaTransaction::new_unsigned(message);
 
         self.tx = Some(tx);
+        self.labels = instructions
+            .iter()
+            .map(|instruction| instruction_label(instruction).map(str::to_string))
+            .collect();
+        self.last_valid_block_height = None;
         Ok(())
     }
 
@@ -171,9 +198,11 @@ impl Transaction {
 
         tx.try_sign(&[&keypair], tx.message.recent_blockhash)
             .map_err(|e| {
+                native_log!(LOG_WARN, "Failed to sign transaction: {}", e);
                 SolanaUnityError::TransactionError(format!("Failed to sign transaction: {}", e))
             })?;
 
+        native_log!(LOG_DEBUG, "Signed transaction with {}", keypair.pubkey());
         self.tx = Some(tx);
         Ok(())
     }
@@ -202,13 +231,117 @@ impl Transaction {
 
         tx.try_sign(&keypair_refs, tx.message.recent_blockhash)
             .map_err(|e| {
+                native_log!(LOG_WARN, "Failed to sign transaction: {}", e);
                 SolanaUnityError::TransactionError(format!("Failed to sign transaction: {}", e))
             })?;
 
+        native_log!(
+            LOG_DEBUG,
+            "Signed transaction with {} keypairs",
+            keypair_refs.len()
+        );
         self.tx = Some(tx);
         Ok(())
     }
 
+    /// Signs with the account's own keypair, so the secret key never has to be exported
+    /// to the caller
+    pub fn sign_with_account(&mut self, account: &Account) -> Result<(), SolanaUnityError> {
+        let keypair = account.get_keypair()?;
+
+        let tx = self.tx.as_mut().ok_or_else(|| {
+            SolanaUnityError::TransactionError("No transaction to sign".to_string())
+        })?;
+
+        let recent_blockhash = tx.message.recent_blockhash;
+        tx.try_sign(&[keypair], recent_blockhash).map_err(|e| {
+            native_log!(LOG_WARN, "Failed to sign transaction: {}", e);
+            SolanaUnityError::TransactionError(format!("Failed to sign transaction: {}", e))
+        })?;
+        native_log!(LOG_DEBUG, "Signed transaction with {}", keypair.pubkey());
+        Ok(())
+    }
+
+    /// Signs for `pubkey` through the registered external signer (e.g. a mobile wallet
+    /// app). The returned signature is verified against the message before it is installed
+    /// in the signer's slot, so other signatures already on the transaction are kept.
+    pub fn sign_externally(&mut self, pubkey: &str) -> Result<(), SolanaUnityError> {
+        let signer = Pubkey::from_str(pubkey)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid pubkey: {}", e)))?;
+
+        let tx = self.tx.as_mut().ok_or_else(|| {
+            SolanaUnityError::TransactionError("No transaction to sign".to_string())
+        })?;
+
+        let num_signers = tx.message.header.num_required_signatures as usize;
+        let index = tx.message.account_keys[..num_signers]
+            .iter()
+            .position(|key| *key == signer)
+            .ok_or_else(|| {
+                SolanaUnityError::TransactionError(format!(
+                    "{} is not a required signer of this transaction",
+                    pubkey
+                ))
+            })?;
+
+        let message_data = tx.message_data();
+        let signature = Signature::from(external_signer::sign(pubkey, &message_data)?);
+
+        if !signature.verify(signer.as_ref(), &message_data) {
+            native_log!(
+                LOG_WARN,
+                "External signer returned an invalid signature for {}",
+                pubkey
+            );
+            return Err(SolanaUnityError::WalletError(format!(
+                "External signer returned an invalid signature for {}",
+                pubkey
+            )));
+        }
+
+        if tx.signatures.len() < num_signers {
+            tx.signatures.resize(num_signers, Signature::default());
+        }
+        tx.signatures[index] = signature;
+        native_log!(LOG_DEBUG, "Signed transaction externally for {}", pubkey);
+        Ok(())
+    }
+
+    /// Points the transaction at a newer blockhash. The existing signatures no longer match
+    /// the message, so they are cleared and the transaction has to be signed again.
+    pub fn update_blockhash(&mut self, blockhash: &str) -> Result<(), SolanaUnityError> {
+        let blockhash = Hash::from_str(blockhash)
+            .map_err(|e| SolanaUnityError::InvalidInput(format!("Invalid blockhash: {}", e)))?;
+
+        let tx = self.tx.as_mut().ok_or_else(|| {
+            SolanaUnityError::TransactionError("No transaction to update".to_string())
+        })?;
+
+        tx.message.recent_blockhash = blockhash;
+        let num_signers = tx.message.header.num_required_signatures as usize;
+        tx.signatures = vec![Signature::default(); num_signers];
+        self.last_valid_block_height = None;
+        Ok(())
+    }
+
+    /// Like `update_blockhash`, also recording the last block height at which `blockhash`
+    /// is valid, as `RpcClient::get_latest_blockhash_with_expiry` returns it. Sending and
+    /// confirming then stops waiting once the chain passes that height.
+    pub fn set_blockhash_with_expiry(
+        &mut self,
+        blockhash: &str,
+        last_valid_block_height: u64,
+    ) -> Result<(), SolanaUnityError> {
+        self.update_blockhash(blockhash)?;
+        self.last_valid_block_height = Some(last_valid_block_height);
+        Ok(())
+    }
+
+    /// The last valid block height of the blockhash, if `set_blockhash_with_expiry` gave it
+    pub fn last_valid_block_height(&self) -> Option<u64> {
+        self.last_valid_block_height
+    }
+
     pub fn serialize(&self) -> Result<Vec<u8>, SolanaUnityError> {
         let tx = self.tx.as_ref().ok_or_else(|| {
             SolanaUnityError::TransactionError("No transaction to serialize".to_string())
@@ -227,16 +360,67 @@ impl Transaction {
             ))
         })?;
 
+        // Labels are not part of the wire format
+        self.labels = vec![None; tx.message.instructions.len()];
+        self.last_valid_block_height = None;
         self.tx = Some(tx);
         Ok(())
     }
 
+    /// The label of each instruction, in order: "system transfer", "token transfer" and
+    /// the like for instructions the builders recognise, else None or what
+    /// `set_instruction_label` gave
+    pub fn instruction_labels(&self) -> &[Option<String>] {
+        &self.labels
+    }
+
+    /// Names instruction `index`, so errors about it can say e.g. "mint reward" rather than
+    /// just its index
+    pub fn set_instruction_label(
+        &mut self,
+        index: usize,
+        label: &str,
+    ) -> Result<(), SolanaUnityError> {
+        let count = self.get_transaction()?.message.instructions.len();
+        if index >= count {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Instruction index {} out of range for {} instructions",
+                index, count
+            )));
+        }
+        self.labels.resize(count, None);
+        self.labels[index] = Some(label.to_string());
+        Ok(())
+    }
+
+    /// Completes the failed instruction `error` names, if any, with its program id and
+    /// label, for errors from sending, confirming or simulating this transaction
+    pub fn annotate_error(&self, error: SolanaUnityError) -> SolanaUnityError {
+        match &self.tx {
+            Some(tx) => error.resolve_failed_instruction(&tx.message, &self.labels),
+            None => error,
+        }
+    }
+
     pub fn get_transaction(&self) -> Result<&SolanaTransaction, SolanaUnityError> {
         self.tx.as_ref().ok_or_else(|| {
             SolanaUnityError::TransactionError("No transaction available".to_string())
         })
     }
 
+    /// `RpcClient::estimate_transaction_cost` as JSON: {"fee_lamports",
+    /// "priority_fee_lamports", "rent_lamports", "total_lamports", "breakdown": [{"kind":
+    /// "fee" | "priority_fee" | "rent", "lamports", ...}]}
+    #[cfg(all(not(target_arch = "wasm32"), feature = "rpc"))]
+    pub fn estimate_total_cost(&self, client: &RpcClient) -> Result<String, SolanaUnityError> {
+        let cost = client.estimate_transaction_cost(&self.get_transaction()?.message)?;
+        serde_json::to_string(&cost).map_err(|e| {
+            SolanaUnityError::SerializationError(format!("Failed to serialize cost: {}", e))
+        })
+    }
+
+    /// A rough offline estimate of 5000 lamports per signature, ignoring priority fees.
+    /// `RpcClient::get_fee_for_message` asks the cluster for the exact fee.
     pub fn get_fee_estimate(&self) -> Result<u64, SolanaUnityError> {
         let tx = self.tx.as_ref().ok_or_else(|| {
             SolanaUnityError::TransactionError("No transaction available".to_string())
@@ -250,6 +434,7 @@ impl Transaction {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ErrorKind;
     use solana_sdk::signature::Keypair;
     use solana_sdk::signer::Signer;
 
@@ -463,7 +648,10 @@ mod tests {
         assert!(result.is_err());
 
         match result {
-            Err(SolanaUnityError::InvalidInput(_)) => {}
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
             _ => panic!("Expected InvalidInput error for invalid pubkey"),
         }
 
@@ -472,7 +660,10 @@ mod tests {
         assert!(result.is_err());
 
         match result {
-            Err(SolanaUnityError::InvalidInput(_)) => {}
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
             _ => panic!("Expected InvalidInput error for invalid pubkey"),
         }
     }
@@ -488,7 +679,10 @@ mod tests {
         assert!(result.is_err());
 
         match result {
-            Err(SolanaUnityError::InvalidInput(_)) => {}
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
             _ => panic!("Expected InvalidInput error for invalid blockhash"),
         }
     }
@@ -505,7 +699,10 @@ mod tests {
         assert!(result.is_err());
 
         match result {
-            Err(SolanaUnityError::TransactionError(_)) => {}
+            Err(SolanaUnityError {
+                kind: ErrorKind::Transaction,
+                ..
+            }) => {}
             _ => panic!("Expected TransactionError when signing empty transaction"),
         }
 
@@ -517,11 +714,34 @@ mod tests {
         assert!(result.is_err());
 
         match result {
-            Err(SolanaUnityError::WalletError(_)) => {}
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                ..
+            }) => {}
             _ => panic!("Expected WalletError when signing with invalid keypair"),
         }
     }
 
+    #[test]
+    fn test_sign_with_account() {
+        let account = Account::generate();
+        let from_pubkey = account.get_pubkey().unwrap();
+        let to_pubkey = Keypair::new().pubkey().to_string();
+        let blockhash = Hash::default().to_string();
+
+        let mut tx = Transaction::new();
+        tx.build_transfer(&from_pubkey, &to_pubkey, 1000, &blockhash)
+            .unwrap();
+
+        // An account that is not a signer fails without discarding the transaction
+        assert!(tx.sign_with_account(&Account::generate()).is_err());
+        assert!(tx.get_transaction().is_ok());
+
+        tx.sign_with_account(&account).unwrap();
+        assert!(tx.get_transaction().unwrap().verify().is_ok());
+    }
+
+    #[cfg(feature = "rpc")]
     #[test]
     fn test_real_transaction_build_and_sign() {
         let url = "https://api.devnet.solana.com";
@@ -565,6 +785,7 @@ mod tests {
         println!("Successfully built and signed a real transaction");
     }
 
+    #[cfg(feature = "rpc")]
     #[test]
     fn test_real_token_transfer_build() {
         let url = "https://api.devnet.solana.com";
@@ -596,6 +817,7 @@ mod tests {
         println!("Successfully built a token transfer transaction");
     }
 
+    #[cfg(feature = "rpc")]
     #[test]
     fn test_transaction_serialize_deserialize() {
         let url = "https://api.devnet.solana.com";
@@ -644,4 +866,230 @@ mod tests {
 
         println!("Successfully serialized and deserialized a real transaction");
     }
+
+    // Signing and serializing need no network, so this holds in builds without the `rpc`
+    // feature too
+    #[test]
+    fn test_offline_build_sign_serialize() {
+        let account = Account::generate();
+        let from_pubkey = account.get_pubkey().unwrap();
+        let to_pubkey = Keypair::new().pubkey().to_string();
+        let blockhash = Hash::new_unique().to_string();
+
+        let mut tx = Transaction::new();
+        tx.build_token_transfer(
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            &from_pubkey,
+            &to_pubkey,
+            &from_pubkey,
+            1000,
+            &blockhash,
+        )
+        .unwrap();
+        tx.sign_with_account(&account).unwrap();
+        let serialized = tx.serialize().unwrap();
+
+        let mut restored = Transaction::new();
+        restored.from_serialized(&serialized).unwrap();
+        let restored = restored.get_transaction().unwrap();
+        assert!(restored.verify().is_ok());
+        assert_eq!(restored.message.recent_blockhash.to_string(), blockhash);
+        assert_eq!(
+            restored.signatures,
+            tx.get_transaction().unwrap().signatures
+        );
+    }
+
+    // The external signer is process-wide, so tests that register one must not overlap
+    static EXTERNAL_SIGNER_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    extern "C" fn keypair_signer(
+        _pubkey: *const std::os::raw::c_char,
+        message: *const u8,
+        message_len: usize,
+        signature_out: *mut u8,
+        user_data: *mut std::os::raw::c_void,
+    ) -> std::os::raw::c_int {
+        let keypair = unsafe { &*(user_data as *const Keypair) };
+        let message = unsafe { std::slice::from_raw_parts(message, message_len) };
+        let signature = keypair.sign_message(message);
+        unsafe {
+            std::ptr::copy_nonoverlapping(signature.as_ref().as_ptr(), signature_out, 64);
+        }
+        0
+    }
+
+    extern "C" fn failing_signer(
+        _pubkey: *const std::os::raw::c_char,
+        _message: *const u8,
+        _message_len: usize,
+        _signature_out: *mut u8,
+        _user_data: *mut std::os::raw::c_void,
+    ) -> std::os::raw::c_int {
+        7
+    }
+
+    #[test]
+    fn test_sign_externally() {
+        let _guard = EXTERNAL_SIGNER_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let keypair = Keypair::new();
+        let from_pubkey = keypair.pubkey().to_string();
+        let to_pubkey = Keypair::new().pubkey().to_string();
+
+        let mut tx = Transaction::new();
+        tx.build_transfer(&from_pubkey, &to_pubkey, 1000, &Hash::default().to_string())
+            .unwrap();
+
+        external_signer::set_external_signer(
+            Some(keypair_signer),
+            &keypair as *const Keypair as *mut std::os::raw::c_void,
+        );
+        let result = tx.sign_externally(&from_pubkey);
+        external_signer::set_external_signer(None, std::ptr::null_mut());
+
+        result.unwrap();
+        let signed = tx.get_transaction().unwrap();
+        assert!(signed.verify().is_ok());
+
+        // Same signature as signing locally
+        let mut local = Transaction::new();
+        local
+            .build_transfer(&from_pubkey, &to_pubkey, 1000, &Hash::default().to_string())
+            .unwrap();
+        local.sign(&keypair.to_bytes()).unwrap();
+        assert_eq!(
+            signed.signatures,
+            local.get_transaction().unwrap().signatures
+        );
+    }
+
+    #[test]
+    fn test_sign_externally_failures() {
+        let _guard = EXTERNAL_SIGNER_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let keypair = Keypair::new();
+        let wrong_keypair = Keypair::new();
+        let from_pubkey = keypair.pubkey().to_string();
+        let to_pubkey = Keypair::new().pubkey().to_string();
+
+        let mut tx = Transaction::new();
+        tx.build_transfer(&from_pubkey, &to_pubkey, 1000, &Hash::default().to_string())
+            .unwrap();
+
+        // Callback reports failure
+        external_signer::set_external_signer(Some(failing_signer), std::ptr::null_mut());
+        let failed = tx.sign_externally(&from_pubkey);
+
+        // Callback signs with the wrong key
+        external_signer::set_external_signer(
+            Some(keypair_signer),
+            &wrong_keypair as *const Keypair as *mut std::os::raw::c_void,
+        );
+        let mismatched = tx.sign_externally(&from_pubkey);
+
+        // Pubkey that is not a signer
+        let not_signer = tx.sign_externally(&to_pubkey);
+
+        external_signer::set_external_signer(None, std::ptr::null_mut());
+        let unregistered = tx.sign_externally(&from_pubkey);
+
+        match failed {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => {
+                assert!(msg.contains(&from_pubkey));
+                assert!(msg.contains('7'));
+            }
+            _ => panic!("Expected WalletError when the callback fails"),
+        }
+        match mismatched {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("invalid signature")),
+            _ => panic!("Expected WalletError for a signature from the wrong key"),
+        }
+        assert!(not_signer.is_err());
+        assert!(unregistered.is_err());
+
+        // Failed attempts leave the transaction intact and unsigned
+        let unsigned = tx.get_transaction().unwrap();
+        assert_eq!(unsigned.signatures[0], Signature::default());
+    }
+
+    #[test]
+    fn test_update_blockhash_clears_signatures() {
+        let payer = Account::generate();
+        let payer_pubkey = payer.get_pubkey().unwrap();
+        let to_pubkey = Keypair::new().pubkey().to_string();
+        let mut tx = Transaction::new();
+        tx.build_transfer(
+            &payer_pubkey,
+            &to_pubkey,
+            1000,
+            &Hash::default().to_string(),
+        )
+        .unwrap();
+        tx.sign_with_account(&payer).unwrap();
+        let first = tx.get_transaction().unwrap().signatures[0];
+
+        let blockhash = Hash::new_unique();
+        tx.update_blockhash(&blockhash.to_string()).unwrap();
+        let updated = tx.get_transaction().unwrap();
+        assert_eq!(updated.message.recent_blockhash, blockhash);
+        assert_eq!(updated.signatures, vec![Signature::default()]);
+
+        tx.sign_with_account(&payer).unwrap();
+        let resigned = tx.get_transaction().unwrap();
+        assert_ne!(resigned.signatures[0], first);
+        assert!(resigned.verify().is_ok());
+
+        let error = tx.update_blockhash("not-a-blockhash").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidInput);
+        assert!(Transaction::new()
+            .update_blockhash(&blockhash.to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn test_blockhash_expiry_is_tracked() {
+        let payer = Account::generate();
+        let mut tx = Transaction::new();
+        let blockhash = Hash::new_unique();
+        tx.build_transfer(
+            &payer.get_pubkey().unwrap(),
+            &Pubkey::new_unique().to_string(),
+            1000,
+            &blockhash.to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            tx.get_transaction().unwrap().message.recent_blockhash,
+            blockhash
+        );
+        assert_eq!(tx.last_valid_block_height(), None);
+
+        let fresh = Hash::new_unique();
+        tx.set_blockhash_with_expiry(&fresh.to_string(), 250)
+            .unwrap();
+        assert_eq!(
+            tx.get_transaction().unwrap().message.recent_blockhash,
+            fresh
+        );
+        assert_eq!(tx.last_valid_block_height(), Some(250));
+
+        // A blockhash of unknown expiry forgets the height
+        tx.update_blockhash(&blockhash.to_string()).unwrap();
+        assert_eq!(tx.last_valid_block_height(), None);
+        assert!(tx.set_blockhash_with_expiry("not a hash", 250).is_err());
+        assert_eq!(tx.last_valid_block_height(), None);
+    }
 }
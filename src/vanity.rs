@@ -0,0 +1,314 @@
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::account::{Account, BASE58_ALPHABET};
+use crate::error::SolanaUnityError;
+
+// Prefixes at least this long are checked against max_attempts up front, since a
+// five character prefix already needs hundreds of millions of keypairs on average
+const PLAUSIBILITY_CHECK_LEN: usize = 5;
+
+// Minimum chance of success (max_attempts / expected attempts) accepted for long prefixes
+const MIN_SUCCESS_RATIO: f64 = 0.01;
+
+struct GrindState {
+    prefix: String,
+    case_insensitive: bool,
+    max_attempts: u64,
+    attempts: AtomicU64,
+    stop: AtomicBool,
+    cancelled: AtomicBool,
+    active_workers: AtomicUsize,
+    result: Mutex<Option<Keypair>>,
+}
+
+impl GrindState {
+    fn matches(&self, address: &str) -> bool {
+        let len = self.prefix.len();
+        if address.len() < len {
+            return false;
+        }
+
+        if self.case_insensitive {
+            address[..len].eq_ignore_ascii_case(&self.prefix)
+        } else {
+            address.starts_with(&self.prefix)
+        }
+    }
+
+    fn run_worker(&self) {
+        while !self.stop.load(Ordering::Relaxed) {
+            if self.attempts.fetch_add(1, Ordering::Relaxed) >= self.max_attempts {
+                break;
+            }
+
+            let keypair = Keypair::new();
+            if self.matches(&keypair.pubkey().to_string()) {
+                let mut result = self.result.lock().unwrap();
+                if result.is_none() {
+                    *result = Some(keypair);
+                }
+                self.stop.store(true, Ordering::Relaxed);
+                break;
+            }
+        }
+
+        self.active_workers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Background search for a keypair whose address starts with a given prefix
+pub struct GrindHandle {
+    state: Arc<GrindState>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl GrindHandle {
+    /// Validates the prefix and starts `threads` worker threads. Passing 0 threads uses
+    /// the available parallelism of the machine.
+    pub fn start(
+        prefix: &str,
+        case_insensitive: bool,
+        max_attempts: u64,
+        threads: usize,
+    ) -> Result<Self, SolanaUnityError> {
+        validate_prefix(prefix, case_insensitive, max_attempts)?;
+
+        let threads = if threads == 0 {
+            thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        } else {
+            threads
+        };
+
+        let state = Arc::new(GrindState {
+            prefix: prefix.to_string(),
+            case_insensitive,
+            max_attempts,
+            attempts: AtomicU64::new(0),
+            stop: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+            active_workers: AtomicUsize::new(threads),
+            result: Mutex::new(None),
+        });
+
+        let mut workers = Vec::with_capacity(threads);
+        for _ in 0..threads {
+            let state = Arc::clone(&state);
+            workers.push(thread::spawn(move || state.run_worker()));
+        }
+
+        Ok(Self { state, workers })
+    }
+
+    /// Number of keypairs generated so far
+    pub fn attempts(&self) -> u64 {
+        self.state
+            .attempts
+            .load(Ordering::Relaxed)
+            .min(self.state.max_attempts)
+    }
+
+    /// Asks all workers to stop. Already running workers finish their current keypair.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+        self.state.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true once every worker has exited
+    pub fn is_finished(&self) -> bool {
+        self.state.active_workers.load(Ordering::SeqCst) == 0
+    }
+
+    /// Non-blocking check. Returns Ok(Some(account)) once a match has been found, Ok(None)
+    /// while the search is still running, and an error once it was cancelled or exhausted.
+    pub fn poll(&self) -> Result<Option<Account>, SolanaUnityError> {
+        if let Some(keypair) = self.state.result.lock().unwrap().take() {
+            return Ok(Some(Account::from_keypair(keypair)));
+        }
+
+        if !self.is_finished() {
+            return Ok(None);
+        }
+
+        // A worker may have stored a result between the first check and the last exit
+        if let Some(keypair) = self.state.result.lock().unwrap().take() {
+            return Ok(Some(Account::from_keypair(keypair)));
+        }
+
+        Err(self.failure())
+    }
+
+    /// Blocks until the search completes
+    pub fn wait(mut self) -> Result<Account, SolanaUnityError> {
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+
+        match self.state.result.lock().unwrap().take() {
+            Some(keypair) => Ok(Account::from_keypair(keypair)),
+            None => Err(self.failure()),
+        }
+    }
+
+    fn failure(&self) -> SolanaUnityError {
+        if self.state.cancelled.load(Ordering::Relaxed) {
+            SolanaUnityError::WalletError("Vanity address search was cancelled".to_string())
+        } else {
+            SolanaUnityError::WalletError(format!(
+                "No address with prefix '{}' found after {} attempts",
+                self.state.prefix, self.state.max_attempts
+            ))
+        }
+    }
+}
+
+impl Drop for GrindHandle {
+    fn drop(&mut self) {
+        self.state.stop.store(true, Ordering::Relaxed);
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Average number of keypairs needed to hit the prefix, treating every base58 character
+/// as equally likely
+pub fn expected_attempts(prefix: &str, case_insensitive: bool) -> f64 {
+    prefix
+        .chars()
+        .map(|c| 58.0 / matching_chars(c, case_insensitive) as f64)
+        .product()
+}
+
+fn matching_chars(c: char, case_insensitive: bool) -> usize {
+    if case_insensitive {
+        BASE58_ALPHABET
+            .chars()
+            .filter(|a| a.eq_ignore_ascii_case(&c))
+            .count()
+    } else {
+        BASE58_ALPHABET.chars().filter(|a| *a == c).count()
+    }
+}
+
+fn validate_prefix(
+    prefix: &str,
+    case_insensitive: bool,
+    max_attempts: u64,
+) -> Result<(), SolanaUnityError> {
+    if prefix.is_empty() {
+        return Err(SolanaUnityError::InvalidInput(
+            "Vanity prefix must not be empty".to_string(),
+        ));
+    }
+
+    if max_attempts == 0 {
+        return Err(SolanaUnityError::InvalidInput(
+            "max_attempts must be greater than zero".to_string(),
+        ));
+    }
+
+    if let Some(c) = prefix
+        .chars()
+        .find(|c| matching_chars(*c, case_insensitive) == 0)
+    {
+        return Err(SolanaUnityError::InvalidInput(format!(
+            "Vanity prefix contains '{}', which is not a base58 character",
+            c
+        )));
+    }
+
+    if prefix.len() >= PLAUSIBILITY_CHECK_LEN {
+        let expected = expected_attempts(prefix, case_insensitive);
+        if (max_attempts as f64) < expected * MIN_SUCCESS_RATIO {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "Prefix '{}' needs about {:.0} attempts on average, max_attempts of {} makes success implausible",
+                prefix, expected, max_attempts
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn test_grind_single_character() {
+        let handle = GrindHandle::start("A", false, 1_000_000, 2).unwrap();
+        let account = handle.wait().unwrap();
+        assert!(account.get_pubkey().unwrap().starts_with('A'));
+        assert!(account.has_private_key());
+    }
+
+    #[test]
+    fn test_grind_case_insensitive() {
+        let handle = GrindHandle::start("k", true, 1_000_000, 1).unwrap();
+        let account = handle.wait().unwrap();
+        let first = account.get_pubkey().unwrap().chars().next().unwrap();
+        assert!(first == 'k' || first == 'K');
+    }
+
+    #[test]
+    fn test_grind_rejects_invalid_prefix() {
+        for prefix in ["", "0", "GAMEO", "ab l"] {
+            match GrindHandle::start(prefix, false, 1_000, 1) {
+                Err(SolanaUnityError {
+                    kind: ErrorKind::InvalidInput,
+                    ..
+                }) => {}
+                _ => panic!("Expected InvalidInput for prefix '{}'", prefix),
+            }
+        }
+
+        // Lowercase 'l' is not base58, but case-insensitive search matches it to 'L'
+        assert!(validate_prefix("l", true, 1_000).is_ok());
+        assert!(validate_prefix("l", false, 1_000).is_err());
+    }
+
+    #[test]
+    fn test_grind_rejects_implausible_long_prefix() {
+        match GrindHandle::start("GAMEXYZ", false, 1_000, 1) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("implausible")),
+            _ => panic!("Expected InvalidInput for an implausible prefix"),
+        }
+    }
+
+    #[test]
+    fn test_grind_exhausts_attempts() {
+        let handle = GrindHandle::start("zzzz", false, 10, 2).unwrap();
+        match handle.wait() {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("10 attempts")),
+            _ => panic!("Expected WalletError after exhausting attempts"),
+        }
+    }
+
+    #[test]
+    fn test_grind_cancel() {
+        let handle = GrindHandle::start("zzzz", false, u64::MAX, 1).unwrap();
+        handle.cancel();
+        match handle.wait() {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("cancelled")),
+            _ => panic!("Expected WalletError after cancelling"),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+use crate::account::Account;
+use crate::error::SolanaUnityError;
+use crate::transaction::Transaction;
+
+/// Non-secret description of a managed account, used for persistence
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletEntry {
+    pub label: String,
+    pub pubkey: String,
+    pub watch_only: bool,
+}
+
+/// Holds several accounts under caller-chosen labels, e.g. a session wallet, the
+/// player's watch-only main wallet and a sponsor wallet
+pub struct WalletManager {
+    accounts: Vec<(String, Account)>,
+}
+
+impl WalletManager {
+    pub fn new() -> Self {
+        Self {
+            accounts: Vec::new(),
+        }
+    }
+
+    /// Adds an account under `label`. Labels must be unique and non-empty.
+    pub fn add(&mut self, label: &str, account: Account) -> Result<(), SolanaUnityError> {
+        if label.is_empty() {
+            return Err(SolanaUnityError::InvalidInput(
+                "Wallet label must not be empty".to_string(),
+            ));
+        }
+
+        if self.contains(label) {
+            return Err(SolanaUnityError::InvalidInput(format!(
+                "A wallet labelled '{}' already exists",
+                label
+            )));
+        }
+
+        // Reject accounts without a public key up front so list() never fails
+        account.get_pubkey()?;
+
+        self.accounts.push((label.to_string(), account));
+        Ok(())
+    }
+
+    pub fn contains(&self, label: &str) -> bool {
+        self.accounts.iter().any(|(l, _)| l == label)
+    }
+
+    pub fn get(&self, label: &str) -> Result<&Account, SolanaUnityError> {
+        self.accounts
+            .iter()
+            .find(|(l, _)| l == label)
+            .map(|(_, account)| account)
+            .ok_or_else(|| SolanaUnityError::WalletError(format!("No wallet labelled '{}'", label)))
+    }
+
+    /// Removes and returns the account stored under `label`
+    pub fn remove(&mut self, label: &str) -> Result<Account, SolanaUnityError> {
+        let index = self
+            .accounts
+            .iter()
+            .position(|(l, _)| l == label)
+            .ok_or_else(|| {
+                SolanaUnityError::WalletError(format!("No wallet labelled '{}'", label))
+            })?;
+
+        Ok(self.accounts.remove(index).1)
+    }
+
+    /// Returns (label, pubkey) pairs in insertion order
+    pub fn list(&self) -> Vec<(String, String)> {
+        self.entries()
+            .into_iter()
+            .map(|entry| (entry.label, entry.pubkey))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Signs the transaction with the account stored under `label`
+    pub fn sign_with(
+        &self,
+        label: &str,
+        transaction: &mut Transaction,
+    ) -> Result<(), SolanaUnityError> {
+        let account = self.get(label)?;
+        let private_key = account.get_private_key_zeroizing().map_err(|_| {
+            SolanaUnityError::WalletError(format!(
+                "Wallet '{}' is watch-only and cannot sign",
+                label
+            ))
+        })?;
+
+        transaction.sign(&private_key[..])
+    }
+
+    pub fn entries(&self) -> Vec<WalletEntry> {
+        self.accounts
+            .iter()
+            .map(|(label, account)| WalletEntry {
+                label: label.clone(),
+                pubkey: account.get_pubkey().unwrap_or_default(),
+                watch_only: !account.has_private_key(),
+            })
+            .collect()
+    }
+
+    /// Exports labels, pubkeys and watch-only flags as JSON. Private keys are never
+    /// included; persist those separately, e.g. with `Account::export_encrypted`.
+    pub fn export_metadata_json(&self) -> Result<String, SolanaUnityError> {
+        serde_json::to_string(&self.entries()).map_err(|e| {
+            SolanaUnityError::SerializationError(format!(
+                "Failed to serialize wallet metadata: {}",
+                e
+            ))
+        })
+    }
+
+    /// Rebuilds a manager from exported metadata. Every entry is restored as a watch-only
+    /// account; signing keys can be swapped back in with `remove` and `add`.
+    pub fn from_metadata_json(json: &str) -> Result<Self, SolanaUnityError> {
+        let entries: Vec<WalletEntry> = serde_json::from_str(json).map_err(|e| {
+            SolanaUnityError::InvalidInput(format!("Invalid wallet metadata JSON: {}", e))
+        })?;
+
+        let mut manager = Self::new();
+        for entry in entries {
+            manager.add(&entry.label, Account::from_pubkey(&entry.pubkey)?)?;
+        }
+        Ok(manager)
+    }
+}
+
+impl Default for WalletManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use solana_sdk::hash::Hash;
+
+    fn build_transfer(from: &str) -> Transaction {
+        let mut tx = Transaction::new();
+        let to = Account::generate().get_pubkey().unwrap();
+        tx.build_transfer(from, &to, 1000, &Hash::default().to_string())
+            .unwrap();
+        tx
+    }
+
+    #[test]
+    fn test_add_and_lookup() {
+        let mut manager = WalletManager::new();
+        let session = Account::generate();
+        let session_pubkey = session.get_pubkey().unwrap();
+        let main_pubkey = Account::generate().get_pubkey().unwrap();
+
+        manager.add("session", session).unwrap();
+        manager
+            .add("main", Account::from_pubkey(&main_pubkey).unwrap())
+            .unwrap();
+
+        assert_eq!(manager.len(), 2);
+        assert_eq!(
+            manager.get("session").unwrap().get_pubkey().unwrap(),
+            session_pubkey
+        );
+        assert_eq!(
+            manager.list(),
+            vec![
+                ("session".to_string(), session_pubkey),
+                ("main".to_string(), main_pubkey),
+            ]
+        );
+        assert!(manager.get("sponsor").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_labels_rejected() {
+        let mut manager = WalletManager::new();
+        manager.add("session", Account::generate()).unwrap();
+
+        match manager.add("session", Account::generate()) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::InvalidInput,
+                ..
+            }) => {}
+            _ => panic!("Expected InvalidInput for a duplicate label"),
+        }
+        assert!(manager.add("", Account::generate()).is_err());
+        assert_eq!(manager.len(), 1);
+    }
+
+    #[test]
+    fn test_sign_routing() {
+        let mut manager = WalletManager::new();
+        let session = Account::generate();
+        let session_pubkey = session.get_pubkey().unwrap();
+        manager.add("session", session).unwrap();
+        manager.add("sponsor", Account::generate()).unwrap();
+        manager
+            .add("main", Account::from_pubkey(&session_pubkey).unwrap())
+            .unwrap();
+
+        let mut tx = build_transfer(&session_pubkey);
+        manager.sign_with("session", &mut tx).unwrap();
+        let signed = tx.get_transaction().unwrap();
+        assert!(signed.verify().is_ok());
+
+        // The sponsor is not a signer of this transaction
+        let mut tx = build_transfer(&session_pubkey);
+        assert!(manager.sign_with("sponsor", &mut tx).is_err());
+
+        // Watch-only wallets cannot sign
+        let mut tx = build_transfer(&session_pubkey);
+        match manager.sign_with("main", &mut tx) {
+            Err(SolanaUnityError {
+                kind: ErrorKind::Wallet,
+                message: msg,
+                ..
+            }) => assert!(msg.contains("watch-only")),
+            _ => panic!("Expected WalletError for a watch-only wallet"),
+        }
+    }
+
+    #[test]
+    fn test_remove_keeps_other_entries() {
+        let mut manager = WalletManager::new();
+        let sponsor = Account::generate();
+        let sponsor_pubkey = sponsor.get_pubkey().unwrap();
+        manager.add("session", Account::generate()).unwrap();
+        manager.add("sponsor", sponsor).unwrap();
+
+        let removed = manager.remove("session").unwrap();
+        assert!(removed.has_private_key());
+        assert!(manager.get("session").is_err());
+        assert!(manager.remove("session").is_err());
+
+        let mut tx = build_transfer(&sponsor_pubkey);
+        manager.sign_with("sponsor", &mut tx).unwrap();
+        assert_eq!(
+            manager.list(),
+            vec![("sponsor".to_string(), sponsor_pubkey)]
+        );
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let mut manager = WalletManager::new();
+        let session = Account::generate();
+        manager.add("session", session).unwrap();
+        manager
+            .add(
+                "main",
+                Account::from_pubkey("11111111111111111111111111111111").unwrap(),
+            )
+            .unwrap();
+
+        let json = manager.export_metadata_json().unwrap();
+        assert!(!json.contains("private"));
+
+        let restored = WalletManager::from_metadata_json(&json).unwrap();
+        assert_eq!(restored.list(), manager.list());
+        assert!(restored.entries().iter().all(|entry| entry.watch_only));
+        assert!(!manager.entries()[0].watch_only);
+    }
+}
@@ -0,0 +1,173 @@
+//! wasm_bindgen exports for Unity WebGL builds, where the C ABI in ffi.rs cannot be used.
+//! Only the offline parts of the SDK are exported: accounts, transaction building and
+//! signing, and PDAs. RPC calls are made from the JavaScript side with fetch, sending the
+//! serialized transactions produced here.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use wasm_bindgen::prelude::*;
+
+use crate::account::Account;
+use crate::error::SolanaUnityError;
+use crate::pda::ProgramDerivedAddress;
+use crate::transaction::Transaction;
+
+fn to_js_error(error: SolanaUnityError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+#[wasm_bindgen(js_name = Account)]
+pub struct WasmAccount {
+    inner: Account,
+}
+
+#[wasm_bindgen(js_class = Account)]
+impl WasmAccount {
+    pub fn generate() -> WasmAccount {
+        WasmAccount {
+            inner: Account::generate(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = fromPrivateKey)]
+    pub fn from_private_key(private_key: &[u8]) -> Result<WasmAccount, JsError> {
+        let inner = Account::from_private_key(private_key).map_err(to_js_error)?;
+        Ok(WasmAccount { inner })
+    }
+
+    #[wasm_bindgen(js_name = fromSeed)]
+    pub fn from_seed(seed: &[u8]) -> Result<WasmAccount, JsError> {
+        let inner = Account::from_seed(seed).map_err(to_js_error)?;
+        Ok(WasmAccount { inner })
+    }
+
+    #[wasm_bindgen(js_name = fromPubkey)]
+    pub fn from_pubkey(pubkey: &str) -> Result<WasmAccount, JsError> {
+        let inner = Account::from_pubkey(pubkey).map_err(to_js_error)?;
+        Ok(WasmAccount { inner })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pubkey(&self) -> Result<String, JsError> {
+        self.inner.get_pubkey().map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = signMessage)]
+    pub fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>, JsError> {
+        self.inner.sign_message(message).map_err(to_js_error)
+    }
+}
+
+#[wasm_bindgen(js_name = Transaction)]
+pub struct WasmTransaction {
+    inner: Transaction,
+}
+
+#[wasm_bindgen(js_class = Transaction)]
+impl WasmTransaction {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmTransaction {
+        WasmTransaction {
+            inner: Transaction::new(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = buildTransfer)]
+    pub fn build_transfer(
+        &mut self,
+        from_pubkey: &str,
+        to_pubkey: &str,
+        lamports: u64,
+        recent_blockhash: &str,
+    ) -> Result<(), JsError> {
+        self.inner
+            .build_transfer(from_pubkey, to_pubkey, lamports, recent_blockhash)
+            .map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = buildTokenTransfer)]
+    pub fn build_token_transfer(
+        &mut self,
+        token_program_id: &str,
+        source_pubkey: &str,
+        destination_pubkey: &str,
+        owner_pubkey: &str,
+        amount: u64,
+        recent_blockhash: &str,
+    ) -> Result<(), JsError> {
+        self.inner
+            .build_token_transfer(
+                token_program_id,
+                source_pubkey,
+                destination_pubkey,
+                owner_pubkey,
+                amount,
+                recent_blockhash,
+            )
+            .map_err(to_js_error)
+    }
+
+    pub fn sign(&mut self, private_key: &[u8]) -> Result<(), JsError> {
+        self.inner.sign(private_key).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = signWithAccount)]
+    pub fn sign_with_account(&mut self, account: &WasmAccount) -> Result<(), JsError> {
+        self.inner
+            .sign_with_account(&account.inner)
+            .map_err(to_js_error)
+    }
+
+    pub fn serialize(&self) -> Result<Vec<u8>, JsError> {
+        self.inner.serialize().map_err(to_js_error)
+    }
+
+    /// The wire format as base64, ready for `sendTransaction` with `encoding: "base64"`
+    #[wasm_bindgen(js_name = serializeBase64)]
+    pub fn serialize_base64(&self) -> Result<String, JsError> {
+        let bytes = self.inner.serialize().map_err(to_js_error)?;
+        Ok(BASE64.encode(bytes))
+    }
+
+    #[wasm_bindgen(js_name = fromSerialized)]
+    pub fn from_serialized(data: &[u8]) -> Result<WasmTransaction, JsError> {
+        let mut inner = Transaction::new();
+        inner.from_serialized(data).map_err(to_js_error)?;
+        Ok(WasmTransaction { inner })
+    }
+}
+
+impl Default for WasmTransaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A program derived address and its bump seed
+#[wasm_bindgen]
+pub struct ProgramAddress {
+    address: String,
+    bump: u8,
+}
+
+#[wasm_bindgen]
+impl ProgramAddress {
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+}
+
+/// Finds a program derived address from a JSON seed specification, see
+/// `ProgramDerivedAddress::find_with_seed_spec_json`
+#[wasm_bindgen(js_name = findProgramAddress)]
+pub fn find_program_address(seeds_json: &str, program_id: &str) -> Result<ProgramAddress, JsError> {
+    let (address, bump) = ProgramDerivedAddress::find_with_seed_spec_json(seeds_json, program_id)
+        .map_err(to_js_error)?;
+    Ok(ProgramAddress { address, bump })
+}
@@ -0,0 +1,95 @@
+//! Host allocator hooks: buffers handed out while hooks are installed must come from and
+//! go back to them, and buffers from before keep using the system allocator.
+#![cfg(feature = "rpc")]
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_uchar, c_void};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use solana_unity::{
+    solana_create_rpc_client, solana_create_token_transfer_instruction, solana_destroy_rpc_client,
+    solana_free_bytes, solana_free_encoded_instruction, solana_get_account_data, solana_init,
+    solana_set_allocator,
+};
+
+#[derive(Default)]
+struct Counts {
+    allocs: AtomicUsize,
+    frees: AtomicUsize,
+}
+
+extern "C" fn counting_alloc(size: usize, user_data: *mut c_void) -> *mut c_void {
+    let counts = unsafe { &*(user_data as *const Counts) };
+    counts.allocs.fetch_add(1, Ordering::SeqCst);
+    unsafe { libc::malloc(size) }
+}
+
+extern "C" fn counting_free(ptr: *mut c_void, user_data: *mut c_void) {
+    let counts = unsafe { &*(user_data as *const Counts) };
+    counts.frees.fetch_add(1, Ordering::SeqCst);
+    unsafe { libc::free(ptr) }
+}
+
+fn encode_transfer() -> (*mut c_uchar, usize) {
+    let source = CString::new("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr").unwrap();
+    let destination = CString::new("11111111111111111111111111111111").unwrap();
+    let owner = CString::new("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr").unwrap();
+    let mut data: *mut c_uchar = ptr::null_mut();
+    let mut len: usize = 0;
+    let mut error: *mut c_char = ptr::null_mut();
+    let result = solana_create_token_transfer_instruction(
+        source.as_ptr(),
+        destination.as_ptr(),
+        owner.as_ptr(),
+        1,
+        &mut data,
+        &mut len,
+        &mut error,
+    );
+    assert_eq!(result, 1);
+    (data, len)
+}
+
+#[test]
+fn buffers_are_released_by_the_allocator_that_produced_them() {
+    let counts: &'static Counts = Box::leak(Box::default());
+    let user_data = counts as *const Counts as *mut c_void;
+
+    // Allocated before the hooks are installed, so freed by the system allocator
+    let (early, _) = encode_transfer();
+
+    let mut error: *mut c_char = ptr::null_mut();
+    assert_eq!(unsafe { solana_init(ptr::null(), &mut error) }, 1);
+    solana_set_allocator(Some(counting_alloc), Some(counting_free), user_data);
+
+    let url = CString::new("https://api.devnet.solana.com").unwrap();
+    let commitment = CString::new("confirmed").unwrap();
+    let pubkey = CString::new("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+    let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+    assert!(!client.is_null());
+
+    let mut len: usize = 0;
+    let data = solana_get_account_data(client, pubkey.as_ptr(), &mut len, &mut error);
+    assert!(!data.is_null());
+    let (instruction, _) = encode_transfer();
+    assert_eq!(counts.allocs.load(Ordering::SeqCst), 2);
+    assert_eq!(counts.frees.load(Ordering::SeqCst), 0);
+
+    solana_free_encoded_instruction(early);
+    assert_eq!(counts.frees.load(Ordering::SeqCst), 0);
+
+    // Replacing the hooks must not send these buffers to the system allocator
+    solana_set_allocator(None, None, ptr::null_mut());
+    unsafe { solana_free_bytes(data, len) };
+    solana_free_encoded_instruction(instruction);
+    assert_eq!(counts.allocs.load(Ordering::SeqCst), 2);
+    assert_eq!(counts.frees.load(Ordering::SeqCst), 2);
+
+    let (late, _) = encode_transfer();
+    solana_free_encoded_instruction(late);
+    assert_eq!(counts.allocs.load(Ordering::SeqCst), 2);
+    assert_eq!(counts.frees.load(Ordering::SeqCst), 2);
+
+    solana_destroy_rpc_client(client);
+}
@@ -0,0 +1,106 @@
+//! Checks the C header generated by build.rs against ffi.rs:
+//! `cargo test --features header --test header`
+#![cfg(feature = "header")]
+
+use std::collections::BTreeSet;
+
+const HEADER: &str = include_str!(concat!(env!("OUT_DIR"), "/solana_unity.h"));
+const FFI_SOURCE: &str = include_str!("../src/ffi.rs");
+
+// Functions declared in the header. Comments and preprocessor lines are dropped, and
+// typedefs skipped so the callback type is not mistaken for a function.
+fn declared_functions(header: &str) -> BTreeSet<String> {
+    let code: String = header
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with("/*") && !line.starts_with("//"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    code.split(';')
+        .map(str::trim)
+        .filter(|statement| !statement.starts_with("typedef") && statement.contains('('))
+        .filter_map(|statement| {
+            let before = statement.split('(').next()?;
+            let name = before.rsplit(|c: char| c == ' ' || c == '*').next()?;
+            Some(name.to_string())
+        })
+        .collect()
+}
+
+// Unprefixed names of the #[ffi_export] functions, whatever features they are gated on
+fn exported_in_source(source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut exported = false;
+    for line in source.lines().map(str::trim) {
+        if line == "#[ffi_export]" {
+            exported = true;
+        } else if line.starts_with("#[") {
+            continue;
+        } else {
+            if exported {
+                let rest = line
+                    .strip_prefix("pub extern \"C\" fn ")
+                    .or_else(|| line.strip_prefix("pub unsafe extern \"C\" fn "));
+                if let Some(rest) = rest {
+                    names.push(rest.split('(').next().unwrap().to_string());
+                }
+            }
+            exported = false;
+        }
+    }
+    names
+}
+
+#[test]
+fn header_declares_every_exported_function() {
+    let manifest: serde_json::Value =
+        serde_json::from_str(solana_unity::EXPORTED_SYMBOLS_JSON).unwrap();
+    let prefix = manifest["prefix"].as_str().unwrap();
+
+    let declared = declared_functions(HEADER);
+    let exported = exported_in_source(FFI_SOURCE);
+    assert!(exported.len() > 100);
+
+    let missing: Vec<String> = exported
+        .iter()
+        .map(|name| format!("{}{}", prefix, name))
+        .filter(|name| !declared.contains(name))
+        .collect();
+    assert!(missing.is_empty(), "missing from the header: {:?}", missing);
+}
+
+#[test]
+fn header_declares_opaque_handles() {
+    for handle in [
+        "SolanaRpcClient",
+        "SolanaTransaction",
+        "SolanaAccount",
+        "SolanaGrindHandle",
+        "SolanaWalletManager",
+        "SolanaDeeplinkSession",
+    ] {
+        let typedef = format!("typedef struct {0} {0};", handle);
+        assert!(HEADER.contains(&typedef), "missing {}", typedef);
+    }
+
+    // The internal Handle layout must not leak into the header
+    assert!(!HEADER.contains("Handle_"));
+    assert!(!HEADER.contains("magic"));
+}
+
+#[test]
+fn header_uses_ffi_safe_signatures() {
+    assert!(HEADER.contains("typedef struct SolanaBuffer {"));
+    assert!(HEADER.contains("typedef struct SolanaU64Result {"));
+    assert!(HEADER.contains("typedef struct SolanaStringResult {"));
+    assert!(HEADER.contains("(*ExternalSignerCallback)("));
+    assert!(HEADER.contains("(*LogCallback)("));
+    assert!(HEADER.contains("(*AllocFn)("));
+    assert!(HEADER.contains("(*FreeFn)("));
+
+    let declared = declared_functions(HEADER);
+    assert!(declared
+        .iter()
+        .any(|name| name.ends_with("solana_get_balance")));
+}
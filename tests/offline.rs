@@ -0,0 +1,120 @@
+//! The build without networking, as used by signing-only consumers:
+//! `cargo test --no-default-features --test offline`
+#![cfg(not(any(feature = "rpc", feature = "websocket")))]
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+use solana_unity::{
+    solana_account_generate, solana_account_get_pubkey, solana_buffer_free, solana_build_transfer,
+    solana_create_transaction, solana_deserialize_transaction_b64, solana_destroy_account,
+    solana_destroy_transaction, solana_find_associated_token_address, solana_free_string,
+    solana_serialize_transaction_buffer, solana_sign_transaction_with_account, Account,
+    Transaction,
+};
+
+fn take_string(ptr: *mut c_char) -> String {
+    assert!(!ptr.is_null());
+    let value = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+    solana_free_string(ptr);
+    value
+}
+
+#[test]
+fn network_functions_are_not_exported() {
+    let manifest: serde_json::Value =
+        serde_json::from_str(solana_unity::EXPORTED_SYMBOLS_JSON).unwrap();
+    let symbols = manifest["symbols"].as_array().unwrap();
+    let exported = |name: &str| {
+        symbols
+            .iter()
+            .any(|symbol| symbol.as_str().unwrap().ends_with(name))
+    };
+
+    assert!(exported("solana_sign_transaction_with_account"));
+    assert!(exported("solana_find_program_address"));
+    for name in [
+        "solana_create_rpc_client",
+        "solana_get_balance",
+        "solana_send_transaction",
+        "solana_request_get_balance",
+        "solana_pay_fetch_metadata",
+        "solana_subscription_poll_next",
+    ] {
+        assert!(!exported(name), "{} is exported", name);
+    }
+}
+
+#[test]
+fn transactions_are_signed_and_serialized_over_ffi() {
+    let mut error: *mut c_char = ptr::null_mut();
+    let account = solana_account_generate();
+    let from = CString::new(take_string(solana_account_get_pubkey(account, &mut error))).unwrap();
+    let to = CString::new(Account::generate().get_pubkey().unwrap()).unwrap();
+    let blockhash = CString::new("EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG").unwrap();
+
+    let transaction = solana_create_transaction();
+    assert_eq!(
+        solana_build_transfer(
+            transaction,
+            from.as_ptr(),
+            to.as_ptr(),
+            1000,
+            blockhash.as_ptr(),
+            &mut error,
+        ),
+        1
+    );
+    assert_eq!(
+        solana_sign_transaction_with_account(transaction, account, &mut error),
+        1
+    );
+    assert!(error.is_null());
+
+    let buffer = solana_serialize_transaction_buffer(transaction, &mut error);
+    assert!(!buffer.data.is_null());
+    let serialized = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) }.to_vec();
+    solana_buffer_free(buffer);
+
+    let restored = solana_create_transaction();
+    let encoded = CString::new(BASE64.encode(&serialized)).unwrap();
+    assert_eq!(
+        solana_deserialize_transaction_b64(restored, encoded.as_ptr(), &mut error),
+        1
+    );
+
+    let mut tx = Transaction::new();
+    tx.from_serialized(&serialized).unwrap();
+    let signed = tx.get_transaction().unwrap();
+    assert!(signed.verify().is_ok());
+    assert_eq!(
+        signed.message.account_keys[0].to_string(),
+        from.to_str().unwrap()
+    );
+
+    solana_destroy_transaction(restored);
+    solana_destroy_transaction(transaction);
+    solana_destroy_account(account);
+}
+
+#[test]
+fn addresses_are_derived_offline() {
+    let wallet = CString::new("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").unwrap();
+    let mint = CString::new("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+    let mut address: *mut c_char = ptr::null_mut();
+    let mut error: *mut c_char = ptr::null_mut();
+
+    assert_eq!(
+        solana_find_associated_token_address(
+            wallet.as_ptr(),
+            mint.as_ptr(),
+            &mut address,
+            &mut error,
+        ),
+        1
+    );
+    assert!(!take_string(address).is_empty());
+}
@@ -0,0 +1,81 @@
+//! Leak check for SolanaStringArray: freeing an array must release exactly the memory its
+//! strings and item table were allocated with.
+#![cfg(feature = "rpc")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+
+use solana_unity::{
+    solana_create_rpc_client, solana_destroy_rpc_client, solana_get_signatures_for_address_list,
+    solana_init, solana_string_array_free,
+};
+
+// Counts bytes allocated minus bytes freed, per thread, so the RPC client's own threads
+// do not disturb the measurement
+struct CountingAllocator;
+
+thread_local! {
+    static NET_BYTES: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(delta: isize) {
+    // try_with: the counter may already be gone while the thread is shutting down
+    let _ = NET_BYTES.try_with(|bytes| bytes.set(bytes.get() + delta));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        track(layout.size() as isize);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track(-(layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        track(new_size as isize - layout.size() as isize);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn net_bytes() -> isize {
+    NET_BYTES.with(Cell::get)
+}
+
+#[test]
+fn freeing_a_string_array_releases_everything() {
+    let url = CString::new("https://api.devnet.solana.com").unwrap();
+    let commitment = CString::new("confirmed").unwrap();
+    let address = CString::new("Ey9yot9JRj8RDjrTk1nxES1EA5Pig7PUMNhtC2xpxuPr").unwrap();
+    let mut error: *mut c_char = ptr::null_mut();
+    assert_eq!(unsafe { solana_init(ptr::null(), &mut error) }, 1);
+    let client = solana_create_rpc_client(url.as_ptr(), commitment.as_ptr(), &mut error);
+    assert!(!client.is_null());
+
+    let array =
+        unsafe { solana_get_signatures_for_address_list(client, address.as_ptr(), 10, &mut error) };
+    assert!(error.is_null());
+    assert!(!array.items.is_null());
+
+    // Each string owns its bytes plus the terminator, and the table one pointer per item
+    let mut expected = (array.len * std::mem::size_of::<*mut c_char>()) as isize;
+    for item in unsafe { slice::from_raw_parts(array.items, array.len) } {
+        let text = unsafe { CStr::from_ptr(*item) }.to_str().unwrap();
+        expected += text.len() as isize + 1;
+    }
+
+    let before = net_bytes();
+    solana_string_array_free(array);
+    assert_eq!(before - net_bytes(), expected);
+
+    solana_destroy_rpc_client(client);
+}
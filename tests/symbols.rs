@@ -0,0 +1,62 @@
+//! Checks the symbols exported by the built native library. Only meaningful with the
+//! prefix enabled: `SOLANA_UNITY_PREFIX=su_ cargo test --features symbol-prefix --test symbols`
+#![cfg(all(unix, feature = "symbol-prefix"))]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+// Integration tests run from target/<profile>/deps, next to which the cdylib is built
+fn native_library() -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    let profile_dir = exe.parent().unwrap().parent().unwrap();
+    profile_dir.join(format!(
+        "{}solana_unity{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ))
+}
+
+// Defined global symbols, with the leading underscore Mach-O adds stripped
+fn exported_symbols() -> Vec<String> {
+    let library = native_library();
+    assert!(library.exists(), "{} not built", library.display());
+
+    let output = Command::new("nm")
+        .arg("-g")
+        .arg("--defined-only")
+        .arg(&library)
+        .output()
+        .expect("failed to run nm");
+    assert!(output.status.success());
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().last())
+        .map(|symbol| {
+            if cfg!(target_os = "macos") {
+                symbol.trim_start_matches('_').to_string()
+            } else {
+                symbol.to_string()
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn prefixed_symbols_replace_bare_ones() {
+    let manifest: serde_json::Value =
+        serde_json::from_str(solana_unity::EXPORTED_SYMBOLS_JSON).unwrap();
+    let prefix = manifest["prefix"].as_str().unwrap();
+    assert!(!prefix.is_empty());
+
+    let symbols = exported_symbols();
+    for name in ["solana_free_string", "solana_get_balance"] {
+        let prefixed = format!("{}{}", prefix, name);
+        assert!(symbols.contains(&prefixed), "{} is not exported", prefixed);
+        assert!(
+            !symbols.iter().any(|symbol| symbol == name),
+            "{} is exported",
+            name
+        );
+    }
+}
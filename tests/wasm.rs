@@ -0,0 +1,49 @@
+//! Runs on wasm32 only:
+//! `wasm-pack test --node --features wasm -- --test wasm`
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use solana_unity::wasm::{WasmAccount, WasmTransaction};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const BLOCKHASH: &str = "11111111111111111111111111111111";
+
+#[wasm_bindgen_test]
+fn transfer_signs_and_serializes_to_base64() {
+    let payer = WasmAccount::from_seed(&[7u8; 32]).unwrap();
+    let recipient = WasmAccount::generate();
+    assert_eq!(
+        payer.pubkey().unwrap(),
+        "GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB"
+    );
+
+    let mut transaction = WasmTransaction::new();
+    transaction
+        .build_transfer(
+            &payer.pubkey().unwrap(),
+            &recipient.pubkey().unwrap(),
+            1000,
+            BLOCKHASH,
+        )
+        .unwrap();
+    transaction.sign_with_account(&payer).unwrap();
+
+    let encoded = transaction.serialize_base64().unwrap();
+    let bytes = BASE64.decode(&encoded).unwrap();
+    assert_eq!(bytes, transaction.serialize().unwrap());
+
+    // The signed transaction survives a round trip through its wire format
+    let restored = WasmTransaction::from_serialized(&bytes).unwrap();
+    assert_eq!(restored.serialize_base64().unwrap(), encoded);
+}
+
+#[wasm_bindgen_test]
+fn program_address_is_found() {
+    let pda = solana_unity::wasm::find_program_address(
+        r#"[{"kind":"utf8","value":"player"}]"#,
+        "11111111111111111111111111111111",
+    )
+    .unwrap();
+    assert!(!pda.address().is_empty());
+}
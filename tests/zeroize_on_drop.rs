@@ -0,0 +1,73 @@
+//! Key material is wiped before its memory goes back to the allocator: freed heap blocks
+//! are scanned for a known secret key while accounts holding it are dropped.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use solana_unity::Account;
+
+// The ed25519 seed of the test keypair, which is also its secret key
+const SECRET: [u8; 32] = [
+    0x5e, 0xc7, 0x1a, 0x93, 0x0b, 0xd4, 0x62, 0xf8, 0x27, 0x9c, 0x41, 0xe6, 0x3d, 0xa8, 0x70, 0x15,
+    0xbb, 0x0e, 0x84, 0x59, 0xf2, 0x36, 0xcd, 0x6a, 0x91, 0x48, 0xe3, 0x2f, 0x7b, 0xd0, 0x0c, 0xa5,
+];
+
+// Set while a drop is being watched, and once a freed block still held the secret
+static WATCHING: AtomicBool = AtomicBool::new(false);
+static FOUND: AtomicBool = AtomicBool::new(false);
+
+struct ScanningAllocator;
+
+unsafe impl GlobalAlloc for ScanningAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if WATCHING.load(Ordering::SeqCst) {
+            let freed = unsafe { slice::from_raw_parts(ptr, layout.size()) };
+            if freed.windows(SECRET.len()).any(|window| window == SECRET) {
+                FOUND.store(true, Ordering::SeqCst);
+            }
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: ScanningAllocator = ScanningAllocator;
+
+// Whether running `f` freed a block that still held the secret
+fn secret_left_behind(f: impl FnOnce()) -> bool {
+    FOUND.store(false, Ordering::SeqCst);
+    WATCHING.store(true, Ordering::SeqCst);
+    f();
+    WATCHING.store(false, Ordering::SeqCst);
+    FOUND.load(Ordering::SeqCst)
+}
+
+#[test]
+fn dropped_keys_leave_no_secret_in_freed_memory() {
+    // A plain Box is freed as is, so the scan does see secrets left behind
+    let boxed = black_box(Box::new(SECRET));
+    assert!(secret_left_behind(|| drop(boxed)));
+
+    let account = Account::from_seed(&SECRET).unwrap();
+    assert_eq!(&account.get_private_key().unwrap()[..32], &SECRET);
+    assert!(!secret_left_behind(|| drop(account)));
+
+    // Moving the key into locked memory and back wipes the storage it leaves
+    let mut account = Account::from_seed(&SECRET).unwrap();
+    assert!(!secret_left_behind(|| {
+        account.set_secure_memory(true).unwrap();
+    }));
+    assert!(!secret_left_behind(|| {
+        account.set_secure_memory(false).unwrap();
+    }));
+
+    let mut account = Account::from_seed(&SECRET).unwrap();
+    assert!(!secret_left_behind(|| account.clear_private_key()));
+    assert!(!account.has_private_key());
+}